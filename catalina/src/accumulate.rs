@@ -0,0 +1,247 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Temporal accumulation of jittered frames, for very high quality antialiasing of static scenes.
+
+use std::borrow::Cow;
+
+use peniko::kurbo::Vec2;
+use wgpu::{Buffer, Device, Queue, Texture, TextureView};
+
+use crate::low_level::{BindType, ImageFormat, ShaderId};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+use crate::{RenderedTile, Result};
+
+/// The size of each side of the workgroups of the accumulation shader.
+pub(crate) const WORKGROUP_SIZE: u32 = 16;
+
+const SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        frames: u32,
+        _padding: u32,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var frame: texture_2d<f32>;
+
+    // The sum of all frames so far, premultiplied so that transparent samples don't bleed color.
+    @group(0) @binding(2)
+    var<storage, read_write> sum: array<vec4<f32>>;
+
+    @group(0) @binding(3)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let ix = id.y * params.width + id.x;
+        let color = textureLoad(frame, vec2<i32>(id.xy), 0);
+        let total = sum[ix] + vec4(color.rgb * color.a, color.a);
+        sum[ix] = total;
+        let average = total / f32(params.frames);
+        var rgb = vec3(0.0);
+        if average.a > 0.0 {
+            rgb = average.rgb / average.a;
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(rgb, average.a));
+    }
+"#;
+
+/// Adds the accumulation shader to `engine`.
+pub(crate) fn add_shader(device: &Device, engine: &mut WgpuEngine) -> ShaderId {
+    engine.add_compute_shader(
+        device,
+        "catalina.accumulate",
+        Cow::Borrowed(SHADER),
+        &[
+            BindType::Uniform,
+            BindType::ImageRead(ImageFormat::Rgba8),
+            BindType::Buffer,
+            BindType::Image(ImageFormat::Rgba8),
+        ],
+        CpuShaderType::Missing,
+    )
+}
+
+/// The running average of many renders of a static scene, each offset by a different
+/// sub-pixel jitter.
+///
+/// Every call to [`Renderer::accumulate`](crate::Renderer::accumulate) adds one frame, and the
+/// average converges towards a very high quality antialiased image, without the memory and time
+/// cost of a single huge supersampled render. This suits previews which refine while the scene
+/// is idle, and exports.
+///
+/// The accumulation must be [`reset`](Self::reset) whenever the scene or view changes.
+#[derive(Debug)]
+pub struct Accumulator {
+    width: u32,
+    height: u32,
+    frames: u32,
+    /// The sum of the premultiplied colors of all frames, as four `f32`s per pixel.
+    pub(crate) sum: Buffer,
+    /// The target each jittered frame is rendered into.
+    pub(crate) frame_view: TextureView,
+    output: Texture,
+    pub(crate) output_view: TextureView,
+}
+
+impl Accumulator {
+    /// Creates an empty accumulation of the given size.
+    pub fn new(device: &Device, width: u32, height: u32) -> Self {
+        let texture = |label, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                usage: wgpu::TextureUsages::STORAGE_BINDING | usage,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                view_formats: &[],
+            })
+        };
+        let frame = texture(
+            "catalina.accumulate_frame",
+            wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let output = texture(
+            "catalina.accumulate_output",
+            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        );
+        let sum = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("catalina.accumulate_sum"),
+            size: (u64::from(width) * u64::from(height) * 16).max(16),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            width,
+            height,
+            frames: 0,
+            sum,
+            frame_view: frame.create_view(&wgpu::TextureViewDescriptor::default()),
+            output_view: output.create_view(&wgpu::TextureViewDescriptor::default()),
+            output,
+        }
+    }
+
+    /// The width of the accumulation, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the accumulation, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The number of frames accumulated since the last reset.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Discards all accumulated frames, such as when the scene or view has changed.
+    pub fn reset(&mut self, device: &Device, queue: &Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("catalina.accumulate_reset"),
+        });
+        encoder.clear_buffer(&self.sum, 0, None);
+        queue.submit([encoder.finish()]);
+        self.frames = 0;
+    }
+
+    /// The sub-pixel offset the next frame will be rendered at, in `-0.5..0.5` on each axis.
+    ///
+    /// The offsets follow a Halton sequence, which covers the pixel evenly for any number of
+    /// frames. The first frame is not offset, so that a single frame matches a normal render.
+    pub fn jitter(&self) -> Vec2 {
+        if self.frames == 0 {
+            return Vec2::ZERO;
+        }
+        Vec2::new(halton(self.frames, 2) - 0.5, halton(self.frames, 3) - 0.5)
+    }
+
+    /// Records that a frame has been added, returning the new number of frames.
+    pub(crate) fn add_frame(&mut self) -> u32 {
+        self.frames += 1;
+        self.frames
+    }
+
+    /// The texture holding the average of the accumulated frames, in the
+    /// [`Rgba8Unorm`](wgpu::TextureFormat::Rgba8Unorm) format.
+    pub fn texture(&self) -> &Texture {
+        &self.output
+    }
+
+    /// Reads back the average of the accumulated frames as unpadded RGBA8 data, with rows from
+    /// top to bottom.
+    ///
+    /// This blocks on the GPU, so is not supported on WebAssembly, where
+    /// [`read_pixels_async`](Self::read_pixels_async) should be used instead.
+    pub fn read_pixels(&self, device: &Device, queue: &Queue) -> Result<Vec<u8>> {
+        self.tile().read_pixels(device, queue)
+    }
+
+    /// Reads back the pixels like [`read_pixels`](Self::read_pixels), without blocking.
+    pub async fn read_pixels_async(&self, device: &Device, queue: &Queue) -> Result<Vec<u8>> {
+        self.tile().read_pixels_async(device, queue).await
+    }
+
+    /// The whole output, as a tile.
+    fn tile(&self) -> RenderedTile<'_> {
+        RenderedTile {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+            texture: &self.output,
+        }
+    }
+}
+
+/// The `index`th element of the Halton sequence with the given base, in `0..1`.
+fn halton(mut index: u32, base: u32) -> f64 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= f64::from(base);
+        result += fraction * f64::from(index % base);
+        index /= base;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::halton;
+
+    #[test]
+    fn jitter_covers_the_pixel_evenly() {
+        let xs: Vec<f64> = (1..8).map(|i| halton(i, 2)).collect();
+        assert_eq!(xs, [0.5, 0.25, 0.75, 0.125, 0.625, 0.375, 0.875]);
+        let ys: Vec<f64> = (1..4).map(|i| halton(i, 3)).collect();
+        assert!((ys[0] - 1.0 / 3.0).abs() < 1e-12);
+        assert!((ys[1] - 2.0 / 3.0).abs() < 1e-12);
+        assert!((ys[2] - 1.0 / 9.0).abs() < 1e-12);
+        // Every quarter of the pixel gets a quarter of the first 64 samples.
+        let mut quadrants = [0; 4];
+        for i in 1..=64 {
+            let (x, y) = (halton(i, 2), halton(i, 3));
+            quadrants[usize::from(x >= 0.5) + 2 * usize::from(y >= 0.5)] += 1;
+        }
+        for count in quadrants {
+            assert!((14..=18).contains(&count), "{quadrants:?}");
+        }
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Offscreen layers which are rendered once and composited with arbitrary transforms.
+
+use peniko::kurbo::{Affine, Rect};
+use peniko::{color::palette, Image};
+use wgpu::{Device, Queue, Texture};
+
+use crate::scene::{max_scale, renderer_image};
+use crate::{AaConfig, RenderParams, Renderer, Result, Scene};
+
+/// The default for [`CachedLayer::with_max_scale_change`].
+const DEFAULT_MAX_SCALE_CHANGE: f64 = 2.0;
+
+/// The largest width or height of the texture a [`CachedLayer`] is rendered into.
+///
+/// This keeps the render within [`MAX_TARGET_BINS`](crate::MAX_TARGET_BINS).
+const MAX_RASTER_SIZE: u32 = 4096;
+
+/// A subtree of a scene which is rendered into a texture once, then drawn with any transform.
+///
+/// Zooming, panning or rotating a complex group, such as an artboard being dragged or a
+/// document during a pinch gesture, then only draws an image rather than rasterizing all of
+/// its paths again. The texture is rendered at the scale of the transform it is first drawn
+/// with, and is rendered again once the scale has changed by more than
+/// [`with_max_scale_change`](Self::with_max_scale_change), so that it doesn't become blurry
+/// when magnified or aliased when minified.
+///
+/// Call [`invalidate`](Self::invalidate) when the content changes.
+#[derive(Debug)]
+pub struct CachedLayer {
+    bounds: Rect,
+    max_scale_change: f64,
+    raster: Option<Raster>,
+    /// Whether the content has changed since it was rendered.
+    invalidated: bool,
+}
+
+/// The rendered contents of a [`CachedLayer`].
+#[derive(Debug)]
+struct Raster {
+    texture: Texture,
+    /// A placeholder image whose contents the renderer replaces with the texture.
+    image: Image,
+    /// The scale of the transform the content was rendered for.
+    scale: f64,
+    /// Maps the content into the pixels of the texture.
+    transform: Affine,
+}
+
+impl CachedLayer {
+    /// Creates a layer for content within `bounds`, in the content's coordinates.
+    ///
+    /// Content outside of the bounds is clipped.
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            bounds: bounds.abs(),
+            max_scale_change: DEFAULT_MAX_SCALE_CHANGE,
+            raster: None,
+            invalidated: false,
+        }
+    }
+
+    /// Sets how far the scale of the transform may change from the one the layer was rendered
+    /// for before it is rendered again, as a factor in either direction. The default is 2.
+    ///
+    /// Smaller factors keep the layer sharper, at the cost of rendering it more often while
+    /// zooming. Factors below 1 are treated as 1.
+    pub fn with_max_scale_change(mut self, factor: f64) -> Self {
+        self.max_scale_change = factor.max(1.0);
+        self
+    }
+
+    /// The bounds of the content, in the content's coordinates.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// The texture holding the most recently rendered content, if it has been rendered.
+    pub fn texture(&self) -> Option<&Texture> {
+        self.raster.as_ref().map(|raster| &raster.texture)
+    }
+
+    /// Marks the content as changed, so that it is rendered again the next time the layer is
+    /// drawn.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    /// Returns `true` if drawing the layer with `transform` would render its content again.
+    pub fn needs_raster(&self, transform: Affine) -> bool {
+        let Some(raster) = self.raster.as_ref().filter(|_| !self.invalidated) else {
+            return true;
+        };
+        let change = max_scale(transform) / raster.scale;
+        !(change <= self.max_scale_change && change * self.max_scale_change >= 1.0)
+    }
+
+    /// Draws the layer into `scene` with `transform`, which maps the content's coordinates
+    /// into those of the scene.
+    ///
+    /// The content is first rendered with `renderer` if it hasn't been yet, or if the scale of
+    /// `transform` has changed too much since it was, and `true` is returned when that happens.
+    /// The scene must then be rendered with the same renderer.
+    pub fn draw(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+        content: &Scene,
+        scene: &mut Scene,
+        transform: Affine,
+    ) -> Result<bool> {
+        let scale = max_scale(transform);
+        if !scale.is_normal() || self.bounds.is_zero_area() {
+            return Ok(false);
+        }
+        let rendered = self.needs_raster(transform);
+        if rendered {
+            self.render(renderer, device, queue, content, scale)?;
+        }
+        let raster = self.raster.as_ref().expect("the layer was rendered above");
+        renderer.override_image(
+            &raster.image,
+            Some(wgpu::TexelCopyTextureInfoBase {
+                texture: raster.texture.clone(),
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            }),
+        );
+        scene.draw_image(&raster.image, transform * raster.transform.inverse());
+        Ok(rendered)
+    }
+
+    /// Renders `content` into a texture for drawing with transforms of the given scale.
+    fn render(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+        content: &Scene,
+        scale: f64,
+    ) -> Result<()> {
+        let max_size = MAX_RASTER_SIZE.min(device.limits().max_texture_dimension_2d);
+        let largest = self.bounds.width().max(self.bounds.height());
+        let raster_scale = scale.min(f64::from(max_size) / largest);
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "The size is clamped to `1..=max_size`."
+        )]
+        let size = |extent: f64| (extent * raster_scale).ceil().clamp(1.0, max_size.into()) as u32;
+        let (width, height) = (size(self.bounds.width()), size(self.bounds.height()));
+        let transform =
+            Affine::scale(raster_scale) * Affine::translate(-self.bounds.origin().to_vec2());
+
+        // Reuse the previous texture if it has the right size.
+        let texture = match self.raster.take() {
+            Some(raster)
+                if raster.texture.width() == width && raster.texture.height() == height =>
+            {
+                renderer.override_image(&raster.image, None);
+                raster.texture
+            }
+            previous => {
+                if let Some(raster) = previous {
+                    renderer.override_image(&raster.image, None);
+                }
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("catalina.cached_layer"),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    usage: wgpu::TextureUsages::STORAGE_BINDING
+                        | wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_SRC,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    view_formats: &[],
+                })
+            }
+        };
+        let mut scene = Scene::new();
+        scene.flatten_shader = content.flatten_shader.clone();
+        scene.append(content, Some(transform));
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        renderer.render_to_texture(
+            device,
+            queue,
+            &scene,
+            &view,
+            &RenderParams {
+                base_color: palette::css::TRANSPARENT,
+                width,
+                height,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+        )?;
+        // The image has no pixels of its own, as the renderer always reads the texture instead.
+        self.raster = Some(Raster {
+            texture,
+            image: renderer_image(Vec::new(), width, height),
+            scale,
+            transform,
+        });
+        self.invalidated = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Conversion of CMYK and spot colors, as used in print documents, to the colors of brushes.
+
+use std::sync::Arc;
+
+use peniko::color::{AlphaColor, LinearSrgb, Srgb};
+use peniko::Color;
+
+/// A color in terms of the amounts of cyan, magenta, yellow and black process ink, each from 0
+/// to 1.
+///
+/// These are device dependent, so are turned into a [`Color`] for drawing by a
+/// [`CmykConverter`] describing the inks and paper.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cmyk {
+    /// The amount of cyan ink.
+    pub c: f32,
+    /// The amount of magenta ink.
+    pub m: f32,
+    /// The amount of yellow ink.
+    pub y: f32,
+    /// The amount of black ink.
+    pub k: f32,
+    /// The opacity of the color.
+    pub alpha: f32,
+}
+
+impl Cmyk {
+    /// Creates an opaque color from amounts of ink.
+    pub const fn new(c: f32, m: f32, y: f32, k: f32) -> Self {
+        Self {
+            c,
+            m,
+            y,
+            k,
+            alpha: 1.0,
+        }
+    }
+
+    /// Returns the color with the given opacity.
+    #[must_use]
+    pub const fn with_alpha(self, alpha: f32) -> Self {
+        Self { alpha, ..self }
+    }
+
+    fn inks(self) -> [f32; 4] {
+        [self.c, self.m, self.y, self.k].map(|ink| ink.clamp(0.0, 1.0))
+    }
+}
+
+/// How a [`CmykConverter`] maps amounts of ink to colors.
+#[derive(Clone, Debug, PartialEq)]
+enum Model {
+    Naive,
+    /// The paper and the solid colors of each ink on it, in linear sRGB.
+    Inks {
+        paper: [f32; 3],
+        inks: [[f32; 3]; 4],
+    },
+    /// sRGB colors sampled on a regular grid with `size` points along each axis.
+    Lut {
+        size: usize,
+        samples: Arc<[[f32; 3]]>,
+    },
+}
+
+/// Converts [`Cmyk`] and spot colors into sRGB [`Color`]s for brushes.
+///
+/// Print documents specify colors as amounts of ink, whose appearance depends on the inks and
+/// paper used. Rather than assuming a conversion when the document is loaded, a renderer can
+/// describe them with a converter, from the least to the most accurate:
+///
+/// - [`naive`](Self::naive), the usual formula which treats the inks as ideal filters.
+/// - [`from_inks`](Self::from_inks), from the measured colors of the paper and of each ink
+///   printed solid on it, which the inks filter in linear light.
+/// - [`from_lut`](Self::from_lut), from a table of colors sampled from an ICC profile.
+///
+/// Spot inks are added by name with [`with_spot`](Self::with_spot).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CmykConverter {
+    model: Model,
+    /// The colors of spot inks printed solid, by name.
+    spots: Vec<(String, Color)>,
+}
+
+impl Default for CmykConverter {
+    fn default() -> Self {
+        Self::naive()
+    }
+}
+
+impl CmykConverter {
+    /// Creates a converter which treats the inks as ideal filters on white paper, so that
+    /// each of red, green and blue is `(1 - ink) * (1 - k)` for the complementary ink.
+    pub fn naive() -> Self {
+        Self {
+            model: Model::Naive,
+            spots: Vec::new(),
+        }
+    }
+
+    /// Creates a converter from the color of the paper and the colors of cyan, magenta,
+    /// yellow and black ink each printed solid on it.
+    ///
+    /// Inks filter the light reflected by the paper, so overlapping inks multiply in linear
+    /// light and partial amounts blend towards the paper.
+    pub fn from_inks(paper: Color, inks: [Color; 4]) -> Self {
+        let linear = |color: Color| {
+            let [r, g, b, _] = color.convert::<LinearSrgb>().components;
+            [r, g, b]
+        };
+        Self {
+            model: Model::Inks {
+                paper: linear(paper),
+                inks: inks.map(linear),
+            },
+            spots: Vec::new(),
+        }
+    }
+
+    /// Creates a converter from sRGB colors sampled on a regular grid of `size` points along
+    /// each of the cyan, magenta, yellow and black axes, such as from the `AToB` table of an
+    /// ICC output profile.
+    ///
+    /// The samples are ordered with black varying fastest, then yellow, magenta and cyan, so
+    /// the sample for grid point `(c, m, y, k)` is at `((c * size + m) * size + y) * size + k`.
+    /// Colors between the grid points are interpolated. Returns `None` if `size` is less than
+    /// 2 or there aren't `size⁴` samples.
+    pub fn from_lut(size: usize, samples: impl Into<Arc<[[f32; 3]]>>) -> Option<Self> {
+        let samples = samples.into();
+        if size < 2 || Some(samples.len()) != size.checked_pow(4) {
+            return None;
+        }
+        Some(Self {
+            model: Model::Lut { size, samples },
+            spots: Vec::new(),
+        })
+    }
+
+    /// Adds a spot ink called `name`, whose color when printed solid is `color`.
+    ///
+    /// An ink with the same name replaces it.
+    #[must_use]
+    pub fn with_spot(mut self, name: impl Into<String>, color: Color) -> Self {
+        let name = name.into();
+        self.spots.retain(|(spot, _)| *spot != name);
+        self.spots.push((name, color));
+        self
+    }
+
+    /// Converts a CMYK color to sRGB.
+    pub fn convert(&self, cmyk: Cmyk) -> Color {
+        let inks = cmyk.inks();
+        let [r, g, b] = match &self.model {
+            Model::Naive => {
+                let [c, m, y, k] = inks;
+                [1.0 - c, 1.0 - m, 1.0 - y].map(|channel| channel * (1.0 - k))
+            }
+            Model::Inks {
+                paper,
+                inks: solids,
+            } => {
+                let mut rgb = *paper;
+                for (amount, solid) in inks.iter().zip(solids) {
+                    for ((channel, base), ink) in rgb.iter_mut().zip(paper).zip(solid) {
+                        // The fraction of the light reflected by the paper which the ink passes.
+                        let filter = ink / base.max(f32::EPSILON);
+                        *channel *= 1.0 + amount * (filter - 1.0);
+                    }
+                }
+                let [r, g, b] = rgb;
+                let [r, g, b, _] = AlphaColor::<LinearSrgb>::new([r, g, b, 1.0])
+                    .convert::<Srgb>()
+                    .components;
+                [r, g, b]
+            }
+            Model::Lut { size, samples } => interpolate(*size, samples, inks),
+        };
+        Color::new([r, g, b, cmyk.alpha].map(|channel| channel.clamp(0.0, 1.0)))
+    }
+
+    /// Converts a tint of the spot ink called `name`, from 0 for none to 1 for solid ink, to
+    /// sRGB, or returns `None` if there is no such ink.
+    ///
+    /// Tints blend in linear light between the paper and the solid ink.
+    pub fn spot(&self, name: &str, tint: f32) -> Option<Color> {
+        let (_, solid) = self.spots.iter().find(|(spot, _)| spot == name)?;
+        let paper = self.convert(Cmyk::new(0.0, 0.0, 0.0, 0.0));
+        let tint = tint.clamp(0.0, 1.0);
+        let [r, g, b, _] = paper.convert::<LinearSrgb>().components;
+        let [sr, sg, sb, alpha] = solid.convert::<LinearSrgb>().components;
+        let mix = |from: f32, to: f32| from + tint * (to - from);
+        let color = AlphaColor::<LinearSrgb>::new([mix(r, sr), mix(g, sg), mix(b, sb), alpha]);
+        Some(color.convert())
+    }
+}
+
+/// Interpolates the samples of a four dimensional grid between the 16 points around `inks`.
+fn interpolate(size: usize, samples: &[[f32; 3]], inks: [f32; 4]) -> [f32; 3] {
+    let last = (size - 1) as f32;
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The position is clamped to the grid, which has fewer than `usize::MAX` points."
+    )]
+    let cells = inks.map(|ink| {
+        let position = ink * last;
+        let base = (position.floor() as usize).min(size - 2);
+        (base, position - base as f32)
+    });
+    let mut rgb = [0.0; 3];
+    for corner in 0..16_usize {
+        let mut index = 0;
+        let mut weight = 1.0;
+        for (axis, (base, fraction)) in cells.iter().enumerate() {
+            let upper = (corner >> (3 - axis)) & 1 == 1;
+            index = index * size + base + usize::from(upper);
+            weight *= if upper { *fraction } else { 1.0 - fraction };
+        }
+        for (channel, sample) in rgb.iter_mut().zip(samples[index]) {
+            *channel += weight * sample;
+        }
+    }
+    rgb
+}
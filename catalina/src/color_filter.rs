@@ -0,0 +1,89 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Filters applied to the colors of the whole output, for accessibility.
+
+/// A filter applied to the colors of every frame when it's blitted to a surface, set with
+/// [`Renderer::set_color_filter`](crate::Renderer::set_color_filter).
+///
+/// The simulations of color vision deficiencies let designers check that their content can be
+/// told apart by everyone, and the other filters can be offered to users as accessibility
+/// modes. They're applied after rendering, so cost the same for any scene, and can be changed
+/// between any two frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorFilter {
+    /// Simulates protanopia, the absence of the long wavelength (red) cones.
+    Protanopia,
+    /// Simulates deuteranopia, the absence of the medium wavelength (green) cones.
+    Deuteranopia,
+    /// Simulates tritanopia, the absence of the short wavelength (blue) cones.
+    Tritanopia,
+    /// Shows colors as shades of gray of the same luminance.
+    Grayscale,
+    /// Scales the contrast of colors around middle gray by this factor, where values above 1
+    /// increase it.
+    Contrast(f32),
+    /// Multiplies colors in linear sRGB by this matrix, given as rows.
+    Matrix([[f32; 3]; 3]),
+}
+
+impl ColorFilter {
+    /// Returns the matrix applied to colors in linear sRGB, as rows, and the contrast factor
+    /// applied after it.
+    fn transform(self) -> ([[f32; 3]; 3], f32) {
+        // The simulations use the matrices for full severity from "A Physiologically-based
+        // Model for Simulation of Color Vision Deficiency" by Machado, Oliveira and Fernandes.
+        let matrix = match self {
+            Self::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            Self::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            Self::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+            Self::Grayscale => [[0.2126, 0.7152, 0.0722]; 3],
+            Self::Contrast(contrast) => return (IDENTITY, contrast.max(0.0)),
+            Self::Matrix(matrix) => matrix,
+        };
+        (matrix, 1.0)
+    }
+}
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Returns the uniforms of the blit shader, for the output opacity and an optional filter.
+///
+/// These are the opacity, the contrast factor, whether a filter is applied (as 0 or 1) and
+/// padding, followed by the rows of the matrix, each padded to four components.
+pub(crate) fn blit_params(alpha: f32, filter: Option<ColorFilter>) -> [f32; 16] {
+    let (matrix, contrast) = filter.map_or((IDENTITY, 1.0), ColorFilter::transform);
+    let enabled = if filter.is_some() { 1.0 } else { 0.0 };
+    let mut params = [0.0; 16];
+    params[..4].copy_from_slice(&[alpha.clamp(0.0, 1.0), contrast, enabled, 0.0]);
+    for (row, params) in matrix.iter().zip(params[4..].chunks_exact_mut(4)) {
+        params[..3].copy_from_slice(row);
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blit_params, ColorFilter};
+
+    #[test]
+    fn output_opacity_is_clamped() {
+        assert_eq!(blit_params(0.25, None)[0], 0.25);
+        assert_eq!(blit_params(-1.0, None)[0], 0.0);
+        assert_eq!(blit_params(2.0, None)[0], 1.0);
+        // Filters don't change the opacity.
+        assert_eq!(blit_params(0.5, Some(ColorFilter::Grayscale))[0], 0.5);
+    }
+}
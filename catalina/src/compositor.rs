@@ -0,0 +1,112 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! User provided passes over the rendered image, run before it's blitted to a surface.
+
+use std::borrow::Cow;
+
+use wgpu::Device;
+
+use crate::low_level::{BindType, ImageFormat, ImageProxy, Recording, ResourceProxy, ShaderId};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+
+/// The size of each side of the workgroups of the compositor shader.
+const WORKGROUP_SIZE: u32 = 16;
+
+/// The number of `f32` uniforms available to a compositor.
+pub(crate) const UNIFORM_COUNT: usize = 16;
+
+/// The declarations available to the source of a compositor.
+const PRELUDE: &str = r#"
+    struct CompositorParams {
+        // The size of the rendered image, in pixels.
+        size: vec2<u32>,
+        _padding: vec2<u32>,
+        // The uniforms set with `Renderer::set_compositor_uniforms`.
+        data: array<vec4<f32>, 4>,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> compositor_params: CompositorParams;
+
+    @group(0) @binding(1)
+    var compositor_input: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var compositor_output: texture_storage_2d<rgba8unorm, write>;
+"#;
+
+/// A compositor set with [`Renderer::set_compositor`](crate::Renderer::set_compositor).
+#[derive(Debug)]
+pub(crate) struct Compositor {
+    /// The full source of the compute shader.
+    wgsl: String,
+    /// The compiled shader, created on first use.
+    pub(crate) shader: Option<ShaderId>,
+}
+
+impl Compositor {
+    pub(crate) fn new(entry_point: &str, source: &str) -> Self {
+        let wgsl = format!(
+            "{PRELUDE}\n{source}\n
+            @compute @workgroup_size({WORKGROUP_SIZE}, {WORKGROUP_SIZE})
+            fn compositor_main(@builtin(global_invocation_id) id: vec3<u32>) {{
+                if id.x >= compositor_params.size.x || id.y >= compositor_params.size.y {{
+                    return;
+                }}
+                let xy = vec2<i32>(id.xy);
+                textureStore(compositor_output, xy, {entry_point}(xy));
+            }}
+            "
+        );
+        Self { wgsl, shader: None }
+    }
+
+    /// Records the pass, reading `input` and writing `output`, which have the same size.
+    pub(crate) fn record(
+        &mut self,
+        device: &Device,
+        engine: &mut WgpuEngine,
+        recording: &mut Recording,
+        uniforms: &[f32; UNIFORM_COUNT],
+        input: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let shader = *self.shader.get_or_insert_with(|| {
+            engine.add_compute_shader(
+                device,
+                "catalina.compositor",
+                Cow::Owned(self.wgsl.clone()),
+                &[
+                    BindType::Uniform,
+                    BindType::ImageRead(ImageFormat::Rgba8),
+                    BindType::Image(ImageFormat::Rgba8),
+                ],
+                CpuShaderType::Missing,
+            )
+        });
+        let mut params = [0_u32; 4 + UNIFORM_COUNT];
+        params[0] = input.width;
+        params[1] = input.height;
+        for (param, uniform) in params[4..].iter_mut().zip(uniforms) {
+            *param = uniform.to_bits();
+        }
+        let params = ResourceProxy::Buffer(
+            recording.upload_uniform("catalina.compositor_params", bytemuck::bytes_of(&params)),
+        );
+        recording.dispatch(
+            shader,
+            (
+                input.width.div_ceil(WORKGROUP_SIZE),
+                input.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            ),
+            [
+                params,
+                ResourceProxy::Image(input),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(params);
+    }
+}
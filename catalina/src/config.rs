@@ -0,0 +1,261 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Runtime configuration of devices and renderers, including from environment variables.
+
+use std::env::VarError;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use wgpu::{Backends, PowerPreference, TextureFormat};
+
+use crate::low_level::DebugLayers;
+use crate::util::{DeviceHandle, DeviceOptions, RenderContext};
+use crate::{
+    AaConfig, AaSupport, DiskCache, Error, PipelineStage, RendererOptions, Result, SmallSceneLimits,
+};
+
+/// The runtime tunables of devices and renderers in one place, so that deployments can be
+/// configured without code changes.
+///
+/// A configuration can be built in code, read from the environment with
+/// [`from_env`](Self::from_env), or both, with the environment overriding the values set in
+/// code using [`with_env`](Self::with_env). It then creates the [`RenderContext`] and the
+/// [`RendererOptions`] for each device with [`render_context`](Self::render_context) and
+/// [`renderer_options`](Self::renderer_options).
+///
+/// The environment variables are:
+///
+/// | Variable                    | Field                                        | Example                   |
+/// |-----------------------------|----------------------------------------------|---------------------------|
+/// | `WGPU_BACKEND`              | [`backends`](Self::backends)                 | `vulkan,metal`            |
+/// | `WGPU_POWER_PREF`           | [`power_preference`](Self::power_preference) | `high`                    |
+/// | `CATALINA_ADAPTER_INDEX`    | [`adapter_index`](Self::adapter_index)       | `1`                       |
+/// | `CATALINA_MAX_BUFFER_SIZE`  | [`max_buffer_size`](Self::max_buffer_size)   | `1073741824`              |
+/// | `CATALINA_ANTIALIASING`     | [`antialiasing`](Self::antialiasing)         | `area,msaa16` or `all`    |
+/// | `CATALINA_USE_CPU`          | [`use_cpu`](Self::use_cpu)                   | `1` or `false`            |
+/// | `CATALINA_GPU_STAGES_FROM`  | [`gpu_stages_from`](Self::gpu_stages_from)   | `coarse`                  |
+/// | `CATALINA_INIT_THREADS`     | [`num_init_threads`](Self::num_init_threads) | `4`                       |
+/// | `CATALINA_FINE_BAND_ROWS`   | [`fine_band_rows`](Self::fine_band_rows)     | `64`                      |
+/// | `CATALINA_FRAMES_IN_FLIGHT` | [`frames_in_flight`](Self::frames_in_flight) | `3`                       |
+/// | `CATALINA_CACHE_DIR`        | [`cache_dir`](Self::cache_dir)               | `/var/cache/catalina`     |
+/// | `CATALINA_SMALL_SCENES`     | [`small_scenes`](Self::small_scenes)         | `1` or `false`            |
+/// | `CATALINA_DEBUG_LAYERS`     | [`debug_layers`](Self::debug_layers)         | `bounding_boxes` or `all` |
+///
+/// Pipeline stages and debug layers are named in snake case, such as `path_tiling` and
+/// `linesoup_segments`. The `WGPU_ADAPTER_NAME` variable is also honored when a
+/// [`RenderContext`] chooses an adapter, as described in [`DeviceOptions`].
+#[derive(Clone, Debug, Default)]
+pub struct CatalinaConfig {
+    /// The backends to create adapters with, or `None` for the default backends of wgpu.
+    pub backends: Option<Backends>,
+    /// See [`DeviceOptions::power_preference`].
+    pub power_preference: Option<PowerPreference>,
+    /// See [`DeviceOptions::adapter_index`].
+    pub adapter_index: Option<usize>,
+    /// The size of the largest buffer the device may create, in bytes, which limits the size
+    /// of the scenes it can render. `None` keeps the default limits of wgpu.
+    pub max_buffer_size: Option<u64>,
+    /// See [`RendererOptions::antialiasing_support`], where `None` supports every method.
+    pub antialiasing: Option<AaSupport>,
+    /// See [`RendererOptions::use_cpu`].
+    pub use_cpu: bool,
+    /// See [`RendererOptions::gpu_stages_from`].
+    pub gpu_stages_from: Option<PipelineStage>,
+    /// See [`RendererOptions::num_init_threads`].
+    pub num_init_threads: Option<NonZeroUsize>,
+    /// See [`RendererOptions::fine_band_rows`].
+    pub fine_band_rows: Option<NonZeroU32>,
+    /// See [`RendererOptions::frames_in_flight`].
+    pub frames_in_flight: Option<NonZeroUsize>,
+    /// The directory of the [`DiskCache`] of each renderer, or `None` for no cache.
+    pub cache_dir: Option<PathBuf>,
+    /// See [`RendererOptions::small_scenes`]. The environment variable enables the default
+    /// limits.
+    pub small_scenes: Option<SmallSceneLimits>,
+    /// The debug layers to pass when rendering to surfaces, which additionally require the
+    /// `debug_layers` feature.
+    pub debug_layers: DebugLayers,
+}
+
+impl CatalinaConfig {
+    /// Reads the configuration from the environment variables listed above, using the
+    /// defaults for those which aren't set.
+    ///
+    /// Returns [`Error::InvalidConfig`] if a variable is set to a value which can't be parsed.
+    pub fn from_env() -> Result<Self> {
+        Self::default().with_env()
+    }
+
+    /// Overrides the configuration with the environment variables which are set.
+    pub fn with_env(mut self) -> Result<Self> {
+        if let Some(backends) = Backends::from_env() {
+            self.backends = Some(backends);
+        }
+        if let Some(power_preference) = PowerPreference::from_env() {
+            self.power_preference = Some(power_preference);
+        }
+        if let Some(index) = var("CATALINA_ADAPTER_INDEX", parse)? {
+            self.adapter_index = Some(index);
+        }
+        if let Some(size) = var("CATALINA_MAX_BUFFER_SIZE", parse)? {
+            self.max_buffer_size = Some(size);
+        }
+        if let Some(antialiasing) = var("CATALINA_ANTIALIASING", parse_antialiasing)? {
+            self.antialiasing = Some(antialiasing);
+        }
+        if let Some(use_cpu) = var("CATALINA_USE_CPU", parse_bool)? {
+            self.use_cpu = use_cpu;
+        }
+        if let Some(stage) = var("CATALINA_GPU_STAGES_FROM", parse_stage)? {
+            self.gpu_stages_from = Some(stage);
+        }
+        if let Some(threads) = var("CATALINA_INIT_THREADS", parse)? {
+            self.num_init_threads = Some(threads);
+        }
+        if let Some(rows) = var("CATALINA_FINE_BAND_ROWS", parse)? {
+            self.fine_band_rows = Some(rows);
+        }
+        if let Some(frames) = var("CATALINA_FRAMES_IN_FLIGHT", parse)? {
+            self.frames_in_flight = Some(frames);
+        }
+        if let Some(dir) = var("CATALINA_CACHE_DIR", |value| Some(PathBuf::from(value)))? {
+            self.cache_dir = Some(dir);
+        }
+        if let Some(small_scenes) = var("CATALINA_SMALL_SCENES", parse_bool)? {
+            self.small_scenes = small_scenes.then(SmallSceneLimits::default);
+        }
+        if let Some(layers) = var("CATALINA_DEBUG_LAYERS", parse_debug_layers)? {
+            self.debug_layers = layers;
+        }
+        Ok(self)
+    }
+
+    /// Returns the options for the devices created by a [`RenderContext`].
+    pub fn device_options(&self) -> DeviceOptions {
+        let required_limits = self.max_buffer_size.map(|size| wgpu::Limits {
+            max_buffer_size: size,
+            max_storage_buffer_binding_size: u32::try_from(size).unwrap_or(u32::MAX),
+            ..Default::default()
+        });
+        DeviceOptions {
+            power_preference: self.power_preference.unwrap_or_default(),
+            adapter_index: self.adapter_index,
+            required_limits,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a [`RenderContext`] with the configured backends and device options.
+    pub fn render_context(&self) -> RenderContext {
+        RenderContext::from_backends(self.backends.unwrap_or_default())
+            .with_device_options(self.device_options())
+    }
+
+    /// Returns the options for a renderer on the device of `handle`, which presents to
+    /// surfaces of `surface_format` if set.
+    pub fn renderer_options(
+        &self,
+        handle: &DeviceHandle,
+        surface_format: Option<TextureFormat>,
+    ) -> RendererOptions {
+        RendererOptions {
+            surface_format,
+            use_cpu: self.use_cpu,
+            gpu_stages_from: self.gpu_stages_from,
+            antialiasing_support: self.antialiasing.unwrap_or_else(AaSupport::all),
+            num_init_threads: self.num_init_threads,
+            fine_band_rows: self.fine_band_rows,
+            frames_in_flight: self.frames_in_flight,
+            disk_cache: self
+                .cache_dir
+                .as_ref()
+                .map(|dir| DiskCache::new(dir, &handle.adapter().get_info())),
+            small_scenes: self.small_scenes,
+        }
+    }
+}
+
+/// Reads and parses the environment variable `name`, returning `None` if it isn't set.
+fn var<T>(name: &'static str, parse: impl FnOnce(&str) -> Option<T>) -> Result<Option<T>> {
+    let value = match std::env::var(name) {
+        Ok(value) => value,
+        Err(VarError::NotPresent) => return Ok(None),
+        Err(VarError::NotUnicode(value)) => {
+            return Err(Error::InvalidConfig(
+                name,
+                value.to_string_lossy().into_owned(),
+            ));
+        }
+    };
+    match parse(value.trim()) {
+        Some(parsed) => Ok(Some(parsed)),
+        None => Err(Error::InvalidConfig(name, value)),
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_antialiasing(value: &str) -> Option<AaSupport> {
+    if value.eq_ignore_ascii_case("all") {
+        return Some(AaSupport::all());
+    }
+    value
+        .split(',')
+        .map(|method| match method.trim().to_ascii_lowercase().as_str() {
+            "area" => Some(AaConfig::Area),
+            "msaa8" => Some(AaConfig::Msaa8),
+            "msaa16" => Some(AaConfig::Msaa16),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|methods| methods.into_iter().collect())
+}
+
+fn parse_stage(value: &str) -> Option<PipelineStage> {
+    Some(match value.to_ascii_lowercase().as_str() {
+        "pathtag_scan" => PipelineStage::PathtagScan,
+        "bbox_clear" => PipelineStage::BboxClear,
+        "flatten" => PipelineStage::Flatten,
+        "draw_reduce" => PipelineStage::DrawReduce,
+        "draw_leaf" => PipelineStage::DrawLeaf,
+        "clip_reduce" => PipelineStage::ClipReduce,
+        "clip_leaf" => PipelineStage::ClipLeaf,
+        "binning" => PipelineStage::Binning,
+        "tile_alloc" => PipelineStage::TileAlloc,
+        "path_count" => PipelineStage::PathCount,
+        "backdrop" => PipelineStage::Backdrop,
+        "coarse" => PipelineStage::Coarse,
+        "path_tiling" => PipelineStage::PathTiling,
+        _ => return None,
+    })
+}
+
+fn parse_debug_layers(value: &str) -> Option<DebugLayers> {
+    let mut layers = DebugLayers::none();
+    for name in value.split(',') {
+        let layer = match name.trim().to_ascii_lowercase().as_str() {
+            "all" => DebugLayers::all(),
+            "none" | "" => DebugLayers::none(),
+            "bounding_boxes" => DebugLayers::BOUNDING_BOXES,
+            "linesoup_segments" => DebugLayers::LINESOUP_SEGMENTS,
+            "linesoup_points" => DebugLayers::LINESOUP_POINTS,
+            "validation" => DebugLayers::VALIDATION,
+            "stroke_expansion" => DebugLayers::STROKE_EXPANSION,
+            _ => return None,
+        };
+        layers = layers | layer;
+    }
+    Some(layers)
+}
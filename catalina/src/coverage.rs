@@ -0,0 +1,101 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Rendering of coverage masks, without evaluating brushes.
+
+use wgpu::{Device, TextureFormat};
+
+use crate::low_level::{BindType, ImageFormat, ShaderId};
+use crate::wgpu_engine::WgpuEngine;
+use crate::{shaders, Error};
+
+/// Copies the coverage rendered by fine rasterization, which is in the alpha channel of an
+/// RGBA image of the same size, into the first channel of the target.
+const COPY_SHADER: &str = r#"
+    @vertex
+    fn vs_main(@builtin(vertex_index) ix: u32) -> @builtin(position) vec4<f32> {
+        // Generate a full screen quad in normalized device coordinates
+        var vertex = vec2(-1.0, 1.0);
+        switch ix {
+            case 1u: {
+                vertex = vec2(-1.0, -1.0);
+            }
+            case 2u, 4u: {
+                vertex = vec2(1.0, -1.0);
+            }
+            case 5u: {
+                vertex = vec2(1.0, 1.0);
+            }
+            default: {}
+        }
+        return vec4(vertex, 0.0, 1.0);
+    }
+
+    @group(0) @binding(0)
+    var coverage: texture_2d<f32>;
+
+    @fragment
+    fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+        let alpha = textureLoad(coverage, vec2<i32>(position.xy), 0).a;
+        return vec4(alpha, 0.0, 0.0, 1.0);
+    }
+"#;
+
+/// Returns `true` if coverage can be rendered to textures of `format`.
+pub(crate) fn is_supported_format(format: TextureFormat) -> bool {
+    matches!(format, TextureFormat::R8Unorm | TextureFormat::R16Float)
+}
+
+/// The shaders used by [`Renderer::render_coverage_to_texture`](crate::Renderer::render_coverage_to_texture),
+/// which are created on first use.
+pub(crate) struct CoverageShaders {
+    /// The variant of fine rasterization which only renders coverage.
+    pub(crate) fine: ShaderId,
+    /// The copy shaders for each target format which has been used.
+    copies: Vec<(TextureFormat, ShaderId)>,
+}
+
+impl CoverageShaders {
+    pub(crate) fn new(device: &Device, engine: &mut WgpuEngine) -> Result<Self, Error> {
+        Ok(Self {
+            fine: shaders::fine_coverage(device, engine)?,
+            copies: Vec::new(),
+        })
+    }
+
+    /// Returns the shader which copies coverage into a target of `format`.
+    pub(crate) fn copy(
+        &mut self,
+        device: &Device,
+        engine: &mut WgpuEngine,
+        format: TextureFormat,
+    ) -> ShaderId {
+        if let Some((_, shader)) = self.copies.iter().find(|(f, _)| *f == format) {
+            return *shader;
+        }
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("coverage copy shaders"),
+            source: wgpu::ShaderSource::Wgsl(COPY_SHADER.into()),
+        });
+        let shader = engine.add_render_shader(
+            device,
+            "catalina.coverage_copy",
+            &module,
+            "vs_main",
+            "fs_main",
+            wgpu::PrimitiveTopology::TriangleList,
+            wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            },
+            None,
+            &[(
+                BindType::ImageRead(ImageFormat::Rgba8),
+                wgpu::ShaderStages::FRAGMENT,
+            )],
+        );
+        self.copies.push((format, shader));
+        shader
+    }
+}
@@ -15,7 +15,7 @@ pub(crate) use renderer::*;
 ///
 /// Currently, all layers additionally require the `debug_layers` feature.
 #[cfg_attr(docsrs, doc(hidden))]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 pub struct DebugLayers(u8);
 
 impl Debug for DebugLayers {
@@ -33,6 +33,9 @@ impl Debug for DebugLayers {
         if self.contains(Self::VALIDATION) {
             tuple.field(&"VALIDATION");
         }
+        if self.contains(Self::STROKE_EXPANSION) {
+            tuple.field(&"STROKE_EXPANSION");
+        }
 
         tuple.finish()
     }
@@ -68,6 +71,22 @@ impl DebugLayers {
     /// Requires the `debug_layers` feature.
     pub const VALIDATION: Self = Self(1 << 3);
 
+    /// Visualize the lines which strokes are expanded into, colored by the part of the stroke
+    /// they were generated for:
+    ///
+    ///    - Grey: the offset curves on either side of each segment.
+    ///    - Magenta: the last line of each offset curve, which is snapped to meet the join or
+    ///      cap after it.
+    ///    - Orange: caps.
+    ///    - Blue: bevel joins.
+    ///    - Green: miter joins.
+    ///    - Red: miter joins which exceeded the miter limit, so were drawn as bevels.
+    ///    - Yellow: round joins.
+    ///
+    /// Lines of filled paths aren't drawn.
+    /// Requires the `debug_layers` feature.
+    pub const STROKE_EXPANSION: Self = Self(1 << 4);
+
     /// Construct a `DebugLayers` from the raw bits.
     pub const fn from_bits(bits: u8) -> Self {
         Self(bits)
@@ -90,7 +109,8 @@ impl DebugLayers {
             Self::BOUNDING_BOXES.0
                 | Self::LINESOUP_SEGMENTS.0
                 | Self::LINESOUP_POINTS.0
-                | Self::VALIDATION.0,
+                | Self::VALIDATION.0
+                | Self::STROKE_EXPANSION.0,
         )
     }
 
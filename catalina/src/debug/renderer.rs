@@ -23,6 +23,7 @@ pub(crate) struct DebugRenderer {
     linesoup: ShaderId,
     linesoup_points: ShaderId,
     unpaired_points: ShaderId,
+    stroke_expansion: ShaderId,
 }
 
 impl DebugRenderer {
@@ -198,12 +199,50 @@ impl DebugRenderer {
             ],
         );
 
+        let stroke_expansion = engine.add_render_shader(
+            device,
+            "catalina.debug.stroke_expansion",
+            &module,
+            "stroke_expansion_vert",
+            "solid_color_frag",
+            wgpu::PrimitiveTopology::TriangleStrip,
+            wgpu::ColorTargetState {
+                format: target_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            },
+            // This mirrors the layout of the LineSoup structure.
+            Some(wgpu::VertexBufferLayout {
+                array_stride: size_of::<LineSoup>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttribute {
+                        format: wgpu::VertexFormat::Float32x2,
+                        offset: offset_of!(LineSoup, p0) as u64,
+                        shader_location: 0,
+                    },
+                    wgpu::VertexAttribute {
+                        format: wgpu::VertexFormat::Float32x2,
+                        offset: offset_of!(LineSoup, p1) as u64,
+                        shader_location: 1,
+                    },
+                    wgpu::VertexAttribute {
+                        format: wgpu::VertexFormat::Uint32,
+                        offset: offset_of!(LineSoup, kind) as u64,
+                        shader_location: 2,
+                    },
+                ],
+            }),
+            &[(BindType::Uniform, wgpu::ShaderStages::VERTEX)],
+        );
+
         Self {
             clear_tint,
             bboxes,
             linesoup,
             linesoup_points,
             unpaired_points,
+            stroke_expansion,
         }
     }
 
@@ -288,6 +327,17 @@ impl DebugRenderer {
                 clear_color: None,
             });
         }
+        if layers.contains(DebugLayers::STROKE_EXPANSION) {
+            recording.draw(DrawParams {
+                shader_id: self.stroke_expansion,
+                instance_count: bump.lines,
+                vertex_count: 4,
+                vertex_buffer: Some(captured.lines),
+                resources: vec![uniforms_buf],
+                target,
+                clear_color: None,
+            });
+        }
         if layers.contains(DebugLayers::LINESOUP_POINTS) {
             recording.draw(DrawParams {
                 shader_id: self.linesoup_points,
@@ -442,6 +492,51 @@ fn linesoup_vert(@builtin(vertex_index) vid: u32, line: LinesoupIn) -> VSOut {
 
 ////////////
 
+struct StrokeLineIn {
+    @location(0) p0: vec2f,
+    @location(1) p1: vec2f,
+    @location(2) kind: u32,
+}
+
+// The colors of the kinds of line, indexed by the LINE_KIND constants of `segment.wgsl`.
+// Lines of filled paths aren't drawn.
+alias LineKindColors = array<vec3f, 8>;
+var<private> line_kind_colors: LineKindColors = LineKindColors(
+    vec3(0., 0., 0.),
+    // Offset curves
+    vec3(0.6, 0.6, 0.6),
+    // Seams
+    vec3(1., 0., 1.),
+    // Caps
+    vec3(1., 0.5, 0.),
+    // Bevel joins
+    vec3(0., 0.5, 1.),
+    // Miter joins
+    vec3(0., 1., 0.),
+    // Miter joins exceeding the miter limit
+    vec3(1., 0., 0.),
+    // Round joins
+    vec3(1., 1., 0.),
+);
+
+@vertex
+fn stroke_expansion_vert(@builtin(vertex_index) vid: u32, line: StrokeLineIn) -> VSOut {
+    if line.kind == 0u || line.kind >= 8u {
+        // Place every vertex outside of the viewport, so that nothing is drawn.
+        return VSOut(vec4(2., 2., 0., 1.), vec4(0.));
+    }
+    let quad_corner = quad_vertices[quad_fill_indices[vid]] - vec2(0.5);
+    let v = line.p1 - line.p0;
+    let m = mix(line.p0, line.p1, 0.5);
+    let s = vec2(LINE_THICKNESS, length(v));
+    let vn = normalize(v);
+    let r = mat2x2(vn.y, -vn.x, vn.x, vn.y);
+    let p = (m + r * (s * quad_corner)) / vec2f(f32(uniforms.width), f32(uniforms.height));
+    return VSOut(map_to_ndc(p), vec4(line_kind_colors[line.kind], 1.));
+}
+
+////////////
+
 struct LinepointsUniforms {
     point_color: vec3f,
     point_size: f32,
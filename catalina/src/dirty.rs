@@ -0,0 +1,85 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Regions of the output which changed since the previous frame.
+
+use peniko::kurbo::Rect;
+
+/// The most regions in a [`DirtyRects`], beyond which added regions are merged.
+///
+/// Each region is rendered separately, so a few large regions are cheaper than many small
+/// ones.
+pub const MAX_DIRTY_RECTS: usize = 4;
+
+/// The regions of the output which changed since the previous frame, for
+/// [`RenderParams::dirty_rects`](crate::RenderParams::dirty_rects).
+///
+/// Regions which overlap are merged into their union as they are added, and once there are
+/// [`MAX_DIRTY_RECTS`] regions, each new region is merged with the one whose area grows the
+/// least.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DirtyRects {
+    rects: [Rect; MAX_DIRTY_RECTS],
+    len: usize,
+}
+
+impl DirtyRects {
+    /// Creates an empty set of regions, for a frame in which nothing changed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a region, in pixels. Regions with no area are ignored.
+    pub fn add(&mut self, rect: Rect) {
+        let mut rect = rect.abs();
+        if rect.is_zero_area() || !rect.is_finite() {
+            return;
+        }
+        // Merging can make a region overlap others, so merge until none do.
+        while let Some(ix) = self
+            .rects()
+            .iter()
+            .position(|r| !r.intersect(rect).is_zero_area())
+        {
+            rect = rect.union(self.remove(ix));
+        }
+        if self.len == MAX_DIRTY_RECTS {
+            let growth = |r: &Rect| r.union(rect).area() - r.area();
+            let ix = (0..self.len)
+                .min_by(|&a, &b| growth(&self.rects[a]).total_cmp(&growth(&self.rects[b])))
+                .unwrap_or(0);
+            let merged = rect.union(self.remove(ix));
+            self.add(merged);
+            return;
+        }
+        self.rects[self.len] = rect;
+        self.len += 1;
+    }
+
+    /// Returns the regions.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects[..self.len]
+    }
+
+    /// Returns `true` if there are no regions.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn remove(&mut self, ix: usize) -> Rect {
+        let rect = self.rects[ix];
+        self.rects.copy_within(ix + 1..self.len, ix);
+        self.len -= 1;
+        rect
+    }
+}
+
+impl FromIterator<Rect> for DirtyRects {
+    fn from_iter<I: IntoIterator<Item = Rect>>(iter: I) -> Self {
+        let mut dirty = Self::new();
+        for rect in iter {
+            dirty.add(rect);
+        }
+        dirty
+    }
+}
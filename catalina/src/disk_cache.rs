@@ -0,0 +1,159 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Persistence of compiled pipelines between runs.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use wgpu::{AdapterInfo, Device, PipelineCache};
+
+/// The start of every pipeline cache file, followed by the length and checksum of the data.
+const MAGIC: &[u8; 8] = b"CATPIPE1";
+
+/// The length of the header before the data of a pipeline cache file.
+const HEADER_LEN: usize = MAGIC.len() + 2 * size_of::<u64>();
+
+/// Counts the files written by this process, to give each temporary file a unique name.
+static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// A directory in which derived data that is expensive to recreate is kept between runs.
+///
+/// Compiling the renderer's pipelines is most of the cost of creating a [`Renderer`], which
+/// matters for deployments which restart often, such as kiosks and embedded devices. With a
+/// cache set in [`RendererOptions::disk_cache`], the driver's compiled pipelines are loaded
+/// when the renderer is created and written by [`Renderer::save_disk_cache`], so later runs
+/// skip most of the compilation.
+///
+/// The cache is only used on backends whose devices were created with
+/// [`wgpu::Features::PIPELINE_CACHE`], which [`RenderContext`] requests where supported. The
+/// files are named after the adapter and driver, so several devices can share a directory, and
+/// a driver update starts a new cache rather than reusing stale data. Missing or unreadable
+/// files are treated as an empty cache.
+///
+/// [`Renderer`]: crate::Renderer
+/// [`RendererOptions::disk_cache`]: crate::RendererOptions::disk_cache
+/// [`Renderer::save_disk_cache`]: crate::Renderer::save_disk_cache
+/// [`RenderContext`]: crate::util::RenderContext
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+    /// The name of the file holding the pipeline cache, or `None` if the backend has none.
+    pipelines: Option<String>,
+}
+
+impl DiskCache {
+    /// Creates a cache in `dir` for devices of the adapter described by `adapter`, which is
+    /// created when the cache is first saved.
+    pub fn new(dir: impl Into<PathBuf>, adapter: &AdapterInfo) -> Self {
+        Self {
+            dir: dir.into(),
+            pipelines: wgpu::util::pipeline_cache_key(adapter),
+        }
+    }
+
+    /// The directory the cache is kept in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Creates a pipeline cache for `device`, with the data saved by an earlier run if there
+    /// is any.
+    pub(crate) fn load_pipelines(&self, device: &Device) -> Option<PipelineCache> {
+        let name = self.pipelines.as_ref()?;
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            return None;
+        }
+        let file = std::fs::read(self.dir.join(name)).ok();
+        let data = file.as_deref().and_then(unwrap_data);
+        // SAFETY: The data was written by `save_pipelines` from `PipelineCache::get_data`, in
+        // the file named after this adapter, and its checksum shows that it wasn't truncated or
+        // changed since. wgpu checks that its header matches the device, and `fallback` starts
+        // an empty cache if it doesn't.
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("catalina.pipeline_cache"),
+                data,
+                fallback: true,
+            })
+        };
+        Some(cache)
+    }
+
+    /// Writes the data of `cache` to the cache directory.
+    ///
+    /// The data is written to a temporary file which then replaces the previous one, so that
+    /// an interrupted write doesn't leave a truncated cache. The temporary file is named after
+    /// this process and save, so that renderers saving to the same directory at once don't
+    /// write to the same file.
+    pub(crate) fn save_pipelines(&self, cache: &PipelineCache) -> std::io::Result<()> {
+        let (Some(name), Some(data)) = (&self.pipelines, cache.get_data()) else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(name);
+        let count = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+        let temporary = self
+            .dir
+            .join(format!("{name}.{}.{count}.tmp", std::process::id()));
+        if let Err(error) = std::fs::write(&temporary, wrap_data(&data)) {
+            let _ = std::fs::remove_file(&temporary);
+            return Err(error);
+        }
+        std::fs::rename(temporary, path)
+    }
+}
+
+/// Prefixes the data of a pipeline cache with the header checked by [`unwrap_data`].
+fn wrap_data(data: &[u8]) -> Vec<u8> {
+    let mut file = Vec::with_capacity(HEADER_LEN + data.len());
+    file.extend_from_slice(MAGIC);
+    file.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    file.extend_from_slice(&checksum(data).to_le_bytes());
+    file.extend_from_slice(data);
+    file
+}
+
+/// Returns the data of a pipeline cache file written by [`wrap_data`], or `None` if its
+/// header doesn't match the data, such as when the file was truncated or corrupted.
+fn unwrap_data(file: &[u8]) -> Option<&[u8]> {
+    let (header, data) = file.split_at_checked(HEADER_LEN)?;
+    let (magic, header) = header.split_at(MAGIC.len());
+    let (len, sum) = header.split_at(size_of::<u64>());
+    let len = u64::from_le_bytes(len.try_into().ok()?);
+    let sum = u64::from_le_bytes(sum.try_into().ok()?);
+    (magic == MAGIC && len == data.len() as u64 && sum == checksum(data)).then_some(data)
+}
+
+/// The 64-bit FNV-1a hash of `data`.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unwrap_data, wrap_data, HEADER_LEN};
+
+    #[test]
+    fn wrapped_data_round_trips() {
+        let data = b"compiled pipelines".as_slice();
+        assert_eq!(unwrap_data(&wrap_data(data)), Some(data));
+        assert_eq!(unwrap_data(&wrap_data(&[])), Some([].as_slice()));
+    }
+
+    #[test]
+    fn damaged_files_are_rejected() {
+        let file = wrap_data(b"compiled pipelines");
+        // Truncated, in the data and in the header.
+        assert_eq!(unwrap_data(&file[..file.len() - 1]), None);
+        assert_eq!(unwrap_data(&file[..HEADER_LEN - 1]), None);
+        // Changed data.
+        let mut changed = file.clone();
+        *changed.last_mut().unwrap() ^= 1;
+        assert_eq!(unwrap_data(&changed), None);
+        // Data from before the header was added.
+        assert_eq!(unwrap_data(b"compiled pipelines without a header"), None);
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! High quality downscaling of textures, such as for thumbnails of images and cached layers.
+
+use std::borrow::Cow;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::Device;
+
+use crate::low_level::{BindType, ImageFormat, ImageProxy, Recording, ResourceProxy, ShaderId};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+
+/// The size of each side of the workgroups of the downscaling shader.
+const WORKGROUP_SIZE: u32 = 16;
+
+/// The filter used by [`Renderer::downscale_texture`](crate::Renderer::downscale_texture).
+///
+/// Both filters weigh every source pixel within their footprint, however large the ratio
+/// between the sizes, so fine detail doesn't alias or shimmer as it does when sampling with a
+/// bilinear filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DownscaleFilter {
+    /// Averages the source pixels covered by each output pixel, weighted by how much of them
+    /// is covered.
+    ///
+    /// This never overshoots, so is best suited to content with hard edges, such as text and
+    /// user interfaces.
+    Area,
+    /// A Lanczos filter with three lobes.
+    ///
+    /// This keeps fine detail sharper than [`Area`](Self::Area), such as in photographs, at
+    /// the cost of slight ringing around hard edges.
+    #[default]
+    Lanczos3,
+}
+
+/// Resamples the source along one direction.
+///
+/// The horizontal pass reads the sRGB encoded source and writes linear, premultiplied colors
+/// to an intermediate texture, which the vertical pass resamples into the output. Filtering in
+/// linear light keeps the brightness and saturation of fine colored detail, which would
+/// darken towards gray if the encoded values were averaged.
+const SHADER: &str = r#"
+    struct Params {
+        // The size of the output of this pass.
+        width: u32,
+        height: u32,
+        // The number of source pixels along the direction of this pass.
+        source_extent: u32,
+        // Whether this is the vertical pass, which reads the output of the horizontal pass.
+        vertical: u32,
+        // The number of source pixels covered by each output pixel.
+        scale: f32,
+        // 0 for area averaging, 1 for Lanczos3.
+        kernel: u32,
+        _padding: vec2<u32>,
+    }
+
+    const PI: f32 = 3.14159265;
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<OUTPUT_FORMAT, write>;
+
+    fn srgb_to_linear(rgb: vec3<f32>) -> vec3<f32> {
+        return select(
+            pow((rgb + 0.055) / 1.055, vec3(2.4)),
+            rgb / 12.92,
+            rgb <= vec3(0.04045)
+        );
+    }
+
+    fn linear_to_srgb(rgb: vec3<f32>) -> vec3<f32> {
+        return select(
+            1.055 * pow(rgb, vec3(1.0 / 2.4)) - 0.055,
+            rgb * 12.92,
+            rgb <= vec3(0.0031308)
+        );
+    }
+
+    fn lanczos3(x: f32) -> f32 {
+        if abs(x) < 1e-5 {
+            return 1.0;
+        }
+        if abs(x) >= 3.0 {
+            return 0.0;
+        }
+        let px = PI * x;
+        return 3.0 * sin(px) * sin(px / 3.0) / (px * px);
+    }
+
+    // Loads the linear, premultiplied color of the source pixel at `position` along the
+    // direction of this pass.
+    fn load(id: vec2<u32>, position: i32) -> vec4<f32> {
+        if params.vertical != 0u {
+            return textureLoad(source, vec2(i32(id.x), position), 0);
+        }
+        let color = textureLoad(source, vec2(position, i32(id.y)), 0);
+        return vec4(srgb_to_linear(color.rgb) * color.a, color.a);
+    }
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let index = select(id.x, id.y, params.vertical != 0u);
+        let start = f32(index) * params.scale;
+        let end = start + params.scale;
+        var sum = vec4(0.0);
+        var total = 0.0;
+        if params.kernel == 0u {
+            for (var i = i32(floor(start)); f32(i) < end; i++) {
+                let weight = min(end, f32(i + 1)) - max(start, f32(i));
+                sum += load(id.xy, i) * weight;
+                total += weight;
+            }
+        } else {
+            // The filter is stretched to cover each output pixel when reducing, and kept at
+            // its natural width when enlarging. Taps outside the source are skipped rather than
+            // repeating the edge pixels, which would give them too much weight.
+            let width = max(params.scale, 1.0);
+            let center = (start + end) * 0.5;
+            let first = max(i32(floor(center - 3.0 * width)), 0);
+            let last = min(i32(ceil(center + 3.0 * width)), i32(params.source_extent) - 1);
+            for (var i = first; i <= last; i++) {
+                let weight = lanczos3((f32(i) + 0.5 - center) / width);
+                sum += load(id.xy, i) * weight;
+                total += weight;
+            }
+        }
+        let color = sum / max(total, 1e-6);
+        if params.vertical == 0u {
+            textureStore(output, vec2<i32>(id.xy), color);
+            return;
+        }
+        // Lanczos overshoots around hard edges, so clamp to a valid premultiplied color
+        // before unpremultiplying.
+        let alpha = clamp(color.a, 0.0, 1.0);
+        var rgb = vec3(0.0);
+        if alpha > 0.0 {
+            rgb = clamp(color.rgb, vec3(0.0), vec3(alpha)) / alpha;
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(linear_to_srgb(rgb), alpha));
+    }
+"#;
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct DownscaleParams {
+    width: u32,
+    height: u32,
+    source_extent: u32,
+    vertical: u32,
+    scale: f32,
+    kernel: u32,
+    _padding: [u32; 2],
+}
+
+/// The shaders used by [`Renderer::downscale_texture`](crate::Renderer::downscale_texture).
+#[derive(Clone, Copy)]
+pub(crate) struct DownscaleShaders {
+    horizontal: ShaderId,
+    vertical: ShaderId,
+}
+
+impl DownscaleShaders {
+    /// Adds the downscaling shaders to `engine`.
+    pub(crate) fn new(device: &Device, engine: &mut WgpuEngine) -> Self {
+        Self {
+            horizontal: engine.add_compute_shader(
+                device,
+                "catalina.downscale_horizontal",
+                Cow::Owned(SHADER.replace("OUTPUT_FORMAT", "rgba16float")),
+                &[
+                    BindType::Uniform,
+                    BindType::ImageRead(ImageFormat::Rgba8),
+                    BindType::Image(ImageFormat::Rgba16Float),
+                ],
+                CpuShaderType::Missing,
+            ),
+            vertical: engine.add_compute_shader(
+                device,
+                "catalina.downscale_vertical",
+                Cow::Owned(SHADER.replace("OUTPUT_FORMAT", "rgba8unorm")),
+                &[
+                    BindType::Uniform,
+                    BindType::ImageRead(ImageFormat::Rgba16Float),
+                    BindType::Image(ImageFormat::Rgba8),
+                ],
+                CpuShaderType::Missing,
+            ),
+        }
+    }
+
+    /// Records the passes which resample `source` into `output` through `intermediate`,
+    /// which must be as wide as the output and as tall as the source.
+    pub(crate) fn record(
+        self,
+        recording: &mut Recording,
+        source: ImageProxy,
+        intermediate: ImageProxy,
+        output: ImageProxy,
+        filter: DownscaleFilter,
+    ) {
+        let kernel = match filter {
+            DownscaleFilter::Area => 0,
+            DownscaleFilter::Lanczos3 => 1,
+        };
+        let passes = [
+            (self.horizontal, source, intermediate, source.width, false),
+            (
+                self.vertical,
+                intermediate,
+                output,
+                intermediate.height,
+                true,
+            ),
+        ];
+        for (shader, from, to, source_extent, vertical) in passes {
+            let output_extent = if vertical { to.height } else { to.width };
+            let scale = source_extent as f32 / output_extent as f32;
+            let params = DownscaleParams {
+                width: to.width,
+                height: to.height,
+                source_extent,
+                vertical: vertical.into(),
+                scale,
+                kernel,
+                _padding: [0; 2],
+            };
+            let config = ResourceProxy::Buffer(
+                recording.upload_uniform("catalina.downscale_params", bytemuck::bytes_of(&params)),
+            );
+            recording.dispatch(
+                shader,
+                (
+                    to.width.div_ceil(WORKGROUP_SIZE),
+                    to.height.div_ceil(WORKGROUP_SIZE),
+                    1,
+                ),
+                [config, ResourceProxy::Image(from), ResourceProxy::Image(to)],
+            );
+            recording.free_resource(config);
+        }
+    }
+}
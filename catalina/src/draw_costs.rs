@@ -0,0 +1,189 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Measuring the cost of fine rasterization for each draw object in a scene.
+
+use std::ops::{AddAssign, Range};
+
+use catalina_encoding::DrawTag;
+
+/// The number of counters written for each draw object by the `fine_cost` shader.
+pub(crate) const COUNTERS_PER_DRAW: usize = 3;
+
+/// The kind of a draw object, which determines how its pixels are shaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DrawKind {
+    /// A fill or stroke with a solid color.
+    Color,
+    /// A fill or stroke with a linear gradient.
+    LinearGradient,
+    /// A fill or stroke with a radial gradient.
+    RadialGradient,
+    /// A fill or stroke with a sweep gradient.
+    SweepGradient,
+    /// A fill or stroke with an image, including [`Scene::draw_image`](crate::Scene::draw_image).
+    Image,
+    /// A blurred rounded rectangle.
+    BlurredRect,
+    /// A fill with a hatch pattern, from [`Scene::fill_hatch`](crate::Scene::fill_hatch).
+    Hatch,
+    /// A fill with a custom paint, from [`Scene::fill_custom`](crate::Scene::fill_custom).
+    Custom,
+    /// The start of a layer, from [`Scene::push_layer`](crate::Scene::push_layer).
+    ///
+    /// This only records where the layer starts. The cost of clipping and blending the layer is
+    /// that of its [`EndLayer`](Self::EndLayer).
+    BeginLayer,
+    /// The end of a layer, from [`Scene::pop_layer`](crate::Scene::pop_layer), which clips and
+    /// blends its contents.
+    EndLayer,
+}
+
+impl DrawKind {
+    fn from_tag(tag: DrawTag) -> Option<Self> {
+        Some(match tag {
+            DrawTag::COLOR => Self::Color,
+            DrawTag::LINEAR_GRADIENT => Self::LinearGradient,
+            DrawTag::RADIAL_GRADIENT => Self::RadialGradient,
+            DrawTag::SWEEP_GRADIENT => Self::SweepGradient,
+            DrawTag::IMAGE => Self::Image,
+            DrawTag::BLUR_RECT => Self::BlurredRect,
+            DrawTag::HATCH => Self::Hatch,
+            DrawTag::CUSTOM => Self::Custom,
+            DrawTag::BEGIN_CLIP => Self::BeginLayer,
+            DrawTag::END_CLIP => Self::EndLayer,
+            _ => return None,
+        })
+    }
+}
+
+/// The cost of fine rasterization for a draw object, or for a group of them.
+///
+/// These are counts of the work done, rather than timings, so they are the same on every
+/// device and can be compared between draws.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawCost {
+    /// The number of commands interpreted by fine rasterization, summed over every tile the
+    /// draw touches.
+    pub commands: u64,
+    /// The number of pixels shaded, where each pixel is counted again for every draw which
+    /// covers it.
+    pub pixels: u64,
+    /// The number of path segments rasterized, where a segment is counted once for every
+    /// tile it crosses.
+    pub segments: u64,
+}
+
+impl AddAssign for DrawCost {
+    fn add_assign(&mut self, other: Self) {
+        self.commands += other.commands;
+        self.pixels += other.pixels;
+        self.segments += other.segments;
+    }
+}
+
+impl std::iter::Sum for DrawCost {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut total = Self::default();
+        for cost in iter {
+            total += cost;
+        }
+        total
+    }
+}
+
+/// The cost of fine rasterization for each draw object in a scene, measured by
+/// [`Renderer::render_to_texture_with_costs`](crate::Renderer::render_to_texture_with_costs).
+///
+/// Draw objects are indexed in the order they were added to the scene, which is also the
+/// index into `scene.encoding().draw_tags`. Each fill, stroke and image is a single draw
+/// object, and each layer is two. To find the cost of a group of draws, such as a widget,
+/// record `scene.encoding().draw_tags.len()` before and after it is drawn and pass the two
+/// to [`range`](Self::range). Layers which are still open when the scene is rendered are
+/// closed by draw objects after the end of the scene.
+#[derive(Clone, Debug, Default)]
+pub struct DrawCosts {
+    draws: Vec<(Option<DrawKind>, DrawCost)>,
+}
+
+impl DrawCosts {
+    /// Creates the report from the draw tags of the scene and the counters downloaded from
+    /// the `fine_cost` shader.
+    pub(crate) fn new(tags: impl Iterator<Item = DrawTag>, counters: &[u32]) -> Self {
+        let draws = tags
+            .zip(counters.chunks_exact(COUNTERS_PER_DRAW))
+            .map(|(tag, counters)| {
+                let cost = DrawCost {
+                    commands: counters[0].into(),
+                    pixels: counters[1].into(),
+                    segments: counters[2].into(),
+                };
+                (DrawKind::from_tag(tag), cost)
+            })
+            .collect();
+        Self { draws }
+    }
+
+    /// Returns the number of draw objects.
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    /// Returns `true` if the scene had no draw objects.
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Returns the kind and cost of the draw object at `ix`.
+    ///
+    /// The kind is `None` for draw objects which are skipped by rendering.
+    pub fn get(&self, ix: usize) -> Option<(Option<DrawKind>, DrawCost)> {
+        self.draws.get(ix).copied()
+    }
+
+    /// Iterates over the kind and cost of each draw object, in the order of the scene.
+    pub fn iter(&self) -> impl Iterator<Item = (Option<DrawKind>, DrawCost)> + '_ {
+        self.draws.iter().copied()
+    }
+
+    /// Returns the total cost of the draw objects in `range`, which is clamped to the
+    /// number of draw objects.
+    pub fn range(&self, range: Range<usize>) -> DrawCost {
+        let end = range.end.min(self.draws.len());
+        let start = range.start.min(end);
+        self.draws[start..end].iter().map(|(_, cost)| *cost).sum()
+    }
+
+    /// Returns the total cost of the scene.
+    pub fn total(&self) -> DrawCost {
+        self.range(0..self.draws.len())
+    }
+
+    /// Returns the total cost of the draw objects of each kind, in the order each kind first
+    /// appears in the scene.
+    pub fn by_kind(&self) -> Vec<(DrawKind, DrawCost)> {
+        let mut totals: Vec<(DrawKind, DrawCost)> = Vec::new();
+        for (kind, cost) in self.draws.iter().copied() {
+            let Some(kind) = kind else {
+                continue;
+            };
+            match totals.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, total)) => *total += cost,
+                None => totals.push((kind, cost)),
+            }
+        }
+        totals
+    }
+
+    /// Returns the indices of the `count` draw objects which shade the most pixels, most
+    /// expensive first, with ties broken by the number of commands.
+    pub fn most_expensive(&self, count: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.draws.len()).collect();
+        indices.sort_by_key(|&ix| {
+            let cost = self.draws[ix].1;
+            std::cmp::Reverse((cost.pixels, cost.commands))
+        });
+        indices.truncate(count);
+        indices
+    }
+}
@@ -0,0 +1,1206 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Filter passes over the content of layers pushed with
+//! [`Scene::push_layer_with_filter`](crate::Scene::push_layer_with_filter).
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bytemuck::{Pod, Zeroable};
+use peniko::kurbo::{Affine, Vec2};
+use peniko::Color;
+use wgpu::Device;
+
+use crate::low_level::{BindType, ImageFormat, ImageProxy, Recording, ResourceProxy, ShaderId};
+use crate::scene::{
+    Filter, FilterGraph, FilterInput, FilterNode, FilterNodeId, LightSource, Lighting, Turbulence,
+};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+
+/// The size of each side of the workgroups of the filter shaders.
+const WORKGROUP_SIZE: u32 = 16;
+
+/// The most images which a filter graph can use at once, including the content of the layer.
+const MAX_FILTER_IMAGES: usize = 8;
+
+/// Blurs along one axis with a Gaussian filter. Pixels outside the image are transparent.
+const BLUR_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        step_x: i32,
+        step_y: i32,
+        radius: i32,
+        std_dev: f32,
+        _padding: vec2<u32>,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let size = vec2(i32(params.width), i32(params.height));
+        let step = vec2(params.step_x, params.step_y);
+        let scale = -0.5 / (params.std_dev * params.std_dev);
+        // Sum premultiplied colors, so that transparent samples don't bleed color.
+        var sum = vec4(0.0);
+        var total = 0.0;
+        for (var i = -params.radius; i <= params.radius; i++) {
+            let weight = exp(f32(i * i) * scale);
+            total += weight;
+            let coords = vec2<i32>(id.xy) + step * i;
+            if all(coords >= vec2(0)) && all(coords < size) {
+                let color = textureLoad(source, coords, 0);
+                sum += weight * vec4(color.rgb * color.a, color.a);
+            }
+        }
+        let blurred = sum / total;
+        var rgb = vec3(0.0);
+        if blurred.a > 0.0 {
+            rgb = blurred.rgb / blurred.a;
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(rgb, blurred.a));
+    }
+"#;
+
+/// Composites the source over the alpha of the blurred source, offset and filled with a color,
+/// or for an inner shadow, the offset and filled alpha over the source, inside the source.
+const SHADOW_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        offset_x: i32,
+        offset_y: i32,
+        // Premultiplied.
+        color: vec4<f32>,
+        // 1 if `blurred` is the inverse of the alpha of the source, for an inner shadow.
+        inner: u32,
+        _padding: u32,
+        _padding2: vec2<u32>,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var blurred: texture_2d<f32>;
+
+    @group(0) @binding(3)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let coords = vec2<i32>(id.xy);
+        let size = vec2(i32(params.width), i32(params.height));
+        let shadow_coords = coords - vec2(params.offset_x, params.offset_y);
+        // Outside the image is outside the source, which casts an inner shadow.
+        var shadow = params.color * f32(params.inner);
+        if all(shadow_coords >= vec2(0)) && all(shadow_coords < size) {
+            shadow = params.color * textureLoad(blurred, shadow_coords, 0).a;
+        }
+        let color = textureLoad(source, coords, 0);
+        var result = vec4(color.rgb * color.a, color.a);
+        if params.inner == 1u {
+            result = vec4(shadow.rgb * color.a + result.rgb * (1.0 - shadow.a), color.a);
+        } else {
+            result += shadow * (1.0 - color.a);
+        }
+        var rgb = vec3(0.0);
+        if result.a > 0.0 {
+            rgb = result.rgb / result.a;
+        }
+        textureStore(output, coords, vec4(rgb, result.a));
+    }
+"#;
+
+/// Averages samples along a line through each pixel, which either has a fixed offset or is
+/// directed towards a center point.
+const LINE_BLUR_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        // 0 for a directional blur along `vector`, or 1 for a radial blur towards `vector`.
+        mode: u32,
+        _padding: u32,
+        vector: vec2<f32>,
+        amount: f32,
+        _padding2: u32,
+    }
+
+    const MAX_SAMPLES = 64u;
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    // Pixels outside the image are transparent.
+    fn load_premultiplied(coords: vec2<i32>) -> vec4<f32> {
+        let size = vec2(i32(params.width), i32(params.height));
+        if any(coords < vec2(0)) || any(coords >= size) {
+            return vec4(0.0);
+        }
+        let color = textureLoad(source, coords, 0);
+        return vec4(color.rgb * color.a, color.a);
+    }
+
+    fn sample_bilinear(p: vec2<f32>) -> vec4<f32> {
+        let q = p - 0.5;
+        let base = floor(q);
+        let f = q - base;
+        let i = vec2<i32>(base);
+        let top = mix(load_premultiplied(i), load_premultiplied(i + vec2(1, 0)), f.x);
+        let bottom = mix(load_premultiplied(i + vec2(0, 1)), load_premultiplied(i + vec2(1, 1)), f.x);
+        return mix(top, bottom, f.y);
+    }
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let p = vec2<f32>(id.xy) + 0.5;
+        var start = p - 0.5 * params.vector;
+        var end = p + 0.5 * params.vector;
+        if params.mode == 1u {
+            start = p;
+            end = mix(p, params.vector, params.amount);
+        }
+        // Take about one sample per pixel along the line.
+        let n = clamp(u32(ceil(distance(start, end))) + 1u, 2u, MAX_SAMPLES);
+        var sum = vec4(0.0);
+        for (var i = 0u; i < n; i++) {
+            sum += sample_bilinear(mix(start, end, f32(i) / f32(n - 1u)));
+        }
+        let blurred = sum / f32(n);
+        var rgb = vec3(0.0);
+        if blurred.a > 0.0 {
+            rgb = blurred.rgb / blurred.a;
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(rgb, blurred.a));
+    }
+"#;
+
+/// Takes the maximum or minimum of each channel along one axis. Pixels outside the image are
+/// transparent.
+const MORPHOLOGY_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        step_x: i32,
+        step_y: i32,
+        radius: i32,
+        // 1 to take the maximum, or 0 to take the minimum.
+        dilate: u32,
+        _padding: vec2<u32>,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let size = vec2(i32(params.width), i32(params.height));
+        let step = vec2(params.step_x, params.step_y);
+        // Compare premultiplied colors, as SVG does.
+        var result = vec4(f32(1u - params.dilate));
+        for (var i = -params.radius; i <= params.radius; i++) {
+            let coords = vec2<i32>(id.xy) + step * i;
+            var color = vec4(0.0);
+            if all(coords >= vec2(0)) && all(coords < size) {
+                let sample = textureLoad(source, coords, 0);
+                color = vec4(sample.rgb * sample.a, sample.a);
+            }
+            if params.dilate != 0u {
+                result = max(result, color);
+            } else {
+                result = min(result, color);
+            }
+        }
+        var rgb = vec3(0.0);
+        if result.a > 0.0 {
+            rgb = min(result.rgb / result.a, vec3(1.0));
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(rgb, result.a));
+    }
+"#;
+
+/// Generates Perlin noise, or displaces the source by offsets read from the noise.
+const NOISE_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        // 0 to output the noise, or 1 to displace the source by it.
+        mode: u32,
+        octaves: u32,
+        // The transform from pixels to the coordinates of the noise.
+        to_layer_matrix: vec4<f32>,
+        to_layer_translation: vec2<f32>,
+        base_frequency: vec2<f32>,
+        // The linear part of the transform from the coordinates of the noise to pixels.
+        from_layer_matrix: vec4<f32>,
+        scale: f32,
+        seed: u32,
+        fractal: u32,
+        _padding: u32,
+    }
+
+    const MAX_OCTAVES = 10u;
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    fn hash(x: u32) -> u32 {
+        var h = x * 747796405u + 2891336453u;
+        h = ((h >> ((h >> 28u) + 4u)) ^ h) * 277803737u;
+        return (h >> 22u) ^ h;
+    }
+
+    // The random unit gradient at a lattice point, for one channel.
+    fn gradient(lattice: vec2<i32>, channel: u32) -> vec2<f32> {
+        let h = hash(hash(hash(bitcast<u32>(lattice.x) ^ params.seed) ^ bitcast<u32>(lattice.y)) + channel);
+        let angle = f32(h) * (6.283185307 / 4294967296.0);
+        return vec2(cos(angle), sin(angle));
+    }
+
+    fn s_curve(t: vec2<f32>) -> vec2<f32> {
+        return t * t * (3.0 - 2.0 * t);
+    }
+
+    // Gradient noise, from about -1 to 1.
+    fn noise(p: vec2<f32>, channel: u32) -> f32 {
+        let cell = floor(p);
+        let i = vec2<i32>(cell);
+        let f = p - cell;
+        let n00 = dot(gradient(i, channel), f);
+        let n10 = dot(gradient(i + vec2(1, 0), channel), f - vec2(1.0, 0.0));
+        let n01 = dot(gradient(i + vec2(0, 1), channel), f - vec2(0.0, 1.0));
+        let n11 = dot(gradient(i + vec2(1, 1), channel), f - vec2(1.0, 1.0));
+        let t = s_curve(f);
+        return mix(mix(n00, n10, t.x), mix(n01, n11, t.x), t.y) * 1.41421356;
+    }
+
+    // The unpremultiplied color of the noise at a point in the coordinates of the layer.
+    fn turbulence(p: vec2<f32>) -> vec4<f32> {
+        var color = vec4(0.0);
+        for (var channel = 0u; channel < 4u; channel++) {
+            var sum = 0.0;
+            var v = p * params.base_frequency;
+            var ratio = 1.0;
+            for (var octave = 0u; octave < min(params.octaves, MAX_OCTAVES); octave++) {
+                let n = noise(v, channel);
+                if params.fractal != 0u {
+                    sum += n / ratio;
+                } else {
+                    sum += abs(n) / ratio;
+                }
+                v *= 2.0;
+                ratio *= 2.0;
+            }
+            if params.fractal != 0u {
+                sum = 0.5 * (sum + 1.0);
+            }
+            color[channel] = clamp(sum, 0.0, 1.0);
+        }
+        return color;
+    }
+
+    // Pixels outside the image are transparent.
+    fn load_premultiplied(coords: vec2<i32>) -> vec4<f32> {
+        let size = vec2(i32(params.width), i32(params.height));
+        if any(coords < vec2(0)) || any(coords >= size) {
+            return vec4(0.0);
+        }
+        let color = textureLoad(source, coords, 0);
+        return vec4(color.rgb * color.a, color.a);
+    }
+
+    fn sample_bilinear(p: vec2<f32>) -> vec4<f32> {
+        let q = p - 0.5;
+        let base = floor(q);
+        let f = q - base;
+        let i = vec2<i32>(base);
+        let top = mix(load_premultiplied(i), load_premultiplied(i + vec2(1, 0)), f.x);
+        let bottom = mix(load_premultiplied(i + vec2(0, 1)), load_premultiplied(i + vec2(1, 1)), f.x);
+        return mix(top, bottom, f.y);
+    }
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let p = vec2<f32>(id.xy) + 0.5;
+        let m = params.to_layer_matrix;
+        let layer_p = m.xy * p.x + m.zw * p.y + params.to_layer_translation;
+        let color = turbulence(layer_p);
+        if params.mode == 0u {
+            textureStore(output, vec2<i32>(id.xy), color);
+            return;
+        }
+        let offset = params.scale * (color.rg - 0.5);
+        let n = params.from_layer_matrix;
+        let displaced = sample_bilinear(p + n.xy * offset.x + n.zw * offset.y);
+        var rgb = vec3(0.0);
+        if displaced.a > 0.0 {
+            rgb = displaced.rgb / displaced.a;
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(rgb, displaced.a));
+    }
+"#;
+
+/// Shades the source with a light, using the alpha of `heights` as the height of a surface.
+const LIGHTING_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        // 1 if `light` is the position of a point light, or 0 if it is the direction
+        // towards a distant light.
+        point: u32,
+        specular_exponent: f32,
+        light: vec4<f32>,
+        color: vec4<f32>,
+        surface_scale: f32,
+        diffuse: f32,
+        specular: f32,
+        _padding: u32,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var heights: texture_2d<f32>;
+
+    @group(0) @binding(3)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    // Pixels outside the image have a height of zero.
+    fn height_at(coords: vec2<i32>) -> f32 {
+        let size = vec2(i32(params.width), i32(params.height));
+        if any(coords < vec2(0)) || any(coords >= size) {
+            return 0.0;
+        }
+        return params.surface_scale * textureLoad(heights, coords, 0).a;
+    }
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let coords = vec2<i32>(id.xy);
+        // The Sobel operator, as used by SVG.
+        let h = array(
+            height_at(coords + vec2(-1, -1)), height_at(coords + vec2(0, -1)), height_at(coords + vec2(1, -1)),
+            height_at(coords + vec2(-1, 0)), height_at(coords), height_at(coords + vec2(1, 0)),
+            height_at(coords + vec2(-1, 1)), height_at(coords + vec2(0, 1)), height_at(coords + vec2(1, 1)),
+        );
+        let dx = (h[2] + 2.0 * h[5] + h[8]) - (h[0] + 2.0 * h[3] + h[6]);
+        let dy = (h[6] + 2.0 * h[7] + h[8]) - (h[0] + 2.0 * h[1] + h[2]);
+        let normal = normalize(vec3(-0.25 * dx, -0.25 * dy, 1.0));
+        var light = params.light.xyz;
+        if params.point == 1u {
+            light = normalize(light - vec3(vec2<f32>(id.xy) + 0.5, h[4]));
+        }
+        let halfway = normalize(light + vec3(0.0, 0.0, 1.0));
+        let diffuse = params.diffuse * max(dot(normal, light), 0.0);
+        let specular = params.specular * pow(max(dot(normal, halfway), 0.0), params.specular_exponent);
+        let color = textureLoad(source, coords, 0);
+        let lit = params.color.rgb * (color.rgb * diffuse + specular);
+        textureStore(output, coords, vec4(clamp(lit, vec3(0.0), vec3(1.0)), color.a));
+    }
+"#;
+
+/// Combines the images of the nodes of filter graphs.
+const COMPOSE_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        // 0 to move `a` by `values`, 1 to compose `a` over `b`, 2 to take the alpha of `a`,
+        // 3 to keep the pixels of `a` brighter than `values.x`, 4 to add `b` times `values.x`
+        // to `a`, or 5 to take the inverse of the alpha of `a`.
+        mode: u32,
+        _padding: u32,
+        values: vec2<f32>,
+        _padding2: vec2<u32>,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var a: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var b: texture_2d<f32>;
+
+    @group(0) @binding(3)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    // Pixels outside the image are transparent.
+    fn load_premultiplied(coords: vec2<i32>) -> vec4<f32> {
+        let size = vec2(i32(params.width), i32(params.height));
+        if any(coords < vec2(0)) || any(coords >= size) {
+            return vec4(0.0);
+        }
+        let color = textureLoad(a, coords, 0);
+        return vec4(color.rgb * color.a, color.a);
+    }
+
+    fn sample_bilinear(p: vec2<f32>) -> vec4<f32> {
+        let q = p - 0.5;
+        let base = floor(q);
+        let f = q - base;
+        let i = vec2<i32>(base);
+        let top = mix(load_premultiplied(i), load_premultiplied(i + vec2(1, 0)), f.x);
+        let bottom = mix(load_premultiplied(i + vec2(0, 1)), load_premultiplied(i + vec2(1, 1)), f.x);
+        return mix(top, bottom, f.y);
+    }
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let coords = vec2<i32>(id.xy);
+        var result = vec4(0.0);
+        switch params.mode {
+            case 0u: {
+                result = sample_bilinear(vec2<f32>(id.xy) + 0.5 - params.values);
+            }
+            case 1u: {
+                let under = textureLoad(b, coords, 0);
+                result = load_premultiplied(coords);
+                result += vec4(under.rgb * under.a, under.a) * (1.0 - result.a);
+            }
+            case 3u: {
+                let color = load_premultiplied(coords);
+                if max(color.r, max(color.g, color.b)) > params.values.x {
+                    result = color;
+                }
+            }
+            case 4u: {
+                let glow = textureLoad(b, coords, 0);
+                result = load_premultiplied(coords) + vec4(glow.rgb * glow.a, glow.a) * params.values.x;
+                // Keep the color premultiplied, so it can be unpremultiplied below.
+                result.a = min(result.a, 1.0);
+                result = vec4(min(result.rgb, vec3(result.a)), result.a);
+            }
+            case 5u: {
+                result = vec4(0.0, 0.0, 0.0, 1.0 - textureLoad(a, coords, 0).a);
+            }
+            default: {
+                result = vec4(0.0, 0.0, 0.0, textureLoad(a, coords, 0).a);
+            }
+        }
+        var rgb = vec3(0.0);
+        if result.a > 0.0 {
+            rgb = result.rgb / result.a;
+        }
+        textureStore(output, coords, vec4(rgb, result.a));
+    }
+"#;
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct BlurParams {
+    width: u32,
+    height: u32,
+    step_x: i32,
+    step_y: i32,
+    radius: i32,
+    std_dev: f32,
+    _padding: [u32; 2],
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct LineBlurParams {
+    width: u32,
+    height: u32,
+    mode: u32,
+    _padding: u32,
+    vector: [f32; 2],
+    amount: f32,
+    _padding2: u32,
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct ShadowParams {
+    width: u32,
+    height: u32,
+    offset_x: i32,
+    offset_y: i32,
+    color: [f32; 4],
+    inner: u32,
+    _padding: [u32; 3],
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct MorphologyParams {
+    width: u32,
+    height: u32,
+    step_x: i32,
+    step_y: i32,
+    radius: i32,
+    dilate: u32,
+    _padding: [u32; 2],
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct NoiseParams {
+    width: u32,
+    height: u32,
+    mode: u32,
+    octaves: u32,
+    to_layer_matrix: [f32; 4],
+    to_layer_translation: [f32; 2],
+    base_frequency: [f32; 2],
+    from_layer_matrix: [f32; 4],
+    scale: f32,
+    seed: u32,
+    fractal: u32,
+    _padding: u32,
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct LightingParams {
+    width: u32,
+    height: u32,
+    point: u32,
+    specular_exponent: f32,
+    light: [f32; 4],
+    color: [f32; 4],
+    surface_scale: f32,
+    diffuse: f32,
+    specular: f32,
+    _padding: u32,
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct ComposeParams {
+    width: u32,
+    height: u32,
+    mode: u32,
+    _padding: u32,
+    values: [f32; 2],
+    _padding2: [u32; 2],
+}
+
+/// The images used to apply a filter graph, as scheduled by [`FilterShaders::record_graph`].
+pub(crate) struct FilterSchedule {
+    /// The images, the first of which must hold the content of the layer.
+    pub(crate) images: Vec<ImageProxy>,
+    /// The index of the image holding the result.
+    pub(crate) output: usize,
+}
+
+/// Allocates the images used by a filter graph, reusing those which are no longer read.
+struct ImagePool {
+    images: Vec<ImageProxy>,
+    free: Vec<usize>,
+}
+
+impl ImagePool {
+    /// Returns the index of an unused image, or `None` if too many are in use.
+    fn allocate(&mut self) -> Option<usize> {
+        if let Some(index) = self.free.pop() {
+            return Some(index);
+        }
+        if self.images.len() == MAX_FILTER_IMAGES {
+            return None;
+        }
+        let [width, height] = [self.images[0].width, self.images[0].height];
+        self.images
+            .push(ImageProxy::new(width, height, ImageFormat::Rgba8));
+        Some(self.images.len() - 1)
+    }
+
+    fn release(&mut self, index: usize) {
+        if !self.free.contains(&index) {
+            self.free.push(index);
+        }
+    }
+}
+
+/// The shaders used to apply filters.
+#[derive(Clone, Copy)]
+pub(crate) struct FilterShaders {
+    blur: ShaderId,
+    shadow: ShaderId,
+    line_blur: ShaderId,
+    noise: ShaderId,
+    morphology: ShaderId,
+    lighting: ShaderId,
+    compose: ShaderId,
+}
+
+impl FilterShaders {
+    /// Adds the filter shaders to `engine`.
+    pub(crate) fn new(device: &Device, engine: &mut WgpuEngine) -> Self {
+        let image = BindType::Image(ImageFormat::Rgba8);
+        let image_read = BindType::ImageRead(ImageFormat::Rgba8);
+        Self {
+            blur: engine.add_compute_shader(
+                device,
+                "catalina.filter_blur",
+                Cow::Borrowed(BLUR_SHADER),
+                &[BindType::Uniform, image_read, image],
+                CpuShaderType::Missing,
+            ),
+            shadow: engine.add_compute_shader(
+                device,
+                "catalina.filter_shadow",
+                Cow::Borrowed(SHADOW_SHADER),
+                &[BindType::Uniform, image_read, image_read, image],
+                CpuShaderType::Missing,
+            ),
+            line_blur: engine.add_compute_shader(
+                device,
+                "catalina.filter_line_blur",
+                Cow::Borrowed(LINE_BLUR_SHADER),
+                &[BindType::Uniform, image_read, image],
+                CpuShaderType::Missing,
+            ),
+            noise: engine.add_compute_shader(
+                device,
+                "catalina.filter_noise",
+                Cow::Borrowed(NOISE_SHADER),
+                &[BindType::Uniform, image_read, image],
+                CpuShaderType::Missing,
+            ),
+            morphology: engine.add_compute_shader(
+                device,
+                "catalina.filter_morphology",
+                Cow::Borrowed(MORPHOLOGY_SHADER),
+                &[BindType::Uniform, image_read, image],
+                CpuShaderType::Missing,
+            ),
+            lighting: engine.add_compute_shader(
+                device,
+                "catalina.filter_lighting",
+                Cow::Borrowed(LIGHTING_SHADER),
+                &[BindType::Uniform, image_read, image_read, image],
+                CpuShaderType::Missing,
+            ),
+            compose: engine.add_compute_shader(
+                device,
+                "catalina.filter_compose",
+                Cow::Borrowed(COMPOSE_SHADER),
+                &[BindType::Uniform, image_read, image_read, image],
+                CpuShaderType::Missing,
+            ),
+        }
+    }
+
+    /// Records the passes which apply `graph` to the content of a layer of the given size,
+    /// allocating images as they are needed and reusing them once no later node reads them.
+    /// `to_layer` maps pixels to the coordinate system of the layer, in which noise is
+    /// generated.
+    ///
+    /// Returns `None` if the graph needs more than [`MAX_FILTER_IMAGES`] images at once.
+    pub(crate) fn record_graph(
+        &self,
+        recording: &mut Recording,
+        graph: &FilterGraph,
+        to_layer: Affine,
+        width: u32,
+        height: u32,
+    ) -> Option<FilterSchedule> {
+        let mut pool = ImagePool {
+            images: vec![ImageProxy::new(width, height, ImageFormat::Rgba8)],
+            free: vec![],
+        };
+        // The index of the last node which reads each input, so that its image can be reused
+        // afterwards. The result of the last node is never released.
+        let mut last_reads: HashMap<FilterInput, usize> = HashMap::new();
+        for (i, node) in graph.nodes.iter().enumerate() {
+            for input in node.inputs() {
+                last_reads.insert(*input, i);
+            }
+        }
+        let mut results: Vec<usize> = Vec::with_capacity(graph.nodes.len());
+        let mut source_alpha = None;
+        for (i, node) in graph.nodes.iter().enumerate() {
+            let mut inputs = Vec::with_capacity(node.inputs().len());
+            for input in node.inputs() {
+                inputs.push(match input {
+                    FilterInput::Source => 0,
+                    FilterInput::SourceAlpha => match source_alpha {
+                        Some(index) => index,
+                        None => {
+                            let index = pool.allocate()?;
+                            let [source, output] = [0, index].map(|i| pool.images[i]);
+                            self.record_compose(recording, 2, [0.0; 2], source, source, output);
+                            source_alpha = Some(index);
+                            index
+                        }
+                    },
+                    FilterInput::Node(id) => results[id.0],
+                });
+            }
+            let result = match node {
+                FilterNode::Filter { filter, .. } => {
+                    let scratch = [pool.allocate()?, pool.allocate()?];
+                    let indices = [inputs[0], scratch[0], scratch[1]];
+                    let output =
+                        self.record(recording, filter, to_layer, indices.map(|i| pool.images[i]));
+                    for index in scratch {
+                        if index != indices[output] {
+                            pool.release(index);
+                        }
+                    }
+                    indices[output]
+                }
+                FilterNode::Offset { offset, .. } => {
+                    let index = pool.allocate()?;
+                    let [input, output] = [inputs[0], index].map(|i| pool.images[i]);
+                    let offset = [offset.x as f32, offset.y as f32];
+                    self.record_compose(recording, 0, offset, input, input, output);
+                    index
+                }
+                FilterNode::Merge { .. } => {
+                    // Compose each input over the result so far, starting with a copy of the
+                    // first.
+                    let mut index = pool.allocate()?;
+                    let [first, output] = [inputs[0], index].map(|i| pool.images[i]);
+                    self.record_compose(recording, 0, [0.0; 2], first, first, output);
+                    for &input in &inputs[1..] {
+                        let next = pool.allocate()?;
+                        let [over, under, output] = [input, index, next].map(|i| pool.images[i]);
+                        self.record_compose(recording, 1, [0.0; 2], over, under, output);
+                        pool.release(index);
+                        index = next;
+                    }
+                    index
+                }
+            };
+            results.push(result);
+            for (input, &index) in node.inputs().iter().zip(&inputs) {
+                if last_reads[input] == i && index != result {
+                    pool.release(index);
+                }
+            }
+            let unread = !last_reads.contains_key(&FilterInput::Node(FilterNodeId(i)));
+            if unread && i + 1 < graph.nodes.len() {
+                pool.release(result);
+            }
+        }
+        Some(FilterSchedule {
+            images: pool.images,
+            output: results.last().copied().unwrap_or(0),
+        })
+    }
+
+    /// Records a pass which combines images according to `mode` and `values`, as described
+    /// in [`COMPOSE_SHADER`].
+    fn record_compose(
+        &self,
+        recording: &mut Recording,
+        mode: u32,
+        values: [f32; 2],
+        a: ImageProxy,
+        b: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let params = ComposeParams {
+            width: output.width,
+            height: output.height,
+            mode,
+            _padding: 0,
+            values,
+            _padding2: [0; 2],
+        };
+        let config = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.filter_compose_params",
+            bytemuck::bytes_of(&params),
+        ));
+        recording.dispatch(
+            self.compose,
+            workgroups(output.width, output.height),
+            [
+                config,
+                ResourceProxy::Image(a),
+                ResourceProxy::Image(b),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+    }
+
+    /// Records the passes which apply `filter` to the first of `images`, using the others as
+    /// scratch space. `to_layer` maps the pixels of the images to the coordinate system of the
+    /// layer, in which noise is generated.
+    ///
+    /// Returns the index of the image holding the result.
+    fn record(
+        &self,
+        recording: &mut Recording,
+        filter: &Filter,
+        to_layer: Affine,
+        images: [ImageProxy; 3],
+    ) -> usize {
+        match filter {
+            Filter::Blur { std_dev } => {
+                self.record_blur(recording, *std_dev, images[0], images[1], images[2]);
+                2
+            }
+            Filter::DropShadow {
+                offset,
+                std_dev,
+                color,
+            } => {
+                self.record_blur(recording, *std_dev, images[0], images[1], images[2]);
+                let [source, output, blurred] = images;
+                self.record_shadow(recording, false, *offset, *color, source, blurred, output);
+                1
+            }
+            Filter::InnerShadow {
+                offset,
+                std_dev,
+                color,
+            } => {
+                let [source, blurred, output] = images;
+                self.record_compose(recording, 5, [0.0; 2], source, source, blurred);
+                self.record_blur(recording, *std_dev, blurred, output, blurred);
+                self.record_shadow(recording, true, *offset, *color, source, blurred, output);
+                2
+            }
+            Filter::DirectionalBlur { offset } => {
+                let vector = [offset.x as f32, offset.y as f32];
+                self.record_line_blur(recording, 0, vector, 0.0, images[0], images[1]);
+                1
+            }
+            Filter::RadialBlur { center, amount } => {
+                let vector = [center.x as f32, center.y as f32];
+                self.record_line_blur(recording, 1, vector, *amount as f32, images[0], images[1]);
+                1
+            }
+            Filter::Dilate { radius } => {
+                self.record_morphology(recording, true, *radius, images[0], images[1], images[2]);
+                2
+            }
+            Filter::Erode { radius } => {
+                self.record_morphology(recording, false, *radius, images[0], images[1], images[2]);
+                2
+            }
+            Filter::Turbulence(noise) => {
+                self.record_noise(recording, noise, None, to_layer, images[0], images[1]);
+                1
+            }
+            Filter::DisplacementMap { scale, map } => {
+                let scale = Some(*scale as f32);
+                self.record_noise(recording, map, scale, to_layer, images[0], images[1]);
+                1
+            }
+            Filter::Lighting(lighting) => {
+                let [source, output, heights] = images;
+                self.record_blur(recording, lighting.softness, source, output, heights);
+                self.record_lighting(recording, lighting, source, heights, output);
+                1
+            }
+            Filter::Glow {
+                std_dev,
+                threshold,
+                intensity,
+            } => {
+                let [source, scratch, glow] = images;
+                self.record_compose(recording, 3, [*threshold, 0.0], source, source, glow);
+                self.record_blur(recording, *std_dev, glow, scratch, glow);
+                self.record_compose(recording, 4, [*intensity, 0.0], source, glow, scratch);
+                1
+            }
+        }
+    }
+
+    /// Records composing `source` with `blurred`, offset and filled with `color`, as a drop
+    /// shadow or if `inner` is set, an inner shadow.
+    fn record_shadow(
+        &self,
+        recording: &mut Recording,
+        inner: bool,
+        offset: Vec2,
+        color: Color,
+        source: ImageProxy,
+        blurred: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let params = ShadowParams {
+            width: source.width,
+            height: source.height,
+            offset_x: offset.x.round() as i32,
+            offset_y: offset.y.round() as i32,
+            color: color.premultiply().components,
+            inner: inner as u32,
+            _padding: [0; 3],
+        };
+        let config = ResourceProxy::Buffer(
+            recording.upload_uniform("catalina.filter_shadow_params", bytemuck::bytes_of(&params)),
+        );
+        recording.dispatch(
+            self.shadow,
+            workgroups(source.width, source.height),
+            [
+                config,
+                ResourceProxy::Image(source),
+                ResourceProxy::Image(blurred),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+    }
+
+    /// Records shading `source` with `lighting`, using the alpha of `heights` as the height of
+    /// the surface.
+    fn record_lighting(
+        &self,
+        recording: &mut Recording,
+        lighting: &Lighting,
+        source: ImageProxy,
+        heights: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let (point, light) = match lighting.light {
+            LightSource::Distant { azimuth, elevation } => {
+                let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+                let (sin_elevation, cos_elevation) = elevation.sin_cos();
+                let direction = [
+                    cos_azimuth * cos_elevation,
+                    sin_azimuth * cos_elevation,
+                    sin_elevation,
+                ];
+                (0, direction)
+            }
+            LightSource::Point { position, z } => (1, [position.x, position.y, z]),
+        };
+        let params = LightingParams {
+            width: source.width,
+            height: source.height,
+            point,
+            specular_exponent: lighting.specular_exponent as f32,
+            light: [light[0] as f32, light[1] as f32, light[2] as f32, 0.0],
+            color: lighting.color.components,
+            surface_scale: lighting.surface_scale as f32,
+            diffuse: lighting.diffuse as f32,
+            specular: lighting.specular as f32,
+            _padding: 0,
+        };
+        let config = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.filter_lighting_params",
+            bytemuck::bytes_of(&params),
+        ));
+        recording.dispatch(
+            self.lighting,
+            workgroups(source.width, source.height),
+            [
+                config,
+                ResourceProxy::Image(source),
+                ResourceProxy::Image(heights),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+    }
+
+    /// Records a horizontal then vertical pass of dilation or erosion of `source` into
+    /// `output`.
+    fn record_morphology(
+        &self,
+        recording: &mut Recording,
+        dilate: bool,
+        radius: Vec2,
+        source: ImageProxy,
+        scratch: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let passes = [
+            ((1, 0), radius.x, source, scratch),
+            ((0, 1), radius.y, scratch, output),
+        ];
+        for (step, radius, from, to) in passes {
+            let params = MorphologyParams {
+                width: from.width,
+                height: from.height,
+                step_x: step.0,
+                step_y: step.1,
+                radius: radius.abs().round() as i32,
+                dilate: dilate as u32,
+                _padding: [0; 2],
+            };
+            let config = ResourceProxy::Buffer(recording.upload_uniform(
+                "catalina.filter_morphology_params",
+                bytemuck::bytes_of(&params),
+            ));
+            recording.dispatch(
+                self.morphology,
+                workgroups(from.width, from.height),
+                [config, ResourceProxy::Image(from), ResourceProxy::Image(to)],
+            );
+            recording.free_resource(config);
+        }
+    }
+
+    /// Records generating `noise` into `output`, or if `displacement_scale` is given,
+    /// displacing `source` by the noise.
+    fn record_noise(
+        &self,
+        recording: &mut Recording,
+        noise: &Turbulence,
+        displacement_scale: Option<f32>,
+        to_layer: Affine,
+        source: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let [a, b, c, d, e, f] = to_layer.as_coeffs().map(|x| x as f32);
+        let [ia, ib, ic, id, _, _] = to_layer.inverse().as_coeffs().map(|x| x as f32);
+        let params = NoiseParams {
+            width: source.width,
+            height: source.height,
+            mode: displacement_scale.is_some() as u32,
+            octaves: noise.octaves,
+            to_layer_matrix: [a, b, c, d],
+            to_layer_translation: [e, f],
+            base_frequency: [noise.base_frequency.x as f32, noise.base_frequency.y as f32],
+            from_layer_matrix: [ia, ib, ic, id],
+            scale: displacement_scale.unwrap_or(0.0),
+            seed: noise.seed,
+            fractal: noise.fractal as u32,
+            _padding: 0,
+        };
+        let config = ResourceProxy::Buffer(
+            recording.upload_uniform("catalina.filter_noise_params", bytemuck::bytes_of(&params)),
+        );
+        recording.dispatch(
+            self.noise,
+            workgroups(source.width, source.height),
+            [
+                config,
+                ResourceProxy::Image(source),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+    }
+
+    /// Records a blur of `source` along lines through each pixel into `output`.
+    fn record_line_blur(
+        &self,
+        recording: &mut Recording,
+        mode: u32,
+        vector: [f32; 2],
+        amount: f32,
+        source: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let params = LineBlurParams {
+            width: source.width,
+            height: source.height,
+            mode,
+            _padding: 0,
+            vector,
+            amount,
+            _padding2: 0,
+        };
+        let config = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.filter_line_blur_params",
+            bytemuck::bytes_of(&params),
+        ));
+        recording.dispatch(
+            self.line_blur,
+            workgroups(source.width, source.height),
+            [
+                config,
+                ResourceProxy::Image(source),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+    }
+
+    /// Records a horizontal then vertical blur of `source` into `output`.
+    fn record_blur(
+        &self,
+        recording: &mut Recording,
+        std_dev: f64,
+        source: ImageProxy,
+        scratch: ImageProxy,
+        output: ImageProxy,
+    ) {
+        // A tiny standard deviation leaves the image unchanged, without dividing by zero.
+        let std_dev = std_dev.max(1e-3);
+        for (step, from, to) in [((1, 0), source, scratch), ((0, 1), scratch, output)] {
+            let params = BlurParams {
+                width: from.width,
+                height: from.height,
+                step_x: step.0,
+                step_y: step.1,
+                radius: Filter::gaussian_radius(std_dev) as i32,
+                std_dev: std_dev as f32,
+                _padding: [0; 2],
+            };
+            let config = ResourceProxy::Buffer(
+                recording
+                    .upload_uniform("catalina.filter_blur_params", bytemuck::bytes_of(&params)),
+            );
+            recording.dispatch(
+                self.blur,
+                workgroups(from.width, from.height),
+                [config, ResourceProxy::Image(from), ResourceProxy::Image(to)],
+            );
+            recording.free_resource(config);
+        }
+    }
+}
+
+fn workgroups(width: u32, height: u32) -> (u32, u32, u32) {
+    (
+        width.div_ceil(WORKGROUP_SIZE),
+        height.div_ceil(WORKGROUP_SIZE),
+        1,
+    )
+}
@@ -0,0 +1,200 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Adaptive render quality for interactive zooming and panning.
+
+use std::time::Duration;
+
+use crate::AaConfig;
+
+/// The quality at which to render a frame, as chosen by a [`QualityGovernor`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quality {
+    /// The fraction of the full resolution to render at, in `0..=1`.
+    pub resolution_scale: f32,
+    /// The antialiasing method to use instead of the one requested, if any.
+    pub antialiasing: Option<AaConfig>,
+}
+
+impl Quality {
+    /// Full resolution with the requested antialiasing.
+    pub const FULL: Self = Self {
+        resolution_scale: 1.0,
+        antialiasing: None,
+    };
+
+    /// Returns `true` if this is full quality.
+    pub fn is_full(&self) -> bool {
+        self.resolution_scale >= 1.0 && self.antialiasing.is_none()
+    }
+}
+
+/// Chooses the render quality of each frame, reducing it while the user is interacting and
+/// refining to full quality once input goes idle.
+///
+/// While interacting, the resolution scale adapts to the measured frame times, so that weak
+/// GPUs stay at the target frame rate and fast ones stay close to full resolution.
+///
+/// Times are given as [`Duration`]s since any fixed point, such as the start of the
+/// application, so that the governor works with whichever clock is available on the platform.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use catalina::QualityGovernor;
+/// let mut governor = QualityGovernor::new();
+/// governor.notify_input(Duration::from_millis(0));
+/// let quality = governor.quality(Duration::from_millis(5));
+/// assert!(!quality.is_full());
+/// // Once input has been idle for a while, the next frame is at full quality.
+/// assert!(governor.quality(Duration::from_secs(1)).is_full());
+/// ```
+#[derive(Clone, Debug)]
+pub struct QualityGovernor {
+    /// The frame time to aim for while interacting.
+    pub target_frame_time: Duration,
+    /// How long after the last input to render at full quality.
+    pub idle_delay: Duration,
+    /// The lowest resolution scale used while interacting.
+    pub min_resolution_scale: f32,
+    /// The antialiasing method used while interacting, if different from the requested one.
+    pub interactive_antialiasing: Option<AaConfig>,
+    /// The resolution scale currently used while interacting.
+    interactive_scale: f32,
+    last_input: Option<Duration>,
+    /// Whether the last frame was rendered at reduced quality.
+    last_frame_reduced: bool,
+}
+
+impl Default for QualityGovernor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QualityGovernor {
+    /// The factor by which the resolution scale changes after each slow or fast frame.
+    const STEP: f32 = 0.85;
+
+    /// Creates a governor targeting 60 frames per second, which starts interactive frames at
+    /// three quarters resolution with area antialiasing.
+    pub fn new() -> Self {
+        Self {
+            target_frame_time: Duration::from_micros(16_667),
+            idle_delay: Duration::from_millis(150),
+            min_resolution_scale: 0.25,
+            interactive_antialiasing: Some(AaConfig::Area),
+            interactive_scale: 0.75,
+            last_input: None,
+            last_frame_reduced: false,
+        }
+    }
+
+    /// Records that the user zoomed, panned or otherwise interacted at `time`.
+    pub fn notify_input(&mut self, time: Duration) {
+        self.last_input = Some(time);
+    }
+
+    /// Returns `true` if the user has interacted within the idle delay of `time`.
+    pub fn is_interacting(&self, time: Duration) -> bool {
+        self.last_input
+            .is_some_and(|last| time.saturating_sub(last) < self.idle_delay)
+    }
+
+    /// Returns the quality at which to render a frame at `time`.
+    pub fn quality(&mut self, time: Duration) -> Quality {
+        let quality = if self.is_interacting(time) {
+            Quality {
+                resolution_scale: self.interactive_scale,
+                antialiasing: self.interactive_antialiasing,
+            }
+        } else {
+            Quality::FULL
+        };
+        self.last_frame_reduced = !quality.is_full();
+        quality
+    }
+
+    /// Returns `true` if the last frame was rendered at reduced quality and input has since
+    /// gone idle, so another frame should be requested to refine it.
+    pub fn needs_refinement(&self, time: Duration) -> bool {
+        self.last_frame_reduced && !self.is_interacting(time)
+    }
+
+    /// Records how long the last frame took to render, which adapts the resolution scale used
+    /// while interacting.
+    pub fn record_frame_time(&mut self, frame_time: Duration) {
+        if !self.last_frame_reduced {
+            // Full quality frames are expected to be slow, and say nothing about whether the
+            // interactive scale is right.
+            return;
+        }
+        if frame_time > self.target_frame_time {
+            self.interactive_scale =
+                (self.interactive_scale * Self::STEP).max(self.min_resolution_scale.min(1.0));
+        } else if frame_time.as_secs_f32() < self.target_frame_time.as_secs_f32() * 0.6 {
+            self.interactive_scale = (self.interactive_scale / Self::STEP).min(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Quality, QualityGovernor};
+    use crate::AaConfig;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn quality_is_reduced_while_interacting() {
+        let mut governor = QualityGovernor::new();
+        assert_eq!(governor.quality(ms(0)), Quality::FULL);
+        assert!(!governor.needs_refinement(ms(0)));
+        governor.notify_input(ms(100));
+        let quality = governor.quality(ms(110));
+        assert_eq!(quality.resolution_scale, 0.75);
+        assert_eq!(quality.antialiasing, Some(AaConfig::Area));
+        // Still interacting, so there's nothing to refine yet.
+        assert!(!governor.needs_refinement(ms(200)));
+        // Once input is idle, the reduced frame is refined by one full quality frame.
+        assert!(governor.needs_refinement(ms(250)));
+        assert!(governor.quality(ms(250)).is_full());
+        assert!(!governor.needs_refinement(ms(300)));
+    }
+
+    #[test]
+    fn resolution_adapts_to_frame_times() {
+        let mut governor = QualityGovernor::new();
+        governor.notify_input(ms(0));
+        let mut scale = governor.quality(ms(0)).resolution_scale;
+        // Slow frames lower the resolution down to the minimum.
+        for _ in 0..20 {
+            governor.record_frame_time(ms(40));
+            let next = governor.quality(ms(0)).resolution_scale;
+            assert!(next <= scale);
+            scale = next;
+        }
+        assert_eq!(scale, governor.min_resolution_scale);
+        // Fast frames raise it back up to full resolution.
+        for _ in 0..20 {
+            governor.record_frame_time(ms(1));
+            scale = governor.quality(ms(0)).resolution_scale;
+        }
+        assert_eq!(scale, 1.0);
+        // Frame times around the target keep the scale.
+        governor.record_frame_time(ms(14));
+        assert_eq!(governor.quality(ms(0)).resolution_scale, 1.0);
+    }
+
+    #[test]
+    fn full_quality_frames_are_not_measured() {
+        let mut governor = QualityGovernor::new();
+        assert!(governor.quality(ms(0)).is_full());
+        governor.record_frame_time(ms(100));
+        governor.notify_input(ms(1000));
+        assert_eq!(governor.quality(ms(1000)).resolution_scale, 0.75);
+    }
+}
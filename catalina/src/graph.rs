@@ -0,0 +1,184 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A description of the passes in a [`Recording`], for scheduling by frame graphs.
+//!
+//! A [`RenderGraph`] can be created using [`WgpuEngine::describe`](crate::wgpu_engine::WgpuEngine::describe)
+//! or [`Renderer::describe_passes`](crate::Renderer::describe_passes).
+//! To interleave custom passes with Vello's, use [`Render`](crate::low_level::Render) to record
+//! the passes up to and including coarse rasterization and fine rasterization separately,
+//! and submit your own work between the two recordings.
+
+use crate::recording::{
+    BindType, BufferProxy, Command, ImageProxy, Recording, ResourceId, ResourceProxy,
+};
+use crate::ShaderId;
+
+/// The sequence of passes in a [`Recording`], with the resources each pass uses.
+#[derive(Default)]
+pub struct RenderGraph {
+    /// The passes, in the order they are executed.
+    pub passes: Vec<PassDesc>,
+}
+
+/// A single compute or render pass.
+pub struct PassDesc {
+    /// The shader run by this pass.
+    pub shader: ShaderId,
+    /// The label of the shader, such as `"catalina.coarse"`.
+    pub label: &'static str,
+    /// How the pass is dispatched.
+    pub dispatch: PassDispatch,
+    /// The resources bound to the pass, in binding order.
+    pub resources: Vec<ResourceUse>,
+}
+
+/// How a [`PassDesc`] is dispatched.
+pub enum PassDispatch {
+    /// A compute dispatch with the given number of workgroups.
+    Workgroups(u32, u32, u32),
+    /// A compute dispatch whose workgroup counts are read from a buffer on the GPU.
+    Indirect {
+        /// The buffer containing the workgroup counts.
+        buffer: BufferProxy,
+        /// The offset of the workgroup counts in `buffer`, in bytes.
+        offset: u64,
+    },
+    /// A draw call into an image.
+    Draw {
+        /// The number of vertices drawn.
+        vertex_count: u32,
+        /// The number of instances drawn.
+        instance_count: u32,
+        /// The image being drawn into.
+        target: ImageProxy,
+    },
+}
+
+/// A resource bound to a pass.
+#[derive(Clone, Copy)]
+pub struct ResourceUse {
+    /// The resource.
+    pub resource: ResourceProxy,
+    /// How the resource is bound.
+    pub bind_type: BindType,
+}
+
+impl ResourceUse {
+    /// Whether the pass can write to the resource.
+    pub fn is_write(&self) -> bool {
+        matches!(self.bind_type, BindType::Buffer | BindType::Image(_))
+    }
+
+    /// The size of the resource in bytes.
+    pub fn size_in_bytes(&self) -> u64 {
+        match self.resource {
+            ResourceProxy::Buffer(proxy) => proxy.size,
+            ResourceProxy::BufferRange { size, .. } => size,
+            ResourceProxy::Image(proxy) => u64::from(proxy.width) * u64::from(proxy.height) * 4,
+        }
+    }
+}
+
+impl RenderGraph {
+    /// Builds the description of `recording`.
+    ///
+    /// `shader_info` returns the label and binding types of a shader.
+    #[cfg_attr(
+        not(feature = "wgpu"),
+        expect(
+            dead_code,
+            reason = "only recordings run by the wgpu engine are described"
+        )
+    )]
+    pub(crate) fn new<'a>(
+        recording: &Recording,
+        shader_info: impl Fn(ShaderId) -> (&'static str, &'a [BindType]),
+    ) -> Self {
+        let mut passes = Vec::new();
+        let mut add = |shader: ShaderId, dispatch, resources: &[ResourceProxy]| {
+            let (label, bind_types) = shader_info(shader);
+            let resources = resources
+                .iter()
+                .zip(bind_types)
+                .map(|(resource, bind_type)| ResourceUse {
+                    resource: *resource,
+                    bind_type: *bind_type,
+                })
+                .collect();
+            passes.push(PassDesc {
+                shader,
+                label,
+                dispatch,
+                resources,
+            });
+        };
+        for command in &recording.commands {
+            match command {
+                Command::Dispatch(shader, (x, y, z), resources) => {
+                    add(*shader, PassDispatch::Workgroups(*x, *y, *z), resources);
+                }
+                Command::DispatchIndirect(shader, buffer, offset, resources) => add(
+                    *shader,
+                    PassDispatch::Indirect {
+                        buffer: *buffer,
+                        offset: *offset,
+                    },
+                    resources,
+                ),
+                Command::Draw(draw) => add(
+                    draw.shader_id,
+                    PassDispatch::Draw {
+                        vertex_count: draw.vertex_count,
+                        instance_count: draw.instance_count,
+                        target: draw.target,
+                    },
+                    &draw.resources,
+                ),
+                _ => {}
+            }
+        }
+        Self { passes }
+    }
+
+    /// Returns the index of each pass which `pass` depends on.
+    ///
+    /// A pass depends on an earlier pass if it uses a resource which the earlier pass writes,
+    /// or writes a resource which the earlier pass uses.
+    pub fn dependencies(&self, pass: usize) -> Vec<usize> {
+        let Some(desc) = self.passes.get(pass) else {
+            return Vec::new();
+        };
+        (0..pass)
+            .filter(|&earlier| {
+                self.passes[earlier].uses().any(|(a, a_write)| {
+                    desc.uses()
+                        .any(|(b, b_write)| a == b && (a_write || b_write))
+                })
+            })
+            .collect()
+    }
+}
+
+impl PassDesc {
+    /// The ids of the resources used by this pass, and whether each is written.
+    fn uses(&self) -> impl Iterator<Item = (ResourceId, bool)> + '_ {
+        let extra = match &self.dispatch {
+            PassDispatch::Workgroups(..) => None,
+            PassDispatch::Indirect { buffer, .. } => Some((buffer.id, false)),
+            PassDispatch::Draw { target, .. } => Some((target.id, true)),
+        };
+        self.resources
+            .iter()
+            .map(|resource| {
+                let id = match resource.resource {
+                    ResourceProxy::Buffer(proxy) | ResourceProxy::BufferRange { proxy, .. } => {
+                        proxy.id
+                    }
+                    ResourceProxy::Image(proxy) => proxy.id,
+                };
+                (id, resource.is_write())
+            })
+            .chain(extra)
+    }
+}
@@ -0,0 +1,374 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Packing of many small offscreen renders into shared texture array pages.
+
+use peniko::kurbo::{Affine, Rect};
+use peniko::{color::palette, BlendMode, Compose, Mix};
+use wgpu::{Device, Queue, Texture, TextureView};
+
+use crate::{AaConfig, Error, RenderParams, Renderer, Result, Scene};
+
+/// The transparent border kept around each entry, so that sampling an entry bilinearly at its
+/// edges doesn't pick up its neighbors.
+const GUTTER: u32 = 1;
+
+/// A texture array into which many small scenes, such as cached widgets, are rendered and
+/// packed, to be drawn by a compositor with the coordinates of each entry.
+///
+/// Creating a texture for each cached widget makes caching at the scale of widgets costly, as
+/// every texture is a separate allocation and binding. An atlas instead creates its pages up
+/// front, as the layers of one [`Texture`], and packs each render into free space on a page,
+/// reusing the space of entries which have been [freed](Self::free).
+///
+/// Each [`AtlasEntry`] gives the page and the texture coordinates of its content, and is kept
+/// apart from its neighbors by a transparent border so that it can be sampled with linear
+/// filtering.
+pub struct LayerAtlas {
+    texture: Texture,
+    page_size: u32,
+    pages: Vec<Page>,
+    /// The texture each entry is rendered into before being copied to its page.
+    scratch: Option<(Texture, TextureView)>,
+    scratch_scene: Scene,
+}
+
+/// The space for a scene rendered into a [`LayerAtlas`].
+///
+/// This is only created by the atlas, and is returned to it with [`LayerAtlas::free`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct AtlasEntry {
+    page: u32,
+    /// The top left corner of the space, including the gutter.
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    page_size: u32,
+}
+
+impl AtlasEntry {
+    /// The index of the layer of [`LayerAtlas::texture`] the entry is on.
+    pub fn page(&self) -> u32 {
+        self.page
+    }
+
+    /// The width of the content, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the content, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The position of the top left pixel of the content on its page.
+    pub fn origin(&self) -> (u32, u32) {
+        (self.x + GUTTER, self.y + GUTTER)
+    }
+
+    /// The bounds of the content in the normalized texture coordinates of its page, where
+    /// the page spans 0 to 1 in each direction.
+    pub fn uv_rect(&self) -> Rect {
+        let (x, y) = self.origin();
+        let size = f64::from(self.page_size);
+        Rect::new(
+            f64::from(x) / size,
+            f64::from(y) / size,
+            f64::from(x + self.width) / size,
+            f64::from(y + self.height) / size,
+        )
+    }
+}
+
+impl LayerAtlas {
+    /// Creates an atlas of `pages` square pages which are `page_size` pixels wide, limited to
+    /// the largest texture array the device supports.
+    pub fn new(device: &Device, page_size: u32, pages: u32) -> Self {
+        let limits = device.limits();
+        let page_size = page_size.clamp(1, limits.max_texture_dimension_2d);
+        let page_count = pages.clamp(1, limits.max_texture_array_layers);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("catalina.layer_atlas"),
+            size: wgpu::Extent3d {
+                width: page_size,
+                height: page_size,
+                depth_or_array_layers: page_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            view_formats: &[],
+        });
+        Self {
+            texture,
+            page_size,
+            pages: (0..page_count).map(|_| Page::default()).collect(),
+            scratch: None,
+            scratch_scene: Scene::new(),
+        }
+    }
+
+    /// The texture array holding the pages, in the
+    /// [`Rgba8Unorm`](wgpu::TextureFormat::Rgba8Unorm) format.
+    ///
+    /// Each page is a layer of the array, which can be bound with a view of dimension
+    /// [`D2Array`](wgpu::TextureViewDimension::D2Array).
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// The width and height of each page, in pixels.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// The number of pages.
+    pub fn page_count(&self) -> u32 {
+        self.texture.depth_or_array_layers()
+    }
+
+    /// Renders `scene` into free space of `width` by `height` pixels, returning where it was
+    /// placed.
+    ///
+    /// The scene is drawn with its origin at the top left of the entry, and content outside
+    /// of the entry's size is clipped. Returns [`Error::LayerAtlasFull`] if there is no space
+    /// on any page, such as when the entry is larger than a page.
+    pub fn render(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> Result<AtlasEntry> {
+        let (width, height) = (width.max(1), height.max(1));
+        let (padded_width, padded_height) = (width + 2 * GUTTER, height + 2 * GUTTER);
+        let page_size = self.page_size;
+        let (page, (x, y)) = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, page)| {
+                let position = page.allocate(padded_width, padded_height, page_size)?;
+                Some((index, position))
+            })
+            .ok_or(Error::LayerAtlasFull(width, height))?;
+        let entry = AtlasEntry {
+            page: u32::try_from(page).expect("the page count is a `u32`"),
+            x,
+            y,
+            width,
+            height,
+            page_size,
+        };
+        if let Err(error) = self.rerender(renderer, device, queue, &entry, scene) {
+            self.free(entry);
+            return Err(error);
+        }
+        Ok(entry)
+    }
+
+    /// Renders `scene` again into the space of `entry`, such as when the widget it caches
+    /// has changed.
+    pub fn rerender(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+        entry: &AtlasEntry,
+        scene: &Scene,
+    ) -> Result<()> {
+        let (padded_width, padded_height) = (entry.width + 2 * GUTTER, entry.height + 2 * GUTTER);
+        let (_, view) = self.scratch.get_or_insert_with(|| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("catalina.layer_atlas_scratch"),
+                size: wgpu::Extent3d {
+                    width: self.page_size,
+                    height: self.page_size,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            (texture, view)
+        });
+        // The gutter is rendered along with the content, so that it is cleared of whatever
+        // was previously in this space.
+        let content = Rect::new(0.0, 0.0, entry.width.into(), entry.height.into());
+        let offset = Affine::translate((f64::from(GUTTER), f64::from(GUTTER)));
+        self.scratch_scene.reset();
+        self.scratch_scene.flatten_shader = scene.flatten_shader.clone();
+        self.scratch_scene.push_layer(
+            BlendMode::new(Mix::Normal, Compose::SrcOver),
+            1.0,
+            offset,
+            &content,
+        );
+        self.scratch_scene.append(scene, Some(offset));
+        self.scratch_scene.pop_layer();
+        renderer.render_to_texture(
+            device,
+            queue,
+            &self.scratch_scene,
+            view,
+            &RenderParams {
+                base_color: palette::css::TRANSPARENT,
+                width: padded_width,
+                height: padded_height,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+        )?;
+        let (scratch, _) = self
+            .scratch
+            .as_ref()
+            .expect("the scratch texture was created above");
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("catalina.layer_atlas_copy"),
+        });
+        encoder.copy_texture_to_texture(
+            scratch.as_image_copy(),
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: entry.x,
+                    y: entry.y,
+                    z: entry.page,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: padded_width,
+                height: padded_height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit([encoder.finish()]);
+        Ok(())
+    }
+
+    /// Returns the space of `entry` to the atlas, for later entries to reuse.
+    ///
+    /// The pixels of the entry are left in place until they are overwritten.
+    pub fn free(&mut self, entry: AtlasEntry) {
+        if let Some(page) = self.pages.get_mut(entry.page as usize) {
+            page.free(entry.x, entry.y, entry.width + 2 * GUTTER, self.page_size);
+        }
+    }
+}
+
+impl std::fmt::Debug for LayerAtlas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayerAtlas")
+            .field("page_size", &self.page_size)
+            .field("page_count", &self.page_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The free space of a page, as rows of shelves which each hold entries of similar height.
+#[derive(Default)]
+struct Page {
+    shelves: Vec<Shelf>,
+    /// The top of the space below the last shelf.
+    bottom: u32,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// The free spans of the shelf, as their start and width, in order.
+    free: Vec<(u32, u32)>,
+}
+
+impl Page {
+    /// Finds space of the given size, returning its top left corner.
+    fn allocate(&mut self, width: u32, height: u32, page_size: u32) -> Option<(u32, u32)> {
+        if width > page_size || height > page_size {
+            return None;
+        }
+        // Prefer the shortest shelf which fits without wasting more than half of the height
+        // of the entry, then start a new shelf, and only then accept more wasted space.
+        let fits = |shelf: &Shelf| {
+            shelf.height >= height && shelf.free.iter().any(|&(_, free)| free >= width)
+        };
+        let snug = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| fits(shelf) && shelf.height <= height + height / 2)
+            .min_by_key(|(_, shelf)| shelf.height)
+            .map(|(index, _)| index);
+        let index = match snug {
+            Some(index) => index,
+            None if page_size - self.bottom >= height => {
+                self.shelves.push(Shelf {
+                    y: self.bottom,
+                    height,
+                    free: vec![(0, page_size)],
+                });
+                self.bottom += height;
+                self.shelves.len() - 1
+            }
+            None => self.shelves.iter().position(fits)?,
+        };
+        let shelf = &mut self.shelves[index];
+        let span = shelf.free.iter().position(|&(_, free)| free >= width)?;
+        let (x, free) = shelf.free[span];
+        if free == width {
+            shelf.free.remove(span);
+        } else {
+            shelf.free[span] = (x + width, free - width);
+        }
+        Some((x, shelf.y))
+    }
+
+    /// Returns the space of the given width at the top left corner `(x, y)` to its shelf.
+    fn free(&mut self, x: u32, y: u32, width: u32, page_size: u32) {
+        let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == y) else {
+            return;
+        };
+        let index = shelf.free.partition_point(|&(start, _)| start < x);
+        shelf.free.insert(index, (x, width));
+        // Merge with the following span, then the preceding one.
+        if let Some(&(next, next_width)) = shelf.free.get(index + 1) {
+            if x + width == next {
+                shelf.free[index].1 += next_width;
+                shelf.free.remove(index + 1);
+            }
+        }
+        if index > 0 {
+            let (previous, previous_width) = shelf.free[index - 1];
+            if previous + previous_width == x {
+                shelf.free[index - 1].1 += shelf.free[index].1;
+                shelf.free.remove(index);
+            }
+        }
+        // Empty shelves at the bottom are released, so that their space can be used for
+        // entries of other heights.
+        while self
+            .shelves
+            .last()
+            .is_some_and(|shelf| shelf.free == [(0, page_size)])
+        {
+            let shelf = self.shelves.pop().expect("there is a last shelf");
+            self.bottom = shelf.y;
+        }
+    }
+}
@@ -40,6 +40,11 @@
 //!       use_cpu: false,
 //!       antialiasing_support: vello::AaSupport::all(),
 //!       num_init_threads: NonZeroUsize::new(1),
+//!       gpu_stages_from: None,
+//!       fine_band_rows: None,
+//!       frames_in_flight: None,
+//!       disk_cache: None,
+//!       small_scenes: None,
 //!    },
 //! ).expect("Failed to create renderer");
 //!
@@ -73,6 +78,10 @@
 //!          width,
 //!          height,
 //!          antialiasing_method: AaConfig::Msaa16,
+//!          global_alpha: 1.0,
+//!          dash_phase: 0.0,
+//!          glow_amount: 1.0,
+//!          dirty_rects: None,
 //!       },
 //!    )
 //!    .expect("Failed to render to surface");
@@ -80,6 +89,15 @@
 //! ```
 //!
 //! See the [`examples/`](https://github.com/linebender/vello/tree/main/examples) folder to see how that code integrates with frameworks like winit.
+//!
+//! ## API stability
+//!
+//! The [`stable`] module gathers the parts of the API which applications need to build scenes and render them, which are only changed incompatibly in releases marked as breaking by semantic versioning.
+//! Importing items through it documents that a crate relies on nothing else.
+//! [`RenderParams`] and [`RendererOptions`] are not part of it yet, as they are plain structs which still gain fields.
+//!
+//! The encoding and pipeline internals are available from the `unstable` module with the `unstable` feature, for renderers and tools built on Catalina's internals.
+//! They may change in any release.
 
 // LINEBENDER LINT SET - lib.rs - v2
 // See https://linebender.org/wiki/canonical-lints/
@@ -110,17 +128,64 @@
     reason = "Deferred, only apply in some feature sets so not expect"
 )]
 
+#[cfg(feature = "wgpu")]
+mod accumulate;
+#[cfg(feature = "wgpu")]
+mod cached_layer;
+mod cmyk;
+#[cfg(feature = "wgpu")]
+mod color_filter;
+#[cfg(feature = "wgpu")]
+mod compositor;
+#[cfg(feature = "wgpu")]
+mod config;
+#[cfg(feature = "wgpu")]
+mod coverage;
 mod debug;
+mod dirty;
+#[cfg(feature = "wgpu")]
+mod disk_cache;
+#[cfg(feature = "wgpu")]
+mod downscale;
+#[cfg(feature = "draw-costs")]
+mod draw_costs;
+#[cfg(feature = "wgpu")]
+mod filter;
+mod governor;
+pub mod graph;
+#[cfg(feature = "wgpu")]
+mod layer_atlas;
+mod occlusion;
+#[cfg(feature = "wgpu")]
+mod overprint;
+#[cfg(feature = "wgpu")]
+mod paint_layer;
+#[cfg(all(feature = "wgpu", feature = "wgpu-profiler"))]
+mod profile;
+#[cfg(feature = "wgpu")]
+mod quirks;
 mod recording;
 pub mod render;
 mod scene;
 mod shaders;
+mod snap;
+#[cfg(feature = "wgpu")]
+mod statistics;
+#[cfg(feature = "wgpu")]
+mod supersample;
+mod text_cache;
+#[cfg(feature = "wgpu")]
+mod thumbnail;
+#[cfg(all(feature = "wgpu", feature = "debug_validation"))]
+mod validation;
+mod winding;
 
 #[cfg(feature = "wgpu")]
 pub mod util;
 #[cfg(feature = "wgpu")]
 /// The WebGPU Backend, Engine & utilities.
 pub mod wgpu_engine;
+pub mod world;
 
 pub mod low_level {
     //! Utilities which can be used to create an alternative Vello renderer to [`Renderer`][crate::Renderer].
@@ -137,6 +202,43 @@ pub mod low_level {
     /// Temporary export, used in `with_winit` for stats
     pub use catalina_encoding::BumpAllocators;
 }
+
+pub mod stable {
+    //! The API for building scenes and rendering them, covered by semantic versioning.
+    //!
+    //! Items here are only changed incompatibly in breaking releases, after being deprecated
+    //! for at least one release.
+    //!
+    //! [`RenderParams`](crate::RenderParams) and [`RendererOptions`](crate::RendererOptions)
+    //! are left out, as new fields are still added to them in minor releases.
+    //! They are available from the crate root.
+
+    pub use crate::{
+        kurbo, peniko, AaConfig, AaSupport, DrawGlyphs, Error, Glyph, Scene, SceneFragment,
+    };
+    #[cfg(feature = "wgpu")]
+    pub use crate::{
+        util::{DeviceHandle, DeviceOptions, RenderContext, RenderSurface},
+        wgpu, Renderer,
+    };
+}
+
+#[cfg(feature = "unstable")]
+pub mod unstable {
+    //! The encoding and pipeline internals, which may change in any release.
+    //!
+    //! This requires the `unstable` feature.
+
+    pub use crate::low_level::*;
+    #[cfg(feature = "wgpu")]
+    pub use crate::wgpu_engine::WgpuEngine;
+    /// The encoding of scenes for the pipeline.
+    pub use catalina_encoding as encoding;
+    /// The shaders of the pipeline.
+    #[cfg(feature = "wgpu")]
+    pub use catalina_shaders as shaders;
+}
+
 /// Styling and composition primitives.
 pub use peniko;
 /// 2D geometry, with a focus on curves.
@@ -145,15 +247,61 @@ pub use peniko::kurbo;
 #[cfg(feature = "wgpu")]
 pub use wgpu;
 
-pub use catalina_encoding::{Glyph, NormalizedCoord};
-pub use scene::{DrawGlyphs, Scene};
+#[cfg(feature = "wgpu")]
+pub use accumulate::Accumulator;
+#[cfg(feature = "wgpu")]
+pub use cached_layer::CachedLayer;
+pub use catalina_encoding::{
+    check_color_stops, compile_static, normalize_color_stops, ColorStopsError, DecodeError,
+    EncodingFragmentId, FontId, Glyph, ImageId, LayerInfo, Lint, LintKind, LintOptions,
+    NormalizedCoord, OversizedImages, Placeholder, UploadPriority, UploadQueue,
+};
+pub use cmyk::{Cmyk, CmykConverter};
+#[cfg(feature = "wgpu")]
+pub use color_filter::ColorFilter;
+#[cfg(feature = "wgpu")]
+pub use config::CatalinaConfig;
+pub use dirty::{DirtyRects, MAX_DIRTY_RECTS};
+#[cfg(feature = "wgpu")]
+pub use disk_cache::DiskCache;
+#[cfg(feature = "wgpu")]
+pub use downscale::DownscaleFilter;
+#[cfg(feature = "draw-costs")]
+pub use draw_costs::{DrawCost, DrawCosts, DrawKind};
+pub use governor::{Quality, QualityGovernor};
+#[cfg(feature = "wgpu")]
+pub use layer_atlas::{AtlasEntry, LayerAtlas};
+pub use occlusion::{CompositorWindow, OcclusionCuller};
+#[cfg(feature = "wgpu")]
+pub use overprint::Separation;
+#[cfg(feature = "wgpu")]
+pub use paint_layer::{PaintLayer, PaintMode};
+#[cfg(all(feature = "wgpu", feature = "wgpu-profiler"))]
+pub use profile::StageTiming;
+#[cfg(feature = "wgpu")]
+pub use quirks::{Quirk, Quirks, QUIRKS};
+pub use scene::{
+    BrushTip, CustomPaint, CustomPaintId, DrawGlyphs, Filter, FilterGraph, FilterInput,
+    FilterNodeId, FragmentResolver, Hatch, LightSource, Lighting, LineMetrics,
+    ParallelSceneBuilder, PartBuilder, ResourceTable, RubyAlign, RubyLayout, RubyPosition, RubyRun,
+    Scene, SceneFragment, SimpleShaper, SmoothRect, StrokeDynamics, StrokePoint, TextMeasure,
+    Turbulence, VerticalLayout, VerticalOrientation, IOS_CORNER_SMOOTHING, MIN_RASTER_SCALE,
+};
+pub use snap::{PixelSnap, SnappedStroke};
+#[cfg(feature = "wgpu")]
+pub use statistics::{FrameStatistics, HISTOGRAM_BINS, MAX_LOG2_LUMINANCE, MIN_LOG2_LUMINANCE};
+pub use text_cache::{TextRunCache, TextRunKey};
+#[cfg(feature = "wgpu")]
+pub use thumbnail::ThumbnailJob;
+pub use winding::{misoriented_subpaths, normalize_winding, winding_directions, WindingDirection};
 
 pub use vune;
 
 pub use low_level::{BindType, ShaderId};
 #[cfg(feature = "wgpu")]
 use low_level::{
-    BumpAllocators, FullShaders, ImageFormat, ImageProxy, Recording, Render, ResourceProxy,
+    BufferProxy, BumpAllocators, FullShaders, ImageFormat, ImageProxy, Recording, Render,
+    ResourceProxy,
 };
 use thiserror::Error;
 
@@ -165,7 +313,10 @@ use debug::DebugLayers;
 use wgpu_engine::{ExternalResource, WgpuEngine};
 
 #[cfg(feature = "wgpu")]
-use std::{num::NonZeroUsize, sync::atomic::AtomicBool};
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    sync::atomic::AtomicBool,
+};
 #[cfg(feature = "wgpu")]
 use wgpu::{Device, Queue, SurfaceTexture, TextureFormat, TextureView};
 #[cfg(all(feature = "wgpu", feature = "wgpu-profiler"))]
@@ -278,10 +429,10 @@ pub enum Error {
     #[error("Couldn't create wgpu surface")]
     WgpuCreateSurfaceError(#[from] wgpu::CreateSurfaceError),
     /// Surface doesn't support the required texture formats.
-    /// Make sure that you have a surface which provides one of
-    /// [`TextureFormat::Rgba8Unorm`] or [`TextureFormat::Bgra8Unorm`] as texture formats.
+    /// Make sure that you have a surface which provides one of the formats in
+    /// [`util::SURFACE_FORMATS`], or one of those requested when creating it.
     #[cfg(feature = "wgpu")]
-    #[error("Couldn't find `Rgba8Unorm` or `Bgra8Unorm` texture formats for surface")]
+    #[error("Couldn't find a supported texture format for surface")]
     UnsupportedSurfaceFormat,
 
     /// Used a buffer inside a recording while it was not available.
@@ -289,6 +440,73 @@ pub enum Error {
     #[cfg(feature = "wgpu")]
     #[error("Buffer '{0}' is not available but used for {1}")]
     UnavailableBufferUsed(&'static str, &'static str),
+    /// The render target covers more than [`MAX_TARGET_BINS`] bins, so can't be rendered in
+    /// one pass. Use [`Renderer::render_tiled`] to render it in smaller pieces instead.
+    #[cfg(feature = "wgpu")]
+    #[error("Target of size {0}x{1} is too large to render in one pass, use tiled rendering")]
+    TargetTooLarge(u32, u32),
+    /// The width or height of the image passed to [`Renderer::render_tiled`] or one of the
+    /// banded rendering methods is zero.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't render an image with a width or height of zero")]
+    EmptyTarget,
+    /// The render parameters passed to [`Renderer::accumulate`] don't match the size of the
+    /// [`Accumulator`].
+    #[cfg(feature = "wgpu")]
+    #[error("Render size doesn't match the size of the accumulator")]
+    AccumulatorSizeMismatch,
+    /// The texture passed to [`Renderer::render_coverage_to_texture`] has a format which
+    /// coverage can't be rendered to.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't render coverage to a texture of format {0:?}, use `R8Unorm` or `R16Float`")]
+    UnsupportedCoverageFormat(TextureFormat),
+    /// The texture passed to [`Renderer::render_winding_to_texture`] doesn't have the
+    /// `R32Float` format.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't render winding numbers to a texture of format {0:?}, use `R32Float`")]
+    UnsupportedWindingFormat(TextureFormat),
+    /// The texture passed to [`util::read_texture`] has a format which can't be copied to a
+    /// buffer, such as a depth format.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't read back a texture of format {0:?}")]
+    UnsupportedReadbackFormat(TextureFormat),
+    /// The texture passed to [`Renderer::frame_statistics`] has a format which can't be
+    /// measured.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't measure the statistics of a texture of format {0:?}")]
+    UnsupportedStatisticsFormat(TextureFormat),
+    /// A texture passed to [`Renderer::downscale_texture`] doesn't have the
+    /// [`TextureFormat::Rgba8Unorm`] format.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't downscale to or from a texture of format {0:?}")]
+    UnsupportedDownscaleFormat(TextureFormat),
+    /// The cache couldn't be written by [`Renderer::save_disk_cache`].
+    #[cfg(feature = "wgpu")]
+    #[error("Couldn't write the disk cache")]
+    DiskCacheWrite(#[source] std::io::Error),
+    /// The environment variable read by [`CatalinaConfig::from_env`] has a value which
+    /// can't be parsed.
+    #[cfg(feature = "wgpu")]
+    #[error("Invalid value {1:?} for the environment variable {0}")]
+    InvalidConfig(&'static str, String),
+    /// The texture passed to [`Renderer::register_external_texture`] doesn't have the
+    /// [`TextureFormat::Rgba8Unorm`] format.
+    #[cfg(feature = "wgpu")]
+    #[error("Can't draw an external texture of format {0:?}")]
+    UnsupportedExternalTextureFormat(TextureFormat),
+    /// The texture passed to [`Renderer::register_external_texture`] is larger than the
+    /// image atlas.
+    #[cfg(feature = "wgpu")]
+    #[error("External texture of {0}x{1} pixels is larger than the image atlas")]
+    ExternalTextureTooLarge(u32, u32),
+    /// There is no free space for an entry of the given size on any page of a [`LayerAtlas`].
+    #[cfg(feature = "wgpu")]
+    #[error("No space for a layer of {0}x{1} pixels in the atlas")]
+    LayerAtlasFull(u32, u32),
+    /// The scene passed to [`Scene::to_bytes`] has content which is kept outside of its
+    /// encoding, so can't be serialized.
+    #[error("Can't serialize the scene, as {0}")]
+    UnserializableScene(&'static str),
     /// Failed to async map a buffer.
     /// See [`wgpu::BufferAsyncError`] for more information.
     #[cfg(feature = "wgpu")]
@@ -312,11 +530,16 @@ pub enum Error {
     #[doc(hidden)] // End-users of Vello should not have `wgpu-profiler` enabled.
     ProfilerCreationError(#[from] wgpu_profiler::CreationError),
 
-    /// Failed to compile the shaders.
+    /// Failed to compile the shaders when reloading them with [`Renderer::reload_shaders`].
+    /// See [`catalina_shaders::compile::ErrorVec::errors`] for the error in each shader.
     #[cfg(feature = "hot_reload")]
     #[error("Failed to compile shaders:\n{0}")]
-    #[doc(hidden)] // End-users of Vello should not have `hot_reload` enabled.
     ShaderCompilation(#[from] catalina_shaders::compile::ErrorVec),
+    /// A shader used by the renderer wasn't found in the shader directory when reloading
+    /// them with [`Renderer::reload_shaders`].
+    #[cfg(feature = "hot_reload")]
+    #[error("Shader '{0}' is missing from the shader directory")]
+    MissingShader(&'static str),
 }
 
 #[cfg_attr(
@@ -332,23 +555,61 @@ pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
 /// This is an assumption which is known to be limiting, and is planned to change.
 #[cfg(feature = "wgpu")]
 pub struct Renderer {
-    #[cfg_attr(
-        not(feature = "hot_reload"),
-        expect(
-            dead_code,
-            reason = "Options are only used to reinitialise on a hot reload"
-        )
-    )]
     options: RendererOptions,
     engine: WgpuEngine,
     resolver: Resolver,
     shaders: FullShaders,
+    /// The shaders used for scenes within [`RendererOptions::small_scenes`].
+    small_scene_shaders: Option<FullShaders>,
+    /// Whether the resources of draws outside of the target are resolved once they're drawn
+    /// within it, set by [`Self::set_deferred_resolve`].
+    deferred_resolve: bool,
     /// This is where Vune Shaders are stored internally (In the future, the types are probably going to change).
     pub vune_shaders: HashMap<String, ShaderId>,
+    /// The paint functions registered with [`Self::register_custom_paint`], in order of id.
+    custom_paints: Vec<catalina_shaders::CustomPaintSource>,
     blit: Option<BlitPipeline>,
+    /// The pass run on the rendered image before it's blitted, set by [`Self::set_compositor`].
+    compositor: Option<compositor::Compositor>,
+    /// The uniforms of the compositor, set by [`Self::set_compositor_uniforms`].
+    compositor_uniforms: [f32; compositor::UNIFORM_COUNT],
+    /// The output of the compositor, kept between frames of the same size.
+    composited: Option<TargetTexture>,
+    /// The filter applied to the output when it's blitted, set by [`Self::set_color_filter`].
+    color_filter: Option<ColorFilter>,
+    /// The shader used by [`Self::accumulate`], created on first use.
+    accumulate: Option<ShaderId>,
+    /// The shader used by [`Self::render_to_texture_supersampled`], created on first use.
+    downsample: Option<ShaderId>,
+    /// The shaders used by [`Self::downscale_texture`], created on first use.
+    downscale: Option<downscale::DownscaleShaders>,
+    /// The shaders used by layers pushed with [`Scene::push_layer_with_filter`], created on
+    /// first use.
+    filters: Option<filter::FilterShaders>,
+    /// The shaders used by [`Self::render_coverage_to_texture`], created on first use.
+    coverage: Option<coverage::CoverageShaders>,
+    /// The shaders used by [`Self::render_overprint_preview_to_texture`], created on first use.
+    overprint: Option<overprint::OverprintShaders>,
+    /// The shader used by [`Self::frame_statistics`], created on first use.
+    statistics: Option<ShaderId>,
+    /// The coarse and fine shaders used by [`Self::render_winding_to_texture`], created on
+    /// first use.
+    winding: Option<(ShaderId, ShaderId)>,
+    /// The coarse and fine shaders used by [`Self::render_to_texture_with_costs`], created on
+    /// first use.
+    #[cfg(feature = "draw-costs")]
+    draw_costs: Option<(ShaderId, ShaderId)>,
+    /// The image atlas kept from the previous frame, which only new images are written to.
+    image_atlas: Option<ImageProxy>,
     #[cfg(feature = "debug_layers")]
     debug: Option<debug::DebugRenderer>,
     target: Option<TargetTexture>,
+    /// Whether `target` holds the last frame rendered to a surface, which the regions outside
+    /// of the dirty rects of the next frame are kept from.
+    target_holds_frame: bool,
+    /// The intermediate texture of renders to other textures, kept apart from `target` so
+    /// that they don't overwrite the last frame rendered to a surface.
+    scratch: Option<TargetTexture>,
     #[cfg(feature = "wgpu-profiler")]
     #[doc(hidden)] // End-users of Vello should not have `wgpu-profiler` enabled.
     /// The profiler used with events for this renderer. This is *not* treated as public API.
@@ -366,6 +627,13 @@ pub struct Renderer {
 #[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
 static_assertions::assert_impl_all!(Renderer: Send);
 
+/// The maximum number of bins in a render target, where each bin covers 256x256 pixels.
+///
+/// The binning stage keeps one bitmap per bin in workgroup shared memory, so larger targets
+/// would be silently corrupted. For example, a 4096x4096 target uses all 256 bins, as does a
+/// 16384x1024 target. Larger images can be rendered in pieces using [`Renderer::render_tiled`].
+pub const MAX_TARGET_BINS: u32 = 256;
+
 /// Parameters used in a single render that are configurable by the client.
 ///
 /// These are used in [`Renderer::render_to_surface`] and [`Renderer::render_to_texture`].
@@ -382,6 +650,63 @@ pub struct RenderParams {
     /// The anti-aliasing algorithm. The selected algorithm must have been initialized while
     /// constructing the `Renderer`.
     pub antialiasing_method: AaConfig,
+
+    /// The opacity of the whole output, from 0 to 1, such as for fading a window in or out.
+    ///
+    /// This is applied when the rendered image is blitted to a surface, so it doesn't need the
+    /// scene to be wrapped in a layer. It is only used by the methods which render to a
+    /// surface, such as [`Renderer::render_to_surface`].
+    pub global_alpha: f32,
+
+    /// The distance added to the dash offset of strokes drawn with
+    /// [`Scene::stroke_with_dash_phase`].
+    ///
+    /// Changing this each frame animates the dashes, such as for the "marching ants" outline of
+    /// a selection, without encoding the scene again.
+    pub dash_phase: f64,
+
+    /// The fraction of the radius and intensity of [`Filter::Glow`]s to draw, from 0 to 1.
+    ///
+    /// Changing this each frame animates glows, such as for a pulsing highlight, without
+    /// encoding the scene again. 1 draws glows as they were added to the scene.
+    pub glow_amount: f32,
+
+    /// The regions of the output which changed since the previous frame, or `None` to render
+    /// all of it.
+    ///
+    /// Only these regions are rendered, and the rest of the output is kept from the previous
+    /// frame, which cuts the GPU time of frames where little changes, such as a blinking
+    /// cursor. This is only used by [`Renderer::render_to_surface`], and only when the
+    /// previous frame it rendered had the same size; otherwise the whole output is rendered.
+    pub dirty_rects: Option<DirtyRects>,
+}
+
+#[cfg(feature = "wgpu")]
+impl RenderParams {
+    /// Returns an error if the target is too large to be rendered in a single pass.
+    ///
+    /// See [`MAX_TARGET_BINS`].
+    fn check_target_size(&self) -> Result<()> {
+        // Each bin is 16x16 tiles, each of which is 16x16 pixels.
+        const BIN_SIZE: u32 = 256;
+        let bins =
+            u64::from(self.width.div_ceil(BIN_SIZE)) * u64::from(self.height.div_ceil(BIN_SIZE));
+        if bins > u64::from(MAX_TARGET_BINS) {
+            return Err(Error::TargetTooLarge(self.width, self.height));
+        }
+        Ok(())
+    }
+}
+
+/// Shrinks a tile of `width` by `height` pixels, which must be non-zero, until it fits within
+/// the maximum texture size of `device` and covers at most [`MAX_TARGET_BINS`] bins.
+#[cfg(feature = "wgpu")]
+fn max_tile_size(device: &Device, width: u32, height: u32) -> (u32, u32) {
+    const BIN_SIZE: u32 = 256;
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    let width = width.min(max_dimension).min(MAX_TARGET_BINS * BIN_SIZE);
+    let max_height = MAX_TARGET_BINS / width.div_ceil(BIN_SIZE) * BIN_SIZE;
+    (width, height.min(max_dimension).min(max_height))
 }
 
 #[cfg(feature = "wgpu")]
@@ -389,13 +714,32 @@ pub struct RenderParams {
 pub struct RendererOptions {
     /// The format of the texture used for surfaces with this renderer/device
     /// If None, the renderer cannot be used with surfaces
+    ///
+    /// This must be one of [`util::SURFACE_FORMATS`]. The rendered colors are sRGB encoded,
+    /// and are decoded to linear values when written to an sRGB or floating point surface.
     pub surface_format: Option<TextureFormat>,
 
     /// If true, run all stages up to fine rasterization on the CPU.
+    ///
+    /// Fine rasterization always runs on the GPU, so a device is still needed.
     // TODO: Consider evolving this so that the CPU stages can be configured dynamically via
     // `RenderParams`.
     pub use_cpu: bool,
 
+    /// When [`use_cpu`](Self::use_cpu) is set, run this stage and every later stage on the
+    /// GPU instead.
+    ///
+    /// Comparing the output for each stage against rendering entirely on the GPU shows which
+    /// stage a difference comes from. Data can only be passed from the CPU to the GPU, so the
+    /// stages on the CPU are always the ones before this.
+    ///
+    /// Set to `None` to run every stage which has a CPU implementation on the CPU.
+    ///
+    /// This doesn't let Catalina render without a GPU. The CPU stages are run by the wgpu
+    /// engine between its GPU passes, and the CPU version of fine rasterization only draws
+    /// solid fills, so it isn't offered as a stage.
+    pub gpu_stages_from: Option<PipelineStage>,
+
     /// Represents the enabled set of AA configurations. This will be used to determine which
     /// pipeline permutations should be compiled at startup.
     pub antialiasing_support: AaSupport,
@@ -409,6 +753,151 @@ pub struct RendererOptions {
     ///
     /// Has no effect on WebAssembly
     pub num_init_threads: Option<NonZeroUsize>,
+
+    /// Split fine rasterization into horizontal bands of this many tile rows, each of which
+    /// is submitted to the queue separately.
+    ///
+    /// Platforms with a GPU watchdog (such as TDR on Windows) can reset the device if a single
+    /// submission runs for too long, which can happen for extremely complex scenes.
+    /// Splitting the work keeps each submission short, at the cost of some overhead for each
+    /// submission. The bands are queued together, without waiting for each to finish.
+    /// Each tile row is 16 pixels high.
+    ///
+    /// Set to `None` to rasterize the whole target in one submission.
+    pub fine_band_rows: Option<NonZeroU32>,
+
+    /// How many frames can be in flight at once.
+    ///
+    /// The internal buffers freed by a frame aren't reused until this many frames have been
+    /// rendered, so that uploading the data for a frame can overlap with the GPU running the
+    /// previous one rather than waiting for it. Higher values use more memory, and `Some(1)`
+    /// reuses buffers immediately.
+    ///
+    /// Set to `None` to use the default of 2.
+    pub frames_in_flight: Option<NonZeroUsize>,
+
+    /// A directory in which compiled pipelines are kept between runs, to shorten the creation
+    /// of renderers in later runs.
+    ///
+    /// The cache is loaded when the renderer is created, and written by
+    /// [`Renderer::save_disk_cache`]. Set to `None` to compile every pipeline from scratch.
+    pub disk_cache: Option<DiskCache>,
+
+    /// Scenes within these limits run every stage before fine rasterization on the CPU, even
+    /// if [`use_cpu`](Self::use_cpu) isn't set.
+    ///
+    /// Each stage on the GPU is a separate dispatch with a fixed cost, which dominates for tiny
+    /// scenes such as cursors and badges. On the CPU, such scenes take a few microseconds per
+    /// stage instead. The CPU implementations are created along with the renderer.
+    ///
+    /// Set to `None` to run the stages of every scene on the GPU.
+    pub small_scenes: Option<SmallSceneLimits>,
+}
+
+/// The largest scenes rendered with the fast path for small scenes.
+///
+/// See [`RendererOptions::small_scenes`].
+#[cfg(feature = "wgpu")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SmallSceneLimits {
+    /// The most paths in the scene, including clips and layers.
+    pub max_paths: u32,
+    /// The most segments of all paths in the scene, before flattening.
+    pub max_path_segments: u32,
+    /// The most pixels in the target.
+    pub max_pixels: u32,
+}
+
+#[cfg(feature = "wgpu")]
+impl Default for SmallSceneLimits {
+    /// Scenes of up to 16 paths with 256 segments, rendered to at most 128x128 pixels.
+    fn default() -> Self {
+        Self {
+            max_paths: 16,
+            max_path_segments: 256,
+            max_pixels: 128 * 128,
+        }
+    }
+}
+
+#[cfg(feature = "wgpu")]
+impl SmallSceneLimits {
+    /// Returns `true` if a resolved scene, with `layout` and the packed scene data `packed`,
+    /// rendered with `params` is within the limits.
+    ///
+    /// This includes the paths and segments added by glyph runs and dashed strokes when the
+    /// scene is resolved.
+    pub(crate) fn contains(
+        self,
+        layout: &catalina_encoding::Layout,
+        packed: &[u8],
+        params: &RenderParams,
+    ) -> bool {
+        u64::from(params.width) * u64::from(params.height) <= u64::from(self.max_pixels)
+            && layout.n_paths <= self.max_paths
+            && layout
+                .path_tags(packed)
+                .iter()
+                .filter(|tag| tag.is_path_segment())
+                .count()
+                <= self.max_path_segments as usize
+    }
+}
+
+/// A stage of the pipeline which can run on the CPU, in the order they run.
+///
+/// See [`RendererOptions::gpu_stages_from`].
+#[cfg(feature = "wgpu")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PipelineStage {
+    /// The reduction and prefix sum of the path tags.
+    PathtagScan,
+    /// Clearing the bounding boxes of paths.
+    BboxClear,
+    /// Flattening of paths into line segments.
+    Flatten,
+    /// The reduction of the draw tags.
+    DrawReduce,
+    /// The prefix sum of the draw tags, which also prepares the info for each brush.
+    DrawLeaf,
+    /// The reduction of the clip stack.
+    ClipReduce,
+    /// Computing the bounding box of each clip.
+    ClipLeaf,
+    /// Assigning draw objects to bins.
+    Binning,
+    /// Allocating the tiles of each path.
+    TileAlloc,
+    /// Counting the segments in each tile.
+    PathCount,
+    /// Propagating the backdrop of each tile.
+    Backdrop,
+    /// Writing the per-tile command lists.
+    Coarse,
+    /// Writing the segments of each tile.
+    PathTiling,
+}
+
+#[cfg(feature = "wgpu")]
+impl PipelineStage {
+    /// Returns the name of the first shader of the stage.
+    pub(crate) fn first_shader(self) -> &'static str {
+        match self {
+            Self::PathtagScan => "pathtag_reduce",
+            Self::BboxClear => "bbox_clear",
+            Self::Flatten => "flatten",
+            Self::DrawReduce => "draw_reduce",
+            Self::DrawLeaf => "draw_leaf",
+            Self::ClipReduce => "clip_reduce",
+            Self::ClipLeaf => "clip_leaf",
+            Self::Binning => "binning",
+            Self::TileAlloc => "tile_alloc",
+            Self::PathCount => "path_count_setup",
+            Self::Backdrop => "backdrop_dyn",
+            Self::Coarse => "coarse",
+            Self::PathTiling => "path_tiling_setup",
+        }
+    }
 }
 
 #[cfg(feature = "wgpu")]
@@ -422,13 +911,33 @@ struct RenderResult {
 impl Renderer {
     /// Creates a new renderer for the specified device.
     pub fn new(device: &Device, options: RendererOptions) -> Result<Self> {
+        #[cfg(feature = "debug-logs")]
+        log::info!(
+            "Creating renderer: surface_format={:?}, use_cpu={}, antialiasing_support={:?}, num_init_threads={:?}",
+            options.surface_format,
+            options.use_cpu,
+            options.antialiasing_support,
+            options.num_init_threads
+        );
         let mut engine = WgpuEngine::new(options.use_cpu);
+        if let Some(frames) = options.frames_in_flight {
+            engine.set_frames_in_flight(frames);
+        }
+        if let Some(cache) = &options.disk_cache {
+            engine.set_pipeline_cache(cache.load_pipelines(device));
+        }
         // If we are running in parallel (i.e. the number of threads is not 1)
         if options.num_init_threads != NonZeroUsize::new(1) {
             #[cfg(not(target_arch = "wasm32"))]
             engine.use_parallel_initialisation();
         }
-        let shaders = shaders::full_shaders(device, &mut engine, &options)?;
+        let shaders = shaders::full_shaders(device, &mut engine, &options, &[])?;
+        let small_scene_shaders = match options.small_scenes {
+            Some(_) if !options.use_cpu => {
+                Some(shaders::small_scene_shaders(device, &mut engine, &shaders)?)
+            }
+            _ => None,
+        };
         #[cfg(not(target_arch = "wasm32"))]
         engine.build_shaders_if_needed(device, options.num_init_threads);
         let blit = options
@@ -439,17 +948,39 @@ impl Renderer {
         let debug = options
             .surface_format
             .map(|surface_format| debug::DebugRenderer::new(device, surface_format, &mut engine));
+        let mut resolver = Resolver::new();
+        resolver.set_max_atlas_size(device.limits().max_texture_dimension_2d);
 
         Ok(Self {
             options,
             engine,
-            resolver: Resolver::new(),
+            resolver,
             shaders,
+            small_scene_shaders,
+            deferred_resolve: false,
             vune_shaders: HashMap::new(),
+            custom_paints: Vec::new(),
             blit,
+            compositor: None,
+            compositor_uniforms: [0.0; compositor::UNIFORM_COUNT],
+            composited: None,
+            color_filter: None,
+            accumulate: None,
+            downsample: None,
+            downscale: None,
+            filters: None,
+            coverage: None,
+            overprint: None,
+            statistics: None,
+            winding: None,
+            #[cfg(feature = "draw-costs")]
+            draw_costs: None,
+            image_atlas: None,
             #[cfg(feature = "debug_layers")]
             debug,
             target: None,
+            target_holds_frame: false,
+            scratch: None,
             #[cfg(feature = "wgpu-profiler")]
             profiler: GpuProfiler::new(GpuProfilerSettings {
                 ..Default::default()
@@ -506,11 +1037,94 @@ impl Renderer {
         *self.vune_shaders.get(name).unwrap()
     }
 
+    /// Registers a paint function written in WGSL, for filling shapes with
+    /// [`Scene::fill_custom`].
+    ///
+    /// `source` must declare a function named `entry_point` with the signature
+    /// `fn(xy: vec2<f32>, data: vec4<u32>) -> vec4<f32>`, which is evaluated for the center of
+    /// each pixel in the coordinates of the brush, along with the data of the [`CustomPaint`].
+    /// It returns a premultiplied color. The names declared in `source` must not clash with
+    /// those of other paints or of the fine rasterization shader.
+    ///
+    /// The fine rasterization shaders are recompiled with every registered paint linked in, so
+    /// paints should be registered before rendering starts. An invalid `source` is reported by
+    /// wgpu as a validation error when the shaders are compiled.
+    pub fn register_custom_paint(
+        &mut self,
+        device: &Device,
+        entry_point: &str,
+        source: &str,
+    ) -> Result<CustomPaintId> {
+        let id = CustomPaintId(self.custom_paints.len() as u32);
+        self.custom_paints
+            .push(catalina_shaders::CustomPaintSource {
+                entry_point: entry_point.to_string(),
+                source: source.to_string(),
+            });
+        let (fine_area, fine_msaa8, fine_msaa16) = shaders::fine_shaders(
+            device,
+            &mut self.engine,
+            self.options.antialiasing_support,
+            &self.custom_paints,
+        )?;
+        for shaders in [Some(&mut self.shaders), self.small_scene_shaders.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            shaders.fine_area = fine_area;
+            shaders.fine_msaa8 = fine_msaa8;
+            shaders.fine_msaa16 = fine_msaa16;
+        }
+        Ok(id)
+    }
+
+    /// Sets a pass written in WGSL which is run over the rendered image before it's blitted to
+    /// the surface by [`Self::render_to_surface`], for effects over the whole frame such as
+    /// color grading, without copying the image out of the renderer and back.
+    ///
+    /// `source` must declare a function named `entry_point` with the signature
+    /// `fn(xy: vec2<i32>) -> vec4<f32>`, which returns the color of the pixel at `xy`. It can
+    /// read any pixel of the rendered image from `compositor_input`, a `texture_2d<f32>`, and
+    /// the uniforms set with [`Self::set_compositor_uniforms`] from `compositor_params.data`,
+    /// an `array<vec4<f32>, 4>`. The size of the image is in `compositor_params.size`. Colors
+    /// are sRGB encoded and not premultiplied, both in the image and in the result. The names
+    /// declared in `source` must not start with `compositor_`.
+    ///
+    /// This replaces any previous compositor. The shader is compiled when it's first used, and
+    /// an invalid `source` is reported by wgpu as a validation error then. Rendering to a
+    /// texture doesn't run the compositor, as the texture can be processed directly.
+    pub fn set_compositor(&mut self, entry_point: &str, source: &str) {
+        self.compositor = Some(compositor::Compositor::new(entry_point, source));
+    }
+
+    /// Removes the compositor set with [`Self::set_compositor`], so that the rendered image is
+    /// blitted unchanged.
+    pub fn clear_compositor(&mut self) {
+        self.compositor = None;
+        self.composited = None;
+    }
+
+    /// Sets the uniforms available to the compositor in `compositor_params.data`, as four
+    /// `vec4<f32>`s, which can be changed every frame without recompiling it.
+    pub fn set_compositor_uniforms(&mut self, uniforms: [f32; 16]) {
+        self.compositor_uniforms = uniforms;
+    }
+
+    /// Sets the filter applied to the colors of each frame when it's blitted to a surface by
+    /// [`Self::render_to_surface`], or `None` to show them unchanged.
+    ///
+    /// This can be changed for any frame, such as to toggle a simulation of color blindness,
+    /// without any cost to rendering the scene. Rendering to a texture isn't filtered.
+    pub fn set_color_filter(&mut self, filter: Option<ColorFilter>) {
+        self.color_filter = filter;
+    }
+
     /// Renders a scene to the target texture.
     ///
     /// The texture is assumed to be of the specified dimensions and have been created with
     /// the [`wgpu::TextureFormat::Rgba8Unorm`] format and the [`wgpu::TextureUsages::STORAGE_BINDING`]
-    /// flag set.
+    /// flag set. This doesn't need a window, and [`util::create_target_texture`] and
+    /// [`util::read_texture`] create such a texture and read back its pixels.
     pub fn render_to_texture(
         &mut self,
         device: &Device,
@@ -519,8 +1133,99 @@ impl Renderer {
         texture: &TextureView,
         params: &RenderParams,
     ) -> Result<()> {
-        let (recording, target) =
-            render::render_full(scene, &mut self.resolver, &self.shaders, params);
+        let result = self.render_frame_to_texture(device, queue, scene, texture, params);
+        self.end_frame(queue);
+        result
+    }
+
+    /// Renders a scene to the target texture as part of a larger frame, which the caller ends.
+    fn render_frame_to_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &TextureView,
+        params: &RenderParams,
+    ) -> Result<()> {
+        params.check_target_size()?;
+        let filtered = self.render_filter_layers(device, queue, scene, params)?;
+        let result = self.render_to_texture_unfiltered(device, queue, scene, texture, params);
+        self.clear_filter_overrides(&filtered);
+        result
+    }
+
+    /// Returns the GPU time taken by each stage of the most recent frame whose timings are
+    /// available, such as `catalina.binning`, `catalina.coarse` and `catalina.fine_area`.
+    ///
+    /// Each call to a render method is one frame. Timings are read back without blocking, so
+    /// usually lag a few frames behind, and this returns `None` until the first are available.
+    /// Use [`StageTiming::total`] to find the time of a stage wherever it is nested.
+    #[cfg(feature = "wgpu-profiler")]
+    pub fn profile_results(&self) -> Option<Vec<StageTiming>> {
+        self.profile_result
+            .as_ref()
+            .map(|results| results.iter().map(StageTiming::from).collect())
+    }
+
+    /// Ends the frame of the passes submitted since the last one, so that the buffers it freed
+    /// can be reused once it's no longer in flight, and keeps the profiler results of the most
+    /// recent frame which has finished.
+    #[cfg_attr(
+        not(feature = "wgpu-profiler"),
+        expect(unused_variables, reason = "Only used to read back timings")
+    )]
+    pub(crate) fn end_frame(&mut self, queue: &Queue) {
+        self.engine.end_frame();
+        #[cfg(feature = "wgpu-profiler")]
+        {
+            self.profiler.end_frame().unwrap();
+            if let Some(result) = self
+                .profiler
+                .process_finished_frame(queue.get_timestamp_period())
+            {
+                self.profile_result = Some(result);
+            }
+        }
+    }
+
+    /// Takes the intermediate texture of renders to other textures, with the given size.
+    fn take_scratch(&mut self, device: &Device, width: u32, height: u32) -> TargetTexture {
+        self.scratch
+            .take()
+            .filter(|scratch| scratch.width == width && scratch.height == height)
+            .unwrap_or_else(|| TargetTexture::new(device, width, height))
+    }
+
+    /// Renders a scene to the target texture, once its filtered layers have been rendered.
+    fn render_to_texture_unfiltered(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &TextureView,
+        params: &RenderParams,
+    ) -> Result<()> {
+        if let Some(band_rows) = self.options.fine_band_rows {
+            return self
+                .render_to_texture_fine_banded(device, queue, scene, texture, params, band_rows);
+        }
+        let mut render = self.new_render();
+        // Custom flattening shaders only run on the GPU, and data can't be passed back to the
+        // CPU stages after them.
+        if let (Some(small), Some(limits)) = (&self.small_scene_shaders, self.options.small_scenes)
+        {
+            if scene.flatten_shader.id.is_none() {
+                render.use_small_scene_shaders(small.clone(), limits);
+            }
+        }
+        let (recording, target) = render::render_full(
+            &mut render,
+            scene,
+            &mut self.resolver,
+            &self.shaders,
+            params,
+        );
+        self.image_atlas = render.image_atlas();
         let external_resources = [ExternalResource::Image(
             *target.as_image().unwrap(),
             texture,
@@ -537,92 +1242,1372 @@ impl Renderer {
         Ok(())
     }
 
-    /// Renders a scene to the target surface.
+    /// Renders the content of each layer in `scene` pushed with
+    /// [`Scene::push_layer_with_filter`] and applies its filter, so that the placeholder image
+    /// drawn in place of the layer displays the result.
     ///
-    /// This renders to an intermediate texture and then runs a render pass to blit to the
-    /// specified surface texture.
+    /// Returns the placeholder images, which should be passed to
+    /// [`clear_filter_overrides`](Self::clear_filter_overrides) once the scene is rendered.
+    fn render_filter_layers(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+    ) -> Result<Vec<peniko::Image>> {
+        let mut images = Vec::new();
+        for layer in scene.filter_layers() {
+            let (width, height) = (layer.image.width, layer.image.height);
+            let layer_params = RenderParams {
+                base_color: peniko::color::palette::css::TRANSPARENT,
+                width,
+                height,
+                antialiasing_method: params.antialiasing_method,
+                global_alpha: 1.0,
+                dash_phase: params.dash_phase,
+                glow_amount: params.glow_amount,
+                dirty_rects: None,
+            };
+            if layer_params.check_target_size().is_err()
+                || width.max(height) > device.limits().max_texture_dimension_2d
+            {
+                log::warn!("Filtered layer of {width}x{height} pixels is too large to render");
+                continue;
+            }
+            let shaders = *self
+                .filters
+                .get_or_insert_with(|| filter::FilterShaders::new(device, &mut self.engine));
+            let mut recording = Recording::default();
+            let graph = layer.graph.with_glow_amount(params.glow_amount);
+            let Some(schedule) =
+                shaders.record_graph(&mut recording, &graph, layer.to_layer, width, height)
+            else {
+                log::warn!("Filter graph of layer needs too many intermediate images to render");
+                continue;
+            };
+            let textures: Vec<_> = schedule
+                .images
+                .iter()
+                .map(|_| util::create_target_texture(device, width, height))
+                .collect();
+            let views: Vec<_> = textures
+                .iter()
+                .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()))
+                .collect();
+            // This also renders any filtered layers nested in the content.
+            self.render_frame_to_texture(device, queue, &layer.content, &views[0], &layer_params)?;
+            let external_resources: Vec<_> = schedule
+                .images
+                .iter()
+                .zip(&views)
+                .map(|(proxy, view)| ExternalResource::Image(*proxy, view))
+                .collect();
+            self.engine.run_recording(
+                device,
+                queue,
+                &recording,
+                &external_resources,
+                "filter layer",
+                #[cfg(feature = "wgpu-profiler")]
+                &mut self.profiler,
+            )?;
+            self.override_image(
+                &layer.image,
+                Some(wgpu::TexelCopyTextureInfoBase {
+                    texture: textures[schedule.output].clone(),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                }),
+            );
+            images.push(layer.image.clone());
+        }
+        Ok(images)
+    }
+
+    /// Stops replacing the placeholder images of filtered layers returned by
+    /// [`render_filter_layers`](Self::render_filter_layers).
+    fn clear_filter_overrides(&mut self, images: &[peniko::Image]) {
+        for image in images {
+            self.override_image(image, None);
+        }
+    }
+
+    /// Renders a scene to the target texture at `supersample` times the resolution in each
+    /// direction, then downsamples it.
     ///
-    /// The surface is assumed to be of the specified dimensions and have been configured with
-    /// the same format passed in the constructing [`RendererOptions`]' `surface_format`.
-    /// Panics if `surface_format` was `None`
-    pub fn render_to_surface(
+    /// This is intended for export quality output where the antialiasing method alone isn't
+    /// sufficient, such as for dense hairlines or conflation artifacts between adjacent shapes.
+    /// Each output pixel is the average of the `supersample` by `supersample` pixels it covers.
+    /// The intermediate render must not exceed [`MAX_TARGET_BINS`], and a `supersample` of 0 or 1
+    /// is the same as [`render_to_texture`](Self::render_to_texture).
+    ///
+    /// The texture has the same requirements as for [`render_to_texture`](Self::render_to_texture).
+    pub fn render_to_texture_supersampled(
         &mut self,
         device: &Device,
         queue: &Queue,
         scene: &Scene,
-        surface: &SurfaceTexture,
+        texture: &TextureView,
         params: &RenderParams,
-        clear: bool,
+        supersample: u8,
     ) -> Result<()> {
-        let width = params.width;
-        let height = params.height;
-        let mut target = self
-            .target
-            .take()
-            .unwrap_or_else(|| TargetTexture::new(device, width, height));
-        // TODO: implement clever resizing semantics here to avoid thrashing the memory allocator
-        // during resize, specifically on metal.
-        if target.width != width || target.height != height {
-            target = TargetTexture::new(device, width, height);
+        if supersample <= 1 {
+            return self.render_to_texture(device, queue, scene, texture, params);
         }
-        self.render_to_texture(device, queue, scene, &target.view, params)?;
-        let blit = self
-            .blit
-            .as_ref()
-            .expect("renderer should have configured surface_format to use on a surface");
+        let factor = u32::from(supersample);
+        let large_params = supersample::large_params(params, factor)?;
+        let large = TargetTexture::new(device, large_params.width, large_params.height);
+        let mut scaled = Scene::new();
+        scaled.flatten_shader = scene.flatten_shader.clone();
+        scaled.append(scene, Some(kurbo::Affine::scale(factor.into())));
+        self.render_frame_to_texture(device, queue, &scaled, &large.view, &large_params)?;
+        let shader = *self
+            .downsample
+            .get_or_insert_with(|| supersample::add_shader(device, &mut self.engine));
         let mut recording = Recording::default();
-        let target_proxy = ImageProxy::new(
-            width,
-            height,
-            ImageFormat::from_wgpu(target.format)
-                .expect("`TargetTexture` always has a supported texture format"),
-        );
-        let surface_proxy = ImageProxy::new(
-            width,
-            height,
-            ImageFormat::from_wgpu(surface.texture.format())
-                .ok_or(Error::UnsupportedSurfaceFormat)?,
+        let config = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.downsample_params",
+            bytemuck::bytes_of(&[params.width, params.height, factor, 0]),
+        ));
+        let source = ImageProxy::new(large.width, large.height, ImageFormat::Rgba8);
+        let output = ImageProxy::new(params.width, params.height, ImageFormat::Rgba8);
+        recording.dispatch(
+            shader,
+            (
+                params.width.div_ceil(supersample::WORKGROUP_SIZE),
+                params.height.div_ceil(supersample::WORKGROUP_SIZE),
+                1,
+            ),
+            [
+                config,
+                ResourceProxy::Image(source),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &[
+                ExternalResource::Image(source, &large.view),
+                ExternalResource::Image(output, texture),
+            ],
+            "render_to_texture_supersampled",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )?;
+        self.end_frame(queue);
+        Ok(())
+    }
+
+    /// Writes the pipelines compiled so far to the [`RendererOptions::disk_cache`], so that
+    /// renderers created in later runs don't have to compile them again.
+    ///
+    /// This is usually called once the renderer has been created and has rendered its first
+    /// frames, by which point the pipelines for the features in use have been compiled. It
+    /// does nothing if there is no disk cache, or the device doesn't support pipeline caches.
+    pub fn save_disk_cache(&self) -> Result<()> {
+        let (Some(disk_cache), Some(cache)) =
+            (&self.options.disk_cache, self.engine.pipeline_cache())
+        else {
+            return Ok(());
+        };
+        disk_cache
+            .save_pipelines(cache)
+            .map_err(Error::DiskCacheWrite)
+    }
+
+    /// Resamples `source` into `target` with a high quality filter, such as to make a
+    /// thumbnail of a [`CachedLayer::texture`], an image registered with
+    /// [`register_external_texture`](Self::register_external_texture), or a previous render.
+    ///
+    /// Unlike drawing the source scaled down, which samples it bilinearly, every source pixel
+    /// within the footprint of each output pixel contributes to it, so large reductions don't
+    /// alias or shimmer as the content moves. Colors are filtered in linear light with
+    /// premultiplied alpha, which keeps the saturation of fine detail and doesn't bleed the
+    /// color of transparent pixels. The target is usually smaller than the source, but may be
+    /// of any size.
+    ///
+    /// Both textures must have the [`TextureFormat::Rgba8Unorm`] format with sRGB encoded
+    /// colors and unpremultiplied alpha, as rendered by Vello. The source must have the
+    /// `TEXTURE_BINDING` usage, and the target the `STORAGE_BINDING` usage.
+    pub fn downscale_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        source: &wgpu::Texture,
+        target: &wgpu::Texture,
+        filter: DownscaleFilter,
+    ) -> Result<()> {
+        for texture in [source, target] {
+            if texture.format() != TextureFormat::Rgba8Unorm {
+                return Err(Error::UnsupportedDownscaleFormat(texture.format()));
+            }
+        }
+        let shaders = *self
+            .downscale
+            .get_or_insert_with(|| downscale::DownscaleShaders::new(device, &mut self.engine));
+        // The horizontal pass is kept in a float texture, as the vertical pass reads linear
+        // colors which would lose precision in the darks if stored in 8 bits.
+        let intermediate_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("catalina.downscale_intermediate"),
+            size: wgpu::Extent3d {
+                width: target.width(),
+                height: source.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            format: TextureFormat::Rgba16Float,
+            view_formats: &[],
+        });
+        let views = [source, &intermediate_texture, target]
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let source_image = ImageProxy::new(source.width(), source.height(), ImageFormat::Rgba8);
+        let intermediate =
+            ImageProxy::new(target.width(), source.height(), ImageFormat::Rgba16Float);
+        let output = ImageProxy::new(target.width(), target.height(), ImageFormat::Rgba8);
+        let mut recording = Recording::default();
+        shaders.record(&mut recording, source_image, intermediate, output, filter);
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &[
+                ExternalResource::Image(source_image, &views[0]),
+                ExternalResource::Image(intermediate, &views[1]),
+                ExternalResource::Image(output, &views[2]),
+            ],
+            "downscale_texture",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )?;
+        self.end_frame(queue);
+        Ok(())
+    }
+
+    /// Renders a scene to the target texture, submitting fine rasterization in separate bands.
+    ///
+    /// See [`RendererOptions::fine_band_rows`].
+    fn render_to_texture_fine_banded(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &TextureView,
+        params: &RenderParams,
+        band_rows: NonZeroU32,
+    ) -> Result<()> {
+        let mut render = self.new_render();
+        let recording =
+            render.render_encoding_coarse(scene, &mut self.resolver, &self.shaders, params, false);
+        self.image_atlas = render.image_atlas();
+        let target = render.out_image();
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &[],
+            "render_to_texture (coarse)",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )?;
+        let bands = render.record_fine_banded(&self.shaders, band_rows.get());
+        #[cfg(feature = "debug-logs")]
+        log::debug!(
+            "Splitting fine rasterization of {}x{} target into {} submissions",
+            params.width,
+            params.height,
+            bands.len()
+        );
+        let external_resources = [ExternalResource::Image(target, texture)];
+        // The bands are all queued without waiting for the GPU, which runs the submissions in
+        // order, so each band still runs on its own and none holds the GPU for long.
+        for recording in &bands {
+            self.engine.run_recording(
+                device,
+                queue,
+                recording,
+                &external_resources,
+                "render_to_texture (fine band)",
+                #[cfg(feature = "wgpu-profiler")]
+                &mut self.profiler,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Renders a scene to `texture` in horizontal bands of at most `band_height` pixels.
+    ///
+    /// The intermediate buffers used by the pipeline are sized for a single band rather than the
+    /// full target, which makes it possible to render large targets (such as poster-size
+    /// exports) on memory-constrained devices. Bands which would cover more than
+    /// [`MAX_TARGET_BINS`] bins are split into several tiles.
+    ///
+    /// `texture` must have the [`Rgba8Unorm`](TextureFormat::Rgba8Unorm) format, the
+    /// [`COPY_DST`](wgpu::TextureUsages::COPY_DST) usage, and be at least as large as the
+    /// dimensions in `params`. Use [`render_to_image_in_bands`](Self::render_to_image_in_bands)
+    /// for images larger than the maximum texture size of the device.
+    pub fn render_to_texture_in_bands(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &wgpu::Texture,
+        params: &RenderParams,
+        band_height: NonZeroU32,
+    ) -> Result<()> {
+        self.render_tiles(
+            device,
+            queue,
+            scene,
+            params,
+            (params.width, band_height.get()),
+            |tile| {
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("catalina.band_copy"),
+                });
+                encoder.copy_texture_to_texture(
+                    tile.texture.as_image_copy(),
+                    wgpu::TexelCopyTextureInfo {
+                        texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d {
+                            x: tile.x,
+                            y: tile.y,
+                            z: 0,
+                        },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    wgpu::Extent3d {
+                        width: tile.width,
+                        height: tile.height,
+                        depth_or_array_layers: 1,
+                    },
+                );
+                queue.submit([encoder.finish()]);
+                Ok(())
+            },
+        )
+    }
+
+    /// Renders a scene to a CPU image in horizontal bands of at most `band_height` pixels.
+    ///
+    /// Returns the unpadded RGBA8 pixel data of the image, with rows from top to bottom.
+    /// Only the memory for a single band is required on the GPU. Bands wider than the
+    /// maximum texture size of the device, or covering more than [`MAX_TARGET_BINS`] bins,
+    /// are split into several tiles, so the image can be of any size.
+    ///
+    /// This blocks on the GPU, so is not supported on WebAssembly.
+    pub fn render_to_image_in_bands(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+        band_height: NonZeroU32,
+    ) -> Result<Vec<u8>> {
+        self.render_tiles_to_image(
+            device,
+            queue,
+            scene,
+            params,
+            (params.width, band_height.get()),
+        )
+    }
+
+    /// Renders a scene of any size to a CPU image, in the largest tiles which the device can
+    /// render in one pass.
+    ///
+    /// This is like [`render_to_image_in_bands`](Self::render_to_image_in_bands) with the
+    /// tallest bands that fit, and is intended for exporting images whose size is chosen by
+    /// users, such as from language bindings.
+    ///
+    /// This blocks on the GPU, so is not supported on WebAssembly.
+    pub fn render_to_image_tiled(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+    ) -> Result<Vec<u8>> {
+        self.render_tiles_to_image(device, queue, scene, params, (u32::MAX, u32::MAX))
+    }
+
+    /// Renders a scene in tiles of at most `tile_size` pixels, copying each into a CPU image.
+    fn render_tiles_to_image(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+        tile_size: (u32, u32),
+    ) -> Result<Vec<u8>> {
+        let stride = params.width as usize * 4;
+        let mut pixels = vec![0; stride * params.height as usize];
+        self.render_tiles(device, queue, scene, params, tile_size, |tile| {
+            let tile_pixels = tile.read_pixels(device, queue)?;
+            let tile_stride = tile.width as usize * 4;
+            for (row, tile_row) in tile_pixels.chunks_exact(tile_stride).enumerate() {
+                let start = (tile.y as usize + row) * stride + tile.x as usize * 4;
+                pixels[start..start + tile_stride].copy_from_slice(tile_row);
+            }
+            Ok(())
+        })?;
+        Ok(pixels)
+    }
+
+    /// Renders a scene in tiles of at most `tile_size` pixels, calling `callback` for each tile.
+    ///
+    /// Tiles are produced in row-major order, starting from the top left of the image described
+    /// by `params`. The pipeline only needs memory for a single tile, so this can be used to
+    /// export images of arbitrary size, or to generate the levels of a deep-zoom pyramid.
+    /// Tiles are made smaller than `tile_size` where needed to fit within the maximum texture
+    /// size of the device and [`MAX_TARGET_BINS`].
+    ///
+    /// The tile's texture is reused for the next tile once `callback` returns, so its contents
+    /// should be copied or read back (using [`RenderedTile::read_pixels`]) within the callback.
+    ///
+    /// Layers with filters are rendered whole before the first tile and shared by all of them,
+    /// so blurs and shadows are continuous across the edges of tiles. Unlike the tiles, each
+    /// filtered layer must fit within the maximum texture size of the device.
+    pub fn render_tiled(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+        tile_size: (NonZeroU32, NonZeroU32),
+        callback: impl FnMut(&RenderedTile<'_>) -> Result<()>,
+    ) -> Result<()> {
+        self.render_tiles(
+            device,
+            queue,
+            scene,
+            params,
+            (tile_size.0.get(), tile_size.1.get()),
+            callback,
+        )
+    }
+
+    /// Renders `scene` one tile at a time into an intermediate texture, calling `on_tile` after
+    /// each tile.
+    fn render_tiles(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+        (tile_width, tile_height): (u32, u32),
+        mut on_tile: impl FnMut(&RenderedTile<'_>) -> Result<()>,
+    ) -> Result<()> {
+        if params.width == 0 || params.height == 0 {
+            return Err(Error::EmptyTarget);
+        }
+        let (tile_width, tile_height) = max_tile_size(
+            device,
+            tile_width.min(params.width),
+            tile_height.min(params.height),
+        );
+        let tile_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("catalina.tile_target"),
+            size: wgpu::Extent3d {
+                width: tile_width,
+                height: tile_height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            format: TextureFormat::Rgba8Unorm,
+            view_formats: &[],
+        });
+        let tile_view = tile_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Filtered layers are rendered whole, once for all the tiles, so that filters which
+        // spread their content, such as blurs, are continuous across the edges of tiles.
+        let filtered = self.render_filter_layers(device, queue, scene, params)?;
+        let mut tile_scene = Scene::new();
+        tile_scene.flatten_shader = scene.flatten_shader.clone();
+        let mut y = 0;
+        while y < params.height {
+            let height = tile_height.min(params.height - y);
+            let mut x = 0;
+            while x < params.width {
+                let width = tile_width.min(params.width - x);
+                self.render_tile(
+                    device,
+                    queue,
+                    scene,
+                    &mut tile_scene,
+                    &tile_view,
+                    params,
+                    (x, y, width, height),
+                    false,
+                )?;
+                on_tile(&RenderedTile {
+                    x,
+                    y,
+                    width,
+                    height,
+                    texture: &tile_texture,
+                })?;
+                x += width;
+            }
+            y += height;
+        }
+        self.clear_filter_overrides(&filtered);
+        self.end_frame(queue);
+        Ok(())
+    }
+
+    /// Renders the region `(x, y, width, height)` of `scene` into the top left corner of
+    /// `view`, using `tile_scene` as scratch space.
+    ///
+    /// The filtered layers of `scene` are rendered whole if `render_filters` is set. Otherwise
+    /// they must already have been rendered with
+    /// [`render_filter_layers`](Self::render_filter_layers), which lets several tiles share
+    /// them.
+    pub(crate) fn render_tile(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        tile_scene: &mut Scene,
+        view: &TextureView,
+        params: &RenderParams,
+        (x, y, width, height): (u32, u32, u32, u32),
+        render_filters: bool,
+    ) -> Result<()> {
+        tile_scene.reset();
+        tile_scene.append(
+            scene,
+            Some(kurbo::Affine::translate((-f64::from(x), -f64::from(y)))),
+        );
+        if !render_filters {
+            tile_scene.clear_filter_layers();
+        }
+        let tile_params = RenderParams {
+            base_color: params.base_color,
+            width,
+            height,
+            antialiasing_method: params.antialiasing_method,
+            global_alpha: params.global_alpha,
+            dash_phase: params.dash_phase,
+            glow_amount: params.glow_amount,
+            dirty_rects: None,
+        };
+        self.render_frame_to_texture(device, queue, tile_scene, view, &tile_params)
+    }
+
+    /// Renders a scene to the target surface.
+    ///
+    /// This renders to an intermediate texture and then runs a render pass to blit to the
+    /// specified surface texture.
+    ///
+    /// The surface is assumed to be of the specified dimensions and have been configured with
+    /// the same format passed in the constructing [`RendererOptions`]' `surface_format`.
+    /// Panics if `surface_format` was `None`
+    ///
+    /// If `params` is smaller than the surface, the image is stretched to fill it, which
+    /// is used to render at a reduced resolution with [`render_to_surface_with_quality`].
+    ///
+    /// [`render_to_surface_with_quality`]: Self::render_to_surface_with_quality
+    pub fn render_to_surface(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        surface: &SurfaceTexture,
+        params: &RenderParams,
+        clear: bool,
+    ) -> Result<()> {
+        self.render_to_presented_texture(device, queue, scene, &surface.texture, params, clear)
+    }
+
+    /// Renders a scene to a texture which stands in for a surface, like
+    /// [`render_to_surface`](Self::render_to_surface).
+    ///
+    /// This is for textures which are presented by other means, such as the swapchain images of
+    /// an XR runtime. The texture must have the `surface_format` passed in the constructing
+    /// [`RendererOptions`] and the `RENDER_ATTACHMENT` usage. As for surfaces, only the
+    /// [`dirty_rects`](RenderParams::dirty_rects) of `params` are rendered again when the
+    /// previous frame had the same size.
+    pub fn render_to_presented_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &wgpu::Texture,
+        params: &RenderParams,
+        clear: bool,
+    ) -> Result<()> {
+        let width = params.width;
+        let height = params.height;
+        // TODO: implement clever resizing semantics here to avoid thrashing the memory allocator
+        // during resize, specifically on metal.
+        let previous = self
+            .target
+            .take()
+            .filter(|target| target.width == width && target.height == height);
+        // The rest of the output can only be kept from a previous frame of the same size.
+        let dirty_rects = previous
+            .as_ref()
+            .filter(|_| self.target_holds_frame)
+            .and(params.dirty_rects);
+        let target = previous.unwrap_or_else(|| TargetTexture::new(device, width, height));
+        match dirty_rects {
+            Some(dirty_rects) => {
+                self.render_dirty_rects(device, queue, scene, &target, params, &dirty_rects)?;
+            }
+            None => self.render_frame_to_texture(device, queue, scene, &target.view, params)?,
+        }
+        let blit = self
+            .blit
+            .as_ref()
+            .expect("renderer should have configured surface_format to use on a surface")
+            .0;
+        let mut recording = Recording::default();
+        let target_proxy = ImageProxy::new(
+            width,
+            height,
+            ImageFormat::from_wgpu(target.format)
+                .expect("`TargetTexture` always has a supported texture format"),
+        );
+        let surface_proxy = ImageProxy::new(
+            texture.width(),
+            texture.height(),
+            ImageFormat::from_wgpu(texture.format()).ok_or(Error::UnsupportedSurfaceFormat)?,
+        );
+        let composited = self.record_compositor(device, &mut recording, target_proxy);
+        let blit_source = composited
+            .as_ref()
+            .map_or(target_proxy, |(_, proxy)| *proxy);
+        let blit_params = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.blit_params",
+            bytemuck::bytes_of(&color_filter::blit_params(
+                params.global_alpha,
+                self.color_filter,
+            )),
+        ));
+        recording.draw(recording::DrawParams {
+            shader_id: blit,
+            instance_count: 1,
+            vertex_count: 6,
+            vertex_buffer: None,
+            resources: vec![ResourceProxy::Image(blit_source), blit_params],
+            target: surface_proxy,
+            clear_color: match clear {
+                true => Some([0., 0., 0., 0.]),
+                false => None,
+            },
+        });
+        recording.free_resource(blit_params);
+
+        let surface_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut external_resources = vec![
+            ExternalResource::Image(target_proxy, &target.view),
+            ExternalResource::Image(surface_proxy, &surface_view),
+        ];
+        if let Some((composited, proxy)) = &composited {
+            external_resources.push(ExternalResource::Image(*proxy, &composited.view));
+        }
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &external_resources,
+            "blit (render_to_surface)",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )?;
+        self.composited = composited.map(|(texture, _)| texture);
+        self.target = Some(target);
+        self.target_holds_frame = true;
+        self.end_frame(queue);
+        Ok(())
+    }
+
+    /// Records the compositor pass over `target`, if one is set, returning the texture it's
+    /// written to and its proxy.
+    fn record_compositor(
+        &mut self,
+        device: &Device,
+        recording: &mut Recording,
+        target: ImageProxy,
+    ) -> Option<(TargetTexture, ImageProxy)> {
+        let compositor = self.compositor.as_mut()?;
+        let texture = self
+            .composited
+            .take()
+            .filter(|texture| texture.width == target.width && texture.height == target.height)
+            .unwrap_or_else(|| TargetTexture::new(device, target.width, target.height));
+        let output = ImageProxy::new(target.width, target.height, ImageFormat::Rgba8);
+        compositor.record(
+            device,
+            &mut self.engine,
+            recording,
+            &self.compositor_uniforms,
+            target,
+            output,
+        );
+        Some((texture, output))
+    }
+
+    /// Renders the regions of `scene` in `dirty_rects` into `target`, keeping the rest of it.
+    fn render_dirty_rects(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        target: &TargetTexture,
+        params: &RenderParams,
+        dirty_rects: &DirtyRects,
+    ) -> Result<()> {
+        let bounds = kurbo::Rect::new(0.0, 0.0, f64::from(target.width), f64::from(target.height));
+        let regions: Vec<_> = dirty_rects
+            .rects()
+            .iter()
+            .map(|rect| rect.intersect(bounds).expand())
+            .filter(|rect| !rect.is_zero_area())
+            .collect();
+        if regions.is_empty() {
+            return Ok(());
+        }
+        let (max_width, max_height) = regions.iter().fold((0, 0), |(width, height), rect| {
+            (
+                width.max(rect.width() as u32),
+                height.max(rect.height() as u32),
+            )
+        });
+        // Each region is rendered into the top left of a scratch texture, then copied into place.
+        let scratch = TargetTexture::new(device, max_width, max_height);
+        let filtered = self.render_filter_layers(device, queue, scene, params)?;
+        let mut tile_scene = Scene::new();
+        tile_scene.flatten_shader = scene.flatten_shader.clone();
+        for region in regions {
+            let (x, y) = (region.x0 as u32, region.y0 as u32);
+            let (width, height) = (region.width() as u32, region.height() as u32);
+            self.render_tile(
+                device,
+                queue,
+                scene,
+                &mut tile_scene,
+                &scratch.view,
+                params,
+                (x, y, width, height),
+                false,
+            )?;
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("catalina.dirty_rect_copy"),
+            });
+            encoder.copy_texture_to_texture(
+                scratch.texture.as_image_copy(),
+                wgpu::TexelCopyTextureInfo {
+                    texture: &target.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+            queue.submit([encoder.finish()]);
+        }
+        self.clear_filter_overrides(&filtered);
+        Ok(())
+    }
+
+    /// Renders a scene to the target surface at the quality chosen by a [`QualityGovernor`].
+    ///
+    /// `params` describes the full quality render, at the size of the surface. At a reduced
+    /// resolution scale, the scene is rendered into a smaller image which is stretched to fill
+    /// the surface.
+    pub fn render_to_surface_with_quality(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        surface: &SurfaceTexture,
+        params: &RenderParams,
+        quality: Quality,
+    ) -> Result<()> {
+        let scale = quality.resolution_scale.clamp(0.0, 1.0);
+        if scale >= 1.0 && quality.antialiasing.is_none() {
+            return self.render_to_surface(device, queue, scene, surface, params, true);
+        }
+        let scaled = |extent: u32| ((extent as f32 * scale).ceil() as u32).clamp(1, extent.max(1));
+        let scaled_params = RenderParams {
+            base_color: params.base_color,
+            width: scaled(params.width),
+            height: scaled(params.height),
+            antialiasing_method: quality.antialiasing.unwrap_or(params.antialiasing_method),
+            global_alpha: params.global_alpha,
+            dash_phase: params.dash_phase,
+            glow_amount: params.glow_amount,
+            dirty_rects: None,
+        };
+        let mut scaled_scene = Scene::new();
+        scaled_scene.flatten_shader = scene.flatten_shader.clone();
+        scaled_scene.append(
+            scene,
+            Some(kurbo::Affine::scale_non_uniform(
+                f64::from(scaled_params.width) / f64::from(params.width.max(1)),
+                f64::from(scaled_params.height) / f64::from(params.height.max(1)),
+            )),
+        );
+        self.render_to_surface(device, queue, &scaled_scene, surface, &scaled_params, true)
+    }
+
+    /// Renders `scene` with the next sub-pixel jitter of `accumulator`, and adds it to the
+    /// running average.
+    ///
+    /// Calling this repeatedly for a static scene converges towards a very high quality
+    /// antialiased image, available from [`Accumulator::texture`]. `params` must have the same
+    /// size as the accumulator.
+    pub fn accumulate(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+        accumulator: &mut Accumulator,
+    ) -> Result<()> {
+        let (width, height) = (accumulator.width(), accumulator.height());
+        if params.width != width || params.height != height {
+            return Err(Error::AccumulatorSizeMismatch);
+        }
+        let mut jittered = Scene::new();
+        jittered.flatten_shader = scene.flatten_shader.clone();
+        jittered.append(scene, Some(kurbo::Affine::translate(accumulator.jitter())));
+        self.render_frame_to_texture(device, queue, &jittered, &accumulator.frame_view, params)?;
+        let shader = *self
+            .accumulate
+            .get_or_insert_with(|| accumulate::add_shader(device, &mut self.engine));
+        let frames = accumulator.add_frame();
+        let mut recording = Recording::default();
+        let config = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.accumulate_params",
+            bytemuck::bytes_of(&[width, height, frames, 0]),
+        ));
+        let frame = ImageProxy::new(width, height, ImageFormat::Rgba8);
+        let sum = BufferProxy::new(accumulator.sum.size(), "catalina.accumulate_sum");
+        let output = ImageProxy::new(width, height, ImageFormat::Rgba8);
+        recording.dispatch(
+            shader,
+            (
+                width.div_ceil(accumulate::WORKGROUP_SIZE),
+                height.div_ceil(accumulate::WORKGROUP_SIZE),
+                1,
+            ),
+            [
+                config,
+                ResourceProxy::Image(frame),
+                ResourceProxy::Buffer(sum),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &[
+                ExternalResource::Image(frame, &accumulator.frame_view),
+                ExternalResource::Buffer(sum, &accumulator.sum),
+                ExternalResource::Image(output, &accumulator.output_view),
+            ],
+            "accumulate",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )?;
+        self.end_frame(queue);
+        Ok(())
+    }
+
+    /// Renders the coverage of a scene to a single channel texture, for use as a mask.
+    ///
+    /// Every draw is treated as an opaque fill, so brushes are never evaluated, which makes
+    /// fine rasterization much cheaper than rendering colors. Clips and the opacity of layers
+    /// still apply. The base color and antialiasing method of `params` are ignored, as coverage
+    /// is always rendered with area antialiasing.
+    ///
+    /// The texture must have the size in `params`, the `R8Unorm` or `R16Float` format, and the
+    /// `RENDER_ATTACHMENT` usage.
+    pub fn render_coverage_to_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &wgpu::Texture,
+        params: &RenderParams,
+    ) -> Result<()> {
+        let format = texture.format();
+        if !coverage::is_supported_format(format) {
+            return Err(Error::UnsupportedCoverageFormat(format));
+        }
+        params.check_target_size()?;
+        let params = RenderParams {
+            antialiasing_method: AaConfig::Area,
+            ..*params
+        };
+        let (width, height) = (params.width, params.height);
+        if self.coverage.is_none() {
+            self.coverage = Some(coverage::CoverageShaders::new(device, &mut self.engine)?);
+        }
+        let shaders = self.coverage.as_mut().unwrap();
+        let fine_coverage = shaders.fine;
+        let copy = shaders.copy(device, &mut self.engine, format);
+        let target = self.take_scratch(device, width, height);
+
+        let mut render = self.new_render();
+        render.use_fine_variant(fine_coverage);
+        let (mut recording, output) = render::render_full(
+            &mut render,
+            scene,
+            &mut self.resolver,
+            &self.shaders,
+            &params,
+        );
+        self.image_atlas = render.image_atlas();
+        let coverage = *output.as_image().unwrap();
+        let mask = ImageProxy::new(
+            width,
+            height,
+            ImageFormat::from_wgpu(format).expect("coverage formats are supported images"),
         );
         recording.draw(recording::DrawParams {
-            shader_id: blit.0,
+            shader_id: copy,
             instance_count: 1,
             vertex_count: 6,
             vertex_buffer: None,
-            resources: vec![ResourceProxy::Image(target_proxy)],
-            target: surface_proxy,
-            clear_color: match clear {
-                true => Some([0., 0., 0., 0.]),
-                false => None,
-            },
+            resources: vec![ResourceProxy::Image(coverage)],
+            target: mask,
+            clear_color: None,
         });
-
-        let surface_view = surface
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mask_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         let external_resources = [
-            ExternalResource::Image(target_proxy, &target.view),
-            ExternalResource::Image(surface_proxy, &surface_view),
+            ExternalResource::Image(coverage, &target.view),
+            ExternalResource::Image(mask, &mask_view),
         ];
+        let result = self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &external_resources,
+            "render_coverage_to_texture",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        );
+        self.scratch = Some(target);
+        self.end_frame(queue);
+        result
+    }
+
+    /// Renders a scene to the target texture as a preview of how it would print, where
+    /// overlapping draws combine like inks instead of covering each other.
+    ///
+    /// Each draw is multiplied with the content below it, as with the
+    /// [`Multiply`](peniko::Mix::Multiply) blend mode, which is the effect of setting every
+    /// object to overprint, so the result doesn't depend on the order of the draws. Layers are
+    /// still composed with their own blend modes, and the draws within them are combined in
+    /// the same way. If `separation` is set, only the amount of that ink is shown, in black on
+    /// white.
+    ///
+    /// The antialiasing method of `params` is ignored, as the preview is always rendered with
+    /// area antialiasing. The texture has the same requirements as for
+    /// [`render_to_texture`](Self::render_to_texture).
+    pub fn render_overprint_preview_to_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &TextureView,
+        params: &RenderParams,
+        separation: Option<Separation>,
+    ) -> Result<()> {
+        params.check_target_size()?;
+        let params = RenderParams {
+            antialiasing_method: AaConfig::Area,
+            ..*params
+        };
+        let shaders = match self.overprint {
+            Some(shaders) => shaders,
+            None => *self
+                .overprint
+                .insert(overprint::OverprintShaders::new(device, &mut self.engine)?),
+        };
+        let filtered = self.render_filter_layers(device, queue, scene, &params)?;
+        let mut render = self.new_render();
+        render.use_fine_variant(shaders.fine);
+        let (mut recording, output) = render::render_full(
+            &mut render,
+            scene,
+            &mut self.resolver,
+            &self.shaders,
+            &params,
+        );
+        self.image_atlas = render.image_atlas();
+        let preview = *output.as_image().unwrap();
+        let result = match separation {
+            None => self.engine.run_recording(
+                device,
+                queue,
+                &recording,
+                &[ExternalResource::Image(preview, texture)],
+                "render_overprint_preview_to_texture",
+                #[cfg(feature = "wgpu-profiler")]
+                &mut self.profiler,
+            ),
+            Some(separation) => {
+                let (width, height) = (params.width, params.height);
+                let target = self.take_scratch(device, width, height);
+                let plate = ImageProxy::new(width, height, ImageFormat::Rgba8);
+                shaders.record_separation(&mut recording, separation, preview, plate);
+                let result = self.engine.run_recording(
+                    device,
+                    queue,
+                    &recording,
+                    &[
+                        ExternalResource::Image(preview, &target.view),
+                        ExternalResource::Image(plate, texture),
+                    ],
+                    "render_overprint_preview_to_texture",
+                    #[cfg(feature = "wgpu-profiler")]
+                    &mut self.profiler,
+                );
+                self.scratch = Some(target);
+                result
+            }
+        };
+        self.clear_filter_overrides(&filtered);
+        self.end_frame(queue);
+        result
+    }
+
+    /// Measures the luminance histogram and average color of a rendered frame, blocking until
+    /// they are read back.
+    ///
+    /// This is a separate pass over `texture`, which is typically the target of
+    /// [`Self::render_to_texture`], for uses such as auto-exposure or choosing colors which
+    /// contrast with the frame. `texture` must have the `TEXTURE_BINDING` usage, and one of
+    /// the `Rgba8Unorm`, `Rgba8UnormSrgb`, `Bgra8Unorm`, `Bgra8UnormSrgb` or `Rgba16Float`
+    /// formats. `Rgba8Unorm` and `Bgra8Unorm` textures are assumed to contain sRGB encoded
+    /// colors, like those rendered by Vello, and `Rgba16Float` textures linear colors, which
+    /// may be brighter than white.
+    ///
+    /// See [`Self::frame_statistics_async`] to avoid blocking, such as on WebAssembly.
+    pub fn frame_statistics(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        texture: &wgpu::Texture,
+    ) -> Result<FrameStatistics> {
+        util::block_on_wgpu(device, self.frame_statistics_async(device, queue, texture))
+    }
+
+    /// Measures the statistics of a rendered frame like [`Self::frame_statistics`], without
+    /// blocking while they are read back.
+    pub async fn frame_statistics_async(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        texture: &wgpu::Texture,
+    ) -> Result<FrameStatistics> {
+        let format = texture.format();
+        let linear = match format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm => false,
+            TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8UnormSrgb
+            | TextureFormat::Rgba16Float => true,
+            _ => return Err(Error::UnsupportedStatisticsFormat(format)),
+        };
+        let shader = *self
+            .statistics
+            .get_or_insert_with(|| statistics::shader(device, &mut self.engine));
+        let mut recording = Recording::default();
+        let frame = ImageProxy::new(texture.width(), texture.height(), ImageFormat::Rgba8);
+        let buffers = statistics::record(&mut recording, shader, frame, linear);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &[ExternalResource::Image(frame, &view)],
+            "frame_statistics",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )?;
+
+        let histogram = self
+            .engine
+            .get_download(buffers.histogram)
+            .expect("the histogram was downloaded")
+            .slice(..);
+        let sums = self
+            .engine
+            .get_download(buffers.sums)
+            .expect("the sums were downloaded")
+            .slice(..);
+        for slice in [histogram, sums] {
+            let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+            slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+            util::wait_on_wgpu(device, receiver.receive())
+                .await
+                .expect("channel was closed")?;
+        }
+        let statistics = buffers.read(&histogram.get_mapped_range(), &sums.get_mapped_range());
+        self.engine.free_download(buffers.histogram);
+        self.engine.free_download(buffers.sums);
+        Ok(statistics)
+    }
+
+    /// Renders the winding number of each pixel for a single path to a texture, instead of
+    /// shading it.
+    ///
+    /// This is for integrations which apply their own shading, such as a custom shading pass
+    /// which implements a fill rule or effect of its own. The winding number is signed, and
+    /// has a fractional part along the edges of the path where pixels are partially covered,
+    /// so the area coverage for the non-zero fill rule is `min(abs(winding), 1)`. Pixels
+    /// outside the path are zero. The base color and antialiasing method of `params` are
+    /// ignored.
+    ///
+    /// The texture must have the size in `params`, the `R32Float` format, and the
+    /// `STORAGE_BINDING` usage.
+    pub fn render_winding_to_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        path: &impl kurbo::Shape,
+        transform: kurbo::Affine,
+        texture: &wgpu::Texture,
+        params: &RenderParams,
+    ) -> Result<()> {
+        let format = texture.format();
+        if format != TextureFormat::R32Float {
+            return Err(Error::UnsupportedWindingFormat(format));
+        }
+        params.check_target_size()?;
+        let params = RenderParams {
+            base_color: peniko::color::palette::css::TRANSPARENT,
+            antialiasing_method: AaConfig::Area,
+            ..*params
+        };
+        let (coarse, fine) = match self.winding {
+            Some(shaders) => shaders,
+            None => *self
+                .winding
+                .insert(shaders::winding(device, &mut self.engine)?),
+        };
+        let mut scene = Scene::new();
+        scene.fill(
+            peniko::Fill::NonZero,
+            transform,
+            peniko::color::palette::css::WHITE,
+            None,
+            path,
+        );
+
+        let mut render = self.new_render();
+        render.use_coarse_variant(coarse);
+        render.use_fine_variant(fine);
+        let (recording, target) = render::render_full(
+            &mut render,
+            &scene,
+            &mut self.resolver,
+            &self.shaders,
+            &params,
+        );
+        self.image_atlas = render.image_atlas();
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let external_resources = [ExternalResource::Image(*target.as_image().unwrap(), &view)];
         self.engine.run_recording(
             device,
             queue,
             &recording,
             &external_resources,
-            "blit (render_to_surface)",
+            "render_winding_to_texture",
+            #[cfg(feature = "wgpu-profiler")]
+            &mut self.profiler,
+        )
+    }
+
+    /// Renders a scene to the target texture like [`render_to_texture`](Self::render_to_texture),
+    /// while measuring the cost of fine rasterization for each of its draw objects.
+    ///
+    /// This shows which draws of an application are expensive to render, where a GPU profiler
+    /// only shows which stages of the pipeline are. Measuring makes rendering much slower and
+    /// adds to the memory needed for the per-tile command lists, so this is intended for
+    /// profiling rather than for every frame. The scene is always rendered with area
+    /// antialiasing, whatever the method in `params`.
+    ///
+    /// This blocks on the GPU to read back the costs, so is not supported on WebAssembly, where
+    /// [`render_to_texture_with_costs_async`](Self::render_to_texture_with_costs_async) should
+    /// be used instead.
+    #[cfg(feature = "draw-costs")]
+    pub fn render_to_texture_with_costs(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &TextureView,
+        params: &RenderParams,
+    ) -> Result<DrawCosts> {
+        util::block_on_wgpu(
+            device,
+            self.render_to_texture_with_costs_async(device, queue, scene, texture, params),
+        )
+    }
+
+    /// Renders a scene while measuring the cost of each draw object like
+    /// [`render_to_texture_with_costs`](Self::render_to_texture_with_costs), without blocking
+    /// while the costs are read back.
+    #[cfg(feature = "draw-costs")]
+    pub async fn render_to_texture_with_costs_async(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        texture: &TextureView,
+        params: &RenderParams,
+    ) -> Result<DrawCosts> {
+        params.check_target_size()?;
+        let params = RenderParams {
+            antialiasing_method: AaConfig::Area,
+            ..*params
+        };
+        let (coarse, fine) = match self.draw_costs {
+            Some(shaders) => shaders,
+            None => *self
+                .draw_costs
+                .insert(shaders::draw_costs(device, &mut self.engine)?),
+        };
+        let encoding = scene.encoding();
+        // Layers which are still open are closed when the scene is resolved.
+        let tags: Vec<_> = encoding
+            .draw_tags
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(
+                catalina_encoding::DrawTag::END_CLIP,
+                encoding.n_open_clips as usize,
+            ))
+            .collect();
+
+        let mut recording = Recording::default();
+        let counters = tags.len().max(1) * draw_costs::COUNTERS_PER_DRAW;
+        let costs_buf = recording.upload("catalina.draw_costs", vec![0; counters * 4]);
+        let mut render = self.new_render();
+        render.use_coarse_variant(coarse);
+        render.use_fine_variant(fine);
+        render.use_costs_buf(costs_buf);
+        let (render_recording, target) = render::render_full(
+            &mut render,
+            scene,
+            &mut self.resolver,
+            &self.shaders,
+            &params,
+        );
+        self.image_atlas = render.image_atlas();
+        for command in render_recording.into_commands() {
+            recording.push(command);
+        }
+        recording.download(costs_buf);
+        recording.free_buffer(costs_buf);
+        let external_resources = [ExternalResource::Image(
+            *target.as_image().unwrap(),
+            texture,
+        )];
+        self.engine.run_recording(
+            device,
+            queue,
+            &recording,
+            &external_resources,
+            "render_to_texture_with_costs",
             #[cfg(feature = "wgpu-profiler")]
             &mut self.profiler,
         )?;
+
+        let download = self
+            .engine
+            .get_download(costs_buf)
+            .expect("the costs were downloaded");
+        let slice = download.slice(..);
+        let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+        slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+        util::wait_on_wgpu(device, receiver.receive())
+            .await
+            .expect("channel was closed")?;
+        let costs = {
+            let mapped = slice.get_mapped_range();
+            DrawCosts::new(
+                tags.into_iter(),
+                bytemuck::cast_slice(&mapped[..counters * 4]),
+            )
+        };
+        self.engine.free_download(costs_buf);
+        Ok(costs)
+    }
+
+    /// Describes the passes which would be used to render `scene` with `params`.
+    ///
+    /// This can be used by frame-graph based engines to understand the work done by Vello,
+    /// including the dependencies between passes and the sizes of the buffers they use.
+    /// Note that the sizes depend on the scene, so the description should not be reused
+    /// for a different scene.
+    ///
+    /// This doesn't change the state of the renderer. The scene is resolved without the
+    /// glyphs, gradients and images cached by earlier renders, so the passes are those of
+    /// its first render.
+    pub fn describe_passes(&self, scene: &Scene, params: &RenderParams) -> graph::RenderGraph {
+        let mut resolver = Resolver::new();
+        *resolver.uploads_mut() = self.resolver.uploads().clone();
+        let (recording, _) = render::render_full(
+            &mut Render::new(),
+            scene,
+            &mut resolver,
+            &self.shaders,
+            params,
+        );
+        self.engine.describe(&recording)
+    }
+
+    /// Prepares the renderer to draw scenes similar to `scene` without a first-frame hitch.
+    ///
+    /// This renders `scene` once into the renderer's intermediate target, which makes sure that
+    /// all pipelines needed for `params` are compiled, steady-state GPU buffers are allocated,
+    /// and the glyphs, gradient ramps and images used by `scene` are cached. It is intended to be
+    /// called with a representative scene while a loading screen is shown.
+    ///
+    /// The intermediate target is retained, so a subsequent call to
+    /// [`render_to_surface`](Self::render_to_surface) with the same dimensions will reuse it.
+    /// That frame is rendered in full, even if it has [`dirty_rects`](RenderParams::dirty_rects),
+    /// as the target doesn't hold a previous frame.
+    pub fn warmup(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        params: &RenderParams,
+    ) -> Result<()> {
+        let target = self
+            .target
+            .take()
+            .filter(|target| target.width == params.width && target.height == params.height)
+            .unwrap_or_else(|| TargetTexture::new(device, params.width, params.height));
+        self.render_to_texture(device, queue, scene, &target.view, params)?;
         self.target = Some(target);
-        #[cfg(feature = "wgpu-profiler")]
-        {
-            self.profiler.end_frame().unwrap();
-            if let Some(result) = self
-                .profiler
-                .process_finished_frame(queue.get_timestamp_period())
-            {
-                self.profile_result = Some(result);
-            }
-        }
+        self.target_holds_frame = false;
+        // Wait for the work to finish, so that the transient resources are returned to the pool
+        // before the first real frame.
+        device.poll(wgpu::Maintain::Wait);
         Ok(())
     }
 
@@ -646,31 +2631,152 @@ impl Renderer {
         }
     }
 
-    /// Reload the shaders. This should only be used during `vello` development
+    /// Returns an image which draws `texture`, such as a decoded video frame or the result of
+    /// another render pass, without copying its pixels through the CPU.
+    ///
+    /// The image has no pixel data of its own. In each frame it is drawn in, the current
+    /// contents of the texture are copied into the image atlas on the GPU, so the texture can
+    /// be written to between frames. The texture must have the
+    /// [`TextureFormat::Rgba8Unorm`] format with unpremultiplied alpha, and the
+    /// [`wgpu::TextureUsages::COPY_SRC`] usage. Pass the image to
+    /// [`override_image`](Self::override_image) with `None` to release the texture.
+    pub fn register_external_texture(&mut self, texture: &wgpu::Texture) -> Result<peniko::Image> {
+        if texture.format() != TextureFormat::Rgba8Unorm {
+            return Err(Error::UnsupportedExternalTextureFormat(texture.format()));
+        }
+        let (width, height) = (texture.width(), texture.height());
+        let max_size = self.resolver.max_atlas_size();
+        if width > max_size || height > max_size {
+            return Err(Error::ExternalTextureTooLarge(width, height));
+        }
+        let image = scene::renderer_image(Vec::new(), width, height);
+        self.override_image(
+            &image,
+            Some(wgpu::TexelCopyTextureInfoBase {
+                texture: texture.clone(),
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            }),
+        );
+        Ok(image)
+    }
+
+    /// Returns the queue of images waiting to be uploaded to the image atlas.
+    ///
+    /// By default, every image is uploaded in the first frame it is drawn in. Setting a
+    /// [budget](UploadQueue::set_bytes_per_frame) spreads the uploads of many new images over
+    /// several frames, with images which haven't been uploaded yet drawn as a placeholder.
+    /// Images can also be [queued](UploadQueue::queue) before they are drawn. Images larger
+    /// than the atlas, whose size is limited by the device, are
+    /// [scaled down](UploadQueue::set_oversized_images) by default.
+    pub fn uploads(&mut self) -> &mut UploadQueue {
+        self.resolver.uploads_mut()
+    }
+
+    /// Defers loading the glyph outlines, baking the gradient ramps and uploading the images
+    /// of draws which are entirely outside of the target until a frame in which they're
+    /// within it.
+    ///
+    /// This reduces the peak memory of scenes where most content is offscreen, such as long
+    /// documents which are encoded whole and scrolled with a transform. Images which come into
+    /// view are uploaded in that frame, subject to the [upload budget](Self::uploads), so may
+    /// briefly show their placeholder. Each draw is checked against the bounds of its own
+    /// path, ignoring any clips.
+    pub fn set_deferred_resolve(&mut self, enabled: bool) {
+        self.deferred_resolve = enabled;
+    }
+
+    /// Creates a [`Render`] which writes to the image atlas kept from the previous frame.
+    fn new_render(&mut self) -> Render {
+        let mut render = Render::new();
+        render.reuse_image_atlas(self.image_atlas.take());
+        // Overridden images are copied into the atlas in every frame, as their textures may
+        // have changed.
+        render.set_dynamic_images(self.engine.image_overrides.keys().copied().collect());
+        render.set_cull_to_target(self.deferred_resolve);
+        render
+    }
+
+    /// Recompiles the shaders from [`catalina_shaders::compile::shader_dir`] and rebuilds
+    /// the renderer's pipelines with them, so that changes to the WGSL sources can be seen
+    /// without restarting.
+    ///
+    /// The shaders are validated before any pipeline is replaced, so if this returns an
+    /// error the renderer keeps rendering with its previous shaders. Errors in the WGSL are
+    /// reported as [`Error::ShaderCompilation`], with the diagnostic for each shader which
+    /// failed, and errors creating the pipelines as [`Error::WgpuErrorFromScope`].
+    ///
+    /// Shader variants which are only created when first used, such as for
+    /// [`Self::render_coverage_to_texture`], are compiled from the new sources the next time
+    /// they're used. Images kept in the atlas are uploaded again in the next frame.
+    ///
+    /// This blocks until the device has validated the pipelines, so isn't available on
+    /// WebAssembly. Calling it when the shader sources change, such as from a file watcher,
+    /// is left to the application.
     #[cfg(feature = "hot_reload")]
-    #[doc(hidden)] // End-users of Vello should not have `hot_reload` enabled.
-    pub async fn reload_shaders(&mut self, device: &Device) -> Result<(), Error> {
-        device.push_error_scope(wgpu::ErrorFilter::Validation);
+    pub fn reload_shaders(&mut self, device_handle: &util::DeviceHandle) -> Result<(), Error> {
+        let device = &device_handle.device;
         let mut engine = WgpuEngine::new(self.options.use_cpu);
+        if let Some(frames) = self.options.frames_in_flight {
+            engine.set_frames_in_flight(frames);
+        }
+        if let Some(cache) = &self.options.disk_cache {
+            engine.set_pipeline_cache(cache.load_pipelines(device));
+        }
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
         // We choose not to initialise these shaders in parallel, to ensure the error scope works correctly
-        let shaders = shaders::full_shaders(device, &mut engine, &self.options)?;
-        let blit = self
-            .options
-            .surface_format
-            .map(|surface_format| BlitPipeline::new(device, surface_format, &mut engine))
-            .transpose()?;
+        let pipelines =
+            shaders::full_shaders(device, &mut engine, &self.options, &self.custom_paints)
+                .and_then(|shaders| {
+                    let small_scene_shaders = match self.options.small_scenes {
+                        Some(_) if !self.options.use_cpu => {
+                            Some(shaders::small_scene_shaders(device, &mut engine, &shaders)?)
+                        }
+                        _ => None,
+                    };
+                    let blit = self
+                        .options
+                        .surface_format
+                        .map(|surface_format| {
+                            BlitPipeline::new(device, surface_format, &mut engine)
+                        })
+                        .transpose()?;
+                    Ok((shaders, small_scene_shaders, blit))
+                });
         #[cfg(feature = "debug_layers")]
         let debug = self
             .options
             .surface_format
             .map(|format| debug::DebugRenderer::new(device, format, &mut engine));
-        let error = device.pop_error_scope().await;
+        // The scope is popped even if compilation failed, so that it doesn't catch errors
+        // from later frames.
+        let error = util::block_on_wgpu(device, device.pop_error_scope());
+        let (shaders, small_scene_shaders, blit) = pipelines?;
         if let Some(error) = error {
             return Err(error.into());
         }
         self.engine = engine;
         self.shaders = shaders;
+        self.small_scene_shaders = small_scene_shaders;
         self.blit = blit;
+        if let Some(compositor) = &mut self.compositor {
+            compositor.shader = None;
+        }
+        self.accumulate = None;
+        self.downsample = None;
+        self.downscale = None;
+        self.filters = None;
+        self.coverage = None;
+        self.overprint = None;
+        self.statistics = None;
+        self.winding = None;
+        #[cfg(feature = "draw-costs")]
+        {
+            self.draw_costs = None;
+        }
+        // The kept atlas belonged to the old engine.
+        self.image_atlas = None;
         #[cfg(feature = "debug_layers")]
         {
             self.debug = debug;
@@ -737,13 +2843,17 @@ impl Renderer {
         texture: &TextureView,
         params: &RenderParams,
     ) -> Result<RenderResult> {
-        let mut render = Render::new();
+        params.check_target_size()?;
+        let filtered = self.render_filter_layers(device, queue, scene, params)?;
+        let mut render = self.new_render();
         // TODO: turn this on; the download feature interacts with CPU dispatch.
         // Currently this is always enabled when the `debug_layers` setting is enabled as the bump
-        // counts are used for debug visualiation.
-        let robust = cfg!(feature = "debug_layers");
+        // counts are used for debug visualiation, and when `debug-logs` is enabled so that
+        // overflows are logged.
+        let robust = cfg!(any(feature = "debug_layers", feature = "debug-logs"));
         let recording =
             render.render_encoding_coarse(scene, &mut self.resolver, &self.shaders, params, robust);
+        self.image_atlas = render.image_atlas();
         let target = render.out_image();
         let bump_buf = render.bump_buf();
         #[cfg(feature = "debug_layers")]
@@ -763,10 +2873,21 @@ impl Renderer {
             let buf_slice = bump_buf.slice(..);
             let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
             buf_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
-            receiver.receive().await.expect("channel was closed")?;
+            util::wait_on_wgpu(device, receiver.receive())
+                .await
+                .expect("channel was closed")?;
             let mapped = buf_slice.get_mapped_range();
             bump = Some(bytemuck::pod_read_unaligned(&mapped));
         }
+        #[cfg(feature = "debug-logs")]
+        if let Some(bump) = bump.as_ref().filter(|bump| bump.failed != 0) {
+            log::warn!(
+                "Bump allocation overflow (failed stages bitmask {:#x}) for {}x{} frame: {bump:?}",
+                bump.failed,
+                params.width,
+                params.height
+            );
+        }
         // TODO: apply logic to determine whether we need to rerun coarse, and also
         // allocate the blend stack as needed.
         self.engine.free_download(bump_buf);
@@ -783,6 +2904,7 @@ impl Renderer {
             #[cfg(feature = "wgpu-profiler")]
             &mut self.profiler,
         )?;
+        self.clear_filter_overrides(&filtered);
         Ok(RenderResult {
             bump,
             #[cfg(feature = "debug_layers")]
@@ -836,7 +2958,8 @@ impl Renderer {
         let blit = self
             .blit
             .as_ref()
-            .expect("renderer should have configured surface_format to use on a surface");
+            .expect("renderer should have configured surface_format to use on a surface")
+            .0;
         let mut recording = Recording::default();
         let target_proxy = ImageProxy::new(
             width,
@@ -845,23 +2968,35 @@ impl Renderer {
                 .expect("`TargetTexture` always has a supported texture format"),
         );
         let surface_proxy = ImageProxy::new(
-            width,
-            height,
+            surface.texture.width(),
+            surface.texture.height(),
             ImageFormat::from_wgpu(surface.texture.format())
                 .ok_or(Error::UnsupportedSurfaceFormat)?,
         );
+        let composited = self.record_compositor(device, &mut recording, target_proxy);
+        let blit_source = composited
+            .as_ref()
+            .map_or(target_proxy, |(_, proxy)| *proxy);
+        let blit_params = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.blit_params",
+            bytemuck::bytes_of(&color_filter::blit_params(
+                params.global_alpha,
+                self.color_filter,
+            )),
+        ));
         recording.draw(recording::DrawParams {
-            shader_id: blit.0,
+            shader_id: blit,
             instance_count: 1,
             vertex_count: 6,
             vertex_buffer: None,
-            resources: vec![ResourceProxy::Image(target_proxy)],
+            resources: vec![ResourceProxy::Image(blit_source), blit_params],
             target: surface_proxy,
             clear_color: match clear {
                 true => Some([0., 0., 0., 0.]),
                 false => None,
             },
         });
+        recording.free_resource(blit_params);
 
         #[cfg(feature = "debug_layers")]
         {
@@ -893,10 +3028,13 @@ impl Renderer {
         let surface_view = surface
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
-        let external_resources = [
+        let mut external_resources = vec![
             ExternalResource::Image(target_proxy, &target.view),
             ExternalResource::Image(surface_proxy, &surface_view),
         ];
+        if let Some((texture, proxy)) = &composited {
+            external_resources.push(ExternalResource::Image(*proxy, &texture.view));
+        }
         self.engine.run_recording(
             device,
             queue,
@@ -906,27 +3044,63 @@ impl Renderer {
             #[cfg(feature = "wgpu-profiler")]
             &mut self.profiler,
         )?;
+        self.composited = composited.map(|(texture, _)| texture);
 
-        #[cfg(feature = "wgpu-profiler")]
-        {
-            self.profiler.end_frame().unwrap();
-            if let Some(result) = self
-                .profiler
-                .process_finished_frame(queue.get_timestamp_period())
-            {
-                self.profile_result = Some(result);
-            }
-        }
+        self.end_frame(queue);
 
         self.target = Some(target);
+        self.target_holds_frame = true;
         Ok(result.bump)
     }
 }
 
+/// A single tile of an image rendered by [`Renderer::render_tiled`].
+#[cfg(feature = "wgpu")]
+#[derive(Debug)]
+pub struct RenderedTile<'a> {
+    /// The horizontal offset of the tile within the full image, in pixels.
+    pub x: u32,
+    /// The vertical offset of the tile within the full image, in pixels.
+    pub y: u32,
+    /// The width of the tile, in pixels.
+    pub width: u32,
+    /// The height of the tile, in pixels.
+    pub height: u32,
+    /// The texture containing the tile in its top left corner.
+    ///
+    /// This has the [`Rgba8Unorm`](TextureFormat::Rgba8Unorm) format and the
+    /// [`COPY_SRC`](wgpu::TextureUsages::COPY_SRC) usage, and may be larger than the tile.
+    pub texture: &'a wgpu::Texture,
+}
+
+#[cfg(feature = "wgpu")]
+impl RenderedTile<'_> {
+    /// Reads back the pixels of the tile as unpadded RGBA8 data, with rows from top to bottom.
+    ///
+    /// This blocks on the GPU, so is not supported on WebAssembly, where
+    /// [`read_pixels_async`](Self::read_pixels_async) should be used instead.
+    pub fn read_pixels(&self, device: &Device, queue: &Queue) -> Result<Vec<u8>> {
+        util::block_on_wgpu(device, self.read_pixels_async(device, queue))
+    }
+
+    /// Reads back the pixels of the tile like [`read_pixels`](Self::read_pixels), without
+    /// blocking.
+    pub async fn read_pixels_async(&self, device: &Device, queue: &Queue) -> Result<Vec<u8>> {
+        let size = wgpu::Extent3d {
+            width: self.width,
+            height: self.height,
+            depth_or_array_layers: 1,
+        };
+        util::read_texture_region(device, queue, self.texture, size, 4).await
+    }
+}
+
 #[cfg(feature = "wgpu")]
 /// A cross-backend representation of a Target Texture.
 /// At the moment, this works a utility to create new textures easily in WebGPU.
 pub struct TargetTexture {
+    /// The WebGPU `Texture`.
+    texture: wgpu::Texture,
     /// The WebGPU `TextureView`.
     view: TextureView,
     /// The texture's width.
@@ -951,12 +3125,16 @@ impl TargetTexture {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
             format,
             view_formats: &[],
         });
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         Self {
+            texture,
             view,
             width,
             height,
@@ -972,8 +3150,14 @@ struct BlitPipeline(ShaderId);
 impl BlitPipeline {
     fn new(device: &Device, format: TextureFormat, engine: &mut WgpuEngine) -> Result<Self> {
         const SHADERS: &str = r#"
+            struct VertexOutput {
+                @builtin(position) position: vec4<f32>,
+                // The position within the output, from (0, 0) at the top left to (1, 1).
+                @location(0) uv: vec2<f32>,
+            }
+
             @vertex
-            fn vs_main(@builtin(vertex_index) ix: u32) -> @builtin(position) vec4<f32> {
+            fn vs_main(@builtin(vertex_index) ix: u32) -> VertexOutput {
                 // Generate a full screen quad in normalized device coordinates
                 var vertex = vec2(-1.0, 1.0);
                 switch ix {
@@ -988,21 +3172,70 @@ impl BlitPipeline {
                     }
                     default: {}
                 }
-                return vec4(vertex, 0.0, 1.0);
+                return VertexOutput(vec4(vertex, 0.0, 1.0), vec2(0.5 + 0.5 * vertex.x, 0.5 - 0.5 * vertex.y));
             }
 
             @group(0) @binding(0)
             var fine_output: texture_2d<f32>;
 
+            fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+                return select(pow((c + 0.055) / 1.055, vec3(2.4)), c / 12.92, c <= vec3(0.04045));
+            }
+
+            struct BlitParams {
+                // The opacity of the whole output.
+                alpha: f32,
+                // The contrast factor of the color filter, applied around middle gray.
+                contrast: f32,
+                // Whether the color filter is applied, as 0 or 1.
+                use_filter: f32,
+                _padding: f32,
+                // The rows of the matrix of the color filter, applied in linear sRGB.
+                matrix: array<vec4<f32>, 3>,
+            }
+
+            @group(0) @binding(1)
+            var<uniform> params: BlitParams;
+
+            fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+                return select(1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3(0.0031308));
+            }
+
+            fn color_filter(c: vec3<f32>) -> vec3<f32> {
+                let linear = srgb_to_linear(c);
+                let filtered = vec3(
+                    dot(params.matrix[0].xyz, linear),
+                    dot(params.matrix[1].xyz, linear),
+                    dot(params.matrix[2].xyz, linear),
+                );
+                let srgb = linear_to_srgb(clamp(filtered, vec3(0.0), vec3(1.0)));
+                return clamp((srgb - 0.5) * params.contrast + 0.5, vec3(0.0), vec3(1.0));
+            }
+
             @fragment
-            fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
-                let rgba_sep = textureLoad(fine_output, vec2<i32>(pos.xy), 0);
-                return vec4(rgba_sep.rgb * rgba_sep.a, rgba_sep.a);
+            fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+                // The output may be larger than the rendered image, such as when rendering at a
+                // reduced resolution while interacting, so scale with nearest neighbour sampling.
+                let dims = vec2<i32>(textureDimensions(fine_output));
+                let coords = min(vec2<i32>(in.uv * vec2<f32>(dims)), dims - 1);
+                let rgba_sep = textureLoad(fine_output, coords, 0);
+                var rgb = rgba_sep.rgb;
+                if params.use_filter != 0.0 {
+                    rgb = color_filter(rgb);
+                }
+                if LINEAR_OUTPUT {
+                    rgb = srgb_to_linear(rgb);
+                }
+                return vec4(rgb * rgba_sep.a, rgba_sep.a) * params.alpha;
             }
         "#;
+        // The rendered colors are sRGB encoded, but sRGB surfaces encode what is written to
+        // them, and floating point surfaces are linear.
+        let linear_output = format.is_srgb() || format == TextureFormat::Rgba16Float;
+        let source = format!("const LINEAR_OUTPUT: bool = {linear_output};\n{SHADERS}");
         let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("blit shaders"),
-            source: wgpu::ShaderSource::Wgsl(SHADERS.into()),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
         });
         let shader_id = engine.add_render_shader(
             device,
@@ -1017,12 +3250,15 @@ impl BlitPipeline {
                 write_mask: wgpu::ColorWrites::ALL,
             },
             None,
-            &[(
-                BindType::ImageRead(
-                    ImageFormat::from_wgpu(format).ok_or(Error::UnsupportedSurfaceFormat)?,
+            &[
+                (
+                    BindType::ImageRead(
+                        ImageFormat::from_wgpu(format).ok_or(Error::UnsupportedSurfaceFormat)?,
+                    ),
+                    wgpu::ShaderStages::FRAGMENT,
                 ),
-                wgpu::ShaderStages::FRAGMENT,
-            )],
+                (BindType::Uniform, wgpu::ShaderStages::FRAGMENT),
+            ],
         );
         Ok(Self(shader_id))
     }
@@ -1053,3 +3289,66 @@ impl<'a> DebugDownloads<'a> {
         Ok(Self { lines })
     }
 }
+
+#[cfg(all(test, feature = "wgpu"))]
+mod tests {
+    use std::sync::Arc;
+
+    use catalina_encoding::Resolver;
+    use peniko::kurbo::{Affine, Rect};
+    use peniko::{color::palette, Blob, Fill, Font};
+
+    use super::{AaConfig, Glyph, RenderParams, Scene, SmallSceneLimits};
+
+    const ROBOTO: &[u8] = include_bytes!("../../examples/assets/roboto/Roboto-Regular.ttf");
+    /// The glyph of `A` in Roboto.
+    const GLYPH_A: u32 = 37;
+
+    fn is_small(scene: &Scene) -> bool {
+        let params = RenderParams {
+            base_color: palette::css::WHITE,
+            width: 128,
+            height: 128,
+            antialiasing_method: AaConfig::Area,
+            global_alpha: 1.0,
+            dash_phase: 0.0,
+            glow_amount: 1.0,
+            dirty_rects: None,
+        };
+        let mut packed = Vec::new();
+        let (layout, _, _) = Resolver::new().resolve(scene.encoding(), &mut packed);
+        SmallSceneLimits::default().contains(&layout, &packed, &params)
+    }
+
+    #[test]
+    fn small_scenes_count_glyph_outlines() {
+        let limits = SmallSceneLimits::default();
+        let mut scene = Scene::new();
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            palette::css::RED,
+            None,
+            &Rect::new(0.0, 0.0, 16.0, 16.0),
+        );
+        assert!(is_small(&scene));
+        let font = Font::new(Blob::new(Arc::new(ROBOTO)), 0);
+        scene
+            .draw_glyphs(&font)
+            .font_size(8.0)
+            .brush(palette::css::BLACK)
+            .draw(
+                Fill::NonZero,
+                (0..40_u8).map(|i| Glyph {
+                    id: GLYPH_A,
+                    x: f32::from(i % 20) * 6.0,
+                    y: f32::from(i / 20 + 1) * 10.0,
+                }),
+            );
+        // The outlines of the glyphs are only added when the scene is resolved.
+        let encoding = scene.encoding();
+        assert!(encoding.n_paths <= limits.max_paths);
+        assert!(encoding.n_path_segments <= limits.max_path_segments);
+        assert!(!is_small(&scene));
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Culling of windows which are hidden behind opaque windows, for compositors.
+
+use peniko::kurbo::{Affine, Rect};
+
+use crate::Scene;
+
+/// A window to be composited by an [`OcclusionCuller`].
+#[derive(Clone, Copy)]
+pub struct CompositorWindow<'a> {
+    /// The content of the window, in window coordinates.
+    pub scene: &'a Scene,
+    /// The transform from window coordinates to the output.
+    pub transform: Affine,
+    /// The bounds of the content, in window coordinates.
+    ///
+    /// The window is culled when these bounds are covered, so the scene must not draw outside
+    /// of them.
+    pub bounds: Rect,
+    /// The regions of the window which are fully opaque, in window coordinates, such as from
+    /// the `opaque_region` of a Wayland surface.
+    ///
+    /// These hide the windows behind them. They are only used while the transform keeps them
+    /// axis-aligned, as rotated or skewed windows are rare enough that they aren't worth
+    /// occluding with.
+    pub opaque: &'a [Rect],
+}
+
+impl std::fmt::Debug for CompositorWindow<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompositorWindow")
+            .field("transform", &self.transform)
+            .field("bounds", &self.bounds)
+            .field("opaque", &self.opaque)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> CompositorWindow<'a> {
+    /// Creates a window drawing `scene` within `bounds`, with no transform and no opaque
+    /// regions.
+    pub fn new(scene: &'a Scene, bounds: Rect) -> Self {
+        Self {
+            scene,
+            transform: Affine::IDENTITY,
+            bounds,
+            opaque: &[],
+        }
+    }
+
+    /// Builder method for setting the transform to the output.
+    #[must_use]
+    pub fn with_transform(mut self, transform: Affine) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Builder method for setting the opaque regions.
+    ///
+    /// A window which is opaque throughout can pass its bounds, with
+    /// [`std::slice::from_ref`].
+    #[must_use]
+    pub fn with_opaque(mut self, opaque: &'a [Rect]) -> Self {
+        self.opaque = opaque;
+        self
+    }
+}
+
+/// Composes the scenes of a stack of windows, leaving out those which are entirely hidden
+/// behind opaque windows, and finds the region of each window which remains visible.
+///
+/// A compositor, such as for Wayland, can then skip encoding the content of hidden windows,
+/// and use the visible regions for its damage tracking or for direct scanout. Windows which
+/// are only partly covered are drawn whole, as coarse rasterization already skips the tiles of
+/// them which are behind opaque fills.
+///
+/// The culler keeps its allocations between frames, so should be reused.
+#[derive(Clone, Debug, Default)]
+pub struct OcclusionCuller {
+    /// The opaque regions of the windows in front of the window being culled, in output
+    /// coordinates.
+    occluders: Vec<Rect>,
+    /// The visible regions of each window, in output coordinates.
+    visible: Vec<Vec<Rect>>,
+    /// The region being subtracted from, kept for its allocation.
+    scratch: Vec<Rect>,
+}
+
+impl OcclusionCuller {
+    /// Creates a culler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the windows which are visible within `viewport` to `target`.
+    ///
+    /// The `windows` are ordered from back to front, as they would be drawn. Afterwards,
+    /// [`visible_region`](Self::visible_region) gives the region of each of them which can be
+    /// seen.
+    pub fn compose(
+        &mut self,
+        target: &mut Scene,
+        viewport: Rect,
+        windows: &[CompositorWindow<'_>],
+    ) {
+        self.occluders.clear();
+        self.visible.resize_with(windows.len(), Vec::new);
+        // Walk from front to back, removing the region covered by the windows in front.
+        for (window, visible) in windows.iter().zip(&mut self.visible).rev() {
+            visible.clear();
+            let bounds = window
+                .transform
+                .transform_rect_bbox(window.bounds)
+                .intersect(viewport);
+            if bounds.is_zero_area() || !bounds.is_finite() {
+                continue;
+            }
+            visible.push(bounds);
+            for occluder in &self.occluders {
+                subtract(visible, *occluder, &mut self.scratch);
+                if visible.is_empty() {
+                    break;
+                }
+            }
+            if visible.is_empty() || !is_axis_aligned(window.transform) {
+                continue;
+            }
+            for opaque in window.opaque {
+                let opaque = window
+                    .transform
+                    .transform_rect_bbox(opaque.intersect(window.bounds))
+                    .intersect(viewport);
+                if !opaque.is_zero_area() && opaque.is_finite() {
+                    self.occluders.push(opaque);
+                }
+            }
+        }
+        for (window, visible) in windows.iter().zip(&self.visible) {
+            if !visible.is_empty() {
+                target.append(window.scene, Some(window.transform));
+            }
+        }
+    }
+
+    /// Returns the region of the window at `index` in the last call to
+    /// [`compose`](Self::compose) which is visible, in output coordinates, as rectangles which
+    /// don't overlap.
+    ///
+    /// This is empty for windows which were culled.
+    pub fn visible_region(&self, index: usize) -> &[Rect] {
+        self.visible.get(index).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns `true` if any of the window at `index` was visible, so was drawn.
+    pub fn is_visible(&self, index: usize) -> bool {
+        !self.visible_region(index).is_empty()
+    }
+}
+
+/// Returns `true` if `transform` maps axis-aligned rectangles to axis-aligned rectangles.
+fn is_axis_aligned(transform: Affine) -> bool {
+    let [a, b, c, d, _, _] = transform.as_coeffs();
+    (b == 0.0 && c == 0.0) || (a == 0.0 && d == 0.0)
+}
+
+/// Removes `occluder` from `region`, a set of rectangles which don't overlap.
+fn subtract(region: &mut Vec<Rect>, occluder: Rect, scratch: &mut Vec<Rect>) {
+    scratch.clear();
+    for rect in region.drain(..) {
+        let overlap = rect.intersect(occluder);
+        if overlap.is_zero_area() {
+            scratch.push(rect);
+            continue;
+        }
+        // The parts above and below the overlap span the whole width, and the parts to its
+        // left and right fill the rows in between.
+        let pieces = [
+            Rect::new(rect.x0, rect.y0, rect.x1, overlap.y0),
+            Rect::new(rect.x0, overlap.y1, rect.x1, rect.y1),
+            Rect::new(rect.x0, overlap.y0, overlap.x0, overlap.y1),
+            Rect::new(overlap.x1, overlap.y0, rect.x1, overlap.y1),
+        ];
+        scratch.extend(pieces.into_iter().filter(|piece| !piece.is_zero_area()));
+    }
+    std::mem::swap(region, scratch);
+}
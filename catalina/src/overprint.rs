@@ -0,0 +1,147 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Previews of printed output, where overlapping inks combine rather than covering each other.
+
+use std::borrow::Cow;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::Device;
+
+use crate::low_level::{BindType, ImageFormat, ImageProxy, Recording, ResourceProxy, ShaderId};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+use crate::{shaders, Error};
+
+/// The size of each side of the workgroups of the separation shader.
+const WORKGROUP_SIZE: u32 = 16;
+
+/// Converts an overprint preview into the amount of one process ink, shown as that ink would
+/// print in black on white paper.
+const SEPARATE_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        // 0 to 3 for cyan, magenta, yellow and black.
+        channel: u32,
+        _padding: u32,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var preview: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        let coords = vec2<i32>(id.xy);
+        let color = textureLoad(preview, coords, 0);
+        // Transparent areas are unprinted paper.
+        let rgb = mix(vec3(1.0), color.rgb, color.a);
+        let black = 1.0 - max(rgb.r, max(rgb.g, rgb.b));
+        var ink = black;
+        if params.channel < 3u {
+            ink = select(0.0, (1.0 - rgb[params.channel] - black) / (1.0 - black), black < 1.0);
+        }
+        textureStore(output, coords, vec4(vec3(1.0 - ink), 1.0));
+    }
+"#;
+
+/// A process ink plate, which [`Renderer::render_overprint_preview_to_texture`] can show on
+/// its own.
+///
+/// The plates are separated from the preview with a simple conversion with full black
+/// generation, rather than a color profile of the press, so they show where each ink is used
+/// rather than exact ink amounts.
+///
+/// [`Renderer::render_overprint_preview_to_texture`]: crate::Renderer::render_overprint_preview_to_texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Separation {
+    /// The cyan plate.
+    Cyan,
+    /// The magenta plate.
+    Magenta,
+    /// The yellow plate.
+    Yellow,
+    /// The black plate.
+    Black,
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct SeparateParams {
+    width: u32,
+    height: u32,
+    channel: u32,
+    _padding: u32,
+}
+
+/// The shaders used by [`Renderer::render_overprint_preview_to_texture`](crate::Renderer::render_overprint_preview_to_texture),
+/// which are created on first use.
+#[derive(Clone, Copy)]
+pub(crate) struct OverprintShaders {
+    /// The variant of fine rasterization which combines draws like overlapping inks.
+    pub(crate) fine: ShaderId,
+    separate: ShaderId,
+}
+
+impl OverprintShaders {
+    pub(crate) fn new(device: &Device, engine: &mut WgpuEngine) -> Result<Self, Error> {
+        let separate = engine.add_compute_shader(
+            device,
+            "catalina.overprint_separate",
+            Cow::Borrowed(SEPARATE_SHADER),
+            &[
+                BindType::Uniform,
+                BindType::ImageRead(ImageFormat::Rgba8),
+                BindType::Image(ImageFormat::Rgba8),
+            ],
+            CpuShaderType::Missing,
+        );
+        Ok(Self {
+            fine: shaders::fine_overprint(device, engine)?,
+            separate,
+        })
+    }
+
+    /// Records a pass which writes `separation` of `preview` to `output`, which must have the
+    /// same size.
+    pub(crate) fn record_separation(
+        self,
+        recording: &mut Recording,
+        separation: Separation,
+        preview: ImageProxy,
+        output: ImageProxy,
+    ) {
+        let params = SeparateParams {
+            width: output.width,
+            height: output.height,
+            channel: separation as u32,
+            _padding: 0,
+        };
+        let config = ResourceProxy::Buffer(recording.upload_uniform(
+            "catalina.overprint_separate_params",
+            bytemuck::bytes_of(&params),
+        ));
+        recording.dispatch(
+            self.separate,
+            (
+                output.width.div_ceil(WORKGROUP_SIZE),
+                output.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            ),
+            [
+                config,
+                ResourceProxy::Image(preview),
+                ResourceProxy::Image(output),
+            ],
+        );
+        recording.free_resource(config);
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Retained raster layers which strokes are painted into, for painting applications.
+
+use peniko::kurbo::{Affine, Rect};
+use peniko::{color::palette, BlendMode, Compose, Image, Mix};
+use wgpu::{Device, Queue, Texture};
+
+use crate::scene::renderer_image;
+use crate::{AaConfig, RenderParams, Renderer, Result, Scene};
+
+/// How newly painted content is combined with the existing content of a [`PaintLayer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaintMode {
+    /// Paint over the existing content.
+    Normal,
+    /// Paint only behind the existing content, where it is transparent.
+    Behind,
+    /// Remove the existing content where painted, in proportion to the painted alpha.
+    Erase,
+    /// Multiply the existing content by the painted colors.
+    Multiply,
+    /// Paint over the existing content, without changing its alpha, so that transparent
+    /// areas stay transparent.
+    LockAlpha,
+    /// Combine with an arbitrary blend mode.
+    Blend(BlendMode),
+}
+
+impl From<PaintMode> for BlendMode {
+    fn from(mode: PaintMode) -> Self {
+        match mode {
+            PaintMode::Normal => Self::new(Mix::Normal, Compose::SrcOver),
+            PaintMode::Behind => Self::new(Mix::Normal, Compose::DestOver),
+            PaintMode::Erase => Self::new(Mix::Normal, Compose::DestOut),
+            PaintMode::Multiply => Self::new(Mix::Multiply, Compose::SrcOver),
+            PaintMode::LockAlpha => Self::new(Mix::Normal, Compose::SrcAtop),
+            PaintMode::Blend(blend) => blend,
+        }
+    }
+}
+
+/// A raster layer which retains everything painted into it on the GPU.
+///
+/// Painting applications can draw each finished stroke into a layer with [`paint`](Self::paint)
+/// and then discard it, instead of keeping an ever-growing list of strokes in a [`Scene`].
+/// The layer is displayed by drawing its [`image`](Self::image) into a scene, using the same
+/// [`Renderer`] which painted into it.
+///
+/// The contents are stored as 8-bit RGBA, so repeatedly erasing with partial alpha
+/// accumulates rounding.
+#[derive(Debug)]
+pub struct PaintLayer {
+    width: u32,
+    height: u32,
+    /// The texture holding the current contents, and a spare which the next paint renders into.
+    textures: [Texture; 2],
+    current: usize,
+    /// A placeholder image whose contents the renderer replaces with the current texture.
+    image: Image,
+}
+
+impl PaintLayer {
+    /// Creates a transparent layer of the given size.
+    pub fn new(device: &Device, width: u32, height: u32) -> Self {
+        let texture = |label| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                usage: wgpu::TextureUsages::STORAGE_BINDING
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC
+                    | wgpu::TextureUsages::COPY_DST,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                view_formats: &[],
+            })
+        };
+        let data = vec![0_u8; width as usize * height as usize * 4];
+        Self {
+            width,
+            height,
+            textures: [
+                texture("catalina.paint_layer"),
+                texture("catalina.paint_layer"),
+            ],
+            current: 0,
+            image: renderer_image(data, width, height),
+        }
+    }
+
+    /// The width of the layer, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the layer, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The texture holding the current contents of the layer.
+    ///
+    /// This changes after each call to [`paint`](Self::paint).
+    pub fn texture(&self) -> &Texture {
+        &self.textures[self.current]
+    }
+
+    /// An image which displays the contents of the layer when drawn into a scene.
+    ///
+    /// This is only valid when rendered by the renderer most recently passed to
+    /// [`paint`](Self::paint) or [`bind`](Self::bind).
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Makes `renderer` display the current contents of the layer wherever its
+    /// [`image`](Self::image) is drawn.
+    ///
+    /// This is done automatically by [`paint`](Self::paint), and is only needed to display the
+    /// layer with a different renderer.
+    pub fn bind(&self, renderer: &mut Renderer) {
+        renderer.override_image(
+            &self.image,
+            Some(wgpu::TexelCopyTextureInfoBase {
+                texture: self.texture().clone(),
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            }),
+        );
+    }
+
+    /// Paints `scene` into the layer, combining it with the existing contents using `mode`.
+    ///
+    /// The scene is drawn in the pixel coordinates of the layer. Once this returns, the scene
+    /// can be reset, as its content is retained by the layer.
+    pub fn paint(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+        mode: PaintMode,
+    ) -> Result<()> {
+        let bounds = Rect::new(0.0, 0.0, self.width.into(), self.height.into());
+        let mut composite = Scene::new();
+        composite.flatten_shader = scene.flatten_shader.clone();
+        composite.draw_image(&self.image, Affine::IDENTITY);
+        composite.push_layer(mode, 1.0, Affine::IDENTITY, &bounds);
+        composite.append(scene, None);
+        composite.pop_layer();
+        self.render(renderer, device, queue, &composite)
+    }
+
+    /// Clears the layer to transparent.
+    pub fn clear(&mut self, renderer: &mut Renderer, device: &Device, queue: &Queue) -> Result<()> {
+        self.render(renderer, device, queue, &Scene::new())
+    }
+
+    /// Renders `scene` into the spare texture and makes it current.
+    fn render(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+        scene: &Scene,
+    ) -> Result<()> {
+        self.bind(renderer);
+        let target = &self.textures[1 - self.current];
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        renderer.render_to_texture(
+            device,
+            queue,
+            scene,
+            &view,
+            &RenderParams {
+                base_color: palette::css::TRANSPARENT,
+                width: self.width,
+                height: self.height,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+        )?;
+        self.current = 1 - self.current;
+        self.bind(renderer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::{BlendMode, Compose, Mix};
+
+    use super::PaintMode;
+
+    #[test]
+    fn modes_composite_with_the_existing_content() {
+        let blend = |mode: PaintMode| BlendMode::from(mode);
+        // Erasing keeps the destination only where nothing is painted.
+        assert_eq!(blend(PaintMode::Erase).compose, Compose::DestOut);
+        assert_eq!(blend(PaintMode::Behind).compose, Compose::DestOver);
+        assert_eq!(blend(PaintMode::LockAlpha).compose, Compose::SrcAtop);
+        assert_eq!(blend(PaintMode::Multiply).mix, Mix::Multiply);
+        assert_eq!(blend(PaintMode::Multiply).compose, Compose::SrcOver);
+        assert_eq!(
+            blend(PaintMode::Normal),
+            BlendMode::new(Mix::Normal, Compose::SrcOver)
+        );
+        let screen = BlendMode::new(Mix::Screen, Compose::Xor);
+        assert_eq!(blend(PaintMode::Blend(screen)), screen);
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Per-stage GPU timings of rendered frames.
+
+use std::time::Duration;
+
+use wgpu_profiler::GpuTimerQueryResult;
+
+/// The GPU time taken by a stage of a frame, from [`Renderer::profile_results`].
+///
+/// Stages are labeled by the work they cover, such as `catalina.coarse` for a shader or
+/// `render_to_texture` for a whole submission, and contain the stages nested within them.
+///
+/// [`Renderer::profile_results`]: crate::Renderer::profile_results
+#[derive(Clone, Debug, PartialEq)]
+pub struct StageTiming {
+    /// The label of the stage.
+    pub label: String,
+    /// The GPU time taken by the stage, which is zero if the GPU didn't report it.
+    pub duration: Duration,
+    /// The stages within this one.
+    pub nested: Vec<StageTiming>,
+}
+
+impl StageTiming {
+    /// Returns the total time of every stage in `timings` and their nested stages with the
+    /// given label, such as `catalina.fine_area`.
+    pub fn total(timings: &[Self], label: &str) -> Duration {
+        timings
+            .iter()
+            .map(|timing| {
+                let nested = Self::total(&timing.nested, label);
+                if timing.label == label {
+                    timing.duration + nested
+                } else {
+                    nested
+                }
+            })
+            .sum()
+    }
+}
+
+impl From<&GpuTimerQueryResult> for StageTiming {
+    fn from(result: &GpuTimerQueryResult) -> Self {
+        let duration = result
+            .time
+            .as_ref()
+            .map(|time| Duration::from_secs_f64((time.end - time.start).max(0.0)))
+            .unwrap_or_default();
+        Self {
+            label: result.label.clone(),
+            duration,
+            nested: result.nested_queries.iter().map(Self::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Known problems with particular adapters and drivers, and the workarounds for them.
+
+use std::num::{NonZeroU32, NonZeroUsize};
+
+use wgpu::{AdapterInfo, Backend, DeviceType};
+
+use crate::RendererOptions;
+
+/// Workarounds for problems with particular adapters or drivers.
+///
+/// Renderers created with
+/// [`DeviceHandle::create_renderer`](crate::util::DeviceHandle::create_renderer) use the
+/// workarounds for their adapter from the built-in [`QUIRKS`] table. Applications creating
+/// renderers with [`Renderer::new`](crate::Renderer::new) can look them up with
+/// [`for_adapter`](Self::for_adapter) and apply them to the options, changing their fields
+/// first to override the result, or look the adapter up in their own table with
+/// [`from_table`](Self::from_table).
+///
+/// ```no_run
+/// # fn example(adapter: &catalina::wgpu::Adapter, mut options: catalina::RendererOptions) {
+/// let mut quirks = catalina::Quirks::for_adapter(&adapter.get_info());
+/// // This application has its own fallback for slow adapters.
+/// quirks.use_cpu = false;
+/// quirks.apply(&mut options);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// Run the stages up to fine rasterization on the CPU.
+    ///
+    /// See [`RendererOptions::use_cpu`].
+    pub use_cpu: bool,
+    /// Compile shaders on a single thread.
+    ///
+    /// See [`RendererOptions::num_init_threads`].
+    pub single_threaded_init: bool,
+    /// Don't compile the MSAA variants of fine rasterization.
+    ///
+    /// If no other antialiasing method was requested, area antialiasing is enabled instead.
+    pub disable_msaa: bool,
+    /// Split fine rasterization into bands of at most this many tile rows.
+    ///
+    /// See [`RendererOptions::fine_band_rows`].
+    pub fine_band_rows: Option<NonZeroU32>,
+}
+
+impl Quirks {
+    /// No workarounds.
+    pub const NONE: Self = Self {
+        use_cpu: false,
+        single_threaded_init: false,
+        disable_msaa: false,
+        fine_band_rows: None,
+    };
+
+    /// Returns the workarounds from the built-in [`QUIRKS`] table which apply to the adapter.
+    pub fn for_adapter(info: &AdapterInfo) -> Self {
+        Self::from_table(QUIRKS, info)
+    }
+
+    /// Returns the combined workarounds of every entry in `table` which matches the adapter.
+    pub fn from_table(table: &[Quirk], info: &AdapterInfo) -> Self {
+        let mut quirks = Self::NONE;
+        for quirk in table.iter().filter(|quirk| quirk.matches(info)) {
+            #[cfg(feature = "debug-logs")]
+            log::info!(
+                "Applying workarounds for adapter {:?}: {}",
+                info.name,
+                quirk.reason
+            );
+            quirks = quirks.union(quirk.workarounds);
+        }
+        quirks
+    }
+
+    /// Returns the workarounds needed by either `self` or `other`.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            use_cpu: self.use_cpu || other.use_cpu,
+            single_threaded_init: self.single_threaded_init || other.single_threaded_init,
+            disable_msaa: self.disable_msaa || other.disable_msaa,
+            fine_band_rows: min_rows(self.fine_band_rows, other.fine_band_rows),
+        }
+    }
+
+    /// Changes `options` to enable these workarounds.
+    ///
+    /// Options which are already at least as conservative are left unchanged.
+    pub fn apply(&self, options: &mut RendererOptions) {
+        if self.use_cpu {
+            options.use_cpu = true;
+        }
+        if self.single_threaded_init {
+            options.num_init_threads = NonZeroUsize::new(1);
+        }
+        if self.disable_msaa {
+            let support = &mut options.antialiasing_support;
+            support.msaa8 = false;
+            support.msaa16 = false;
+            support.area = true;
+        }
+        options.fine_band_rows = min_rows(options.fine_band_rows, self.fine_band_rows);
+    }
+}
+
+fn min_rows(a: Option<NonZeroU32>, b: Option<NonZeroU32>) -> Option<NonZeroU32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// An entry in a table of adapter quirks.
+///
+/// An entry applies to an adapter if every criterion which is set matches.
+#[derive(Clone, Copy, Debug)]
+pub struct Quirk {
+    /// The PCI vendor id of the adapter.
+    pub vendor: Option<u32>,
+    /// The type of the adapter.
+    pub device_type: Option<DeviceType>,
+    /// The backend the adapter is used through.
+    pub backend: Option<Backend>,
+    /// A case-insensitive substring of the adapter name.
+    pub name_contains: Option<&'static str>,
+    /// A case-insensitive substring of the driver name or driver information.
+    pub driver_contains: Option<&'static str>,
+    /// Why the workarounds are needed, for logging.
+    pub reason: &'static str,
+    /// The workarounds to apply.
+    pub workarounds: Quirks,
+}
+
+impl Quirk {
+    /// An entry which matches every adapter, to be narrowed down by setting its criteria.
+    pub const ANY: Self = Self {
+        vendor: None,
+        device_type: None,
+        backend: None,
+        name_contains: None,
+        driver_contains: None,
+        reason: "",
+        workarounds: Quirks::NONE,
+    };
+
+    /// Returns `true` if this entry applies to the adapter.
+    pub fn matches(&self, info: &AdapterInfo) -> bool {
+        let contains = |haystack: &str, needle: &str| {
+            haystack
+                .to_ascii_lowercase()
+                .contains(&needle.to_ascii_lowercase())
+        };
+        self.vendor.is_none_or(|vendor| vendor == info.vendor)
+            && self.device_type.is_none_or(|ty| ty == info.device_type)
+            && self.backend.is_none_or(|backend| backend == info.backend)
+            && self
+                .name_contains
+                .is_none_or(|name| contains(&info.name, name))
+            && self.driver_contains.is_none_or(|driver| {
+                contains(&info.driver, driver) || contains(&info.driver_info, driver)
+            })
+    }
+}
+
+/// The built-in table of known adapter quirks.
+///
+/// Entries should only be added for problems which have been reported, with a link to the
+/// report in their reason.
+pub const QUIRKS: &[Quirk] = &[
+    Quirk {
+        backend: Some(Backend::Metal),
+        reason: "Compiling shaders in parallel is slower than on a single thread on macOS \
+                 (see https://github.com/bevyengine/bevy/pull/10812#discussion_r1496138004)",
+        workarounds: Quirks {
+            single_threaded_init: true,
+            ..Quirks::NONE
+        },
+        ..Quirk::ANY
+    },
+    Quirk {
+        device_type: Some(DeviceType::Cpu),
+        reason: "Software adapters run compute shaders much more slowly than the native CPU \
+                 implementations of the pipeline stages",
+        workarounds: Quirks {
+            use_cpu: true,
+            ..Quirks::NONE
+        },
+        ..Quirk::ANY
+    },
+];
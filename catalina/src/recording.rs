@@ -6,7 +6,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 
 use peniko::Image;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
 /// A Shader's ID/pointer.
 pub struct ShaderId(pub usize);
 
@@ -48,6 +48,18 @@ pub enum ImageFormat {
     Rgba8,
     /// 8-bit BGRA format.
     Bgra8,
+    /// 8-bit single channel format.
+    R8,
+    /// 16-bit floating point single channel format.
+    R16Float,
+    /// 32-bit floating point single channel format.
+    R32Float,
+    /// 8-bit RGBA format with sRGB encoding.
+    Rgba8Srgb,
+    /// 8-bit BGRA format with sRGB encoding.
+    Bgra8Srgb,
+    /// 16-bit floating point RGBA format.
+    Rgba16Float,
 }
 
 /// Proxy used as a handle to an image.
@@ -273,6 +285,12 @@ impl ImageFormat {
         match self {
             Self::Rgba8 => wgpu::TextureFormat::Rgba8Unorm,
             Self::Bgra8 => wgpu::TextureFormat::Bgra8Unorm,
+            Self::R8 => wgpu::TextureFormat::R8Unorm,
+            Self::R16Float => wgpu::TextureFormat::R16Float,
+            Self::R32Float => wgpu::TextureFormat::R32Float,
+            Self::Rgba8Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            Self::Bgra8Srgb => wgpu::TextureFormat::Bgra8UnormSrgb,
+            Self::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
         }
     }
 
@@ -282,6 +300,12 @@ impl ImageFormat {
         match format {
             wgpu::TextureFormat::Rgba8Unorm => Some(Self::Rgba8),
             wgpu::TextureFormat::Bgra8Unorm => Some(Self::Bgra8),
+            wgpu::TextureFormat::R8Unorm => Some(Self::R8),
+            wgpu::TextureFormat::R16Float => Some(Self::R16Float),
+            wgpu::TextureFormat::R32Float => Some(Self::R32Float),
+            wgpu::TextureFormat::Rgba8UnormSrgb => Some(Self::Rgba8Srgb),
+            wgpu::TextureFormat::Bgra8UnormSrgb => Some(Self::Bgra8Srgb),
+            wgpu::TextureFormat::Rgba16Float => Some(Self::Rgba16Float),
             _ => None,
         }
     }
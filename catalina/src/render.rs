@@ -13,13 +13,29 @@ use crate::{AaConfig, RenderParams};
 )]
 use crate::{Scene, ShaderId};
 
-use catalina_encoding::{make_mask_lut, make_mask_lut_16, Resolver, WorkgroupSize};
+use catalina_encoding::{make_mask_lut, make_mask_lut_16, ConfigUniform, Resolver, WorkgroupSize};
 
 /// State for a render in progress.
 pub struct Render {
     fine_wg_count: Option<WorkgroupSize>,
     fine_resources: Option<FineResources>,
     mask_buf: Option<ResourceProxy>,
+    image_atlas: Option<ImageProxy>,
+    reuse_image_atlas: bool,
+    /// The ids of the data of images which are written to a reused atlas in every render.
+    dynamic_images: Vec<u64>,
+    /// Whether the resources of draws outside of the target are left unresolved.
+    cull_to_target: bool,
+    /// The variant of coarse rasterization to use instead of `coarse`.
+    coarse_variant: Option<ShaderId>,
+    /// The variant of fine rasterization to use instead of the one for the antialiasing method.
+    fine_variant: Option<ShaderId>,
+    /// The shaders used instead of the given ones for resolved scenes within the limits.
+    #[cfg(feature = "wgpu")]
+    small_scenes: Option<(FullShaders, crate::SmallSceneLimits)>,
+    /// The buffer of draw object costs, bound after the other resources of the fine variant.
+    #[cfg(feature = "draw-costs")]
+    costs_buf: Option<BufferProxy>,
 
     #[cfg(feature = "debug_layers")]
     captured_buffers: Option<CapturedBuffers>,
@@ -38,6 +54,7 @@ impl Drop for Render {
 struct FineResources {
     aa_config: AaConfig,
 
+    config: ConfigUniform,
     config_buf: ResourceProxy,
     bump_buf: ResourceProxy,
     tile_buf: ResourceProxy,
@@ -46,6 +63,8 @@ struct FineResources {
     gradient_image: ResourceProxy,
     info_bin_data_buf: ResourceProxy,
     image_atlas: ResourceProxy,
+    /// Whether the image atlas is kept for the next render, rather than freed.
+    image_atlas_retained: bool,
     blend_spill_buf: ResourceProxy,
 
     out_image: ImageProxy,
@@ -159,12 +178,13 @@ impl WgpuVuneData {
 #[cfg(feature = "wgpu")]
 /// Render an entire scene in the GPU.
 pub(crate) fn render_full(
+    render: &mut Render,
     scene: &Scene,
     resolver: &mut Resolver,
     shaders: &FullShaders,
     params: &RenderParams,
 ) -> (Recording, ResourceProxy) {
-    render_encoding_full(scene, resolver, shaders, params)
+    render_encoding_full(render, scene, resolver, shaders, params)
 }
 
 #[cfg(feature = "wgpu")]
@@ -173,12 +193,12 @@ pub(crate) fn render_full(
 /// This function is not recommended when the scene can be complex, as it does not
 /// implement robust dynamic memory.
 pub(crate) fn render_encoding_full(
+    render: &mut Render,
     scene: &Scene,
     resolver: &mut Resolver,
     shaders: &FullShaders,
     params: &RenderParams,
 ) -> (Recording, ResourceProxy) {
-    let mut render = Render::new();
     let mut recording = render.render_encoding_coarse(scene, resolver, shaders, params, false);
     let out_image = render.out_image();
     render.record_fine(shaders, &mut recording);
@@ -198,11 +218,85 @@ impl Render {
             fine_wg_count: None,
             fine_resources: None,
             mask_buf: None,
+            image_atlas: None,
+            reuse_image_atlas: false,
+            dynamic_images: Vec::new(),
+            cull_to_target: false,
+            coarse_variant: None,
+            fine_variant: None,
+            #[cfg(feature = "wgpu")]
+            small_scenes: None,
+            #[cfg(feature = "draw-costs")]
+            costs_buf: None,
             #[cfg(feature = "debug_layers")]
             captured_buffers: None,
         }
     }
 
+    /// Uses a variant of coarse rasterization, which has the same bindings as `coarse`.
+    #[cfg(feature = "wgpu")]
+    pub(crate) fn use_coarse_variant(&mut self, coarse: ShaderId) {
+        self.coarse_variant = Some(coarse);
+    }
+
+    /// Uses `shaders` for the coarse stages of scenes within `limits`.
+    ///
+    /// This is decided once the scene is resolved, so that the paths and segments of glyph
+    /// runs and dashed strokes are counted.
+    #[cfg(feature = "wgpu")]
+    pub(crate) fn use_small_scene_shaders(
+        &mut self,
+        shaders: FullShaders,
+        limits: crate::SmallSceneLimits,
+    ) {
+        self.small_scenes = Some((shaders, limits));
+    }
+
+    /// Uses a variant of fine rasterization, which has the same bindings as `fine_area`.
+    #[cfg(feature = "wgpu")]
+    pub(crate) fn use_fine_variant(&mut self, fine: ShaderId) {
+        self.fine_variant = Some(fine);
+    }
+
+    /// Binds `costs` to the variant of fine rasterization after the bindings of `fine_area`,
+    /// for the variant which measures the cost of each draw object.
+    #[cfg(feature = "draw-costs")]
+    pub(crate) fn use_costs_buf(&mut self, costs: BufferProxy) {
+        self.costs_buf = Some(costs);
+    }
+
+    /// Keeps the image atlas between renders using the same resolver, so that only the images
+    /// added to the atlas since the previous render are written to it.
+    ///
+    /// `atlas` should be the value of [`image_atlas`](Self::image_atlas) after the previous
+    /// render. It is freed and replaced if the resolver's atlas has grown since. The atlas is
+    /// not freed at the end of this render, so the caller is responsible for freeing it once
+    /// it is no longer reused.
+    pub fn reuse_image_atlas(&mut self, atlas: Option<ImageProxy>) {
+        self.reuse_image_atlas = true;
+        self.image_atlas = atlas;
+    }
+
+    /// Sets the ids of the [data](peniko::Image::data) of images whose contents change
+    /// between renders, such as those overridden with textures, which are written to a reused
+    /// atlas in every render.
+    pub fn set_dynamic_images(&mut self, ids: Vec<u64>) {
+        self.dynamic_images = ids;
+    }
+
+    /// Only resolves the glyph outlines, gradient ramps and images of draws which are within
+    /// the target, as described in [`Resolver::set_cull_rect`].
+    pub fn set_cull_to_target(&mut self, cull: bool) {
+        self.cull_to_target = cull;
+    }
+
+    /// Returns the image atlas to reuse in the next render.
+    ///
+    /// This is only set if [`reuse_image_atlas`](Self::reuse_image_atlas) was called.
+    pub fn image_atlas(&self) -> Option<ImageProxy> {
+        self.image_atlas
+    }
+
     /// Prepare a recording for the coarse rasterization phase.
     ///
     /// The `robust` parameter controls whether we're preparing for readback
@@ -219,6 +313,10 @@ impl Render {
         let mut recording = Recording::default();
         let mut packed = vec![];
 
+        resolver.set_dash_phase(params.dash_phase);
+        resolver.set_cull_rect(self.cull_to_target.then(|| {
+            peniko::kurbo::Rect::new(0.0, 0.0, params.width.into(), params.height.into())
+        }));
         let (layout, ramps, images) = resolver.resolve(scene.encoding(), &mut packed);
         let gradient_image = if ramps.height == 0 {
             ResourceProxy::new_image(1, 1, ImageFormat::Rgba8)
@@ -231,14 +329,44 @@ impl Render {
                 data,
             ))
         };
-        let image_atlas = if images.images.is_empty() {
-            ImageProxy::new(1, 1, ImageFormat::Rgba8)
+        let (image_atlas, image_atlas_retained) = if images.images.is_empty() {
+            (ImageProxy::new(1, 1, ImageFormat::Rgba8), false)
         } else {
-            ImageProxy::new(images.width, images.height, ImageFormat::Rgba8)
+            let mut new_images = images.images;
+            let mut dynamic_images: &[_] = &[];
+            let retained = match self.image_atlas.take() {
+                Some(atlas) if atlas.width == images.width && atlas.height == images.height => {
+                    (dynamic_images, new_images) = images.images.split_at(images.written);
+                    Some(atlas)
+                }
+                Some(atlas) => {
+                    recording.free_image(atlas);
+                    None
+                }
+                None => None,
+            };
+            let atlas = retained.unwrap_or_else(|| {
+                ImageProxy::new(images.width, images.height, ImageFormat::Rgba8)
+            });
+            let dynamic_images = dynamic_images
+                .iter()
+                .filter(|image| self.dynamic_images.contains(&image.0.data.id()));
+            for image in dynamic_images.chain(new_images) {
+                recording.write_image(atlas, image.1, image.2, image.0.clone());
+            }
+            if self.reuse_image_atlas {
+                self.image_atlas = Some(atlas);
+            }
+            (atlas, self.reuse_image_atlas)
         };
-        for image in images.images {
-            recording.write_image(image_atlas, image.1, image.2, image.0.clone());
-        }
+        #[cfg(feature = "wgpu")]
+        let small_scene_shaders = self
+            .small_scenes
+            .as_ref()
+            .filter(|(_, limits)| limits.contains(&layout, &packed, params))
+            .map(|(shaders, _)| shaders.clone());
+        #[cfg(feature = "wgpu")]
+        let shaders = small_scene_shaders.as_ref().unwrap_or(shaders);
         let cpu_config =
             RenderConfig::new(&layout, params.width, params.height, &params.base_color);
         // HACK: The coarse workgroup counts is the number of active bins.
@@ -249,6 +377,7 @@ impl Render {
         {
             log::warn!(
                 "Trying to paint too large image. {}x{}.\n\
+                Consider rendering it in tiles using `Renderer::render_tiled`.\n\
                 See https://github.com/linebender/vello/issues/680 for details",
                 params.width,
                 params.height
@@ -289,7 +418,8 @@ impl Render {
             "catalina.reduced_buf",
         );
         // TODO: really only need pathtag_wgs - 1
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             shaders.pathtag_reduce,
             wg_counts.path_reduce,
             [config_buf, scene_buf, reduced_buf],
@@ -302,7 +432,8 @@ impl Render {
                 buffer_sizes.path_reduced2.size_in_bytes().into(),
                 "catalina.reduced2_buf",
             );
-            recording.dispatch(
+            dispatch_stage(
+                &mut recording,
                 shaders.pathtag_reduce2,
                 wg_counts.path_reduce2,
                 [reduced_buf, reduced2_buf],
@@ -311,7 +442,8 @@ impl Render {
                 buffer_sizes.path_reduced_scan.size_in_bytes().into(),
                 "reduced_scan_buf",
             );
-            recording.dispatch(
+            dispatch_stage(
+                &mut recording,
                 shaders.pathtag_scan1,
                 wg_counts.path_scan1,
                 [reduced_buf, reduced2_buf, reduced_scan_buf],
@@ -329,7 +461,8 @@ impl Render {
         } else {
             shaders.pathtag_scan
         };
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             pathtag_scan,
             wg_counts.path_scan,
             [config_buf, scene_buf, pathtag_parent, tagmonoid_buf],
@@ -343,7 +476,8 @@ impl Render {
             buffer_sizes.path_bboxes.size_in_bytes().into(),
             "catalina.path_bbox_buf",
         );
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             shaders.bbox_clear,
             wg_counts.bbox_clear,
             [config_buf, path_bbox_buf],
@@ -370,6 +504,8 @@ impl Render {
         ]);
 
         if scene.flatten_shader.id.is_some() {
+            #[cfg(feature = "debug-logs")]
+            log::debug!("Using custom Vune flatten shader instead of the built-in stage");
             for i in &scene.flatten_shader.data {
                 if matches!(i, WgpuVuneData::Empty) {
                     continue;
@@ -379,7 +515,8 @@ impl Render {
             }
         }
 
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             match scene.flatten_shader.id {
                 Some(f) => f,
                 None => shaders.flatten,
@@ -391,7 +528,8 @@ impl Render {
             buffer_sizes.draw_reduced.size_in_bytes().into(),
             "catalina.draw_reduced_buf",
         );
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             shaders.draw_reduce,
             wg_counts.draw_reduce,
             [config_buf, scene_buf, draw_reduced_buf],
@@ -404,7 +542,8 @@ impl Render {
             buffer_sizes.clip_inps.size_in_bytes().into(),
             "catalina.clip_inp_buf",
         );
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             shaders.draw_leaf,
             wg_counts.draw_leaf,
             [
@@ -426,32 +565,30 @@ impl Render {
             buffer_sizes.clip_bics.size_in_bytes().into(),
             "catalina.clip_bic_buf",
         );
-        if wg_counts.clip_reduce.0 > 0 {
-            recording.dispatch(
-                shaders.clip_reduce,
-                wg_counts.clip_reduce,
-                [clip_inp_buf, path_bbox_buf, clip_bic_buf, clip_el_buf],
-            );
-        }
+        dispatch_stage(
+            &mut recording,
+            shaders.clip_reduce,
+            wg_counts.clip_reduce,
+            [clip_inp_buf, path_bbox_buf, clip_bic_buf, clip_el_buf],
+        );
         let clip_bbox_buf = ResourceProxy::new_buf(
             buffer_sizes.clip_bboxes.size_in_bytes().into(),
             "catalina.clip_bbox_buf",
         );
-        if wg_counts.clip_leaf.0 > 0 {
-            recording.dispatch(
-                shaders.clip_leaf,
-                wg_counts.clip_leaf,
-                [
-                    config_buf,
-                    clip_inp_buf,
-                    path_bbox_buf,
-                    clip_bic_buf,
-                    clip_el_buf,
-                    draw_monoid_buf,
-                    clip_bbox_buf,
-                ],
-            );
-        }
+        dispatch_stage(
+            &mut recording,
+            shaders.clip_leaf,
+            wg_counts.clip_leaf,
+            [
+                config_buf,
+                clip_inp_buf,
+                path_bbox_buf,
+                clip_bic_buf,
+                clip_el_buf,
+                draw_monoid_buf,
+                clip_bbox_buf,
+            ],
+        );
         recording.free_resource(clip_inp_buf);
         recording.free_resource(clip_bic_buf);
         recording.free_resource(clip_el_buf);
@@ -463,7 +600,8 @@ impl Render {
             buffer_sizes.bin_headers.size_in_bytes().into(),
             "catalina.bin_header_buf",
         );
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             shaders.binning,
             wg_counts.binning,
             [
@@ -485,7 +623,8 @@ impl Render {
             buffer_sizes.paths.size_in_bytes().into(),
             "catalina.path_buf",
         );
-        recording.dispatch(
+        dispatch_stage(
+            &mut recording,
             shaders.tile_alloc,
             wg_counts.tile_alloc,
             [
@@ -503,35 +642,39 @@ impl Render {
             buffer_sizes.indirect_count.size_in_bytes().into(),
             "catalina.indirect_count",
         );
-        recording.dispatch(
-            shaders.path_count_setup,
-            wg_counts.path_count_setup,
-            [bump_buf, indirect_count_buf.into()],
-        );
         let seg_counts_buf = ResourceProxy::new_buf(
             buffer_sizes.seg_counts.size_in_bytes().into(),
             "catalina.seg_counts_buf",
         );
-        recording.dispatch_indirect(
-            shaders.path_count,
-            indirect_count_buf,
-            0,
-            [
-                config_buf,
-                bump_buf,
-                lines_buf,
-                path_buf,
-                tile_buf,
-                seg_counts_buf,
-            ],
-        );
-        recording.dispatch(
+        if wg_counts.path_count_setup.0 > 0 {
+            recording.dispatch(
+                shaders.path_count_setup,
+                wg_counts.path_count_setup,
+                [bump_buf, indirect_count_buf.into()],
+            );
+            recording.dispatch_indirect(
+                shaders.path_count,
+                indirect_count_buf,
+                0,
+                [
+                    config_buf,
+                    bump_buf,
+                    lines_buf,
+                    path_buf,
+                    tile_buf,
+                    seg_counts_buf,
+                ],
+            );
+        }
+        dispatch_stage(
+            &mut recording,
             shaders.backdrop,
             wg_counts.backdrop,
             [config_buf, bump_buf, path_buf, tile_buf],
         );
-        recording.dispatch(
-            shaders.coarse,
+        dispatch_stage(
+            &mut recording,
+            self.coarse_variant.unwrap_or(shaders.coarse),
             wg_counts.coarse,
             [
                 config_buf,
@@ -545,24 +688,26 @@ impl Render {
                 ptcl_buf,
             ],
         );
-        recording.dispatch(
-            shaders.path_tiling_setup,
-            wg_counts.path_tiling_setup,
-            [bump_buf, indirect_count_buf.into(), ptcl_buf],
-        );
-        recording.dispatch_indirect(
-            shaders.path_tiling,
-            indirect_count_buf,
-            0,
-            [
-                bump_buf,
-                seg_counts_buf,
-                lines_buf,
-                path_buf,
-                tile_buf,
-                segments_buf,
-            ],
-        );
+        if wg_counts.path_tiling_setup.0 > 0 {
+            recording.dispatch(
+                shaders.path_tiling_setup,
+                wg_counts.path_tiling_setup,
+                [bump_buf, indirect_count_buf.into(), ptcl_buf],
+            );
+            recording.dispatch_indirect(
+                shaders.path_tiling,
+                indirect_count_buf,
+                0,
+                [
+                    bump_buf,
+                    seg_counts_buf,
+                    lines_buf,
+                    path_buf,
+                    tile_buf,
+                    segments_buf,
+                ],
+            );
+        }
         recording.free_buffer(indirect_count_buf);
         recording.free_resource(seg_counts_buf);
         recording.free_resource(scene_buf);
@@ -577,6 +722,7 @@ impl Render {
         self.fine_wg_count = Some(wg_counts.fine);
         self.fine_resources = Some(FineResources {
             aa_config: params.antialiasing_method,
+            config: cpu_config.gpu,
             config_buf,
             bump_buf,
             tile_buf,
@@ -586,6 +732,7 @@ impl Render {
             info_bin_data_buf,
             blend_spill_buf: ResourceProxy::Buffer(blend_spill_buf),
             image_atlas: ResourceProxy::Image(image_atlas),
+            image_atlas_retained,
             out_image,
         });
         if robust {
@@ -595,7 +742,9 @@ impl Render {
 
         #[cfg(feature = "debug_layers")]
         {
-            if robust {
+            // The lines buffer is only allocated when flattening runs, so there's nothing to
+            // capture for scenes which draw nothing.
+            if robust && wg_counts.flatten.0 > 0 {
                 let path_bboxes = *path_bbox_buf.as_buf().unwrap();
                 let lines = *lines_buf.as_buf().unwrap();
                 recording.download(lines);
@@ -623,6 +772,76 @@ impl Render {
     pub fn record_fine(&mut self, shaders: &FullShaders, recording: &mut Recording) {
         let fine_wg_count = self.fine_wg_count.take().unwrap();
         let fine = self.fine_resources.take().unwrap();
+        self.dispatch_fine(shaders, recording, &fine, fine.config_buf, fine_wg_count);
+        self.free_fine(recording, fine);
+    }
+
+    /// Records the fine rasterization stage split into horizontal bands of `band_rows` tile rows.
+    ///
+    /// Each of the returned recordings covers one band and is intended to be submitted
+    /// separately, which keeps the duration of each submission short. The last recording also
+    /// frees the resources used by the fine stage.
+    pub fn record_fine_banded(&mut self, shaders: &FullShaders, band_rows: u32) -> Vec<Recording> {
+        let (wg_x, wg_y, wg_z) = self.fine_wg_count.take().unwrap();
+        let fine = self.fine_resources.take().unwrap();
+        let band_rows = band_rows.max(1);
+        let mut recordings = Vec::new();
+        let mut row = 0;
+        while row < wg_y {
+            let rows = band_rows.min(wg_y - row);
+            let mut recording = Recording::default();
+            let config = ConfigUniform {
+                fine_row_offset: row,
+                ..fine.config
+            };
+            let config_buf = ResourceProxy::Buffer(
+                recording.upload_uniform("catalina.fine_band_config", bytemuck::bytes_of(&config)),
+            );
+            self.dispatch_fine(
+                shaders,
+                &mut recording,
+                &fine,
+                config_buf,
+                (wg_x, rows, wg_z),
+            );
+            recording.free_resource(config_buf);
+            recordings.push(recording);
+            row += rows;
+        }
+        let mut last = recordings.pop().unwrap_or_default();
+        self.free_fine(&mut last, fine);
+        recordings.push(last);
+        recordings
+    }
+
+    fn dispatch_fine(
+        &mut self,
+        shaders: &FullShaders,
+        recording: &mut Recording,
+        fine: &FineResources,
+        config_buf: ResourceProxy,
+        fine_wg_count: WorkgroupSize,
+    ) {
+        if let Some(fine_variant) = self.fine_variant {
+            #[cfg_attr(
+                not(feature = "draw-costs"),
+                expect(unused_mut, reason = "only extended when measuring draw costs")
+            )]
+            let mut resources = vec![
+                config_buf,
+                fine.segments_buf,
+                fine.ptcl_buf,
+                fine.info_bin_data_buf,
+                fine.blend_spill_buf,
+                ResourceProxy::Image(fine.out_image),
+                fine.gradient_image,
+                fine.image_atlas,
+            ];
+            #[cfg(feature = "draw-costs")]
+            resources.extend(self.costs_buf.map(ResourceProxy::Buffer));
+            recording.dispatch(fine_variant, fine_wg_count, resources);
+            return;
+        }
         match fine.aa_config {
             AaConfig::Area => {
                 recording.dispatch(
@@ -631,7 +850,7 @@ impl Render {
                         .expect("shaders not configured to support AA mode: area"),
                     fine_wg_count,
                     [
-                        fine.config_buf,
+                        config_buf,
                         fine.segments_buf,
                         fine.ptcl_buf,
                         fine.info_bin_data_buf,
@@ -665,7 +884,7 @@ impl Render {
                     fine_shader,
                     fine_wg_count,
                     [
-                        fine.config_buf,
+                        config_buf,
                         fine.segments_buf,
                         fine.ptcl_buf,
                         fine.info_bin_data_buf,
@@ -678,12 +897,17 @@ impl Render {
                 );
             }
         }
+    }
+
+    fn free_fine(&mut self, recording: &mut Recording, fine: FineResources) {
         recording.free_resource(fine.config_buf);
         recording.free_resource(fine.tile_buf);
         recording.free_resource(fine.segments_buf);
         recording.free_resource(fine.ptcl_buf);
         recording.free_resource(fine.gradient_image);
-        recording.free_resource(fine.image_atlas);
+        if !fine.image_atlas_retained {
+            recording.free_resource(fine.image_atlas);
+        }
         recording.free_resource(fine.info_bin_data_buf);
         recording.free_resource(fine.blend_spill_buf);
         // TODO: make mask buf persistent
@@ -718,3 +942,18 @@ impl Render {
         self.captured_buffers.take()
     }
 }
+
+/// Records a dispatch of a stage, unless it has no workgroups.
+fn dispatch_stage<R>(
+    recording: &mut Recording,
+    shader: ShaderId,
+    wg_size: WorkgroupSize,
+    resources: R,
+) where
+    R: IntoIterator,
+    R::Item: Into<ResourceProxy>,
+{
+    if wg_size.0 * wg_size.1 * wg_size.2 > 0 {
+        recording.dispatch(shader, wg_size, resources);
+    }
+}
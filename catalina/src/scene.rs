@@ -2,15 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 mod bitmap;
-
+mod custom_paint;
+mod dynamics;
+mod filter;
+mod filter_graph;
+mod hatch;
+mod layer_overlay;
+mod measure;
+mod parallel;
+mod ruby;
+mod shaping;
+mod smooth_rect;
+mod stamp;
+mod vertical;
+
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[cfg(feature = "bump_estimate")]
 use catalina_encoding::BumpAllocatorMemory;
-use catalina_encoding::{Encoding, Glyph, GlyphRun, NormalizedCoord, Patch, Transform};
+use catalina_encoding::{
+    DecodeError, DrawBeginClip, Encoding, Fragment, Glyph, GlyphRun, LayerInfo, Lint, LintOptions,
+    NormalizedCoord, Patch, Placeholder, Resolver, Transform,
+};
 use peniko::{
     color::{palette, AlphaColor, DynamicColor, Srgb},
-    kurbo::{Affine, BezPath, Point, Rect, Shape, Stroke, StrokeOpts, Vec2},
+    kurbo::{Affine, BezPath, PathEl, Point, Rect, Shape, Stroke, StrokeOpts, Vec2},
     BlendMode, Blob, Brush, BrushRef, Color, ColorStop, ColorStops, ColorStopsSource, Compose,
     Extend, Fill, Font, Gradient, Image, Mix, StyleRef,
 };
@@ -25,6 +42,25 @@ use skrifa::{
 };
 
 use crate::render::WgpuVune;
+use crate::Error;
+use filter::FilterFrame;
+
+pub use custom_paint::{CustomPaint, CustomPaintId};
+pub use dynamics::{StrokeDynamics, StrokePoint};
+pub(crate) use filter::FilterLayer;
+pub use filter::{Filter, LightSource, Lighting, Turbulence, MIN_RASTER_SCALE};
+#[cfg(feature = "wgpu")]
+pub(crate) use filter_graph::FilterNode;
+pub use filter_graph::{FilterGraph, FilterInput, FilterNodeId};
+pub use hatch::Hatch;
+pub use measure::{LineMetrics, TextMeasure};
+pub(crate) use parallel::renderer_image;
+pub use parallel::{ParallelSceneBuilder, PartBuilder};
+pub use ruby::{RubyAlign, RubyLayout, RubyPosition, RubyRun};
+pub use shaping::SimpleShaper;
+pub use smooth_rect::{SmoothRect, IOS_CORNER_SMOOTHING};
+pub use stamp::BrushTip;
+pub use vertical::{VerticalLayout, VerticalOrientation};
 
 // TODO - Document invariants and edge cases (#470)
 // - What happens when we pass a transform matrix with NaN values to the Scene?
@@ -49,6 +85,18 @@ pub struct Scene {
     estimator: catalina_encoding::BumpEstimator,
     /// The scene's Vune Flatten Shader.
     pub flatten_shader: WgpuVune,
+    /// The layers with filters, which are rendered before the scene.
+    filter_layers: Vec<FilterLayer>,
+    /// The filtered layers which haven't been popped yet, innermost last.
+    filter_stack: Vec<FilterFrame>,
+    /// The masked layers which haven't been popped yet, innermost last.
+    mask_stack: Vec<MaskFrame>,
+    /// Decoded bitmap glyphs, so that emoji drawn in each frame keep the same images. When the
+    /// scene is reset, only the glyphs drawn since the previous reset are kept.
+    bitmap_glyphs: HashMap<BitmapGlyphKey, DecodedBitmapGlyph>,
+    /// Whether the winding of non-zero fills is corrected, set by
+    /// [`Self::set_normalize_winding`].
+    normalize_winding: bool,
 }
 static_assertions::assert_impl_all!(Scene: Send, Sync);
 
@@ -63,6 +111,22 @@ impl Scene {
         self.encoding.reset();
         #[cfg(feature = "bump_estimate")]
         self.estimator.reset();
+        self.filter_layers.clear();
+        self.filter_stack.clear();
+        self.mask_stack.clear();
+        self.bitmap_glyphs
+            .retain(|_, glyph| std::mem::take(&mut glyph.used));
+    }
+
+    /// Corrects the winding direction of the subpaths of shapes filled with [`Fill::NonZero`]
+    /// by [`fill`](Self::fill), with [`normalize_winding`](crate::normalize_winding).
+    ///
+    /// This makes holes in imported geometry, such as from CAD or SVG files, render as holes
+    /// whichever direction they were drawn in. It costs a test of each subpath of a shape
+    /// against every other, so should only be enabled while encoding such content. The setting
+    /// is kept when the scene is reset.
+    pub fn set_normalize_winding(&mut self, enabled: bool) {
+        self.normalize_winding = enabled;
     }
 
     /// Tally up the bump allocator estimate for the current state of the encoding,
@@ -78,6 +142,73 @@ impl Scene {
         &self.encoding
     }
 
+    /// Serializes the scene with [`Encoding::to_bytes`], such as to cache it on disk or to
+    /// render it in another process.
+    ///
+    /// Only the encoding is written, so scenes with content which is kept outside of it can't
+    /// be serialized, and return [`Error::UnserializableScene`]. These are scenes with
+    /// [filtered layers](Self::push_layer_with_filter), layers with filters or
+    /// [masks](Self::push_layer_with_mask) which haven't been popped yet, and images which
+    /// draw a texture, such as those of a [`CachedLayer`](crate::CachedLayer) or
+    /// [`Renderer::register_external_texture`](crate::Renderer::register_external_texture).
+    /// Masked layers which have been popped are part of the encoding.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        if !self.filter_layers.is_empty() {
+            return Err(Error::UnserializableScene("it has filtered layers"));
+        }
+        if !self.filter_stack.is_empty() || !self.mask_stack.is_empty() {
+            return Err(Error::UnserializableScene(
+                "it has filtered or masked layers which haven't been popped",
+            ));
+        }
+        let draws_texture = self.encoding.resources.patches.iter().any(|patch| {
+            // The images drawing textures have no pixels of their own.
+            matches!(patch, Patch::Image { image, .. } if image.data.data().is_empty()
+                && image.width != 0 && image.height != 0)
+        });
+        if draws_texture {
+            return Err(Error::UnserializableScene("it draws a texture"));
+        }
+        Ok(self.encoding.to_bytes())
+    }
+
+    /// Reads a scene serialized with [`to_bytes`](Self::to_bytes).
+    ///
+    /// See [`Encoding::from_bytes`] for the errors this returns.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Encoding::from_bytes(bytes).map(Self::from)
+    }
+
+    /// Checks the scene for patterns which are known to be slow to render, such as many tiny
+    /// layers or images scaled far down, returning a description of each mistake found.
+    ///
+    /// This is intended to be run occasionally during development, as it walks every path
+    /// in the scene. See [`LintOptions`] for the thresholds used.
+    pub fn lint(&self, options: &LintOptions) -> Vec<Lint> {
+        catalina_encoding::lint(&self.encoding, options)
+    }
+
+    /// Returns the layers of the scene in the order they are pushed, with their depth and
+    /// the shape each is clipped to.
+    ///
+    /// Like [`lint`](Self::lint), this walks every path in the scene, so is intended for
+    /// debugging.
+    pub fn layers(&self) -> Vec<LayerInfo> {
+        catalina_encoding::layers(&self.encoding)
+    }
+
+    /// Returns a scene which outlines the clip of each layer of this one, for finding out
+    /// why content is clipped away or hidden.
+    ///
+    /// The overlay should be appended over the scene with the same transform. Outlines are
+    /// colored by the depth of their layer, and dashed for layers which blend rather than
+    /// only clip. Each is labelled `depth:draw`, with its depth counted from 0 for layers at
+    /// the top level, and the index of the draw object which begins it, which matches the
+    /// indices of [`Lint::draws`].
+    pub fn layer_overlay(&self) -> Self {
+        layer_overlay::layer_overlay(&self.layers())
+    }
+
     /// Returns a mutable reference to the underlying raw encoding.
     ///
     /// This can be used to more easily create invalid scenes, and so should be used with care.
@@ -102,34 +233,306 @@ impl Scene {
         transform: Affine,
         clip: &impl Shape,
     ) {
-        let blend = blend.into();
+        self.encode_layer_clip(transform, clip);
+        self.encode_begin_layer(blend.into(), alpha);
+    }
+
+    /// Pushes a new layer like [`push_layer`](Self::push_layer), clipped by a path given by
+    /// its elements.
+    ///
+    /// Like [`fill_path_elements`](Self::fill_path_elements), the elements are encoded as
+    /// they're produced, except with bump estimation, for which the path is collected first.
+    pub fn push_layer_path_elements(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: impl IntoIterator<Item = PathEl>,
+    ) {
+        let clip = clip.into_iter();
+        if cfg!(feature = "bump_estimate") {
+            self.push_layer(blend, alpha, transform, &BezPath::from_iter(clip));
+            return;
+        }
+        self.begin_layer_clip(transform);
+        if !self.encoding.encode_path_elements(clip, true) {
+            self.encoding.encode_empty_shape();
+        }
+        self.encode_begin_layer(blend.into(), alpha);
+    }
+
+    /// Encodes the start of a layer, after its clip.
+    fn encode_begin_layer(&mut self, blend: BlendMode, alpha: f32) {
         if blend.mix == Mix::Clip && alpha != 1.0 {
             log::warn!("Clip mix mode used with semitransparent alpha");
         }
-        let t = Transform::from_kurbo(&transform);
-        self.encoding.encode_transform(t);
-        self.encoding.encode_fill_style(Fill::NonZero);
+        self.encoding
+            .encode_begin_clip(blend, alpha.clamp(0.0, 1.0));
+    }
+
+    /// Pushes a new non-isolated layer clipped by the specified shape.
+    ///
+    /// Unlike the layers of [`push_layer`](Self::push_layer), which are drawn onto a
+    /// transparent layer and then composed, the content of a non-isolated layer is drawn
+    /// directly onto what is beneath it. Layers with a [`Mix`] mode inside it blend with the
+    /// full backdrop, as for non-isolated groups in SVG (`isolation: auto`) and PDF. The clip
+    /// and `alpha` are then applied to the result, fading between the backdrop and the
+    /// backdrop with the content drawn onto it.
+    pub fn push_non_isolated_layer(&mut self, alpha: f32, transform: Affine, clip: &impl Shape) {
+        self.encode_layer_clip(transform, clip);
+        self.encoding
+            .encode_begin_non_isolated_clip(alpha.clamp(0.0, 1.0));
+    }
+
+    /// Pushes a new knockout layer clipped by the specified shape and composed with previous
+    /// layers using the specified blend mode.
+    ///
+    /// Within a knockout layer, each draw replaces the earlier draws beneath it rather than
+    /// being composed over them, as for knockout groups in PDF. Where a draw is translucent,
+    /// what shows through is the transparent start of the layer rather than its earlier
+    /// content. Layers nested within it are composed with its content as usual.
+    pub fn push_knockout_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.encode_layer_clip(transform, clip);
+        self.encoding.encode_begin_clip_data(
+            DrawBeginClip::new(blend.into(), alpha.clamp(0.0, 1.0)).with_knockout(),
+        );
+    }
+
+    /// Pushes a new knockout layer like [`push_knockout_layer`](Self::push_knockout_layer),
+    /// which isn't isolated, like the layers of
+    /// [`push_non_isolated_layer`](Self::push_non_isolated_layer).
+    ///
+    /// Where a draw is translucent, what shows through is the backdrop of the layer rather
+    /// than the earlier content of the layer.
+    pub fn push_non_isolated_knockout_layer(
+        &mut self,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.encode_layer_clip(transform, clip);
+        self.encoding.encode_begin_clip_data(
+            DrawBeginClip::new_non_isolated(alpha.clamp(0.0, 1.0)).with_knockout(),
+        );
+    }
+
+    /// Encodes the clip shape of a layer which is being pushed.
+    fn encode_layer_clip(&mut self, transform: Affine, clip: &impl Shape) {
+        self.begin_layer_clip(transform);
         if !self.encoding.encode_shape(clip, true) {
             // If the layer shape is invalid, encode a valid empty path. This suppresses
             // all drawing until the layer is popped.
             self.encoding.encode_empty_shape();
             #[cfg(feature = "bump_estimate")]
             {
-                use peniko::kurbo::PathEl;
                 let path = [PathEl::MoveTo(Point::ZERO), PathEl::LineTo(Point::ZERO)];
+                let t = Transform::from_kurbo(&transform);
                 self.estimator.count_path(path.into_iter(), &t, None);
             }
         } else {
             #[cfg(feature = "bump_estimate")]
-            self.estimator.count_path(clip.path_elements(0.1), &t, None);
+            self.estimator.count_path(
+                clip.path_elements(0.1),
+                &Transform::from_kurbo(&transform),
+                None,
+            );
+        }
+    }
+
+    /// Encodes the transform and style of the clip of a layer which is being pushed, before its
+    /// path.
+    fn begin_layer_clip(&mut self, transform: Affine) {
+        if let Some(frame) = self.filter_stack.last_mut() {
+            frame.depth += 1;
+        }
+        if let Some(frame) = self.mask_stack.last_mut() {
+            frame.depth += 1;
         }
         self.encoding
-            .encode_begin_clip(blend, alpha.clamp(0.0, 1.0));
+            .encode_transform(Transform::from_kurbo(&transform));
+        self.encoding.encode_fill_style(Fill::NonZero);
+    }
+
+    /// Pushes a new layer like [`push_layer`](Self::push_layer), whose content is processed
+    /// by `filter` before it is composed with previous layers.
+    ///
+    /// This is either a single [`Filter`] or a [`FilterGraph`] combining several. The
+    /// parameters of the filters are in the coordinate system of `transform`. The content
+    /// is rendered into a separate texture covering the clip shape and the area painted by the
+    /// filter, which is then drawn into the scene as an image. This is more expensive than an
+    /// unfiltered layer, so the clip shape should be kept as small as possible. If the scene is
+    /// [appended](Self::append) with a transform which scales it, the texture is scaled rather
+    /// than the content being rendered at the new scale.
+    pub fn push_layer_with_filter(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+        filter: impl Into<FilterGraph>,
+    ) {
+        self.push_layer_with_filter_at_scale(blend, alpha, transform, clip, filter, 1.0);
+    }
+
+    /// Pushes a new layer like [`push_layer_with_filter`](Self::push_layer_with_filter),
+    /// whose content is rendered and filtered at `raster_scale` times the resolution of the
+    /// scene in each direction.
+    ///
+    /// The filtered texture is scaled back up when it's drawn. This hugely reduces the cost
+    /// of large blurs and shadows, whose result is smooth enough that the lost detail isn't
+    /// visible, but also blurs the content itself, so suits layers where it is hidden or
+    /// blurred anyway. The scale is clamped to between [`MIN_RASTER_SCALE`] and 1.
+    pub fn push_layer_with_filter_at_scale(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+        filter: impl Into<FilterGraph>,
+        raster_scale: f64,
+    ) {
+        if let Some(frame) = self.mask_stack.last_mut() {
+            frame.depth += 1;
+        }
+        let parent = (
+            std::mem::take(&mut self.encoding),
+            std::mem::take(&mut self.filter_layers),
+        );
+        self.filter_stack.push(FilterFrame::new(
+            parent,
+            blend.into(),
+            alpha.clamp(0.0, 1.0),
+            transform,
+            clip.into_path(0.1),
+            &filter.into(),
+            raster_scale.clamp(MIN_RASTER_SCALE, 1.0),
+        ));
+    }
+
+    /// Pushes a new layer like [`push_layer`](Self::push_layer), whose content is multiplied
+    /// by the alpha of `mask` before it is composed with previous layers.
+    ///
+    /// The mask is drawn with `mask_transform` like [`draw_image`](Self::draw_image), and the
+    /// content is hidden wherever the mask doesn't cover it. This suits shapes which are
+    /// easier to paint than to describe with paths, such as photo frames with ragged edges or
+    /// reveals through a brush texture.
+    pub fn push_layer_with_mask(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+        mask: &Image,
+        mask_transform: Affine,
+    ) {
+        self.push_layer(blend, alpha, transform, clip);
+        self.mask_stack.push(MaskFrame {
+            depth: 0,
+            image: mask.clone(),
+            image_transform: mask_transform,
+            transform,
+            clip: clip.into_path(0.1),
+        });
     }
 
     /// Pops the current layer.
     pub fn pop_layer(&mut self) {
-        self.encoding.encode_end_clip();
+        if self.mask_stack.last().is_some_and(|frame| frame.depth == 0) {
+            let frame = self.mask_stack.pop().unwrap();
+            // Keep only the content where the mask is opaque.
+            self.push_layer(
+                BlendMode::new(Mix::Normal, Compose::DestIn),
+                1.0,
+                frame.transform,
+                &frame.clip,
+            );
+            self.draw_image(&frame.image, frame.image_transform);
+            self.pop_layer();
+        }
+        if let Some(frame) = self.mask_stack.last_mut() {
+            frame.depth -= 1;
+        }
+        match self.filter_stack.last_mut() {
+            Some(frame) if frame.depth == 0 => {
+                let frame = self.filter_stack.pop().unwrap();
+                self.pop_filter_layer(frame);
+            }
+            Some(frame) => {
+                frame.depth -= 1;
+                self.encoding.encode_end_clip();
+            }
+            None => self.encoding.encode_end_clip(),
+        }
+    }
+
+    /// Moves the content drawn since `frame` was pushed into a new filtered layer, and draws
+    /// the layer's placeholder image in its place.
+    fn pop_filter_layer(&mut self, frame: FilterFrame) {
+        let bounds = frame.bounds();
+        let (encoding, filter_layers) = frame.parent;
+        let inner = Self {
+            encoding: std::mem::replace(&mut self.encoding, encoding),
+            filter_layers: std::mem::replace(&mut self.filter_layers, filter_layers),
+            flatten_shader: self.flatten_shader.clone(),
+            ..Self::default()
+        };
+        if !bounds.is_finite() || bounds.is_zero_area() {
+            return;
+        }
+        // The transform from the scene to the pixels of the image.
+        let scale = frame.raster_scale;
+        let to_image = Affine::scale(scale) * Affine::translate(-bounds.origin().to_vec2());
+        let mut content = Self::new();
+        content.flatten_shader = self.flatten_shader.clone();
+        content.push_layer(Mix::Normal, 1.0, to_image * frame.transform, &frame.clip);
+        content.append(&inner, Some(to_image));
+        content.pop_layer();
+        let width = (bounds.width() * scale).ceil() as u32;
+        let height = (bounds.height() * scale).ceil() as u32;
+        let data = vec![0_u8; width as usize * height as usize * 4];
+        let image = renderer_image(data, width, height);
+        self.filter_layers.push(FilterLayer {
+            content,
+            graph: frame
+                .graph
+                .relative_to(bounds.origin())
+                .transformed(Affine::scale(scale)),
+            to_layer: frame.transform.inverse() * to_image.inverse(),
+            image: image.clone(),
+        });
+        let image_transform = to_image.inverse();
+        if frame.blend == BlendMode::default() {
+            self.draw_image(&image.with_alpha(frame.alpha), image_transform);
+        } else {
+            self.push_layer(frame.blend, frame.alpha, Affine::IDENTITY, &bounds);
+            self.draw_image(&image, image_transform);
+            self.pop_layer();
+        }
+    }
+
+    /// Returns the layers with filters, which must be rendered before the scene.
+    #[cfg_attr(
+        not(feature = "wgpu"),
+        expect(dead_code, reason = "this can be unused when wgpu feature is not used")
+    )]
+    pub(crate) fn filter_layers(&self) -> &[FilterLayer] {
+        &self.filter_layers
+    }
+
+    /// Removes the layers with filters, for scenes whose filtered layers have already been
+    /// rendered, while keeping the images drawn in their place.
+    #[cfg_attr(
+        not(feature = "wgpu"),
+        expect(dead_code, reason = "this can be unused when wgpu feature is not used")
+    )]
+    pub(crate) fn clear_filter_layers(&mut self) {
+        self.filter_layers.clear();
     }
 
     /// Draw a rounded rectangle blurred with a gaussian filter.
@@ -188,16 +591,17 @@ impl Scene {
         }
     }
 
-    /// Fills a shape using the specified style and brush.
-    #[expect(
-        single_use_lifetimes,
-        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
-    )]
-    pub fn fill<'b>(
+    /// Fills a shape with a hatch pattern of parallel lines.
+    ///
+    /// The lines are placed in the coordinates of `brush_transform` if given, so that the
+    /// pattern of adjacent shapes lines up, and otherwise in the coordinates of the shape.
+    /// This is much cheaper than stroking each line, and the lines don't need to be clipped to
+    /// the shape.
+    pub fn fill_hatch(
         &mut self,
         style: Fill,
         transform: Affine,
-        brush: impl Into<BrushRef<'b>>,
+        hatch: &Hatch,
         brush_transform: Option<Affine>,
         shape: &impl Shape,
     ) {
@@ -213,13 +617,182 @@ impl Scene {
                     self.encoding.swap_last_path_tags();
                 }
             }
-            self.encoding.encode_brush(brush, 1.0);
+            self.encoding.encode_hatch(
+                hatch.color,
+                hatch.angle as f32,
+                hatch.spacing as f32,
+                hatch.line_width as f32,
+                hatch.cross,
+            );
             #[cfg(feature = "bump_estimate")]
             self.estimator
                 .count_path(shape.path_elements(0.1), &t, None);
         }
     }
 
+    /// Fills a shape with a paint evaluated by a registered WGSL function.
+    ///
+    /// The function is evaluated in the coordinates of `brush_transform` if given, and
+    /// otherwise in the coordinates of the shape. See
+    /// [`Renderer::register_custom_paint`](crate::Renderer::register_custom_paint).
+    pub fn fill_custom(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        paint: &CustomPaint,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let t = Transform::from_kurbo(&transform);
+        self.encoding.encode_transform(t);
+        self.encoding.encode_fill_style(style);
+        if self.encoding.encode_shape(shape, true) {
+            if let Some(brush_transform) = brush_transform {
+                if self
+                    .encoding
+                    .encode_transform(Transform::from_kurbo(&(transform * brush_transform)))
+                {
+                    self.encoding.swap_last_path_tags();
+                }
+            }
+            self.encoding.encode_custom_paint(paint.id.0, paint.data);
+            #[cfg(feature = "bump_estimate")]
+            self.estimator
+                .count_path(shape.path_elements(0.1), &t, None);
+        }
+    }
+
+    /// Fills a shape using the specified style and brush.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    pub fn fill<'b>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.fill_with_alpha(style, transform, brush, 1.0, brush_transform, shape);
+    }
+
+    /// Fills a path given by its elements, using the specified style and brush.
+    ///
+    /// This is the same as [`fill`](Self::fill) for paths which aren't a [`Shape`]. The
+    /// elements are encoded as they're produced, so procedural geometry, such as charts or
+    /// outlines from other libraries, never needs to be built into a [`BezPath`]. Paths built
+    /// every frame can also be kept in an arena owned by the application and passed as slices
+    /// of it with `elements.iter().copied()`, so that encoding them doesn't allocate.
+    ///
+    /// When the path must be traversed more than once, for
+    /// [winding normalization](Self::set_normalize_winding) or bump estimation, it's collected
+    /// into a [`BezPath`] first.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    pub fn fill_path_elements<'b>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        path: impl IntoIterator<Item = PathEl>,
+    ) {
+        let path = path.into_iter();
+        if cfg!(feature = "bump_estimate") || (self.normalize_winding && style == Fill::NonZero) {
+            let path = BezPath::from_iter(path);
+            self.fill(style, transform, brush, brush_transform, &path);
+            return;
+        }
+        self.encoding
+            .encode_transform(Transform::from_kurbo(&transform));
+        self.encoding.encode_fill_style(style);
+        if self.encoding.encode_path_elements(path, true) {
+            self.encode_draw_brush(transform, brush, 1.0, brush_transform);
+        }
+    }
+
+    /// Fills a shape, multiplying the opacity of the brush by `alpha`.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    fn fill_with_alpha<'b>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        alpha: f32,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        if self.normalize_winding && style == Fill::NonZero {
+            let path = BezPath::from_iter(shape.path_elements(0.1));
+            // A single subpath fills the same whichever way it winds.
+            let n_subpaths = path
+                .elements()
+                .iter()
+                .filter(|el| matches!(el, PathEl::MoveTo(_)))
+                .count();
+            if n_subpaths > 1 {
+                let path = crate::normalize_winding(&path);
+                self.encode_fill(style, transform, brush, alpha, brush_transform, &path);
+                return;
+            }
+        }
+        self.encode_fill(style, transform, brush, alpha, brush_transform, shape);
+    }
+
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    fn encode_fill<'b>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        alpha: f32,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let t = Transform::from_kurbo(&transform);
+        self.encoding.encode_transform(t);
+        self.encoding.encode_fill_style(style);
+        if self.encoding.encode_shape(shape, true) {
+            self.encode_draw_brush(transform, brush, alpha, brush_transform);
+            #[cfg(feature = "bump_estimate")]
+            self.estimator
+                .count_path(shape.path_elements(0.1), &t, None);
+        }
+    }
+
+    /// Encodes the brush of a draw whose path has just been encoded with `transform`.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    fn encode_draw_brush<'b>(
+        &mut self,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        alpha: f32,
+        brush_transform: Option<Affine>,
+    ) {
+        if let Some(brush_transform) = brush_transform {
+            if self
+                .encoding
+                .encode_transform(Transform::from_kurbo(&(transform * brush_transform)))
+            {
+                self.encoding.swap_last_path_tags();
+            }
+        }
+        self.encoding.encode_brush(brush, alpha);
+    }
+
     /// Strokes a shape using the specified style and brush.
     #[expect(
         single_use_lifetimes,
@@ -280,15 +853,7 @@ impl Scene {
                     .encode_path_elements(dashed.into_iter(), false)
             };
             if encode_result {
-                if let Some(brush_transform) = brush_transform {
-                    if self
-                        .encoding
-                        .encode_transform(Transform::from_kurbo(&(transform * brush_transform)))
-                    {
-                        self.encoding.swap_last_path_tags();
-                    }
-                }
-                self.encoding.encode_brush(brush, 1.0);
+                self.encode_draw_brush(transform, brush, 1.0, brush_transform);
             }
         } else {
             let stroked = peniko::kurbo::stroke(
@@ -301,6 +866,190 @@ impl Scene {
         }
     }
 
+    /// Strokes a path given by its elements, using the specified style and brush.
+    ///
+    /// This is the same as [`stroke`](Self::stroke) for paths which aren't a [`Shape`]. Like
+    /// [`fill_path_elements`](Self::fill_path_elements), the elements are encoded as they're
+    /// produced, except for dashed strokes and with bump estimation, for which the path is
+    /// collected first.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    pub fn stroke_path_elements<'b>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        path: impl IntoIterator<Item = PathEl>,
+    ) {
+        let path = path.into_iter();
+        if cfg!(feature = "bump_estimate") || !style.dash_pattern.is_empty() {
+            let path = BezPath::from_iter(path);
+            self.stroke(style, transform, brush, brush_transform, &path);
+            return;
+        }
+        self.encoding
+            .encode_transform(Transform::from_kurbo(&transform));
+        self.encoding.encode_stroke_style(style);
+        if self.encoding.encode_path_elements(path, false) {
+            self.encode_draw_brush(transform, brush, 1.0, brush_transform);
+        }
+    }
+
+    /// Strokes a shape with a width in device pixels, whatever the transform, like the
+    /// `non-scaling-stroke` vector effect of SVG.
+    ///
+    /// With [`stroke`](Self::stroke), the pen is transformed along with the shape, so a
+    /// non-uniform scale draws lines of different widths at different angles. This instead
+    /// transforms the shape on the CPU and strokes it in device space, so hairlines and
+    /// outlines keep their width when a view is zoomed or stretched. The dash pattern is also
+    /// in device pixels. Brushes are still positioned by `transform` and `brush_transform`.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    pub fn stroke_non_scaling<'b>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        // Curves are flattened in user space, so the tolerance is scaled to keep the error in
+        // device space the same as for other strokes.
+        const SHAPE_TOLERANCE: f64 = 0.01;
+        let scale = max_scale(transform);
+        let tolerance = SHAPE_TOLERANCE / scale.max(f64::EPSILON);
+        let path = transform * shape.into_path(tolerance);
+        let brush = brush.into();
+        let brush_transform = match brush {
+            BrushRef::Solid(_) => None,
+            _ => Some(transform * brush_transform.unwrap_or(Affine::IDENTITY)),
+        };
+        self.stroke(style, Affine::IDENTITY, brush, brush_transform, &path);
+    }
+
+    /// Strokes a shape with a dash pattern that is shifted by [`RenderParams::dash_phase`].
+    ///
+    /// Unlike [`stroke`](Self::stroke), the dashes are computed when the scene is rendered, so
+    /// that marching ants and similar animations only need to change the render parameters
+    /// rather than encoding the scene again. If `style` has no dash pattern, this is the same
+    /// as [`stroke`](Self::stroke).
+    ///
+    /// [`RenderParams::dash_phase`]: crate::RenderParams::dash_phase
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    pub fn stroke_with_dash_phase<'b>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        const SHAPE_TOLERANCE: f64 = 0.01;
+        if style.dash_pattern.is_empty() {
+            self.stroke(style, transform, brush, brush_transform, shape);
+            return;
+        }
+        let t = Transform::from_kurbo(&transform);
+        self.encoding.encode_transform(t);
+        self.encoding.encode_stroke_style(style);
+        let path = shape.into_path(SHAPE_TOLERANCE);
+        // The dashes depend on the phase, so this estimates using the phase in the style.
+        #[cfg(feature = "bump_estimate")]
+        self.estimator.count_path(
+            peniko::kurbo::dash(path.iter(), style.dash_offset, &style.dash_pattern),
+            &t,
+            Some(style),
+        );
+        self.encoding.encode_dashed_stroke(path, style);
+        if let Some(brush_transform) = brush_transform {
+            if self
+                .encoding
+                .encode_transform(Transform::from_kurbo(&(transform * brush_transform)))
+            {
+                self.encoding.swap_last_path_tags();
+            }
+        }
+        self.encoding.encode_brush(brush, 1.0);
+    }
+
+    /// Strokes a line through points sampled from a pen or stylus, with round ends and joins.
+    ///
+    /// The width and opacity vary along the line according to the attributes of each point, as
+    /// interpreted by `dynamics`. The line is encoded as one draw object for each run of points
+    /// with the same quantized opacity, rather than one per sample.
+    #[expect(
+        single_use_lifetimes,
+        reason = "False positive: https://github.com/rust-lang/rust/issues/129255"
+    )]
+    pub fn stroke_points<'b>(
+        &mut self,
+        width: f64,
+        dynamics: &StrokeDynamics,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        points: &[StrokePoint],
+    ) {
+        let brush = brush.into().to_owned();
+        let mut start = 0;
+        while start < points.len() {
+            let level = dynamics.opacity_level(&points[start]);
+            let mut end = start + 1;
+            while end < points.len() && dynamics.opacity_level(&points[end]) == level {
+                end += 1;
+            }
+            // Include the first point of the next run, so that the runs join up.
+            let run = &points[start..(end + 1).min(points.len())];
+            if level != 0 {
+                self.fill_with_alpha(
+                    Fill::NonZero,
+                    transform,
+                    &brush,
+                    dynamics.level_opacity(level),
+                    brush_transform,
+                    &dynamics.outline(width, run),
+                );
+            }
+            start = end;
+        }
+    }
+
+    /// Stamps a brush tip along a line through points sampled from a pen or stylus.
+    ///
+    /// The size and opacity of each stamp follow the attributes of the points, as interpreted
+    /// by `dynamics`. Each stamp is encoded as a separate image draw.
+    pub fn stamp_points(
+        &mut self,
+        tip: &BrushTip,
+        dynamics: &StrokeDynamics,
+        transform: Affine,
+        points: &[StrokePoint],
+    ) {
+        tip.stamps(dynamics, points, |placement, alpha| {
+            self.stamp(tip, transform * placement, alpha);
+        });
+    }
+
+    /// Stamps a brush tip along the outline of a shape.
+    pub fn stamp_shape(&mut self, tip: &BrushTip, transform: Affine, shape: &impl Shape) {
+        tip.stamps_along(shape, |placement, alpha| {
+            self.stamp(tip, transform * placement, alpha);
+        });
+    }
+
+    fn stamp(&mut self, tip: &BrushTip, transform: Affine, alpha: f32) {
+        let bounds = Rect::new(0.0, 0.0, tip.image.width.into(), tip.image.height.into());
+        self.fill_with_alpha(Fill::NonZero, transform, &tip.image, alpha, None, &bounds);
+    }
+
     /// Draws an image at its natural size with the given transform.
     pub fn draw_image(&mut self, image: &Image, transform: Affine) {
         self.fill(
@@ -312,6 +1061,28 @@ impl Scene {
         );
     }
 
+    /// Draws an image at its natural size with the given transform, choosing what is drawn
+    /// until the image is uploaded.
+    ///
+    /// See [`UploadQueue`](crate::UploadQueue) for when images are uploaded.
+    pub fn draw_image_with_placeholder(
+        &mut self,
+        image: &Image,
+        transform: Affine,
+        placeholder: Placeholder,
+    ) {
+        let t = Transform::from_kurbo(&transform);
+        self.encoding.encode_transform(t);
+        self.encoding.encode_fill_style(Fill::NonZero);
+        let rect = Rect::new(0.0, 0.0, image.width as f64, image.height as f64);
+        if self.encoding.encode_shape(&rect, true) {
+            self.encoding
+                .encode_image_with_placeholder(image, 1.0, Some(placeholder));
+            #[cfg(feature = "bump_estimate")]
+            self.estimator.count_path(rect.path_elements(0.1), &t, None);
+        }
+    }
+
     /// Returns a builder for encoding a glyph run.
     pub fn draw_glyphs(&mut self, font: &Font) -> DrawGlyphs<'_> {
         // TODO: Integrate `BumpEstimator` with the glyph cache.
@@ -327,9 +1098,96 @@ impl Scene {
         self.encoding.append(&other.encoding, &t);
         #[cfg(feature = "bump_estimate")]
         self.estimator.append(&other.estimator, t.as_ref());
+        self.filter_layers.extend_from_slice(&other.filter_layers);
+    }
+
+    /// Appends a fragment prepared by a [`FragmentResolver`].
+    ///
+    /// The given transform is applied to every transform in the fragment. As the glyph runs
+    /// of the fragment were already resolved, this only copies the encoded data, which makes
+    /// it suitable for assembling scenes from content streamed in by worker threads.
+    pub fn append_fragment(&mut self, fragment: &SceneFragment, transform: Option<Affine>) {
+        let t = transform.as_ref().map(Transform::from_kurbo);
+        self.encoding.append_fragment(&fragment.fragment, &t);
+        #[cfg(feature = "bump_estimate")]
+        self.estimator.append(&fragment.estimator, t.as_ref());
+        self.filter_layers
+            .extend_from_slice(&fragment.filter_layers);
+    }
+}
+
+/// Prepares scenes to be appended to other scenes with very little work.
+///
+/// This resolves the glyph runs of a scene into plain paths, which is the most expensive part
+/// of appending and rendering text. Each thread preparing fragments should have its own
+/// resolver, which caches the outlines of the glyphs it has seen.
+///
+/// ```no_run
+/// # use catalina::{FragmentResolver, Scene};
+/// # fn example(tile: &Scene, scene: &mut Scene) {
+/// // On a worker thread:
+/// let mut resolver = FragmentResolver::new();
+/// let fragment = resolver.resolve(tile);
+/// // On the thread which renders the scene:
+/// scene.append_fragment(&fragment, None);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct FragmentResolver {
+    resolver: Resolver,
+}
+
+impl FragmentResolver {
+    /// Creates a new resolver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the glyph runs of `scene`, producing a fragment which can be appended with
+    /// [`Scene::append_fragment`].
+    pub fn resolve(&mut self, scene: &Scene) -> SceneFragment {
+        SceneFragment {
+            fragment: self.resolver.resolve_fragment(&scene.encoding),
+            #[cfg(feature = "bump_estimate")]
+            estimator: scene.estimator.clone(),
+            filter_layers: scene.filter_layers.clone(),
+        }
     }
 }
 
+/// A scene whose glyph runs have been resolved by a [`FragmentResolver`].
+#[derive(Clone, Default)]
+pub struct SceneFragment {
+    fragment: Fragment,
+    #[cfg(feature = "bump_estimate")]
+    estimator: catalina_encoding::BumpEstimator,
+    filter_layers: Vec<FilterLayer>,
+}
+static_assertions::assert_impl_all!(SceneFragment: Send, Sync);
+
+impl SceneFragment {
+    /// Loads a static asset compiled from the encoding of a scene with
+    /// [`compile_static`](crate::compile_static), such as one embedded with `include_bytes!`.
+    ///
+    /// Only the encoding of the scene is compiled, so scenes with
+    /// [filtered layers](Scene::push_layer_with_filter) can't be loaded this way.
+    pub fn load_static(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self {
+            fragment: catalina_encoding::load_static(bytes)?,
+            ..Self::default()
+        })
+    }
+}
+
+/// Shared images, fonts and [scene fragments](SceneFragment), referred to by generational
+/// handles.
+///
+/// See [`catalina_encoding::ResourceTable`] for details. Scenes which draw the resources of a
+/// table can be serialized with [`Encoding::to_bytes_with_table`], which refers to them by
+/// handle rather than copying their data.
+pub type ResourceTable = catalina_encoding::ResourceTable<SceneFragment>;
+static_assertions::assert_impl_all!(ResourceTable: Send, Sync);
+
 impl From<Encoding> for Scene {
     fn from(encoding: Encoding) -> Self {
         // It's fine to create a default estimator here, and that field will be
@@ -339,6 +1197,11 @@ impl From<Encoding> for Scene {
             #[cfg(feature = "bump_estimate")]
             estimator: catalina_encoding::BumpEstimator::default(),
             flatten_shader: WgpuVune::default(),
+            filter_layers: Vec::new(),
+            filter_stack: Vec::new(),
+            mask_stack: Vec::new(),
+            bitmap_glyphs: HashMap::new(),
+            normalize_winding: false,
         }
     }
 }
@@ -346,6 +1209,10 @@ impl From<Encoding> for Scene {
 /// Builder for encoding a glyph run.
 ///
 /// Created using [`Scene::draw_glyphs`].
+///
+/// Glyph outlines are encoded as paths and rasterized on the GPU along with the rest of the
+/// scene, so new glyphs and sizes don't need rasterizing on the CPU. Only the bitmaps of
+/// bitmap glyphs, such as those of emoji fonts, are uploaded into the image atlas.
 pub struct DrawGlyphs<'a> {
     scene: &'a mut Scene,
     run: GlyphRun,
@@ -461,6 +1328,10 @@ impl<'a> DrawGlyphs<'a> {
     ///
     /// For these glyphs, the given [brush](Self::brush) is used as the "foreground color", and should
     /// be [`Solid`](Brush::Solid) for maximum compatibility.
+    ///
+    /// The glyphs are drawn exactly as given. No shaping is done, so ligatures, alternates and
+    /// other OpenType features chosen when the run was shaped are kept.
+    /// [`SimpleShaper`] can shape simple text with a choice of features.
     pub fn draw(mut self, style: impl Into<StyleRef<'a>>, glyphs: impl Iterator<Item = Glyph>) {
         let font_index = self.run.font.index;
         let font = skrifa::FontRef::from_index(self.run.font.data.as_ref(), font_index).unwrap();
@@ -556,90 +1427,26 @@ impl<'a> DrawGlyphs<'a> {
             match emoji {
                 // TODO: This really needs to be moved to resolve time to get proper caching, etc.
                 EmojiLikeGlyph::Bitmap(bitmap) => {
-                    let image = match bitmap.data {
-                        bitmap::BitmapData::Bgra(data) => {
-                            if bitmap.width * bitmap.height * 4
-                                != u32::try_from(data.len()).unwrap()
-                            {
-                                // TODO: Error once?
-                                log::error!("Invalid font");
-                                continue;
-                            }
-                            let data: Box<[u8]> = data
-                                .chunks_exact(4)
-                                .flat_map(|bytes| {
-                                    let [b, g, r, a] = bytes.try_into().unwrap();
-                                    [r, g, b, a]
-                                })
-                                .collect();
-                            Image::new(
-                                // TODO: The design of the Blob type forces the double boxing
-                                Blob::new(Arc::new(data)),
-                                peniko::ImageFormat::Rgba8,
-                                bitmap.width,
-                                bitmap.height,
-                            )
-                        }
-                        bitmap::BitmapData::Png(data) => {
-                            let mut decoder = png::Decoder::new(data);
-                            decoder.set_transformations(
-                                Transformations::ALPHA | Transformations::STRIP_16,
-                            );
-                            let Ok(mut reader) = decoder.read_info() else {
-                                log::error!("Invalid PNG in font");
-                                continue;
-                            };
-
-                            if reader.output_color_type() != (ColorType::Rgba, BitDepth::Eight) {
-                                log::error!("Unsupported `output_color_type`");
-                                continue;
-                            }
-                            let mut buf = vec![0; reader.output_buffer_size()].into_boxed_slice();
-
-                            let info = reader.next_frame(&mut buf).unwrap();
-                            if info.width != bitmap.width || info.height != bitmap.height {
-                                log::error!("Unexpected width and height");
-                                continue;
-                            }
-                            Image::new(
-                                // TODO: The design of the Blob type forces the double boxing
-                                Blob::new(Arc::new(buf)),
-                                peniko::ImageFormat::Rgba8,
-                                bitmap.width,
-                                bitmap.height,
-                            )
-                        }
-                        bitmap::BitmapData::Mask(mask) => {
-                            // TODO: Is this code worth having?
-                            let Some(masks) = bitmap_masks(mask.bpp) else {
-                                // TODO: Error once?
-                                log::warn!("Invalid bpp in bitmap glyph");
-                                continue;
-                            };
-
-                            if !mask.is_packed {
-                                // TODO: Error once?
-                                log::warn!("Unpacked mask data in font not yet supported");
-                                // TODO: How do we get the font name here?
-                                continue;
-                            }
-                            let alphas = mask.data.iter().flat_map(|it| {
-                                masks
-                                    .iter()
-                                    .map(move |mask| (it & mask.mask) >> mask.right_shift)
+                    // Each distinct bitmap is decoded once per scene, and then shares an image,
+                    // so repeated emoji are only uploaded to the image atlas once. Glyphs which
+                    // aren't drawn again before the next reset are then freed.
+                    let key = BitmapGlyphKey {
+                        font: self.run.font.data.id(),
+                        font_index,
+                        glyph: glyph.id,
+                        ppem: (bitmap.ppem_x.to_bits(), bitmap.ppem_y.to_bits()),
+                    };
+                    let decoded =
+                        self.scene
+                            .bitmap_glyphs
+                            .entry(key)
+                            .or_insert_with(|| DecodedBitmapGlyph {
+                                image: decode_bitmap_glyph(&bitmap),
+                                used: false,
                             });
-                            let data: Box<[u8]> = alphas
-                                .flat_map(|alpha| [u8::MAX, u8::MAX, u8::MAX, alpha])
-                                .collect();
-
-                            Image::new(
-                                // TODO: The design of the Blob type forces the double boxing
-                                Blob::new(Arc::new(data)),
-                                peniko::ImageFormat::Rgba8,
-                                bitmap.width,
-                                bitmap.height,
-                            )
-                        }
+                    decoded.used = true;
+                    let Some(image) = decoded.image.clone() else {
+                        continue;
                     };
                     let image = image.multiply_alpha(self.brush_alpha);
                     // Split into multiple statements because rustfmt breaks
@@ -712,6 +1519,119 @@ impl<'a> DrawGlyphs<'a> {
     }
 }
 
+/// A layer with an image mask which hasn't been popped yet.
+#[derive(Clone, Debug)]
+struct MaskFrame {
+    /// The number of layers pushed inside the masked layer which haven't yet been popped.
+    depth: u32,
+    image: Image,
+    image_transform: Affine,
+    transform: Affine,
+    clip: BezPath,
+}
+
+/// Identifies a bitmap glyph within the strike of a font it was taken from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct BitmapGlyphKey {
+    font: u64,
+    font_index: u32,
+    glyph: u32,
+    /// The bits of the strike's pixels per em, horizontally and vertically.
+    ppem: (u32, u32),
+}
+
+/// A bitmap glyph decoded by a scene.
+#[derive(Clone, Debug)]
+struct DecodedBitmapGlyph {
+    /// The image of the glyph, or `None` if it couldn't be decoded.
+    image: Option<Image>,
+    /// Whether the glyph has been drawn since the scene was last reset.
+    used: bool,
+}
+
+/// Decodes a bitmap glyph into an image, or returns `None` if it is invalid or unsupported.
+fn decode_bitmap_glyph(bitmap: &bitmap::BitmapGlyph<'_>) -> Option<Image> {
+    let image = match bitmap.data.clone() {
+        bitmap::BitmapData::Bgra(data) => {
+            if bitmap.width * bitmap.height * 4 != u32::try_from(data.len()).unwrap() {
+                log::error!("Invalid font");
+                return None;
+            }
+            let data: Box<[u8]> = data
+                .chunks_exact(4)
+                .flat_map(|bytes| {
+                    let [b, g, r, a] = bytes.try_into().unwrap();
+                    [r, g, b, a]
+                })
+                .collect();
+            Image::new(
+                // TODO: The design of the Blob type forces the double boxing
+                Blob::new(Arc::new(data)),
+                peniko::ImageFormat::Rgba8,
+                bitmap.width,
+                bitmap.height,
+            )
+        }
+        bitmap::BitmapData::Png(data) => {
+            let mut decoder = png::Decoder::new(data);
+            decoder.set_transformations(Transformations::ALPHA | Transformations::STRIP_16);
+            let Ok(mut reader) = decoder.read_info() else {
+                log::error!("Invalid PNG in font");
+                return None;
+            };
+
+            if reader.output_color_type() != (ColorType::Rgba, BitDepth::Eight) {
+                log::error!("Unsupported `output_color_type`");
+                return None;
+            }
+            let mut buf = vec![0; reader.output_buffer_size()].into_boxed_slice();
+
+            let info = reader.next_frame(&mut buf).unwrap();
+            if info.width != bitmap.width || info.height != bitmap.height {
+                log::error!("Unexpected width and height");
+                return None;
+            }
+            Image::new(
+                // TODO: The design of the Blob type forces the double boxing
+                Blob::new(Arc::new(buf)),
+                peniko::ImageFormat::Rgba8,
+                bitmap.width,
+                bitmap.height,
+            )
+        }
+        bitmap::BitmapData::Mask(mask) => {
+            // TODO: Is this code worth having?
+            let Some(masks) = bitmap_masks(mask.bpp) else {
+                log::warn!("Invalid bpp in bitmap glyph");
+                return None;
+            };
+
+            if !mask.is_packed {
+                log::warn!("Unpacked mask data in font not yet supported");
+                // TODO: How do we get the font name here?
+                return None;
+            }
+            let alphas = mask.data.iter().flat_map(|it| {
+                masks
+                    .iter()
+                    .map(move |mask| (it & mask.mask) >> mask.right_shift)
+            });
+            let data: Box<[u8]> = alphas
+                .flat_map(|alpha| [u8::MAX, u8::MAX, u8::MAX, alpha])
+                .collect();
+
+            Image::new(
+                // TODO: The design of the Blob type forces the double boxing
+                Blob::new(Arc::new(data)),
+                peniko::ImageFormat::Rgba8,
+                bitmap.width,
+                bitmap.height,
+            )
+        }
+    };
+    Some(image)
+}
+
 struct BitmapMask {
     mask: u8,
     right_shift: u8,
@@ -1064,3 +1984,13 @@ impl ColorStopsSource for ColorStopsConverter<'_> {
         }
     }
 }
+
+/// Returns the largest factor by which `transform` scales any direction, which is its largest
+/// singular value.
+pub(crate) fn max_scale(transform: Affine) -> f64 {
+    let [a, b, c, d, _, _] = transform.as_coeffs();
+    let sum = a * a + b * b + c * c + d * d;
+    let det = a * d - b * c;
+    let discriminant = (sum * sum - 4.0 * det * det).max(0.0);
+    ((sum + discriminant.sqrt()) * 0.5).sqrt()
+}
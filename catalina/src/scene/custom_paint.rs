@@ -0,0 +1,63 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Paints evaluated by user provided WGSL functions.
+
+use catalina_encoding::CUSTOM_PAINT_DATA_WORDS;
+
+/// Identifies a paint function registered with a renderer.
+///
+/// Ids are only meaningful to the renderer which returned them, and to renderers which
+/// registered the same paints in the same order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CustomPaintId(pub(crate) u32);
+
+/// A paint evaluated by a registered WGSL function, for
+/// [`Scene::fill_custom`](crate::Scene::fill_custom).
+///
+/// The function is passed a small blob of data, such as colors or parameters, which is stored
+/// with each fill.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CustomPaint {
+    /// The paint function.
+    pub id: CustomPaintId,
+    /// The data passed to the paint function, as a `vec4<u32>`.
+    pub data: [u32; CUSTOM_PAINT_DATA_WORDS],
+}
+
+impl CustomPaint {
+    /// Creates a paint using the function `id`, with data of zeros.
+    pub fn new(id: CustomPaintId) -> Self {
+        Self {
+            id,
+            data: [0; CUSTOM_PAINT_DATA_WORDS],
+        }
+    }
+
+    /// Builder method for setting the data from bytes, which are zero extended to the size of
+    /// the data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is longer than 16 bytes.
+    #[must_use]
+    pub fn with_bytes(mut self, bytes: &[u8]) -> Self {
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut self.data);
+        assert!(
+            bytes.len() <= data.len(),
+            "custom paint data is limited to {} bytes",
+            data.len()
+        );
+        data.fill(0);
+        data[..bytes.len()].copy_from_slice(bytes);
+        self
+    }
+
+    /// Builder method for setting the data from floats, which are read in WGSL with
+    /// `bitcast<vec4<f32>>`.
+    #[must_use]
+    pub fn with_floats(mut self, floats: [f32; CUSTOM_PAINT_DATA_WORDS]) -> Self {
+        self.data = floats.map(f32::to_bits);
+        self
+    }
+}
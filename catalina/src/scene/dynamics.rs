@@ -0,0 +1,276 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Per-point attributes for strokes drawn with a pen or stylus.
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use peniko::kurbo::{Arc, BezPath, Point, Vec2};
+
+/// The tolerance used when flattening the round ends of each segment, in local coordinates.
+const ARC_TOLERANCE: f64 = 0.01;
+
+/// A point sampled from an input device, with the attributes which modulate the stroke there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokePoint {
+    /// The position of the point, in the coordinate space of the stroke.
+    pub position: Point,
+    /// The pressure applied at this point, in `0..=1`.
+    ///
+    /// Devices without pressure support should report `0.5`, as pointer events do.
+    pub pressure: f32,
+    /// The tilt of the stylus from vertical along the x and y axes, in radians.
+    pub tilt: Vec2,
+    /// The speed of the pointer at this point, in units of the stroke's coordinate space per
+    /// second.
+    pub velocity: f64,
+}
+
+impl StrokePoint {
+    /// Creates a point with the given position and pressure, no tilt and no velocity.
+    pub fn new(position: impl Into<Point>, pressure: f32) -> Self {
+        Self {
+            position: position.into(),
+            pressure,
+            tilt: Vec2::ZERO,
+            velocity: 0.0,
+        }
+    }
+}
+
+/// How the attributes of each [`StrokePoint`] modulate the width and opacity of a stroke.
+///
+/// This is used by [`Scene::stroke_points`](crate::Scene::stroke_points), and can also be used
+/// by custom brush engines to interpret the same attributes consistently.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeDynamics {
+    /// How strongly pressure affects the width, in `0..=1`.
+    ///
+    /// At `1.0`, the width scales linearly from zero at no pressure to the full width at full
+    /// pressure. At `0.0`, pressure doesn't affect the width.
+    pub pressure_width: f32,
+    /// How strongly pressure affects the opacity, in `0..=1`, in the same way as
+    /// [`pressure_width`](Self::pressure_width).
+    pub pressure_opacity: f32,
+    /// How much faster movement thins the stroke.
+    ///
+    /// The width is divided by `1 + velocity_thinning * velocity`.
+    pub velocity_thinning: f64,
+    /// How much tilting the stylus widens the stroke, as when shading with the side of a pencil.
+    ///
+    /// The width is multiplied by `1 + tilt_width * t`, where `t` is the angle of the stylus
+    /// from vertical as a fraction of a right angle.
+    pub tilt_width: f64,
+    /// The number of distinct opacities used when pressure affects opacity, at least 2.
+    ///
+    /// Each run of points with the same quantized opacity is encoded as a single draw object,
+    /// so fewer levels give cheaper scenes, at the cost of visible banding.
+    pub opacity_levels: u8,
+}
+
+impl Default for StrokeDynamics {
+    fn default() -> Self {
+        Self {
+            pressure_width: 1.0,
+            pressure_opacity: 0.0,
+            velocity_thinning: 0.0,
+            tilt_width: 0.0,
+            opacity_levels: 16,
+        }
+    }
+}
+
+impl StrokeDynamics {
+    /// Returns the factor by which the width of the stroke is multiplied at `point`.
+    pub fn width_scale(&self, point: &StrokePoint) -> f64 {
+        let pressure = modulate(point.pressure, self.pressure_width);
+        let thinning = 1.0 + self.velocity_thinning.max(0.0) * point.velocity.abs();
+        let tilt = (point.tilt.hypot() / FRAC_PI_2).min(1.0);
+        let widening = 1.0 + self.tilt_width.max(0.0) * tilt;
+        f64::from(pressure) * widening / thinning
+    }
+
+    /// Returns the opacity of the stroke at `point`, in `0..=1`.
+    pub fn opacity(&self, point: &StrokePoint) -> f32 {
+        modulate(point.pressure, self.pressure_opacity)
+    }
+
+    /// Returns the opacity at `point` quantized to one of the [`opacity_levels`](Self::opacity_levels).
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The opacity is in 0..=1, so the result is at most `max_level`."
+    )]
+    pub(crate) fn opacity_level(&self, point: &StrokePoint) -> u8 {
+        let max_level = self.opacity_levels.max(2) - 1;
+        (self.opacity(point) * f32::from(max_level)).round() as u8
+    }
+
+    /// Returns the opacity of a level returned by `opacity_level`.
+    pub(crate) fn level_opacity(&self, level: u8) -> f32 {
+        f32::from(level) / f32::from(self.opacity_levels.max(2) - 1)
+    }
+
+    /// Computes the outline of a stroke with round ends through `points`, which should be
+    /// filled with the non-zero fill rule.
+    ///
+    /// Each segment is outlined as the convex hull of the circles at its end points, so that
+    /// the width varies smoothly along it. All subpaths have the same orientation so that
+    /// overlaps between them don't cancel out.
+    pub fn outline(&self, width: f64, points: &[StrokePoint]) -> BezPath {
+        let mut path = BezPath::new();
+        let radius = |point: &StrokePoint| 0.5 * width * self.width_scale(point);
+        match points {
+            [] => {}
+            [point] => push_circle(&mut path, point.position, radius(point)),
+            _ => {
+                for pair in points.windows(2) {
+                    push_segment(
+                        &mut path,
+                        pair[0].position,
+                        radius(&pair[0]),
+                        pair[1].position,
+                        radius(&pair[1]),
+                    );
+                }
+            }
+        }
+        path
+    }
+}
+
+/// Interpolates between full strength and `value` by `amount`, both clamped to `0..=1`.
+fn modulate(value: f32, amount: f32) -> f32 {
+    let amount = amount.clamp(0.0, 1.0);
+    1.0 - amount * (1.0 - value.clamp(0.0, 1.0))
+}
+
+/// Adds a circle, oriented in the same direction as the segments from `push_segment`.
+fn push_circle(path: &mut BezPath, center: Point, radius: f64) {
+    if !radius.is_finite() || radius <= 0.0 {
+        return;
+    }
+    path.move_to(center + Vec2::new(radius, 0.0));
+    push_arc(path, center, radius, 0.0, -TAU);
+    path.close_path();
+}
+
+/// Adds the convex hull of the circles at `p0` and `p1`.
+fn push_segment(path: &mut BezPath, p0: Point, r0: f64, p1: Point, r1: f64) {
+    let r0 = r0.max(0.0);
+    let r1 = r1.max(0.0);
+    let d = p1 - p0;
+    let length = d.hypot();
+    if length <= (r0 - r1).abs() {
+        // One circle contains the other, so the hull is just the larger circle.
+        if r0 >= r1 {
+            push_circle(path, p0, r0);
+        } else {
+            push_circle(path, p1, r1);
+        }
+        return;
+    }
+    // The outer tangents touch each circle at angles `direction ± spread`.
+    let direction = d.atan2();
+    let spread = ((r0 - r1) / length).acos();
+    let tangent =
+        |center: Point, radius: f64, angle: f64| center + Vec2::from_angle(angle) * radius;
+    path.move_to(tangent(p0, r0, direction + spread));
+    path.line_to(tangent(p1, r1, direction + spread));
+    push_arc(path, p1, r1, direction + spread, -2.0 * spread);
+    path.line_to(tangent(p0, r0, direction - spread));
+    push_arc(path, p0, r0, direction - spread, -2.0 * (PI - spread));
+    path.close_path();
+}
+
+/// Adds a circular arc continuing from the current point, which should be its start.
+fn push_arc(path: &mut BezPath, center: Point, radius: f64, start_angle: f64, sweep_angle: f64) {
+    if radius <= 0.0 {
+        return;
+    }
+    let arc = Arc {
+        center,
+        radii: Vec2::new(radius, radius),
+        start_angle,
+        sweep_angle,
+        x_rotation: 0.0,
+    };
+    for el in arc.append_iter(ARC_TOLERANCE) {
+        path.push(el);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::kurbo::{Shape, Vec2};
+
+    use super::{StrokeDynamics, StrokePoint};
+
+    #[test]
+    fn width_follows_pressure() {
+        let dynamics = StrokeDynamics::default();
+        let scale = |pressure| dynamics.width_scale(&StrokePoint::new((0.0, 0.0), pressure));
+        assert_eq!(scale(1.0), 1.0);
+        assert_eq!(scale(0.5), 0.5);
+        assert_eq!(scale(0.0), 0.0);
+        // Pressure outside of `0..=1` is clamped.
+        assert_eq!(scale(2.0), 1.0);
+        // Half strength only narrows the stroke to half width at no pressure.
+        let half = StrokeDynamics {
+            pressure_width: 0.5,
+            ..dynamics
+        };
+        assert_eq!(half.width_scale(&StrokePoint::new((0.0, 0.0), 0.0)), 0.5);
+        let constant = StrokeDynamics {
+            pressure_width: 0.0,
+            ..dynamics
+        };
+        assert_eq!(
+            constant.width_scale(&StrokePoint::new((0.0, 0.0), 0.2)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn outline_width_varies_with_pressure() {
+        let dynamics = StrokeDynamics::default();
+        let points = [
+            StrokePoint::new((0.0, 0.0), 0.25),
+            StrokePoint::new((100.0, 0.0), 1.0),
+        ];
+        let outline = dynamics.outline(8.0, &points);
+        let bounds = outline.bounding_box();
+        // The ends are round, with a radius of 1 at the start and 4 at the end.
+        assert!((bounds.x0 + 1.0).abs() < 0.05, "{bounds:?}");
+        assert!((bounds.x1 - 104.0).abs() < 0.05, "{bounds:?}");
+        assert!((bounds.height() - 8.0).abs() < 0.05, "{bounds:?}");
+        // In between, the width follows the pressure, which is interpolated along the segment.
+        let height_at = |x: f64| {
+            let inside = (-400..=400)
+                .map(|y| f64::from(y) * 0.01)
+                .filter(|y| outline.winding((x, *y).into()) != 0)
+                .count();
+            inside as f64 * 0.01
+        };
+        assert!((height_at(25.0) - 3.5).abs() < 0.05);
+        assert!((height_at(99.0) - 8.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn velocity_thins_and_tilt_widens() {
+        let dynamics = StrokeDynamics {
+            velocity_thinning: 0.01,
+            tilt_width: 1.0,
+            ..StrokeDynamics::default()
+        };
+        let mut point = StrokePoint::new((0.0, 0.0), 1.0);
+        assert_eq!(dynamics.width_scale(&point), 1.0);
+        point.velocity = 100.0;
+        assert_eq!(dynamics.width_scale(&point), 0.5);
+        point.velocity = 0.0;
+        point.tilt = Vec2::new(std::f64::consts::FRAC_PI_4, 0.0);
+        assert!((dynamics.width_scale(&point) - 1.5).abs() < 1e-9);
+        // Tilt beyond horizontal doesn't widen the stroke further.
+        point.tilt = Vec2::new(3.0, 3.0);
+        assert_eq!(dynamics.width_scale(&point), 2.0);
+    }
+}
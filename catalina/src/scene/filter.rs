@@ -0,0 +1,577 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use peniko::kurbo::{Affine, BezPath, Point, Rect, Shape, Vec2};
+use peniko::{BlendMode, Color, Image};
+
+use catalina_encoding::Encoding;
+
+use super::{FilterGraph, Scene};
+
+/// The extent of a Gaussian filter in standard deviations, beyond which its response is
+/// treated as zero.
+const GAUSSIAN_EXTENT: f64 = 3.0;
+
+/// The largest amount of a radial blur, which limits how far the blur spreads.
+const MAX_RADIAL_AMOUNT: f64 = 0.9;
+
+/// The largest number of octaves of noise, beyond which each octave is too fine to be visible.
+const MAX_OCTAVES: u32 = 10;
+
+/// Perlin noise, like the `feTurbulence` primitive of SVG filters.
+///
+/// The noise is generated in the coordinate system of the layer. It is seeded differently from
+/// SVG, so it has the same character but not the same values as the noise of a browser.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Turbulence {
+    /// The frequency of the first octave of noise along each axis, in cycles per unit.
+    pub base_frequency: Vec2,
+    /// The number of octaves, each of which has twice the frequency and half the amplitude of
+    /// the previous one.
+    pub octaves: u32,
+    /// The seed of the random gradients of the noise.
+    pub seed: u32,
+    /// Whether to generate fractal noise, like `type="fractalNoise"` in SVG, instead of
+    /// turbulence.
+    pub fractal: bool,
+}
+
+impl Turbulence {
+    /// Creates turbulence with the given base frequency and number of octaves, and a seed
+    /// of 0.
+    pub fn new(base_frequency: impl Into<Vec2>, octaves: u32) -> Self {
+        Self {
+            base_frequency: base_frequency.into(),
+            octaves: octaves.min(MAX_OCTAVES),
+            seed: 0,
+            fractal: false,
+        }
+    }
+
+    /// Builder method for setting the seed.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builder method for generating fractal noise instead of turbulence.
+    #[must_use]
+    pub fn with_fractal(mut self, fractal: bool) -> Self {
+        self.fractal = fractal;
+        self
+    }
+}
+
+/// The light of a [`Lighting`] filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightSource {
+    /// A light infinitely far away, like the `feDistantLight` of SVG filters.
+    Distant {
+        /// The direction of the light in the plane of the layer, in radians clockwise from
+        /// the x axis.
+        azimuth: f64,
+        /// The angle of the light above the plane of the layer, in radians.
+        elevation: f64,
+    },
+    /// A light at a point above the layer, like the `fePointLight` of SVG filters.
+    Point {
+        /// The position of the light in the plane of the layer.
+        position: Point,
+        /// The height of the light above the layer.
+        z: f64,
+    },
+}
+
+/// Lighting of the content of a layer, which is treated as a surface whose height is its
+/// alpha, like the `feDiffuseLighting` and `feSpecularLighting` primitives of SVG filters.
+///
+/// Unlike those primitives, the light shades the content rather than replacing it, which
+/// gives beveled and embossed shapes without composing the light with the content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lighting {
+    /// The light.
+    pub light: LightSource,
+    /// The color of the light.
+    pub color: Color,
+    /// The height of the surface where the content is opaque.
+    pub surface_scale: f64,
+    /// The standard deviation of a Gaussian filter applied to the heights, which widens
+    /// the slopes at the edges of the content.
+    pub softness: f64,
+    /// The factor which diffuse light is multiplied by, where 1 keeps the content unchanged
+    /// where it faces the light.
+    pub diffuse: f64,
+    /// The factor which specular highlights are multiplied by.
+    pub specular: f64,
+    /// The exponent of specular highlights, where larger values give smaller highlights.
+    pub specular_exponent: f64,
+}
+
+impl Lighting {
+    /// Creates diffuse lighting with a white light and a surface scale of 1.
+    pub fn new(light: LightSource) -> Self {
+        Self {
+            light,
+            color: Color::WHITE,
+            surface_scale: 1.0,
+            softness: 0.0,
+            diffuse: 1.0,
+            specular: 0.0,
+            specular_exponent: 1.0,
+        }
+    }
+
+    /// Builder method for setting the color of the light.
+    #[must_use]
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Builder method for setting the height of the surface where the content is opaque.
+    #[must_use]
+    pub fn with_surface_scale(mut self, surface_scale: f64) -> Self {
+        self.surface_scale = surface_scale;
+        self
+    }
+
+    /// Builder method for setting the standard deviation of the blur of the heights.
+    #[must_use]
+    pub fn with_softness(mut self, softness: f64) -> Self {
+        self.softness = softness.max(0.0);
+        self
+    }
+
+    /// Builder method for setting the factor of diffuse light.
+    #[must_use]
+    pub fn with_diffuse(mut self, diffuse: f64) -> Self {
+        self.diffuse = diffuse.max(0.0);
+        self
+    }
+
+    /// Builder method for setting the factor and exponent of specular highlights.
+    #[must_use]
+    pub fn with_specular(mut self, specular: f64, exponent: f64) -> Self {
+        self.specular = specular.max(0.0);
+        self.specular_exponent = exponent.clamp(1.0, 128.0);
+        self
+    }
+}
+
+/// An effect applied to the content of a layer pushed with
+/// [`Scene::push_layer_with_filter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// Blurs the content with a Gaussian filter.
+    Blur {
+        /// The standard deviation of the filter.
+        std_dev: f64,
+    },
+    /// Draws a blurred copy of the content, filled with a single color, behind the content.
+    DropShadow {
+        /// The offset of the shadow from the content.
+        offset: Vec2,
+        /// The standard deviation of the Gaussian filter applied to the shadow.
+        std_dev: f64,
+        /// The color of the shadow, which is multiplied by the alpha of the content.
+        color: Color,
+    },
+    /// Draws a blurred shadow, filled with a single color, inside the content, as if the
+    /// content were cut out of a surface lit from the opposite side of the offset.
+    InnerShadow {
+        /// The offset of the shadow from the edges of the content.
+        offset: Vec2,
+        /// The standard deviation of the Gaussian filter applied to the shadow.
+        std_dev: f64,
+        /// The color of the shadow, which is drawn over the content.
+        color: Color,
+    },
+    /// Blurs the content along a line, like the motion blur of a moving object.
+    DirectionalBlur {
+        /// The distance and direction covered by the blur, which is centered on each point.
+        offset: Vec2,
+    },
+    /// Blurs the content towards a center point, like a zoom blur.
+    RadialBlur {
+        /// The point which the blur is directed towards.
+        center: Point,
+        /// The fraction of the distance to the center covered by the blur at each point,
+        /// from 0 to 1.
+        amount: f64,
+    },
+    /// Expands the content by replacing each pixel with the maximum of the pixels in a
+    /// rectangle around it, like the `dilate` operator of the `feMorphology` primitive of SVG
+    /// filters.
+    Dilate {
+        /// The distance from the center of the rectangle to its edges along each axis.
+        radius: Vec2,
+    },
+    /// Shrinks the content by replacing each pixel with the minimum of the pixels in a
+    /// rectangle around it, like the `erode` operator of the `feMorphology` primitive of SVG
+    /// filters.
+    Erode {
+        /// The distance from the center of the rectangle to its edges along each axis.
+        radius: Vec2,
+    },
+    /// Replaces the content with noise, filling the bounding box of the clip.
+    Turbulence(Turbulence),
+    /// Moves each point of the content by an offset read from noise, like the
+    /// `feDisplacementMap` primitive of SVG filters with turbulence as its map.
+    ///
+    /// The red and green channels of the noise are the horizontal and vertical offsets, where
+    /// 0.5 is no offset.
+    DisplacementMap {
+        /// The largest distance which each point is moved along each axis, from
+        /// `-scale / 2` to `scale / 2`.
+        scale: f64,
+        /// The noise which the offsets are read from.
+        map: Turbulence,
+    },
+    /// Shades the content with a light, treating its alpha as the height of a surface.
+    Lighting(Lighting),
+    /// Adds a blurred copy of the brightest parts of the content to the content, like the
+    /// bloom around an emissive light.
+    ///
+    /// The radius and intensity of every glow are scaled by [`RenderParams::glow_amount`],
+    /// which animates them without encoding the scene again.
+    ///
+    /// [`RenderParams::glow_amount`]: crate::RenderParams::glow_amount
+    Glow {
+        /// The standard deviation of the Gaussian filter applied to the glow.
+        std_dev: f64,
+        /// The brightness above which the content glows, from 0 to 1, where the brightness
+        /// of a pixel is its largest premultiplied color component.
+        threshold: f32,
+        /// The factor which the glow is multiplied by before it is added to the content.
+        intensity: f32,
+    },
+}
+
+impl Filter {
+    /// Creates a Gaussian blur with the given standard deviation.
+    pub fn blur(std_dev: f64) -> Self {
+        Self::Blur { std_dev }
+    }
+
+    /// Creates a drop shadow with the given offset, blur and color.
+    pub fn drop_shadow(offset: impl Into<Vec2>, std_dev: f64, color: Color) -> Self {
+        Self::DropShadow {
+            offset: offset.into(),
+            std_dev,
+            color,
+        }
+    }
+
+    /// Creates an inner shadow with the given offset, blur and color.
+    pub fn inner_shadow(offset: impl Into<Vec2>, std_dev: f64, color: Color) -> Self {
+        Self::InnerShadow {
+            offset: offset.into(),
+            std_dev,
+            color,
+        }
+    }
+
+    /// Creates a blur along `offset`, which is centered on each point.
+    pub fn directional_blur(offset: impl Into<Vec2>) -> Self {
+        Self::DirectionalBlur {
+            offset: offset.into(),
+        }
+    }
+
+    /// Creates a blur towards `center`, covering `amount` of the distance to the center.
+    pub fn radial_blur(center: impl Into<Point>, amount: f64) -> Self {
+        Self::RadialBlur {
+            center: center.into(),
+            amount: amount.clamp(0.0, MAX_RADIAL_AMOUNT),
+        }
+    }
+
+    /// Creates a filter which expands the content by `radius` along each axis.
+    pub fn dilate(radius: impl Into<Vec2>) -> Self {
+        Self::Dilate {
+            radius: radius.into(),
+        }
+    }
+
+    /// Creates a filter which shrinks the content by `radius` along each axis.
+    pub fn erode(radius: impl Into<Vec2>) -> Self {
+        Self::Erode {
+            radius: radius.into(),
+        }
+    }
+
+    /// Creates a filter which replaces the content with `noise`.
+    pub fn turbulence(noise: Turbulence) -> Self {
+        Self::Turbulence(noise)
+    }
+
+    /// Creates a filter which moves each point of the content by up to `scale / 2` along each
+    /// axis, as read from `map`.
+    pub fn displacement_map(scale: f64, map: Turbulence) -> Self {
+        Self::DisplacementMap { scale, map }
+    }
+
+    /// Creates a filter which shades the content with `lighting`.
+    pub fn lighting(lighting: Lighting) -> Self {
+        Self::Lighting(lighting)
+    }
+
+    /// Creates a glow of the content brighter than `threshold`, blurred with the given
+    /// standard deviation and added `intensity` times.
+    pub fn glow(std_dev: f64, threshold: f32, intensity: f32) -> Self {
+        Self::Glow {
+            std_dev,
+            threshold: threshold.clamp(0.0, 1.0),
+            intensity: intensity.max(0.0),
+        }
+    }
+
+    /// Returns the filter with the radius and intensity of a glow scaled by `amount`, from 0
+    /// to 1.
+    pub(crate) fn with_glow_amount(self, amount: f32) -> Self {
+        match self {
+            Self::Glow {
+                std_dev,
+                threshold,
+                intensity,
+            } => {
+                let amount = amount.clamp(0.0, 1.0);
+                Self::Glow {
+                    std_dev: std_dev * f64::from(amount),
+                    threshold,
+                    intensity: intensity * amount,
+                }
+            }
+            filter => filter,
+        }
+    }
+
+    /// Returns the filter with its parameters mapped from the coordinate system of the layer
+    /// through `transform`.
+    ///
+    /// Noise is generated in the coordinate system of the layer, so its parameters are
+    /// unchanged.
+    pub(super) fn transformed(self, transform: Affine) -> Self {
+        let scale = transform.determinant().abs().sqrt();
+        let [a, b, c, d, _, _] = transform.as_coeffs();
+        let linear = |v: Vec2| Vec2::new(a * v.x + c * v.y, b * v.x + d * v.y);
+        // The extent of a rectangle around the origin, which remains axis aligned.
+        let extent = |v: Vec2| {
+            Vec2::new(
+                a.abs() * v.x.abs() + c.abs() * v.y.abs(),
+                b.abs() * v.x.abs() + d.abs() * v.y.abs(),
+            )
+        };
+        match self {
+            Self::Blur { std_dev } => Self::Blur {
+                std_dev: std_dev * scale,
+            },
+            Self::DropShadow {
+                offset,
+                std_dev,
+                color,
+            } => Self::DropShadow {
+                offset: linear(offset),
+                std_dev: std_dev * scale,
+                color,
+            },
+            Self::InnerShadow {
+                offset,
+                std_dev,
+                color,
+            } => Self::InnerShadow {
+                offset: linear(offset),
+                std_dev: std_dev * scale,
+                color,
+            },
+            Self::DirectionalBlur { offset } => Self::DirectionalBlur {
+                offset: linear(offset),
+            },
+            Self::RadialBlur { center, amount } => Self::RadialBlur {
+                center: transform * center,
+                amount,
+            },
+            Self::Dilate { radius } => Self::Dilate {
+                radius: extent(radius),
+            },
+            Self::Erode { radius } => Self::Erode {
+                radius: extent(radius),
+            },
+            Self::Lighting(lighting) => {
+                let light = match lighting.light {
+                    LightSource::Distant { azimuth, elevation } => {
+                        let direction = linear(Vec2::from_angle(azimuth));
+                        LightSource::Distant {
+                            azimuth: direction.atan2(),
+                            elevation,
+                        }
+                    }
+                    LightSource::Point { position, z } => LightSource::Point {
+                        position: transform * position,
+                        z: z * scale,
+                    },
+                };
+                Self::Lighting(Lighting {
+                    light,
+                    surface_scale: lighting.surface_scale * scale,
+                    softness: lighting.softness * scale,
+                    ..lighting
+                })
+            }
+            Self::Glow {
+                std_dev,
+                threshold,
+                intensity,
+            } => Self::Glow {
+                std_dev: std_dev * scale,
+                threshold,
+                intensity,
+            },
+            filter @ (Self::Turbulence(_) | Self::DisplacementMap { .. }) => filter,
+        }
+    }
+
+    /// Returns the filter with its positions relative to `origin`.
+    pub(super) fn relative_to(self, origin: Point) -> Self {
+        match self {
+            Self::RadialBlur { center, amount } => Self::RadialBlur {
+                center: center - origin.to_vec2(),
+                amount,
+            },
+            Self::Lighting(mut lighting) => {
+                if let LightSource::Point { position, .. } = &mut lighting.light {
+                    *position -= origin.to_vec2();
+                }
+                Self::Lighting(lighting)
+            }
+            filter => filter,
+        }
+    }
+
+    /// Returns the area which the filter may paint, given the bounds of the content and the
+    /// transform of the layer.
+    pub(super) fn bounds(&self, content: Rect, transform: Affine) -> Rect {
+        match self {
+            // Glows are sized for their full radius, which `glow_amount` can only reduce.
+            Self::Blur { std_dev } | Self::Glow { std_dev, .. } => {
+                let extent = GAUSSIAN_EXTENT * std_dev;
+                content.inflate(extent, extent)
+            }
+            Self::DropShadow {
+                offset, std_dev, ..
+            } => {
+                let extent = GAUSSIAN_EXTENT * std_dev;
+                content.union((content + *offset).inflate(extent, extent))
+            }
+            // The shadow only covers the content, but is cast from the area around it, which
+            // must be in the image.
+            Self::InnerShadow {
+                offset, std_dev, ..
+            } => {
+                let extent = GAUSSIAN_EXTENT * std_dev;
+                content.inflate(extent + offset.x.abs(), extent + offset.y.abs())
+            }
+            Self::DirectionalBlur { offset } => content
+                .union(content + *offset * 0.5)
+                .union(content - *offset * 0.5),
+            Self::RadialBlur { center, amount } => {
+                // A point is painted if the content is between it and `amount` of the way to
+                // the center, so the content is scaled away from the center.
+                let scale = 1.0 / (1.0 - amount.clamp(0.0, MAX_RADIAL_AMOUNT));
+                let spread = Affine::translate(center.to_vec2())
+                    * Affine::scale(scale)
+                    * Affine::translate(-center.to_vec2());
+                let scaled = spread.transform_rect_bbox(content);
+                content.union(scaled)
+            }
+            Self::Dilate { radius } => content.inflate(radius.x.abs(), radius.y.abs()),
+            Self::Erode { .. } | Self::Turbulence(_) | Self::Lighting(_) => content,
+            Self::DisplacementMap { scale, .. } => {
+                let [a, b, c, d, _, _] = transform.as_coeffs();
+                let half = 0.5 * scale.abs();
+                content.inflate(half * (a.abs() + c.abs()), half * (b.abs() + d.abs()))
+            }
+        }
+    }
+
+    /// The number of pixels on each side of a pixel which contribute to a Gaussian filter.
+    #[cfg_attr(
+        not(feature = "wgpu"),
+        expect(dead_code, reason = "this can be unused when wgpu feature is not used")
+    )]
+    pub(crate) fn gaussian_radius(std_dev: f64) -> u32 {
+        (GAUSSIAN_EXTENT * std_dev).ceil().max(1.0) as u32
+    }
+}
+
+/// The smallest resolution which filtered layers can be rendered at, relative to the scene.
+///
+/// See [`Scene::push_layer_with_filter_at_scale`].
+pub const MIN_RASTER_SCALE: f64 = 1.0 / 16.0;
+
+/// A filtered layer, whose content is rendered separately before the scene containing it.
+#[derive(Clone)]
+#[cfg_attr(
+    not(feature = "wgpu"),
+    expect(dead_code, reason = "this can be unused when wgpu feature is not used")
+)]
+pub(crate) struct FilterLayer {
+    /// The clipped content, translated so that the top left of the layer is at the origin.
+    pub(crate) content: Scene,
+    /// The filters, with their parameters in pixels relative to the top left of the layer.
+    pub(crate) graph: FilterGraph,
+    /// The transform from the pixels of `image` to the coordinate system of the layer, in
+    /// which noise is generated.
+    pub(crate) to_layer: Affine,
+    /// The image drawn in place of the layer, which the renderer replaces with the filtered
+    /// content.
+    pub(crate) image: Image,
+}
+
+/// A filtered layer which is still being drawn into.
+#[derive(Clone)]
+pub(super) struct FilterFrame {
+    /// The encoding and filtered layers of the enclosing scene, which are restored when the
+    /// layer is popped.
+    pub(super) parent: (Encoding, Vec<FilterLayer>),
+    /// The number of unfiltered layers pushed inside the filtered layer which haven't yet
+    /// been popped.
+    pub(super) depth: u32,
+    pub(super) blend: BlendMode,
+    pub(super) alpha: f32,
+    pub(super) transform: Affine,
+    pub(super) clip: BezPath,
+    pub(super) graph: FilterGraph,
+    /// The resolution the layer is rendered at, relative to the scene.
+    pub(super) raster_scale: f64,
+}
+
+impl FilterFrame {
+    pub(super) fn new(
+        parent: (Encoding, Vec<FilterLayer>),
+        blend: BlendMode,
+        alpha: f32,
+        transform: Affine,
+        clip: BezPath,
+        graph: &FilterGraph,
+        raster_scale: f64,
+    ) -> Self {
+        Self {
+            parent,
+            depth: 0,
+            blend,
+            alpha,
+            transform,
+            clip,
+            graph: graph.transformed(transform),
+            raster_scale,
+        }
+    }
+
+    /// Returns the pixel aligned area painted by the filtered layer, in scene coordinates.
+    pub(super) fn bounds(&self) -> Rect {
+        let clip = self.transform.transform_rect_bbox(self.clip.bounding_box());
+        self.graph.bounds(clip, self.transform).expand()
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Graphs of filters which combine intermediate results, like SVG filter primitives.
+
+use peniko::kurbo::{Affine, Point, Rect, Vec2};
+
+use super::Filter;
+
+/// Identifies the result of a node in a [`FilterGraph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FilterNodeId(pub(crate) usize);
+
+/// An image which a node of a [`FilterGraph`] reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FilterInput {
+    /// The content of the layer, like `SourceGraphic` in SVG.
+    Source,
+    /// The alpha channel of the content of the layer, in black, like `SourceAlpha` in SVG.
+    SourceAlpha,
+    /// The result of an earlier node of the graph.
+    Node(FilterNodeId),
+}
+
+/// A step of a [`FilterGraph`].
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FilterNode {
+    /// Applies a filter to the input.
+    Filter { input: FilterInput, filter: Filter },
+    /// Moves the input, like `feOffset` in SVG.
+    Offset { input: FilterInput, offset: Vec2 },
+    /// Composes the inputs over each other, with the last on top, like `feMerge` in SVG.
+    Merge { inputs: Vec<FilterInput> },
+}
+
+impl FilterNode {
+    pub(crate) fn inputs(&self) -> &[FilterInput] {
+        match self {
+            Self::Filter { input, .. } | Self::Offset { input, .. } => std::slice::from_ref(input),
+            Self::Merge { inputs } => inputs,
+        }
+    }
+}
+
+/// A graph of filters applied to the content of a layer pushed with
+/// [`Scene::push_layer_with_filter`](crate::Scene::push_layer_with_filter).
+///
+/// Nodes are added in order, and can only read the content of the layer and the results of
+/// earlier nodes. The result of the last node is drawn in place of the layer, or the content
+/// itself if the graph is empty. For example, a drop shadow is:
+///
+/// ```
+/// # use catalina::{Filter, FilterGraph, FilterInput};
+/// let mut graph = FilterGraph::new();
+/// let blurred = graph.filter(FilterInput::SourceAlpha, Filter::blur(2.0));
+/// let shadow = graph.offset(blurred, (4.0, 4.0));
+/// graph.merge([shadow, FilterInput::Source]);
+/// ```
+///
+/// When rendered, each intermediate image is released as soon as no later node reads it, and
+/// layers whose graphs need too many images at once aren't drawn.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilterGraph {
+    pub(crate) nodes: Vec<FilterNode>,
+}
+
+impl FilterGraph {
+    /// Creates an empty graph, which leaves the content unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node which applies `filter` to `input`.
+    pub fn filter(&mut self, input: FilterInput, filter: Filter) -> FilterInput {
+        self.push(FilterNode::Filter { input, filter })
+    }
+
+    /// Adds a node which moves `input` by `offset`.
+    pub fn offset(&mut self, input: FilterInput, offset: impl Into<Vec2>) -> FilterInput {
+        self.push(FilterNode::Offset {
+            input,
+            offset: offset.into(),
+        })
+    }
+
+    /// Adds a node which composes `inputs` over each other, with the last on top.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inputs` is empty.
+    pub fn merge(&mut self, inputs: impl IntoIterator<Item = FilterInput>) -> FilterInput {
+        let inputs: Vec<_> = inputs.into_iter().collect();
+        assert!(!inputs.is_empty(), "a merge needs at least one input");
+        self.push(FilterNode::Merge { inputs })
+    }
+
+    /// Returns whether the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn push(&mut self, node: FilterNode) -> FilterInput {
+        for input in node.inputs() {
+            if let FilterInput::Node(id) = input {
+                assert!(
+                    id.0 < self.nodes.len(),
+                    "filter nodes can only read earlier nodes of the same graph"
+                );
+            }
+        }
+        self.nodes.push(node);
+        FilterInput::Node(FilterNodeId(self.nodes.len() - 1))
+    }
+
+    /// Returns the graph with its parameters mapped from the coordinate system of the layer
+    /// through `transform`.
+    pub(super) fn transformed(&self, transform: Affine) -> Self {
+        let [a, b, c, d, _, _] = transform.as_coeffs();
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                FilterNode::Filter { input, filter } => FilterNode::Filter {
+                    input: *input,
+                    filter: filter.transformed(transform),
+                },
+                FilterNode::Offset { input, offset } => FilterNode::Offset {
+                    input: *input,
+                    offset: Vec2::new(a * offset.x + c * offset.y, b * offset.x + d * offset.y),
+                },
+                node => node.clone(),
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Returns the graph with its positions relative to `origin`.
+    pub(super) fn relative_to(&self, origin: Point) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                FilterNode::Filter { input, filter } => FilterNode::Filter {
+                    input: *input,
+                    filter: filter.relative_to(origin),
+                },
+                node => node.clone(),
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Returns the graph with the radius and intensity of its glows scaled by `amount`.
+    #[cfg_attr(
+        not(feature = "wgpu"),
+        expect(dead_code, reason = "this can be unused when wgpu feature is not used")
+    )]
+    pub(crate) fn with_glow_amount(&self, amount: f32) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                FilterNode::Filter { input, filter } => FilterNode::Filter {
+                    input: *input,
+                    filter: filter.with_glow_amount(amount),
+                },
+                node => node.clone(),
+            })
+            .collect();
+        Self { nodes }
+    }
+
+    /// Returns the area which the graph may paint, given the bounds of the content and the
+    /// transform of the layer.
+    ///
+    /// This covers the content and the results of every node, as intermediate images are the
+    /// same size as the result.
+    pub(super) fn bounds(&self, content: Rect, transform: Affine) -> Rect {
+        let mut bounds: Vec<Rect> = Vec::with_capacity(self.nodes.len());
+        let input_bounds = |bounds: &[Rect], input: &FilterInput| match input {
+            FilterInput::Source | FilterInput::SourceAlpha => content,
+            FilterInput::Node(id) => bounds[id.0],
+        };
+        for node in &self.nodes {
+            let node_bounds = match node {
+                FilterNode::Filter { input, filter } => {
+                    filter.bounds(input_bounds(&bounds, input), transform)
+                }
+                FilterNode::Offset { input, offset } => input_bounds(&bounds, input) + *offset,
+                FilterNode::Merge { inputs } => inputs
+                    .iter()
+                    .map(|input| input_bounds(&bounds, input))
+                    .reduce(|a, b| a.union(b))
+                    .unwrap_or(content),
+            };
+            bounds.push(node_bounds);
+        }
+        bounds.into_iter().fold(content, |a, b| a.union(b))
+    }
+}
+
+impl From<Filter> for FilterGraph {
+    fn from(filter: Filter) -> Self {
+        let mut graph = Self::new();
+        graph.filter(FilterInput::Source, filter);
+        graph
+    }
+}
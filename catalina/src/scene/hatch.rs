@@ -0,0 +1,58 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Hatch patterns of parallel lines, as used in technical drawings.
+
+use peniko::Color;
+
+/// A pattern of evenly spaced parallel lines, for [`Scene::fill_hatch`](crate::Scene::fill_hatch).
+///
+/// The lines are evaluated for each pixel during fine rasterization, so a hatched region costs
+/// about the same to render as a gradient, however many lines it contains.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hatch {
+    /// The color of the lines.
+    pub color: Color,
+    /// The angle of the lines in radians, clockwise from the x axis.
+    pub angle: f64,
+    /// The distance between the centers of adjacent lines.
+    pub spacing: f64,
+    /// The width of each line.
+    pub line_width: f64,
+    /// Whether a second set of lines is drawn at right angles to the first.
+    pub cross: bool,
+}
+
+impl Hatch {
+    /// Creates a pattern of horizontal lines `spacing` apart, with a width of one unit.
+    pub fn new(color: Color, spacing: f64) -> Self {
+        Self {
+            color,
+            angle: 0.0,
+            spacing,
+            line_width: 1.0,
+            cross: false,
+        }
+    }
+
+    /// Builder method for setting the angle of the lines, in radians.
+    #[must_use]
+    pub fn with_angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    /// Builder method for setting the width of the lines.
+    #[must_use]
+    pub fn with_line_width(mut self, line_width: f64) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Builder method for drawing a second set of lines at right angles to the first.
+    #[must_use]
+    pub fn with_cross(mut self, cross: bool) -> Self {
+        self.cross = cross;
+        self
+    }
+}
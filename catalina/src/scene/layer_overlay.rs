@@ -0,0 +1,116 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! An overlay showing the layers of a scene, for debugging clips and blends.
+
+use catalina_encoding::LayerInfo;
+use peniko::color::palette;
+use peniko::kurbo::{Affine, BezPath, Cap, Point, Rect, Shape, Stroke, Vec2};
+use peniko::{Color, Fill};
+
+use super::Scene;
+
+/// The colors of outlines, by the depth of their layers.
+const DEPTH_COLORS: [Color; 6] = [
+    palette::css::DEEP_SKY_BLUE,
+    palette::css::ORANGE,
+    palette::css::LIME,
+    palette::css::MAGENTA,
+    palette::css::YELLOW,
+    palette::css::RED,
+];
+
+/// The size of a cell of the seven segment display used for labels, in pixels.
+const DIGIT_SIZE: Vec2 = Vec2::new(5.0, 10.0);
+/// The space between the characters of labels, and around labels.
+const LABEL_SPACING: f64 = 3.0;
+
+/// The segments of the seven segment display, from the top clockwise and then the middle, in
+/// units of the width of a cell horizontally and half its height vertically.
+const SEGMENTS: [((f64, f64), (f64, f64)); 7] = [
+    ((0.0, 0.0), (1.0, 0.0)),
+    ((1.0, 0.0), (1.0, 1.0)),
+    ((1.0, 1.0), (1.0, 2.0)),
+    ((0.0, 2.0), (1.0, 2.0)),
+    ((0.0, 1.0), (0.0, 2.0)),
+    ((0.0, 0.0), (0.0, 1.0)),
+    ((0.0, 1.0), (1.0, 1.0)),
+];
+
+/// The segments lit for each digit, with the top segment in the lowest bit.
+const DIGIT_SEGMENTS: [u8; 10] = [
+    0b011_1111, 0b000_0110, 0b101_1011, 0b100_1111, 0b110_0110, 0b110_1101, 0b111_1101, 0b000_0111,
+    0b111_1111, 0b110_1111,
+];
+
+/// Builds a scene outlining the clip of each of `layers`, labelled with its depth and the
+/// index of the draw object which begins it.
+pub(crate) fn layer_overlay(layers: &[LayerInfo]) -> Scene {
+    let mut overlay = Scene::new();
+    for layer in layers {
+        if layer.clip.elements().is_empty() {
+            continue;
+        }
+        let color = DEPTH_COLORS[layer.depth % DEPTH_COLORS.len()];
+        let mut outline = Stroke::new(2.0);
+        if !layer.is_clip {
+            outline = outline.with_dashes(0.0, [6.0, 4.0]);
+        }
+        overlay.stroke(&outline, Affine::IDENTITY, color, None, &layer.clip);
+
+        let (text, width) = label(&format!("{}:{}", layer.depth, layer.draw));
+        let origin = layer.clip.bounding_box().origin();
+        let background = Rect::from_origin_size(
+            origin,
+            (
+                width + 2.0 * LABEL_SPACING,
+                DIGIT_SIZE.y + 2.0 * LABEL_SPACING,
+            ),
+        );
+        overlay.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            palette::css::BLACK.with_alpha(0.75),
+            None,
+            &background,
+        );
+        overlay.stroke(
+            &Stroke::new(1.5).with_caps(Cap::Round),
+            Affine::translate(origin.to_vec2() + Vec2::new(LABEL_SPACING, LABEL_SPACING)),
+            color,
+            None,
+            &text,
+        );
+    }
+    overlay
+}
+
+/// Returns the segments of a label made of digits and colons, and its width.
+fn label(text: &str) -> (BezPath, f64) {
+    let mut path = BezPath::new();
+    let mut x = 0.0;
+    for ch in text.chars() {
+        if let Some(digit) = ch.to_digit(10) {
+            let lit = DIGIT_SEGMENTS[digit as usize];
+            for (ix, (p0, p1)) in SEGMENTS.iter().enumerate() {
+                if lit & (1 << ix) != 0 {
+                    path.move_to(segment_point(x, *p0));
+                    path.line_to(segment_point(x, *p1));
+                }
+            }
+            x += DIGIT_SIZE.x + LABEL_SPACING;
+        } else if ch == ':' {
+            for y in [0.5, 1.3] {
+                path.move_to(segment_point(x, (0.0, y)));
+                path.line_to(segment_point(x, (0.0, y + 0.2)));
+            }
+            x += LABEL_SPACING;
+        }
+    }
+    (path, (x - LABEL_SPACING).max(0.0))
+}
+
+/// Returns a point of a segment of the character starting at `x`.
+fn segment_point(x: f64, (sx, sy): (f64, f64)) -> Point {
+    Point::new(x + sx * DIGIT_SIZE.x, sy * DIGIT_SIZE.y / 2.0)
+}
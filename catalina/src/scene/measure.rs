@@ -0,0 +1,183 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Measurement of glyph runs, and truncation of them with an ellipsis.
+
+use peniko::Font;
+use skrifa::{
+    instance::{LocationRef, Size},
+    metrics::GlyphMetrics,
+    FontRef, GlyphId, MetadataProvider,
+};
+
+use catalina_encoding::Glyph;
+
+/// The vertical metrics of lines of text in a font, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LineMetrics {
+    /// The distance from the baseline to the top of the line.
+    pub ascent: f32,
+    /// The distance from the baseline to the bottom of the line, which is positive below
+    /// the baseline.
+    pub descent: f32,
+    /// The extra space recommended between lines.
+    pub leading: f32,
+}
+
+impl LineMetrics {
+    /// Returns the distance between the baselines of consecutive lines.
+    pub fn line_height(&self) -> f32 {
+        self.ascent + self.descent + self.leading
+    }
+}
+
+/// Measures glyph runs in a font at a size, and truncates runs which are too wide with an
+/// ellipsis.
+///
+/// Runs are given as positioned glyphs, as produced by a shaper for a single line with its
+/// origin on the baseline, so that kerning and other positioning is taken into account.
+/// Truncation happens at glyph boundaries, so ligatures and clusters of several glyphs may be
+/// split.
+///
+/// ```no_run
+/// # use catalina::peniko::{Fill, Font};
+/// # use catalina::{Glyph, Scene, TextMeasure};
+/// # fn example(scene: &mut Scene, font: &Font, label: &[Glyph]) {
+/// let measure = TextMeasure::new(font, 14.0).unwrap();
+/// let label = measure.truncate(label, 120.0);
+/// scene.draw_glyphs(font).font_size(14.0).draw(Fill::NonZero, label.into_iter());
+/// # }
+/// ```
+pub struct TextMeasure<'a> {
+    line_metrics: LineMetrics,
+    glyph_metrics: GlyphMetrics<'a>,
+    /// The glyphs of the ellipsis, with their advances.
+    ellipsis: Vec<(u32, f32)>,
+}
+
+impl<'a> TextMeasure<'a> {
+    /// Creates a measure for glyphs of `font` at `font_size` pixels per em, or returns `None`
+    /// if the font can't be read.
+    ///
+    /// Runs are truncated with the font's `…`, or three full stops if it has none.
+    pub fn new(font: &'a Font, font_size: f32) -> Option<Self> {
+        let font = FontRef::from_index(font.data.as_ref(), font.index).ok()?;
+        let size = Size::new(font_size);
+        let metrics = font.metrics(size, LocationRef::default());
+        let glyph_metrics = font.glyph_metrics(size, LocationRef::default());
+        let charmap = font.charmap();
+        let ellipsis = match charmap.map('…') {
+            Some(glyph) => vec![glyph],
+            None => charmap
+                .map('.')
+                .map(|glyph| vec![glyph; 3])
+                .unwrap_or_default(),
+        };
+        let ellipsis = ellipsis
+            .into_iter()
+            .map(|glyph| {
+                let advance = glyph_metrics.advance_width(glyph).unwrap_or_default();
+                (glyph.to_u32(), advance)
+            })
+            .collect();
+        Some(Self {
+            line_metrics: LineMetrics {
+                ascent: metrics.ascent,
+                descent: -metrics.descent,
+                leading: metrics.leading,
+            },
+            glyph_metrics,
+            ellipsis,
+        })
+    }
+
+    /// Returns the vertical metrics of lines of text.
+    pub fn line_metrics(&self) -> LineMetrics {
+        self.line_metrics
+    }
+
+    /// Returns the horizontal advance of `glyph`.
+    pub fn advance(&self, glyph: u32) -> f32 {
+        self.glyph_metrics
+            .advance_width(GlyphId::new(glyph))
+            .unwrap_or_default()
+    }
+
+    /// Returns the width of a run of positioned glyphs, from the start of the first to the
+    /// end of the advance of the last.
+    pub fn width(&self, glyphs: &[Glyph]) -> f32 {
+        match (glyphs.first(), glyphs.last()) {
+            (Some(first), Some(last)) => last.x + self.advance(last.id) - first.x,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the width of the ellipsis which truncated runs end with.
+    pub fn ellipsis_width(&self) -> f32 {
+        self.ellipsis.iter().map(|(_, advance)| advance).sum()
+    }
+
+    /// Returns the glyphs of a run which fit in `max_width`, followed by an ellipsis if any
+    /// had to be removed.
+    ///
+    /// If not even the ellipsis fits, the result is empty.
+    pub fn truncate(&self, glyphs: &[Glyph], max_width: f32) -> Vec<Glyph> {
+        if self.width(glyphs) <= max_width {
+            return glyphs.to_vec();
+        }
+        self.ellipsize(glyphs, max_width)
+    }
+
+    /// Returns the first `max_lines` lines of a paragraph, each of which is truncated to
+    /// `max_width`.
+    ///
+    /// If lines are removed, the last remaining line ends with an ellipsis, even if it fits.
+    pub fn truncate_lines(
+        &self,
+        lines: &[Vec<Glyph>],
+        max_width: f32,
+        max_lines: usize,
+    ) -> Vec<Vec<Glyph>> {
+        let mut truncated: Vec<_> = lines
+            .iter()
+            .take(max_lines)
+            .map(|line| self.truncate(line, max_width))
+            .collect();
+        if lines.len() > max_lines {
+            if let Some(last) = max_lines.checked_sub(1) {
+                truncated[last] = self.ellipsize(&lines[last], max_width);
+            }
+        }
+        truncated
+    }
+
+    /// Returns the glyphs of a run which fit in `max_width` together with an ellipsis,
+    /// followed by the ellipsis.
+    fn ellipsize(&self, glyphs: &[Glyph], max_width: f32) -> Vec<Glyph> {
+        let ellipsis_width = self.ellipsis_width();
+        let Some(first) = glyphs.first() else {
+            return vec![];
+        };
+        if ellipsis_width > max_width {
+            return vec![];
+        }
+        let start = first.x;
+        let mut end = start;
+        let mut result: Vec<Glyph> = glyphs
+            .iter()
+            .map_while(|glyph| {
+                let glyph_end = glyph.x + self.advance(glyph.id);
+                (glyph_end - start + ellipsis_width <= max_width).then(|| {
+                    end = glyph_end;
+                    *glyph
+                })
+            })
+            .collect();
+        let y = first.y;
+        for &(id, advance) in &self.ellipsis {
+            result.push(Glyph { id, x: end, y });
+            end += advance;
+        }
+        result
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Building a scene from parts encoded on several threads.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use catalina_encoding::ResourceRemap;
+use peniko::{Blob, Font, Image, ImageFormat};
+
+use super::Scene;
+
+/// Builds a scene from parts encoded independently, such as on different threads, which are
+/// merged in an order chosen by the caller.
+///
+/// Each part is encoded into its own [`Scene`] by a [`PartBuilder`], with the same methods as
+/// a scene, and given a key when it's created. The parts are merged in the order of their
+/// keys, whatever order they were finished in, so the scene is the same on every run. This
+/// allows large trees of user interface elements to be encoded by a data-parallel traversal,
+/// keyed by the position of each subtree in the tree.
+///
+/// Threads often load or decode the same images and fonts separately, which would be uploaded
+/// once for each copy. With [`with_resource_dedup`](Self::with_resource_dedup), images and
+/// fonts with the same data as one in an earlier part are replaced by that one when merging,
+/// so each is only uploaded once.
+///
+/// ```no_run
+/// # use catalina::{ParallelSceneBuilder, Scene};
+/// # fn encode_subtree(index: u64, scene: &mut Scene) {}
+/// let builder = ParallelSceneBuilder::new();
+/// std::thread::scope(|scope| {
+///     for index in 0..4 {
+///         let builder = &builder;
+///         scope.spawn(move || {
+///             let mut part = builder.part(index);
+///             encode_subtree(index, &mut part);
+///             part.finish();
+///         });
+///     }
+/// });
+/// let scene = builder.finish();
+/// ```
+pub struct ParallelSceneBuilder<K = u64> {
+    parts: Mutex<Vec<(K, Scene)>>,
+    dedup_resources: bool,
+}
+static_assertions::assert_impl_all!(ParallelSceneBuilder: Send, Sync);
+
+impl<K> Default for ParallelSceneBuilder<K> {
+    fn default() -> Self {
+        Self {
+            parts: Mutex::new(Vec::new()),
+            dedup_resources: false,
+        }
+    }
+}
+
+impl<K: Ord> ParallelSceneBuilder<K> {
+    /// Creates a builder with no parts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether images and fonts with the same data in different parts share the data of
+    /// the first part to use it, so that they're only uploaded once. This is off by default,
+    /// as it hashes the data of every image and font in [`finish`](Self::finish).
+    ///
+    /// Images are drawn by the id of their data, which is what this changes. Images whose
+    /// texture is replaced by the renderer, such as those passed to
+    /// [`Renderer::override_image`](crate::Renderer::override_image), must not be drawn in
+    /// parts merged with this enabled. The images which the renderer creates itself, for
+    /// filtered layers, cached and paint layers and external textures, are never shared.
+    pub fn with_resource_dedup(mut self, dedup: bool) -> Self {
+        self.dedup_resources = dedup;
+        self
+    }
+
+    /// Returns a builder for a part of the scene, which is merged in the order of `key`.
+    ///
+    /// Keys should be unique, as parts with the same key are merged in the order they were
+    /// finished in.
+    pub fn part(&self, key: K) -> PartBuilder<'_, K> {
+        PartBuilder {
+            builder: self,
+            key,
+            scene: Scene::new(),
+        }
+    }
+
+    /// Merges the finished parts into a scene, in the order of their keys.
+    pub fn finish(self) -> Scene {
+        let mut parts = self
+            .parts
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        parts.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut scene = Scene::new();
+        let mut dedup = self.dedup_resources.then(DedupResources::default);
+        for (_, part) in &parts {
+            match &mut dedup {
+                Some(dedup) => scene
+                    .encoding
+                    .append_with_remap(&part.encoding, &None, dedup),
+                None => scene.encoding.append(&part.encoding, &None),
+            }
+            #[cfg(feature = "bump_estimate")]
+            scene.estimator.append(&part.estimator, None);
+            scene.filter_layers.extend_from_slice(&part.filter_layers);
+        }
+        scene
+    }
+}
+
+/// A part of a scene being built by a [`ParallelSceneBuilder`].
+///
+/// This dereferences to a [`Scene`] to encode the part into. It's added to the builder by
+/// [`finish`](Self::finish), and discarded if it's dropped without being finished.
+pub struct PartBuilder<'a, K = u64> {
+    builder: &'a ParallelSceneBuilder<K>,
+    key: K,
+    scene: Scene,
+}
+
+impl<K> PartBuilder<'_, K> {
+    /// Adds the part to its builder.
+    pub fn finish(self) {
+        self.builder
+            .parts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((self.key, self.scene));
+    }
+}
+
+impl<K> Deref for PartBuilder<'_, K> {
+    type Target = Scene;
+
+    fn deref(&self) -> &Scene {
+        &self.scene
+    }
+}
+
+impl<K> DerefMut for PartBuilder<'_, K> {
+    fn deref_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+}
+
+/// Replaces images and fonts with the first seen with the same data.
+#[derive(Default)]
+struct DedupResources {
+    /// The data to use in place of each blob, by id.
+    replacements: HashMap<u64, Blob<u8>>,
+    /// The first images seen, by the hash of their data.
+    images: HashMap<u64, Vec<Image>>,
+    /// The data of the first fonts seen, by its hash.
+    fonts: HashMap<u64, Vec<Blob<u8>>>,
+}
+
+fn hash_data(blob: &Blob<u8>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    blob.data().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Marks the ids of the data of images created by the renderer, which are never shared.
+///
+/// Blob ids are allocated from a counter starting at zero, so they never have this bit set.
+const RENDERER_IMAGE_ID: u64 = 1 << 63;
+
+/// Creates an image whose texture is provided by the renderer, such as for a filtered layer,
+/// with `data` as its pixels until the texture is ready.
+///
+/// The image is marked so that it keeps its data when resources are deduplicated, as its
+/// texture is looked up by the id of its data.
+pub(crate) fn renderer_image(data: Vec<u8>, width: u32, height: u32) -> Image {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let id = RENDERER_IMAGE_ID | NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let data = Blob::from_raw_parts(Arc::new(data), id);
+    Image::new(data, ImageFormat::Rgba8, width, height)
+}
+
+/// Returns `true` for images created by [`renderer_image`].
+fn is_renderer_image(image: &Image) -> bool {
+    image.data.id() & RENDERER_IMAGE_ID != 0
+}
+
+impl ResourceRemap for DedupResources {
+    fn image(&mut self, image: &Image) -> Option<Image> {
+        let images = &mut self.images;
+        let data = self.replacements.entry(image.data.id()).or_insert_with(|| {
+            if is_renderer_image(image) {
+                return image.data.clone();
+            }
+            let candidates = images.entry(hash_data(&image.data)).or_default();
+            // Images are uploaded by the id of their data, so only images of the same size and
+            // format can share it.
+            let canonical = candidates.iter().find(|candidate| {
+                candidate.format == image.format
+                    && candidate.width == image.width
+                    && candidate.height == image.height
+                    && candidate.data.data() == image.data.data()
+            });
+            match canonical {
+                Some(canonical) => canonical.data.clone(),
+                None => {
+                    candidates.push(image.clone());
+                    image.data.clone()
+                }
+            }
+        });
+        (data.id() != image.data.id()).then(|| Image {
+            data: data.clone(),
+            ..image.clone()
+        })
+    }
+
+    fn font(&mut self, font: &Font) -> Option<Font> {
+        let fonts = &mut self.fonts;
+        let data = self.replacements.entry(font.data.id()).or_insert_with(|| {
+            let candidates = fonts.entry(hash_data(&font.data)).or_default();
+            match candidates
+                .iter()
+                .find(|candidate| candidate.data() == font.data.data())
+            {
+                Some(canonical) => canonical.clone(),
+                None => {
+                    candidates.push(font.data.clone());
+                    font.data.clone()
+                }
+            }
+        });
+        (data.id() != font.data.id()).then(|| Font::new(data.clone(), font.index))
+    }
+}
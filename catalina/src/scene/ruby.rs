@@ -0,0 +1,224 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Ruby annotations, the small readings set beside Chinese and Japanese text.
+
+use peniko::kurbo::Point;
+use peniko::Font;
+use skrifa::{
+    instance::{LocationRef, Size},
+    metrics::GlyphMetrics,
+    FontRef, GlyphId, MetadataProvider,
+};
+
+use catalina_encoding::Glyph;
+
+/// How the glyphs of the narrower of a base run and its ruby are spread across the width of
+/// the wider.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RubyAlign {
+    /// The glyphs are kept together at the start.
+    Start,
+    /// The glyphs are kept together in the middle.
+    Center,
+    /// The extra space is divided evenly between the glyphs, with half as much at each end,
+    /// as recommended by JIS X 4051.
+    #[default]
+    SpaceAround,
+    /// The extra space is divided evenly between the glyphs, with none at the ends.
+    SpaceBetween,
+}
+
+/// Which side of the base run its ruby is set on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RubyPosition {
+    /// Above horizontal text.
+    #[default]
+    Over,
+    /// Below horizontal text.
+    Under,
+}
+
+/// The glyphs of a base run and its ruby, positioned by [`RubyLayout::layout`].
+#[derive(Clone, Debug, Default)]
+pub struct RubyRun {
+    /// The glyphs of the base run, to be drawn at the base font size.
+    pub base: Vec<Glyph>,
+    /// The glyphs of the ruby, to be drawn at [`RubyLayout::ruby_font_size`].
+    pub ruby: Vec<Glyph>,
+}
+
+/// Positions ruby annotations relative to the base runs they annotate, in horizontal text.
+///
+/// Ruby is set at half the size of the base text by default. When the ruby is narrower than
+/// its base, it is spread across the base as given by its [`RubyAlign`]. When it is wider, it
+/// may overhang the characters on either side by up to the maximum overhang, and the base is
+/// spread to cover the rest. Overhanging is usually only allowed onto kana and punctuation,
+/// so the maximum overhang should be set for each run from the characters next to it.
+///
+/// ```no_run
+/// # use catalina::kurbo::Point;
+/// # use catalina::peniko::{Fill, Font};
+/// # use catalina::{RubyLayout, Scene};
+/// # fn example(scene: &mut Scene, font: &Font, kanji: &[u32], kana: &[u32]) {
+/// let layout = RubyLayout::new(font, 24.0, font).unwrap();
+/// let mut pen = Point::new(20.0, 60.0);
+/// let run = layout.layout(kanji.iter().copied(), kana.iter().copied(), &mut pen);
+/// scene.draw_glyphs(font).font_size(24.0).draw(Fill::NonZero, run.base.into_iter());
+/// scene
+///     .draw_glyphs(font)
+///     .font_size(layout.ruby_font_size())
+///     .draw(Fill::NonZero, run.ruby.into_iter());
+/// # }
+/// ```
+pub struct RubyLayout<'a> {
+    base_font: FontRef<'a>,
+    ruby_font: FontRef<'a>,
+    base_size: f32,
+    ruby_size: f32,
+    base_metrics: GlyphMetrics<'a>,
+    ruby_metrics: GlyphMetrics<'a>,
+    align: RubyAlign,
+    position: RubyPosition,
+    max_overhang: f32,
+}
+
+impl<'a> RubyLayout<'a> {
+    /// Creates a layout for base text in `base_font` at `base_size` pixels per em, annotated
+    /// in `ruby_font`, or returns `None` if either font can't be read.
+    pub fn new(base_font: &'a Font, base_size: f32, ruby_font: &'a Font) -> Option<Self> {
+        let base_font = FontRef::from_index(base_font.data.as_ref(), base_font.index).ok()?;
+        let ruby_font = FontRef::from_index(ruby_font.data.as_ref(), ruby_font.index).ok()?;
+        let ruby_size = base_size / 2.0;
+        Some(Self {
+            base_metrics: base_font.glyph_metrics(Size::new(base_size), LocationRef::default()),
+            ruby_metrics: ruby_font.glyph_metrics(Size::new(ruby_size), LocationRef::default()),
+            base_font,
+            ruby_font,
+            base_size,
+            ruby_size,
+            align: RubyAlign::default(),
+            position: RubyPosition::default(),
+            max_overhang: 0.0,
+        })
+    }
+
+    /// Builder method for setting the font size of the ruby, which is half the size of the
+    /// base text by default.
+    #[must_use]
+    pub fn with_ruby_size(mut self, ruby_size: f32) -> Self {
+        self.ruby_size = ruby_size;
+        self.ruby_metrics = self
+            .ruby_font
+            .glyph_metrics(Size::new(ruby_size), LocationRef::default());
+        self
+    }
+
+    /// Builder method for setting how glyphs are spread, which is
+    /// [`SpaceAround`](RubyAlign::SpaceAround) by default.
+    #[must_use]
+    pub fn with_align(mut self, align: RubyAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Builder method for setting which side of the base text the ruby is set on.
+    #[must_use]
+    pub fn with_position(mut self, position: RubyPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Builder method for setting how far ruby wider than its base may extend over each of
+    /// the characters next to it, in pixels.
+    ///
+    /// This is 0 by default. One ruby character is usual next to kana.
+    #[must_use]
+    pub fn with_max_overhang(mut self, max_overhang: f32) -> Self {
+        self.max_overhang = max_overhang.max(0.0);
+        self
+    }
+
+    /// Returns the font size the ruby glyphs should be drawn at.
+    pub fn ruby_font_size(&self) -> f32 {
+        self.ruby_size
+    }
+
+    /// Positions the `base` glyphs on the baseline at `pen`, annotated with the `ruby`
+    /// glyphs, and advances `pen` past the base.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Glyph positions are stored as f32."
+    )]
+    pub fn layout(
+        &self,
+        base: impl IntoIterator<Item = u32>,
+        ruby: impl IntoIterator<Item = u32>,
+        pen: &mut Point,
+    ) -> RubyRun {
+        let advance_of = |metrics: &GlyphMetrics<'_>, id: u32| {
+            metrics.advance_width(GlyphId::new(id)).unwrap_or_default()
+        };
+        let base: Vec<(u32, f32)> = base
+            .into_iter()
+            .map(|id| (id, advance_of(&self.base_metrics, id)))
+            .collect();
+        let ruby: Vec<(u32, f32)> = ruby
+            .into_iter()
+            .map(|id| (id, advance_of(&self.ruby_metrics, id)))
+            .collect();
+        let base_width: f32 = base.iter().map(|(_, advance)| advance).sum();
+        let ruby_width: f32 = ruby.iter().map(|(_, advance)| advance).sum();
+        // The width of the group, which the ruby may overhang.
+        let width = base_width.max(ruby_width - 2.0 * self.max_overhang);
+        // Ruby which is still wider than the group is centered on it.
+        let ruby_start = ((width - ruby_width) / 2.0).min(0.0);
+        let ruby_span = width.max(ruby_width);
+
+        let x = pen.x as f32;
+        let base_y = pen.y as f32;
+        let base_metrics = self
+            .base_font
+            .metrics(Size::new(self.base_size), LocationRef::default());
+        let ruby_metrics = self
+            .ruby_font
+            .metrics(Size::new(self.ruby_size), LocationRef::default());
+        // The ruby's descent touches the base's ascent, or its ascent the base's descent.
+        let ruby_y = match self.position {
+            RubyPosition::Over => base_y - base_metrics.ascent + ruby_metrics.descent,
+            RubyPosition::Under => base_y - base_metrics.descent + ruby_metrics.ascent,
+        };
+        let run = RubyRun {
+            base: spread(&base, width, self.align, x, base_y),
+            ruby: spread(&ruby, ruby_span, self.align, x + ruby_start, ruby_y),
+        };
+        pen.x += f64::from(width);
+        run
+    }
+}
+
+/// Positions glyphs with the given advances across `width`, starting at `x`.
+fn spread(glyphs: &[(u32, f32)], width: f32, align: RubyAlign, x: f32, y: f32) -> Vec<Glyph> {
+    let natural: f32 = glyphs.iter().map(|(_, advance)| advance).sum();
+    let extra = (width - natural).max(0.0);
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "Runs have far fewer than 2^24 glyphs."
+    )]
+    let count = glyphs.len() as f32;
+    let (start, gap) = match align {
+        RubyAlign::Start => (0.0, 0.0),
+        RubyAlign::SpaceBetween if glyphs.len() > 1 => (0.0, extra / (count - 1.0)),
+        RubyAlign::Center | RubyAlign::SpaceBetween => (extra / 2.0, 0.0),
+        RubyAlign::SpaceAround => (extra / count / 2.0, extra / count),
+    };
+    let mut pen = x + start;
+    glyphs
+        .iter()
+        .map(|&(id, advance)| {
+            let glyph = Glyph { id, x: pen, y };
+            pen += advance + gap;
+            glyph
+        })
+        .collect()
+}
@@ -0,0 +1,372 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Simple shaping of single lines of text, with a choice of OpenType features.
+
+use std::collections::HashMap;
+
+use peniko::kurbo::Point;
+use peniko::Font;
+use skrifa::{
+    charmap::Charmap,
+    instance::{LocationRef, Size},
+    metrics::GlyphMetrics,
+    raw::{
+        tables::gsub::{
+            AlternateSubstFormat1, ExtensionSubtable, Gsub, LigatureSubstFormat1,
+            MultipleSubstFormat1, SingleSubst, SubstitutionLookup,
+        },
+        types::Tag,
+        TableProvider,
+    },
+    FontRef, GlyphId, MetadataProvider,
+};
+
+use catalina_encoding::Glyph;
+
+/// Shapes single lines of text in a font, applying the glyph substitutions of a chosen set of
+/// OpenType features, such as `liga`, `smcp`, `tnum` or `ss01`.
+///
+/// This is a small built-in alternative to a full shaper for labels and other simple text.
+/// Characters are mapped to glyphs with the font's character map, the substitutions of the
+/// features are applied in the order of their lookups, and the glyphs are placed one after
+/// another by their advances:
+///
+/// ```no_run
+/// # use catalina::kurbo::Point;
+/// # use catalina::peniko::{Fill, Font};
+/// # use catalina::{Scene, SimpleShaper};
+/// # fn example(scene: &mut Scene, font: &Font) {
+/// let shaper = SimpleShaper::new(font, 16.0, &[*b"liga", *b"tnum"]).unwrap();
+/// let mut pen = Point::new(10.0, 30.0);
+/// let glyphs = shaper.shape("office 1024", &mut pen);
+/// scene.draw_glyphs(font).font_size(16.0).draw(Fill::NonZero, glyphs.into_iter());
+/// # }
+/// ```
+///
+/// Only the given features are applied, so unlike a full shaper, `liga` and the other features
+/// which are usually on by default must be asked for. Features are taken from the default
+/// language system of the `latn` script, or the `DFLT` script if the font has no `latn` script. Single, multiple, alternate and ligature
+/// substitutions are supported, using the first alternate of alternate substitutions, while
+/// contextual substitutions, lookup flags and positioning features such as `kern` are not.
+///
+/// Glyph runs which were shaped elsewhere are never reshaped: [`DrawGlyphs::draw`] draws the
+/// glyphs it is given as they are, keeping the features chosen by their shaper.
+///
+/// [`DrawGlyphs::draw`]: crate::DrawGlyphs::draw
+pub struct SimpleShaper<'a> {
+    charmap: Charmap<'a>,
+    glyph_metrics: GlyphMetrics<'a>,
+    /// The lookups of the chosen features, in the order they are applied.
+    lookups: Vec<Lookup>,
+}
+
+impl<'a> SimpleShaper<'a> {
+    /// Creates a shaper for `font` at `font_size` pixels per em, which applies the features
+    /// with the tags `features`, or returns `None` if the font can't be read.
+    ///
+    /// Features which the font doesn't have are ignored.
+    pub fn new(font: &'a Font, font_size: f32, features: &[[u8; 4]]) -> Option<Self> {
+        let font = FontRef::from_index(font.data.as_ref(), font.index).ok()?;
+        let tags: Vec<Tag> = features.iter().map(Tag::new).collect();
+        let lookups = font
+            .gsub()
+            .map(|gsub| feature_lookups(&gsub, &tags))
+            .unwrap_or_default();
+        Some(Self {
+            charmap: font.charmap(),
+            glyph_metrics: font.glyph_metrics(Size::new(font_size), LocationRef::default()),
+            lookups,
+        })
+    }
+
+    /// Returns the glyphs of `text`, after the substitutions of the features.
+    ///
+    /// Characters which aren't in the font are mapped to glyph 0.
+    pub fn glyphs(&self, text: &str) -> Vec<u32> {
+        let glyphs = text
+            .chars()
+            .map(|ch| self.charmap.map(ch).unwrap_or_default().to_u32())
+            .collect();
+        self.substitute(glyphs)
+    }
+
+    /// Applies the substitutions of the features to `glyphs`.
+    pub fn substitute(&self, mut glyphs: Vec<u32>) -> Vec<u32> {
+        for lookup in &self.lookups {
+            glyphs = lookup.apply(&glyphs);
+        }
+        glyphs
+    }
+
+    /// Positions the glyphs of `text` on a line starting at `pen`, which is on the baseline,
+    /// and advances `pen` past them.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Glyph positions are stored as f32."
+    )]
+    pub fn shape(&self, text: &str, pen: &mut Point) -> Vec<Glyph> {
+        let y = pen.y as f32;
+        self.glyphs(text)
+            .into_iter()
+            .map(|id| {
+                let x = pen.x as f32;
+                let advance = self
+                    .glyph_metrics
+                    .advance_width(GlyphId::new(id))
+                    .unwrap_or_default();
+                pen.x += f64::from(advance);
+                Glyph { id, x, y }
+            })
+            .collect()
+    }
+}
+
+/// The substitutions of a lookup, merged from its subtables.
+///
+/// The first subtable which covers a glyph is used, as when applying a lookup.
+enum Lookup {
+    /// Replaces a glyph with another.
+    Single(HashMap<u32, u32>),
+    /// Replaces a glyph with a sequence of glyphs.
+    Multiple(HashMap<u32, Vec<u32>>),
+    /// Replaces sequences of glyphs with a ligature, keyed by their first glyph. Each has the
+    /// rest of its components and the ligature glyph, in order of preference.
+    Ligature(HashMap<u32, Vec<(Vec<u32>, u32)>>),
+}
+
+impl Lookup {
+    /// Reads a lookup of a supported type, or returns `None`.
+    fn read(lookup: SubstitutionLookup<'_>) -> Option<Self> {
+        match lookup {
+            SubstitutionLookup::Single(lookup) => {
+                let mut map = HashMap::new();
+                for subtable in lookup.subtables().iter().flatten() {
+                    add_single(&mut map, &subtable);
+                }
+                Some(Self::Single(map))
+            }
+            SubstitutionLookup::Multiple(lookup) => {
+                let mut map = HashMap::new();
+                for subtable in lookup.subtables().iter().flatten() {
+                    add_multiple(&mut map, &subtable);
+                }
+                Some(Self::Multiple(map))
+            }
+            SubstitutionLookup::Alternate(lookup) => {
+                let mut map = HashMap::new();
+                for subtable in lookup.subtables().iter().flatten() {
+                    add_alternate(&mut map, &subtable);
+                }
+                Some(Self::Single(map))
+            }
+            SubstitutionLookup::Ligature(lookup) => {
+                let mut map = HashMap::new();
+                for subtable in lookup.subtables().iter().flatten() {
+                    add_ligature(&mut map, &subtable);
+                }
+                Some(Self::Ligature(map))
+            }
+            SubstitutionLookup::Extension(lookup) => {
+                // All the subtables of a lookup have the same type.
+                let subtables: Vec<_> = lookup.subtables().iter().flatten().collect();
+                match subtables.first()? {
+                    ExtensionSubtable::Single(_) | ExtensionSubtable::Alternate(_) => {
+                        let mut map = HashMap::new();
+                        for subtable in &subtables {
+                            match subtable {
+                                ExtensionSubtable::Single(table) => {
+                                    if let Ok(table) = table.extension() {
+                                        add_single(&mut map, &table);
+                                    }
+                                }
+                                ExtensionSubtable::Alternate(table) => {
+                                    if let Ok(table) = table.extension() {
+                                        add_alternate(&mut map, &table);
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        Some(Self::Single(map))
+                    }
+                    ExtensionSubtable::Multiple(_) => {
+                        let mut map = HashMap::new();
+                        for subtable in &subtables {
+                            if let ExtensionSubtable::Multiple(table) = subtable {
+                                if let Ok(table) = table.extension() {
+                                    add_multiple(&mut map, &table);
+                                }
+                            }
+                        }
+                        Some(Self::Multiple(map))
+                    }
+                    ExtensionSubtable::Ligature(_) => {
+                        let mut map = HashMap::new();
+                        for subtable in &subtables {
+                            if let ExtensionSubtable::Ligature(table) = subtable {
+                                if let Ok(table) = table.extension() {
+                                    add_ligature(&mut map, &table);
+                                }
+                            }
+                        }
+                        Some(Self::Ligature(map))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies the lookup to each glyph of `glyphs` in turn.
+    fn apply(&self, glyphs: &[u32]) -> Vec<u32> {
+        let mut result = Vec::with_capacity(glyphs.len());
+        let mut i = 0;
+        while i < glyphs.len() {
+            let glyph = glyphs[i];
+            i += 1;
+            match self {
+                Self::Single(map) => result.push(map.get(&glyph).copied().unwrap_or(glyph)),
+                Self::Multiple(map) => match map.get(&glyph) {
+                    Some(sequence) => result.extend_from_slice(sequence),
+                    None => result.push(glyph),
+                },
+                Self::Ligature(map) => {
+                    let ligature = map.get(&glyph).and_then(|ligatures| {
+                        ligatures
+                            .iter()
+                            .find(|(components, _)| glyphs[i..].starts_with(components))
+                    });
+                    match ligature {
+                        Some((components, ligature)) => {
+                            result.push(*ligature);
+                            i += components.len();
+                        }
+                        None => result.push(glyph),
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+fn add_single(map: &mut HashMap<u32, u32>, table: &SingleSubst<'_>) {
+    match table {
+        SingleSubst::Format1(table) => {
+            let Ok(coverage) = table.coverage() else {
+                return;
+            };
+            let delta = table.delta_glyph_id();
+            for glyph in coverage.iter() {
+                let substitute = glyph.to_u16().wrapping_add_signed(delta);
+                map.entry(glyph.to_u32()).or_insert(u32::from(substitute));
+            }
+        }
+        SingleSubst::Format2(table) => {
+            let Ok(coverage) = table.coverage() else {
+                return;
+            };
+            for (glyph, substitute) in coverage.iter().zip(table.substitute_glyph_ids()) {
+                map.entry(glyph.to_u32())
+                    .or_insert(substitute.get().to_u32());
+            }
+        }
+    }
+}
+
+fn add_multiple(map: &mut HashMap<u32, Vec<u32>>, table: &MultipleSubstFormat1<'_>) {
+    let Ok(coverage) = table.coverage() else {
+        return;
+    };
+    for (glyph, sequence) in coverage.iter().zip(table.sequences().iter()) {
+        let Ok(sequence) = sequence else {
+            continue;
+        };
+        map.entry(glyph.to_u32()).or_insert_with(|| {
+            let glyphs = sequence.substitute_glyph_ids().iter();
+            glyphs.map(|glyph| glyph.get().to_u32()).collect()
+        });
+    }
+}
+
+/// Adds the first alternate of each glyph, as shapers choose by default.
+fn add_alternate(map: &mut HashMap<u32, u32>, table: &AlternateSubstFormat1<'_>) {
+    let Ok(coverage) = table.coverage() else {
+        return;
+    };
+    for (glyph, alternates) in coverage.iter().zip(table.alternate_sets().iter()) {
+        let Ok(alternates) = alternates else {
+            continue;
+        };
+        if let Some(alternate) = alternates.alternate_glyph_ids().first() {
+            map.entry(glyph.to_u32())
+                .or_insert(alternate.get().to_u32());
+        }
+    }
+}
+
+fn add_ligature(map: &mut HashMap<u32, Vec<(Vec<u32>, u32)>>, table: &LigatureSubstFormat1<'_>) {
+    let Ok(coverage) = table.coverage() else {
+        return;
+    };
+    for (glyph, ligatures) in coverage.iter().zip(table.ligature_sets().iter()) {
+        let Ok(ligatures) = ligatures else {
+            continue;
+        };
+        let entry = map.entry(glyph.to_u32()).or_default();
+        for ligature in ligatures.ligatures().iter().flatten() {
+            let components = ligature.component_glyph_ids().iter();
+            entry.push((
+                components.map(|glyph| glyph.get().to_u32()).collect(),
+                ligature.ligature_glyph().to_u32(),
+            ));
+        }
+    }
+}
+
+/// Collects the supported lookups of the features of `gsub` with the given tags, in the order
+/// they are applied.
+fn feature_lookups(gsub: &Gsub<'_>, tags: &[Tag]) -> Vec<Lookup> {
+    let (Ok(features), Ok(lookups)) = (gsub.feature_list(), gsub.lookup_list()) else {
+        return Vec::new();
+    };
+    let records = features.feature_records();
+    // The features of the default language system, or all features if there isn't one.
+    let feature_indices: Vec<usize> =
+        default_features(gsub).unwrap_or_else(|| (0..records.len()).collect());
+    let mut indices: Vec<u16> = feature_indices
+        .into_iter()
+        .filter_map(|index| records.get(index))
+        .filter(|record| tags.contains(&record.feature_tag()))
+        .filter_map(|record| record.feature(features.offset_data()).ok())
+        .flat_map(|feature| {
+            feature
+                .lookup_list_indices()
+                .iter()
+                .map(|index| index.get())
+        })
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+        .into_iter()
+        .filter_map(|index| lookups.lookups().get(usize::from(index)).ok())
+        .filter_map(Lookup::read)
+        .collect()
+}
+
+/// Returns the indices of the features of the default language system of the `latn` script,
+/// or of the `DFLT` script if there is no `latn` script.
+fn default_features(gsub: &Gsub<'_>) -> Option<Vec<usize>> {
+    let scripts = gsub.script_list().ok()?;
+    let records = scripts.script_records();
+    let record = [Tag::new(b"latn"), Tag::new(b"DFLT")]
+        .iter()
+        .find_map(|tag| records.iter().find(|record| record.script_tag() == *tag))?;
+    let script = record.script(scripts.offset_data()).ok()?;
+    let lang_sys = script.default_lang_sys()?.ok()?;
+    let indices = lang_sys.feature_indices().iter();
+    Some(indices.map(|index| usize::from(index.get())).collect())
+}
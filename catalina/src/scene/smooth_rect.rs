@@ -0,0 +1,214 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Rounded rectangles with smoothed corners, as used by iOS and design tools.
+
+use std::f64::consts::FRAC_PI_4;
+
+use peniko::kurbo::{
+    Affine, Arc, BezPath, PathEl, Point, Rect, RoundedRect, RoundedRectRadii, Shape, Vec2,
+};
+
+/// The corner smoothing which matches the continuous corners of iOS.
+pub const IOS_CORNER_SMOOTHING: f64 = 0.6;
+
+/// A rectangle whose rounded corners blend smoothly into its sides, also known as a squircle.
+///
+/// The corners of a [`RoundedRect`] are circular arcs, whose curvature changes abruptly where
+/// they meet the sides. Smoothing replaces the start and end of each arc with a Bézier curve
+/// which eases in from the side, like the corners of iOS and the corner smoothing of design
+/// tools. With a `smoothing` of 0 this is the same as a [`RoundedRect`], and with 1 the
+/// corners are made entirely of curves. [`IOS_CORNER_SMOOTHING`] matches iOS.
+///
+/// Smoothing extends a corner along the sides of the rectangle by up to its radius. Where
+/// there isn't room for that, the smoothing is reduced so that the corners don't overlap.
+///
+/// This implements [`Shape`], so can be filled or stroked like any other shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothRect {
+    /// The rectangle whose corners are rounded.
+    pub rect: Rect,
+    /// The radius of each corner.
+    pub radii: RoundedRectRadii,
+    /// How far the corners are smoothed, from 0 for circular corners to 1.
+    pub smoothing: f64,
+}
+
+impl SmoothRect {
+    /// Creates a rectangle with rounded corners of `radii`, smoothed by `smoothing`.
+    pub fn from_rect(rect: Rect, radii: impl Into<RoundedRectRadii>, smoothing: f64) -> Self {
+        Self {
+            rect: rect.abs(),
+            radii: radii.into(),
+            smoothing: smoothing.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Creates a rectangle with rounded corners of `radii`, smoothed like the corners of
+    /// iOS.
+    pub fn ios(rect: Rect, radii: impl Into<RoundedRectRadii>) -> Self {
+        Self::from_rect(rect, radii, IOS_CORNER_SMOOTHING)
+    }
+
+    /// Builder method for setting the corner smoothing.
+    #[must_use]
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Builds the outline of the shape, clockwise from the top edge.
+    fn path(&self, tolerance: f64) -> BezPath {
+        let Rect { x0, y0, x1, y1 } = self.rect;
+        // Each corner is at `point`, with the side before it in the direction of `incoming`
+        // and the side after it in the direction of `outgoing`.
+        let corners = [
+            (
+                self.radii.top_right,
+                Point::new(x1, y0),
+                Vec2::new(1.0, 0.0),
+            ),
+            (
+                self.radii.bottom_right,
+                Point::new(x1, y1),
+                Vec2::new(0.0, 1.0),
+            ),
+            (
+                self.radii.bottom_left,
+                Point::new(x0, y1),
+                Vec2::new(-1.0, 0.0),
+            ),
+            (
+                self.radii.top_left,
+                Point::new(x0, y0),
+                Vec2::new(0.0, -1.0),
+            ),
+        ];
+        let budget = self.rect.width().min(self.rect.height()) / 2.0;
+        let mut path = BezPath::new();
+        let (radius, point, _) = corners[3];
+        let start = Corner::new(radius, self.smoothing, budget).extent;
+        path.move_to(point + Vec2::new(start, 0.0));
+        for (radius, point, incoming) in corners {
+            let outgoing = Vec2::new(-incoming.y, incoming.x);
+            let to_side = Affine::new([
+                incoming.x, incoming.y, outgoing.x, outgoing.y, point.x, point.y,
+            ]);
+            Corner::new(radius, self.smoothing, budget).append(&mut path, to_side, tolerance);
+        }
+        path.close_path();
+        path
+    }
+}
+
+impl From<RoundedRect> for SmoothRect {
+    fn from(rect: RoundedRect) -> Self {
+        Self::from_rect(rect.rect(), rect.radii(), 0.0)
+    }
+}
+
+/// The curves of one corner, following "Desperately seeking squircles" by Figma.
+///
+/// These are in a frame with the corner at the origin, the side before it along the negative
+/// x axis, and the side after it along the positive y axis.
+struct Corner {
+    radius: f64,
+    /// How far the corner extends along each side.
+    extent: f64,
+    /// The angle of the circular arc in the middle of the corner.
+    arc_sweep: f64,
+    /// The distances along the Bézier curve at the start of the corner.
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+}
+
+impl Corner {
+    fn new(radius: f64, smoothing: f64, budget: f64) -> Self {
+        let radius = radius.clamp(0.0, budget);
+        let smoothing = if radius > 0.0 {
+            smoothing.min(budget / radius - 1.0)
+        } else {
+            0.0
+        };
+        let extent = (1.0 + smoothing) * radius;
+        let arc_sweep = 2.0 * FRAC_PI_4 * (1.0 - smoothing);
+        let arc_length = (arc_sweep / 2.0).sin() * radius * 2.0_f64.sqrt();
+        let alpha = FRAC_PI_4 - arc_sweep / 2.0;
+        let beta = FRAC_PI_4 * smoothing;
+        let c = radius * (alpha / 2.0).tan() * beta.cos();
+        let d = c * beta.tan();
+        let b = (extent - arc_length - c - d) / 3.0;
+        Self {
+            radius,
+            extent,
+            arc_sweep,
+            a: 2.0 * b,
+            b,
+            c,
+            d,
+        }
+    }
+
+    /// Appends a line along the side to the start of the corner, and the curves of the
+    /// corner, transformed by `to_side`.
+    fn append(&self, path: &mut BezPath, to_side: Affine, tolerance: f64) {
+        let Self {
+            radius,
+            extent,
+            arc_sweep,
+            a,
+            b,
+            c,
+            d,
+        } = *self;
+        path.line_to(to_side * Point::new(-extent, 0.0));
+        if radius == 0.0 {
+            return;
+        }
+        path.curve_to(
+            to_side * Point::new(-extent + a, 0.0),
+            to_side * Point::new(-extent + a + b, 0.0),
+            to_side * Point::new(-extent + a + b + c, d),
+        );
+        let arc = Arc::new(
+            (-radius, radius),
+            (radius, radius),
+            -FRAC_PI_4 - arc_sweep / 2.0,
+            arc_sweep,
+            0.0,
+        );
+        arc.append_iter(tolerance)
+            .for_each(|el| path.push(to_side * el));
+        path.curve_to(
+            to_side * Point::new(0.0, extent - a - b),
+            to_side * Point::new(0.0, extent - a),
+            to_side * Point::new(0.0, extent),
+        );
+    }
+}
+
+impl Shape for SmoothRect {
+    type PathElementsIter<'iter> = std::vec::IntoIter<PathEl>;
+
+    fn path_elements(&self, tolerance: f64) -> Self::PathElementsIter<'_> {
+        self.path(tolerance).elements().to_vec().into_iter()
+    }
+
+    fn area(&self) -> f64 {
+        self.path(0.1).area()
+    }
+
+    fn perimeter(&self, accuracy: f64) -> f64 {
+        self.path(0.1).perimeter(accuracy)
+    }
+
+    fn winding(&self, pt: Point) -> i32 {
+        self.path(0.1).winding(pt)
+    }
+
+    fn bounding_box(&self) -> Rect {
+        self.rect
+    }
+}
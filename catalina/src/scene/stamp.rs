@@ -0,0 +1,323 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Brush tips stamped along a path, as in painting applications.
+
+use peniko::kurbo::{flatten, Affine, PathEl, Point, Shape, Vec2};
+use peniko::Image;
+
+use super::{StrokeDynamics, StrokePoint};
+
+/// The minimum distance between stamps, so that degenerate tips can't produce unbounded work.
+const MIN_SPACING: f64 = 0.25;
+
+/// An image stamped repeatedly along a path, such as a soft round or textured brush tip.
+///
+/// The tip image is centered on each stamp, and scaled so that its larger side matches the
+/// current size of the brush.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrushTip {
+    /// The image of the tip, usually white or black with the shape of the tip in its alpha.
+    pub image: Image,
+    /// The diameter of the tip at full size, in the local coordinates of the stroke.
+    pub size: f64,
+    /// The distance between stamps, as a fraction of the current size of the tip.
+    pub spacing: f64,
+    /// The rotation of the tip, in radians.
+    pub angle: f64,
+    /// Whether the tip is additionally rotated to follow the direction of the path.
+    pub follow_direction: bool,
+    /// The maximum distance each stamp is moved across the path, as a fraction of its size.
+    pub scatter: f64,
+    /// The maximum fraction by which the size of each stamp is randomly reduced, in `0..=1`.
+    pub size_jitter: f64,
+    /// The maximum random rotation of each stamp, in radians.
+    pub angle_jitter: f64,
+    /// The maximum fraction by which the opacity of each stamp is randomly reduced, in `0..=1`.
+    pub opacity_jitter: f32,
+    /// The seed for the random jitter, so that re-encoding a stroke gives the same result.
+    pub seed: u64,
+}
+
+impl BrushTip {
+    /// The most stamps placed by one call to [`Scene::stamp_points`](crate::Scene::stamp_points)
+    /// or [`Scene::stamp_shape`](crate::Scene::stamp_shape).
+    ///
+    /// Tiny tips along long or non-finite paths would otherwise encode an unbounded number of
+    /// draws. The rest of the path is left without stamps, and a warning is logged.
+    pub const MAX_STAMPS: usize = 65_536;
+
+    /// Creates a brush tip with the given image and size, with a spacing of a quarter of the
+    /// size and no jitter.
+    pub fn new(image: Image, size: f64) -> Self {
+        Self {
+            image,
+            size,
+            spacing: 0.25,
+            angle: 0.0,
+            follow_direction: false,
+            scatter: 0.0,
+            size_jitter: 0.0,
+            angle_jitter: 0.0,
+            opacity_jitter: 0.0,
+            seed: 0,
+        }
+    }
+
+    /// Computes the stamps for a line through `points`, calling `stamp` with the transform
+    /// which maps the tip image into place and the opacity of each stamp.
+    ///
+    /// At most [`MAX_STAMPS`](Self::MAX_STAMPS) stamps are placed.
+    pub(crate) fn stamps(
+        &self,
+        dynamics: &StrokeDynamics,
+        points: &[StrokePoint],
+        stamp: impl FnMut(Affine, f32),
+    ) {
+        let mut budget = Self::MAX_STAMPS;
+        self.stamps_within(dynamics, points, &mut budget, stamp);
+        warn_if_exhausted(budget);
+    }
+
+    /// Computes the stamps for a line through `points` like [`stamps`](Self::stamps), placing
+    /// at most `budget` stamps and subtracting the number placed from it.
+    fn stamps_within(
+        &self,
+        dynamics: &StrokeDynamics,
+        points: &[StrokePoint],
+        budget: &mut usize,
+        mut stamp: impl FnMut(Affine, f32),
+    ) {
+        if *budget == 0 {
+            return;
+        }
+        let mut rng = Rng::new(self.seed);
+        // Places a stamp, returning `false` once the budget is used up.
+        let mut place = |point: &StrokePoint, direction: Vec2| {
+            // Stamps which turn out to be invisible still count, as they cost the same work.
+            *budget -= 1;
+            // Always draw the randomness in the same order, so that disabling one kind of jitter
+            // doesn't change the others.
+            let size_jitter = rng.next_f64();
+            let angle_jitter = rng.next_f64();
+            let scatter = rng.next_f64();
+            let opacity_jitter = rng.next_f64();
+            let size = self.size
+                * dynamics.width_scale(point)
+                * (1.0 - self.size_jitter.clamp(0.0, 1.0) * size_jitter);
+            let mut angle = self.angle + self.angle_jitter * (2.0 * angle_jitter - 1.0);
+            if self.follow_direction {
+                angle += direction.atan2();
+            }
+            let normal = Vec2::new(-direction.y, direction.x);
+            let offset = normal * (self.scatter * size * (2.0 * scatter - 1.0));
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "The jitter is in 0..=1, so this is only a loss of precision."
+            )]
+            let alpha = dynamics.opacity(point)
+                * (1.0 - self.opacity_jitter.clamp(0.0, 1.0) * opacity_jitter as f32);
+            if size > 0.0 && alpha > 0.0 {
+                stamp(self.placement(point.position + offset, angle, size), alpha);
+            }
+            *budget > 0
+        };
+        let Some(first) = points.first() else {
+            return;
+        };
+        let initial_direction = points
+            .windows(2)
+            .map(|pair| pair[1].position - pair[0].position)
+            .find(|d| d.hypot2() > 0.0)
+            .map_or(Vec2::new(1.0, 0.0), Vec2::normalize);
+        if !place(first, initial_direction) {
+            return;
+        }
+        // The distance along the current segment at which the next stamp is placed.
+        let mut next = self.spacing_at(dynamics, first);
+        for pair in points.windows(2) {
+            let (p0, p1) = (&pair[0], &pair[1]);
+            let d = p1.position - p0.position;
+            let length = d.hypot();
+            if length == 0.0 {
+                continue;
+            }
+            let direction = d / length;
+            while next <= length {
+                let point = lerp_point(p0, p1, next / length);
+                if !place(&point, direction) {
+                    return;
+                }
+                next += self.spacing_at(dynamics, &point);
+            }
+            next -= length;
+        }
+    }
+
+    /// Computes the stamps along the outline of a shape, at full pressure.
+    ///
+    /// At most [`MAX_STAMPS`](Self::MAX_STAMPS) stamps are placed, across all subpaths.
+    pub(crate) fn stamps_along(&self, shape: &impl Shape, mut stamp: impl FnMut(Affine, f32)) {
+        let dynamics = StrokeDynamics::default();
+        let mut budget = Self::MAX_STAMPS;
+        let mut points = Vec::new();
+        let mut start = None;
+        let mut flush = |points: &mut Vec<StrokePoint>| {
+            self.stamps_within(&dynamics, points, &mut budget, &mut stamp);
+            points.clear();
+        };
+        flatten(shape.path_elements(0.1), 0.1, |el| match el {
+            PathEl::MoveTo(p) => {
+                flush(&mut points);
+                start = Some(p);
+                points.push(StrokePoint::new(p, 1.0));
+            }
+            PathEl::LineTo(p) => points.push(StrokePoint::new(p, 1.0)),
+            PathEl::ClosePath => {
+                if let Some(p) = start {
+                    points.push(StrokePoint::new(p, 1.0));
+                }
+            }
+            // `flatten` only produces lines.
+            PathEl::QuadTo(..) | PathEl::CurveTo(..) => {}
+        });
+        flush(&mut points);
+        warn_if_exhausted(budget);
+    }
+
+    /// Returns the transform which centers the tip image at `center`, rotated by `angle` and
+    /// scaled so that its larger side is `size`.
+    fn placement(&self, center: Point, angle: f64, size: f64) -> Affine {
+        let width = f64::from(self.image.width);
+        let height = f64::from(self.image.height);
+        let scale = size / width.max(height).max(1.0);
+        Affine::translate(center.to_vec2())
+            * Affine::rotate(angle)
+            * Affine::scale(scale)
+            * Affine::translate((-0.5 * width, -0.5 * height))
+    }
+
+    fn spacing_at(&self, dynamics: &StrokeDynamics, point: &StrokePoint) -> f64 {
+        (self.spacing * self.size * dynamics.width_scale(point)).max(MIN_SPACING)
+    }
+}
+
+fn warn_if_exhausted(budget: usize) {
+    if budget == 0 {
+        log::warn!(
+            "Stopped stamping a brush tip after {} stamps; increase its size or spacing",
+            BrushTip::MAX_STAMPS
+        );
+    }
+}
+
+/// Linearly interpolates the position and attributes of two points.
+fn lerp_point(p0: &StrokePoint, p1: &StrokePoint, t: f64) -> StrokePoint {
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The interpolation factor is in 0..=1, so this is only a loss of precision."
+    )]
+    let t32 = t as f32;
+    StrokePoint {
+        position: p0.position.lerp(p1.position, t),
+        pressure: p0.pressure + (p1.pressure - p0.pressure) * t32,
+        tilt: p0.tilt.lerp(p1.tilt, t),
+        velocity: p0.velocity + (p1.velocity - p0.velocity) * t,
+    }
+}
+
+/// A small deterministic random number generator for jitter (`SplitMix64`).
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns a number in `0..1`.
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // Only the top 53 bits are used, which are exactly representable.
+        (z >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use peniko::kurbo::{BezPath, Point};
+    use peniko::{Blob, Image, ImageFormat};
+
+    use super::BrushTip;
+    use crate::{StrokeDynamics, StrokePoint};
+
+    fn tip(size: f64) -> BrushTip {
+        let image = Image::new(
+            Blob::new(Arc::new(vec![255_u8; 4])),
+            ImageFormat::Rgba8,
+            1,
+            1,
+        );
+        BrushTip::new(image, size)
+    }
+
+    /// The centers of the stamps along a line through `points`.
+    fn centers(tip: &BrushTip, points: &[StrokePoint]) -> Vec<Point> {
+        let mut centers = Vec::new();
+        tip.stamps(&StrokeDynamics::default(), points, |placement, _| {
+            centers.push(placement * Point::new(0.5, 0.5));
+        });
+        centers
+    }
+
+    #[test]
+    fn stamps_are_spaced_by_the_tip_size() {
+        // A spacing of a quarter of the size.
+        let tip = tip(4.0);
+        let points = [
+            StrokePoint::new((0.0, 0.0), 1.0),
+            StrokePoint::new((2.5, 0.0), 1.0),
+            StrokePoint::new((5.0, 0.0), 1.0),
+        ];
+        let stamps = centers(&tip, &points);
+        assert_eq!(stamps.len(), 6);
+        for (center, x) in stamps.iter().zip(0..) {
+            assert!((center.x - f64::from(x)).abs() < 1e-9, "{stamps:?}");
+            assert!(center.y.abs() < 1e-9, "{stamps:?}");
+        }
+        // At half pressure, the tip is half the size, so the stamps are twice as dense.
+        let half_pressure = points.map(|point| StrokePoint::new(point.position, 0.5));
+        assert_eq!(centers(&tip, &half_pressure).len(), 11);
+    }
+
+    #[test]
+    fn stamps_are_capped() {
+        // The spacing of tiny tips is limited, but long paths still need too many stamps.
+        let tip = tip(1e-6);
+        let points = [
+            StrokePoint::new((0.0, 0.0), 1.0),
+            StrokePoint::new((1e9, 0.0), 1.0),
+        ];
+        assert_eq!(centers(&tip, &points).len(), BrushTip::MAX_STAMPS);
+        // Without the cap, this would never finish.
+        let infinite = [
+            StrokePoint::new((0.0, 0.0), 1.0),
+            StrokePoint::new((f64::INFINITY, 0.0), 1.0),
+        ];
+        assert!(centers(&tip, &infinite).len() <= BrushTip::MAX_STAMPS);
+        // The cap is shared by the subpaths of a shape.
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((1e9, 0.0));
+        path.move_to((0.0, 10.0));
+        path.line_to((1e9, 10.0));
+        let mut count = 0;
+        tip.stamps_along(&path, |_, _| count += 1);
+        assert_eq!(count, BrushTip::MAX_STAMPS);
+    }
+}
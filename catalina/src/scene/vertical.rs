@@ -0,0 +1,380 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Vertical writing mode, as used for Chinese, Japanese and Korean text.
+
+use std::collections::HashMap;
+
+use peniko::kurbo::{Affine, Point};
+use peniko::Font;
+use skrifa::{
+    instance::{LocationRef, Size},
+    metrics::GlyphMetrics,
+    raw::{
+        tables::{
+            gsub::{SingleSubst, SubstitutionLookup},
+            vmtx::Vmtx,
+            vorg::Vorg,
+        },
+        types::Tag,
+        TableProvider,
+    },
+    FontRef, GlyphId, MetadataProvider,
+};
+
+use catalina_encoding::Glyph;
+
+/// How a character is oriented in vertical text, from the `Vertical_Orientation` property
+/// of [UAX #50](https://www.unicode.org/reports/tr50/).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VerticalOrientation {
+    /// Displayed upright, as in horizontal text, such as ideographs and kana.
+    Upright,
+    /// Displayed rotated 90 degrees clockwise, such as Latin letters.
+    Rotated,
+    /// Displayed with a vertical alternate glyph if the font has one, and otherwise upright,
+    /// such as small kana and ideographic punctuation.
+    TransformedOrUpright,
+    /// Displayed with a vertical alternate glyph if the font has one, and otherwise rotated,
+    /// such as brackets and the prolonged sound mark.
+    TransformedOrRotated,
+}
+
+impl VerticalOrientation {
+    /// Returns the orientation of `ch` in vertical text.
+    pub fn of(ch: char) -> Self {
+        let cp = u32::from(ch);
+        if matches!(
+            cp,
+            0x2329..=0x232A
+                | 0x3008..=0x3011
+                | 0x3014..=0x301F
+                | 0x3030
+                | 0x30A0
+                | 0x30FC
+                | 0xFE59..=0xFE5E
+                | 0xFF08..=0xFF09
+                | 0xFF0D
+                | 0xFF1C..=0xFF1E
+                | 0xFF3B
+                | 0xFF3D
+                | 0xFF3F
+                | 0xFF5B..=0xFF60
+                | 0xFFE3
+        ) {
+            Self::TransformedOrRotated
+        } else if matches!(
+            cp,
+            0x3001..=0x3002
+                | 0x3041
+                | 0x3043
+                | 0x3045
+                | 0x3047
+                | 0x3049
+                | 0x3063
+                | 0x3083
+                | 0x3085
+                | 0x3087
+                | 0x308E
+                | 0x3095..=0x3096
+                | 0x309B..=0x309C
+                | 0x30A1
+                | 0x30A3
+                | 0x30A5
+                | 0x30A7
+                | 0x30A9
+                | 0x30C3
+                | 0x30E3
+                | 0x30E5
+                | 0x30E7
+                | 0x30EE
+                | 0x30F5..=0x30F6
+                | 0x3127
+                | 0x31F0..=0x31FF
+                | 0x3300..=0x3357
+                | 0x337B..=0x337F
+                | 0xFE50..=0xFE52
+                | 0xFF01
+                | 0xFF0C
+                | 0xFF0E
+                | 0xFF1F
+        ) {
+            Self::TransformedOrUpright
+        } else if matches!(
+            cp,
+            0x00A7
+                | 0x00A9
+                | 0x00AE
+                | 0x00B1
+                | 0x00BC..=0x00BE
+                | 0x00D7
+                | 0x00F7
+                | 0x02EA..=0x02EB
+                | 0x1100..=0x11FF
+                | 0x1401..=0x167F
+                | 0x18B0..=0x18FF
+                | 0x2016
+                | 0x2020..=0x2021
+                | 0x2030..=0x2031
+                | 0x203B..=0x203C
+                | 0x2042
+                | 0x2047..=0x2049
+                | 0x2051
+                | 0x20DD..=0x20E4
+                | 0x2100..=0x2189
+                | 0x218C..=0x218F
+                | 0x221E
+                | 0x2234..=0x2235
+                | 0x2300..=0x2307
+                | 0x230C..=0x231F
+                | 0x2322..=0x232B
+                | 0x237D..=0x239A
+                | 0x23BE..=0x23CD
+                | 0x23CF
+                | 0x23D1..=0x23DB
+                | 0x23E2..=0x2422
+                | 0x2424..=0x24FF
+                | 0x25A0..=0x2619
+                | 0x2620..=0x2767
+                | 0x2776..=0x2793
+                | 0x2B12..=0x2B2F
+                | 0x2B50..=0x2B59
+                | 0x2BB8..=0x2BFF
+                | 0x2E80..=0xA4CF
+                | 0xA960..=0xA97F
+                | 0xAC00..=0xD7FF
+                | 0xE000..=0xFAFF
+                | 0xFE10..=0xFE1F
+                | 0xFE30..=0xFE48
+                | 0xFE53..=0xFE57
+                | 0xFE5F..=0xFE62
+                | 0xFE67..=0xFE6F
+                | 0xFF00..=0xFF60
+                | 0xFFE0..=0xFFE7
+                | 0xFFF0..=0xFFF8
+                | 0xFFFC..=0xFFFD
+                | 0x1F000..=0x1FAFF
+                | 0x20000..=0x3FFFD
+                | 0xF0000..=0x10FFFD
+        ) {
+            Self::Upright
+        } else {
+            Self::Rotated
+        }
+    }
+}
+
+/// Positions glyphs in a vertical line, for the vertical writing mode of Chinese, Japanese
+/// and Korean text.
+///
+/// Glyphs are either upright, advancing down the line by their vertical advance, or rotated
+/// 90 degrees clockwise, advancing by their horizontal advance as for sideways Latin text.
+/// Which a character uses is given by [`VerticalOrientation`]. Upright glyphs are replaced by
+/// their vertical alternates from the `vert` and `vrt2` features of the font, such as
+/// ideographic punctuation which is moved to the top right of its em box.
+///
+/// The glyphs are positioned relative to a pen at the top of the next glyph, on the center
+/// line of the column, which is advanced past them:
+///
+/// ```no_run
+/// # use catalina::kurbo::Point;
+/// # use catalina::peniko::{Fill, Font};
+/// # use catalina::{Scene, VerticalLayout};
+/// # fn example(scene: &mut Scene, font: &Font, kanji: &[u32], latin: &[u32]) {
+/// let layout = VerticalLayout::new(font, 24.0).unwrap();
+/// let mut pen = Point::new(100.0, 20.0);
+/// let upright = layout.upright(kanji.iter().copied(), &mut pen);
+/// scene.draw_glyphs(font).font_size(24.0).draw(Fill::NonZero, upright.into_iter());
+/// let rotated = layout.rotated(latin.iter().copied(), &mut pen);
+/// scene
+///     .draw_glyphs(font)
+///     .font_size(24.0)
+///     .glyph_transform(Some(VerticalLayout::ROTATION))
+///     .draw(Fill::NonZero, rotated.into_iter());
+/// # }
+/// ```
+///
+/// Only vertical alternates from single substitution lookups, which aren't wrapped in
+/// extension lookups, are used.
+pub struct VerticalLayout<'a> {
+    font_size: f32,
+    /// Converts from font units to pixels.
+    scale: f32,
+    ascent: f32,
+    descent: f32,
+    glyph_metrics: GlyphMetrics<'a>,
+    vmtx: Option<Vmtx<'a>>,
+    vorg: Option<Vorg<'a>>,
+    /// The vertical alternates of glyphs.
+    alternates: HashMap<u32, u32>,
+}
+
+impl<'a> VerticalLayout<'a> {
+    /// The glyph transform which rotated glyphs should be drawn with, using
+    /// [`DrawGlyphs::glyph_transform`](crate::DrawGlyphs::glyph_transform).
+    pub const ROTATION: Affine = Affine::new([0.0, 1.0, -1.0, 0.0, 0.0, 0.0]);
+
+    /// Creates a layout for `font` at `font_size` pixels per em, or returns `None` if the
+    /// font can't be read.
+    pub fn new(font: &'a Font, font_size: f32) -> Option<Self> {
+        let font = FontRef::from_index(font.data.as_ref(), font.index).ok()?;
+        let size = Size::new(font_size);
+        let metrics = font.metrics(size, LocationRef::default());
+        Some(Self {
+            font_size,
+            scale: font_size / f32::from(metrics.units_per_em.max(1)),
+            ascent: metrics.ascent,
+            descent: metrics.descent,
+            glyph_metrics: font.glyph_metrics(size, LocationRef::default()),
+            vmtx: font.vmtx().ok(),
+            vorg: font.vorg().ok(),
+            alternates: vertical_alternates(&font),
+        })
+    }
+
+    /// Returns the vertical alternate of `glyph`, or `glyph` if it has none.
+    pub fn alternate(&self, glyph: u32) -> u32 {
+        self.alternates.get(&glyph).copied().unwrap_or(glyph)
+    }
+
+    /// Returns whether the font has a vertical alternate of `glyph`.
+    pub fn has_alternate(&self, glyph: u32) -> bool {
+        self.alternates.contains_key(&glyph)
+    }
+
+    /// Returns whether the glyph `glyph` for the character `ch` should be upright, which
+    /// depends on whether the font has a vertical alternate for some characters.
+    pub fn is_upright(&self, ch: char, glyph: u32) -> bool {
+        match VerticalOrientation::of(ch) {
+            VerticalOrientation::Upright | VerticalOrientation::TransformedOrUpright => true,
+            VerticalOrientation::Rotated => false,
+            VerticalOrientation::TransformedOrRotated => self.has_alternate(glyph),
+        }
+    }
+
+    /// Returns the distance `glyph` advances the pen down the line when upright.
+    ///
+    /// Fonts without vertical metrics advance by one em.
+    pub fn vertical_advance(&self, glyph: u32) -> f32 {
+        self.vmtx
+            .as_ref()
+            .and_then(|vmtx| vmtx.advance(GlyphId::new(glyph)))
+            .map_or(self.font_size, |advance| f32::from(advance) * self.scale)
+    }
+
+    /// Returns the distance `glyph` advances the pen down the line when rotated.
+    pub fn horizontal_advance(&self, glyph: u32) -> f32 {
+        self.glyph_metrics
+            .advance_width(GlyphId::new(glyph))
+            .unwrap_or_default()
+    }
+
+    /// Returns the distance from the top of the vertical em box of `glyph` down to its
+    /// baseline.
+    fn vertical_origin(&self, glyph: u32) -> f32 {
+        let glyph_id = GlyphId::new(glyph);
+        if let Some(vorg) = &self.vorg {
+            return f32::from(vorg.vertical_origin_y(glyph_id)) * self.scale;
+        }
+        let top = self.vmtx.as_ref().and_then(|vmtx| {
+            let side_bearing = vmtx.side_bearing(glyph_id)?;
+            let bounds = self.glyph_metrics.bounds(glyph_id)?;
+            Some(f32::from(side_bearing) * self.scale + bounds.y_max)
+        });
+        top.unwrap_or(self.ascent)
+    }
+
+    /// Positions `glyphs` upright, replaced by their vertical alternates, and advances `pen`
+    /// past them.
+    ///
+    /// Glyphs without a vertical alternate, including alternates already chosen by a shaper,
+    /// are kept.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Glyph positions are stored as f32."
+    )]
+    pub fn upright(&self, glyphs: impl IntoIterator<Item = u32>, pen: &mut Point) -> Vec<Glyph> {
+        glyphs
+            .into_iter()
+            .map(|glyph| {
+                let id = self.alternate(glyph);
+                let x = pen.x as f32 - self.horizontal_advance(id) / 2.0;
+                let y = pen.y as f32 + self.vertical_origin(id);
+                pen.y += f64::from(self.vertical_advance(id));
+                Glyph { id, x, y }
+            })
+            .collect()
+    }
+
+    /// Positions `glyphs` rotated 90 degrees clockwise, centered on the line, and advances
+    /// `pen` past them.
+    ///
+    /// The glyphs must be drawn with the [`ROTATION`](Self::ROTATION) glyph transform.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Glyph positions are stored as f32."
+    )]
+    pub fn rotated(&self, glyphs: impl IntoIterator<Item = u32>, pen: &mut Point) -> Vec<Glyph> {
+        // The ascent is to the right of the baseline once rotated, and the descent to the left.
+        let x = pen.x as f32 - (self.ascent + self.descent) / 2.0;
+        glyphs
+            .into_iter()
+            .map(|id| {
+                let y = pen.y as f32;
+                pen.y += f64::from(self.horizontal_advance(id));
+                Glyph { id, x, y }
+            })
+            .collect()
+    }
+}
+
+/// Collects the single substitutions of the `vert` and `vrt2` features of `font`.
+fn vertical_alternates(font: &FontRef<'_>) -> HashMap<u32, u32> {
+    let mut alternates = HashMap::new();
+    let Ok(gsub) = font.gsub() else {
+        return alternates;
+    };
+    let (Ok(features), Ok(lookups)) = (gsub.feature_list(), gsub.lookup_list()) else {
+        return alternates;
+    };
+    let tags = [Tag::new(b"vert"), Tag::new(b"vrt2")];
+    for record in features.feature_records() {
+        if !tags.contains(&record.feature_tag()) {
+            continue;
+        }
+        let Ok(feature) = record.feature(features.offset_data()) else {
+            continue;
+        };
+        for index in feature.lookup_list_indices() {
+            let Ok(SubstitutionLookup::Single(lookup)) =
+                lookups.lookups().get(usize::from(index.get()))
+            else {
+                continue;
+            };
+            for subtable in lookup.subtables().iter().flatten() {
+                match subtable {
+                    SingleSubst::Format1(table) => {
+                        let Ok(coverage) = table.coverage() else {
+                            continue;
+                        };
+                        let delta = table.delta_glyph_id();
+                        for glyph in coverage.iter() {
+                            let alternate = glyph.to_u16().wrapping_add_signed(delta);
+                            alternates.insert(glyph.to_u32(), u32::from(alternate));
+                        }
+                    }
+                    SingleSubst::Format2(table) => {
+                        let Ok(coverage) = table.coverage() else {
+                            continue;
+                        };
+                        for (glyph, alternate) in coverage.iter().zip(table.substitute_glyph_ids())
+                        {
+                            alternates.insert(glyph.to_u32(), alternate.get().to_u32());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    alternates
+}
@@ -11,15 +11,49 @@ use crate::ShaderId;
 #[cfg(feature = "wgpu")]
 use crate::{
     recording::{BindType, ImageFormat},
-    wgpu_engine::WgpuEngine,
-    Error, RendererOptions,
+    wgpu_engine::{CpuShaderType, WgpuEngine},
+    AaSupport, Error, PipelineStage, RendererOptions,
 };
+#[cfg(feature = "wgpu")]
+use catalina_shaders::CustomPaintSource;
+#[cfg(feature = "wgpu")]
+use std::borrow::Cow;
+
+/// The bindings of the coarse rasterization shaders.
+#[cfg(feature = "wgpu")]
+const COARSE_RESOURCES: [BindType; 9] = [
+    BindType::Uniform,
+    BindType::BufReadOnly,
+    BindType::BufReadOnly,
+    BindType::BufReadOnly,
+    BindType::BufReadOnly,
+    BindType::BufReadOnly,
+    BindType::Buffer,
+    BindType::Buffer,
+    BindType::Buffer,
+];
+
+/// The bindings of the fine rasterization shaders.
+#[cfg(feature = "wgpu")]
+const FINE_RESOURCES: [BindType; 9] = [
+    BindType::Uniform,
+    BindType::BufReadOnly,
+    BindType::BufReadOnly,
+    BindType::BufReadOnly,
+    BindType::Buffer,
+    BindType::Image(ImageFormat::Rgba8),
+    BindType::ImageRead(ImageFormat::Rgba8),
+    BindType::ImageRead(ImageFormat::Rgba8),
+    // Mask LUT buffer, used only when MSAA is enabled.
+    BindType::BufReadOnly,
+];
 
 /// Shaders for the full pipeline
 #[allow(
     missing_docs,
     reason = "Too big to document, so it'll be dedicated to a separate PR in the future."
 )]
+#[derive(Clone)]
 pub struct FullShaders {
     pub pathtag_reduce: ShaderId,
     pub pathtag_reduce2: ShaderId,
@@ -53,15 +87,83 @@ pub(crate) fn full_shaders(
     device: &Device,
     engine: &mut WgpuEngine,
     options: &RendererOptions,
+    custom_paints: &[CustomPaintSource],
+) -> Result<FullShaders, Error> {
+    let mut shaders = stage_shaders(device, engine, options.gpu_stages_from)?;
+    (shaders.fine_area, shaders.fine_msaa8, shaders.fine_msaa16) =
+        fine_shaders(device, engine, options.antialiasing_support, custom_paints)?;
+
+    #[cfg(feature = "debug-logs")]
+    {
+        log::info!(
+            "Compiled fine shader variants: area={}, msaa8={}, msaa16={}",
+            shaders.fine_area.is_some(),
+            shaders.fine_msaa8.is_some(),
+            shaders.fine_msaa16.is_some()
+        );
+        if options.use_cpu {
+            log::info!(
+                "Using CPU implementations of pipeline stages where available{}",
+                match options.gpu_stages_from {
+                    Some(stage) => format!(" (GPU forced from `{}`)", stage.first_shader()),
+                    None => String::new(),
+                }
+            );
+        }
+    }
+
+    // Stages which allocate with atomics, such as flatten and binning, write their outputs in
+    // a nondeterministic order, so can't be compared against the CPU element by element.
+    #[cfg(feature = "debug_validation")]
+    {
+        use catalina_shaders::cpu;
+        engine.set_reference_shader(shaders.pathtag_reduce, cpu::pathtag_reduce);
+        engine.set_reference_shader(shaders.pathtag_scan, cpu::pathtag_scan);
+        engine.set_reference_shader(shaders.bbox_clear, cpu::bbox_clear);
+        engine.set_reference_shader(shaders.draw_reduce, cpu::draw_reduce);
+        engine.set_reference_shader(shaders.draw_leaf, cpu::draw_leaf);
+        engine.set_reference_shader(shaders.clip_reduce, cpu::clip_reduce);
+        engine.set_reference_shader(shaders.clip_leaf, cpu::clip_leaf);
+    }
+
+    Ok(shaders)
+}
+
+/// Adds the CPU implementations of every stage before fine rasterization, for
+/// [`RendererOptions::small_scenes`], sharing the fine rasterization shaders of `shaders`.
+///
+/// This is used by renderers which otherwise run every stage on the GPU, for scenes so small
+/// that the fixed cost of dispatching each stage outweighs the work.
+#[cfg(feature = "wgpu")]
+pub(crate) fn small_scene_shaders(
+    device: &Device,
+    engine: &mut WgpuEngine,
+    shaders: &FullShaders,
+) -> Result<FullShaders, Error> {
+    let use_cpu = std::mem::replace(&mut engine.use_cpu, true);
+    let small = stage_shaders(device, engine, None);
+    engine.use_cpu = use_cpu;
+    let mut small = small?;
+    small.fine_area = shaders.fine_area;
+    small.fine_msaa8 = shaders.fine_msaa8;
+    small.fine_msaa16 = shaders.fine_msaa16;
+    Ok(small)
+}
+
+/// Adds the shaders of every stage before fine rasterization, using their CPU implementations
+/// if the engine uses the CPU, up to `gpu_stages_from`.
+///
+/// The fine rasterization shaders of the result are unset.
+#[cfg(feature = "wgpu")]
+fn stage_shaders(
+    device: &Device,
+    engine: &mut WgpuEngine,
+    gpu_stages_from: Option<PipelineStage>,
 ) -> Result<FullShaders, Error> {
-    use crate::wgpu_engine::CpuShaderType;
     use BindType::*;
 
     let mut force_gpu = false;
-    let force_gpu_from: Option<&str> = None;
-    // Uncomment this to force use of GPU shaders from the specified shader and later even
-    // if `engine.use_cpu` is specified.
-    //let force_gpu_from = Some("binning");
+    let force_gpu_from = gpu_stages_from.map(PipelineStage::first_shader);
 
     #[cfg(feature = "hot_reload")]
     let mut shaders = catalina_shaders::compile::ShaderInfo::from_default()?;
@@ -76,7 +178,7 @@ pub(crate) fn full_shaders(
             #[cfg(feature = "hot_reload")]
             let source = shaders
                 .remove(stringify!($name))
-                .expect(stringify!($name))
+                .ok_or(Error::MissingShader(stringify!($name)))?
                 .source
                 .into();
             #[cfg(not(feature = "hot_reload"))]
@@ -194,20 +296,7 @@ pub(crate) fn full_shaders(
         [Uniform, Buffer, BufReadOnly, Buffer],
         CpuShaderType::Present(catalina_shaders::cpu::backdrop)
     );
-    let coarse = add_shader!(
-        coarse,
-        [
-            Uniform,
-            BufReadOnly,
-            BufReadOnly,
-            BufReadOnly,
-            BufReadOnly,
-            BufReadOnly,
-            Buffer,
-            Buffer,
-            Buffer,
-        ]
-    );
+    let coarse = add_shader!(coarse, COARSE_RESOURCES);
     let path_tiling_setup = add_shader!(path_tiling_setup, [Buffer, Buffer, Buffer]);
     let path_tiling = add_shader!(
         path_tiling,
@@ -220,48 +309,6 @@ pub(crate) fn full_shaders(
             Buffer,
         ]
     );
-    let fine_resources = [
-        Uniform,
-        BufReadOnly,
-        BufReadOnly,
-        BufReadOnly,
-        Buffer,
-        Image(ImageFormat::Rgba8),
-        ImageRead(ImageFormat::Rgba8),
-        ImageRead(ImageFormat::Rgba8),
-        // Mask LUT buffer, used only when MSAA is enabled.
-        BufReadOnly,
-    ];
-
-    let aa_support = &options.antialiasing_support;
-    let fine_area = if aa_support.area {
-        Some(add_shader!(
-            fine_area,
-            fine_resources[..fine_resources.len() - 1],
-            CpuShaderType::Missing
-        ))
-    } else {
-        None
-    };
-    let fine_msaa8 = if aa_support.msaa8 {
-        Some(add_shader!(
-            fine_msaa8,
-            fine_resources,
-            CpuShaderType::Missing
-        ))
-    } else {
-        None
-    };
-    let fine_msaa16 = if aa_support.msaa16 {
-        Some(add_shader!(
-            fine_msaa16,
-            fine_resources,
-            CpuShaderType::Missing
-        ))
-    } else {
-        None
-    };
-
     Ok(FullShaders {
         pathtag_reduce,
         pathtag_reduce2,
@@ -282,9 +329,160 @@ pub(crate) fn full_shaders(
         coarse,
         path_tiling_setup,
         path_tiling,
-        fine_area,
-        fine_msaa8,
-        fine_msaa16,
-        pathtag_is_cpu: options.use_cpu,
+        fine_area: None,
+        fine_msaa8: None,
+        fine_msaa16: None,
+        pathtag_is_cpu: engine.use_cpu && gpu_stages_from != Some(PipelineStage::PathtagScan),
     })
 }
+
+/// Returns the source of a shader variant which isn't part of [`FullShaders`], as it's only
+/// compiled when first used.
+#[cfg(feature = "wgpu")]
+macro_rules! variant_source {
+    ($name:ident) => {{
+        #[cfg(feature = "hot_reload")]
+        let source = catalina_shaders::compile::ShaderInfo::from_default()?
+            .remove(stringify!($name))
+            .ok_or(Error::MissingShader(stringify!($name)))?
+            .source
+            .into();
+        #[cfg(not(feature = "hot_reload"))]
+        let source = catalina_shaders::SHADERS.$name.wgsl.code;
+        source
+    }};
+}
+
+/// The variants of fine rasterization, in the order area, MSAA8 and MSAA16.
+#[cfg(feature = "wgpu")]
+type FineShaders = (Option<ShaderId>, Option<ShaderId>, Option<ShaderId>);
+
+/// Adds the enabled variants of fine rasterization, with `custom_paints` linked in.
+#[cfg(feature = "wgpu")]
+pub(crate) fn fine_shaders(
+    device: &Device,
+    engine: &mut WgpuEngine,
+    aa_support: AaSupport,
+    custom_paints: &[CustomPaintSource],
+) -> Result<FineShaders, Error> {
+    let mut add = |label, source: Cow<'static, str>, bindings: &[BindType]| {
+        let source = if custom_paints.is_empty() {
+            source
+        } else {
+            catalina_shaders::link_custom_paints(&source, custom_paints)
+                .expect("fine shaders contain the custom paint placeholder")
+                .into()
+        };
+        engine.add_compute_shader(device, label, source, bindings, CpuShaderType::Missing)
+    };
+    let fine_area = if aa_support.area {
+        Some(add(
+            "catalina.fine_area",
+            variant_source!(fine_area),
+            &FINE_RESOURCES[..FINE_RESOURCES.len() - 1],
+        ))
+    } else {
+        None
+    };
+    let fine_msaa8 = if aa_support.msaa8 {
+        Some(add(
+            "catalina.fine_msaa8",
+            variant_source!(fine_msaa8),
+            &FINE_RESOURCES,
+        ))
+    } else {
+        None
+    };
+    let fine_msaa16 = if aa_support.msaa16 {
+        Some(add(
+            "catalina.fine_msaa16",
+            variant_source!(fine_msaa16),
+            &FINE_RESOURCES,
+        ))
+    } else {
+        None
+    };
+    Ok((fine_area, fine_msaa8, fine_msaa16))
+}
+
+/// Adds the variant of fine rasterization which only renders coverage, with the same bindings
+/// as `fine_area`.
+#[cfg(feature = "wgpu")]
+pub(crate) fn fine_coverage(device: &Device, engine: &mut WgpuEngine) -> Result<ShaderId, Error> {
+    Ok(engine.add_compute_shader(
+        device,
+        "catalina.fine_coverage",
+        variant_source!(fine_coverage),
+        &FINE_RESOURCES[..FINE_RESOURCES.len() - 1],
+        CpuShaderType::Missing,
+    ))
+}
+
+/// Adds the variant of fine rasterization which combines draws like overlapping inks, with the
+/// same bindings as `fine_area`.
+#[cfg(feature = "wgpu")]
+pub(crate) fn fine_overprint(device: &Device, engine: &mut WgpuEngine) -> Result<ShaderId, Error> {
+    Ok(engine.add_compute_shader(
+        device,
+        "catalina.fine_overprint",
+        variant_source!(fine_overprint),
+        &FINE_RESOURCES[..FINE_RESOURCES.len() - 1],
+        CpuShaderType::Missing,
+    ))
+}
+
+/// Adds the variants of coarse and fine rasterization which output the winding numbers of a
+/// single path, returned in that order.
+///
+/// The fine variant writes to an `R32Float` image instead of an `Rgba8` one.
+#[cfg(feature = "wgpu")]
+pub(crate) fn winding(
+    device: &Device,
+    engine: &mut WgpuEngine,
+) -> Result<(ShaderId, ShaderId), Error> {
+    let coarse = engine.add_compute_shader(
+        device,
+        "catalina.coarse_winding",
+        variant_source!(coarse_winding),
+        &COARSE_RESOURCES,
+        CpuShaderType::Missing,
+    );
+    let mut fine_resources = FINE_RESOURCES;
+    fine_resources[5] = BindType::Image(ImageFormat::R32Float);
+    let fine = engine.add_compute_shader(
+        device,
+        "catalina.fine_winding",
+        variant_source!(fine_winding),
+        &fine_resources[..fine_resources.len() - 1],
+        CpuShaderType::Missing,
+    );
+    Ok((coarse, fine))
+}
+
+/// Adds the variants of coarse and fine rasterization which measure the cost of each draw
+/// object, returned in that order.
+///
+/// The fine variant has the bindings of `fine_area`, followed by the buffer of costs.
+#[cfg(feature = "draw-costs")]
+pub(crate) fn draw_costs(
+    device: &Device,
+    engine: &mut WgpuEngine,
+) -> Result<(ShaderId, ShaderId), Error> {
+    let coarse = engine.add_compute_shader(
+        device,
+        "catalina.coarse_cost",
+        variant_source!(coarse_cost),
+        &COARSE_RESOURCES,
+        CpuShaderType::Missing,
+    );
+    let mut fine_resources = FINE_RESOURCES;
+    fine_resources[8] = BindType::Buffer;
+    let fine = engine.add_compute_shader(
+        device,
+        "catalina.fine_cost",
+        variant_source!(fine_cost),
+        &fine_resources,
+        CpuShaderType::Missing,
+    );
+    Ok((coarse, fine))
+}
@@ -0,0 +1,183 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Snapping of lines, rectangles and images to device pixels, so that they are drawn crisply.
+
+use peniko::kurbo::{Affine, Line, Point, Rect};
+
+/// A stroke width adjusted by [`PixelSnap`] to a whole number of device pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnappedStroke {
+    /// The width of the stroke, in user space.
+    pub width: f64,
+    /// The opacity to multiply the color of the stroke by.
+    ///
+    /// Strokes thinner than a device pixel are widened to one pixel, so this is less than 1
+    /// for them to keep their weight, rather than their being drawn as a blurry partially
+    /// covered line.
+    pub alpha: f32,
+}
+
+/// Adjusts the geometry of user interface elements, such as borders and hairlines, so that
+/// their edges land on device pixel boundaries under a transform.
+///
+/// Antialiasing spreads an edge which lies within a pixel over that pixel, so a 1 pixel
+/// border centered on a pixel boundary covers two pixels at half opacity, and looks like a
+/// blurry 2 pixel border. Snapping moves the edges of fills to pixel boundaries, and the
+/// centers of strokes with an odd width in device pixels to the middle of pixels.
+///
+/// Only transforms which keep lines horizontal and vertical, made of scales and
+/// translations, can be snapped. Other geometry, and geometry under any other transform, is
+/// returned unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelSnap {
+    transform: Affine,
+}
+
+impl PixelSnap {
+    /// Creates a snapper for geometry which will be drawn with `transform`, including any
+    /// scale factor of the display.
+    pub fn new(transform: Affine) -> Self {
+        Self { transform }
+    }
+
+    /// Returns whether geometry drawn with the transform can be snapped.
+    pub fn is_axis_aligned(&self) -> bool {
+        let [a, b, c, d, _, _] = self.transform.as_coeffs();
+        b == 0.0 && c == 0.0 && a != 0.0 && d != 0.0
+    }
+
+    /// Snaps a point to the nearest corner of a device pixel.
+    pub fn point(&self, point: Point) -> Point {
+        if !self.is_axis_aligned() {
+            return point;
+        }
+        Point::new(self.snap_x(point.x, 0.0), self.snap_y(point.y, 0.0))
+    }
+
+    /// Snaps the edges of a rectangle which will be filled to the nearest device pixel
+    /// boundaries.
+    ///
+    /// A rectangle narrower than a pixel may snap to an empty rectangle.
+    pub fn rect(&self, rect: Rect) -> Rect {
+        if !self.is_axis_aligned() {
+            return rect;
+        }
+        Rect::new(
+            self.snap_x(rect.x0, 0.0),
+            self.snap_y(rect.y0, 0.0),
+            self.snap_x(rect.x1, 0.0),
+            self.snap_y(rect.y1, 0.0),
+        )
+    }
+
+    /// Rounds a stroke width to a whole number of device pixels, of at least one.
+    pub fn stroke(&self, width: f64) -> SnappedStroke {
+        let unchanged = SnappedStroke { width, alpha: 1.0 };
+        if !self.is_axis_aligned() {
+            return unchanged;
+        }
+        let scale = self.transform.determinant().abs().sqrt();
+        let device_width = width * scale;
+        if !device_width.is_finite() || device_width <= 0.0 {
+            return unchanged;
+        }
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "The opacity is below 1, so representable as f32."
+        )]
+        let alpha = device_width.min(1.0) as f32;
+        SnappedStroke {
+            width: device_width.round().max(1.0) / scale,
+            alpha,
+        }
+    }
+
+    /// Snaps the border of a rectangle which will be stroked with `width`, so that both
+    /// edges of the stroke land on device pixel boundaries.
+    ///
+    /// The stroke should be drawn with the returned rectangle and [`SnappedStroke`].
+    pub fn stroke_rect(&self, rect: Rect, width: f64) -> (Rect, SnappedStroke) {
+        let stroke = self.stroke(width);
+        if !self.is_axis_aligned() {
+            return (rect, stroke);
+        }
+        let offset = self.stroke_offset(stroke);
+        let snapped = Rect::new(
+            self.snap_x(rect.x0, offset),
+            self.snap_y(rect.y0, offset),
+            self.snap_x(rect.x1, offset),
+            self.snap_y(rect.y1, offset),
+        );
+        (snapped, stroke)
+    }
+
+    /// Snaps a horizontal or vertical line which will be stroked with `width`, so that the
+    /// edges of the stroke land on device pixel boundaries, and its ends on the nearest
+    /// boundaries for butt caps.
+    ///
+    /// Diagonal lines are returned unchanged, with only their width snapped.
+    pub fn line(&self, line: Line, width: f64) -> (Line, SnappedStroke) {
+        let stroke = self.stroke(width);
+        if !self.is_axis_aligned() {
+            return (line, stroke);
+        }
+        let offset = self.stroke_offset(stroke);
+        let Line { p0, p1 } = line;
+        let snapped = if p0.y == p1.y {
+            let y = self.snap_y(p0.y, offset);
+            Line::new((self.snap_x(p0.x, 0.0), y), (self.snap_x(p1.x, 0.0), y))
+        } else if p0.x == p1.x {
+            let x = self.snap_x(p0.x, offset);
+            Line::new((x, self.snap_y(p0.y, 0.0)), (x, self.snap_y(p1.y, 0.0)))
+        } else {
+            line
+        };
+        (snapped, stroke)
+    }
+
+    /// Snaps the transform of an image, or of an image brush, so that each of its texels
+    /// covers exactly one device pixel.
+    ///
+    /// This applies when the image is drawn at its natural size in device pixels, possibly
+    /// flipped, which is the case for pixel art and the tiles of tile maps. The image is
+    /// moved to the nearest whole device pixel, so that it's sampled at the centers of its
+    /// texels, without the blurring and the seams between repeated tiles from sampling
+    /// between them. Images drawn with any other transform are returned unchanged.
+    pub fn image(&self, transform: Affine) -> Affine {
+        const TOLERANCE: f64 = 1e-6;
+        let [a, b, c, d, e, f] = (self.transform * transform).as_coeffs();
+        let is_unit = |scale: f64| (scale.abs() - 1.0).abs() < TOLERANCE;
+        if !self.is_axis_aligned()
+            || b.abs() >= TOLERANCE
+            || c.abs() >= TOLERANCE
+            || !is_unit(a)
+            || !is_unit(d)
+        {
+            return transform;
+        }
+        let device = Affine::new([a.signum(), 0.0, 0.0, d.signum(), e.round(), f.round()]);
+        self.transform.inverse() * device
+    }
+
+    /// Returns the distance in device pixels from a pixel boundary to the center of a stroke
+    /// whose edges are on pixel boundaries, which is half a pixel for odd widths.
+    fn stroke_offset(&self, stroke: SnappedStroke) -> f64 {
+        let scale = self.transform.determinant().abs().sqrt();
+        ((stroke.width * scale).round() * 0.5).fract()
+    }
+
+    /// Snaps a user space x coordinate to `offset` pixels past the nearest device pixel
+    /// boundary.
+    fn snap_x(&self, x: f64, offset: f64) -> f64 {
+        let [a, _, _, _, e, _] = self.transform.as_coeffs();
+        ((a * x + e - offset).round() + offset - e) / a
+    }
+
+    /// Snaps a user space y coordinate to `offset` pixels past the nearest device pixel
+    /// boundary.
+    fn snap_y(&self, y: f64, offset: f64) -> f64 {
+        let [_, _, _, d, _, f] = self.transform.as_coeffs();
+        ((d * y + f - offset).round() + offset - f) / d
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Statistics of rendered frames, such as for auto-exposure or choosing contrasting colors.
+
+use std::borrow::Cow;
+
+use bytemuck::{Pod, Zeroable};
+use peniko::color::{AlphaColor, LinearSrgb};
+use peniko::Color;
+use wgpu::Device;
+
+use crate::low_level::{
+    BindType, BufferProxy, ImageFormat, ImageProxy, Recording, ResourceProxy, ShaderId,
+};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+
+/// The number of bins in [`FrameStatistics::histogram`].
+pub const HISTOGRAM_BINS: usize = 256;
+
+/// The base 2 logarithm of the luminance at the start of the first bin of
+/// [`FrameStatistics::histogram`].
+pub const MIN_LOG2_LUMINANCE: f32 = -12.0;
+
+/// The base 2 logarithm of the luminance at the end of the last bin of
+/// [`FrameStatistics::histogram`].
+///
+/// This is above the luminance of white, so that the histogram covers the brighter colors of
+/// extended range textures.
+pub const MAX_LOG2_LUMINANCE: f32 = 4.0;
+
+/// The size of each side of the workgroups of the statistics shader.
+const WORKGROUP_SIZE: u32 = 16;
+
+/// Adds each pixel to the luminance histogram, and writes the sum of the colors in each
+/// workgroup to `sums`.
+const STATISTICS_SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        // Whether the texture is read as linear rather than sRGB encoded colors.
+        linear: u32,
+        _padding: u32,
+    }
+
+    const BINS: f32 = 256.0;
+    const MIN_LOG2: f32 = -12.0;
+    const MAX_LOG2: f32 = 4.0;
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var frame: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var<storage, read_write> histogram: array<atomic<u32>>;
+
+    @group(0) @binding(3)
+    var<storage, read_write> sums: array<vec4<f32>>;
+
+    var<workgroup> partial: array<vec4<f32>, 256>;
+
+    fn srgb_to_linear(rgb: vec3<f32>) -> vec3<f32> {
+        return select(
+            pow((rgb + 0.055) / 1.055, vec3(2.4)),
+            rgb / 12.92,
+            rgb <= vec3(0.04045)
+        );
+    }
+
+    @compute @workgroup_size(16, 16)
+    fn main(
+        @builtin(global_invocation_id) id: vec3<u32>,
+        @builtin(local_invocation_index) local_ix: u32,
+        @builtin(workgroup_id) wg_id: vec3<u32>,
+        @builtin(num_workgroups) n_wg: vec3<u32>,
+    ) {
+        var color = vec4(0.0);
+        if id.x < params.width && id.y < params.height {
+            let texel = textureLoad(frame, vec2<i32>(id.xy), 0);
+            var rgb = texel.rgb;
+            if params.linear == 0u {
+                rgb = srgb_to_linear(rgb);
+            }
+            // Premultiplied, so that transparent pixels count as black.
+            color = vec4(rgb * texel.a, texel.a);
+            let luminance = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+            var bin = 0u;
+            if luminance > 0.0 {
+                let position = (log2(luminance) - MIN_LOG2) / (MAX_LOG2 - MIN_LOG2) * BINS;
+                bin = u32(clamp(position, 0.0, BINS - 1.0));
+            }
+            atomicAdd(&histogram[bin], 1u);
+        }
+        partial[local_ix] = color;
+        for (var stride = 128u; stride > 0u; stride >>= 1u) {
+            workgroupBarrier();
+            if local_ix < stride {
+                partial[local_ix] += partial[local_ix + stride];
+            }
+        }
+        if local_ix == 0u {
+            sums[wg_id.y * n_wg.x + wg_id.x] = partial[0];
+        }
+    }
+"#;
+
+/// Statistics of the colors of a rendered frame, from [`Renderer::frame_statistics`].
+///
+/// Colors are measured in linear light, with each pixel composited over black, so
+/// transparent pixels count as black.
+///
+/// [`Renderer::frame_statistics`]: crate::Renderer::frame_statistics
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameStatistics {
+    /// The number of pixels in each range of luminance.
+    ///
+    /// The bins are evenly spaced in the logarithm of the luminance, from
+    /// [`MIN_LOG2_LUMINANCE`] to [`MAX_LOG2_LUMINANCE`]. Pixels outside that range are
+    /// counted in the first or last bin, so black pixels are in the first bin.
+    pub histogram: [u32; HISTOGRAM_BINS],
+    /// The average color of the frame.
+    pub average: Color,
+    /// The average luminance of the frame, from 0 for black to 1 for white.
+    pub average_luminance: f32,
+}
+
+impl FrameStatistics {
+    /// Returns the luminance at the middle of `bin` of the [`histogram`](Self::histogram).
+    pub fn bin_luminance(bin: usize) -> f32 {
+        let step = (MAX_LOG2_LUMINANCE - MIN_LOG2_LUMINANCE) / HISTOGRAM_BINS as f32;
+        (MIN_LOG2_LUMINANCE + (bin as f32 + 0.5) * step).exp2()
+    }
+
+    /// Returns the luminance below which `fraction` of the pixels are, to the precision of
+    /// the histogram bins.
+    ///
+    /// A fraction of 0.5 gives the median luminance. Auto-exposure often uses a high
+    /// percentile instead of the maximum, so that a few bright pixels don't darken the frame.
+    pub fn luminance_percentile(&self, fraction: f32) -> f32 {
+        let total: u64 = self.histogram.iter().map(|&count| u64::from(count)).sum();
+        let target = f64::from(fraction.clamp(0.0, 1.0)) * total as f64;
+        let mut count = 0;
+        for (bin, &pixels) in self.histogram.iter().enumerate() {
+            count += u64::from(pixels);
+            if count as f64 >= target && count > 0 {
+                return Self::bin_luminance(bin);
+            }
+        }
+        0.0
+    }
+
+    fn new(histogram: &[u32], sums: &[[f32; 4]], pixels: u64) -> Self {
+        let mut sum = [0.0_f64; 4];
+        for partial in sums {
+            for (total, channel) in sum.iter_mut().zip(partial) {
+                *total += f64::from(*channel);
+            }
+        }
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "The average of colors is within the range of their components."
+        )]
+        let [r, g, b, alpha] = sum.map(|total| (total / pixels.max(1) as f64) as f32);
+        let average_luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        // The sums are premultiplied.
+        let average = if alpha > 0.0 {
+            AlphaColor::<LinearSrgb>::new([r / alpha, g / alpha, b / alpha, alpha]).convert()
+        } else {
+            peniko::color::palette::css::TRANSPARENT
+        };
+        let mut bins = [0; HISTOGRAM_BINS];
+        bins.copy_from_slice(&histogram[..HISTOGRAM_BINS]);
+        Self {
+            histogram: bins,
+            average,
+            average_luminance,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct StatisticsParams {
+    width: u32,
+    height: u32,
+    linear: u32,
+    _padding: u32,
+}
+
+/// The buffers written by a statistics pass, which are downloaded when it has run.
+pub(crate) struct StatisticsBuffers {
+    pub(crate) histogram: BufferProxy,
+    pub(crate) sums: BufferProxy,
+    workgroups: usize,
+    pixels: u64,
+}
+
+impl StatisticsBuffers {
+    /// Reads the statistics from the mapped contents of the buffers.
+    pub(crate) fn read(&self, histogram: &[u8], sums: &[u8]) -> FrameStatistics {
+        FrameStatistics::new(
+            bytemuck::cast_slice(histogram),
+            &bytemuck::cast_slice(sums)[..self.workgroups],
+            self.pixels,
+        )
+    }
+}
+
+/// Creates the shader used by [`Renderer::frame_statistics`](crate::Renderer::frame_statistics).
+pub(crate) fn shader(device: &Device, engine: &mut WgpuEngine) -> ShaderId {
+    engine.add_compute_shader(
+        device,
+        "catalina.frame_statistics",
+        Cow::Borrowed(STATISTICS_SHADER),
+        &[
+            BindType::Uniform,
+            BindType::ImageRead(ImageFormat::Rgba8),
+            BindType::Buffer,
+            BindType::Buffer,
+        ],
+        CpuShaderType::Missing,
+    )
+}
+
+/// Records a pass which measures the statistics of `frame`, whose colors are sRGB encoded
+/// unless `linear` is set, and downloads the results.
+pub(crate) fn record(
+    recording: &mut Recording,
+    shader: ShaderId,
+    frame: ImageProxy,
+    linear: bool,
+) -> StatisticsBuffers {
+    let params = StatisticsParams {
+        width: frame.width,
+        height: frame.height,
+        linear: linear.into(),
+        _padding: 0,
+    };
+    let config = ResourceProxy::Buffer(recording.upload_uniform(
+        "catalina.frame_statistics_params",
+        bytemuck::bytes_of(&params),
+    ));
+    let dispatch = (
+        frame.width.div_ceil(WORKGROUP_SIZE),
+        frame.height.div_ceil(WORKGROUP_SIZE),
+        1,
+    );
+    let workgroups = (dispatch.0 * dispatch.1) as usize;
+    let histogram = recording.upload(
+        "catalina.frame_statistics_histogram",
+        vec![0; HISTOGRAM_BINS * 4],
+    );
+    let sums = recording.upload("catalina.frame_statistics_sums", vec![0; workgroups * 16]);
+    recording.dispatch(
+        shader,
+        dispatch,
+        [
+            config,
+            ResourceProxy::Image(frame),
+            ResourceProxy::Buffer(histogram),
+            ResourceProxy::Buffer(sums),
+        ],
+    );
+    recording.download(histogram);
+    recording.download(sums);
+    recording.free_resource(config);
+    recording.free_buffer(histogram);
+    recording.free_buffer(sums);
+    StatisticsBuffers {
+        histogram,
+        sums,
+        workgroups,
+        pixels: u64::from(frame.width) * u64::from(frame.height),
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Downsampling of supersampled renders.
+
+use std::borrow::Cow;
+
+use wgpu::Device;
+
+use crate::low_level::{BindType, ImageFormat, ShaderId};
+use crate::wgpu_engine::{CpuShaderType, WgpuEngine};
+use crate::{RenderParams, Result};
+
+/// The size of each side of the workgroups of the downsampling shader.
+pub(crate) const WORKGROUP_SIZE: u32 = 16;
+
+/// Averages each block of `factor` by `factor` pixels, which is the exact coverage of the
+/// output pixel they subdivide.
+const SHADER: &str = r#"
+    struct Params {
+        width: u32,
+        height: u32,
+        factor: u32,
+        _padding: u32,
+    }
+
+    @group(0) @binding(0)
+    var<uniform> params: Params;
+
+    @group(0) @binding(1)
+    var source: texture_2d<f32>;
+
+    @group(0) @binding(2)
+    var output: texture_storage_2d<rgba8unorm, write>;
+
+    @compute @workgroup_size(16, 16)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        if id.x >= params.width || id.y >= params.height {
+            return;
+        }
+        // Sum premultiplied colors, so that transparent samples don't bleed color.
+        var sum = vec4(0.0);
+        for (var y = 0u; y < params.factor; y++) {
+            for (var x = 0u; x < params.factor; x++) {
+                let coords = id.xy * params.factor + vec2(x, y);
+                let color = textureLoad(source, vec2<i32>(coords), 0);
+                sum += vec4(color.rgb * color.a, color.a);
+            }
+        }
+        let average = sum / f32(params.factor * params.factor);
+        var rgb = vec3(0.0);
+        if average.a > 0.0 {
+            rgb = average.rgb / average.a;
+        }
+        textureStore(output, vec2<i32>(id.xy), vec4(rgb, average.a));
+    }
+"#;
+
+/// Adds the downsampling shader to `engine`.
+pub(crate) fn add_shader(device: &Device, engine: &mut WgpuEngine) -> ShaderId {
+    engine.add_compute_shader(
+        device,
+        "catalina.downsample",
+        Cow::Borrowed(SHADER),
+        &[
+            BindType::Uniform,
+            BindType::ImageRead(ImageFormat::Rgba8),
+            BindType::Image(ImageFormat::Rgba8),
+        ],
+        CpuShaderType::Missing,
+    )
+}
+
+/// Returns the parameters of the intermediate render for a supersampling `factor`, or an error
+/// if it would be too large to render.
+pub(crate) fn large_params(params: &RenderParams, factor: u32) -> Result<RenderParams> {
+    let large_params = RenderParams {
+        width: params.width.saturating_mul(factor),
+        height: params.height.saturating_mul(factor),
+        ..*params
+    };
+    large_params.check_target_size()?;
+    Ok(large_params)
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::color::palette;
+
+    use super::large_params;
+    use crate::{AaConfig, Error, RenderParams, MAX_TARGET_BINS};
+
+    fn params(width: u32, height: u32) -> RenderParams {
+        RenderParams {
+            base_color: palette::css::WHITE,
+            width,
+            height,
+            antialiasing_method: AaConfig::Area,
+            global_alpha: 1.0,
+            dash_phase: 0.0,
+            glow_amount: 1.0,
+            dirty_rects: None,
+        }
+    }
+
+    #[test]
+    fn intermediate_renders_are_scaled_and_limited() {
+        let large = large_params(&params(100, 30), 4).unwrap();
+        assert_eq!((large.width, large.height), (400, 120));
+        assert_eq!(large.global_alpha, 1.0);
+        // The intermediate render has to fit in a single pass, such as a square of bins.
+        let side = 4096;
+        assert_eq!((side / 256) * (side / 256), MAX_TARGET_BINS);
+        assert!(large_params(&params(side / 4, side / 4), 4).is_ok());
+        assert!(matches!(
+            large_params(&params(side / 4 + 1, side / 4), 4),
+            Err(Error::TargetTooLarge(..))
+        ));
+        assert!(matches!(
+            large_params(&params(u32::MAX, 1), 2),
+            Err(Error::TargetTooLarge(u32::MAX, 2))
+        ));
+    }
+}
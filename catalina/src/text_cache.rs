@@ -0,0 +1,163 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Caching of laid out and encoded text runs, so that unchanged labels are cheap to redraw.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use peniko::kurbo::Affine;
+
+use crate::{FragmentResolver, Scene, SceneFragment};
+
+/// The key of a text run in a [`TextRunCache`], made of everything which affects how it is
+/// laid out and drawn.
+///
+/// `S` is the style of the text as known to the application, such as its font, size and
+/// color, and must identify every property the layout depends on.
+#[derive(Clone, Debug)]
+pub struct TextRunKey<S> {
+    /// The text of the run.
+    pub text: String,
+    /// The style of the text.
+    pub style: S,
+    /// The width the text was wrapped or truncated to, if any.
+    pub max_width: Option<f64>,
+}
+
+impl<S> TextRunKey<S> {
+    /// Creates a key for `text` in `style`, laid out to fit in `max_width`.
+    pub fn new(text: impl Into<String>, style: S, max_width: Option<f64>) -> Self {
+        Self {
+            text: text.into(),
+            style,
+            max_width,
+        }
+    }
+}
+
+// Widths are compared by their bits, so that keys can be hashed.
+impl<S: PartialEq> PartialEq for TextRunKey<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+            && self.style == other.style
+            && self.max_width.map(f64::to_bits) == other.max_width.map(f64::to_bits)
+    }
+}
+
+impl<S: Eq> Eq for TextRunKey<S> {}
+
+impl<S: Hash> Hash for TextRunKey<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.text.hash(state);
+        self.style.hash(state);
+        self.max_width.map(f64::to_bits).hash(state);
+    }
+}
+
+struct Entry {
+    fragment: SceneFragment,
+    /// Whether the run has been drawn since the last [`TextRunCache::evict_unused`].
+    used: bool,
+}
+
+/// A cache of text runs which have been laid out and encoded, so that labels which don't
+/// change between frames skip shaping and glyph encoding entirely.
+///
+/// Each run is drawn into a scene of its own once by the application's layout code, and kept
+/// as a [`SceneFragment`] with its glyphs already resolved to outlines. Drawing the run again
+/// appends the fragment, which only copies its encoded data.
+///
+/// Runs which are no longer drawn are removed by [`evict_unused`](Self::evict_unused), which
+/// is typically called once per frame.
+///
+/// ```no_run
+/// # use catalina::kurbo::Affine;
+/// # use catalina::{Scene, TextRunCache, TextRunKey};
+/// # fn layout_label(text: &str, size: u32, scene: &mut Scene) {}
+/// # fn example(cache: &mut TextRunCache<TextRunKey<u32>>, scene: &mut Scene) {
+/// let key = TextRunKey::new("Save", 14, None);
+/// cache.append(scene, &key, Affine::translate((20.0, 40.0)), |run| {
+///     layout_label(&key.text, key.style, run);
+/// });
+/// # }
+/// ```
+pub struct TextRunCache<K> {
+    entries: HashMap<K, Entry>,
+    resolver: FragmentResolver,
+}
+
+impl<K> Default for TextRunCache<K> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            resolver: FragmentResolver::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> TextRunCache<K> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the text run for `key` to `scene` with `transform`.
+    ///
+    /// If the run isn't cached, `layout` is called to lay it out and draw it into an empty
+    /// scene, in its own coordinate space.
+    pub fn append(
+        &mut self,
+        scene: &mut Scene,
+        key: &K,
+        transform: Affine,
+        layout: impl FnOnce(&mut Scene),
+    ) {
+        scene.append_fragment(self.get_or_insert_with(key, layout), Some(transform));
+    }
+
+    /// Returns the fragment for the text run of `key`, calling `layout` to draw it into an
+    /// empty scene if it isn't cached.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: &K,
+        layout: impl FnOnce(&mut Scene),
+    ) -> &SceneFragment {
+        if !self.entries.contains_key(key) {
+            let mut run = Scene::new();
+            layout(&mut run);
+            let fragment = self.resolver.resolve(&run);
+            self.entries.insert(
+                key.clone(),
+                Entry {
+                    fragment,
+                    used: false,
+                },
+            );
+        }
+        let entry = self.entries.get_mut(key).expect("the run was just cached");
+        entry.used = true;
+        &entry.fragment
+    }
+
+    /// Removes the text runs which haven't been drawn since the last call.
+    pub fn evict_unused(&mut self) {
+        self.entries
+            .retain(|_, entry| std::mem::take(&mut entry.used));
+    }
+
+    /// Removes every cached text run.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the number of cached text runs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no text runs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Incremental rendering of downsampled overviews, such as minimaps and document thumbnails.
+
+use peniko::kurbo::{Affine, Rect};
+use peniko::Color;
+use wgpu::{Device, Queue, Texture, TextureView};
+
+use crate::{AaConfig, RenderParams, RenderedTile, Renderer, Result, Scene};
+
+/// The default size of the tiles rendered by each [`ThumbnailJob::step`].
+const DEFAULT_TILE_SIZE: u32 = 128;
+
+/// A downsampled overview of some content, rendered a tile at a time.
+///
+/// Each call to [`step`](Self::step) renders one small tile, so a minimap or thumbnail can be
+/// produced in the gaps between frames of the main view without delaying them. The finished
+/// image is available from [`texture`](Self::texture) or [`read_pixels`](Self::read_pixels).
+///
+/// Layers with filters are rendered whole in every step, so that blurs and shadows are
+/// continuous across tiles, which makes steps whose content has large filtered layers slower.
+pub struct ThumbnailJob {
+    scene: Scene,
+    tile_scene: Scene,
+    params: RenderParams,
+    texture: Texture,
+    tile_texture: Texture,
+    tile_view: TextureView,
+    tile_size: u32,
+    /// The top left corner of the next tile, or `None` once finished.
+    next: Option<(u32, u32)>,
+}
+
+impl ThumbnailJob {
+    /// Prepares a thumbnail of `scene`, covering `bounds` in the scene's coordinates, which fits
+    /// within `max_size` pixels in each direction while keeping its aspect ratio.
+    pub fn new(
+        device: &Device,
+        scene: &Scene,
+        bounds: Rect,
+        max_size: u32,
+        base_color: Color,
+    ) -> Self {
+        Self::from_fragments(
+            device,
+            [(scene, Affine::IDENTITY)],
+            bounds,
+            max_size,
+            base_color,
+        )
+    }
+
+    /// Prepares a thumbnail of several scenes, each placed with its transform, such as the
+    /// retained fragments of a document or a [`World`](crate::world::World).
+    ///
+    /// `bounds` is in the shared coordinates the transforms map into.
+    pub fn from_fragments<'a>(
+        device: &Device,
+        fragments: impl IntoIterator<Item = (&'a Scene, Affine)>,
+        bounds: Rect,
+        max_size: u32,
+        base_color: Color,
+    ) -> Self {
+        let (width, height, fit) = fit(bounds, max_size);
+        let mut scene = Scene::new();
+        for (fragment, transform) in fragments {
+            scene.flatten_shader = fragment.flatten_shader.clone();
+            scene.append(fragment, Some(fit * transform));
+        }
+        let tile_size = DEFAULT_TILE_SIZE;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("catalina.thumbnail"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            usage: wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            view_formats: &[],
+        });
+        let (tile_texture, tile_view) = tile_target(device, tile_size);
+        let mut tile_scene = Scene::new();
+        tile_scene.flatten_shader = scene.flatten_shader.clone();
+        Self {
+            scene,
+            tile_scene,
+            params: RenderParams {
+                base_color,
+                width,
+                height,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+            texture,
+            tile_texture,
+            tile_view,
+            tile_size,
+            next: Some((0, 0)),
+        }
+    }
+
+    /// Sets the size of the tiles rendered by each step, which trades the number of steps
+    /// against the time each one takes. The default is 128 pixels.
+    pub fn with_tile_size(mut self, device: &Device, tile_size: u32) -> Self {
+        self.tile_size = tile_size.max(1);
+        (self.tile_texture, self.tile_view) = tile_target(device, self.tile_size);
+        self
+    }
+
+    /// The width of the thumbnail, in pixels.
+    pub fn width(&self) -> u32 {
+        self.params.width
+    }
+
+    /// The height of the thumbnail, in pixels.
+    pub fn height(&self) -> u32 {
+        self.params.height
+    }
+
+    /// Returns `true` once every tile has been rendered.
+    pub fn is_finished(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Renders the next tile, returning whether the thumbnail is now finished.
+    pub fn step(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+    ) -> Result<bool> {
+        let Some((x, y)) = self.next else {
+            return Ok(true);
+        };
+        let width = self.tile_size.min(self.params.width - x);
+        let height = self.tile_size.min(self.params.height - y);
+        renderer.render_tile(
+            device,
+            queue,
+            &self.scene,
+            &mut self.tile_scene,
+            &self.tile_view,
+            &self.params,
+            (x, y, width, height),
+            true,
+        )?;
+        renderer.end_frame(queue);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("catalina.thumbnail_copy"),
+        });
+        encoder.copy_texture_to_texture(
+            self.tile_texture.as_image_copy(),
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit([encoder.finish()]);
+        self.next = if x + width < self.params.width {
+            Some((x + width, y))
+        } else if y + height < self.params.height {
+            Some((0, y + height))
+        } else {
+            None
+        };
+        Ok(self.next.is_none())
+    }
+
+    /// Renders all remaining tiles.
+    pub fn finish(
+        &mut self,
+        renderer: &mut Renderer,
+        device: &Device,
+        queue: &Queue,
+    ) -> Result<()> {
+        while !self.step(renderer, device, queue)? {}
+        Ok(())
+    }
+
+    /// The texture the thumbnail is rendered into, in the
+    /// [`Rgba8Unorm`](wgpu::TextureFormat::Rgba8Unorm) format.
+    ///
+    /// Regions which haven't been rendered yet are transparent.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Reads back the thumbnail as unpadded RGBA8 data, with rows from top to bottom.
+    ///
+    /// This blocks on the GPU, so is not supported on WebAssembly, where
+    /// [`read_pixels_async`](Self::read_pixels_async) should be used instead.
+    pub fn read_pixels(&self, device: &Device, queue: &Queue) -> Result<Vec<u8>> {
+        self.tile().read_pixels(device, queue)
+    }
+
+    /// Reads back the pixels like [`read_pixels`](Self::read_pixels), without blocking.
+    pub async fn read_pixels_async(&self, device: &Device, queue: &Queue) -> Result<Vec<u8>> {
+        self.tile().read_pixels_async(device, queue).await
+    }
+
+    /// The whole output, as a tile.
+    fn tile(&self) -> RenderedTile<'_> {
+        RenderedTile {
+            x: 0,
+            y: 0,
+            width: self.params.width,
+            height: self.params.height,
+            texture: &self.texture,
+        }
+    }
+}
+
+/// Returns the size of a thumbnail of `bounds` which fits within `max_size` pixels, and the
+/// transform from `bounds` to the thumbnail.
+fn fit(bounds: Rect, max_size: u32) -> (u32, u32, Affine) {
+    let max_size = f64::from(max_size.max(1));
+    let scale = max_size / bounds.width().max(bounds.height()).max(f64::EPSILON);
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The size is clamped to `1..=max_size`."
+    )]
+    let size = |extent: f64| (extent * scale).ceil().clamp(1.0, max_size) as u32;
+    let transform = Affine::scale(scale) * Affine::translate(-bounds.origin().to_vec2());
+    (size(bounds.width()), size(bounds.height()), transform)
+}
+
+fn tile_target(device: &Device, tile_size: u32) -> (Texture, TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("catalina.thumbnail_tile"),
+        size: wgpu::Extent3d {
+            width: tile_size,
+            height: tile_size,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (texture, view)
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::kurbo::{Point, Rect};
+
+    use super::fit;
+
+    #[test]
+    fn thumbnails_keep_the_aspect_ratio() {
+        let (width, height, transform) = fit(Rect::new(-100.0, 50.0, 300.0, 250.0), 64);
+        assert_eq!((width, height), (64, 32));
+        assert_eq!(transform * Point::new(-100.0, 50.0), Point::ZERO);
+        assert_eq!(transform * Point::new(300.0, 250.0), Point::new(64.0, 32.0));
+        // Thin content is still at least a pixel across.
+        let (width, height, _) = fit(Rect::new(0.0, 0.0, 1000.0, 1.0), 100);
+        assert_eq!((width, height), (100, 1));
+        // Empty bounds and sizes don't produce empty textures.
+        assert_eq!(fit(Rect::ZERO, 0).0, 1);
+        assert_eq!(fit(Rect::ZERO, 0).1, 1);
+    }
+}
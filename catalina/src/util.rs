@@ -4,13 +4,28 @@
 //! Simple helpers for managing wgpu state and surfaces.
 
 use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
 
 use wgpu::{
-    Adapter, Device, Instance, Limits, MemoryHints, Queue, Surface, SurfaceConfiguration,
-    SurfaceTarget, TextureFormat,
+    Adapter, Device, Features, Instance, Limits, MemoryHints, PowerPreference, Queue, Surface,
+    SurfaceConfiguration, SurfaceTarget, Texture, TextureFormat,
 };
 
-use crate::{Error, Result};
+use crate::{Error, Quirks, RenderParams, Renderer, RendererOptions, Result, Scene};
+
+/// The surface formats which renderers can present to, in the order of preference used by
+/// [`RenderContext::create_surface`].
+///
+/// `Rgba16Float` is treated as extended linear sRGB, which is how HDR displays are usually
+/// driven, so isn't chosen unless requested with [`RenderContext::create_surface_with_formats`].
+pub const SURFACE_FORMATS: [TextureFormat; 5] = [
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Bgra8Unorm,
+    TextureFormat::Rgba8UnormSrgb,
+    TextureFormat::Bgra8UnormSrgb,
+    TextureFormat::Rgba16Float,
+];
 
 /// Simple render context that maintains wgpu state for rendering the pipeline.
 /// TODO: Add better documentation.
@@ -19,6 +34,35 @@ pub struct RenderContext {
     pub instance: Instance,
     /// All of the available devices of that context.
     pub devices: Vec<DeviceHandle>,
+    /// How new devices are chosen and configured.
+    pub device_options: DeviceOptions,
+}
+
+/// Options for the devices which a [`RenderContext`] creates.
+///
+/// An adapter is chosen by `adapter_index` if set, then by the `WGPU_ADAPTER_NAME`
+/// environment variable, then by the `WGPU_POWER_PREF` environment variable or
+/// `power_preference`.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceOptions {
+    /// The kind of adapter to prefer, such as [`PowerPreference::HighPerformance`] for a
+    /// discrete GPU.
+    pub power_preference: PowerPreference,
+    /// The index of the adapter to use in [`RenderContext::enumerate_adapters`].
+    ///
+    /// Devices aren't created if this adapter doesn't exist or can't present to the surface.
+    /// This is ignored on WebAssembly, where adapters can't be enumerated.
+    pub adapter_index: Option<usize>,
+    /// The limits to require of the device, or `None` for the default limits of wgpu.
+    ///
+    /// Large scenes may need bigger buffers than the defaults allow, up to the limits of
+    /// the adapter from [`Adapter::limits`].
+    pub required_limits: Option<Limits>,
+    /// Features to require of the device, in addition to those used by the renderer when
+    /// the adapter supports them.
+    pub required_features: Features,
+    /// Hints for the memory allocation strategy of the device.
+    pub memory_hints: MemoryHints,
 }
 
 /// A handler made to handle wgpu devices.
@@ -39,7 +83,14 @@ impl RenderContext {
     )]
     /// Creates a new [`RenderContext`] with a new wgpu Instance.
     pub fn new() -> Self {
-        let backends = wgpu::Backends::from_env().unwrap_or_default();
+        Self::from_backends(wgpu::Backends::from_env().unwrap_or_default())
+    }
+
+    /// Creates a new [`RenderContext`] with a new wgpu Instance, which creates adapters of
+    /// the given backends.
+    ///
+    /// Unlike [`new`](Self::new), this ignores the `WGPU_BACKEND` environment variable.
+    pub fn from_backends(backends: wgpu::Backends) -> Self {
         let flags = wgpu::InstanceFlags::from_build_config().with_env();
         let backend_options = wgpu::BackendOptions::from_env_or_default();
         let instance = Instance::new(&wgpu::InstanceDescriptor {
@@ -50,10 +101,41 @@ impl RenderContext {
         Self {
             instance,
             devices: Vec::new(),
+            device_options: DeviceOptions::default(),
         }
     }
 
+    /// Builder method for setting the options of the devices which the context creates.
+    #[must_use]
+    pub fn with_device_options(mut self, options: DeviceOptions) -> Self {
+        self.device_options = options;
+        self
+    }
+
+    /// Returns the adapters of every backend of the instance, in the order used by
+    /// [`DeviceOptions::adapter_index`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn enumerate_adapters(&self) -> Vec<Adapter> {
+        self.instance.enumerate_adapters(wgpu::Backends::all())
+    }
+
+    /// Adds a device created outside of the context, such as one shared with another
+    /// library, and returns its handle id.
+    ///
+    /// The device should have been created from `adapter`, with the
+    /// [`Features::CLEAR_TEXTURE`] feature if the adapter supports it.
+    pub fn add_device_handle(&mut self, adapter: Adapter, device: Device, queue: Queue) -> usize {
+        self.devices.push(DeviceHandle {
+            adapter,
+            device,
+            queue,
+        });
+        self.devices.len() - 1
+    }
+
     /// Creates a new surface for the specified window and dimensions.
+    ///
+    /// The surface has the first of the 8-bit formats in [`SURFACE_FORMATS`] which it supports.
     pub async fn create_surface<'w>(
         &mut self,
         window: impl Into<SurfaceTarget<'w>>,
@@ -61,22 +143,58 @@ impl RenderContext {
         height: u32,
         present_mode: wgpu::PresentMode,
     ) -> Result<RenderSurface<'w>> {
-        self.create_render_surface(
-            self.instance.create_surface(window.into())?,
+        self.create_surface_with_formats(window, width, height, present_mode, &SURFACE_FORMATS[..4])
+            .await
+    }
+
+    /// Creates a new surface for the specified window and dimensions, with the first of
+    /// `formats` which it supports, such as [`TextureFormat::Rgba16Float`] for HDR output.
+    ///
+    /// Each format must be one of [`SURFACE_FORMATS`].
+    pub async fn create_surface_with_formats<'w>(
+        &mut self,
+        window: impl Into<SurfaceTarget<'w>>,
+        width: u32,
+        height: u32,
+        present_mode: wgpu::PresentMode,
+        formats: &[TextureFormat],
+    ) -> Result<RenderSurface<'w>> {
+        let surface = self.instance.create_surface(window.into())?;
+        self.create_render_surface_with_formats(surface, width, height, present_mode, formats)
+            .await
+    }
+
+    /// Creates a new render surface for the specified window and dimensions.
+    ///
+    /// The surface has the first of the 8-bit formats in [`SURFACE_FORMATS`] which it supports.
+    pub async fn create_render_surface<'w>(
+        &mut self,
+        surface: Surface<'w>,
+        width: u32,
+        height: u32,
+        present_mode: wgpu::PresentMode,
+    ) -> Result<RenderSurface<'w>> {
+        self.create_render_surface_with_formats(
+            surface,
             width,
             height,
             present_mode,
+            &SURFACE_FORMATS[..4],
         )
         .await
     }
 
-    /// Creates a new render surface for the specified window and dimensions.
-    pub async fn create_render_surface<'w>(
+    /// Creates a new render surface for the specified window and dimensions, with the first of
+    /// `formats` which it supports.
+    ///
+    /// Each format must be one of [`SURFACE_FORMATS`].
+    pub async fn create_render_surface_with_formats<'w>(
         &mut self,
         surface: Surface<'w>,
         width: u32,
         height: u32,
         present_mode: wgpu::PresentMode,
+        formats: &[TextureFormat],
     ) -> Result<RenderSurface<'w>> {
         let dev_id = self
             .device(Some(&surface))
@@ -85,10 +203,11 @@ impl RenderContext {
 
         let device_handle = &self.devices[dev_id];
         let capabilities = surface.get_capabilities(&device_handle.adapter);
-        let format = capabilities
-            .formats
-            .into_iter()
-            .find(|it| matches!(it, TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm))
+        let format = formats
+            .iter()
+            .copied()
+            .filter(|format| SURFACE_FORMATS.contains(format))
+            .find(|format| capabilities.formats.contains(format))
             .ok_or(Error::UnsupportedSurfaceFormat)?;
 
         let config = SurfaceConfiguration {
@@ -152,22 +271,55 @@ impl RenderContext {
 
     /// Creates a compatible device handle id.
     async fn new_device(&mut self, compatible_surface: Option<&Surface<'_>>) -> Option<usize> {
-        let adapter =
-            wgpu::util::initialize_adapter_from_env_or_default(&self.instance, compatible_surface)
-                .await?;
+        let adapter = self.select_adapter(compatible_surface).await?;
+        #[cfg(feature = "debug-logs")]
+        {
+            let info = adapter.get_info();
+            let reason = match (
+                self.device_options.adapter_index,
+                std::env::var("WGPU_ADAPTER_NAME"),
+            ) {
+                (Some(index), _) if cfg!(not(target_arch = "wasm32")) => {
+                    format!("adapter index {index}")
+                }
+                (_, Ok(name)) => format!("matched `WGPU_ADAPTER_NAME={name}`"),
+                _ => "power preference".to_string(),
+            };
+            log::info!(
+                "Selected adapter {:?} ({:?}, {:?} backend, driver {:?} {:?}) using {reason}",
+                info.name,
+                info.device_type,
+                info.backend,
+                info.driver,
+                info.driver_info,
+            );
+        }
         let features = adapter.features();
-        let limits = Limits::default();
-        let maybe_features = wgpu::Features::CLEAR_TEXTURE;
+        let limits = self
+            .device_options
+            .required_limits
+            .clone()
+            .unwrap_or_default();
+        let maybe_features = wgpu::Features::CLEAR_TEXTURE | wgpu::Features::PIPELINE_CACHE;
         #[cfg(feature = "wgpu-profiler")]
         let maybe_features = maybe_features | wgpu_profiler::GpuProfiler::ALL_WGPU_TIMER_FEATURES;
 
+        #[cfg(feature = "debug-logs")]
+        if !features.contains(maybe_features) {
+            log::info!(
+                "Adapter is missing optional features {:?}",
+                maybe_features.difference(features)
+            );
+        }
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
-                    required_features: features & maybe_features,
+                    required_features: (features & maybe_features)
+                        | self.device_options.required_features,
                     required_limits: limits,
-                    memory_hints: MemoryHints::default(),
+                    memory_hints: self.device_options.memory_hints.clone(),
                 },
                 None,
             )
@@ -181,6 +333,29 @@ impl RenderContext {
         self.devices.push(device_handle);
         Some(self.devices.len() - 1)
     }
+
+    /// Chooses the adapter for a new device, as described in [`DeviceOptions`].
+    async fn select_adapter(&self, compatible_surface: Option<&Surface<'_>>) -> Option<Adapter> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(index) = self.device_options.adapter_index {
+            let adapter = self.enumerate_adapters().into_iter().nth(index)?;
+            let compatible = compatible_surface.is_none_or(|s| adapter.is_surface_supported(s));
+            return compatible.then_some(adapter);
+        }
+        if let Some(adapter) =
+            wgpu::util::initialize_adapter_from_env(&self.instance, compatible_surface)
+        {
+            return Some(adapter);
+        }
+        self.instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: PowerPreference::from_env()
+                    .unwrap_or(self.device_options.power_preference),
+                force_fallback_adapter: false,
+                compatible_surface,
+            })
+            .await
+    }
 }
 
 impl DeviceHandle {
@@ -188,6 +363,23 @@ impl DeviceHandle {
     pub fn adapter(&self) -> &Adapter {
         &self.adapter
     }
+
+    /// Returns the workarounds from the built-in quirk table which apply to the adapter.
+    ///
+    /// These are applied by [`create_renderer`](Self::create_renderer).
+    pub fn quirks(&self) -> Quirks {
+        Quirks::for_adapter(&self.adapter.get_info())
+    }
+
+    /// Creates a renderer for the device, with the workarounds for its adapter
+    /// [applied](Quirks::apply) to `options`.
+    ///
+    /// To use `options` unchanged, or to change the workarounds first, pass them to
+    /// [`Renderer::new`] with [`device`](Self::device) instead.
+    pub fn create_renderer(&self, mut options: RendererOptions) -> Result<Renderer> {
+        self.quirks().apply(&mut options);
+        Renderer::new(&self.device, options)
+    }
 }
 
 /// Combination of surface and its configuration.
@@ -203,6 +395,173 @@ pub struct RenderSurface<'s> {
     pub format: TextureFormat,
 }
 
+/// Creates a texture which [`Renderer::render_to_texture`] can render to without a window,
+/// and which can be read back with [`read_texture`].
+pub fn create_target_texture(device: &Device, width: u32, height: u32) -> Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Render target"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::STORAGE_BINDING
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Copies the contents of `texture` to the CPU, blocking until the copy is done.
+///
+/// The pixels are returned row by row without padding, in the layout of the texture format,
+/// which is 4 bytes of RGBA for the textures from [`create_target_texture`]. The texture
+/// must have been created with [`wgpu::TextureUsages::COPY_SRC`].
+///
+/// This uses [`block_on_wgpu`], so can't be used on WASM, where [`read_texture_async`] should
+/// be used instead.
+pub fn read_texture(device: &Device, queue: &Queue, texture: &Texture) -> Result<Vec<u8>> {
+    block_on_wgpu(device, read_texture_async(device, queue, texture))
+}
+
+/// Copies the contents of `texture` to the CPU like [`read_texture`], without blocking.
+///
+/// This waits for the copy with [`wait_on_wgpu`], so works both natively and on the web.
+pub async fn read_texture_async(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+) -> Result<Vec<u8>> {
+    let format = texture.format();
+    let bytes_per_pixel = format
+        .block_copy_size(None)
+        .ok_or(Error::UnsupportedReadbackFormat(format))?;
+    read_texture_region(device, queue, texture, texture.size(), bytes_per_pixel).await
+}
+
+/// Copies the top left `size` pixels of `texture` to the CPU, returning its rows without
+/// padding.
+pub(crate) async fn read_texture_region(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    size: wgpu::Extent3d,
+    bytes_per_pixel: u32,
+) -> Result<Vec<u8>> {
+    let byte_width = size.width * bytes_per_pixel;
+    let padded_byte_width = byte_width.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Texture readback"),
+        size: u64::from(padded_byte_width) * u64::from(size.height),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Copy out texture"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_byte_width),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+    queue.submit([encoder.finish()]);
+    let slice = buffer.slice(..);
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+    wait_on_wgpu(device, receiver.receive())
+        .await
+        .expect("channel was closed")?;
+    let mapped = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity(byte_width as usize * size.height as usize);
+    for row in mapped.chunks_exact(padded_byte_width as usize) {
+        pixels.extend_from_slice(&row[..byte_width as usize]);
+    }
+    drop(mapped);
+    buffer.unmap();
+    Ok(pixels)
+}
+
+/// Renders `scene` offscreen and returns its RGBA pixels, row by row.
+///
+/// This allocates a texture of the size in `params`, renders to it with
+/// [`Renderer::render_to_texture`] and reads it back with [`read_texture`]. To render many
+/// scenes of the same size, create the texture once with [`create_target_texture`] instead.
+pub fn render_to_pixels(
+    renderer: &mut Renderer,
+    device: &Device,
+    queue: &Queue,
+    scene: &Scene,
+    params: &RenderParams,
+) -> Result<Vec<u8>> {
+    let texture = create_target_texture(device, params.width, params.height);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer.render_to_texture(device, queue, scene, &view, params)?;
+    read_texture(device, queue, &texture)
+}
+
+/// Renders `scene` offscreen and returns its RGBA pixels like [`render_to_pixels`], without
+/// blocking.
+///
+/// This reads the pixels back with [`read_texture_async`], so works both natively and on the
+/// web.
+pub async fn render_to_pixels_async(
+    renderer: &mut Renderer,
+    device: &Device,
+    queue: &Queue,
+    scene: &Scene,
+    params: &RenderParams,
+) -> Result<Vec<u8>> {
+    let texture = create_target_texture(device, params.width, params.height);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer.render_to_texture(device, queue, scene, &view, params)?;
+    read_texture_async(device, queue, &texture).await
+}
+
+/// Waits for a future which depends on GPU progress, such as a buffer mapping, without
+/// blocking.
+///
+/// Natively, nothing else polls the device, so while the future is pending a thread waits for
+/// the device's work to finish and then wakes the task. The executor isn't blocked meanwhile.
+/// On the web, the browser makes progress on its own and wakes the task once the work is done.
+/// Unlike [`block_on_wgpu`], this can be awaited on WASM.
+pub async fn wait_on_wgpu<F: Future>(device: &Device, fut: F) -> F::Output {
+    let mut fut = std::pin::pin!(fut);
+    // The waker of the latest poll of the task, which the waiting thread wakes.
+    let waker = Arc::new(Mutex::new(None::<Waker>));
+    let mut waiting: Option<std::thread::JoinHandle<()>> = None;
+    std::future::poll_fn(|context| {
+        if let Poll::Ready(item) = fut.as_mut().poll(context) {
+            return Poll::Ready(item);
+        }
+        if !cfg!(target_arch = "wasm32") {
+            *waker.lock().unwrap() = Some(context.waker().clone());
+            if waiting.as_ref().is_none_or(|thread| thread.is_finished()) {
+                let device = device.clone();
+                let waker = waker.clone();
+                waiting = Some(std::thread::spawn(move || {
+                    device.poll(wgpu::Maintain::Wait);
+                    if let Some(waker) = waker.lock().unwrap().take() {
+                        waker.wake();
+                    }
+                }));
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
 struct NullWake;
 
 impl std::task::Wake for NullWake {
@@ -213,7 +572,7 @@ impl std::task::Wake for NullWake {
 ///
 /// This will deadlock if the future is awaiting anything other than GPU progress.
 #[cfg_attr(docsrs, doc(hidden))]
-pub fn block_on_wgpu<F: Future>(device: &Device, mut fut: F) -> F::Output {
+pub fn block_on_wgpu<F: Future>(device: &Device, fut: F) -> F::Output {
     if cfg!(target_arch = "wasm32") {
         panic!("Blocking can't work on WASM, so don't try");
     }
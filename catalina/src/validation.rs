@@ -0,0 +1,200 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Validation of GPU pipeline stages against their CPU implementations.
+//!
+//! With the `debug_validation` feature, the engine reads back the bindings of each stage which
+//! has a registered reference implementation before and after it runs on the GPU, replays the
+//! stage on the CPU from the same inputs, and panics at the first output element which differs.
+//! This turns a driver-specific miscompilation into a report of the stage, binding and element
+//! which went wrong.
+
+use std::cell::RefCell;
+
+use catalina_shaders::cpu::CpuBinding;
+use wgpu::{Buffer, BufferUsages, CommandEncoderDescriptor, Device, Queue};
+
+use crate::recording::BindType;
+
+/// The number of units in the last place by which floating point results may differ, as GPUs
+/// are allowed to compute some operations less precisely than the CPU.
+const MAX_ULPS: u32 = 4;
+
+/// Where the contents of a binding can be read from.
+pub(crate) enum Source<'a> {
+    /// The binding was uploaded from the CPU, and isn't written by the stage.
+    Bytes(&'a [u8]),
+    /// The binding is a range of a GPU buffer with the `COPY_SRC` usage.
+    Gpu {
+        buffer: &'a Buffer,
+        offset: u64,
+        size: u64,
+    },
+}
+
+/// Reads the current contents of each binding, waiting for the GPU to finish.
+///
+/// All work using the buffers must have been submitted to `queue` beforehand.
+pub(crate) fn read(device: &Device, queue: &Queue, sources: &[Source<'_>]) -> Vec<Vec<u8>> {
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("catalina.validation_readback"),
+    });
+    let staging: Vec<Option<Buffer>> = sources
+        .iter()
+        .map(|source| match *source {
+            Source::Bytes(_) => None,
+            Source::Gpu {
+                buffer,
+                offset,
+                size,
+            } => {
+                let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("catalina.validation_staging"),
+                    size,
+                    usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                encoder.copy_buffer_to_buffer(buffer, offset, &staging, 0, size);
+                Some(staging)
+            }
+        })
+        .collect();
+    queue.submit([encoder.finish()]);
+    for buffer in staging.iter().flatten() {
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    }
+    device.poll(wgpu::Maintain::Wait);
+    sources
+        .iter()
+        .zip(&staging)
+        .map(|(source, staging)| match (source, staging) {
+            (Source::Bytes(bytes), _) => bytes.to_vec(),
+            (Source::Gpu { .. }, Some(staging)) => {
+                let data = staging.slice(..).get_mapped_range().to_vec();
+                staging.unmap();
+                data
+            }
+            (Source::Gpu { .. }, None) => unreachable!("every GPU source has a staging buffer"),
+        })
+        .collect()
+}
+
+/// Runs `reference` on `inputs`, and panics if the read-write bindings it produces differ
+/// from `outputs`, the same bindings after the stage ran on the GPU.
+pub(crate) fn check(
+    label: &str,
+    reference: fn(u32, &[CpuBinding<'_>]),
+    n_wg: u32,
+    bind_types: &[BindType],
+    names: &[&str],
+    inputs: &[Vec<u8>],
+    outputs: &[Vec<u8>],
+) {
+    let cells: Vec<RefCell<Vec<u8>>> = inputs.iter().cloned().map(RefCell::new).collect();
+    let bindings: Vec<CpuBinding<'_>> = bind_types
+        .iter()
+        .zip(inputs.iter().zip(&cells))
+        .map(|(bind_type, (input, cell))| match bind_type {
+            BindType::Buffer => CpuBinding::BufferRW(cell),
+            _ => CpuBinding::Buffer(input),
+        })
+        .collect();
+    reference(n_wg, &bindings);
+    for (ix, bind_type) in bind_types.iter().enumerate() {
+        if *bind_type != BindType::Buffer {
+            continue;
+        }
+        let expected = cells[ix].borrow();
+        let mismatch = words(&outputs[ix])
+            .zip(words(&expected))
+            .enumerate()
+            .find(|(_, (gpu, cpu))| !matches(*gpu, *cpu));
+        if let Some((element, (gpu, cpu))) = mismatch {
+            panic!(
+                "GPU stage `{label}` disagrees with its CPU implementation in binding {ix} \
+                 (`{}`) at element {element}: GPU wrote {gpu:#010x}, CPU wrote {cpu:#010x}",
+                names[ix]
+            );
+        }
+    }
+}
+
+fn words(bytes: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    bytes
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+}
+
+/// Returns `true` if two words are equal, or are both normal floating point numbers of the same
+/// sign within [`MAX_ULPS`] of each other.
+///
+/// Integers below 2<sup>23</sup> are subnormal when interpreted as floats, so are always
+/// compared exactly.
+fn matches(gpu: u32, cpu: u32) -> bool {
+    if gpu == cpu {
+        return true;
+    }
+    let (a, b) = (f32::from_bits(gpu), f32::from_bits(cpu));
+    a.is_normal()
+        && b.is_normal()
+        && a.is_sign_positive() == b.is_sign_positive()
+        && gpu.abs_diff(cpu) <= MAX_ULPS
+}
+
+#[cfg(test)]
+mod tests {
+    use catalina_shaders::cpu::CpuBinding;
+
+    use super::{check, matches};
+    use crate::recording::BindType;
+
+    /// A stage which writes the doubled input to the output.
+    fn double(_n_wg: u32, resources: &[CpuBinding<'_>]) {
+        let input = resources[0].as_slice::<f32>();
+        let mut output = resources[1].as_slice_mut::<f32>();
+        for (output, input) in output.iter_mut().zip(input.iter()) {
+            *output = 2.0 * input;
+        }
+    }
+
+    fn bytes(values: &[f32]) -> Vec<u8> {
+        bytemuck::cast_slice(values).to_vec()
+    }
+
+    fn check_double(gpu_output: &[f32]) {
+        let input = bytes(&[1.0, 2.5, -3.0]);
+        check(
+            "double",
+            double,
+            1,
+            &[BindType::BufReadOnly, BindType::Buffer],
+            &["input", "output"],
+            &[input.clone(), bytes(&[0.0; 3])],
+            &[input, bytes(gpu_output)],
+        );
+    }
+
+    #[test]
+    fn matching_outputs_pass() {
+        check_double(&[2.0, 5.0, -6.0]);
+        // GPUs may round slightly differently.
+        check_double(&[2.0, f32::from_bits(5.0_f32.to_bits() + 2), -6.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "binding 1 (`output`) at element 2")]
+    fn mismatches_are_pinpointed() {
+        check_double(&[2.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn integers_are_compared_exactly() {
+        assert!(matches(7, 7));
+        assert!(!matches(7, 8));
+        assert!(matches(1.0_f32.to_bits(), 1.0_f32.to_bits() + 4));
+        assert!(!matches(1.0_f32.to_bits(), 1.0_f32.to_bits() + 5));
+        assert!(!matches(0.0_f32.to_bits(), (-0.0_f32).to_bits()));
+    }
+}
@@ -4,17 +4,22 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::num::NonZeroUsize;
 
 use catalina_shaders::cpu::CpuBinding;
 
 use wgpu::{
     BindGroup, BindGroupLayout, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor,
-    ComputePassDescriptor, ComputePipeline, Device, PipelineCompilationOptions, Queue,
-    RenderPipeline, Texture, TextureAspect, TextureUsages, TextureView, TextureViewDimension,
+    ComputePassDescriptor, ComputePipeline, Device, PipelineCache, PipelineCompilationOptions,
+    Queue, RenderPipeline, Texture, TextureAspect, TextureUsages, TextureView,
+    TextureViewDimension,
 };
 
+#[cfg(feature = "debug_validation")]
+use crate::validation;
 use crate::{
+    graph::RenderGraph,
     low_level::{BufferProxy, Command, ImageProxy, Recording, ResourceId, ResourceProxy, ShaderId},
     recording::BindType,
     Error, Result,
@@ -51,6 +56,11 @@ pub struct WgpuEngine {
     ///
     /// The `Texture` should have the same size as the `Image`.
     pub(crate) image_overrides: HashMap<u64, wgpu::TexelCopyTextureInfoBase<Texture>>,
+    /// The cache pipelines are compiled with, if any.
+    pipeline_cache: Option<PipelineCache>,
+    /// The CPU implementations which GPU stages are checked against, by shader index.
+    #[cfg(feature = "debug_validation")]
+    reference_shaders: HashMap<usize, fn(u32, &[CpuBinding<'_>])>,
 }
 
 enum PipelineState {
@@ -84,6 +94,8 @@ enum ShaderKind<'a> {
 
 struct Shader {
     label: &'static str,
+    /// The types of the shader's bindings, in binding order.
+    bind_types: Vec<BindType>,
     wgpu: Option<WgpuShader>,
     cpu: Option<CpuShader>,
 }
@@ -135,9 +147,31 @@ struct BufferProperties {
     name: &'static str,
 }
 
-#[derive(Default)]
+/// The number of frames whose buffers are kept apart by default, so that a frame can be
+/// prepared while the previous one runs on the GPU.
+const DEFAULT_FRAMES_IN_FLIGHT: NonZeroUsize = NonZeroUsize::MIN.saturating_add(1);
+
 struct ResourcePool {
+    /// Buffers which no frame in flight uses, and can be reused.
     bufs: HashMap<BufferProperties, Vec<Buffer>>,
+    /// Buffers freed during the current frame, which can be reused until it ends.
+    frame_bufs: HashMap<BufferProperties, Vec<Buffer>>,
+    /// Buffers freed during recent frames, oldest first, which may still be in use by the
+    /// GPU.
+    retired: VecDeque<HashMap<BufferProperties, Vec<Buffer>>>,
+    /// The number of frames whose buffers are kept apart, including the current one.
+    frames_in_flight: NonZeroUsize,
+}
+
+impl Default for ResourcePool {
+    fn default() -> Self {
+        Self {
+            bufs: HashMap::new(),
+            frame_bufs: HashMap::new(),
+            retired: VecDeque::new(),
+            frames_in_flight: DEFAULT_FRAMES_IN_FLIGHT,
+        }
+    }
 }
 
 /// The transient bind map contains short-lifetime resources.
@@ -170,6 +204,56 @@ impl WgpuEngine {
         }
     }
 
+    /// Registers the CPU implementation of `shader`, which each direct dispatch of the GPU
+    /// shader is checked against.
+    ///
+    /// The outputs must not depend on the order of atomic operations, as they are compared
+    /// element by element.
+    #[cfg(feature = "debug_validation")]
+    pub(crate) fn set_reference_shader(
+        &mut self,
+        shader: ShaderId,
+        reference: fn(u32, &[CpuBinding<'_>]),
+    ) {
+        self.reference_shaders.insert(shader.0, reference);
+    }
+
+    /// Sets the number of frames which can be in flight at once, which defaults to 2.
+    ///
+    /// Buffers freed during a frame are only reused by the same frame, or once this many
+    /// frames have ended, so that uploads for a frame don't have to wait for earlier frames
+    /// which still use its buffers. Higher values use more memory. With 1, buffers are reused
+    /// as soon as they're freed.
+    pub fn set_frames_in_flight(&mut self, frames: NonZeroUsize) {
+        self.pool.frames_in_flight = frames;
+        self.pool.release_retired();
+    }
+
+    /// Ends a frame, after all of its recordings have been run.
+    ///
+    /// See [`set_frames_in_flight`](Self::set_frames_in_flight). Engines which are never told
+    /// when frames end reuse buffers as soon as they're freed.
+    pub fn end_frame(&mut self) {
+        self.pool.end_frame();
+    }
+
+    /// The number of buffers kept for reuse by later frames, including those of frames which
+    /// may still be in flight.
+    pub fn pooled_buffers(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Sets the cache which pipelines added after this are compiled with, such as one loaded
+    /// from an earlier run.
+    pub fn set_pipeline_cache(&mut self, cache: Option<PipelineCache>) {
+        self.pipeline_cache = cache;
+    }
+
+    /// The cache pipelines are compiled with, if any.
+    pub fn pipeline_cache(&self) -> Option<&PipelineCache> {
+        self.pipeline_cache.as_ref()
+    }
+
     /// Enable creating any remaining shaders in parallel
     #[cfg(not(target_arch = "wasm32"))]
     pub fn use_parallel_initialisation(&mut self) {
@@ -208,6 +292,7 @@ impl WgpuEngine {
             // use a mutex for our work queue
             let work_queue = std::sync::Mutex::new(remainder.into_iter());
             let work_queue = &work_queue;
+            let cache = self.pipeline_cache.as_ref();
             std::thread::scope(|scope| {
                 let tx = tx;
                 new_shaders
@@ -218,7 +303,7 @@ impl WgpuEngine {
                             .name("catalina Shader initialisation worker thread".into())
                             .spawn_scoped(scope, move || {
                                 let shader = Self::create_compute_pipeline(
-                                    device, it.label, it.wgsl, it.entries,
+                                    device, it.label, it.wgsl, it.entries, cache,
                                 );
                                 // We know the rx can only be closed if all the tx references are dropped
                                 tx.send((it.shader_id, shader)).unwrap();
@@ -230,6 +315,7 @@ impl WgpuEngine {
                                             value.label,
                                             value.wgsl,
                                             value.entries,
+                                            cache,
                                         );
                                         tx.send((value.shader_id, shader)).unwrap();
                                     } else {
@@ -297,6 +383,7 @@ impl WgpuEngine {
                         wgpu: None,
                         cpu: Some(CpuShader { shader }),
                         label,
+                        bind_types: layout.to_vec(),
                     });
                 }
                 // This shader is unused in CPU mode, create a dummy shader
@@ -305,6 +392,7 @@ impl WgpuEngine {
                         wgpu: None,
                         cpu: None,
                         label,
+                        bind_types: layout.to_vec(),
                     });
                 }
                 // Create a GPU shader as we don't have a CPU shader
@@ -319,6 +407,7 @@ impl WgpuEngine {
         if let Some(uninit) = self.shaders_to_initialise.as_mut() {
             let id = add(Shader {
                 label,
+                bind_types: layout.to_vec(),
                 wgpu: None,
                 cpu: None,
             });
@@ -330,11 +419,18 @@ impl WgpuEngine {
             });
             return id;
         }
-        let wgpu = Self::create_compute_pipeline(device, label, wgsl, entries);
+        let wgpu = Self::create_compute_pipeline(
+            device,
+            label,
+            wgsl,
+            entries,
+            self.pipeline_cache.as_ref(),
+        );
         add(Shader {
             wgpu: Some(wgpu),
             cpu: None,
             label,
+            bind_types: layout.to_vec(),
         })
     }
 
@@ -388,7 +484,7 @@ impl WgpuEngine {
             depth_stencil: None,
             multisample: wgpu::MultisampleState::default(),
             multiview: None,
-            cache: None,
+            cache: self.pipeline_cache.as_ref(),
         });
         let id = self.shaders.len();
         self.shaders.push(Shader {
@@ -398,6 +494,10 @@ impl WgpuEngine {
             }),
             cpu: None,
             label,
+            bind_types: bind_layout
+                .iter()
+                .map(|(bind_type, _)| *bind_type)
+                .collect(),
         });
         ShaderId(id)
     }
@@ -583,20 +683,80 @@ impl WgpuEngine {
                                 &wgpu_shader.bind_group_layout,
                                 bindings,
                             );
-                            let mut cpass =
-                                encoder.begin_compute_pass(&ComputePassDescriptor::default());
-                            #[cfg(feature = "wgpu-profiler")]
-                            let query = profiler
-                                .begin_query(shader.label, &mut cpass, device)
-                                .with_parent(Some(&query));
-                            let PipelineState::Compute(pipeline) = &wgpu_shader.pipeline else {
-                                panic!("cannot issue a dispatch with a render pipeline");
-                            };
-                            cpass.set_pipeline(pipeline);
-                            cpass.set_bind_group(0, &bind_group, &[]);
-                            cpass.dispatch_workgroups(x, y, z);
-                            #[cfg(feature = "wgpu-profiler")]
-                            profiler.end_query(&mut cpass, query);
+                            #[cfg(feature = "debug_validation")]
+                            let validation =
+                                self.reference_shaders
+                                    .get(&shader_id.0)
+                                    .and_then(|reference| {
+                                        let sources = transient_map.validation_sources(
+                                            &self.bind_map,
+                                            &shader.bind_types,
+                                            bindings,
+                                        )?;
+                                        // Submit the work so far, so that the inputs can be read.
+                                        queue.submit([std::mem::replace(
+                                            &mut encoder,
+                                            device.create_command_encoder(
+                                                &CommandEncoderDescriptor { label: Some(label) },
+                                            ),
+                                        )
+                                        .finish()]);
+                                        Some((
+                                            *reference,
+                                            validation::read(device, queue, &sources),
+                                        ))
+                                    });
+                            {
+                                let mut cpass =
+                                    encoder.begin_compute_pass(&ComputePassDescriptor::default());
+                                #[cfg(feature = "wgpu-profiler")]
+                                let query = profiler
+                                    .begin_query(shader.label, &mut cpass, device)
+                                    .with_parent(Some(&query));
+                                let PipelineState::Compute(pipeline) = &wgpu_shader.pipeline else {
+                                    panic!("cannot issue a dispatch with a render pipeline");
+                                };
+                                cpass.set_pipeline(pipeline);
+                                cpass.set_bind_group(0, &bind_group, &[]);
+                                cpass.dispatch_workgroups(x, y, z);
+                                #[cfg(feature = "wgpu-profiler")]
+                                profiler.end_query(&mut cpass, query);
+                            }
+                            #[cfg(feature = "debug_validation")]
+                            if let Some((reference, inputs)) = validation {
+                                queue.submit([std::mem::replace(
+                                    &mut encoder,
+                                    device.create_command_encoder(&CommandEncoderDescriptor {
+                                        label: Some(label),
+                                    }),
+                                )
+                                .finish()]);
+                                let sources = transient_map
+                                    .validation_sources(
+                                        &self.bind_map,
+                                        &shader.bind_types,
+                                        bindings,
+                                    )
+                                    .expect("sources were available before the dispatch");
+                                let outputs = validation::read(device, queue, &sources);
+                                let names: Vec<&str> = bindings
+                                    .iter()
+                                    .map(|binding| match binding {
+                                        ResourceProxy::Buffer(proxy)
+                                        | ResourceProxy::BufferRange { proxy, .. } => proxy.name,
+                                        ResourceProxy::Image(_) => "image",
+                                    })
+                                    .collect();
+                                validation::check(
+                                    shader.label,
+                                    reference,
+                                    x,
+                                    &shader.bind_types,
+                                    &names,
+                                    &inputs,
+                                    &outputs,
+                                );
+                            }
                         }
                     }
                 }
@@ -764,7 +924,7 @@ impl WgpuEngine {
                         usages: gpu_buf.usage(),
                         name: buf.label,
                     };
-                    self.pool.bufs.entry(props).or_default().push(gpu_buf);
+                    self.pool.frame_bufs.entry(props).or_default().push(gpu_buf);
                 }
             }
         }
@@ -778,6 +938,16 @@ impl WgpuEngine {
         Ok(())
     }
 
+    /// Describes the passes in `recording` which would be run by this engine.
+    ///
+    /// The shaders used in `recording` must have been added to this engine.
+    pub fn describe(&self, recording: &Recording) -> RenderGraph {
+        RenderGraph::new(recording, |id| {
+            let shader = &self.shaders[id.0];
+            (shader.label, &shader.bind_types)
+        })
+    }
+
     /// Get an already downloaded buffer proxy.
     /// TODO: Add better documentation.
     pub fn get_download(&self, buf: BufferProxy) -> Option<&Buffer> {
@@ -847,6 +1017,7 @@ impl WgpuEngine {
         label: &str,
         wgsl: Cow<'_, str>,
         entries: Vec<wgpu::BindGroupLayoutEntry>,
+        cache: Option<&PipelineCache>,
     ) -> WgpuShader {
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some(label),
@@ -871,8 +1042,7 @@ impl WgpuEngine {
                 zero_initialize_workgroup_memory: false,
                 ..Default::default()
             },
-            // TODO: Support providing a cache here.
-            cache: None,
+            cache,
         });
         WgpuShader {
             pipeline: PipelineState::Compute(pipeline),
@@ -986,8 +1156,8 @@ impl ResourcePool {
             usages: usage,
             name,
         };
-        if let Some(buf_vec) = self.bufs.get_mut(&props) {
-            if let Some(buf) = buf_vec.pop() {
+        for bufs in [&mut self.frame_bufs, &mut self.bufs] {
+            if let Some(buf) = bufs.get_mut(&props).and_then(Vec::pop) {
                 return buf;
             }
         }
@@ -999,6 +1169,33 @@ impl ResourcePool {
         })
     }
 
+    /// Retires the buffers freed during the current frame.
+    fn end_frame(&mut self) {
+        self.retired.push_back(std::mem::take(&mut self.frame_bufs));
+        self.release_retired();
+    }
+
+    /// Makes the buffers of frames which are no longer in flight available for reuse.
+    fn release_retired(&mut self) {
+        // The current frame is in flight too, so one fewer retired frame is kept.
+        while self.retired.len() >= self.frames_in_flight.get() {
+            let Some(frame) = self.retired.pop_front() else {
+                break;
+            };
+            for (props, bufs) in frame {
+                self.bufs.entry(props).or_default().extend(bufs);
+            }
+        }
+    }
+
+    /// The number of buffers in the pool.
+    fn len(&self) -> usize {
+        let frames = [&self.bufs, &self.frame_bufs]
+            .into_iter()
+            .chain(&self.retired);
+        frames.flat_map(HashMap::values).map(Vec::len).sum()
+    }
+
     /// Quantize a size up to the nearest size class.
     fn size_class(x: u64, bits: u32) -> u64 {
         if x > 1 << bits {
@@ -1209,6 +1406,48 @@ impl<'a> TransientBindMap<'a> {
         })
     }
 
+    /// Returns where the contents of each binding can be read from for validation, or `None`
+    /// if any of them can't be read back.
+    #[cfg(feature = "debug_validation")]
+    fn validation_sources<'b>(
+        &'b self,
+        bind_map: &'b BindMap,
+        bind_types: &[BindType],
+        bindings: &[ResourceProxy],
+    ) -> Option<Vec<validation::Source<'b>>> {
+        bindings
+            .iter()
+            .zip(bind_types)
+            .map(|(binding, bind_type)| {
+                let (proxy, offset, size) = match *binding {
+                    ResourceProxy::Buffer(proxy) => (proxy, 0, proxy.size),
+                    ResourceProxy::BufferRange {
+                        proxy,
+                        offset,
+                        size,
+                    } => (proxy, offset, size),
+                    ResourceProxy::Image(_) => return None,
+                };
+                match self.bufs.get(&proxy.id) {
+                    // Uniform buffers can't be copied from, but are never written by a stage.
+                    Some(TransientBuf::Cpu(bytes)) if *bind_type == BindType::Uniform => {
+                        Some(validation::Source::Bytes(bytes))
+                    }
+                    // External buffers might not have the `COPY_SRC` usage.
+                    Some(TransientBuf::Gpu(_)) => None,
+                    _ if *bind_type == BindType::Uniform => None,
+                    _ => bind_map
+                        .get_gpu_buf(proxy.id)
+                        .map(|buffer| validation::Source::Gpu {
+                            buffer,
+                            offset,
+                            size,
+                        }),
+                }
+            })
+            .collect()
+    }
+
     fn create_cpu_resources(
         &self,
         bind_map: &'a mut BindMap,
@@ -0,0 +1,180 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Detection and correction of the winding direction of subpaths.
+//!
+//! Under the non-zero fill rule, a hole is only left unfilled if it winds in the opposite
+//! direction to the contour around it. Geometry imported from CAD tools and SVG files often
+//! doesn't follow this, so holes are filled in, or overlapping contours which wind in opposite
+//! directions cancel out.
+
+use peniko::kurbo::{BezPath, ParamCurve, PathEl, Point, Shape};
+
+/// The direction a closed subpath winds in, as seen on screen with the y axis pointing down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindingDirection {
+    /// The subpath winds clockwise, which is a positive area in kurbo.
+    Clockwise,
+    /// The subpath winds counter-clockwise.
+    CounterClockwise,
+}
+
+impl WindingDirection {
+    fn of(subpath: &BezPath) -> Option<Self> {
+        let area = subpath.area();
+        if area > 0.0 {
+            Some(Self::Clockwise)
+        } else if area < 0.0 {
+            Some(Self::CounterClockwise)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the winding direction of each subpath of `path`, or `None` for subpaths which
+/// enclose no area.
+///
+/// Subpaths which aren't closed are treated as closed by a line back to their start, as they
+/// are when filled.
+pub fn winding_directions(path: &BezPath) -> Vec<Option<WindingDirection>> {
+    subpaths(path).iter().map(WindingDirection::of).collect()
+}
+
+/// Returns the indices of the subpaths of `path` which wind in the same direction as the
+/// innermost subpath around them, so are filled rather than left as holes under the non-zero
+/// fill rule.
+///
+/// A subpath is taken to be inside another if its first point is. This is exact for contours
+/// which don't cross each other, as in most imported content.
+pub fn misoriented_subpaths(path: &BezPath) -> Vec<usize> {
+    let subpaths = subpaths(path);
+    let nesting = Nesting::new(&subpaths);
+    (0..subpaths.len())
+        .filter(|&index| {
+            nesting.parents[index].is_some_and(|parent| {
+                let direction = WindingDirection::of(&subpaths[index]);
+                direction.is_some() && direction == WindingDirection::of(&subpaths[parent])
+            })
+        })
+        .collect()
+}
+
+/// Returns `path` with the direction of its subpaths corrected, so that filling it with the
+/// non-zero rule matches filling it with the even-odd rule.
+///
+/// Outermost contours wind clockwise, the holes inside them counter-clockwise, the islands
+/// inside those clockwise, and so on. The order and shape of the subpaths are unchanged. Finding
+/// the nesting tests every subpath against every other, so this is quadratic in the number of
+/// subpaths.
+pub fn normalize_winding(path: &BezPath) -> BezPath {
+    let subpaths = subpaths(path);
+    let nesting = Nesting::new(&subpaths);
+    let mut normalized = BezPath::new();
+    for (subpath, depth) in subpaths.iter().zip(nesting.depths) {
+        let expected = if depth % 2 == 0 {
+            WindingDirection::Clockwise
+        } else {
+            WindingDirection::CounterClockwise
+        };
+        match WindingDirection::of(subpath) {
+            Some(direction) if direction != expected => {
+                normalized.extend(reverse(subpath));
+            }
+            _ => normalized.extend(subpath.elements().iter().copied()),
+        }
+    }
+    normalized
+}
+
+/// Splits a path into its subpaths, closing each of them.
+fn subpaths(path: &BezPath) -> Vec<BezPath> {
+    let mut subpaths = Vec::new();
+    let mut current = BezPath::new();
+    let mut start = Point::ZERO;
+    for element in path.elements() {
+        match *element {
+            PathEl::MoveTo(point) => {
+                finish_subpath(&mut subpaths, &mut current);
+                start = point;
+            }
+            // A segment after a `ClosePath` continues from the start of the closed subpath.
+            _ if current.elements().is_empty() => current.move_to(start),
+            _ => {}
+        }
+        current.push(*element);
+        if *element == PathEl::ClosePath {
+            finish_subpath(&mut subpaths, &mut current);
+        }
+    }
+    finish_subpath(&mut subpaths, &mut current);
+    subpaths
+}
+
+fn finish_subpath(subpaths: &mut Vec<BezPath>, current: &mut BezPath) {
+    let mut subpath = std::mem::take(current);
+    // A lone `MoveTo` draws nothing.
+    if subpath.elements().len() < 2 {
+        return;
+    }
+    if subpath.elements().last() != Some(&PathEl::ClosePath) {
+        subpath.close_path();
+    }
+    subpaths.push(subpath);
+}
+
+/// Reverses the direction of a closed subpath.
+fn reverse(subpath: &BezPath) -> BezPath {
+    let segments: Vec<_> = subpath.segments().collect();
+    let mut reversed = BezPath::new();
+    if let Some(last) = segments.last() {
+        reversed.move_to(last.end());
+    }
+    for segment in segments.iter().rev() {
+        reversed.push(segment.reverse().as_path_el());
+    }
+    reversed.close_path();
+    reversed
+}
+
+/// How the subpaths of a path are nested inside each other.
+struct Nesting {
+    /// The number of subpaths around each subpath.
+    depths: Vec<usize>,
+    /// The innermost subpath around each subpath.
+    parents: Vec<Option<usize>>,
+}
+
+impl Nesting {
+    fn new(subpaths: &[BezPath]) -> Self {
+        let starts: Vec<Point> = subpaths
+            .iter()
+            .map(|subpath| {
+                subpath
+                    .segments()
+                    .next()
+                    .map_or(Point::ZERO, |seg| seg.start())
+            })
+            .collect();
+        let containers: Vec<Vec<usize>> = starts
+            .iter()
+            .enumerate()
+            .map(|(index, start)| {
+                (0..subpaths.len())
+                    .filter(|&other| other != index && subpaths[other].winding(*start) != 0)
+                    .collect()
+            })
+            .collect();
+        let depths: Vec<usize> = containers.iter().map(Vec::len).collect();
+        let parents = containers
+            .iter()
+            .map(|containers| {
+                containers
+                    .iter()
+                    .copied()
+                    .max_by_key(|&other| depths[other])
+            })
+            .collect();
+        Self { depths, parents }
+    }
+}
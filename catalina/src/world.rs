@@ -0,0 +1,530 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Coordinate management for unbounded, "infinite canvas" scenes.
+//!
+//! Scenes are encoded with 32-bit floating point coordinates, which lose precision quickly far
+//! from the origin. A [`World`] instead stores content as retained fragments, each with its own
+//! local coordinates and an anchor in a chunked world coordinate system. When rendering a
+//! [`Viewport`], the offset of each fragment from the viewport is computed exactly before being
+//! converted to a transform, so content stays sharp however far the view is panned.
+
+use hashbrown::{HashMap, HashSet};
+use peniko::kurbo::{Affine, Point, Rect, Size, Vec2};
+
+use crate::Scene;
+
+/// A position in a [`World`], as a chunk index and an offset within that chunk.
+///
+/// Positions are normalized so that the offset is always within the chunk, which keeps the
+/// floating point part small at any distance from the origin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldPoint {
+    /// The index of the chunk containing the point.
+    pub chunk: ChunkId,
+    /// The offset of the point from the top left corner of the chunk.
+    pub offset: Vec2,
+}
+
+/// The index of a square chunk of a [`World`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkId {
+    /// The column of the chunk.
+    pub x: i64,
+    /// The row of the chunk.
+    pub y: i64,
+}
+
+/// Identifies a fragment inserted into a [`World`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FragmentId(u64);
+
+/// A view of a [`World`], as seen in a window or on a canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// The world position shown at the center of the view.
+    pub center: WorldPoint,
+    /// The number of pixels per world unit.
+    pub zoom: f64,
+    /// The size of the view, in pixels.
+    pub size: Size,
+}
+
+/// A retained piece of content in a [`World`].
+#[derive(Clone)]
+struct Fragment {
+    anchor: WorldPoint,
+    /// The bounds of the scene, in its local coordinates.
+    bounds: Rect,
+    scene: Scene,
+}
+
+/// The fragments overlapping a chunk.
+#[derive(Clone, Default)]
+struct Chunk {
+    fragments: Vec<FragmentId>,
+    /// The union of the bounds of the fragments, relative to the chunk.
+    bounds: Rect,
+}
+
+/// An unbounded 2D canvas made of retained scene fragments.
+///
+/// Fragments are indexed by the chunks they overlap, so that [`query`](Self::query) and
+/// [`render`](Self::render) only visit content near the viewport.
+#[derive(Clone)]
+pub struct World {
+    chunk_size: f64,
+    fragments: HashMap<FragmentId, Fragment>,
+    chunks: HashMap<ChunkId, Chunk>,
+    next_id: u64,
+    /// The smallest size on screen, in pixels, at which fragments are still drawn.
+    min_screen_size: f64,
+}
+
+impl core::fmt::Debug for World {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("World")
+            .field("chunk_size", &self.chunk_size)
+            .field("fragments", &self.fragments.len())
+            .field("chunks", &self.chunks.len())
+            .field("min_screen_size", &self.min_screen_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WorldPoint {
+    /// The origin of the world.
+    pub const ORIGIN: Self = Self {
+        chunk: ChunkId { x: 0, y: 0 },
+        offset: Vec2::ZERO,
+    };
+
+    /// Creates a normalized point in a world with the given chunk size.
+    pub fn new(chunk: ChunkId, offset: Vec2, chunk_size: f64) -> Self {
+        Self { chunk, offset }.normalize(chunk_size)
+    }
+
+    /// Converts plain world coordinates, which are only precise near the origin.
+    pub fn from_point(point: Point, chunk_size: f64) -> Self {
+        Self::new(ChunkId { x: 0, y: 0 }, point.to_vec2(), chunk_size)
+    }
+
+    /// Returns the point moved by `delta` world units.
+    pub fn translate(self, delta: Vec2, chunk_size: f64) -> Self {
+        Self::new(self.chunk, self.offset + delta, chunk_size)
+    }
+
+    /// Returns the vector from `other` to this point.
+    ///
+    /// This is exact for any points whose distance fits comfortably in an `f64`.
+    pub fn offset_from(self, other: Self, chunk_size: f64) -> Vec2 {
+        // Chunk distances are far smaller than 2^53 for any practical world, so this is exact.
+        let chunks = Vec2::new(
+            self.chunk.x.saturating_sub(other.chunk.x) as f64,
+            self.chunk.y.saturating_sub(other.chunk.y) as f64,
+        );
+        chunks * chunk_size + (self.offset - other.offset)
+    }
+
+    /// Moves whole chunks out of the offset, so that it lies within `0..chunk_size`.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The quotient is a whole number of chunks, saturating on overflow."
+    )]
+    fn normalize(self, chunk_size: f64) -> Self {
+        let dx = (self.offset.x / chunk_size).floor();
+        let dy = (self.offset.y / chunk_size).floor();
+        if !dx.is_finite() || !dy.is_finite() {
+            return self;
+        }
+        Self {
+            chunk: ChunkId {
+                x: self.chunk.x.saturating_add(dx as i64),
+                y: self.chunk.y.saturating_add(dy as i64),
+            },
+            offset: Vec2::new(
+                self.offset.x - dx * chunk_size,
+                self.offset.y - dy * chunk_size,
+            ),
+        }
+    }
+}
+
+impl Viewport {
+    /// Creates a viewport of the given size, centered on `center`.
+    pub fn new(center: WorldPoint, zoom: f64, size: Size) -> Self {
+        Self { center, zoom, size }
+    }
+
+    /// Converts a position in the view, in pixels, to a world position.
+    pub fn to_world(&self, point: Point, chunk_size: f64) -> WorldPoint {
+        let delta = (point.to_vec2() - self.size.to_vec2() * 0.5) / self.zoom;
+        self.center.translate(delta, chunk_size)
+    }
+
+    /// Converts a world position to a position in the view, in pixels.
+    pub fn to_view(&self, point: WorldPoint, chunk_size: f64) -> Point {
+        let delta = point.offset_from(self.center, chunk_size) * self.zoom;
+        (self.size.to_vec2() * 0.5 + delta).to_point()
+    }
+
+    /// Returns the transform from coordinates relative to `anchor` to view coordinates.
+    pub fn transform_for(&self, anchor: WorldPoint, chunk_size: f64) -> Affine {
+        Affine::translate(self.to_view(anchor, chunk_size).to_vec2()) * Affine::scale(self.zoom)
+    }
+
+    /// Pans the view by `delta` pixels.
+    pub fn pan(&mut self, delta: Vec2, chunk_size: f64) {
+        self.center = self.center.translate(-delta / self.zoom, chunk_size);
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the world position under `focus` fixed.
+    pub fn zoom_about(&mut self, factor: f64, focus: Point, chunk_size: f64) {
+        let anchor = self.to_world(focus, chunk_size);
+        self.zoom *= factor;
+        let moved = self.to_view(anchor, chunk_size);
+        self.pan(focus - moved, chunk_size);
+    }
+}
+
+impl World {
+    /// Creates an empty world with chunks of the given size, in world units.
+    ///
+    /// The chunk size should be a few times larger than typical fragments.
+    ///
+    /// # Panics
+    ///
+    /// If the chunk size isn't positive and finite.
+    pub fn new(chunk_size: f64) -> Self {
+        assert!(
+            chunk_size.is_finite() && chunk_size > 0.0,
+            "chunk size must be positive and finite"
+        );
+        Self {
+            chunk_size,
+            fragments: HashMap::new(),
+            chunks: HashMap::new(),
+            next_id: 0,
+            min_screen_size: 0.25,
+        }
+    }
+
+    /// The size of each chunk, in world units.
+    pub fn chunk_size(&self) -> f64 {
+        self.chunk_size
+    }
+
+    /// Sets the smallest size on screen, in pixels, at which fragments are drawn.
+    ///
+    /// Fragments smaller than this are skipped by [`render`](Self::render), which keeps
+    /// extreme zoom outs cheap. The default is a quarter of a pixel.
+    pub fn set_min_screen_size(&mut self, size: f64) {
+        self.min_screen_size = size;
+    }
+
+    /// The number of fragments in the world.
+    pub fn len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Returns `true` if the world has no fragments.
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+
+    /// Creates a world position from plain coordinates, which are only precise near the origin.
+    pub fn point(&self, point: Point) -> WorldPoint {
+        WorldPoint::from_point(point, self.chunk_size)
+    }
+
+    /// Adds a fragment, whose local origin is placed at `anchor`.
+    ///
+    /// `bounds` must contain everything drawn by `scene`, in its local coordinates.
+    pub fn insert(&mut self, anchor: WorldPoint, scene: Scene, bounds: Rect) -> FragmentId {
+        let id = FragmentId(self.next_id);
+        self.next_id += 1;
+        let anchor = anchor.normalize(self.chunk_size);
+        for chunk in self.chunk_range(anchor, bounds) {
+            let relative = relative_bounds(self.chunk_size, anchor, bounds, chunk);
+            let entry = self.chunks.entry(chunk).or_default();
+            entry.bounds = if entry.fragments.is_empty() {
+                relative
+            } else {
+                entry.bounds.union(relative)
+            };
+            entry.fragments.push(id);
+        }
+        self.fragments.insert(
+            id,
+            Fragment {
+                anchor,
+                bounds,
+                scene,
+            },
+        );
+        id
+    }
+
+    /// Removes a fragment, returning its scene.
+    pub fn remove(&mut self, id: FragmentId) -> Option<Scene> {
+        let fragment = self.fragments.remove(&id)?;
+        for chunk in self.chunk_range(fragment.anchor, fragment.bounds) {
+            let Some(entry) = self.chunks.get_mut(&chunk) else {
+                continue;
+            };
+            entry.fragments.retain(|f| *f != id);
+            if entry.fragments.is_empty() {
+                self.chunks.remove(&chunk);
+            } else {
+                // Recompute the bounds, which may have shrunk.
+                let fragments = &self.fragments;
+                let chunk_size = self.chunk_size;
+                entry.bounds = entry
+                    .fragments
+                    .iter()
+                    .map(|f| {
+                        let f = &fragments[f];
+                        relative_bounds(chunk_size, f.anchor, f.bounds, chunk)
+                    })
+                    .reduce(|a, b| a.union(b))
+                    .unwrap_or_default();
+            }
+        }
+        Some(fragment.scene)
+    }
+
+    /// Returns the scene of a fragment.
+    pub fn get(&self, id: FragmentId) -> Option<&Scene> {
+        self.fragments.get(&id).map(|f| &f.scene)
+    }
+
+    /// Returns the anchor of a fragment.
+    pub fn anchor(&self, id: FragmentId) -> Option<WorldPoint> {
+        self.fragments.get(&id).map(|f| f.anchor)
+    }
+
+    /// Returns the fragments which may be visible in `viewport`, in insertion order.
+    pub fn query(&self, viewport: &Viewport) -> Vec<FragmentId> {
+        let half = viewport.size.to_vec2() * (0.5 / viewport.zoom);
+        let min = viewport.center.translate(-half, self.chunk_size);
+        let max = viewport.center.translate(half, self.chunk_size);
+        // Fragments are registered in every chunk they overlap, so the visible chunks are all
+        // that need to be visited.
+        let columns = max.chunk.x.saturating_sub(min.chunk.x).saturating_add(1);
+        let rows = max.chunk.y.saturating_sub(min.chunk.y).saturating_add(1);
+        let visible_chunks = u64::try_from(columns.saturating_mul(rows)).unwrap_or(u64::MAX);
+        let in_view = |id: &ChunkId| {
+            (min.chunk.x..=max.chunk.x).contains(&id.x)
+                && (min.chunk.y..=max.chunk.y).contains(&id.y)
+        };
+        let view_rect = |chunk: ChunkId| {
+            let origin = WorldPoint {
+                chunk,
+                offset: Vec2::ZERO,
+            };
+            let top_left = min.offset_from(origin, self.chunk_size).to_point();
+            let bottom_right = max.offset_from(origin, self.chunk_size).to_point();
+            Rect::from_points(top_left, bottom_right)
+        };
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+        let mut visit = |chunk_id: ChunkId, chunk: &Chunk| {
+            let view = view_rect(chunk_id);
+            if !chunk.bounds.overlaps(view) {
+                return;
+            }
+            for id in &chunk.fragments {
+                if seen.contains(id) {
+                    continue;
+                }
+                let fragment = &self.fragments[id];
+                let bounds =
+                    relative_bounds(self.chunk_size, fragment.anchor, fragment.bounds, chunk_id);
+                if bounds.overlaps(view) {
+                    seen.insert(*id);
+                    found.push(*id);
+                }
+            }
+        };
+        if visible_chunks <= self.chunks.len() as u64 {
+            for y in min.chunk.y..=max.chunk.y {
+                for x in min.chunk.x..=max.chunk.x {
+                    let id = ChunkId { x, y };
+                    if let Some(chunk) = self.chunks.get(&id) {
+                        visit(id, chunk);
+                    }
+                }
+            }
+        } else {
+            // When zoomed far out, there are fewer occupied chunks than visible ones.
+            for (id, chunk) in self.chunks.iter().filter(|(id, _)| in_view(id)) {
+                visit(*id, chunk);
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+
+    /// Appends the fragments visible in `viewport` to `scene`, in insertion order, transformed
+    /// into view coordinates.
+    pub fn render(&self, scene: &mut Scene, viewport: &Viewport) {
+        for id in self.query(viewport) {
+            let fragment = &self.fragments[&id];
+            let screen_size = fragment.bounds.width().max(fragment.bounds.height()) * viewport.zoom;
+            if screen_size < self.min_screen_size {
+                continue;
+            }
+            let transform = viewport.transform_for(fragment.anchor, self.chunk_size);
+            scene.append(&fragment.scene, Some(transform));
+        }
+    }
+
+    /// Returns the chunks overlapped by a fragment.
+    fn chunk_range(&self, anchor: WorldPoint, bounds: Rect) -> impl Iterator<Item = ChunkId> {
+        let min = anchor.translate(bounds.origin().to_vec2(), self.chunk_size);
+        let max = anchor.translate(Vec2::new(bounds.max_x(), bounds.max_y()), self.chunk_size);
+        (min.chunk.y..=max.chunk.y)
+            .flat_map(move |y| (min.chunk.x..=max.chunk.x).map(move |x| ChunkId { x, y }))
+    }
+}
+
+/// Returns fragment bounds relative to the top left corner of `chunk`.
+fn relative_bounds(chunk_size: f64, anchor: WorldPoint, bounds: Rect, chunk: ChunkId) -> Rect {
+    let origin = WorldPoint {
+        chunk,
+        offset: Vec2::ZERO,
+    };
+    bounds + anchor.offset_from(origin, chunk_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::kurbo::{Affine, Point, Rect, Size, Vec2};
+
+    use super::{ChunkId, Viewport, World, WorldPoint};
+    use crate::Scene;
+
+    const CHUNK_SIZE: f64 = 100.0;
+
+    fn square_bounds() -> Rect {
+        Rect::new(0.0, 0.0, 10.0, 10.0)
+    }
+
+    fn square() -> Scene {
+        let mut scene = Scene::new();
+        scene.fill(
+            peniko::Fill::NonZero,
+            Affine::IDENTITY,
+            peniko::color::palette::css::RED,
+            None,
+            &square_bounds(),
+        );
+        scene
+    }
+
+    #[test]
+    fn points_are_normalized_into_their_chunk() {
+        let point = WorldPoint::from_point(Point::new(250.0, -30.0), CHUNK_SIZE);
+        assert_eq!(point.chunk, ChunkId { x: 2, y: -1 });
+        assert_eq!(point.offset, Vec2::new(50.0, 70.0));
+        let moved = point.translate(Vec2::new(-300.0, 30.0), CHUNK_SIZE);
+        assert_eq!(moved.chunk, ChunkId { x: -1, y: 0 });
+        assert_eq!(moved.offset, Vec2::new(50.0, 0.0));
+        assert_eq!(
+            point.offset_from(moved, CHUNK_SIZE),
+            Vec2::new(300.0, -30.0)
+        );
+    }
+
+    #[test]
+    fn offsets_are_exact_far_from_the_origin() {
+        // Far enough that an `f64` can't represent a quarter unit at this distance.
+        let far = ChunkId {
+            x: 1 << 60,
+            y: -(1 << 60),
+        };
+        let a = WorldPoint::new(far, Vec2::new(0.25, 0.5), CHUNK_SIZE);
+        let b = a.translate(Vec2::new(1.5, -0.25), CHUNK_SIZE);
+        assert_eq!(b.offset_from(a, CHUNK_SIZE), Vec2::new(1.5, -0.25));
+        let viewport = Viewport::new(a, 2.0, Size::new(100.0, 100.0));
+        assert_eq!(viewport.to_view(b, CHUNK_SIZE), Point::new(53.0, 49.5));
+        assert_eq!(viewport.to_world(Point::new(53.0, 49.5), CHUNK_SIZE), b);
+    }
+
+    #[test]
+    fn zooming_keeps_the_focus_fixed() {
+        let mut viewport = Viewport::new(WorldPoint::ORIGIN, 1.0, Size::new(200.0, 100.0));
+        let focus = Point::new(150.0, 20.0);
+        let under_focus = viewport.to_world(focus, CHUNK_SIZE);
+        viewport.zoom_about(4.0, focus, CHUNK_SIZE);
+        assert_eq!(viewport.zoom, 4.0);
+        let view = viewport.to_view(under_focus, CHUNK_SIZE);
+        assert!((view - focus).hypot() < 1e-9, "{view:?}");
+    }
+
+    #[test]
+    fn queries_find_the_visible_fragments() {
+        let mut world = World::new(CHUNK_SIZE);
+        let near = world.insert(
+            world.point(Point::new(10.0, 10.0)),
+            square(),
+            square_bounds(),
+        );
+        // Straddling the corner of four chunks.
+        let corner = world.insert(
+            world.point(Point::new(295.0, 95.0)),
+            square(),
+            square_bounds(),
+        );
+        let far = world.insert(
+            world.point(Point::new(5000.0, 5000.0)),
+            square(),
+            square_bounds(),
+        );
+        assert_eq!(world.len(), 3);
+        let view = |center: Point, zoom| {
+            Viewport::new(
+                WorldPoint::from_point(center, CHUNK_SIZE),
+                zoom,
+                Size::new(100.0, 100.0),
+            )
+        };
+        assert_eq!(world.query(&view(Point::new(50.0, 50.0), 1.0)), [near]);
+        // Only the corner of the fragment which crosses into the next chunk is visible.
+        assert_eq!(world.query(&view(Point::new(350.0, 150.0), 1.0)), [corner]);
+        assert_eq!(world.query(&view(Point::new(1000.0, 1000.0), 1.0)), []);
+        // When zoomed out, every fragment is visible, in insertion order.
+        assert_eq!(
+            world.query(&view(Point::new(2500.0, 2500.0), 0.01)),
+            [near, corner, far]
+        );
+
+        assert!(world.remove(corner).is_some());
+        assert!(world.remove(corner).is_none());
+        assert_eq!(world.query(&view(Point::new(350.0, 150.0), 1.0)), []);
+        assert_eq!(
+            world.anchor(far),
+            Some(world.point(Point::new(5000.0, 5000.0)))
+        );
+    }
+
+    #[test]
+    fn tiny_fragments_are_skipped() {
+        let mut world = World::new(CHUNK_SIZE);
+        world.insert(WorldPoint::ORIGIN, square(), square_bounds());
+        let mut scene = Scene::new();
+        let viewport = Viewport::new(WorldPoint::ORIGIN, 1.0, Size::new(100.0, 100.0));
+        world.render(&mut scene, &viewport);
+        assert_eq!(scene.encoding().n_paths, 1);
+        // At this zoom, the fragment is a hundredth of a pixel across.
+        scene.reset();
+        world.render(
+            &mut scene,
+            &Viewport {
+                zoom: 0.001,
+                ..viewport
+            },
+        );
+        assert_eq!(scene.encoding().n_paths, 0);
+    }
+}
@@ -4,9 +4,15 @@
 use bytemuck::{Pod, Zeroable};
 
 /// Binning header.
+///
+/// Binning writes one header for each bin in each partition of 256 draw objects, indexed by
+/// the partition times 256 plus the index of the bin.
 #[derive(Copy, Clone, Pod, Zeroable, Debug, Default)]
 #[repr(C)]
 pub struct BinHeader {
+    /// The number of draw objects of the partition which touch the bin.
     pub element_count: u32,
+    /// Offset of the indices of those draw objects in the bin data, which starts at
+    /// `bin_data_start` in the info buffer.
     pub chunk_offset: u32,
 }
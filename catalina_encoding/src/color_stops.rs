@@ -0,0 +1,218 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Validation and repair of the color stops of gradients.
+
+use std::fmt;
+
+use peniko::color::{DynamicColor, HueDirection, Srgb};
+use peniko::{ColorStop, ColorStops};
+
+/// A problem with the color stops of a gradient, found by [`check_color_stops`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorStopsError {
+    /// The offset of the stop at this index is NaN or infinite.
+    NonFiniteOffset(usize),
+    /// The offset of the stop at this index is outside of `0..=1`.
+    OutOfRange(usize, f32),
+    /// The stop at this index has a smaller offset than the stop before it.
+    Unsorted(usize),
+}
+
+impl fmt::Display for ColorStopsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFiniteOffset(index) => {
+                write!(f, "color stop {index} has a non-finite offset")
+            }
+            Self::OutOfRange(index, offset) => {
+                write!(
+                    f,
+                    "color stop {index} has offset {offset}, outside of 0 to 1"
+                )
+            }
+            Self::Unsorted(index) => {
+                write!(f, "color stop {index} is before the stop preceding it")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ColorStopsError {}
+
+/// Checks that color stops have finite offsets between 0 and 1, in increasing order.
+///
+/// Stops with the same offset are allowed, and make a hard edge between their colors. This is
+/// the strict counterpart of [`normalize_color_stops`], for applications which would rather
+/// reject malformed gradients when they're constructed, such as when importing assets. Without
+/// it, the stops of every gradient are normalized when they're encoded.
+pub fn check_color_stops(stops: &[ColorStop]) -> Result<(), ColorStopsError> {
+    let mut previous = 0.0;
+    for (index, stop) in stops.iter().enumerate() {
+        if !stop.offset.is_finite() {
+            return Err(ColorStopsError::NonFiniteOffset(index));
+        }
+        if !(0.0..=1.0).contains(&stop.offset) {
+            return Err(ColorStopsError::OutOfRange(index, stop.offset));
+        }
+        if stop.offset < previous {
+            return Err(ColorStopsError::Unsorted(index));
+        }
+        previous = stop.offset;
+    }
+    Ok(())
+}
+
+/// Repairs color stops so that they pass [`check_color_stops`], keeping the colors of the
+/// gradient between 0 and 1 where possible.
+///
+/// Stops with non-finite offsets are removed, and the rest are sorted by offset. Stops with the
+/// same offset keep their order, so hard edges are preserved. Stops outside of `0..=1` are
+/// replaced by a stop at the nearest end with the color interpolated at it, so the visible part
+/// of the gradient is unchanged.
+pub fn normalize_color_stops(stops: &mut ColorStops) {
+    if check_color_stops(stops).is_ok() {
+        return;
+    }
+    let mut normalized = stops.to_vec();
+    normalize_tail(&mut normalized, 0);
+    stops.clear();
+    stops.extend(normalized);
+}
+
+/// Normalizes the stops of `stops` from index `start`, in place.
+pub(crate) fn normalize_tail(stops: &mut Vec<ColorStop>, start: usize) {
+    let mut tail = stops.split_off(start);
+    tail.retain(|stop| stop.offset.is_finite());
+    // The sort is stable, so stops with the same offset keep their order.
+    tail.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+
+    let first_inside = tail.partition_point(|stop| stop.offset < 0.0);
+    if first_inside == tail.len() {
+        // Every stop is before the start, so the gradient is the color of the last one.
+        tail.drain(..tail.len().saturating_sub(1));
+    } else if first_inside > 0 {
+        let start = interpolate(tail[first_inside - 1], tail[first_inside], 0.0);
+        tail.drain(..first_inside);
+        tail.insert(0, start);
+    }
+    let first_after = tail.partition_point(|stop| stop.offset <= 1.0);
+    if first_after == 0 {
+        // Every stop is after the end, so the gradient is the color of the first one.
+        tail.truncate(1);
+    } else if first_after < tail.len() {
+        let end = interpolate(tail[first_after - 1], tail[first_after], 1.0);
+        tail.truncate(first_after);
+        tail.push(end);
+    }
+    for stop in &mut tail {
+        stop.offset = stop.offset.clamp(0.0, 1.0);
+    }
+    stops.extend(tail);
+}
+
+/// Returns a stop at `offset`, which is between the offsets of `a` and `b`, with the color of
+/// the gradient there.
+///
+/// Colors are interpolated in sRGB, as they are when the ramp is baked.
+fn interpolate(a: ColorStop, b: ColorStop, offset: f32) -> ColorStop {
+    if a.offset == offset || b.offset - a.offset < 1e-9 {
+        return ColorStop { offset, ..a };
+    }
+    let t = (offset - a.offset) / (b.offset - a.offset);
+    let color = a.color.to_alpha_color::<Srgb>().lerp(
+        b.color.to_alpha_color::<Srgb>(),
+        t,
+        HueDirection::default(),
+    );
+    ColorStop {
+        offset,
+        color: DynamicColor::from_alpha_color(color),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::color::{palette, DynamicColor, Srgb};
+    use peniko::{ColorStop, ColorStops};
+
+    use super::{check_color_stops, normalize_color_stops, ColorStopsError};
+    use crate::ramp_cache::RampCache;
+
+    fn stops(stops: &[(f32, peniko::Color)]) -> ColorStops {
+        ColorStops(
+            stops
+                .iter()
+                .map(|(offset, color)| ColorStop {
+                    offset: *offset,
+                    color: DynamicColor::from_alpha_color(*color),
+                })
+                .collect(),
+        )
+    }
+
+    fn rgba8(color: DynamicColor) -> u32 {
+        color
+            .to_alpha_color::<Srgb>()
+            .premultiply()
+            .to_rgba8()
+            .to_u32()
+    }
+
+    #[test]
+    fn duplicate_offsets_make_hard_edges() {
+        let (red, blue) = (palette::css::RED, palette::css::BLUE);
+        let stops = stops(&[(0.0, red), (0.5, red), (0.5, blue), (1.0, blue)]);
+        assert_eq!(check_color_stops(&stops), Ok(()));
+        let mut cache = RampCache::default();
+        cache.add(&stops);
+        let ramp = cache.ramps();
+        let half = ramp.width as usize / 2;
+        assert_eq!(
+            ramp.data[half - 1],
+            rgba8(DynamicColor::from_alpha_color(red))
+        );
+        assert_eq!(ramp.data[half], rgba8(DynamicColor::from_alpha_color(blue)));
+    }
+
+    #[test]
+    fn unsorted_duplicates_keep_their_order() {
+        let (red, lime, blue) = (palette::css::RED, palette::css::LIME, palette::css::BLUE);
+        let mut unsorted = stops(&[(1.0, blue), (0.5, red), (0.0, red), (0.5, lime)]);
+        assert_eq!(
+            check_color_stops(&unsorted),
+            Err(ColorStopsError::Unsorted(1))
+        );
+        normalize_color_stops(&mut unsorted);
+        assert_eq!(
+            unsorted[..],
+            stops(&[(0.0, red), (0.5, red), (0.5, lime), (1.0, blue)])[..]
+        );
+    }
+
+    #[test]
+    fn out_of_range_stops_are_interpolated() {
+        let (black, white) = (palette::css::BLACK, palette::css::WHITE);
+        let mut wide = stops(&[(-1.0, black), (2.0, white), (f32::NAN, black)]);
+        assert_eq!(
+            check_color_stops(&wide),
+            Err(ColorStopsError::OutOfRange(0, -1.0))
+        );
+        normalize_color_stops(&mut wide);
+        assert_eq!(check_color_stops(&wide), Ok(()));
+        assert_eq!(wide.len(), 2);
+        let [start, end] = [wide[0], wide[1]].map(|stop| {
+            (
+                stop.offset,
+                stop.color.to_alpha_color::<Srgb>().to_rgba8().r,
+            )
+        });
+        assert_eq!(start, (0.0, 85));
+        assert_eq!(end, (1.0, 170));
+
+        // Stops which are all outside take the color of the nearest.
+        let mut after = stops(&[(1.5, black), (3.0, white)]);
+        normalize_color_stops(&mut after);
+        assert_eq!(after[..], stops(&[(1.0, black)])[..]);
+    }
+}
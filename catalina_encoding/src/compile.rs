@@ -0,0 +1,87 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Ahead of time compilation of static assets.
+
+use crate::{DecodeError, Encoding, Fragment, Resolver};
+
+/// Compiles an encoding into bytes to embed in a program, to be loaded with [`load_static`].
+///
+/// This is for assets which never change, such as icons and splash screens. A build script
+/// converts their vector source into an encoding, such as by drawing an SVG file parsed by an
+/// SVG library into a scene, and compiles it. Glyph runs are resolved into paths and dashed
+/// strokes into their dashes, so loading the asset needs neither fonts nor any parsing of its
+/// source, and images are embedded with it. Gradient ramps and images are allocated when the
+/// scene it's appended to is rendered, as they are for any fragment.
+///
+/// ```ignore
+/// // In build.rs:
+/// let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+/// std::fs::write(out_dir.join("logo.bin"), compile_static(&logo_encoding()))?;
+///
+/// // In the program:
+/// static LOGO: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/logo.bin"));
+/// let logo = load_static(LOGO)?;
+/// scene_encoding.append_fragment(&logo, &None);
+/// ```
+pub fn compile_static(encoding: &Encoding) -> Vec<u8> {
+    Resolver::new()
+        .resolve_fragment(encoding)
+        .encoding()
+        .to_bytes()
+}
+
+/// Loads an asset compiled by [`compile_static`], as a fragment which can be appended to
+/// other encodings.
+///
+/// Assets must be compiled by the same version of this crate, as the format of encodings
+/// changes between versions. Others return [`DecodeError::UnsupportedVersion`], which build
+/// scripts avoid by compiling assets with the version of the crate the program is built with.
+pub fn load_static(bytes: &[u8]) -> Result<Fragment, DecodeError> {
+    let encoding = Encoding::from_bytes(bytes)?;
+    let resources = &encoding.resources;
+    if !resources.glyph_runs.is_empty() || !resources.dashed_strokes.is_empty() {
+        return Err(DecodeError::Invalid(
+            "asset wasn't compiled with compile_static",
+        ));
+    }
+    Ok(Fragment { encoding })
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::color::palette;
+    use peniko::kurbo::{Affine, Rect, Shape, Stroke};
+    use peniko::{Brush, Fill};
+
+    use super::{compile_static, load_static};
+    use crate::{DecodeError, Encoding, Transform};
+
+    #[test]
+    fn compiled_assets_load_resolved() {
+        let mut encoding = Encoding::new();
+        encoding.encode_transform(Transform::from_kurbo(&Affine::translate((2.0, 3.0))));
+        encoding.encode_fill_style(Fill::NonZero);
+        encoding.encode_shape(&Rect::new(0.0, 0.0, 10.0, 10.0), true);
+        encoding.encode_brush(&Brush::Solid(palette::css::RED), 1.0);
+        let stroke = Stroke::new(1.0).with_dashes(0.0, [2.0, 1.0]);
+        encoding.encode_stroke_style(&stroke);
+        let line = Rect::new(0.0, 0.0, 10.0, 1.0).to_path(0.1);
+        encoding.encode_dashed_stroke(line, &stroke);
+        encoding.encode_brush(&Brush::Solid(palette::css::BLUE), 1.0);
+
+        let bytes = compile_static(&encoding);
+        let asset = load_static(&bytes).unwrap();
+        let resolved = asset.encoding();
+        assert!(resolved.resources.dashed_strokes.is_empty());
+        assert_eq!(resolved.draw_data, encoding.draw_data);
+        assert_eq!(resolved.transforms, encoding.transforms);
+        assert!(resolved.path_data.len() > encoding.path_data.len());
+
+        // Uncompiled encodings are rejected, as they would need resolving.
+        assert!(matches!(
+            load_static(&encoding.to_bytes()),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+}
@@ -151,6 +151,10 @@ pub struct ConfigUniform {
     pub blend_size: u32,
     /// Size of per-tile command list buffer allocation (in `u32`s).
     pub ptcl_size: u32,
+    /// First row of tiles processed by the fine stage.
+    ///
+    /// This is zero unless the fine stage is split into bands.
+    pub fine_row_offset: u32,
 }
 
 /// CPU side setup and configuration.
@@ -188,6 +192,7 @@ impl RenderConfig {
                 segments_size: buffer_sizes.segments.len(),
                 blend_size: buffer_sizes.blend_spill.len(),
                 ptcl_size: buffer_sizes.ptcl.len(),
+                fine_row_offset: 0,
                 layout: *layout,
             },
             workgroup_counts,
@@ -200,6 +205,9 @@ impl RenderConfig {
 pub type WorkgroupSize = (u32, u32, u32);
 
 /// Computed sizes for all dispatches.
+///
+/// Stages with no workgroups aren't dispatched. The indirect dispatches of `path_count` and
+/// `path_tiling` are skipped along with their setup stages.
 #[derive(Copy, Clone, Debug, Default)]
 pub struct WorkgroupCounts {
     pub use_large_path_scan: bool,
@@ -250,6 +258,18 @@ impl WorkgroupCounts {
         let path_wgs = n_paths.div_ceil(PATH_BBOX_WG);
         let width_in_bins = width_in_tiles.div_ceil(16);
         let height_in_bins = height_in_tiles.div_ceil(16);
+        let coarse = (width_in_bins, height_in_bins, 1);
+        let fine = (width_in_tiles, height_in_tiles, 1);
+        if n_draw_objects == 0 {
+            // Nothing is drawn, so coarse rasterization only has to write an empty command
+            // list for each tile, which fine rasterization fills with the base color. The
+            // other stages would only process transforms and styles.
+            return Self {
+                coarse,
+                fine,
+                ..Self::default()
+            };
+        }
         Self {
             use_large_path_scan,
             path_reduce: (path_tag_wgs, 1, 1),
@@ -266,9 +286,9 @@ impl WorkgroupCounts {
             tile_alloc: (path_wgs, 1, 1),
             path_count_setup: (1, 1, 1),
             backdrop: (path_wgs, 1, 1),
-            coarse: (width_in_bins, height_in_bins, 1),
+            coarse,
             path_tiling_setup: (1, 1, 1),
-            fine: (width_in_tiles, height_in_tiles, 1),
+            fine,
         }
     }
 }
@@ -434,3 +454,31 @@ impl BufferSizes {
 const fn align_up(len: u32, alignment: u32) -> u32 {
     len + (len.wrapping_neg() & (alignment - 1))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::WorkgroupCounts;
+    use crate::Layout;
+
+    #[test]
+    fn empty_scenes_only_run_coarse_and_fine() {
+        // Transforms and styles add path tags even when nothing is drawn.
+        let counts = WorkgroupCounts::new(&Layout::default(), 20, 10, 8);
+        assert_eq!(counts.coarse, (2, 1, 1));
+        assert_eq!(counts.fine, (20, 10, 1));
+        assert_eq!(counts.path_reduce, (0, 0, 0));
+        assert_eq!(counts.flatten, (0, 0, 0));
+        assert_eq!(counts.path_count_setup, (0, 0, 0));
+        assert_eq!(counts.path_tiling_setup, (0, 0, 0));
+
+        let layout = Layout {
+            n_draw_objects: 1,
+            n_paths: 1,
+            ..Layout::default()
+        };
+        let counts = WorkgroupCounts::new(&layout, 20, 10, 8);
+        assert_eq!(counts.flatten, (1, 1, 1));
+        assert_eq!(counts.path_count_setup, (1, 1, 1));
+        assert_eq!(counts.path_tiling_setup, (1, 1, 1));
+    }
+}
@@ -36,6 +36,12 @@ impl DrawTag {
     /// Blurred rounded rectangle.
     pub const BLUR_RECT: Self = Self(0x2d4); // info: 11, scene: 5 (DrawBlurRoundedRect)
 
+    /// Hatch pattern fill.
+    pub const HATCH: Self = Self(0x214); // info: 8, scene: 5 (DrawHatch)
+
+    /// Custom paint, evaluated by a shader function registered with the renderer.
+    pub const CUSTOM: Self = Self(0x714); // info: 12, scene: 5 (DrawCustom)
+
     /// Begin layer/clip.
     pub const BEGIN_CLIP: Self = Self(0x9);
 
@@ -164,8 +170,11 @@ pub struct DrawImage {
     pub xy: u32,
     /// Packed image dimensions.
     pub width_height: u32,
-    /// Packed quality, extend mode and 8-bit alpha (bits `qqxxyyaaaaaaaa`,
-    /// 18 unused prefix bits).
+    /// Packed downscale, quality, extend mode and 8-bit alpha (bits
+    /// `ssssqqxxyyaaaaaaaa`, 14 unused prefix bits).
+    ///
+    /// The downscale is the power of two by which the image in the atlas was scaled down to
+    /// fit, which is only set when the encoding is resolved.
     pub sample_alpha: u32,
 }
 
@@ -185,6 +194,35 @@ pub struct DrawBlurRoundedRect {
     pub std_dev: f32,
 }
 
+/// Draw data for a hatch pattern of parallel lines.
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct DrawHatch {
+    /// Color of the lines.
+    pub color: DrawColor,
+    /// Angle of the lines in radians, clockwise from the x axis.
+    pub angle: f32,
+    /// Distance between the centers of adjacent lines.
+    pub spacing: f32,
+    /// Width of each line.
+    pub line_width: f32,
+    /// Nonzero if a second set of lines is drawn at right angles to the first.
+    pub cross: u32,
+}
+
+/// The number of words of data passed to a custom paint.
+pub const CUSTOM_PAINT_DATA_WORDS: usize = 4;
+
+/// Draw data for a custom paint.
+#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct DrawCustom {
+    /// Index of the paint function in the order it was registered.
+    pub id: u32,
+    /// Data passed to the paint function.
+    pub data: [u32; CUSTOM_PAINT_DATA_WORDS],
+}
+
 /// Draw data for a clip or layer.
 #[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
 #[repr(C)]
@@ -196,6 +234,13 @@ pub struct DrawBeginClip {
 }
 
 impl DrawBeginClip {
+    /// The flag set in the blend mode of non-isolated layers, whose content is drawn onto
+    /// their backdrop rather than onto a transparent layer.
+    pub const NON_ISOLATED: u32 = 1 << 16;
+    /// The flag set in the blend mode of knockout layers, in which each draw replaces the
+    /// earlier draws beneath it rather than being composed over them.
+    pub const KNOCKOUT: u32 = 1 << 17;
+
     /// Creates new clip draw data.
     pub fn new(blend_mode: BlendMode, alpha: f32) -> Self {
         Self {
@@ -203,6 +248,32 @@ impl DrawBeginClip {
             alpha,
         }
     }
+
+    /// Creates new clip draw data for a non-isolated layer.
+    pub fn new_non_isolated(alpha: f32) -> Self {
+        let mut clip = Self::new(BlendMode::default(), alpha);
+        clip.blend_mode |= Self::NON_ISOLATED;
+        clip
+    }
+
+    /// Makes the layer a knockout layer.
+    #[must_use]
+    pub fn with_knockout(mut self) -> Self {
+        self.blend_mode |= Self::KNOCKOUT;
+        self
+    }
+
+    /// Returns whether the content of the layer is drawn onto a transparent layer, rather
+    /// than onto its backdrop.
+    pub fn is_isolated(&self) -> bool {
+        self.blend_mode & Self::NON_ISOLATED == 0
+    }
+
+    /// Returns whether each draw in the layer replaces the earlier draws beneath it, being
+    /// composed over the pixels the layer started with rather than over them.
+    pub fn is_knockout(&self) -> bool {
+        self.blend_mode & Self::KNOCKOUT != 0
+    }
 }
 
 /// Monoid for the draw tag stream.
@@ -2,14 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 use super::{
-    DrawBlurRoundedRect, DrawColor, DrawImage, DrawLinearGradient, DrawRadialGradient,
-    DrawSweepGradient, DrawTag, Glyph, GlyphRun, NormalizedCoord, Patch, PathEncoder, PathTag,
-    Style, Transform,
+    DrawBeginClip, DrawBlurRoundedRect, DrawColor, DrawCustom, DrawHatch, DrawImage,
+    DrawLinearGradient, DrawRadialGradient, DrawSweepGradient, DrawTag, Fragment, Glyph, GlyphRun,
+    NormalizedCoord, Patch, PathEncoder, PathTag, Placeholder, Style, Transform,
+    CUSTOM_PAINT_DATA_WORDS,
 };
+use crate::color_stops::{check_color_stops, normalize_tail};
 
 use peniko::color::{palette, DynamicColor};
-use peniko::kurbo::{Shape, Stroke};
-use peniko::{BlendMode, BrushRef, ColorStop, Extend, Fill, GradientKind, Image};
+use peniko::kurbo::{BezPath, Shape, Stroke};
+use peniko::{BlendMode, BrushRef, ColorStop, ColorStops, Extend, Fill, Font, GradientKind, Image};
 
 /// Encoded data streams for a scene.
 ///
@@ -85,10 +87,28 @@ impl Encoding {
 
     /// Appends another encoding to this one with an optional transform.
     pub fn append(&mut self, other: &Self, transform: &Option<Transform>) {
+        self.append_with_remap(other, transform, &mut KeepResources);
+    }
+
+    /// Appends another encoding to this one with an optional transform, replacing the images,
+    /// fonts and gradient stops it references as chosen by `remap`.
+    ///
+    /// This is for merging encodings built separately, such as on other threads or in other
+    /// processes, whose resources must be swapped for their counterparts in this encoding's
+    /// context, such as images decoded again from the same source. The references to late
+    /// bound resources in `other`, which are indices into its resource buffers and offsets
+    /// into its streams, are rebased onto the end of this encoding, and its resource patches
+    /// are merged with this encoding's, so it is resolved as if it had been encoded here.
+    pub fn append_with_remap(
+        &mut self,
+        other: &Self,
+        transform: &Option<Transform>,
+        remap: &mut impl ResourceRemap,
+    ) {
         let glyph_runs_base = {
             let offsets = self.stream_offsets();
-            let stops_base = self.resources.color_stops.len();
             let glyph_runs_base = self.resources.glyph_runs.len();
+            let dashed_strokes_base = self.resources.dashed_strokes.len();
             let glyphs_base = self.resources.glyphs.len();
             let coords_base = self.resources.normalized_coords.len();
             self.resources
@@ -100,6 +120,9 @@ impl Encoding {
             self.resources
                 .glyph_runs
                 .extend(other.resources.glyph_runs.iter().cloned().map(|mut run| {
+                    if let Some(font) = remap.font(&run.font) {
+                        run.font = font;
+                    }
                     run.glyphs.start += glyphs_base;
                     run.glyphs.end += glyphs_base;
                     run.normalized_coords.start += coords_base;
@@ -112,6 +135,23 @@ impl Encoding {
                     run.stream_offsets.styles += offsets.styles;
                     run
                 }));
+            self.resources.dashed_strokes.extend(
+                other
+                    .resources
+                    .dashed_strokes
+                    .iter()
+                    .cloned()
+                    .map(|mut stroke| {
+                        stroke.stream_offsets.path_tags += offsets.path_tags;
+                        stroke.stream_offsets.path_data += offsets.path_data;
+                        stroke.stream_offsets.draw_tags += offsets.draw_tags;
+                        stroke.stream_offsets.draw_data += offsets.draw_data;
+                        stroke.stream_offsets.transforms += offsets.transforms;
+                        stroke.stream_offsets.styles += offsets.styles;
+                        stroke
+                    }),
+            );
+            let color_stops = &mut self.resources.color_stops;
             self.resources
                 .patches
                 .extend(other.resources.patches.iter().map(|patch| match patch {
@@ -120,7 +160,13 @@ impl Encoding {
                         stops,
                         extend,
                     } => {
-                        let stops = stops.start + stops_base..stops.end + stops_base;
+                        let original = &other.resources.color_stops[stops.clone()];
+                        let start = color_stops.len();
+                        match remap.color_stops(original) {
+                            Some(remapped) => color_stops.extend_from_slice(&remapped),
+                            None => color_stops.extend_from_slice(original),
+                        }
+                        let stops = start..color_stops.len();
                         Patch::Ramp {
                             draw_data_offset: offset + offsets.draw_data,
                             stops,
@@ -130,17 +176,19 @@ impl Encoding {
                     Patch::GlyphRun { index } => Patch::GlyphRun {
                         index: index + glyph_runs_base,
                     },
+                    Patch::DashedStroke { index } => Patch::DashedStroke {
+                        index: index + dashed_strokes_base,
+                    },
                     Patch::Image {
                         image,
                         draw_data_offset,
+                        placeholder,
                     } => Patch::Image {
-                        image: image.clone(),
+                        image: remap.image(image).unwrap_or_else(|| image.clone()),
                         draw_data_offset: *draw_data_offset + offsets.draw_data,
+                        placeholder: *placeholder,
                     },
                 }));
-            self.resources
-                .color_stops
-                .extend_from_slice(&other.resources.color_stops);
             glyph_runs_base
         };
         self.path_tags.extend_from_slice(&other.path_tags);
@@ -164,6 +212,15 @@ impl Encoding {
         self.styles.extend_from_slice(&other.styles);
     }
 
+    /// Appends a fragment with resolved glyph runs to this encoding with an optional
+    /// transform.
+    ///
+    /// Unlike [`append`](Self::append), this adds no glyph runs to be resolved when the
+    /// encoding is rendered.
+    pub fn append_fragment(&mut self, fragment: &Fragment, transform: &Option<Transform>) {
+        self.append(fragment.encoding(), transform);
+    }
+
     /// Returns a snapshot of the current stream offsets.
     pub fn stream_offsets(&self) -> StreamOffsets {
         StreamOffsets {
@@ -247,6 +304,25 @@ impl Encoding {
         encoder.finish(true) != 0
     }
 
+    /// Encodes a stroke whose dashes are computed when the encoding is resolved, so that the
+    /// dash offset can be changed with [`Resolver::set_dash_phase`](crate::Resolver::set_dash_phase)
+    /// without encoding the path again.
+    ///
+    /// The transform and stroke style must be encoded before this, and the brush after it.
+    pub fn encode_dashed_stroke(&mut self, path: BezPath, style: &Stroke) {
+        let index = self.resources.dashed_strokes.len();
+        self.resources.dashed_strokes.push(DashedStroke {
+            path,
+            dash_offset: style.dash_offset,
+            dash_pattern: style.dash_pattern.to_vec(),
+            stream_offsets: self.stream_offsets(),
+        });
+        self.resources.patches.push(Patch::DashedStroke { index });
+        // The segments are inserted before the path marker when resolved.
+        self.path_tags.push(PathTag::PATH);
+        self.n_paths += 1;
+    }
+
     /// Encodes a brush with an optional alpha modifier.
     #[expect(
         single_use_lifetimes,
@@ -399,6 +475,19 @@ impl Encoding {
 
     /// Encodes an image brush.
     pub fn encode_image(&mut self, image: &Image, alpha: f32) {
+        self.encode_image_with_placeholder(image, alpha, None);
+    }
+
+    /// Encodes an image brush, with what to draw until the image is uploaded.
+    ///
+    /// If `placeholder` is `None`, the default of the [`UploadQueue`](crate::UploadQueue) is
+    /// drawn.
+    pub fn encode_image_with_placeholder(
+        &mut self,
+        image: &Image,
+        alpha: f32,
+        placeholder: Option<Placeholder>,
+    ) {
         let alpha = (alpha * image.alpha * 255.0).round() as u8;
         // TODO: feed the alpha multiplier through the full pipeline for consistency
         // with other brushes?
@@ -406,6 +495,7 @@ impl Encoding {
         self.resources.patches.push(Patch::Image {
             image: image.clone(),
             draw_data_offset: self.draw_data.len(),
+            placeholder,
         });
         self.draw_tags.push(DrawTag::IMAGE);
         self.draw_data
@@ -439,12 +529,53 @@ impl Encoding {
             }));
     }
 
+    /// Encodes a hatch pattern brush of parallel lines, which are crossed by a second set of
+    /// lines at right angles if `cross` is set.
+    ///
+    /// The lines are evaluated procedurally during fine rasterization, in the coordinates of
+    /// the brush transform.
+    pub fn encode_hatch(
+        &mut self,
+        color: impl Into<DrawColor>,
+        angle: f32,
+        spacing: f32,
+        line_width: f32,
+        cross: bool,
+    ) {
+        self.draw_tags.push(DrawTag::HATCH);
+        self.draw_data
+            .extend_from_slice(bytemuck::bytes_of(&DrawHatch {
+                color: color.into(),
+                angle,
+                spacing,
+                line_width,
+                cross: cross as u32,
+            }));
+    }
+
+    /// Encodes a custom paint brush, which is evaluated by the registered paint function `id`
+    /// during fine rasterization, in the coordinates of the brush transform.
+    pub fn encode_custom_paint(&mut self, id: u32, data: [u32; CUSTOM_PAINT_DATA_WORDS]) {
+        self.draw_tags.push(DrawTag::CUSTOM);
+        self.draw_data
+            .extend_from_slice(bytemuck::bytes_of(&DrawCustom { id, data }));
+    }
+
     /// Encodes a begin clip command.
     pub fn encode_begin_clip(&mut self, blend_mode: BlendMode, alpha: f32) {
-        use super::DrawBeginClip;
+        self.encode_begin_clip_data(DrawBeginClip::new(blend_mode, alpha));
+    }
+
+    /// Encodes a begin clip command for a non-isolated layer, whose content is drawn onto its
+    /// backdrop, so that blend modes within it composite against everything beneath.
+    pub fn encode_begin_non_isolated_clip(&mut self, alpha: f32) {
+        self.encode_begin_clip_data(DrawBeginClip::new_non_isolated(alpha));
+    }
+
+    /// Encodes a begin clip command with the given draw data, such as for knockout layers.
+    pub fn encode_begin_clip_data(&mut self, clip: DrawBeginClip) {
         self.draw_tags.push(DrawTag::BEGIN_CLIP);
-        self.draw_data
-            .extend_from_slice(bytemuck::bytes_of(&DrawBeginClip::new(blend_mode, alpha)));
+        self.draw_data.extend_from_slice(bytemuck::bytes_of(&clip));
         self.n_clips += 1;
         self.n_open_clips += 1;
     }
@@ -489,6 +620,11 @@ impl Encoding {
         } else {
             self.resources.color_stops.extend(color_stops);
         }
+        // Stops which are unsorted or outside of the ramp would otherwise be baked into a
+        // ramp which jumps between colors.
+        if check_color_stops(&self.resources.color_stops[stops_start..]).is_err() {
+            normalize_tail(&mut self.resources.color_stops, stops_start);
+        }
         let stops_end = self.resources.color_stops.len();
         match stops_end - stops_start {
             0 => RampStops::Empty,
@@ -515,6 +651,40 @@ enum RampStops {
     Many,
 }
 
+/// Chooses the resources referenced by an encoding appended with
+/// [`Encoding::append_with_remap`].
+///
+/// Each method is called for every reference to a resource, and returns its replacement, or
+/// `None` to keep the original. Implementations which replace many references to the same
+/// resource may want to cache their replacements, such as by [`Blob::id`](peniko::Blob::id).
+pub trait ResourceRemap {
+    /// Returns the image to draw in place of `image`.
+    ///
+    /// The replacement should have the same size and sampling options, as those of the
+    /// original are already encoded in the draw data.
+    fn image(&mut self, image: &Image) -> Option<Image> {
+        let _ = image;
+        None
+    }
+
+    /// Returns the font to draw a glyph run with in place of `font`.
+    fn font(&mut self, font: &Font) -> Option<Font> {
+        let _ = font;
+        None
+    }
+
+    /// Returns the color stops of a gradient in place of `stops`, which must not be empty.
+    fn color_stops(&mut self, stops: &[ColorStop]) -> Option<ColorStops> {
+        let _ = stops;
+        None
+    }
+}
+
+/// The remapping used by [`Encoding::append`], which keeps every resource.
+struct KeepResources;
+
+impl ResourceRemap for KeepResources {}
+
 /// Encoded data for late bound resources.
 #[derive(Clone, Default)]
 pub struct Resources {
@@ -528,6 +698,8 @@ pub struct Resources {
     pub glyph_runs: Vec<GlyphRun>,
     /// Normalized coordinate buffer for variable fonts.
     pub normalized_coords: Vec<NormalizedCoord>,
+    /// Strokes which are dashed when the encoding is resolved.
+    pub dashed_strokes: Vec<DashedStroke>,
 }
 
 impl Resources {
@@ -540,9 +712,23 @@ impl Resources {
         self.glyphs.clear();
         self.glyph_runs.clear();
         self.normalized_coords.clear();
+        self.dashed_strokes.clear();
     }
 }
 
+/// A stroke whose dashes are computed when the encoding is resolved.
+#[derive(Clone, Debug)]
+pub struct DashedStroke {
+    /// The path to dash, in the coordinates of its transform.
+    pub path: BezPath,
+    /// The dash offset of the stroke style, to which the dash phase of the resolver is added.
+    pub dash_offset: f64,
+    /// The lengths of the dashes and gaps.
+    pub dash_pattern: Vec<f64>,
+    /// Stream offsets where the dashed segments should be inserted.
+    pub stream_offsets: StreamOffsets,
+}
+
 /// Snapshot of offsets for encoded streams.
 #[derive(Copy, Clone, Default, Debug)]
 pub struct StreamOffsets {
@@ -596,4 +782,89 @@ mod tests {
             Extend::Pad | Extend::Repeat | Extend::Reflect => {}
         }
     }
+
+    #[test]
+    fn append_with_remap_replaces_resources() {
+        use crate::{Encoding, Patch, ResourceRemap};
+        use peniko::color::palette;
+        use peniko::{Blob, ColorStop, ColorStops, Gradient, Image, ImageFormat};
+        use std::sync::Arc;
+
+        struct Remap(Image);
+
+        impl ResourceRemap for Remap {
+            fn image(&mut self, _: &Image) -> Option<Image> {
+                Some(self.0.clone())
+            }
+
+            fn color_stops(&mut self, stops: &[ColorStop]) -> Option<ColorStops> {
+                Some(ColorStops(stops.iter().rev().copied().collect()))
+            }
+        }
+
+        let image = |pixel: u8| {
+            let data = Blob::new(Arc::new(vec![pixel; 4]));
+            Image::new(data, ImageFormat::Rgba8, 1, 1)
+        };
+        let gradient = Gradient::new_linear((0.0, 0.0), (1.0, 0.0))
+            .with_stops([palette::css::RED, palette::css::BLUE]);
+        let mut other = Encoding::new();
+        other.encode_brush(&gradient, 1.0);
+        other.encode_image(&image(0), 1.0);
+
+        let replacement = image(255);
+        let mut encoding = Encoding::new();
+        encoding.encode_brush(&gradient, 1.0);
+        encoding.append_with_remap(&other, &None, &mut Remap(replacement.clone()));
+
+        let patches = &encoding.resources.patches;
+        assert_eq!(patches.len(), 3);
+        let Patch::Ramp { stops, .. } = &patches[1] else {
+            panic!("expected a ramp");
+        };
+        assert_eq!(*stops, 2..4);
+        let stops = &encoding.resources.color_stops[stops.clone()];
+        assert_eq!(stops[0].color, gradient.stops[1].color);
+        let Patch::Image {
+            image,
+            draw_data_offset,
+            ..
+        } = &patches[2]
+        else {
+            panic!("expected an image");
+        };
+        assert_eq!(image.data.id(), replacement.data.id());
+        assert_eq!(*draw_data_offset, encoding.draw_data.len() - 12);
+    }
+
+    #[test]
+    fn dash_phase_is_late_bound() {
+        use crate::{Encoding, Resolver};
+        use peniko::kurbo::{BezPath, Line, Shape, Stroke};
+
+        let mut encoding = Encoding::new();
+        let style = Stroke::new(1.0).with_dashes(0.0, [10.0, 10.0]);
+        let path: BezPath = Line::new((0.0, 0.0), (40.0, 0.0)).into_path(0.1);
+        encoding.encode_stroke_style(&style);
+        encoding.encode_dashed_stroke(path, &style);
+        assert_eq!(encoding.n_paths, 1);
+
+        // The dashes aren't in order along the path, so this finds the leftmost point.
+        let first_x = |phase: f64| {
+            let mut resolver = Resolver::new();
+            resolver.set_dash_phase(phase);
+            let fragment = resolver.resolve_fragment(&encoding);
+            let resolved = fragment.encoding();
+            assert_eq!(resolved.n_paths, 1);
+            // Each dash is a line and the marker segment for its caps.
+            assert_eq!(resolved.n_path_segments, 4);
+            resolved
+                .path_data
+                .chunks_exact(8)
+                .map(|point| bytemuck::pod_read_unaligned::<f32>(&point[0..4]))
+                .fold(f32::INFINITY, f32::min)
+        };
+        assert_eq!(first_x(0.0), 0.0);
+        assert_eq!(first_x(10.0), 10.0);
+    }
 }
@@ -0,0 +1,367 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Generational handles to shared resources.
+
+use std::collections::HashMap;
+
+use peniko::{Font, Image};
+
+use crate::Fragment;
+
+/// The index of a slot in a [`Slots`] and the generation of the value in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct RawId {
+    index: u32,
+    generation: u32,
+}
+
+impl RawId {
+    fn to_bits(self) -> u64 {
+        (u64::from(self.generation) << 32) | u64::from(self.index)
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        Self {
+            index: bits as u32,
+            generation: (bits >> 32) as u32,
+        }
+    }
+}
+
+macro_rules! handle {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        ///
+        /// Handles are small, `Copy` and `Send`, so can be passed between threads in place of
+        /// the resource. A handle outlives the resource it refers to, and looking it up after
+        /// the resource was removed returns `None`, even if the slot was reused by another
+        /// resource.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(RawId);
+
+        impl $name {
+            /// Returns the handle as a number, which stays the same between runs of a
+            /// program which inserts the same resources in the same order, so can be
+            /// serialized.
+            pub fn to_bits(self) -> u64 {
+                self.0.to_bits()
+            }
+
+            /// Returns the handle with the number returned by [`to_bits`](Self::to_bits).
+            ///
+            /// Any number is a valid handle, though most won't refer to a resource.
+            pub fn from_bits(bits: u64) -> Self {
+                Self(RawId::from_bits(bits))
+            }
+        }
+    };
+}
+
+handle!(
+    /// A handle to an image in a [`ResourceTable`].
+    ImageId
+);
+handle!(
+    /// A handle to a font in a [`ResourceTable`].
+    FontId
+);
+handle!(
+    /// A handle to a fragment in a [`ResourceTable`].
+    ///
+    /// This is unrelated to the fragments of a `World` in Catalina, which have their own ids.
+    EncodingFragmentId
+);
+
+/// A slot in a [`Slots`].
+#[derive(Clone, Debug)]
+struct Slot<T> {
+    /// The generation of the current value, or of the next value if the slot is empty.
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Values stored in reusable slots, indexed by [`RawId`].
+#[derive(Clone, Debug)]
+struct Slots<T> {
+    slots: Vec<Slot<T>>,
+    /// The indices of the empty slots.
+    free: Vec<u32>,
+}
+
+impl<T> Default for Slots<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> Slots<T> {
+    fn insert(&mut self, value: T) -> RawId {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: None,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.value = Some(value);
+        RawId {
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    fn get(&self, id: RawId) -> Option<&T> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    fn remove(&mut self, id: RawId) -> Option<T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        // A slot whose generations are used up is never reused, so that its old handles
+        // can't refer to a new value.
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(id.index);
+        }
+        Some(value)
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+}
+
+/// The resource which owns a blob, used to serialize references to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BlobRef {
+    Image(ImageId),
+    Font(FontId),
+}
+
+/// Shared images, fonts and fragments, referred to by generational handles.
+///
+/// A table lets scenes and the code building them refer to resources by small, stable
+/// handles rather than by the identity of their data. Resources can be removed when they're
+/// no longer needed, and stale handles are detected rather than referring to whatever took
+/// their place. Encodings can be serialized with
+/// [`Encoding::to_bytes_with_table`](crate::Encoding::to_bytes_with_table), which writes the
+/// handles of the images and fonts in the table in place of their data.
+///
+/// The fragment type defaults to [`Fragment`], but can be any type, such as a scene.
+#[derive(Clone, Debug)]
+pub struct ResourceTable<F = Fragment> {
+    images: Slots<Image>,
+    fonts: Slots<Font>,
+    fragments: Slots<F>,
+    /// The resource which owns each blob in the table, by blob id.
+    blobs: HashMap<u64, BlobRef>,
+}
+
+impl<F> Default for ResourceTable<F> {
+    fn default() -> Self {
+        Self {
+            images: Slots::default(),
+            fonts: Slots::default(),
+            fragments: Slots::default(),
+            blobs: HashMap::new(),
+        }
+    }
+}
+
+impl<F> ResourceTable<F> {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an image to the table, returning its handle.
+    pub fn insert_image(&mut self, image: Image) -> ImageId {
+        let blob = image.data.id();
+        let id = ImageId(self.images.insert(image));
+        self.blobs.entry(blob).or_insert(BlobRef::Image(id));
+        id
+    }
+
+    /// Returns the image with the given handle, or `None` if it was removed.
+    pub fn image(&self, id: ImageId) -> Option<&Image> {
+        self.images.get(id.0)
+    }
+
+    /// Removes the image with the given handle from the table, returning it.
+    ///
+    /// The image stays in the atlas of a renderer until it's no longer drawn, or until it's
+    /// [cancelled](crate::UploadQueue::cancel) if its upload is still queued.
+    pub fn remove_image(&mut self, id: ImageId) -> Option<Image> {
+        let image = self.images.remove(id.0)?;
+        self.forget_blob(image.data.id(), BlobRef::Image(id));
+        Some(image)
+    }
+
+    /// Returns the handle of an image in the table with the same data as `image`.
+    pub fn image_id(&self, image: &Image) -> Option<ImageId> {
+        match self.blobs.get(&image.data.id()) {
+            Some(BlobRef::Image(id)) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of images in the table.
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Adds a font to the table, returning its handle.
+    pub fn insert_font(&mut self, font: Font) -> FontId {
+        let blob = font.data.id();
+        let id = FontId(self.fonts.insert(font));
+        self.blobs.entry(blob).or_insert(BlobRef::Font(id));
+        id
+    }
+
+    /// Returns the font with the given handle, or `None` if it was removed.
+    pub fn font(&self, id: FontId) -> Option<&Font> {
+        self.fonts.get(id.0)
+    }
+
+    /// Removes the font with the given handle from the table, returning it.
+    pub fn remove_font(&mut self, id: FontId) -> Option<Font> {
+        let font = self.fonts.remove(id.0)?;
+        self.forget_blob(font.data.id(), BlobRef::Font(id));
+        Some(font)
+    }
+
+    /// Returns the handle of a font in the table with the same data as `font`.
+    pub fn font_id(&self, font: &Font) -> Option<FontId> {
+        match self.blobs.get(&font.data.id()) {
+            Some(BlobRef::Font(id)) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of fonts in the table.
+    pub fn font_count(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Adds a fragment to the table, returning its handle.
+    pub fn insert_fragment(&mut self, fragment: F) -> EncodingFragmentId {
+        EncodingFragmentId(self.fragments.insert(fragment))
+    }
+
+    /// Returns the fragment with the given handle, or `None` if it was removed.
+    pub fn fragment(&self, id: EncodingFragmentId) -> Option<&F> {
+        self.fragments.get(id.0)
+    }
+
+    /// Removes the fragment with the given handle from the table, returning it.
+    pub fn remove_fragment(&mut self, id: EncodingFragmentId) -> Option<F> {
+        self.fragments.remove(id.0)
+    }
+
+    /// Returns the number of fragments in the table.
+    pub fn fragment_count(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Returns the resource which owns the blob with the given id.
+    pub(crate) fn blob_ref(&self, blob: u64) -> Option<BlobRef> {
+        self.blobs.get(&blob).copied()
+    }
+
+    /// Returns the data of the resource referred to by `blob`.
+    pub(crate) fn blob(&self, blob: BlobRef) -> Option<&peniko::Blob<u8>> {
+        match blob {
+            BlobRef::Image(id) => self.image(id).map(|image| &image.data),
+            BlobRef::Font(id) => self.font(id).map(|font| &font.data),
+        }
+    }
+
+    fn forget_blob(&mut self, blob: u64, owner: BlobRef) {
+        if self.blobs.get(&blob) == Some(&owner) {
+            self.blobs.remove(&blob);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use peniko::kurbo::Rect;
+    use peniko::{Blob, Fill, Image, ImageFormat};
+
+    use super::{ImageId, ResourceTable};
+    use crate::{DecodeError, Encoding};
+
+    fn image() -> Image {
+        Image::new(Blob::new(Arc::new(vec![0_u8; 4])), ImageFormat::Rgba8, 1, 1)
+    }
+
+    #[test]
+    fn stale_handles_are_rejected() {
+        let mut table = ResourceTable::<()>::new();
+        let first = table.insert_image(image());
+        assert!(table.image(first).is_some());
+        assert!(table.remove_image(first).is_some());
+        assert!(table.image(first).is_none());
+        assert!(table.remove_image(first).is_none());
+
+        // The slot is reused with a new generation.
+        let second = table.insert_image(image());
+        assert_ne!(first, second);
+        assert!(table.image(first).is_none());
+        assert!(table.image(second).is_some());
+        assert_eq!(table.image_count(), 1);
+    }
+
+    #[test]
+    fn handles_round_trip_through_bits() {
+        let mut table = ResourceTable::<()>::new();
+        let image = image();
+        let id = table.insert_image(image.clone());
+        assert_eq!(ImageId::from_bits(id.to_bits()), id);
+        assert_eq!(table.image_id(&image), Some(id));
+        table.remove_image(id);
+        assert_eq!(table.image_id(&image), None);
+    }
+
+    #[test]
+    fn serialized_encodings_refer_to_the_table() {
+        let mut table = ResourceTable::<()>::new();
+        let image = image();
+        let id = table.insert_image(image.clone());
+        let mut encoding = Encoding::new();
+        encoding.encode_fill_style(Fill::NonZero);
+        encoding.encode_shape(&Rect::new(0.0, 0.0, 1.0, 1.0), true);
+        encoding.encode_image(&image, 1.0);
+
+        let bytes = encoding.to_bytes_with_table(&table);
+        assert!(bytes.len() < encoding.to_bytes().len());
+        let decoded = Encoding::from_bytes_with_table(&bytes, &table).unwrap();
+        assert_eq!(decoded.draw_data, encoding.draw_data);
+        assert!(matches!(
+            Encoding::from_bytes(&bytes),
+            Err(DecodeError::MissingResource(_))
+        ));
+
+        table.remove_image(id);
+        assert_eq!(
+            Encoding::from_bytes_with_table(&bytes, &table).err(),
+            Some(DecodeError::MissingResource(id.to_bits()))
+        );
+    }
+}
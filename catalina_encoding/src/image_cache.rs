@@ -1,10 +1,10 @@
 // Copyright 2022-2025 the Catalina & Vello Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use guillotiere::{size2, AtlasAllocator};
-use peniko::Image;
-use std::collections::hash_map::Entry;
+use guillotiere::{size2, AllocId, AtlasAllocator};
+use peniko::{Blob, Image};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 const DEFAULT_ATLAS_SIZE: i32 = 1024;
 const MAX_ATLAS_SIZE: i32 = 8192;
@@ -14,14 +14,32 @@ pub struct Images<'a> {
     pub width: u32,
     pub height: u32,
     pub images: &'a [(Image, u32, u32)],
+    /// The number of images at the start of `images` which were already in the atlas
+    /// in the previous frame.
+    pub written: usize,
 }
 
+/// Location of an image in the atlas.
+struct Slot {
+    alloc: AllocId,
+    x: u32,
+    y: u32,
+    /// The frame in which the image was last used.
+    epoch: u64,
+}
+
+/// Allocations in the image atlas, which are retained between frames while they're used.
 pub(crate) struct ImageCache {
     atlas: AtlasAllocator,
     /// Map from image blob id to atlas location.
-    map: HashMap<u64, (u32, u32)>,
+    map: HashMap<u64, Slot>,
     /// List of all allocated images with associated atlas location.
     images: Vec<(Image, u32, u32)>,
+    /// The number of images at the start of `images` which were allocated before this frame.
+    written: usize,
+    epoch: u64,
+    /// The largest width and height the atlas may grow to.
+    max_size: i32,
 }
 
 impl Default for ImageCache {
@@ -36,6 +54,9 @@ impl ImageCache {
             atlas: AtlasAllocator::new(size2(DEFAULT_ATLAS_SIZE, DEFAULT_ATLAS_SIZE)),
             map: HashMap::default(),
             images: Vec::default(),
+            written: 0,
+            epoch: 0,
+            max_size: MAX_ATLAS_SIZE,
         }
     }
 
@@ -44,38 +65,236 @@ impl ImageCache {
             width: self.atlas.size().width as u32,
             height: self.atlas.size().height as u32,
             images: &self.images,
+            written: self.written,
+        }
+    }
+
+    /// Starts a new frame.
+    pub(crate) fn maintain(&mut self) {
+        self.epoch += 1;
+        self.written = self.images.len();
+    }
+
+    /// Limits the size of the atlas, such as to the maximum texture dimension of the device.
+    pub(crate) fn set_max_size(&mut self, max_size: u32) {
+        self.max_size = max_size.min(MAX_ATLAS_SIZE as u32) as i32;
+        if self.atlas.size().width > self.max_size {
+            // Every image needs to be written again, as when the atlas grows.
+            self.atlas = AtlasAllocator::new(size2(self.max_size, self.max_size));
+            self.map.clear();
+            self.images.clear();
+            self.written = 0;
         }
     }
 
+    /// Returns the largest width and height the atlas may grow to.
+    pub(crate) fn max_size(&self) -> u32 {
+        self.max_size as u32
+    }
+
+    /// Returns `true` if the image is small enough to ever fit in the atlas.
+    pub(crate) fn fits(&self, image: &Image) -> bool {
+        image.width <= self.max_size() && image.height <= self.max_size()
+    }
+
+    /// Doubles the size of the atlas up to its maximum, moving the images in it to new
+    /// locations.
+    ///
+    /// All images need to be written again after this.
     pub(crate) fn bump_size(&mut self) -> bool {
-        let new_size = self.atlas.size().width * 2;
-        if new_size > MAX_ATLAS_SIZE {
+        let size = self.atlas.size().width;
+        let new_size = (size * 2).min(self.max_size);
+        if new_size <= size {
             return false;
         }
         self.atlas = AtlasAllocator::new(size2(new_size, new_size));
-        self.map.clear();
-        self.images.clear();
+        let epochs: HashMap<u64, u64> = self
+            .map
+            .drain()
+            .map(|(id, slot)| (id, slot.epoch))
+            .collect();
+        let images = std::mem::take(&mut self.images);
+        self.written = 0;
+        for (image, _, _) in images {
+            // The images which no longer fit are dropped, and uploaded again when next used.
+            self.allocate(&image, epochs[&image.data.id()]);
+        }
         true
     }
 
-    pub(crate) fn clear(&mut self) {
-        self.atlas.clear();
-        self.map.clear();
-        self.images.clear();
+    /// Returns the location of an image which is already in the atlas, and marks it as used.
+    pub(crate) fn get(&mut self, image: &Image) -> Option<(u32, u32)> {
+        let slot = self.map.get_mut(&image.data.id())?;
+        slot.epoch = self.epoch;
+        Some((slot.x, slot.y))
+    }
+
+    /// Adds an image to the atlas, evicting images which weren't used in this frame if there
+    /// isn't enough space.
+    pub(crate) fn insert(&mut self, image: &Image) -> Option<(u32, u32)> {
+        if let Some(xy) = self.get(image) {
+            return Some(xy);
+        }
+        if let Some(xy) = self.allocate(image, self.epoch) {
+            return Some(xy);
+        }
+        if self.evict_unused() {
+            self.allocate(image, self.epoch)
+        } else {
+            None
+        }
+    }
+
+    fn allocate(&mut self, image: &Image, epoch: u64) -> Option<(u32, u32)> {
+        let alloc = self
+            .atlas
+            .allocate(size2(image.width as _, image.height as _))?;
+        let x = alloc.rectangle.min.x as u32;
+        let y = alloc.rectangle.min.y as u32;
+        self.images.push((image.clone(), x, y));
+        self.map.insert(
+            image.data.id(),
+            Slot {
+                alloc: alloc.id,
+                x,
+                y,
+                epoch,
+            },
+        );
+        Some((x, y))
     }
 
-    pub(crate) fn get_or_insert(&mut self, image: &Image) -> Option<(u32, u32)> {
-        match self.map.entry(image.data.id()) {
-            Entry::Occupied(occupied) => Some(*occupied.get()),
-            Entry::Vacant(vacant) => {
-                let alloc = self
-                    .atlas
-                    .allocate(size2(image.width as _, image.height as _))?;
-                let x = alloc.rectangle.min.x as u32;
-                let y = alloc.rectangle.min.y as u32;
-                self.images.push((image.clone(), x, y));
-                Some(*vacant.insert((x, y)))
+    /// Frees the space of every image which wasn't used in this frame, returning `true` if
+    /// there were any.
+    fn evict_unused(&mut self) -> bool {
+        let epoch = self.epoch;
+        let atlas = &mut self.atlas;
+        self.map.retain(|_, slot| {
+            let used = slot.epoch == epoch;
+            if !used {
+                atlas.deallocate(slot.alloc);
             }
+            used
+        });
+        let map = &self.map;
+        let mut ix = 0;
+        let mut evicted_written = 0;
+        let written = self.written;
+        self.images.retain(|(image, _, _)| {
+            let keep = map.contains_key(&image.data.id());
+            if !keep && ix < written {
+                evicted_written += 1;
+            }
+            ix += 1;
+            keep
+        });
+        self.written -= evicted_written;
+        self.images.len() < ix
+    }
+}
+
+/// Scales `image` down by the smallest power of two which makes it fit in an atlas of
+/// `max_size`, returning the copy and the exponent of the scale.
+///
+/// Each pixel of the copy is the average of a square of pixels of the image, weighted by
+/// their alpha. The copy is rounded up to whole pixels, so the pixels at its right and bottom
+/// edges may average fewer pixels of the image.
+pub(crate) fn downscale(image: &Image, max_size: u32) -> (Image, u32) {
+    let mut shift = 0;
+    while image.width.div_ceil(1 << shift) > max_size
+        || image.height.div_ceil(1 << shift) > max_size
+    {
+        shift += 1;
+    }
+    let factor = 1_usize << shift;
+    let (width, height) = (image.width as usize, image.height as usize);
+    let (new_width, new_height) = (width.div_ceil(factor), height.div_ceil(factor));
+    let src = image.data.data();
+    let mut data = vec![0_u8; new_width * new_height * 4];
+    for (new_y, row) in data.chunks_exact_mut(new_width * 4).enumerate() {
+        for (new_x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let mut sums = [0_u64; 4];
+            let mut count = 0;
+            for y in new_y * factor..((new_y + 1) * factor).min(height) {
+                for x in new_x * factor..((new_x + 1) * factor).min(width) {
+                    let offset = (y * width + x) * 4;
+                    let rgba = &src[offset..offset + 4];
+                    let alpha = u64::from(rgba[3]);
+                    for (sum, value) in sums.iter_mut().zip(&rgba[..3]) {
+                        *sum += u64::from(*value) * alpha;
+                    }
+                    sums[3] += alpha;
+                    count += 1;
+                }
+            }
+            for (value, sum) in pixel.iter_mut().zip(&sums[..3]) {
+                if let Some(average) = sum.checked_div(sums[3]) {
+                    *value = average as u8;
+                }
+            }
+            pixel[3] = (sums[3] / count) as u8;
         }
     }
+    let copy = Image {
+        data: Blob::new(Arc::new(data)),
+        width: new_width as u32,
+        height: new_height as u32,
+        ..image.clone()
+    };
+    (copy, shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use peniko::{Blob, Image, ImageFormat};
+
+    use super::downscale;
+    use crate::{Encoding, OversizedImages, Resolver};
+
+    fn image(width: u32, height: u32, data: Vec<u8>) -> Image {
+        Image::new(Blob::new(Arc::new(data)), ImageFormat::Rgba8, width, height)
+    }
+
+    #[test]
+    fn downscale_weights_by_alpha() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let data = [red, [0, 255, 0, 0], blue].concat();
+        let (copy, shift) = downscale(&image(3, 1, data), 2);
+        assert_eq!(shift, 1);
+        assert_eq!((copy.width, copy.height), (2, 1));
+        // The transparent green doesn't affect the color of the first pixel.
+        assert_eq!(copy.data.data(), [255, 0, 0, 127, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn oversized_images() {
+        let mut encoding = Encoding::new();
+        encoding.encode_image(&image(20, 4, vec![255; 20 * 4 * 4]), 1.0);
+        let mut resolver = Resolver::new();
+        resolver.set_max_atlas_size(8);
+        let mut packed = Vec::new();
+        let (layout, _, images) = resolver.resolve(&encoding, &mut packed);
+        assert_eq!(images.images.len(), 1);
+        assert_eq!(
+            (images.images[0].0.width, images.images[0].0.height),
+            (5, 1)
+        );
+        let word = |ix: u32| -> u32 {
+            let offset = (layout.draw_data_base + ix) as usize * 4;
+            bytemuck::pod_read_unaligned(&packed[offset..offset + 4])
+        };
+        assert_eq!(word(1), (5 << 16) | 1);
+        assert_eq!(word(2) >> 14, 2);
+
+        let mut resolver = Resolver::new();
+        resolver.set_max_atlas_size(8);
+        resolver
+            .uploads_mut()
+            .set_oversized_images(OversizedImages::Skip);
+        let (_, _, images) = resolver.resolve(&encoding, &mut packed);
+        assert!(images.images.is_empty());
+    }
 }
@@ -0,0 +1,191 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Inspection of the layers of encoded scenes, for debugging clips and blends.
+
+use peniko::kurbo::{Affine, BezPath};
+use peniko::Mix;
+
+use super::{DrawBeginClip, DrawTag, Encoding, PathTag};
+
+/// A layer of an encoding, found by [`layers`].
+#[derive(Clone, Debug)]
+pub struct LayerInfo {
+    /// The index of the draw object which begins the layer.
+    pub draw: usize,
+    /// The number of layers this one is nested within, which is 0 for layers at the top
+    /// level of the scene.
+    pub depth: usize,
+    /// The shape the layer is clipped to, in device space.
+    ///
+    /// This is empty for clips whose segments are only added when the encoding is resolved,
+    /// such as glyph outlines.
+    pub clip: BezPath,
+    /// Whether the layer only clips its content, using [`Mix::Clip`], rather than blending
+    /// it.
+    pub is_clip: bool,
+    /// Whether the content of the layer is drawn onto a transparent layer, rather than onto
+    /// its backdrop.
+    pub is_isolated: bool,
+    /// Whether each draw in the layer replaces the earlier draws beneath it.
+    pub is_knockout: bool,
+    /// The opacity the content of the layer is composited with.
+    pub alpha: f32,
+}
+
+/// Finds the layers of `encoding` in the order they begin, with the shapes they are
+/// clipped to.
+///
+/// Like [`lint`](crate::lint), this walks every path in the encoding, so it's intended for
+/// debugging rather than for every frame.
+pub fn layers(encoding: &Encoding) -> Vec<LayerInfo> {
+    let glyph_run_draws: Vec<usize> = encoding
+        .resources
+        .glyph_runs
+        .iter()
+        .map(|run| run.stream_offsets.draw_tags)
+        .collect();
+    let mut paths = draw_paths(encoding).into_iter();
+    let mut layers = Vec::new();
+    let mut depth = 0;
+    // The byte offset of the data of the next draw object.
+    let mut data_offset = 0;
+    for (ix, tag) in encoding.draw_tags.iter().enumerate() {
+        let clip = if glyph_run_draws.contains(&ix) {
+            BezPath::new()
+        } else {
+            paths.next().unwrap_or_default()
+        };
+        if *tag == DrawTag::BEGIN_CLIP {
+            let data: DrawBeginClip = bytemuck::pod_read_unaligned(
+                &encoding.draw_data[data_offset..data_offset + size_of::<DrawBeginClip>()],
+            );
+            layers.push(LayerInfo {
+                draw: ix,
+                depth,
+                clip,
+                is_clip: data.blend_mode >> 8 == Mix::Clip as u32,
+                is_isolated: data.is_isolated(),
+                is_knockout: data.is_knockout(),
+                alpha: data.alpha,
+            });
+            depth += 1;
+        } else if *tag == DrawTag::END_CLIP {
+            depth = depth.saturating_sub(1);
+        }
+        // The number of words of draw data is stored in bits 2 to 4 of the tag.
+        data_offset += ((tag.0 >> 2) & 0x7) as usize * 4;
+    }
+    layers
+}
+
+/// Walks the path tag stream to find the path of each draw object, in device space.
+fn draw_paths(encoding: &Encoding) -> Vec<BezPath> {
+    let mut paths = Vec::new();
+    let mut path = BezPath::new();
+    let mut transform = Affine::IDENTITY;
+    let mut n_transforms = 0;
+    let mut is_subpath_start = true;
+    // The byte offset of the first point of the next segment in the path data stream.
+    let mut offset = 0;
+    for tag in &encoding.path_tags {
+        if *tag == PathTag::TRANSFORM {
+            transform = encoding.transforms[n_transforms].to_kurbo();
+            n_transforms += 1;
+        } else if *tag == PathTag::PATH {
+            paths.push(std::mem::take(&mut path));
+            is_subpath_start = true;
+        } else if tag.is_path_segment() {
+            let point_size = if tag.is_f32() { 8 } else { 4 };
+            let n_points = usize::from(tag.path_segment_type().0);
+            let point = |i: usize| {
+                transform
+                    * super::lint::read_point(
+                        &encoding.path_data,
+                        offset + i * point_size,
+                        tag.is_f32(),
+                    )
+            };
+            if is_subpath_start {
+                path.move_to(point(0));
+            }
+            match n_points {
+                1 => path.line_to(point(1)),
+                2 => path.quad_to(point(1), point(2)),
+                _ => path.curve_to(point(1), point(2), point(3)),
+            }
+            offset += n_points * point_size;
+            is_subpath_start = tag.is_subpath_end();
+            if is_subpath_start {
+                offset += point_size;
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::kurbo::{Affine, Rect, Shape};
+    use peniko::{color::palette, BlendMode, Compose, Fill, Mix};
+
+    use super::layers;
+    use crate::{Encoding, Transform};
+
+    fn fill(encoding: &mut Encoding, transform: Affine, rect: Rect) {
+        encoding.encode_transform(Transform::from_kurbo(&transform));
+        encoding.encode_fill_style(Fill::NonZero);
+        encoding.encode_shape(&rect, true);
+    }
+
+    #[test]
+    fn nested_layers() {
+        let mut encoding = Encoding::new();
+        fill(
+            &mut encoding,
+            Affine::IDENTITY,
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+        );
+        encoding.encode_color(palette::css::RED);
+        fill(
+            &mut encoding,
+            Affine::translate((100.0, 0.0)),
+            Rect::new(0.0, 0.0, 50.0, 50.0),
+        );
+        encoding.encode_begin_clip(BlendMode::new(Mix::Clip, Compose::SrcOver), 1.0);
+        fill(
+            &mut encoding,
+            Affine::scale(2.0),
+            Rect::new(10.0, 10.0, 20.0, 20.0),
+        );
+        encoding.encode_begin_clip(BlendMode::new(Mix::Normal, Compose::SrcOver), 0.5);
+        fill(
+            &mut encoding,
+            Affine::IDENTITY,
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+        );
+        encoding.encode_color(palette::css::RED);
+        encoding.encode_end_clip();
+        encoding.encode_end_clip();
+
+        let found = layers(&encoding);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].draw, 1);
+        assert_eq!(found[0].depth, 0);
+        assert!(found[0].is_clip);
+        assert_eq!(found[0].alpha, 1.0);
+        assert_eq!(
+            found[0].clip.bounding_box(),
+            Rect::new(100.0, 0.0, 150.0, 50.0)
+        );
+        assert_eq!(found[1].draw, 2);
+        assert_eq!(found[1].depth, 1);
+        assert!(!found[1].is_clip);
+        assert!(found[1].is_isolated);
+        assert_eq!(found[1].alpha, 0.5);
+        assert_eq!(
+            found[1].clip.bounding_box(),
+            Rect::new(20.0, 20.0, 40.0, 40.0)
+        );
+    }
+}
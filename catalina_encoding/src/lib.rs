@@ -32,6 +32,8 @@
 
 mod binning;
 mod clip;
+mod color_stops;
+mod compile;
 mod config;
 mod draw;
 mod encoding;
@@ -39,26 +41,38 @@ mod encoding;
 mod estimate;
 mod glyph;
 mod glyph_cache;
+mod handles;
 mod image_cache;
+mod layers;
+mod lint;
 mod mask;
 pub mod math;
 mod monoid;
 mod path;
+mod ptcl;
 mod ramp_cache;
 mod resolve;
+mod serialize;
+mod upload;
 
 pub use binning::BinHeader;
 pub use clip::{Clip, ClipBbox, ClipBic, ClipElement};
+pub use color_stops::{check_color_stops, normalize_color_stops, ColorStopsError};
+pub use compile::{compile_static, load_static};
 pub use config::{
     BufferSize, BufferSizes, BumpAllocatorMemory, BumpAllocators, ConfigUniform, IndirectCount,
     RenderConfig, WorkgroupCounts, WorkgroupSize,
 };
 pub use draw::{
-    DrawBbox, DrawBeginClip, DrawBlurRoundedRect, DrawColor, DrawImage, DrawLinearGradient,
-    DrawMonoid, DrawRadialGradient, DrawSweepGradient, DrawTag, DRAW_INFO_FLAGS_FILL_RULE_BIT,
+    DrawBbox, DrawBeginClip, DrawBlurRoundedRect, DrawColor, DrawCustom, DrawHatch, DrawImage,
+    DrawLinearGradient, DrawMonoid, DrawRadialGradient, DrawSweepGradient, DrawTag,
+    CUSTOM_PAINT_DATA_WORDS, DRAW_INFO_FLAGS_FILL_RULE_BIT,
 };
-pub use encoding::{Encoding, Resources, StreamOffsets};
+pub use encoding::{DashedStroke, Encoding, ResourceRemap, Resources, StreamOffsets};
 pub use glyph::{Glyph, GlyphRun};
+pub use handles::{EncodingFragmentId, FontId, ImageId, ResourceTable};
+pub use layers::{layers, LayerInfo};
+pub use lint::{lint, Lint, LintKind, LintOptions};
 pub use mask::{make_mask_lut, make_mask_lut_16};
 pub use math::Transform;
 pub use monoid::Monoid;
@@ -66,8 +80,15 @@ pub use path::{
     Cubic, LineSoup, Path, PathBbox, PathEncoder, PathMonoid, PathSegment, PathSegmentType,
     PathTag, SegmentCount, Style, Tile,
 };
+pub use ptcl::{
+    CmdBeginClip, CmdBlurRect, CmdColor, CmdCustom, CmdDraw, CmdEndClip, CmdFill, CmdGradient,
+    CmdHatch, CmdImage, CmdJump, PtclCommand, PtclError, PtclReader, PtclTag, TileCommands,
+    PTCL_HEADROOM, PTCL_INCREMENT, PTCL_INITIAL_ALLOC,
+};
 pub use ramp_cache::Ramps;
-pub use resolve::{resolve_solid_paths_only, Layout, Patch, Resolver};
+pub use resolve::{resolve_solid_paths_only, Fragment, Layout, Patch, Resolver};
+pub use serialize::DecodeError;
+pub use upload::{OversizedImages, Placeholder, UploadPriority, UploadQueue};
 
 #[cfg(feature = "bump_estimate")]
 pub use estimate::BumpEstimator;
@@ -0,0 +1,449 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Detection of common performance mistakes in encoded scenes.
+
+use std::f64::consts::SQRT_2;
+use std::fmt;
+
+use peniko::kurbo::{Affine, Point, Rect};
+
+use super::{DrawTag, Encoding, PathTag, Style};
+use crate::math::f16_to_f32;
+
+/// The thresholds used by [`lint`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LintOptions {
+    /// The area rendered to, in pixels, which is usually the size of the target.
+    ///
+    /// Geometry is only checked against the viewport if this is set.
+    pub viewport: Option<Rect>,
+    /// The area in square pixels below which the clip of a layer is tiny.
+    pub tiny_layer_area: f64,
+    /// The number of tiny layers above which they are reported.
+    pub max_tiny_layers: usize,
+    /// The number of layers containing a single draw above which they are reported.
+    pub max_single_draw_layers: usize,
+    /// The multiple of the area of the viewport above which geometry is reported as
+    /// oversized.
+    pub oversized_geometry_factor: f64,
+    /// The factor by which an image brush must be scaled down to be reported.
+    pub image_downscale_factor: f64,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            viewport: None,
+            tiny_layer_area: 1024.0,
+            max_tiny_layers: 100,
+            max_single_draw_layers: 32,
+            oversized_geometry_factor: 16.0,
+            image_downscale_factor: 10.0,
+        }
+    }
+}
+
+/// The kind of mistake found by a [`Lint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// Many layers with clips covering only a few pixels.
+    TinyLayers,
+    /// Many layers which each contain a single draw, such as a clip for each item of a list.
+    SingleDrawLayers,
+    /// Draws entirely outside of the viewport.
+    OffViewport,
+    /// Draws with geometry much larger than the viewport.
+    OversizedGeometry,
+    /// Image brushes which are scaled down by a large factor.
+    DownscaledImages,
+}
+
+/// A performance mistake found in an encoding by [`lint`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// The kind of mistake.
+    pub kind: LintKind,
+    /// The indices of the draw objects involved, in the order of the draw tag stream.
+    ///
+    /// For layers, these are the draw objects which begin them.
+    pub draws: Vec<usize>,
+    /// A description of the mistake and how to avoid it.
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// The geometry of a draw object.
+pub(crate) struct DrawGeometry {
+    pub(crate) tag: DrawTag,
+    /// The bounds of the path in device space, or `None` if it has no segments or is a glyph
+    /// run or dashed stroke, whose segments are only added when the encoding is resolved.
+    pub(crate) bounds: Option<Rect>,
+    /// The transform in effect at the end of the path, which is used for the brush.
+    brush_transform: Affine,
+}
+
+/// Analyzes `encoding` for patterns which are known to be slow to render, returning the
+/// mistakes found.
+///
+/// This walks every path in the encoding, so it's intended to be run occasionally during
+/// development rather than for every frame.
+pub fn lint(encoding: &Encoding, options: &LintOptions) -> Vec<Lint> {
+    let draws = draw_geometry(encoding);
+    let mut lints = Vec::new();
+
+    let mut tiny_layers = Vec::new();
+    let mut single_draw_layers = Vec::new();
+    for (ix, draw) in draws.iter().enumerate() {
+        if draw.tag != DrawTag::BEGIN_CLIP {
+            continue;
+        }
+        if draw
+            .bounds
+            .is_some_and(|bounds| bounds.area() < options.tiny_layer_area)
+        {
+            tiny_layers.push(ix);
+        }
+        let is_single_draw = draws.get(ix + 2).is_some_and(|end| {
+            let inner = draws[ix + 1].tag;
+            end.tag == DrawTag::END_CLIP
+                && inner != DrawTag::BEGIN_CLIP
+                && inner != DrawTag::END_CLIP
+        });
+        if is_single_draw {
+            single_draw_layers.push(ix);
+        }
+    }
+    if tiny_layers.len() > options.max_tiny_layers {
+        lints.push(Lint {
+            kind: LintKind::TinyLayers,
+            message: format!(
+                "{} layers have clips smaller than {} square pixels. Each layer is composited \
+                 separately in every tile it touches, so draw small content without a layer, \
+                 or group it under a shared layer.",
+                tiny_layers.len(),
+                options.tiny_layer_area
+            ),
+            draws: tiny_layers,
+        });
+    }
+    if single_draw_layers.len() > options.max_single_draw_layers {
+        lints.push(Lint {
+            kind: LintKind::SingleDrawLayers,
+            message: format!(
+                "{} layers contain a single draw each. Intersect each item's geometry with \
+                 its clip instead, or push one layer around the whole list rather than one \
+                 per item.",
+                single_draw_layers.len()
+            ),
+            draws: single_draw_layers,
+        });
+    }
+
+    if let Some(viewport) = options.viewport {
+        let mut off_viewport = Vec::new();
+        let mut oversized = Vec::new();
+        let max_area = viewport.area() * options.oversized_geometry_factor;
+        for (ix, draw) in draws.iter().enumerate() {
+            // The paths of layers are clips, which are expected to extend past the viewport.
+            if draw.tag == DrawTag::BEGIN_CLIP || draw.tag == DrawTag::END_CLIP {
+                continue;
+            }
+            let Some(bounds) = draw.bounds else {
+                continue;
+            };
+            if bounds.intersect(viewport).area() <= 0.0 {
+                off_viewport.push(ix);
+            } else if bounds.area() > max_area {
+                oversized.push(ix);
+            }
+        }
+        if !off_viewport.is_empty() {
+            lints.push(Lint {
+                kind: LintKind::OffViewport,
+                message: format!(
+                    "{} draws are entirely outside the viewport. They are still flattened and \
+                     binned, so cull them before encoding.",
+                    off_viewport.len()
+                ),
+                draws: off_viewport,
+            });
+        }
+        if !oversized.is_empty() {
+            lints.push(Lint {
+                kind: LintKind::OversizedGeometry,
+                message: format!(
+                    "{} draws have geometry over {} times the area of the viewport. All of it \
+                     is flattened, so clip the geometry to the viewport before encoding it.",
+                    oversized.len(),
+                    options.oversized_geometry_factor
+                ),
+                draws: oversized,
+            });
+        }
+    }
+
+    let downscaled: Vec<usize> = draws
+        .iter()
+        .enumerate()
+        .filter(|(_, draw)| {
+            draw.tag == DrawTag::IMAGE
+                && draw.brush_transform.determinant().abs().sqrt()
+                    <= 1.0 / options.image_downscale_factor
+        })
+        .map(|(ix, _)| ix)
+        .collect();
+    if !downscaled.is_empty() {
+        lints.push(Lint {
+            kind: LintKind::DownscaledImages,
+            message: format!(
+                "{} images are drawn scaled down by {} times or more. Upload smaller versions \
+                 of them, which saves atlas space and upload bandwidth and avoids aliasing.",
+                downscaled.len(),
+                options.image_downscale_factor
+            ),
+            draws: downscaled,
+        });
+    }
+    lints
+}
+
+/// Walks the path tag stream to find the geometry of each draw object.
+pub(crate) fn draw_geometry(encoding: &Encoding) -> Vec<DrawGeometry> {
+    let glyph_run_draws: Vec<usize> = encoding
+        .resources
+        .glyph_runs
+        .iter()
+        .map(|run| run.stream_offsets.draw_tags)
+        .collect();
+    let mut paths = Vec::new();
+    let mut transform = Affine::IDENTITY;
+    let mut n_transforms = 0;
+    let mut style = Style::default();
+    let mut n_styles = 0;
+    let mut bounds: Option<Rect> = None;
+    // The byte offset of the first point of the next segment in the path data stream.
+    let mut offset = 0;
+    for tag in &encoding.path_tags {
+        if *tag == PathTag::TRANSFORM {
+            transform = encoding.transforms[n_transforms].to_kurbo();
+            n_transforms += 1;
+        } else if *tag == PathTag::STYLE {
+            style = encoding.styles[n_styles];
+            n_styles += 1;
+        } else if *tag == PathTag::PATH {
+            let is_stroke = style.flags_and_miter_limit & Style::FLAGS_STYLE_BIT != 0;
+            let bounds = bounds.take().map(|bounds| {
+                if is_stroke {
+                    let outset = stroke_outset(style, transform);
+                    bounds.inflate(outset, outset)
+                } else {
+                    bounds
+                }
+            });
+            paths.push((bounds, transform));
+        } else if tag.is_path_segment() {
+            let point_size = if tag.is_f32() { 8 } else { 4 };
+            let n_points = usize::from(tag.path_segment_type().0);
+            for i in 0..=n_points {
+                let point = read_point(&encoding.path_data, offset + i * point_size, tag.is_f32());
+                let point = transform * point;
+                bounds = Some(match bounds {
+                    Some(bounds) => bounds.union_pt(point),
+                    None => Rect::from_points(point, point),
+                });
+            }
+            offset += n_points * point_size;
+            if tag.is_subpath_end() {
+                offset += point_size;
+            }
+        }
+    }
+    let mut paths = paths.into_iter();
+    encoding
+        .draw_tags
+        .iter()
+        .enumerate()
+        .map(|(ix, tag)| {
+            let (bounds, brush_transform) = if glyph_run_draws.contains(&ix) {
+                (None, Affine::IDENTITY)
+            } else {
+                paths.next().unwrap_or((None, Affine::IDENTITY))
+            };
+            DrawGeometry {
+                tag: *tag,
+                bounds,
+                brush_transform,
+            }
+        })
+        .collect()
+}
+
+/// The furthest distance, in device space, by which the outline of a stroke can extend beyond
+/// its path.
+///
+/// Miter joins reach up to the miter limit times half the line width from the path, and the
+/// corners of square caps reach `SQRT_2` times half of it. The distance is scaled by the
+/// largest singular value of `transform`, so it also holds for non-uniform scales.
+fn stroke_outset(style: Style, transform: Affine) -> f64 {
+    let mut extent = SQRT_2;
+    if style.flags_and_miter_limit & Style::FLAGS_JOIN_MASK == Style::FLAGS_JOIN_BITS_MITER {
+        let miter_limit =
+            f16_to_f32((style.flags_and_miter_limit & Style::MITER_LIMIT_MASK) as u16);
+        extent = extent.max(f64::from(miter_limit));
+    }
+    let [a, b, c, d, _, _] = transform.as_coeffs();
+    let sum = a * a + b * b + c * c + d * d;
+    let det = a * d - b * c;
+    let discriminant = (sum * sum - 4.0 * det * det).max(0.0);
+    let scale = ((sum + discriminant.sqrt()) * 0.5).sqrt();
+    f64::from(style.line_width) * 0.5 * extent * scale
+}
+
+pub(crate) fn read_point(data: &[u8], offset: usize, is_f32: bool) -> Point {
+    if is_f32 {
+        let x: f32 = bytemuck::pod_read_unaligned(&data[offset..offset + 4]);
+        let y: f32 = bytemuck::pod_read_unaligned(&data[offset + 4..offset + 8]);
+        Point::new(x.into(), y.into())
+    } else {
+        let x: i16 = bytemuck::pod_read_unaligned(&data[offset..offset + 2]);
+        let y: i16 = bytemuck::pod_read_unaligned(&data[offset + 2..offset + 4]);
+        Point::new(x.into(), y.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use peniko::kurbo::{Affine, Join, Line, Rect, Stroke};
+    use peniko::{color::palette, BlendMode, Blob, Fill, Image, ImageFormat};
+
+    use super::{draw_geometry, lint, LintKind, LintOptions};
+    use crate::{Encoding, Transform};
+
+    fn fill(encoding: &mut Encoding, transform: Affine, rect: Rect) {
+        encoding.encode_transform(Transform::from_kurbo(&transform));
+        encoding.encode_fill_style(Fill::NonZero);
+        encoding.encode_shape(&rect, true);
+    }
+
+    fn kinds(encoding: &Encoding, options: &LintOptions) -> Vec<LintKind> {
+        lint(encoding, options)
+            .into_iter()
+            .map(|lint| lint.kind)
+            .collect()
+    }
+
+    #[test]
+    fn clean_scene() {
+        let mut encoding = Encoding::new();
+        fill(
+            &mut encoding,
+            Affine::IDENTITY,
+            Rect::new(0.0, 0.0, 100.0, 100.0),
+        );
+        encoding.encode_color(palette::css::RED);
+        let options = LintOptions {
+            viewport: Some(Rect::new(0.0, 0.0, 100.0, 100.0)),
+            ..LintOptions::default()
+        };
+        assert!(lint(&encoding, &options).is_empty());
+    }
+
+    #[test]
+    fn layers() {
+        let mut encoding = Encoding::new();
+        for i in 0..40 {
+            let rect = Rect::new(f64::from(i) * 4.0, 0.0, f64::from(i) * 4.0 + 4.0, 4.0);
+            fill(&mut encoding, Affine::IDENTITY, rect);
+            encoding.encode_begin_clip(BlendMode::default(), 1.0);
+            fill(&mut encoding, Affine::IDENTITY, rect);
+            encoding.encode_color(palette::css::RED);
+            encoding.encode_end_clip();
+        }
+        let options = LintOptions {
+            max_tiny_layers: 20,
+            ..LintOptions::default()
+        };
+        let lints = lint(&encoding, &options);
+        assert_eq!(
+            lints.iter().map(|lint| lint.kind).collect::<Vec<_>>(),
+            [LintKind::TinyLayers, LintKind::SingleDrawLayers]
+        );
+        assert_eq!(lints[0].draws.len(), 40);
+        assert_eq!(lints[1].draws[1], 3);
+    }
+
+    #[test]
+    fn geometry_outside_viewport() {
+        let mut encoding = Encoding::new();
+        fill(
+            &mut encoding,
+            Affine::IDENTITY,
+            Rect::new(200.0, 0.0, 300.0, 100.0),
+        );
+        encoding.encode_color(palette::css::RED);
+        fill(
+            &mut encoding,
+            Affine::IDENTITY,
+            Rect::new(-1000.0, -1000.0, 1000.0, 1000.0),
+        );
+        encoding.encode_color(palette::css::RED);
+        let options = LintOptions {
+            viewport: Some(Rect::new(0.0, 0.0, 100.0, 100.0)),
+            ..LintOptions::default()
+        };
+        let lints = lint(&encoding, &options);
+        assert_eq!(lints[0].kind, LintKind::OffViewport);
+        assert_eq!(lints[0].draws, [0]);
+        assert_eq!(lints[1].kind, LintKind::OversizedGeometry);
+        assert_eq!(lints[1].draws, [1]);
+        // Without a viewport, geometry isn't checked.
+        assert!(kinds(&encoding, &LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn downscaled_images() {
+        let image = Image::new(
+            Blob::new(std::sync::Arc::new(vec![0_u8; 4 * 1000 * 1000])),
+            ImageFormat::Rgba8,
+            1000,
+            1000,
+        );
+        let mut encoding = Encoding::new();
+        for scale in [0.5, 0.05] {
+            fill(
+                &mut encoding,
+                Affine::scale(scale),
+                Rect::new(0.0, 0.0, 1000.0, 1000.0),
+            );
+            encoding.encode_image(&image, 1.0);
+        }
+        let lints = lint(&encoding, &LintOptions::default());
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::DownscaledImages);
+        assert_eq!(lints[0].draws, [1]);
+    }
+
+    #[test]
+    fn stroke_bounds_cover_joins_and_scales() {
+        let mut encoding = Encoding::new();
+        // A non-uniform scale, which has a determinant of 1.
+        encoding.encode_transform(Transform::from_kurbo(&Affine::scale_non_uniform(10.0, 0.1)));
+        encoding.encode_stroke_style(
+            &Stroke::new(2.0)
+                .with_join(Join::Miter)
+                .with_miter_limit(8.0),
+        );
+        encoding.encode_shape(&Line::new((0.0, 0.0), (0.0, 100.0)), false);
+        encoding.encode_color(palette::css::RED);
+        let bounds = draw_geometry(&encoding)[0].bounds.unwrap();
+        // The miter limit times half the line width, scaled by the larger of the two scales.
+        assert!(bounds.x0 <= -80.0, "{bounds:?}");
+        assert!(bounds.x1 >= 80.0, "{bounds:?}");
+    }
+}
@@ -186,11 +186,34 @@ impl Style {
 #[repr(C)]
 pub struct LineSoup {
     pub path_ix: u32,
-    pub _padding: u32,
+    /// Which part of the path the line was flattened from, one of the `KIND` constants.
+    ///
+    /// This is only used by the debug layers.
+    pub kind: u32,
     pub p0: [f32; 2],
     pub p1: [f32; 2],
 }
 
+impl LineSoup {
+    /// A line of a filled path.
+    pub const KIND_FILL: u32 = 0;
+    /// A line of the offset curves of a stroke.
+    pub const KIND_OFFSET: u32 = 1;
+    /// The last line of an offset curve, which is snapped to the start of the join or cap
+    /// after it.
+    pub const KIND_SEAM: u32 = 2;
+    /// A line of a stroke cap.
+    pub const KIND_CAP: u32 = 3;
+    /// A line of a bevel join.
+    pub const KIND_JOIN_BEVEL: u32 = 4;
+    /// A line of a miter join.
+    pub const KIND_JOIN_MITER: u32 = 5;
+    /// A line of a miter join which exceeded the miter limit, so was drawn as a bevel.
+    pub const KIND_JOIN_MITER_CLIPPED: u32 = 6;
+    /// A line of a round join.
+    pub const KIND_JOIN_ROUND: u32 = 7;
+}
+
 /// Line segment (after flattening, before tiling).
 #[derive(Clone, Copy, Debug, Zeroable, Pod, Default)]
 #[repr(C)]
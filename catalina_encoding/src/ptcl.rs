@@ -0,0 +1,540 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! The per-tile command lists written by coarse rasterization, and a reader for them.
+//!
+//! These mirror the layout in `shared/ptcl.wgsl`, where each command is a tag word followed by
+//! the words of its struct. Commands which need more data than fits in the list refer to it
+//! by an offset into the info buffer, which holds the data written for each draw object.
+
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::PathSegment;
+
+/// The number of words at the start of the command list of each tile, which is followed by
+/// a [`PtclTag::JUMP`] to more space once it's full.
+pub const PTCL_INITIAL_ALLOC: u32 = 64;
+
+/// The number of words allocated each time the command list of a tile runs out of space.
+pub const PTCL_INCREMENT: u32 = 256;
+
+/// The number of words kept free at the end of each allocation for a jump.
+pub const PTCL_HEADROOM: u32 = 2;
+
+/// Tag of a command in a per-tile command list.
+#[derive(Copy, Clone, PartialEq, Eq, Pod, Zeroable, Debug)]
+#[repr(C)]
+pub struct PtclTag(pub u32);
+
+impl PtclTag {
+    /// End of the command list.
+    pub const END: Self = Self(0);
+
+    /// Fill the area of a path in the tile, followed by a [`CmdFill`].
+    pub const FILL: Self = Self(1);
+
+    /// Cover the whole tile.
+    pub const SOLID: Self = Self(3);
+
+    /// Paint the area with a solid color, followed by a [`CmdColor`].
+    pub const COLOR: Self = Self(5);
+
+    /// Paint the area with a linear gradient, followed by a [`CmdGradient`].
+    pub const LINEAR_GRADIENT: Self = Self(6);
+
+    /// Paint the area with a radial gradient, followed by a [`CmdGradient`].
+    pub const RADIAL_GRADIENT: Self = Self(7);
+
+    /// Paint the area with a sweep gradient, followed by a [`CmdGradient`].
+    pub const SWEEP_GRADIENT: Self = Self(8);
+
+    /// Paint the area with an image, followed by a [`CmdImage`].
+    pub const IMAGE: Self = Self(9);
+
+    /// Push a layer, followed by a [`CmdBeginClip`].
+    pub const BEGIN_CLIP: Self = Self(10);
+
+    /// Pop a layer and blend it with the one below, followed by a [`CmdEndClip`].
+    pub const END_CLIP: Self = Self(11);
+
+    /// Continue reading at another offset, followed by a [`CmdJump`].
+    pub const JUMP: Self = Self(12);
+
+    /// Paint a blurred rounded rectangle, followed by a [`CmdBlurRect`].
+    pub const BLUR_RECT: Self = Self(13);
+
+    /// Mark the draw object which the following commands belong to, followed by a
+    /// [`CmdDraw`]. Only written when measuring the cost of draw objects.
+    pub const DRAW: Self = Self(14);
+
+    /// Paint the area with a hatch pattern, followed by a [`CmdHatch`].
+    pub const HATCH: Self = Self(15);
+
+    /// Paint the area with a custom paint function, followed by a [`CmdCustom`].
+    pub const CUSTOM: Self = Self(16);
+}
+
+/// Fill of a path in a tile.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct CmdFill {
+    /// The number of segments in the tile, shifted left by one, with the lowest bit set for
+    /// the even-odd fill rule.
+    pub size_and_rule: u32,
+    /// Index of the first of the tile's segments in the segments buffer.
+    pub seg_data: u32,
+    /// Winding number at the left edge of the tile.
+    pub backdrop: i32,
+}
+
+impl CmdFill {
+    /// Returns the number of segments in the tile.
+    pub const fn segment_count(&self) -> u32 {
+        self.size_and_rule >> 1
+    }
+
+    /// Returns whether the path is filled with the even-odd fill rule.
+    pub const fn is_even_odd(&self) -> bool {
+        self.size_and_rule & 1 != 0
+    }
+
+    /// Returns the segments of the tile, or `None` if they're not all in `segments`.
+    pub fn segments<'a>(&self, segments: &'a [PathSegment]) -> Option<&'a [PathSegment]> {
+        let start = self.seg_data as usize;
+        segments.get(start..start + self.segment_count() as usize)
+    }
+}
+
+/// Solid color paint.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdColor {
+    /// Packed premultiplied color, in the same format as [`DrawColor`](crate::DrawColor).
+    pub rgba_color: u32,
+}
+
+/// Gradient paint.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdGradient {
+    /// Index of the gradient's row in the ramp texture, shifted left by two, with the extend
+    /// mode in the lowest two bits.
+    pub index_mode: u32,
+    /// Offset of the gradient's geometry in the info buffer.
+    pub info_offset: u32,
+}
+
+impl CmdGradient {
+    /// Returns the index of the gradient's row in the ramp texture.
+    pub const fn ramp_index(&self) -> u32 {
+        self.index_mode >> 2
+    }
+
+    /// Returns the extend mode, as `0` for pad, `1` for repeat and `2` for reflect.
+    pub const fn extend_mode(&self) -> u32 {
+        self.index_mode & 0x3
+    }
+}
+
+/// Image paint.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdImage {
+    /// Offset of the image's transform, atlas location and sampling in the info buffer.
+    pub info_offset: u32,
+}
+
+/// Start of a layer.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdBeginClip {
+    /// Packed blend mode of the layer.
+    pub blend: u32,
+}
+
+/// End of a layer.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct CmdEndClip {
+    /// Packed blend mode of the layer.
+    pub blend: u32,
+    /// Opacity of the layer.
+    pub alpha: f32,
+}
+
+/// Jump to another part of the command list.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdJump {
+    /// Offset of the next command, in words.
+    pub new_ix: u32,
+}
+
+/// Blurred rounded rectangle paint.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdBlurRect {
+    /// Offset of the rectangle's geometry and blur in the info buffer.
+    pub info_offset: u32,
+    /// Packed premultiplied color, in the same format as [`DrawColor`](crate::DrawColor).
+    pub rgba_color: u32,
+}
+
+/// Hatch pattern paint.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdHatch {
+    /// Offset of the lines of the pattern in the info buffer.
+    pub info_offset: u32,
+    /// Packed premultiplied color, in the same format as [`DrawColor`](crate::DrawColor).
+    pub rgba_color: u32,
+}
+
+/// Custom paint.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdCustom {
+    /// Offset of the brush transform, paint function and its data in the info buffer.
+    pub info_offset: u32,
+}
+
+/// Draw object marker, when measuring the cost of draw objects.
+#[derive(Copy, Clone, Pod, Zeroable, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CmdDraw {
+    /// Index of the draw object.
+    pub draw_ix: u32,
+}
+
+/// A command read from a per-tile command list by [`PtclReader`].
+///
+/// Each variant corresponds to the [`PtclTag`] of the same name. Jumps are followed by the
+/// reader rather than returned.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PtclCommand {
+    /// Sets the coverage of the tile from the segments of a path.
+    Fill(CmdFill),
+    /// Sets the coverage of the whole tile to full.
+    Solid,
+    /// Paints the coverage with a solid color.
+    Color(CmdColor),
+    /// Paints the coverage with a linear gradient.
+    LinearGradient(CmdGradient),
+    /// Paints the coverage with a radial gradient.
+    RadialGradient(CmdGradient),
+    /// Paints the coverage with a sweep gradient.
+    SweepGradient(CmdGradient),
+    /// Paints the coverage with an image.
+    Image(CmdImage),
+    /// Starts a layer, saving the pixels painted so far.
+    BeginClip(CmdBeginClip),
+    /// Ends a layer, clipping it by the coverage and blending it onto the saved pixels.
+    EndClip(CmdEndClip),
+    /// Paints the coverage with a blurred rounded rectangle.
+    BlurRect(CmdBlurRect),
+    /// Paints the coverage with a hatch pattern.
+    Hatch(CmdHatch),
+    /// Paints the coverage with a custom paint function.
+    Custom(CmdCustom),
+    /// Marks the start of a draw object, when measuring the cost of draw objects.
+    Draw(CmdDraw),
+}
+
+/// An error when reading a per-tile command list with [`PtclReader`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PtclError {
+    /// A command at this offset runs past the end of the buffer.
+    OutOfBounds(u32),
+    /// A tag isn't a known command.
+    UnknownTag {
+        /// The offset of the tag in the buffer, in words.
+        offset: u32,
+        /// The value of the tag.
+        tag: u32,
+    },
+    /// The tile at this position is outside the target, or too far into it for the offset of
+    /// its command list to fit in a `u32`.
+    TileOutOfRange {
+        /// The column of the tile.
+        x: u32,
+        /// The row of the tile.
+        y: u32,
+    },
+    /// The command list never ends, such as when jumps form a cycle.
+    Unterminated,
+}
+
+impl fmt::Display for PtclError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds(offset) => {
+                write!(
+                    f,
+                    "command at offset {offset} runs past the end of the buffer"
+                )
+            }
+            Self::UnknownTag { offset, tag } => {
+                write!(f, "unknown command tag {tag} at offset {offset}")
+            }
+            Self::TileOutOfRange { x, y } => write!(f, "tile ({x}, {y}) is outside the target"),
+            Self::Unterminated => write!(f, "command list isn't terminated"),
+        }
+    }
+}
+
+impl std::error::Error for PtclError {}
+
+/// Reads the per-tile command lists from the contents of a PTCL buffer, such as one read back
+/// from the GPU for a visualizer or analyzer.
+///
+/// The command lists are only complete when coarse rasterization didn't run out of space,
+/// which is reported by the `failed` field of [`BumpAllocators`](crate::BumpAllocators).
+#[derive(Copy, Clone, Debug)]
+pub struct PtclReader<'a> {
+    ptcl: &'a [u32],
+    width_in_tiles: u32,
+    height_in_tiles: u32,
+}
+
+impl<'a> PtclReader<'a> {
+    /// Creates a reader for the command lists of a target of the given size in tiles, as in
+    /// [`ConfigUniform`](crate::ConfigUniform).
+    pub fn new(ptcl: &'a [u32], width_in_tiles: u32, height_in_tiles: u32) -> Self {
+        Self {
+            ptcl,
+            width_in_tiles,
+            height_in_tiles,
+        }
+    }
+
+    /// Returns the offset of the tile's scratch space for blending in the blend spill buffer.
+    ///
+    /// Returns [`PtclError::TileOutOfRange`] if the tile is outside the target.
+    pub fn blend_offset(&self, x: u32, y: u32) -> Result<u32, PtclError> {
+        let start = self.tile_start(x, y)?;
+        self.ptcl
+            .get(start as usize)
+            .copied()
+            .ok_or(PtclError::OutOfBounds(start))
+    }
+
+    /// Returns an iterator over the commands of a tile, which follows jumps and stops at the
+    /// end of the list or at the first error.
+    ///
+    /// Returns [`PtclError::TileOutOfRange`] if the tile is outside the target.
+    pub fn tile(&self, x: u32, y: u32) -> Result<TileCommands<'a>, PtclError> {
+        let start = self.tile_start(x, y)?;
+        Ok(TileCommands {
+            ptcl: self.ptcl,
+            offset: start
+                .checked_add(1)
+                .ok_or(PtclError::TileOutOfRange { x, y })?,
+            remaining: self.ptcl.len(),
+            done: false,
+        })
+    }
+
+    /// Returns the offset of the start of the tile's command list.
+    fn tile_start(&self, x: u32, y: u32) -> Result<u32, PtclError> {
+        if x >= self.width_in_tiles || y >= self.height_in_tiles {
+            return Err(PtclError::TileOutOfRange { x, y });
+        }
+        y.checked_mul(self.width_in_tiles)
+            .and_then(|row| row.checked_add(x))
+            .and_then(|index| index.checked_mul(PTCL_INITIAL_ALLOC))
+            .ok_or(PtclError::TileOutOfRange { x, y })
+    }
+}
+
+/// An iterator over the commands of a tile, returned by [`PtclReader::tile`].
+#[derive(Clone, Debug)]
+pub struct TileCommands<'a> {
+    ptcl: &'a [u32],
+    offset: u32,
+    /// The number of commands which can still be read, which bounds lists with cycles.
+    remaining: usize,
+    done: bool,
+}
+
+impl TileCommands<'_> {
+    /// Reads the command at the current offset, returning `None` at the end of the list.
+    fn read(&mut self) -> Result<Option<PtclCommand>, PtclError> {
+        loop {
+            if self.remaining == 0 {
+                return Err(PtclError::Unterminated);
+            }
+            self.remaining -= 1;
+            let offset = self.offset;
+            let tag = *self
+                .ptcl
+                .get(offset as usize)
+                .ok_or(PtclError::OutOfBounds(offset))?;
+            let command = match PtclTag(tag) {
+                PtclTag::END => return Ok(None),
+                PtclTag::JUMP => {
+                    self.offset = self.payload::<CmdJump>()?.new_ix;
+                    continue;
+                }
+                PtclTag::SOLID => {
+                    self.offset += 1;
+                    PtclCommand::Solid
+                }
+                PtclTag::FILL => PtclCommand::Fill(self.payload()?),
+                PtclTag::COLOR => PtclCommand::Color(self.payload()?),
+                PtclTag::LINEAR_GRADIENT => PtclCommand::LinearGradient(self.payload()?),
+                PtclTag::RADIAL_GRADIENT => PtclCommand::RadialGradient(self.payload()?),
+                PtclTag::SWEEP_GRADIENT => PtclCommand::SweepGradient(self.payload()?),
+                PtclTag::IMAGE => PtclCommand::Image(self.payload()?),
+                PtclTag::BEGIN_CLIP => PtclCommand::BeginClip(self.payload()?),
+                PtclTag::END_CLIP => PtclCommand::EndClip(self.payload()?),
+                PtclTag::BLUR_RECT => PtclCommand::BlurRect(self.payload()?),
+                PtclTag::HATCH => PtclCommand::Hatch(self.payload()?),
+                PtclTag::CUSTOM => PtclCommand::Custom(self.payload()?),
+                PtclTag::DRAW => PtclCommand::Draw(self.payload()?),
+                _ => return Err(PtclError::UnknownTag { offset, tag }),
+            };
+            return Ok(Some(command));
+        }
+    }
+
+    /// Reads the struct following the tag at the current offset, and moves past it.
+    fn payload<T: Pod>(&mut self) -> Result<T, PtclError> {
+        let start = self.offset as usize + 1;
+        let len = size_of::<T>() / 4;
+        let words = self
+            .ptcl
+            .get(start..start + len)
+            .ok_or(PtclError::OutOfBounds(self.offset))?;
+        self.offset += 1 + len as u32;
+        Ok(bytemuck::pod_read_unaligned(bytemuck::cast_slice(words)))
+    }
+}
+
+impl Iterator for TileCommands<'_> {
+    type Item = Result<PtclCommand, PtclError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // The shaders read each command as this many words after its tag.
+    const _COMMAND_SIZES_MATCH: () = assert!(
+        size_of::<super::CmdFill>() == 12
+            && size_of::<super::CmdGradient>() == 8
+            && size_of::<super::CmdEndClip>() == 8
+            && size_of::<super::CmdBlurRect>() == 8
+            && size_of::<super::CmdHatch>() == 8
+    );
+
+    use super::{
+        CmdColor, CmdEndClip, CmdFill, PtclCommand, PtclError, PtclReader, PtclTag,
+        PTCL_INITIAL_ALLOC,
+    };
+    use crate::PathSegment;
+
+    #[test]
+    fn commands_follow_jumps() {
+        // Two tiles, where the second continues after its initial allocation.
+        let dynamic = 2 * PTCL_INITIAL_ALLOC;
+        let mut ptcl = vec![0; dynamic as usize + 8];
+        ptcl[0] = 7;
+        ptcl[1..6].copy_from_slice(&[PtclTag::FILL.0, 5, 2, u32::MAX, PtclTag::SOLID.0]);
+        ptcl[6..8].copy_from_slice(&[PtclTag::COLOR.0, 0xff00_00ff]);
+        let second = PTCL_INITIAL_ALLOC as usize;
+        ptcl[second + 1..second + 3].copy_from_slice(&[PtclTag::JUMP.0, dynamic]);
+        let end_clip = [PtclTag::END_CLIP.0, 3, 0.5_f32.to_bits()];
+        ptcl[dynamic as usize..dynamic as usize + 3].copy_from_slice(&end_clip);
+
+        let reader = PtclReader::new(&ptcl, 2, 1);
+        assert_eq!(reader.blend_offset(0, 0), Ok(7));
+        let first = reader
+            .tile(0, 0)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let fill = CmdFill {
+            size_and_rule: 5,
+            seg_data: 2,
+            backdrop: -1,
+        };
+        assert_eq!(
+            first,
+            [
+                PtclCommand::Fill(fill),
+                PtclCommand::Solid,
+                PtclCommand::Color(CmdColor {
+                    rgba_color: 0xff00_00ff
+                }),
+            ]
+        );
+        assert_eq!(fill.segment_count(), 2);
+        assert!(fill.is_even_odd());
+        let segments = [PathSegment::default(); 4];
+        assert_eq!(fill.segments(&segments).map(<[_]>::len), Some(2));
+        assert!(fill.segments(&segments[..3]).is_none());
+
+        let second = reader.tile(1, 0).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            second,
+            [Ok(PtclCommand::EndClip(CmdEndClip {
+                blend: 3,
+                alpha: 0.5
+            }))]
+        );
+    }
+
+    #[test]
+    fn malformed_lists_are_errors() {
+        let mut ptcl = vec![0; PTCL_INITIAL_ALLOC as usize];
+        ptcl[1] = 99;
+        let reader = PtclReader::new(&ptcl, 1, 1);
+        assert_eq!(
+            reader.tile(0, 0).unwrap().collect::<Vec<_>>(),
+            [Err(PtclError::UnknownTag { offset: 1, tag: 99 })]
+        );
+
+        ptcl[1..3].copy_from_slice(&[PtclTag::JUMP.0, 1]);
+        let reader = PtclReader::new(&ptcl, 1, 1);
+        assert_eq!(
+            reader.tile(0, 0).unwrap().collect::<Vec<_>>(),
+            [Err(PtclError::Unterminated)]
+        );
+
+        ptcl[1..3].copy_from_slice(&[PtclTag::COLOR.0, 0]);
+        let reader = PtclReader::new(&ptcl[..2], 1, 1);
+        assert_eq!(
+            reader.tile(0, 0).unwrap().collect::<Vec<_>>(),
+            [Err(PtclError::OutOfBounds(1))]
+        );
+    }
+
+    #[test]
+    fn tiles_outside_the_target_are_errors() {
+        let ptcl = vec![0; 2 * PTCL_INITIAL_ALLOC as usize];
+        let reader = PtclReader::new(&ptcl, 2, 1);
+        let outside = PtclError::TileOutOfRange { x: 0, y: 1 };
+        assert_eq!(reader.blend_offset(0, 1), Err(outside.clone()));
+        assert_eq!(reader.tile(0, 1).err(), Some(outside));
+        assert_eq!(
+            reader.blend_offset(2, 0),
+            Err(PtclError::TileOutOfRange { x: 2, y: 0 })
+        );
+
+        // Offsets which don't fit in a `u32` are out of range rather than wrapping.
+        let reader = PtclReader::new(&ptcl, u32::MAX, 2);
+        assert_eq!(
+            reader.blend_offset(0, 1),
+            Err(PtclError::TileOutOfRange { x: 0, y: 1 })
+        );
+    }
+}
@@ -2,15 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 use bytemuck::{Pod, Zeroable};
-use peniko::{Extend, Image};
+use peniko::kurbo::Rect;
+use peniko::{Extend, Image, ImageQuality};
+use skrifa::instance::{LocationRef, Size};
+use skrifa::MetadataProvider;
 use std::ops::Range;
 use std::sync::Arc;
 
-use super::{DrawTag, Encoding, PathTag, StreamOffsets, Style, Transform};
+use super::{
+    DrawTag, Encoding, Glyph, GlyphRun, PathTag, Resources, StreamOffsets, Style, Transform,
+};
 
 use crate::glyph_cache::GlyphCache;
-use crate::image_cache::{ImageCache, Images};
+use crate::image_cache::{downscale, ImageCache, Images};
+use crate::lint::draw_geometry;
 use crate::ramp_cache::{RampCache, Ramps};
+use crate::upload::{OversizedImages, Placeholder, UploadPriority, UploadQueue, CHECKERBOARD_SIZE};
 
 /// Layout of a packed encoding.
 #[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
@@ -161,6 +168,16 @@ pub struct Resolver {
     ramp_cache: RampCache,
     image_cache: ImageCache,
     pending_images: Vec<PendingImage>,
+    uploads: UploadQueue,
+    /// The scaled down copies of images too large for the atlas which were drawn in the last
+    /// frame, with the id of the original and the exponent of the scale.
+    downscaled: Vec<(u64, Image, u32)>,
+    /// The distance added to the dash offset of dashed strokes.
+    dash_phase: f64,
+    /// The region outside of which the resources of draws aren't resolved.
+    cull_rect: Option<Rect>,
+    /// The segments of the dashed strokes in the current encoding.
+    dashes: Vec<Encoding>,
     patches: Vec<ResolvedPatch>,
 }
 
@@ -170,6 +187,59 @@ impl Resolver {
         Self::default()
     }
 
+    /// Returns the queue of images waiting to be uploaded to the image atlas.
+    pub fn uploads(&self) -> &UploadQueue {
+        &self.uploads
+    }
+
+    /// Returns the queue of images waiting to be uploaded to the image atlas, to change its
+    /// budget or queue images ahead of their use.
+    pub fn uploads_mut(&mut self) -> &mut UploadQueue {
+        &mut self.uploads
+    }
+
+    /// Limits the size of the image atlas to `max_size` pixels in width and height, which
+    /// should be the maximum texture dimension of the device.
+    ///
+    /// The atlas is never larger than 8192 pixels, which is also the default. Images which
+    /// are larger than the limit are handled according to
+    /// [`UploadQueue::oversized_images`].
+    pub fn set_max_atlas_size(&mut self, max_size: u32) {
+        self.image_cache.set_max_size(max_size);
+    }
+
+    /// Returns the largest width and height of the image atlas, in pixels.
+    pub fn max_atlas_size(&self) -> u32 {
+        self.image_cache.max_size()
+    }
+
+    /// Sets the distance added to the dash offset of strokes encoded with
+    /// [`Encoding::encode_dashed_stroke`], which takes effect when they're next resolved.
+    pub fn set_dash_phase(&mut self, phase: f64) {
+        self.dash_phase = phase;
+    }
+
+    /// Only resolves the glyph outlines, gradient ramps and images of draws which are within
+    /// `rect`, in device coordinates, or of every draw if `None`.
+    ///
+    /// Draws outside of the rect, such as on the offscreen pages of a long document, are still
+    /// encoded, but their glyph runs are left empty, their gradients sample an arbitrary ramp
+    /// and their images aren't added to the atlas. Their resources are resolved in the first
+    /// frame in which they're within the rect, so memory is only used for what is visible.
+    /// Each draw is checked against the bounds of its own path, ignoring clips, and glyph runs
+    /// against the bounding box of their font.
+    ///
+    /// This takes effect when an encoding is next resolved, and doesn't apply to
+    /// [`resolve_fragment`](Self::resolve_fragment), whose final transform isn't known yet.
+    pub fn set_cull_rect(&mut self, rect: Option<Rect>) {
+        self.cull_rect = rect;
+    }
+
+    /// Returns the region set with [`set_cull_rect`](Self::set_cull_rect).
+    pub fn cull_rect(&self) -> Option<Rect> {
+        self.cull_rect
+    }
+
     /// Resolves late bound resources and packs an encoding. Returns the packed
     /// layout and computed ramp data.
     pub fn resolve<'a>(
@@ -202,18 +272,36 @@ impl Resolver {
             let mut pos = 0;
             let stream = &encoding.path_tags;
             for patch in &self.patches {
-                if let ResolvedPatch::GlyphRun { index, glyphs, .. } = patch {
-                    layout.n_paths += 1;
-                    let stream_offset = resources.glyph_runs[*index].stream_offsets.path_tags;
-                    if pos < stream_offset {
-                        data.extend_from_slice(bytemuck::cast_slice(&stream[pos..stream_offset]));
-                        pos = stream_offset;
+                match patch {
+                    ResolvedPatch::GlyphRun { index, glyphs, .. } => {
+                        layout.n_paths += 1;
+                        let stream_offset = resources.glyph_runs[*index].stream_offsets.path_tags;
+                        if pos < stream_offset {
+                            data.extend_from_slice(bytemuck::cast_slice(
+                                &stream[pos..stream_offset],
+                            ));
+                            pos = stream_offset;
+                        }
+                        for glyph in &self.glyphs[glyphs.clone()] {
+                            data.extend_from_slice(bytemuck::bytes_of(&PathTag::TRANSFORM));
+                            data.extend_from_slice(bytemuck::cast_slice(&glyph.path_tags));
+                        }
+                        data.extend_from_slice(bytemuck::bytes_of(&PathTag::PATH));
                     }
-                    for glyph in &self.glyphs[glyphs.clone()] {
-                        data.extend_from_slice(bytemuck::bytes_of(&PathTag::TRANSFORM));
-                        data.extend_from_slice(bytemuck::cast_slice(&glyph.path_tags));
+                    ResolvedPatch::DashedStroke { index, dashes } => {
+                        let stream_offset =
+                            resources.dashed_strokes[*index].stream_offsets.path_tags;
+                        if pos < stream_offset {
+                            data.extend_from_slice(bytemuck::cast_slice(
+                                &stream[pos..stream_offset],
+                            ));
+                            pos = stream_offset;
+                        }
+                        data.extend_from_slice(bytemuck::cast_slice(
+                            &self.dashes[*dashes].path_tags,
+                        ));
                     }
-                    data.extend_from_slice(bytemuck::bytes_of(&PathTag::PATH));
+                    _ => {}
                 }
             }
             if pos < stream.len() {
@@ -230,17 +318,33 @@ impl Resolver {
             let mut pos = 0;
             let stream = &encoding.path_data;
             for patch in &self.patches {
-                if let ResolvedPatch::GlyphRun { index, glyphs, .. } = patch {
-                    let stream_offset = encoding.resources.glyph_runs[*index]
-                        .stream_offsets
-                        .path_data;
-                    if pos < stream_offset {
-                        data.extend_from_slice(bytemuck::cast_slice(&stream[pos..stream_offset]));
-                        pos = stream_offset;
+                match patch {
+                    ResolvedPatch::GlyphRun { index, glyphs, .. } => {
+                        let stream_offset = encoding.resources.glyph_runs[*index]
+                            .stream_offsets
+                            .path_data;
+                        if pos < stream_offset {
+                            data.extend_from_slice(bytemuck::cast_slice(
+                                &stream[pos..stream_offset],
+                            ));
+                            pos = stream_offset;
+                        }
+                        for glyph in &self.glyphs[glyphs.clone()] {
+                            data.extend_from_slice(bytemuck::cast_slice(&glyph.path_data));
+                        }
                     }
-                    for glyph in &self.glyphs[glyphs.clone()] {
-                        data.extend_from_slice(bytemuck::cast_slice(&glyph.path_data));
+                    ResolvedPatch::DashedStroke { index, dashes } => {
+                        let stream_offset =
+                            resources.dashed_strokes[*index].stream_offsets.path_data;
+                        if pos < stream_offset {
+                            data.extend_from_slice(bytemuck::cast_slice(
+                                &stream[pos..stream_offset],
+                            ));
+                            pos = stream_offset;
+                        }
+                        data.extend_from_slice(&self.dashes[*dashes].path_data);
                     }
+                    _ => {}
                 }
             }
             if pos < stream.len() {
@@ -276,7 +380,7 @@ impl Resolver {
                         data.extend_from_slice(bytemuck::bytes_of(&index_mode));
                         pos = *draw_data_offset + 4;
                     }
-                    ResolvedPatch::GlyphRun { .. } => {}
+                    ResolvedPatch::GlyphRun { .. } | ResolvedPatch::DashedStroke { .. } => {}
                     ResolvedPatch::Image {
                         index,
                         draw_data_offset,
@@ -284,15 +388,54 @@ impl Resolver {
                         if pos < *draw_data_offset {
                             data.extend_from_slice(&encoding.draw_data[pos..*draw_data_offset]);
                         }
-                        if let Some((x, y)) = self.pending_images[*index].xy {
+                        let pending_image = &self.pending_images[*index];
+                        if let Some((x, y)) = pending_image.xy {
                             let xy = (x << 16) | y;
                             data.extend_from_slice(bytemuck::bytes_of(&xy));
                             pos = *draw_data_offset + 4;
+                            if pending_image.downscale != 0 {
+                                // Sample the scaled down copy of the image.
+                                let image = &pending_image.image;
+                                let width_height = (image.width << 16) | image.height;
+                                let offset = *draw_data_offset + 8;
+                                let sample_alpha: u32 = bytemuck::pod_read_unaligned(
+                                    &encoding.draw_data[offset..offset + 4],
+                                );
+                                let sample_alpha = (pending_image.downscale << 14) | sample_alpha;
+                                data.extend_from_slice(bytemuck::bytes_of(&width_height));
+                                data.extend_from_slice(bytemuck::bytes_of(&sample_alpha));
+                                pos = *draw_data_offset + 12;
+                            }
+                        } else if let Some((placeholder, (x, y))) = pending_image.substitute {
+                            let xy = (x << 16) | y;
+                            data.extend_from_slice(bytemuck::bytes_of(&xy));
+                            if placeholder == Placeholder::Checkerboard {
+                                // Repeat the checkerboard over the whole draw, with sharp edges
+                                // between its cells.
+                                let size = CHECKERBOARD_SIZE;
+                                let width_height = (size << 16) | size;
+                                let offset = *draw_data_offset + 8;
+                                let sample_alpha: u32 = bytemuck::pod_read_unaligned(
+                                    &encoding.draw_data[offset..offset + 4],
+                                );
+                                let sample_alpha = ((ImageQuality::Low as u32) << 12)
+                                    | ((Extend::Repeat as u32) << 10)
+                                    | ((Extend::Repeat as u32) << 8)
+                                    | (sample_alpha & 0xFF);
+                                data.extend_from_slice(bytemuck::bytes_of(&width_height));
+                                data.extend_from_slice(bytemuck::bytes_of(&sample_alpha));
+                                pos = *draw_data_offset + 12;
+                            } else {
+                                // Sample the single pixel of the solid color, whatever the
+                                // extend mode.
+                                let width_height = (1_u32 << 16) | 1;
+                                data.extend_from_slice(bytemuck::bytes_of(&width_height));
+                                pos = *draw_data_offset + 8;
+                            }
                         } else {
                             // If we get here, we failed to allocate a slot for this image in the atlas.
                             // In this case, let's zero out the dimensions so we don't attempt to render
                             // anything.
-                            // TODO: a better strategy: texture array? downsample large images?
                             data.extend_from_slice(&[0_u8; 8]);
                             pos = *draw_data_offset + 8;
                         }
@@ -322,25 +465,9 @@ impl Resolver {
                         data.extend_from_slice(bytemuck::cast_slice(&stream[pos..stream_offset]));
                         pos = stream_offset;
                     }
-                    if let Some(glyph_transform) = run.glyph_transform {
-                        for glyph in &resources.glyphs[run.glyphs.clone()] {
-                            let xform = *transform
-                                * Transform {
-                                    matrix: [1.0, 0.0, 0.0, -1.0],
-                                    translation: [glyph.x * scale, glyph.y * scale],
-                                }
-                                * glyph_transform;
-                            data.extend_from_slice(bytemuck::bytes_of(&xform));
-                        }
-                    } else {
-                        for glyph in &resources.glyphs[run.glyphs.clone()] {
-                            let xform = *transform
-                                * Transform {
-                                    matrix: [1.0, 0.0, 0.0, -1.0],
-                                    translation: [glyph.x * scale, glyph.y * scale],
-                                };
-                            data.extend_from_slice(bytemuck::bytes_of(&xform));
-                        }
+                    for glyph in &resources.glyphs[run.glyphs.clone()] {
+                        let xform = glyph_transform(run, *glyph, *transform, *scale);
+                        data.extend_from_slice(bytemuck::bytes_of(&xform));
                     }
                 }
             }
@@ -370,20 +497,145 @@ impl Resolver {
             }
         }
         self.glyphs.clear();
+        self.dashes.clear();
         layout.n_draw_objects = layout.n_paths;
         assert_eq!(buffer_size, data.len());
         (layout, self.ramp_cache.ramps(), self.image_cache.images())
     }
 
+    /// Resolves the glyph runs and dashed strokes of an encoding into plain paths, producing a
+    /// fragment which can be cheaply appended to other encodings.
+    ///
+    /// This is intended to be called on a worker thread with its own resolver, so that the
+    /// thread which assembles the final scene doesn't need to load any glyph outlines. Gradient
+    /// ramps and images remain late bound, as they're allocated by the resolver which renders
+    /// the final scene.
+    ///
+    /// Hinted glyph runs are hinted for the transform they have in `encoding`, as the transform
+    /// the fragment will be appended with isn't known yet. Dashed strokes are dashed with the
+    /// current [dash phase](Self::set_dash_phase).
+    pub fn resolve_fragment(&mut self, encoding: &Encoding) -> Fragment {
+        let resources = &encoding.resources;
+        if resources.glyph_runs.is_empty() && resources.dashed_strokes.is_empty() {
+            return Fragment {
+                encoding: encoding.clone(),
+            };
+        }
+        self.glyphs.clear();
+        self.dashes.clear();
+        self.glyph_cache.maintain();
+        let mut resolved = Encoding {
+            draw_tags: encoding.draw_tags.clone(),
+            draw_data: encoding.draw_data.clone(),
+            n_paths: encoding.n_paths,
+            n_path_segments: encoding.n_path_segments,
+            n_clips: encoding.n_clips,
+            n_open_clips: encoding.n_open_clips,
+            flags: encoding.flags,
+            ..Encoding::default()
+        };
+        resolved.resources.color_stops = resources.color_stops.clone();
+        let mut pos = StreamOffsets::default();
+        for patch in &resources.patches {
+            let index = match patch {
+                Patch::GlyphRun { index } => *index,
+                Patch::DashedStroke { index } => {
+                    let dashes = self.resolve_dashed_stroke(resources, *index);
+                    let offsets = &resources.dashed_strokes[*index].stream_offsets;
+                    resolved
+                        .path_tags
+                        .extend_from_slice(&encoding.path_tags[pos.path_tags..offsets.path_tags]);
+                    resolved
+                        .path_data
+                        .extend_from_slice(&encoding.path_data[pos.path_data..offsets.path_data]);
+                    pos.path_tags = offsets.path_tags;
+                    pos.path_data = offsets.path_data;
+                    let dashes = &self.dashes[dashes];
+                    resolved.path_tags.extend_from_slice(&dashes.path_tags);
+                    resolved.path_data.extend_from_slice(&dashes.path_data);
+                    resolved.n_path_segments += dashes.n_path_segments;
+                    continue;
+                }
+                // Glyph encodings have no draw data, so these offsets are unchanged.
+                Patch::Ramp { .. } | Patch::Image { .. } => {
+                    resolved.resources.patches.push(patch.clone());
+                    continue;
+                }
+            };
+            let Some((
+                ResolvedPatch::GlyphRun {
+                    glyphs,
+                    transform,
+                    scale,
+                    ..
+                },
+                _,
+            )) = self.resolve_glyph_run(resources, index, None)
+            else {
+                continue;
+            };
+            let run = &resources.glyph_runs[index];
+            let offsets = &run.stream_offsets;
+            resolved
+                .path_tags
+                .extend_from_slice(&encoding.path_tags[pos.path_tags..offsets.path_tags]);
+            resolved
+                .path_data
+                .extend_from_slice(&encoding.path_data[pos.path_data..offsets.path_data]);
+            resolved
+                .transforms
+                .extend_from_slice(&encoding.transforms[pos.transforms..offsets.transforms]);
+            resolved
+                .styles
+                .extend_from_slice(&encoding.styles[pos.styles..offsets.styles]);
+            pos = *offsets;
+            let placed = &resources.glyphs[run.glyphs.clone()];
+            for (glyph, outline) in placed.iter().zip(&self.glyphs[glyphs]) {
+                resolved.path_tags.push(PathTag::TRANSFORM);
+                resolved.path_tags.extend_from_slice(&outline.path_tags);
+                resolved.path_data.extend_from_slice(&outline.path_data);
+                resolved.styles.extend_from_slice(&outline.styles);
+                resolved
+                    .transforms
+                    .push(glyph_transform(run, *glyph, transform, scale));
+                resolved.n_path_segments += outline.n_path_segments;
+            }
+            resolved.path_tags.push(PathTag::PATH);
+            resolved.n_paths += 1;
+        }
+        resolved
+            .path_tags
+            .extend_from_slice(&encoding.path_tags[pos.path_tags..]);
+        resolved
+            .path_data
+            .extend_from_slice(&encoding.path_data[pos.path_data..]);
+        resolved
+            .transforms
+            .extend_from_slice(&encoding.transforms[pos.transforms..]);
+        resolved
+            .styles
+            .extend_from_slice(&encoding.styles[pos.styles..]);
+        self.glyphs.clear();
+        self.dashes.clear();
+        Fragment { encoding: resolved }
+    }
+
     fn resolve_patches(&mut self, encoding: &Encoding) -> StreamOffsets {
         self.ramp_cache.maintain();
         self.glyphs.clear();
         self.glyph_cache.maintain();
-        self.image_cache.clear();
         self.pending_images.clear();
+        self.dashes.clear();
         self.patches.clear();
         let mut sizes = StreamOffsets::default();
         let resources = &encoding.resources;
+        let cull_rect = self.cull_rect;
+        let visibility = cull_rect.map(|rect| Visibility::new(encoding, rect));
+        let is_visible = |draw_data_offset: usize| {
+            visibility
+                .as_ref()
+                .is_none_or(|visibility| visibility.contains(draw_data_offset))
+        };
         for patch in &resources.patches {
             match patch {
                 Patch::Ramp {
@@ -391,7 +643,13 @@ impl Resolver {
                     stops,
                     extend,
                 } => {
-                    let ramp_id = self.ramp_cache.add(&resources.color_stops[stops.clone()]);
+                    // The ramps of draws which aren't visible are never sampled, so they can
+                    // use any ramp.
+                    let ramp_id = if is_visible(*draw_data_offset) {
+                        self.ramp_cache.add(&resources.color_stops[stops.clone()])
+                    } else {
+                        0
+                    };
                     self.patches.push(ResolvedPatch::Ramp {
                         draw_data_offset: *draw_data_offset + sizes.draw_data,
                         ramp_id,
@@ -399,70 +657,35 @@ impl Resolver {
                     });
                 }
                 Patch::GlyphRun { index } => {
-                    let mut run_sizes = StreamOffsets::default();
-                    let run = &resources.glyph_runs[*index];
-                    let glyphs = &resources.glyphs[run.glyphs.clone()];
-                    let coords = &resources.normalized_coords[run.normalized_coords.clone()];
-                    let mut hint = run.hint;
-                    let mut font_size = run.font_size;
-                    let mut transform = run.transform;
-                    let mut scale = 1.0;
-                    if hint {
-                        // If hinting was requested and our transform matrix is just a uniform
-                        // scale, then adjust our font size and cancel out the matrix. Otherwise,
-                        // disable hinting entirely.
-                        if transform.matrix[0] == transform.matrix[3]
-                            && transform.matrix[1] == 0.0
-                            && transform.matrix[2] == 0.0
-                        {
-                            scale = transform.matrix[0];
-                            font_size *= scale;
-                            transform.matrix = [1.0, 0.0, 0.0, 1.0];
-                        } else {
-                            hint = false;
-                        }
+                    if let Some((patch, run_sizes)) =
+                        self.resolve_glyph_run(resources, *index, cull_rect)
+                    {
+                        sizes.add(&run_sizes);
+                        self.patches.push(patch);
                     }
-                    let Some(mut session) = self.glyph_cache.session(
-                        &run.font,
-                        bytemuck::cast_slice(coords),
-                        font_size,
-                        hint,
-                        &run.style,
-                    ) else {
-                        continue;
-                    };
-                    let glyph_start = self.glyphs.len();
-                    for glyph in glyphs {
-                        let (encoding, stream_sizes) =
-                            session.get_or_insert(glyph.id).unwrap_or_else(|| {
-                                // HACK: We pretend that the encoding was empty.
-                                // In theory, we should be able to skip this glyph, but there is also
-                                // a corresponding entry in `resources`, which means that we would
-                                // need to make the patching process skip this glyph.
-                                (Arc::new(Encoding::new()), StreamOffsets::default())
-                            });
-                        run_sizes.add(&stream_sizes);
-                        self.glyphs.push(encoding);
-                    }
-                    let glyph_end = self.glyphs.len();
-                    run_sizes.path_tags += glyphs.len() + 1;
-                    run_sizes.transforms += glyphs.len();
-                    sizes.add(&run_sizes);
-                    self.patches.push(ResolvedPatch::GlyphRun {
+                }
+                Patch::DashedStroke { index } => {
+                    let dashes = self.resolve_dashed_stroke(resources, *index);
+                    sizes.path_tags += self.dashes[dashes].path_tags.len();
+                    sizes.path_data += self.dashes[dashes].path_data.len();
+                    self.patches.push(ResolvedPatch::DashedStroke {
                         index: *index,
-                        glyphs: glyph_start..glyph_end,
-                        transform,
-                        scale,
+                        dashes,
                     });
                 }
                 Patch::Image {
                     draw_data_offset,
                     image,
+                    placeholder,
                 } => {
                     let index = self.pending_images.len();
                     self.pending_images.push(PendingImage {
                         image: image.clone(),
+                        placeholder: *placeholder,
+                        culled: !is_visible(*draw_data_offset),
+                        downscale: 0,
                         xy: None,
+                        substitute: None,
                     });
                     self.patches.push(ResolvedPatch::Image {
                         index,
@@ -474,30 +697,297 @@ impl Resolver {
         sizes
     }
 
+    /// Fetches the glyph encodings for a glyph run, appending them to `self.glyphs`.
+    ///
+    /// Returns the resolved patch and the sizes the glyphs add to each stream, or `None` if
+    /// the font couldn't be loaded. If the run is entirely outside of `cull_rect`, its glyphs
+    /// are left empty rather than loaded.
+    fn resolve_glyph_run(
+        &mut self,
+        resources: &Resources,
+        index: usize,
+        cull_rect: Option<Rect>,
+    ) -> Option<(ResolvedPatch, StreamOffsets)> {
+        let mut run_sizes = StreamOffsets::default();
+        let run = &resources.glyph_runs[index];
+        let glyphs = &resources.glyphs[run.glyphs.clone()];
+        let coords = &resources.normalized_coords[run.normalized_coords.clone()];
+        let mut hint = run.hint;
+        let mut font_size = run.font_size;
+        let mut transform = run.transform;
+        let mut scale = 1.0;
+        if hint {
+            // If hinting was requested and our transform matrix is just a uniform
+            // scale, then adjust our font size and cancel out the matrix. Otherwise,
+            // disable hinting entirely.
+            if transform.matrix[0] == transform.matrix[3]
+                && transform.matrix[1] == 0.0
+                && transform.matrix[2] == 0.0
+            {
+                scale = transform.matrix[0];
+                font_size *= scale;
+                transform.matrix = [1.0, 0.0, 0.0, 1.0];
+            } else {
+                hint = false;
+            }
+        }
+        let glyph_start = self.glyphs.len();
+        let culled = cull_rect.is_some_and(|cull_rect| {
+            glyph_run_bounds(run, glyphs, coords, font_size, transform, scale)
+                .is_some_and(|bounds| bounds.intersect(cull_rect).area() <= 0.0)
+        });
+        if culled {
+            // The run keeps its entries in the streams, with no outlines.
+            let empty = Arc::new(Encoding::new());
+            self.glyphs.extend(std::iter::repeat_n(empty, glyphs.len()));
+        } else {
+            let mut session = self.glyph_cache.session(
+                &run.font,
+                bytemuck::cast_slice(coords),
+                font_size,
+                hint,
+                &run.style,
+            )?;
+            for glyph in glyphs {
+                let (encoding, stream_sizes) =
+                    session.get_or_insert(glyph.id).unwrap_or_else(|| {
+                        // HACK: We pretend that the encoding was empty.
+                        // In theory, we should be able to skip this glyph, but there is also
+                        // a corresponding entry in `resources`, which means that we would
+                        // need to make the patching process skip this glyph.
+                        (Arc::new(Encoding::new()), StreamOffsets::default())
+                    });
+                run_sizes.add(&stream_sizes);
+                self.glyphs.push(encoding);
+            }
+        }
+        let glyph_end = self.glyphs.len();
+        run_sizes.path_tags += glyphs.len() + 1;
+        run_sizes.transforms += glyphs.len();
+        let patch = ResolvedPatch::GlyphRun {
+            index,
+            glyphs: glyph_start..glyph_end,
+            transform,
+            scale,
+        };
+        Some((patch, run_sizes))
+    }
+
+    /// Dashes a stroke with the current dash phase, appending its segments to `self.dashes`.
+    ///
+    /// Returns the index of the segments.
+    fn resolve_dashed_stroke(&mut self, resources: &Resources, index: usize) -> usize {
+        let stroke = &resources.dashed_strokes[index];
+        let dashed = peniko::kurbo::dash(
+            stroke.path.iter(),
+            stroke.dash_offset + self.dash_phase,
+            &stroke.dash_pattern,
+        );
+        let mut encoding = Encoding::new();
+        let mut encoder = encoding.encode_path(false);
+        encoder.path_elements(dashed);
+        // The path marker is already in the encoding.
+        encoder.finish(false);
+        self.dashes.push(encoding);
+        self.dashes.len() - 1
+    }
+
     fn resolve_pending_images(&mut self) {
-        self.image_cache.clear();
-        'outer: loop {
-            // Loop over the images, attempting to allocate them all into the atlas.
+        self.image_cache.maintain();
+        if self.pending_images.is_empty() {
+            self.downscaled.clear();
+            return;
+        }
+        // Draw scaled down copies of the images which are too large for the atlas, if enabled.
+        let mut downscaled = Vec::new();
+        if self.uploads.oversized_images() == OversizedImages::Downscale {
             for pending_image in &mut self.pending_images {
-                if let Some(xy) = self.image_cache.get_or_insert(&pending_image.image) {
-                    pending_image.xy = Some(xy);
-                } else {
-                    // We failed to allocate. Try to bump the atlas size.
-                    if self.image_cache.bump_size() {
-                        // We were able to increase the atlas size. Restart the outer loop.
-                        continue 'outer;
-                    } else {
-                        // If the atlas is already maximum size, there's nothing we can do. Set
-                        // the xy field to None so this image isn't rendered and then carry on--
-                        // other images might still fit.
-                        pending_image.xy = None;
-                    }
+                if pending_image.culled || self.image_cache.fits(&pending_image.image) {
+                    continue;
                 }
+                let id = pending_image.image.data.id();
+                let (image, shift) = match self
+                    .downscaled
+                    .iter()
+                    .chain(&downscaled)
+                    .find(|(original, _, _)| *original == id)
+                {
+                    Some((_, image, shift)) => (image.clone(), *shift),
+                    None => downscale(&pending_image.image, self.image_cache.max_size()),
+                };
+                if !downscaled.iter().any(|(original, _, _)| *original == id) {
+                    downscaled.push((id, image.clone(), shift));
+                }
+                pending_image.image = image;
+                pending_image.downscale = shift;
+            }
+        }
+        // Copies of images which weren't drawn in this frame are dropped.
+        self.downscaled = downscaled;
+        // Queue the images which aren't in the atlas yet.
+        for pending_image in self.pending_images.iter().filter(|image| !image.culled) {
+            let image = &pending_image.image;
+            if self.image_cache.fits(image) && self.image_cache.get(image).is_none() {
+                self.uploads.queue(image, UploadPriority::Normal);
+            }
+        }
+        let admitted = self.uploads.admit();
+        for image in &admitted {
+            self.insert_image(image);
+        }
+        // Requeue the images which are still missing, and add their placeholders to the atlas.
+        let mut placeholders = Vec::new();
+        for (ix, pending_image) in self.pending_images.iter().enumerate() {
+            let image = &pending_image.image;
+            if pending_image.culled
+                || !self.image_cache.fits(image)
+                || self.image_cache.get(image).is_some()
+            {
+                continue;
+            }
+            // Either the upload is deferred to a later frame, or there wasn't enough space
+            // for it in this one.
+            self.uploads.queue(image, UploadPriority::Normal);
+            let placeholder = pending_image
+                .placeholder
+                .unwrap_or_else(|| self.uploads.placeholder());
+            if let Some(placeholder_image) = self.uploads.placeholder_image(placeholder) {
+                placeholders.push((ix, placeholder, placeholder_image));
             }
-            // If we made it here, we've either successfully allocated all images or we reached
-            // the maximum atlas size.
-            break;
         }
+        for (_, _, image) in &placeholders {
+            self.insert_image(image);
+        }
+        // The atlas no longer changes in this frame, so the locations are final.
+        for pending_image in &mut self.pending_images {
+            if !pending_image.culled {
+                pending_image.xy = self.image_cache.get(&pending_image.image);
+            }
+        }
+        for (ix, placeholder, image) in &placeholders {
+            self.pending_images[*ix].substitute =
+                self.image_cache.get(image).map(|xy| (*placeholder, xy));
+        }
+    }
+
+    /// Adds an image to the atlas, growing it if needed.
+    fn insert_image(&mut self, image: &Image) {
+        while self.image_cache.insert(image).is_none() {
+            // We failed to allocate. Try to bump the atlas size. If the atlas is already
+            // maximum size, there's nothing we can do, and this image isn't rendered. Other
+            // images might still fit.
+            if !self.image_cache.bump_size() {
+                break;
+            }
+        }
+    }
+}
+
+/// An encoding whose glyph runs have been resolved into plain paths.
+///
+/// Created with [`Resolver::resolve_fragment`]. Appending a fragment with
+/// [`Encoding::append_fragment`] only copies its streams and relocates the offsets of its
+/// gradients and images, so fragments can be prepared on worker threads and assembled into a
+/// scene with very little work.
+#[derive(Clone, Default)]
+pub struct Fragment {
+    pub(crate) encoding: Encoding,
+}
+
+impl Fragment {
+    /// Returns the resolved encoding.
+    pub fn encoding(&self) -> &Encoding {
+        &self.encoding
+    }
+
+    /// Consumes the fragment, returning the resolved encoding.
+    pub fn into_encoding(self) -> Encoding {
+        self.encoding
+    }
+}
+
+/// Computes the transform of a glyph in a resolved glyph run.
+fn glyph_transform(run: &GlyphRun, glyph: Glyph, transform: Transform, scale: f32) -> Transform {
+    let xform = transform
+        * Transform {
+            matrix: [1.0, 0.0, 0.0, -1.0],
+            translation: [glyph.x * scale, glyph.y * scale],
+        };
+    match run.glyph_transform {
+        Some(glyph_transform) => xform * glyph_transform,
+        None => xform,
+    }
+}
+
+/// Returns the bounds of a glyph run in device space, from the bounding box of its font, or
+/// `None` if the font has none.
+fn glyph_run_bounds(
+    run: &GlyphRun,
+    glyphs: &[Glyph],
+    coords: &[crate::NormalizedCoord],
+    font_size: f32,
+    transform: Transform,
+    scale: f32,
+) -> Option<Rect> {
+    let font = skrifa::FontRef::from_index(run.font.data.as_ref(), run.font.index).ok()?;
+    let coords: &[skrifa::instance::NormalizedCoord] = bytemuck::cast_slice(coords);
+    let bbox = font
+        .metrics(Size::new(font_size), LocationRef::new(coords))
+        .bounds?;
+    // Hinting can move the outlines by a fraction of a pixel.
+    let mut margin = 1.0;
+    if let peniko::Style::Stroke(stroke) = &run.style {
+        margin += stroke.width * 0.5;
+    }
+    let bbox = Rect::new(
+        bbox.x_min.into(),
+        bbox.y_min.into(),
+        bbox.x_max.into(),
+        bbox.y_max.into(),
+    )
+    .inflate(margin, margin);
+    glyphs
+        .iter()
+        .map(|glyph| {
+            glyph_transform(run, *glyph, transform, scale)
+                .to_kurbo()
+                .transform_rect_bbox(bbox)
+        })
+        .reduce(|a, b| a.union(b))
+}
+
+/// Whether each draw object of an encoding is within a cull rect.
+struct Visibility {
+    /// The offset of the data of each draw object in the draw data stream, in bytes.
+    offsets: Vec<usize>,
+    visible: Vec<bool>,
+}
+
+impl Visibility {
+    fn new(encoding: &Encoding, cull_rect: Rect) -> Self {
+        let mut offsets = Vec::with_capacity(encoding.draw_tags.len());
+        let mut visible = Vec::with_capacity(encoding.draw_tags.len());
+        let mut offset = 0;
+        for draw in draw_geometry(encoding) {
+            offsets.push(offset);
+            // Draws whose bounds aren't known, such as glyph runs, are assumed to be visible.
+            visible.push(
+                draw.bounds
+                    .is_none_or(|bounds| bounds.intersect(cull_rect).area() > 0.0),
+            );
+            // Bits 2 to 4 of the tag are the size of its data in words.
+            offset += ((draw.tag.0 >> 2) & 0x7) as usize * 4;
+        }
+        Self { offsets, visible }
+    }
+
+    /// Returns `true` if the draw object whose data contains `draw_data_offset` is visible.
+    fn contains(&self, draw_data_offset: usize) -> bool {
+        let ix = self
+            .offsets
+            .partition_point(|offset| *offset <= draw_data_offset);
+        ix.checked_sub(1)
+            .is_none_or(|ix| self.visible.get(ix).copied().unwrap_or(true))
     }
 }
 
@@ -518,12 +1008,19 @@ pub enum Patch {
         /// Index in the glyph run buffer.
         index: usize,
     },
+    /// Stroke dashed at resolve time.
+    DashedStroke {
+        /// Index in the dashed stroke buffer.
+        index: usize,
+    },
     /// Image resource.
     Image {
         /// Offset to the atlas coordinates in the draw data stream.
         draw_data_offset: usize,
         /// Underlying image data.
         image: Image,
+        /// What to draw until the image is uploaded, if not the default.
+        placeholder: Option<Placeholder>,
     },
 }
 
@@ -531,7 +1028,16 @@ pub enum Patch {
 #[derive(Clone, Debug)]
 struct PendingImage {
     image: Image,
+    /// The placeholder chosen for this draw, overriding the default.
+    placeholder: Option<Placeholder>,
+    /// Whether the draw is outside of the cull rect, so the image isn't added to the atlas.
+    culled: bool,
+    /// The exponent of the power of two by which `image` was scaled down from the image which
+    /// was drawn, as it was too large for the atlas.
+    downscale: u32,
     xy: Option<(u32, u32)>,
+    /// The placeholder drawn until the image is uploaded, and its location.
+    substitute: Option<(Placeholder, (u32, u32))>,
 }
 
 #[derive(Clone, Debug)]
@@ -554,6 +1060,12 @@ enum ResolvedPatch {
         /// Additional scale factor to apply to translation.
         scale: f32,
     },
+    DashedStroke {
+        /// Index of the original stroke in the encoding.
+        index: usize,
+        /// Index of the dashed segments in `Resolver::dashes`.
+        dashes: usize,
+    },
     Image {
         /// Index of pending image element.
         index: usize,
@@ -603,3 +1115,84 @@ fn size_to_words(byte_size: usize) -> u32 {
 fn align_up(len: usize, alignment: u32) -> usize {
     len + (len.wrapping_neg() & (alignment as usize - 1))
 }
+
+#[cfg(test)]
+mod tests {
+    use peniko::color::palette;
+    use peniko::kurbo::{Affine, Rect};
+    use peniko::{Blob, Fill, Gradient, Image, ImageFormat};
+    use std::sync::Arc;
+
+    use super::Resolver;
+    use crate::{Encoding, Transform};
+
+    const CULL_RECT: Rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+
+    fn fill(encoding: &mut Encoding, rect: Rect) {
+        encoding.encode_transform(Transform::from_kurbo(&Affine::IDENTITY));
+        encoding.encode_fill_style(Fill::NonZero);
+        encoding.encode_shape(&rect, true);
+    }
+
+    fn image() -> Image {
+        Image::new(
+            Blob::new(Arc::new(vec![0_u8; 4 * 8 * 8])),
+            ImageFormat::Rgba8,
+            8,
+            8,
+        )
+    }
+
+    #[test]
+    fn offscreen_images_are_not_uploaded() {
+        let (visible, offscreen) = (image(), image());
+        let mut encoding = Encoding::new();
+        fill(&mut encoding, Rect::new(10.0, 10.0, 18.0, 18.0));
+        encoding.encode_image(&visible, 1.0);
+        fill(&mut encoding, Rect::new(200.0, 10.0, 208.0, 18.0));
+        encoding.encode_image(&offscreen, 1.0);
+
+        let mut resolver = Resolver::new();
+        resolver.set_cull_rect(Some(CULL_RECT));
+        let (_, _, images) = resolver.resolve(&encoding, &mut Vec::new());
+        let ids: Vec<_> = images
+            .images
+            .iter()
+            .map(|(image, ..)| image.data.id())
+            .collect();
+        assert!(ids.contains(&visible.data.id()));
+        assert!(!ids.contains(&offscreen.data.id()));
+
+        // Once culling is turned off, the image is uploaded.
+        resolver.set_cull_rect(None);
+        let (_, _, images) = resolver.resolve(&encoding, &mut Vec::new());
+        let ids: Vec<_> = images
+            .images
+            .iter()
+            .map(|(image, ..)| image.data.id())
+            .collect();
+        assert!(ids.contains(&offscreen.data.id()));
+    }
+
+    #[test]
+    fn offscreen_gradients_are_not_baked() {
+        let mut encoding = Encoding::new();
+        for (rect, color) in [
+            (Rect::new(10.0, 10.0, 50.0, 50.0), palette::css::RED),
+            (Rect::new(10.0, 200.0, 50.0, 250.0), palette::css::BLUE),
+        ] {
+            fill(&mut encoding, rect);
+            let gradient = Gradient::new_linear((rect.x0, rect.y0), (rect.x1, rect.y1))
+                .with_stops([color, palette::css::WHITE]);
+            encoding.encode_brush(&gradient, 1.0);
+        }
+
+        let mut resolver = Resolver::new();
+        resolver.set_cull_rect(Some(CULL_RECT));
+        let (_, ramps, _) = resolver.resolve(&encoding, &mut Vec::new());
+        assert_eq!(ramps.height, 1);
+        resolver.set_cull_rect(None);
+        let (_, ramps, _) = resolver.resolve(&encoding, &mut Vec::new());
+        assert_eq!(ramps.height, 2);
+    }
+}
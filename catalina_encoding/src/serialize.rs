@@ -0,0 +1,917 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Binary serialization of encodings.
+//!
+//! The format starts with a magic number and [`Encoding::FORMAT_VERSION`], followed by the
+//! data of every blob referenced by the encoding, such as images and fonts, or the handle of
+//! the resource in a [`ResourceTable`] which holds it, and then the streams and resources of
+//! the encoding. All numbers are little endian. The streams of `Pod`
+//! types are stored as their bytes, so the format depends on the layout of those types, and
+//! the version is incremented whenever any of them change.
+//!
+//! Reading checks that the counts of paths, segments and clips match the path and draw tag
+//! streams, that the path and draw data have the sizes given by their tags, and that the
+//! resources only refer to data which the encoding contains. The values within the streams
+//! aren't checked, such as the coordinates of paths or the parameters of each draw, so
+//! encodings from untrusted sources may still render as garbage.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytemuck::Pod;
+use peniko::color::{AlphaColor, DynamicColor, Srgb};
+use peniko::kurbo::{BezPath, Cap, Join, PathEl, Point, Stroke};
+use peniko::{Blob, ColorStop, Extend, Fill, Font, Image, ImageFormat, ImageQuality, Style};
+
+use crate::handles::BlobRef;
+use crate::{
+    DashedStroke, DrawMonoid, Encoding, FontId, Glyph, GlyphRun, ImageId, Monoid, Patch,
+    PathMonoid, Placeholder, ResourceTable, StreamOffsets,
+};
+
+/// Identifies serialized encodings.
+const MAGIC: [u8; 4] = *b"CTLE";
+
+/// An error when reading a serialized encoding with [`Encoding::from_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data doesn't start with the magic number of a serialized encoding.
+    NotAnEncoding,
+    /// The encoding was serialized with a different version of the format, such as by an
+    /// older version of this crate.
+    UnsupportedVersion(u32),
+    /// The data ends partway through the encoding.
+    UnexpectedEnd,
+    /// The data is malformed, such as a reference to a resource which doesn't exist.
+    Invalid(&'static str),
+    /// The encoding refers to an image or font by a handle which isn't in the
+    /// [`ResourceTable`] it's read with, given as its [bits](ImageId::to_bits).
+    MissingResource(u64),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnEncoding => write!(f, "data isn't a serialized encoding"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "encoding format version {version} is unsupported, expected {}",
+                Encoding::FORMAT_VERSION
+            ),
+            Self::UnexpectedEnd => write!(f, "serialized encoding is truncated"),
+            Self::Invalid(reason) => write!(f, "serialized encoding is invalid: {reason}"),
+            Self::MissingResource(bits) => {
+                write!(
+                    f,
+                    "serialized encoding refers to missing resource {bits:#x}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Encoding {
+    /// The version of the format written by [`to_bytes`](Self::to_bytes), which is
+    /// incremented whenever the format changes.
+    pub const FORMAT_VERSION: u32 = 2;
+
+    /// Serializes the encoding, including the data of its images and fonts.
+    ///
+    /// Blobs shared by several resources are only written once. Gradient stops are stored
+    /// in sRGB, which is the color space they are interpolated in when resolved.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.write_bytes(|_| None)
+    }
+
+    /// Serializes the encoding like [`to_bytes`](Self::to_bytes), but writes the handles of
+    /// the images and fonts which are in `table` in place of their data.
+    ///
+    /// This keeps the output small when many encodings share resources, such as the pages
+    /// of a document. The encoding must be read with
+    /// [`from_bytes_with_table`](Self::from_bytes_with_table) and a table which holds the
+    /// same resources under the same handles.
+    pub fn to_bytes_with_table<F>(&self, table: &ResourceTable<F>) -> Vec<u8> {
+        self.write_bytes(|blob| table.blob_ref(blob))
+    }
+
+    fn write_bytes(&self, blob_ref: impl Fn(u64) -> Option<BlobRef>) -> Vec<u8> {
+        let mut body = Writer::default();
+        body.pod_slice(&self.path_tags);
+        body.bytes(&self.path_data);
+        body.pod_slice(&self.draw_tags);
+        body.bytes(&self.draw_data);
+        body.pod_slice(&self.transforms);
+        body.pod_slice(&self.styles);
+        for count in [
+            self.n_paths,
+            self.n_path_segments,
+            self.n_clips,
+            self.n_open_clips,
+            self.flags,
+        ] {
+            body.u32(count);
+        }
+
+        let resources = &self.resources;
+        body.usize(resources.patches.len());
+        for patch in &resources.patches {
+            body.patch(patch);
+        }
+        body.usize(resources.color_stops.len());
+        for stop in &resources.color_stops {
+            body.f32(stop.offset);
+            body.pod(&stop.color.to_alpha_color::<Srgb>().components);
+        }
+        body.usize(resources.glyphs.len());
+        for glyph in &resources.glyphs {
+            body.u32(glyph.id);
+            body.f32(glyph.x);
+            body.f32(glyph.y);
+        }
+        body.usize(resources.glyph_runs.len());
+        for run in &resources.glyph_runs {
+            body.glyph_run(run);
+        }
+        body.pod_slice(&resources.normalized_coords);
+        body.usize(resources.dashed_strokes.len());
+        for stroke in &resources.dashed_strokes {
+            body.path(&stroke.path);
+            body.f64(stroke.dash_offset);
+            body.pod_slice(&stroke.dash_pattern);
+            body.stream_offsets(&stroke.stream_offsets);
+        }
+
+        let mut out = Writer::default();
+        out.bytes.extend_from_slice(&MAGIC);
+        out.u32(Self::FORMAT_VERSION);
+        out.usize(body.blobs.len());
+        for blob in &body.blobs {
+            match blob_ref(blob.id()) {
+                None => {
+                    out.u8(0);
+                    out.bytes(blob.data());
+                }
+                Some(BlobRef::Image(id)) => {
+                    out.u8(1);
+                    out.pod(&id.to_bits());
+                }
+                Some(BlobRef::Font(id)) => {
+                    out.u8(2);
+                    out.pod(&id.to_bits());
+                }
+            }
+        }
+        out.bytes.extend_from_slice(&body.bytes);
+        out.bytes
+    }
+
+    /// Reads an encoding serialized with [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns an error if the data was written with a different
+    /// [`FORMAT_VERSION`](Self::FORMAT_VERSION), so that stale caches can be discarded, or if
+    /// it is truncated or refers to resources which it doesn't contain.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::read_bytes(bytes, |_| None)
+    }
+
+    /// Reads an encoding serialized with [`to_bytes_with_table`](Self::to_bytes_with_table),
+    /// taking the data of the images and fonts it refers to from `table`.
+    ///
+    /// Returns [`DecodeError::MissingResource`] if a resource has been removed from the table.
+    pub fn from_bytes_with_table<F>(
+        bytes: &[u8],
+        table: &ResourceTable<F>,
+    ) -> Result<Self, DecodeError> {
+        Self::read_bytes(bytes, |blob| table.blob(blob).cloned())
+    }
+
+    fn read_bytes(
+        bytes: &[u8],
+        resolve: impl Fn(BlobRef) -> Option<Blob<u8>>,
+    ) -> Result<Self, DecodeError> {
+        let mut reader = Reader {
+            bytes,
+            blobs: vec![],
+        };
+        if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(DecodeError::NotAnEncoding);
+        }
+        let version = reader.u32()?;
+        if version != Self::FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        for _ in 0..reader.usize()? {
+            let blob = match reader.u8()? {
+                0 => Blob::new(Arc::new(reader.byte_vec()?)),
+                kind @ (1 | 2) => {
+                    let bits = reader.pod::<u64>()?;
+                    let blob = if kind == 1 {
+                        BlobRef::Image(ImageId::from_bits(bits))
+                    } else {
+                        BlobRef::Font(FontId::from_bits(bits))
+                    };
+                    resolve(blob).ok_or(DecodeError::MissingResource(bits))?
+                }
+                _ => return Err(DecodeError::Invalid("invalid blob kind")),
+            };
+            reader.blobs.push(blob);
+        }
+
+        let mut encoding = Self {
+            path_tags: reader.pod_vec()?,
+            path_data: reader.byte_vec()?,
+            draw_tags: reader.pod_vec()?,
+            draw_data: reader.byte_vec()?,
+            transforms: reader.pod_vec()?,
+            styles: reader.pod_vec()?,
+            ..Self::default()
+        };
+        encoding.n_paths = reader.u32()?;
+        encoding.n_path_segments = reader.u32()?;
+        encoding.n_clips = reader.u32()?;
+        encoding.n_open_clips = reader.u32()?;
+        encoding.flags = reader.u32()?;
+
+        let resources = &mut encoding.resources;
+        for _ in 0..reader.usize()? {
+            resources.patches.push(reader.patch()?);
+        }
+        for _ in 0..reader.usize()? {
+            let offset = reader.f32()?;
+            let components = reader.pod::<[f32; 4]>()?;
+            resources.color_stops.push(ColorStop {
+                offset,
+                color: DynamicColor::from_alpha_color(AlphaColor::<Srgb>::new(components)),
+            });
+        }
+        for _ in 0..reader.usize()? {
+            resources.glyphs.push(Glyph {
+                id: reader.u32()?,
+                x: reader.f32()?,
+                y: reader.f32()?,
+            });
+        }
+        for _ in 0..reader.usize()? {
+            resources.glyph_runs.push(reader.glyph_run()?);
+        }
+        resources.normalized_coords = reader.pod_vec()?;
+        for _ in 0..reader.usize()? {
+            resources.dashed_strokes.push(DashedStroke {
+                path: reader.path()?,
+                dash_offset: reader.f64()?,
+                dash_pattern: reader.pod_vec()?,
+                stream_offsets: reader.stream_offsets()?,
+            });
+        }
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::Invalid("trailing data"));
+        }
+        validate(&encoding)?;
+        Ok(encoding)
+    }
+}
+
+/// Checks that the counts and data of a decoded encoding match its tag streams, which the
+/// GPU stages use to index the other streams.
+fn validate_streams(encoding: &Encoding) -> Result<(), DecodeError> {
+    // The counts are summed as `usize`, as those of the monoids can overflow for corrupt data.
+    let (mut paths, mut segments, mut path_words, mut transforms, mut styles) = (0, 0, 0, 0, 0);
+    for tag in &encoding.path_tags {
+        let monoid = PathMonoid::new(u32::from(tag.0));
+        paths += monoid.path_ix as usize;
+        segments += monoid.pathseg_ix as usize;
+        path_words += monoid.pathseg_offset as usize;
+        transforms += monoid.trans_ix as usize;
+        styles += usize::from(monoid.style_ix != 0);
+    }
+    if paths != encoding.n_paths as usize || segments != encoding.n_path_segments as usize {
+        return Err(DecodeError::Invalid(
+            "path counts don't match the path tags",
+        ));
+    }
+    if path_words * 4 != encoding.path_data.len() {
+        return Err(DecodeError::Invalid(
+            "path data doesn't match the path tags",
+        ));
+    }
+    if transforms > encoding.transforms.len() || styles > encoding.styles.len() {
+        return Err(DecodeError::Invalid(
+            "path tags refer to missing transforms or styles",
+        ));
+    }
+    let (mut clips, mut draw_words) = (0, 0);
+    for tag in &encoding.draw_tags {
+        let monoid = DrawMonoid::new(*tag);
+        clips += monoid.clip_ix as usize;
+        draw_words += monoid.scene_offset as usize;
+    }
+    if clips != encoding.n_clips as usize || encoding.n_open_clips > encoding.n_clips {
+        return Err(DecodeError::Invalid(
+            "clip counts don't match the draw tags",
+        ));
+    }
+    if draw_words * 4 != encoding.draw_data.len() {
+        return Err(DecodeError::Invalid(
+            "draw data doesn't match the draw tags",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the resources of a decoded encoding only refer to data which it contains.
+fn validate(encoding: &Encoding) -> Result<(), DecodeError> {
+    let resources = &encoding.resources;
+    let in_draw_data = |offset: usize| offset.saturating_add(4) <= encoding.draw_data.len();
+    let in_range = |range: &Range<usize>, len: usize| range.start <= range.end && range.end <= len;
+    let in_streams = |offsets: &StreamOffsets| {
+        offsets.path_tags <= encoding.path_tags.len()
+            && offsets.path_data <= encoding.path_data.len()
+            && offsets.draw_tags <= encoding.draw_tags.len()
+            && offsets.draw_data <= encoding.draw_data.len()
+            && offsets.transforms <= encoding.transforms.len()
+            && offsets.styles <= encoding.styles.len()
+    };
+    if encoding.path_data.len() % 4 != 0 || encoding.draw_data.len() % 4 != 0 {
+        return Err(DecodeError::Invalid(
+            "stream is not a whole number of words",
+        ));
+    }
+    validate_streams(encoding)?;
+    for patch in &resources.patches {
+        let valid = match patch {
+            Patch::Ramp {
+                draw_data_offset,
+                stops,
+                ..
+            } => in_draw_data(*draw_data_offset) && in_range(stops, resources.color_stops.len()),
+            Patch::GlyphRun { index } => *index < resources.glyph_runs.len(),
+            Patch::DashedStroke { index } => *index < resources.dashed_strokes.len(),
+            Patch::Image {
+                draw_data_offset, ..
+            } => in_draw_data(*draw_data_offset),
+        };
+        if !valid {
+            return Err(DecodeError::Invalid("patch refers to missing data"));
+        }
+    }
+    for run in &resources.glyph_runs {
+        if !in_range(&run.glyphs, resources.glyphs.len())
+            || !in_range(&run.normalized_coords, resources.normalized_coords.len())
+            || !in_streams(&run.stream_offsets)
+        {
+            return Err(DecodeError::Invalid("glyph run refers to missing data"));
+        }
+    }
+    for stroke in &resources.dashed_strokes {
+        if !in_streams(&stroke.stream_offsets) {
+            return Err(DecodeError::Invalid("dashed stroke refers to missing data"));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+    /// The blobs referenced by the data, by index.
+    blobs: Vec<Blob<u8>>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.bytes.extend_from_slice(&(value as u64).to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.usize(bytes.len());
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn pod<T: Pod>(&mut self, value: &T) {
+        self.bytes.extend_from_slice(bytemuck::bytes_of(value));
+    }
+
+    fn pod_slice<T: Pod>(&mut self, values: &[T]) {
+        self.usize(values.len());
+        self.bytes.extend_from_slice(bytemuck::cast_slice(values));
+    }
+
+    fn range(&mut self, range: &Range<usize>) {
+        self.usize(range.start);
+        self.usize(range.end);
+    }
+
+    fn blob(&mut self, blob: &Blob<u8>) {
+        let index = match self.blobs.iter().position(|b| b.id() == blob.id()) {
+            Some(index) => index,
+            None => {
+                self.blobs.push(blob.clone());
+                self.blobs.len() - 1
+            }
+        };
+        self.usize(index);
+    }
+
+    fn stream_offsets(&mut self, offsets: &StreamOffsets) {
+        for offset in [
+            offsets.path_tags,
+            offsets.path_data,
+            offsets.draw_tags,
+            offsets.draw_data,
+            offsets.transforms,
+            offsets.styles,
+        ] {
+            self.usize(offset);
+        }
+    }
+
+    fn patch(&mut self, patch: &Patch) {
+        match patch {
+            Patch::Ramp {
+                draw_data_offset,
+                stops,
+                extend,
+            } => {
+                self.u8(0);
+                self.usize(*draw_data_offset);
+                self.range(stops);
+                self.u8(*extend as u8);
+            }
+            Patch::GlyphRun { index } => {
+                self.u8(1);
+                self.usize(*index);
+            }
+            Patch::DashedStroke { index } => {
+                self.u8(2);
+                self.usize(*index);
+            }
+            Patch::Image {
+                draw_data_offset,
+                image,
+                placeholder,
+            } => {
+                self.u8(3);
+                self.usize(*draw_data_offset);
+                self.image(image);
+                match placeholder {
+                    None => self.u8(0),
+                    Some(Placeholder::Skip) => self.u8(1),
+                    Some(Placeholder::Color(color)) => {
+                        self.u8(2);
+                        self.pod(&color.components);
+                    }
+                    Some(Placeholder::Checkerboard) => self.u8(3),
+                }
+            }
+        }
+    }
+
+    /// Writes an image, whose format isn't stored as `Rgba8` is the only format.
+    fn image(&mut self, image: &Image) {
+        self.blob(&image.data);
+        self.u32(image.width);
+        self.u32(image.height);
+        self.u8(image.x_extend as u8);
+        self.u8(image.y_extend as u8);
+        self.u8(image.quality as u8);
+        self.f32(image.alpha);
+    }
+
+    fn glyph_run(&mut self, run: &GlyphRun) {
+        self.blob(&run.font.data);
+        self.u32(run.font.index);
+        self.pod(&run.transform);
+        match &run.glyph_transform {
+            Some(transform) => {
+                self.bool(true);
+                self.pod(transform);
+            }
+            None => self.bool(false),
+        }
+        self.f32(run.font_size);
+        self.bool(run.hint);
+        self.range(&run.normalized_coords);
+        match &run.style {
+            Style::Fill(fill) => {
+                self.u8(0);
+                self.u8(match fill {
+                    Fill::NonZero => 0,
+                    Fill::EvenOdd => 1,
+                });
+            }
+            Style::Stroke(stroke) => {
+                self.u8(1);
+                self.f64(stroke.width);
+                self.u8(match stroke.join {
+                    Join::Bevel => 0,
+                    Join::Miter => 1,
+                    Join::Round => 2,
+                });
+                self.f64(stroke.miter_limit);
+                for cap in [stroke.start_cap, stroke.end_cap] {
+                    self.u8(match cap {
+                        Cap::Butt => 0,
+                        Cap::Square => 1,
+                        Cap::Round => 2,
+                    });
+                }
+                self.pod_slice(&stroke.dash_pattern);
+                self.f64(stroke.dash_offset);
+            }
+        }
+        self.range(&run.glyphs);
+        self.stream_offsets(&run.stream_offsets);
+    }
+
+    fn path(&mut self, path: &BezPath) {
+        let elements = path.elements();
+        self.usize(elements.len());
+        for element in elements {
+            match *element {
+                PathEl::MoveTo(p) => {
+                    self.u8(0);
+                    self.point(p);
+                }
+                PathEl::LineTo(p) => {
+                    self.u8(1);
+                    self.point(p);
+                }
+                PathEl::QuadTo(p1, p2) => {
+                    self.u8(2);
+                    self.point(p1);
+                    self.point(p2);
+                }
+                PathEl::CurveTo(p1, p2, p3) => {
+                    self.u8(3);
+                    self.point(p1);
+                    self.point(p2);
+                    self.point(p3);
+                }
+                PathEl::ClosePath => self.u8(4),
+            }
+        }
+    }
+
+    fn point(&mut self, point: Point) {
+        self.f64(point.x);
+        self.f64(point.y);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    /// The blobs read from the start of the data.
+    blobs: Vec<Blob<u8>>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if len > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        self.pod()
+    }
+
+    fn usize(&mut self) -> Result<usize, DecodeError> {
+        usize::try_from(self.pod::<u64>()?).map_err(|_| DecodeError::Invalid("size overflow"))
+    }
+
+    fn f32(&mut self) -> Result<f32, DecodeError> {
+        self.pod()
+    }
+
+    fn f64(&mut self) -> Result<f64, DecodeError> {
+        self.pod()
+    }
+
+    fn bool(&mut self) -> Result<bool, DecodeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::Invalid("invalid bool")),
+        }
+    }
+
+    fn byte_vec(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let len = self.usize()?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    /// Reads a value written by [`Writer::pod`], which is little endian on the platforms
+    /// supported by this crate.
+    fn pod<T: Pod>(&mut self) -> Result<T, DecodeError> {
+        let bytes = self.take(size_of::<T>())?;
+        Ok(bytemuck::pod_read_unaligned(bytes))
+    }
+
+    fn pod_vec<T: Pod>(&mut self) -> Result<Vec<T>, DecodeError> {
+        let count = self.usize()?;
+        // The length is checked before allocating, so corrupt data can't exhaust memory.
+        let len = count
+            .checked_mul(size_of::<T>())
+            .ok_or(DecodeError::Invalid("size overflow"))?;
+        Ok(bytemuck::pod_collect_to_vec(self.take(len)?))
+    }
+
+    fn range(&mut self) -> Result<Range<usize>, DecodeError> {
+        Ok(self.usize()?..self.usize()?)
+    }
+
+    fn blob(&mut self) -> Result<Blob<u8>, DecodeError> {
+        let index = self.usize()?;
+        self.blobs
+            .get(index)
+            .cloned()
+            .ok_or(DecodeError::Invalid("reference to missing blob"))
+    }
+
+    fn stream_offsets(&mut self) -> Result<StreamOffsets, DecodeError> {
+        Ok(StreamOffsets {
+            path_tags: self.usize()?,
+            path_data: self.usize()?,
+            draw_tags: self.usize()?,
+            draw_data: self.usize()?,
+            transforms: self.usize()?,
+            styles: self.usize()?,
+        })
+    }
+
+    fn extend(&mut self) -> Result<Extend, DecodeError> {
+        match self.u8()? {
+            0 => Ok(Extend::Pad),
+            1 => Ok(Extend::Repeat),
+            2 => Ok(Extend::Reflect),
+            _ => Err(DecodeError::Invalid("invalid extend mode")),
+        }
+    }
+
+    fn patch(&mut self) -> Result<Patch, DecodeError> {
+        Ok(match self.u8()? {
+            0 => Patch::Ramp {
+                draw_data_offset: self.usize()?,
+                stops: self.range()?,
+                extend: self.extend()?,
+            },
+            1 => Patch::GlyphRun {
+                index: self.usize()?,
+            },
+            2 => Patch::DashedStroke {
+                index: self.usize()?,
+            },
+            3 => Patch::Image {
+                draw_data_offset: self.usize()?,
+                image: self.image()?,
+                placeholder: match self.u8()? {
+                    0 => None,
+                    1 => Some(Placeholder::Skip),
+                    2 => Some(Placeholder::Color(AlphaColor::new(self.pod()?))),
+                    3 => Some(Placeholder::Checkerboard),
+                    _ => return Err(DecodeError::Invalid("invalid placeholder")),
+                },
+            },
+            _ => return Err(DecodeError::Invalid("invalid patch")),
+        })
+    }
+
+    fn image(&mut self) -> Result<Image, DecodeError> {
+        let data = self.blob()?;
+        let width = self.u32()?;
+        let height = self.u32()?;
+        if data.data().len() as u64 != u64::from(width) * u64::from(height) * 4 {
+            return Err(DecodeError::Invalid("image data doesn't match its size"));
+        }
+        let x_extend = self.extend()?;
+        let y_extend = self.extend()?;
+        let quality = match self.u8()? {
+            0 => ImageQuality::Low,
+            1 => ImageQuality::Medium,
+            2 => ImageQuality::High,
+            _ => return Err(DecodeError::Invalid("invalid image quality")),
+        };
+        Ok(Image::new(data, ImageFormat::Rgba8, width, height)
+            .with_x_extend(x_extend)
+            .with_y_extend(y_extend)
+            .with_quality(quality)
+            .with_alpha(self.f32()?))
+    }
+
+    fn glyph_run(&mut self) -> Result<GlyphRun, DecodeError> {
+        let font = Font::new(self.blob()?, self.u32()?);
+        let transform = self.pod()?;
+        let glyph_transform = if self.bool()? {
+            Some(self.pod()?)
+        } else {
+            None
+        };
+        let font_size = self.f32()?;
+        let hint = self.bool()?;
+        let normalized_coords = self.range()?;
+        let style = match self.u8()? {
+            0 => Style::Fill(match self.u8()? {
+                0 => Fill::NonZero,
+                1 => Fill::EvenOdd,
+                _ => return Err(DecodeError::Invalid("invalid fill rule")),
+            }),
+            1 => {
+                let width = self.f64()?;
+                let join = match self.u8()? {
+                    0 => Join::Bevel,
+                    1 => Join::Miter,
+                    2 => Join::Round,
+                    _ => return Err(DecodeError::Invalid("invalid join")),
+                };
+                let miter_limit = self.f64()?;
+                let mut caps = [Cap::Butt; 2];
+                for cap in &mut caps {
+                    *cap = match self.u8()? {
+                        0 => Cap::Butt,
+                        1 => Cap::Square,
+                        2 => Cap::Round,
+                        _ => return Err(DecodeError::Invalid("invalid cap")),
+                    };
+                }
+                let dash_pattern = self.pod_vec::<f64>()?;
+                let dash_offset = self.f64()?;
+                Style::Stroke(
+                    Stroke::new(width)
+                        .with_join(join)
+                        .with_miter_limit(miter_limit)
+                        .with_start_cap(caps[0])
+                        .with_end_cap(caps[1])
+                        .with_dashes(dash_offset, dash_pattern),
+                )
+            }
+            _ => return Err(DecodeError::Invalid("invalid style")),
+        };
+        Ok(GlyphRun {
+            font,
+            transform,
+            glyph_transform,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            glyphs: self.range()?,
+            stream_offsets: self.stream_offsets()?,
+        })
+    }
+
+    fn path(&mut self) -> Result<BezPath, DecodeError> {
+        let mut path = BezPath::new();
+        for _ in 0..self.usize()? {
+            let element = match self.u8()? {
+                0 => PathEl::MoveTo(self.point()?),
+                1 => PathEl::LineTo(self.point()?),
+                2 => PathEl::QuadTo(self.point()?, self.point()?),
+                3 => PathEl::CurveTo(self.point()?, self.point()?, self.point()?),
+                4 => PathEl::ClosePath,
+                _ => return Err(DecodeError::Invalid("invalid path element")),
+            };
+            path.push(element);
+        }
+        Ok(path)
+    }
+
+    fn point(&mut self) -> Result<Point, DecodeError> {
+        Ok(Point::new(self.f64()?, self.f64()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use peniko::color::palette;
+    use peniko::kurbo::{Affine, BezPath, Line, Rect, Shape, Stroke};
+    use peniko::{Blob, Fill, Gradient, Image, ImageFormat};
+
+    use super::DecodeError;
+    use crate::{Encoding, Resolver, Transform};
+
+    fn test_encoding() -> Encoding {
+        let mut encoding = Encoding::new();
+        encoding.encode_transform(Transform::from_kurbo(&Affine::scale(2.0)));
+        encoding.encode_fill_style(Fill::EvenOdd);
+        encoding.encode_shape(&Rect::new(0.0, 0.0, 10.0, 10.0), true);
+        encoding.encode_color(palette::css::RED);
+        encoding.encode_shape(&Rect::new(10.0, 0.0, 20.0, 10.0), true);
+        encoding.encode_brush(
+            &Gradient::new_linear((0.0, 0.0), (10.0, 0.0))
+                .with_stops([palette::css::RED, palette::css::BLUE]),
+            1.0,
+        );
+        let image = Image::new(
+            Blob::new(Arc::new(vec![255_u8; 16])),
+            ImageFormat::Rgba8,
+            2,
+            2,
+        );
+        encoding.encode_shape(&Rect::new(0.0, 10.0, 2.0, 12.0), true);
+        encoding.encode_image(&image, 0.5);
+        // The same image is only stored once.
+        encoding.encode_shape(&Rect::new(2.0, 10.0, 4.0, 12.0), true);
+        encoding.encode_image(&image, 1.0);
+        let style = Stroke::new(1.0).with_dashes(0.0, [4.0, 2.0]);
+        let path: BezPath = Line::new((0.0, 20.0), (20.0, 20.0)).into_path(0.1);
+        encoding.encode_stroke_style(&style);
+        encoding.encode_dashed_stroke(path, &style);
+        encoding.encode_color(palette::css::BLUE);
+        encoding
+    }
+
+    #[test]
+    fn round_trip() {
+        let encoding = test_encoding();
+        let bytes = encoding.to_bytes();
+        let decoded = Encoding::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.n_paths, encoding.n_paths);
+        assert_eq!(decoded.resources.patches.len(), 4);
+
+        // The decoded encoding resolves to the same streams.
+        let resolve = |encoding: &Encoding| {
+            let mut resolver = Resolver::new();
+            let fragment = resolver.resolve_fragment(encoding);
+            let resolved = fragment.encoding();
+            (resolved.path_data.clone(), resolved.draw_data.clone())
+        };
+        assert_eq!(resolve(&decoded), resolve(&encoding));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = test_encoding().to_bytes();
+        bytes[4..8].copy_from_slice(&(Encoding::FORMAT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Encoding::from_bytes(&bytes).err(),
+            Some(DecodeError::UnsupportedVersion(
+                Encoding::FORMAT_VERSION + 1
+            ))
+        );
+        assert_eq!(
+            Encoding::from_bytes(b"not an encoding").err(),
+            Some(DecodeError::NotAnEncoding)
+        );
+    }
+
+    #[test]
+    fn rejects_inconsistent_streams() {
+        let encoding = test_encoding();
+        let mut paths = encoding.clone();
+        paths.n_paths += 1;
+        let mut path_data = encoding.clone();
+        path_data.path_data.truncate(path_data.path_data.len() - 4);
+        let mut draw_tags = encoding.clone();
+        draw_tags.draw_tags.pop();
+        let mut clips = encoding;
+        clips.n_clips += 1;
+        for encoding in [paths, path_data, draw_tags, clips] {
+            assert!(matches!(
+                Encoding::from_bytes(&encoding.to_bytes()),
+                Err(DecodeError::Invalid(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = test_encoding().to_bytes();
+        for len in [8, bytes.len() / 2, bytes.len() - 1] {
+            assert!(Encoding::from_bytes(&bytes[..len]).is_err());
+        }
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Arc;
+
+use peniko::color::{palette, AlphaColor, Srgb};
+use peniko::{Blob, Image, ImageFormat};
+
+/// The width and height of the checkerboard placeholder, in pixels.
+pub(crate) const CHECKERBOARD_SIZE: u32 = 16;
+
+/// The width and height of a cell of the checkerboard placeholder, in pixels.
+const CHECKERBOARD_CELL: u32 = 8;
+
+/// What is drawn in place of an image which hasn't been uploaded yet.
+///
+/// The default for every draw is set with [`UploadQueue::set_placeholder`], and can be
+/// overridden for a single draw when it is encoded. Glyph runs don't need placeholders, as
+/// their outlines are always resolved in the frame they are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placeholder {
+    /// Draw nothing.
+    Skip,
+    /// Fill the draw with a solid color.
+    Color(AlphaColor<Srgb>),
+    /// Fill the draw with a checkerboard in cells of 8 image pixels, repeated over the whole
+    /// draw whatever the extend mode of the image.
+    Checkerboard,
+}
+
+impl Default for Placeholder {
+    fn default() -> Self {
+        Self::Color(palette::css::SILVER)
+    }
+}
+
+/// What happens to images which are larger than the maximum size of the image atlas.
+///
+/// The maximum size is the smaller of 8192 pixels and the maximum texture dimension of the
+/// device.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OversizedImages {
+    /// Draw nothing.
+    Skip,
+    /// Upload a copy of the image scaled down by a power of two to fit, which is drawn at the
+    /// original size.
+    ///
+    /// The copy is made on the CPU when the image is first drawn, which is slow for very large
+    /// images, and kept while the image is drawn in every frame. Where the size of the image
+    /// isn't a multiple of the scale, the edges of the copy are sampled slightly differently
+    /// from the original.
+    #[default]
+    Downscale,
+}
+
+/// The order in which queued uploads are performed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UploadPriority {
+    /// Uploaded once nothing else is waiting, such as images which are prefetched.
+    Low,
+    /// The priority of images which are drawn without being queued first.
+    #[default]
+    Normal,
+    /// Uploaded before other images.
+    High,
+    /// Uploaded in the first frame it is needed, regardless of the budget.
+    Immediate,
+}
+
+#[derive(Clone, Debug)]
+struct QueuedUpload {
+    image: Image,
+    priority: UploadPriority,
+    /// Order of arrival, for uploads of the same priority.
+    serial: u64,
+}
+
+/// A queue of images waiting to be uploaded to the image atlas.
+///
+/// Each frame, the queued images are uploaded in order of priority until the frame's byte
+/// budget is spent. Images which are drawn before they are uploaded are added to the queue,
+/// and drawn as a placeholder until their upload happens. This spreads the cost of a burst of
+/// new images over several frames, instead of stalling the frame they first appear in.
+///
+/// Uploaded images stay in the atlas while they're used, so only new images count towards
+/// the budget.
+#[derive(Clone, Debug, Default)]
+pub struct UploadQueue {
+    bytes_per_frame: Option<u64>,
+    queue: Vec<QueuedUpload>,
+    serial: u64,
+    placeholder: Placeholder,
+    oversized_images: OversizedImages,
+    /// Images of the solid color placeholders which have been used.
+    color_images: Vec<([u8; 4], Image)>,
+    checkerboard: Option<Image>,
+}
+
+impl UploadQueue {
+    /// Creates an empty queue without a budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes of image data which may be uploaded each frame.
+    ///
+    /// `None` means that every image is uploaded in the frame it's needed.
+    pub fn bytes_per_frame(&self) -> Option<u64> {
+        self.bytes_per_frame
+    }
+
+    /// Sets the number of bytes of image data which may be uploaded each frame.
+    ///
+    /// An image larger than the budget is uploaded on its own in a frame.
+    pub fn set_bytes_per_frame(&mut self, bytes_per_frame: Option<u64>) {
+        self.bytes_per_frame = bytes_per_frame;
+    }
+
+    /// Returns what is drawn in place of images which haven't been uploaded yet, for draws
+    /// which don't choose their own placeholder.
+    pub fn placeholder(&self) -> Placeholder {
+        self.placeholder
+    }
+
+    /// Sets what is drawn in place of images which haven't been uploaded yet, for draws
+    /// which don't choose their own placeholder.
+    pub fn set_placeholder(&mut self, placeholder: Placeholder) {
+        self.placeholder = placeholder;
+    }
+
+    /// Returns what happens to images which are too large for the image atlas.
+    pub fn oversized_images(&self) -> OversizedImages {
+        self.oversized_images
+    }
+
+    /// Sets what happens to images which are too large for the image atlas.
+    pub fn set_oversized_images(&mut self, oversized_images: OversizedImages) {
+        self.oversized_images = oversized_images;
+    }
+
+    /// Queues `image` for upload with the given priority.
+    ///
+    /// If the image is already queued, its priority is raised to `priority`. An image which is
+    /// already in the atlas isn't uploaded again.
+    pub fn queue(&mut self, image: &Image, priority: UploadPriority) {
+        let id = image.data.id();
+        if let Some(queued) = self.queue.iter_mut().find(|q| q.image.data.id() == id) {
+            queued.priority = queued.priority.max(priority);
+            return;
+        }
+        self.queue.push(QueuedUpload {
+            image: image.clone(),
+            priority,
+            serial: self.serial,
+        });
+        self.serial += 1;
+    }
+
+    /// Removes `image` from the queue, if it hasn't been uploaded yet.
+    pub fn cancel(&mut self, image: &Image) {
+        let id = image.data.id();
+        self.queue.retain(|queued| queued.image.data.id() != id);
+    }
+
+    /// Returns `true` if `image` is waiting to be uploaded.
+    pub fn is_queued(&self, image: &Image) -> bool {
+        let id = image.data.id();
+        self.queue.iter().any(|queued| queued.image.data.id() == id)
+    }
+
+    /// Returns the number of images waiting to be uploaded.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no images are waiting to be uploaded.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the total size of the images waiting to be uploaded, in bytes.
+    pub fn pending_bytes(&self) -> u64 {
+        self.queue
+            .iter()
+            .map(|queued| image_bytes(&queued.image))
+            .sum()
+    }
+
+    /// Removes and returns the images to upload this frame, in order of priority.
+    pub(crate) fn admit(&mut self) -> Vec<Image> {
+        self.queue
+            .sort_by(|a, b| b.priority.cmp(&a.priority).then(a.serial.cmp(&b.serial)));
+        let mut spent = 0;
+        let mut count = 0;
+        for queued in &self.queue {
+            if queued.priority != UploadPriority::Immediate {
+                let bytes = image_bytes(&queued.image);
+                let within_budget = self
+                    .bytes_per_frame
+                    .is_none_or(|budget| spent + bytes <= budget);
+                // An upload larger than the whole budget is made on its own. Stop at the first
+                // upload which doesn't fit rather than skipping over it, so that large images
+                // aren't starved by smaller ones of a lower priority.
+                if spent != 0 && !within_budget {
+                    break;
+                }
+                spent += bytes;
+            }
+            count += 1;
+        }
+        self.queue
+            .drain(..count)
+            .map(|queued| queued.image)
+            .collect()
+    }
+
+    /// Returns the image drawn for `placeholder`, or `None` if nothing is drawn.
+    ///
+    /// Placeholder images are created once and reused, so that they stay in the atlas.
+    pub(crate) fn placeholder_image(&mut self, placeholder: Placeholder) -> Option<Image> {
+        match placeholder {
+            Placeholder::Skip => None,
+            Placeholder::Color(color) => {
+                // Image data is premultiplied.
+                let rgba = color.premultiply().to_rgba8().to_u8_array();
+                if let Some((_, image)) = self.color_images.iter().find(|(key, _)| *key == rgba) {
+                    return Some(image.clone());
+                }
+                let image = Image::new(Blob::new(Arc::new(rgba)), ImageFormat::Rgba8, 1, 1);
+                self.color_images.push((rgba, image.clone()));
+                Some(image)
+            }
+            Placeholder::Checkerboard => Some(
+                self.checkerboard
+                    .get_or_insert_with(checkerboard_image)
+                    .clone(),
+            ),
+        }
+    }
+}
+
+fn checkerboard_image() -> Image {
+    const LIGHT: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+    const DARK: [u8; 4] = [0xCC, 0xCC, 0xCC, 0xFF];
+    let data: Vec<u8> = (0..CHECKERBOARD_SIZE * CHECKERBOARD_SIZE)
+        .flat_map(|ix| {
+            let (x, y) = (ix % CHECKERBOARD_SIZE, ix / CHECKERBOARD_SIZE);
+            if (x / CHECKERBOARD_CELL + y / CHECKERBOARD_CELL) % 2 == 0 {
+                LIGHT
+            } else {
+                DARK
+            }
+        })
+        .collect();
+    Image::new(
+        Blob::new(Arc::new(data)),
+        ImageFormat::Rgba8,
+        CHECKERBOARD_SIZE,
+        CHECKERBOARD_SIZE,
+    )
+}
+
+fn image_bytes(image: &Image) -> u64 {
+    u64::from(image.width) * u64::from(image.height) * 4
+}
@@ -0,0 +1,104 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// C API for building and rendering Catalina scenes.
+// See `src/lib.rs` for the documentation of each function.
+
+#ifndef CATALINA_H
+#define CATALINA_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum CatalinaStatus {
+    CATALINA_STATUS_OK = 0,
+    CATALINA_STATUS_NULL_POINTER = 1,
+    CATALINA_STATUS_INVALID_ARGUMENT = 2,
+    CATALINA_STATUS_NO_DEVICE = 3,
+    CATALINA_STATUS_RENDERER_CREATION_FAILED = 4,
+    CATALINA_STATUS_RENDER_FAILED = 5,
+    CATALINA_STATUS_BUFFER_TOO_SMALL = 6,
+} CatalinaStatus;
+
+typedef enum CatalinaFillRule {
+    CATALINA_FILL_RULE_NON_ZERO = 0,
+    CATALINA_FILL_RULE_EVEN_ODD = 1,
+} CatalinaFillRule;
+
+typedef struct CatalinaAffine {
+    double coeffs[6];
+} CatalinaAffine;
+
+typedef struct CatalinaColor {
+    float r;
+    float g;
+    float b;
+    float a;
+} CatalinaColor;
+
+typedef struct CatalinaColorStop {
+    float offset;
+    CatalinaColor color;
+} CatalinaColorStop;
+
+typedef struct CatalinaGlyph {
+    uint32_t id;
+    float x;
+    float y;
+} CatalinaGlyph;
+
+typedef struct CatalinaScene CatalinaScene;
+typedef struct CatalinaPath CatalinaPath;
+typedef struct CatalinaFont CatalinaFont;
+typedef struct CatalinaRenderer CatalinaRenderer;
+
+CatalinaScene *catalina_scene_new(void);
+void catalina_scene_free(CatalinaScene *scene);
+CatalinaStatus catalina_scene_reset(CatalinaScene *scene);
+
+CatalinaPath *catalina_path_new(void);
+void catalina_path_free(CatalinaPath *path);
+CatalinaStatus catalina_path_move_to(CatalinaPath *path, double x, double y);
+CatalinaStatus catalina_path_line_to(CatalinaPath *path, double x, double y);
+CatalinaStatus catalina_path_quad_to(CatalinaPath *path, double x1, double y1, double x2, double y2);
+CatalinaStatus catalina_path_curve_to(CatalinaPath *path, double x1, double y1, double x2, double y2,
+                                      double x3, double y3);
+CatalinaStatus catalina_path_close(CatalinaPath *path);
+
+CatalinaStatus catalina_scene_fill_path(CatalinaScene *scene, const CatalinaPath *path,
+                                        uint32_t fill_rule, const CatalinaAffine *transform,
+                                        CatalinaColor color);
+CatalinaStatus catalina_scene_fill_path_linear_gradient(
+    CatalinaScene *scene, const CatalinaPath *path, uint32_t fill_rule,
+    const CatalinaAffine *transform, double start_x, double start_y, double end_x, double end_y,
+    const CatalinaColorStop *stops, size_t stop_count);
+CatalinaStatus catalina_scene_stroke_path(CatalinaScene *scene, const CatalinaPath *path,
+                                          double width, const CatalinaAffine *transform,
+                                          CatalinaColor color);
+CatalinaStatus catalina_scene_push_layer(CatalinaScene *scene, uint8_t mix, float alpha,
+                                         const CatalinaAffine *transform, const CatalinaPath *clip);
+CatalinaStatus catalina_scene_pop_layer(CatalinaScene *scene);
+
+CatalinaFont *catalina_font_new(const uint8_t *data, size_t len, uint32_t index);
+void catalina_font_free(CatalinaFont *font);
+CatalinaStatus catalina_scene_draw_glyphs(CatalinaScene *scene, const CatalinaFont *font,
+                                          float font_size, const CatalinaAffine *transform,
+                                          CatalinaColor color, const CatalinaGlyph *glyphs,
+                                          size_t glyph_count);
+
+CatalinaStatus catalina_renderer_new(CatalinaRenderer **out);
+void catalina_renderer_free(CatalinaRenderer *renderer);
+CatalinaStatus catalina_renderer_render_to_pixels(CatalinaRenderer *renderer,
+                                                  const CatalinaScene *scene, uint32_t width,
+                                                  uint32_t height, CatalinaColor base_color,
+                                                  uint8_t *pixels, size_t pixels_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,731 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A C API for building and rendering Catalina scenes.
+//!
+//! All objects are exposed as opaque handles which are created by a `catalina_*_new` function
+//! and must be released with the matching `catalina_*_free` function.
+//! Functions which can fail return a [`CatalinaStatus`], where [`CatalinaStatus::Ok`] is zero.
+//!
+//! The matching C header is available at `include/catalina.h`.
+
+// LINEBENDER LINT SET - lib.rs - v2
+// See https://linebender.org/wiki/canonical-lints/
+// These lints aren't included in Cargo.toml because they
+// shouldn't apply to examples and tests
+#![warn(unused_crate_dependencies)]
+#![warn(clippy::print_stdout, clippy::print_stderr)]
+// Targeting e.g. 32-bit means structs containing usize can give false positives for 64-bit.
+#![cfg_attr(target_pointer_width = "64", warn(clippy::trivially_copy_pass_by_ref))]
+// END LINEBENDER LINT SET
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use catalina::kurbo::{Affine, BezPath, Point, Stroke};
+use catalina::peniko::{BlendMode, Blob, Color, Compose, Fill, Font, Mix};
+use catalina::util::RenderContext;
+use catalina::{AaConfig, AaSupport, Glyph, RenderParams, Renderer, RendererOptions, Scene};
+
+/// The result of a fallible operation.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CatalinaStatus {
+    /// The operation succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// An argument was outside of its valid range.
+    InvalidArgument = 2,
+    /// No compatible GPU device could be found.
+    NoDevice = 3,
+    /// Creating the renderer failed.
+    RendererCreationFailed = 4,
+    /// Rendering the scene failed.
+    RenderFailed = 5,
+    /// The output buffer is too small for the rendered image.
+    BufferTooSmall = 6,
+}
+
+/// A 2D affine transformation, in the same order as [`Affine::as_coeffs`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CatalinaAffine {
+    /// The coefficients `[a, b, c, d, e, f]` of the transformation.
+    pub coeffs: [f64; 6],
+}
+
+/// An sRGB color with straight (not premultiplied) alpha, with components in `0..=1`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CatalinaColor {
+    /// The red component.
+    pub r: f32,
+    /// The green component.
+    pub g: f32,
+    /// The blue component.
+    pub b: f32,
+    /// The alpha component.
+    pub a: f32,
+}
+
+/// A color stop of a gradient.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CatalinaColorStop {
+    /// The offset of the stop, in `0..=1`.
+    pub offset: f32,
+    /// The color of the stop.
+    pub color: CatalinaColor,
+}
+
+/// A glyph to draw as part of a glyph run.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CatalinaGlyph {
+    /// The glyph identifier in the font.
+    pub id: u32,
+    /// The horizontal offset of the glyph, relative to the run's transform.
+    pub x: f32,
+    /// The vertical offset of the glyph, relative to the run's transform.
+    pub y: f32,
+}
+
+/// The rule used to determine the interior of a filled path.
+///
+/// Functions take the rule as a `u32` with the value of one of these variants, so that other
+/// values from C can be rejected with [`CatalinaStatus::InvalidArgument`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum CatalinaFillRule {
+    /// The non-zero winding rule.
+    NonZero = 0,
+    /// The even-odd rule.
+    EvenOdd = 1,
+}
+
+/// An opaque handle to a [`Scene`].
+pub struct CatalinaScene(Scene);
+
+/// An opaque handle to a path under construction.
+#[derive(Debug)]
+pub struct CatalinaPath(BezPath);
+
+/// An opaque handle to a font.
+#[derive(Debug)]
+pub struct CatalinaFont(Font);
+
+/// An opaque handle to a renderer, together with the GPU device it renders with.
+pub struct CatalinaRenderer {
+    context: RenderContext,
+    device_id: usize,
+    renderer: Renderer,
+}
+
+impl std::fmt::Debug for CatalinaScene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CatalinaScene").finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for CatalinaRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CatalinaRenderer")
+            .field("device_id", &self.device_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<CatalinaColor> for Color {
+    fn from(color: CatalinaColor) -> Self {
+        Self::new([color.r, color.g, color.b, color.a])
+    }
+}
+
+/// Reads an optional transform, where null means the identity.
+///
+/// # Safety
+///
+/// `transform` must be null or valid for reads.
+unsafe fn read_transform(transform: *const CatalinaAffine) -> Affine {
+    // Safety: The caller guarantees that a non-null `transform` is valid for reads.
+    match unsafe { transform.as_ref() } {
+        Some(transform) => Affine::new(transform.coeffs),
+        None => Affine::IDENTITY,
+    }
+}
+
+/// Builds a slice from a pointer and length, allowing a null pointer for an empty slice.
+///
+/// # Safety
+///
+/// If `len` is non-zero, `ptr` must be valid for reads of `len` elements.
+unsafe fn read_slice<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        // Safety: The caller guarantees that `ptr` is valid for reads of `len` elements.
+        Some(unsafe { std::slice::from_raw_parts(ptr, len) })
+    }
+}
+
+/// Creates a new, empty scene.
+///
+/// The scene must be released with [`catalina_scene_free`].
+#[no_mangle]
+pub extern "C" fn catalina_scene_new() -> *mut CatalinaScene {
+    Box::into_raw(Box::new(CatalinaScene(Scene::new())))
+}
+
+/// Releases a scene. Passing null is allowed and does nothing.
+///
+/// # Safety
+///
+/// `scene` must be null or have been created by [`catalina_scene_new`] and not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_free(scene: *mut CatalinaScene) {
+    if !scene.is_null() {
+        // Safety: The caller guarantees that `scene` was created by `Box::into_raw`.
+        drop(unsafe { Box::from_raw(scene) });
+    }
+}
+
+/// Removes all content from a scene, retaining its allocations.
+///
+/// # Safety
+///
+/// `scene` must be null or a valid scene handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_reset(scene: *mut CatalinaScene) -> CatalinaStatus {
+    // Safety: The caller guarantees that a non-null `scene` is a valid handle.
+    let Some(scene) = (unsafe { scene.as_mut() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    scene.0.reset();
+    CatalinaStatus::Ok
+}
+
+/// Creates a new, empty path.
+///
+/// The path must be released with [`catalina_path_free`].
+#[no_mangle]
+pub extern "C" fn catalina_path_new() -> *mut CatalinaPath {
+    Box::into_raw(Box::new(CatalinaPath(BezPath::new())))
+}
+
+/// Releases a path. Passing null is allowed and does nothing.
+///
+/// # Safety
+///
+/// `path` must be null or have been created by [`catalina_path_new`] and not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_path_free(path: *mut CatalinaPath) {
+    if !path.is_null() {
+        // Safety: The caller guarantees that `path` was created by `Box::into_raw`.
+        drop(unsafe { Box::from_raw(path) });
+    }
+}
+
+/// Applies `f` to the path behind `path`, returning [`CatalinaStatus::NullPointer`] if it is null.
+///
+/// # Safety
+///
+/// `path` must be null or a valid path handle.
+unsafe fn with_path(path: *mut CatalinaPath, f: impl FnOnce(&mut BezPath)) -> CatalinaStatus {
+    // Safety: The caller guarantees that a non-null `path` is a valid handle.
+    match unsafe { path.as_mut() } {
+        Some(path) => {
+            f(&mut path.0);
+            CatalinaStatus::Ok
+        }
+        None => CatalinaStatus::NullPointer,
+    }
+}
+
+/// Starts a new subpath at the given point.
+///
+/// # Safety
+///
+/// `path` must be null or a valid path handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_path_move_to(
+    path: *mut CatalinaPath,
+    x: f64,
+    y: f64,
+) -> CatalinaStatus {
+    // Safety: Forwarded from the caller.
+    unsafe { with_path(path, |path| path.move_to((x, y))) }
+}
+
+/// Adds a line to the given point.
+///
+/// # Safety
+///
+/// `path` must be null or a valid path handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_path_line_to(
+    path: *mut CatalinaPath,
+    x: f64,
+    y: f64,
+) -> CatalinaStatus {
+    // Safety: Forwarded from the caller.
+    unsafe { with_path(path, |path| path.line_to((x, y))) }
+}
+
+/// Adds a quadratic Bézier curve to the given point.
+///
+/// # Safety
+///
+/// `path` must be null or a valid path handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_path_quad_to(
+    path: *mut CatalinaPath,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+) -> CatalinaStatus {
+    // Safety: Forwarded from the caller.
+    unsafe { with_path(path, |path| path.quad_to((x1, y1), (x2, y2))) }
+}
+
+/// Adds a cubic Bézier curve to the given point.
+///
+/// # Safety
+///
+/// `path` must be null or a valid path handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_path_curve_to(
+    path: *mut CatalinaPath,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    x3: f64,
+    y3: f64,
+) -> CatalinaStatus {
+    // Safety: Forwarded from the caller.
+    unsafe { with_path(path, |path| path.curve_to((x1, y1), (x2, y2), (x3, y3))) }
+}
+
+/// Closes the current subpath.
+///
+/// # Safety
+///
+/// `path` must be null or a valid path handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_path_close(path: *mut CatalinaPath) -> CatalinaStatus {
+    // Safety: Forwarded from the caller.
+    unsafe { with_path(path, BezPath::close_path) }
+}
+
+/// Fills a path with a solid color.
+///
+/// `fill_rule` is the value of a [`CatalinaFillRule`].
+/// A null `transform` is treated as the identity.
+///
+/// # Safety
+///
+/// `scene` and `path` must be null or valid handles, and `transform` must be null or valid for
+/// reads.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_fill_path(
+    scene: *mut CatalinaScene,
+    path: *const CatalinaPath,
+    fill_rule: u32,
+    transform: *const CatalinaAffine,
+    color: CatalinaColor,
+) -> CatalinaStatus {
+    // Safety: The caller guarantees that non-null handles are valid.
+    let (Some(scene), Some(path)) = (unsafe { scene.as_mut() }, unsafe { path.as_ref() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    let Some(fill_rule) = fill_from_u32(fill_rule) else {
+        return CatalinaStatus::InvalidArgument;
+    };
+    // Safety: Forwarded from the caller.
+    let transform = unsafe { read_transform(transform) };
+    scene
+        .0
+        .fill(fill_rule, transform, Color::from(color), None, &path.0);
+    CatalinaStatus::Ok
+}
+
+/// Fills a path with a linear gradient between `start` and `end`.
+///
+/// `fill_rule` is the value of a [`CatalinaFillRule`].
+/// The gradient's coordinates are in the same space as the path.
+/// A null `transform` is treated as the identity.
+///
+/// # Safety
+///
+/// `scene` and `path` must be null or valid handles, `transform` must be null or valid for
+/// reads, and `stops` must be valid for reads of `stop_count` elements.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_fill_path_linear_gradient(
+    scene: *mut CatalinaScene,
+    path: *const CatalinaPath,
+    fill_rule: u32,
+    transform: *const CatalinaAffine,
+    start_x: f64,
+    start_y: f64,
+    end_x: f64,
+    end_y: f64,
+    stops: *const CatalinaColorStop,
+    stop_count: usize,
+) -> CatalinaStatus {
+    // Safety: The caller guarantees that non-null handles are valid.
+    let (Some(scene), Some(path)) = (unsafe { scene.as_mut() }, unsafe { path.as_ref() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    // Safety: Forwarded from the caller.
+    let Some(stops) = (unsafe { read_slice(stops, stop_count) }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    let Some(fill_rule) = fill_from_u32(fill_rule) else {
+        return CatalinaStatus::InvalidArgument;
+    };
+    // Safety: Forwarded from the caller.
+    let transform = unsafe { read_transform(transform) };
+    let gradient = catalina::peniko::Gradient::new_linear(
+        Point::new(start_x, start_y),
+        Point::new(end_x, end_y),
+    )
+    .with_stops(
+        stops
+            .iter()
+            .map(|stop| (stop.offset, Color::from(stop.color)))
+            .collect::<Vec<_>>()
+            .as_slice(),
+    );
+    scene.0.fill(fill_rule, transform, &gradient, None, &path.0);
+    CatalinaStatus::Ok
+}
+
+/// Strokes a path with a solid color.
+///
+/// A null `transform` is treated as the identity.
+///
+/// # Safety
+///
+/// `scene` and `path` must be null or valid handles, and `transform` must be null or valid for
+/// reads.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_stroke_path(
+    scene: *mut CatalinaScene,
+    path: *const CatalinaPath,
+    width: f64,
+    transform: *const CatalinaAffine,
+    color: CatalinaColor,
+) -> CatalinaStatus {
+    // Safety: The caller guarantees that non-null handles are valid.
+    let (Some(scene), Some(path)) = (unsafe { scene.as_mut() }, unsafe { path.as_ref() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    if !(width.is_finite() && width >= 0.0) {
+        return CatalinaStatus::InvalidArgument;
+    }
+    // Safety: Forwarded from the caller.
+    let transform = unsafe { read_transform(transform) };
+    scene.0.stroke(
+        &Stroke::new(width),
+        transform,
+        Color::from(color),
+        None,
+        &path.0,
+    );
+    CatalinaStatus::Ok
+}
+
+/// Pushes a layer clipped by `clip`, which is composited with `alpha` when popped.
+///
+/// `mix` is the numeric value of a `peniko::Mix` blend mode, where 0 is `Normal`.
+/// A null `transform` is treated as the identity.
+///
+/// # Safety
+///
+/// `scene` and `clip` must be null or valid handles, and `transform` must be null or valid for
+/// reads.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_push_layer(
+    scene: *mut CatalinaScene,
+    mix: u8,
+    alpha: f32,
+    transform: *const CatalinaAffine,
+    clip: *const CatalinaPath,
+) -> CatalinaStatus {
+    // Safety: The caller guarantees that non-null handles are valid.
+    let (Some(scene), Some(clip)) = (unsafe { scene.as_mut() }, unsafe { clip.as_ref() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    let Some(mix) = mix_from_u8(mix) else {
+        return CatalinaStatus::InvalidArgument;
+    };
+    // Safety: Forwarded from the caller.
+    let transform = unsafe { read_transform(transform) };
+    scene.0.push_layer(
+        BlendMode::new(mix, Compose::SrcOver),
+        alpha,
+        transform,
+        &clip.0,
+    );
+    CatalinaStatus::Ok
+}
+
+/// Pops the most recently pushed layer.
+///
+/// # Safety
+///
+/// `scene` must be null or a valid scene handle.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_pop_layer(scene: *mut CatalinaScene) -> CatalinaStatus {
+    // Safety: The caller guarantees that a non-null `scene` is a valid handle.
+    let Some(scene) = (unsafe { scene.as_mut() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    scene.0.pop_layer();
+    CatalinaStatus::Ok
+}
+
+fn fill_from_u32(rule: u32) -> Option<Fill> {
+    const NON_ZERO: u32 = CatalinaFillRule::NonZero as u32;
+    const EVEN_ODD: u32 = CatalinaFillRule::EvenOdd as u32;
+    match rule {
+        NON_ZERO => Some(Fill::NonZero),
+        EVEN_ODD => Some(Fill::EvenOdd),
+        _ => None,
+    }
+}
+
+fn mix_from_u8(mix: u8) -> Option<Mix> {
+    Some(match mix {
+        0 => Mix::Normal,
+        1 => Mix::Multiply,
+        2 => Mix::Screen,
+        3 => Mix::Overlay,
+        4 => Mix::Darken,
+        5 => Mix::Lighten,
+        6 => Mix::ColorDodge,
+        7 => Mix::ColorBurn,
+        8 => Mix::HardLight,
+        9 => Mix::SoftLight,
+        10 => Mix::Difference,
+        11 => Mix::Exclusion,
+        12 => Mix::Hue,
+        13 => Mix::Saturation,
+        14 => Mix::Color,
+        15 => Mix::Luminosity,
+        128 => Mix::Clip,
+        _ => return None,
+    })
+}
+
+/// Creates a font from the contents of a font file.
+///
+/// The data is copied, so the buffer can be released after this call.
+/// `index` selects the font within a collection, and should be zero otherwise.
+/// Returns null if `data` is null.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_font_new(
+    data: *const u8,
+    len: usize,
+    index: u32,
+) -> *mut CatalinaFont {
+    // Safety: Forwarded from the caller.
+    let Some(data) = (unsafe { read_slice(data, len) }) else {
+        return std::ptr::null_mut();
+    };
+    let font = Font::new(Blob::new(Arc::new(data.to_vec())), index);
+    Box::into_raw(Box::new(CatalinaFont(font)))
+}
+
+/// Releases a font. Passing null is allowed and does nothing.
+///
+/// # Safety
+///
+/// `font` must be null or have been created by [`catalina_font_new`] and not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_font_free(font: *mut CatalinaFont) {
+    if !font.is_null() {
+        // Safety: The caller guarantees that `font` was created by `Box::into_raw`.
+        drop(unsafe { Box::from_raw(font) });
+    }
+}
+
+/// Draws a run of glyphs from `font`, filled with a solid color.
+///
+/// A null `transform` is treated as the identity.
+///
+/// # Safety
+///
+/// `scene` and `font` must be null or valid handles, `transform` must be null or valid for
+/// reads, and `glyphs` must be valid for reads of `glyph_count` elements.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_scene_draw_glyphs(
+    scene: *mut CatalinaScene,
+    font: *const CatalinaFont,
+    font_size: f32,
+    transform: *const CatalinaAffine,
+    color: CatalinaColor,
+    glyphs: *const CatalinaGlyph,
+    glyph_count: usize,
+) -> CatalinaStatus {
+    // Safety: The caller guarantees that non-null handles are valid.
+    let (Some(scene), Some(font)) = (unsafe { scene.as_mut() }, unsafe { font.as_ref() }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    // Safety: Forwarded from the caller.
+    let Some(glyphs) = (unsafe { read_slice(glyphs, glyph_count) }) else {
+        return CatalinaStatus::NullPointer;
+    };
+    if !(font_size.is_finite() && font_size > 0.0) {
+        return CatalinaStatus::InvalidArgument;
+    }
+    // Safety: Forwarded from the caller.
+    let transform = unsafe { read_transform(transform) };
+    scene
+        .0
+        .draw_glyphs(&font.0)
+        .font_size(font_size)
+        .transform(transform)
+        .brush(Color::from(color))
+        .draw(
+            Fill::NonZero,
+            glyphs.iter().map(|glyph| Glyph {
+                id: glyph.id,
+                x: glyph.x,
+                y: glyph.y,
+            }),
+        );
+    CatalinaStatus::Ok
+}
+
+/// Creates a renderer using a GPU device chosen by wgpu.
+///
+/// On success, the renderer is written to `out` and must be released with
+/// [`catalina_renderer_free`].
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_renderer_new(out: *mut *mut CatalinaRenderer) -> CatalinaStatus {
+    if out.is_null() {
+        return CatalinaStatus::NullPointer;
+    }
+    let mut context = RenderContext::new();
+    let Some(device_id) = pollster::block_on(context.device(None)) else {
+        return CatalinaStatus::NoDevice;
+    };
+    let renderer = context.devices[device_id].create_renderer(RendererOptions {
+        surface_format: None,
+        use_cpu: false,
+        antialiasing_support: AaSupport::area_only(),
+        num_init_threads: NonZeroUsize::new(1),
+        gpu_stages_from: None,
+        fine_band_rows: None,
+        frames_in_flight: None,
+        disk_cache: None,
+        small_scenes: None,
+    });
+    let Ok(renderer) = renderer else {
+        return CatalinaStatus::RendererCreationFailed;
+    };
+    let renderer = Box::new(CatalinaRenderer {
+        context,
+        device_id,
+        renderer,
+    });
+    // Safety: `out` was checked to be non-null, and the caller guarantees it is valid for writes.
+    unsafe { out.write(Box::into_raw(renderer)) };
+    CatalinaStatus::Ok
+}
+
+/// Releases a renderer. Passing null is allowed and does nothing.
+///
+/// # Safety
+///
+/// `renderer` must be null or have been created by [`catalina_renderer_new`] and not yet
+/// released.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_renderer_free(renderer: *mut CatalinaRenderer) {
+    if !renderer.is_null() {
+        // Safety: The caller guarantees that `renderer` was created by `Box::into_raw`.
+        drop(unsafe { Box::from_raw(renderer) });
+    }
+}
+
+/// Renders a scene into a buffer of unpadded, non-premultiplied RGBA8 pixels.
+///
+/// `pixels` must have room for at least `width * height * 4` bytes, given in `pixels_len`.
+/// Large images are rendered in tiles, so their size is not limited by the GPU's maximum
+/// texture size. Returns [`CatalinaStatus::InvalidArgument`] if `width` or `height` is zero,
+/// or the image has too many pixels to address.
+///
+/// # Safety
+///
+/// `renderer` and `scene` must be null or valid handles, and `pixels` must be valid for writes
+/// of `pixels_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn catalina_renderer_render_to_pixels(
+    renderer: *mut CatalinaRenderer,
+    scene: *const CatalinaScene,
+    width: u32,
+    height: u32,
+    base_color: CatalinaColor,
+    pixels: *mut u8,
+    pixels_len: usize,
+) -> CatalinaStatus {
+    // Safety: The caller guarantees that non-null handles are valid.
+    let (Some(renderer), Some(scene)) = (unsafe { renderer.as_mut() }, unsafe { scene.as_ref() })
+    else {
+        return CatalinaStatus::NullPointer;
+    };
+    if pixels.is_null() {
+        return CatalinaStatus::NullPointer;
+    }
+    if width == 0 || height == 0 {
+        return CatalinaStatus::InvalidArgument;
+    }
+    let Some(required) = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|count| count.checked_mul(4))
+    else {
+        return CatalinaStatus::InvalidArgument;
+    };
+    if pixels_len < required {
+        return CatalinaStatus::BufferTooSmall;
+    }
+    let handle = &renderer.context.devices[renderer.device_id];
+    let params = RenderParams {
+        base_color: base_color.into(),
+        width,
+        height,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    let result =
+        renderer
+            .renderer
+            .render_to_image_tiled(&handle.device, &handle.queue, &scene.0, &params);
+    let image = match result {
+        Ok(image) => image,
+        Err(catalina::Error::EmptyTarget) => return CatalinaStatus::InvalidArgument,
+        Err(_) => return CatalinaStatus::RenderFailed,
+    };
+    // Safety: `pixels` was checked to be non-null, and the caller guarantees it is valid for
+    // writes of `pixels_len` bytes, which is at least `required`.
+    let out = unsafe { std::slice::from_raw_parts_mut(pixels, required) };
+    out.copy_from_slice(&image[..required]);
+    CatalinaStatus::Ok
+}
@@ -0,0 +1,322 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Python bindings for headless rendering with Catalina.
+//!
+//! ```python
+//! import catalina
+//!
+//! scene = catalina.Scene()
+//! scene.fill_path("M 10 10 L 90 10 L 50 90 Z", (1.0, 0.0, 0.0, 1.0))
+//! renderer = catalina.Renderer()
+//! renderer.save_png(scene, "triangle.png", 100, 100, background=(1.0, 1.0, 1.0, 1.0))
+//! ```
+
+use std::num::NonZeroUsize;
+
+use catalina::kurbo::{Affine, BezPath, Circle, Rect, Stroke};
+use catalina::peniko::{Color, Fill, Mix};
+use catalina::util::RenderContext;
+use catalina::{AaConfig, AaSupport, RenderParams, RendererOptions};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+type Rgba = (f32, f32, f32, f32);
+type Coeffs = (f64, f64, f64, f64, f64, f64);
+
+fn color((r, g, b, a): Rgba) -> Color {
+    Color::new([r, g, b, a])
+}
+
+fn transform(coeffs: Option<Coeffs>) -> Affine {
+    coeffs.map_or(Affine::IDENTITY, |(a, b, c, d, e, f)| {
+        Affine::new([a, b, c, d, e, f])
+    })
+}
+
+fn parse_path(svg: &str) -> PyResult<BezPath> {
+    BezPath::from_svg(svg).map_err(|e| PyValueError::new_err(format!("invalid SVG path: {e}")))
+}
+
+/// A scene of 2D vector graphics.
+///
+/// Paths are given as SVG path data strings, colors as `(r, g, b, a)` tuples with components
+/// in `0..=1`, and transforms as `(a, b, c, d, e, f)` affine coefficients.
+#[pyclass(unsendable)]
+#[derive(Default)]
+struct Scene {
+    scene: catalina::Scene,
+}
+
+#[pymethods]
+impl Scene {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes all content from the scene.
+    fn reset(&mut self) {
+        self.scene.reset();
+    }
+
+    /// Fills an SVG path with a solid color.
+    #[pyo3(signature = (path, color, transform=None, even_odd=false))]
+    fn fill_path(
+        &mut self,
+        path: &str,
+        color: Rgba,
+        transform: Option<Coeffs>,
+        even_odd: bool,
+    ) -> PyResult<()> {
+        let path = parse_path(path)?;
+        let fill = if even_odd {
+            Fill::EvenOdd
+        } else {
+            Fill::NonZero
+        };
+        self.scene.fill(
+            fill,
+            self::transform(transform),
+            self::color(color),
+            None,
+            &path,
+        );
+        Ok(())
+    }
+
+    /// Strokes an SVG path with a solid color.
+    #[pyo3(signature = (path, width, color, transform=None))]
+    fn stroke_path(
+        &mut self,
+        path: &str,
+        width: f64,
+        color: Rgba,
+        transform: Option<Coeffs>,
+    ) -> PyResult<()> {
+        let path = parse_path(path)?;
+        if !(width.is_finite() && width >= 0.0) {
+            return Err(PyValueError::new_err(
+                "width must be finite and non-negative",
+            ));
+        }
+        self.scene.stroke(
+            &Stroke::new(width),
+            self::transform(transform),
+            self::color(color),
+            None,
+            &path,
+        );
+        Ok(())
+    }
+
+    /// Fills a rectangle with a solid color.
+    #[pyo3(signature = (x, y, width, height, color, transform=None))]
+    fn fill_rect(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        color: Rgba,
+        transform: Option<Coeffs>,
+    ) {
+        self.scene.fill(
+            Fill::NonZero,
+            self::transform(transform),
+            self::color(color),
+            None,
+            &Rect::new(x, y, x + width, y + height),
+        );
+    }
+
+    /// Fills a circle with a solid color.
+    #[pyo3(signature = (cx, cy, radius, color, transform=None))]
+    fn fill_circle(
+        &mut self,
+        cx: f64,
+        cy: f64,
+        radius: f64,
+        color: Rgba,
+        transform: Option<Coeffs>,
+    ) {
+        self.scene.fill(
+            Fill::NonZero,
+            self::transform(transform),
+            self::color(color),
+            None,
+            &Circle::new((cx, cy), radius),
+        );
+    }
+
+    /// Pushes a layer clipped to an SVG path, which is composited with `alpha` when popped.
+    #[pyo3(signature = (clip, alpha=1.0, transform=None))]
+    fn push_layer(&mut self, clip: &str, alpha: f32, transform: Option<Coeffs>) -> PyResult<()> {
+        let clip = parse_path(clip)?;
+        self.scene
+            .push_layer(Mix::Normal, alpha, self::transform(transform), &clip);
+        Ok(())
+    }
+
+    /// Pops the most recently pushed layer.
+    fn pop_layer(&mut self) {
+        self.scene.pop_layer();
+    }
+
+    /// Appends another scene to this one, with an optional transform.
+    #[pyo3(signature = (other, transform=None))]
+    fn append(&mut self, other: PyRef<'_, Self>, transform: Option<Coeffs>) {
+        self.scene
+            .append(&other.scene, transform.map(|t| self::transform(Some(t))));
+    }
+
+    /// Serializes the scene, such as to save it to disk or render it in another process.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = self
+            .scene
+            .to_bytes()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Reads a scene serialized with `to_bytes`.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let scene = catalina::Scene::from_bytes(data)
+            .map_err(|e| PyValueError::new_err(format!("invalid scene: {e}")))?;
+        Ok(Self { scene })
+    }
+}
+
+/// A headless renderer, owning a GPU device.
+#[pyclass(unsendable)]
+struct Renderer {
+    context: RenderContext,
+    device_id: usize,
+    renderer: catalina::Renderer,
+}
+
+impl Renderer {
+    fn render(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+        background: Rgba,
+    ) -> PyResult<Vec<u8>> {
+        let handle = &self.context.devices[self.device_id];
+        let params = RenderParams {
+            base_color: color(background),
+            width,
+            height,
+            antialiasing_method: AaConfig::Area,
+            global_alpha: 1.0,
+            dash_phase: 0.0,
+            glow_amount: 1.0,
+            dirty_rects: None,
+        };
+        // Images larger than the device's limits are rendered in several tiles.
+        self.renderer
+            .render_to_image_tiled(&handle.device, &handle.queue, &scene.scene, &params)
+            .map_err(|e| match e {
+                catalina::Error::EmptyTarget => {
+                    PyValueError::new_err("width and height must be non-zero")
+                }
+                e => PyRuntimeError::new_err(format!("rendering failed: {e}")),
+            })
+    }
+
+    fn encode_png(pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let to_err = |e: png::EncodingError| PyRuntimeError::new_err(e.to_string());
+        let mut writer = encoder.write_header().map_err(to_err)?;
+        writer.write_image_data(pixels).map_err(to_err)?;
+        writer.finish().map_err(to_err)?;
+        Ok(data)
+    }
+}
+
+#[pymethods]
+impl Renderer {
+    #[new]
+    fn new() -> PyResult<Self> {
+        let mut context = RenderContext::new();
+        let device_id = pollster::block_on(context.device(None))
+            .ok_or_else(|| PyRuntimeError::new_err("no compatible GPU device found"))?;
+        let renderer = context.devices[device_id]
+            .create_renderer(RendererOptions {
+                surface_format: None,
+                use_cpu: false,
+                antialiasing_support: AaSupport::area_only(),
+                num_init_threads: NonZeroUsize::new(1),
+                gpu_stages_from: None,
+                fine_band_rows: None,
+                frames_in_flight: None,
+                disk_cache: None,
+                small_scenes: None,
+            })
+            .map_err(|e| PyRuntimeError::new_err(format!("failed to create renderer: {e}")))?;
+        Ok(Self {
+            context,
+            device_id,
+            renderer,
+        })
+    }
+
+    /// Renders a scene to unpadded RGBA8 pixels, with rows from top to bottom.
+    #[pyo3(signature = (scene, width, height, background=(0.0, 0.0, 0.0, 0.0)))]
+    fn render_rgba<'py>(
+        &mut self,
+        py: Python<'py>,
+        scene: PyRef<'_, Scene>,
+        width: u32,
+        height: u32,
+        background: Rgba,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let pixels = self.render(&scene, width, height, background)?;
+        Ok(PyBytes::new(py, &pixels))
+    }
+
+    /// Renders a scene to the contents of a PNG file.
+    #[pyo3(signature = (scene, width, height, background=(0.0, 0.0, 0.0, 0.0)))]
+    fn render_png<'py>(
+        &mut self,
+        py: Python<'py>,
+        scene: PyRef<'_, Scene>,
+        width: u32,
+        height: u32,
+        background: Rgba,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let pixels = self.render(&scene, width, height, background)?;
+        Ok(PyBytes::new(py, &Self::encode_png(&pixels, width, height)?))
+    }
+
+    /// Renders a scene and writes it to a PNG file at `path`.
+    #[pyo3(signature = (scene, path, width, height, background=(0.0, 0.0, 0.0, 0.0)))]
+    fn save_png(
+        &mut self,
+        scene: PyRef<'_, Scene>,
+        path: std::path::PathBuf,
+        width: u32,
+        height: u32,
+        background: Rgba,
+    ) -> PyResult<()> {
+        let pixels = self.render(&scene, width, height, background)?;
+        std::fs::write(path, Self::encode_png(&pixels, width, height)?)?;
+        Ok(())
+    }
+}
+
+/// Headless 2D vector rendering on the GPU.
+// The module is named after the `catalina` crate, so the function can't be.
+#[pymodule]
+#[pyo3(name = "catalina")]
+fn catalina_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Scene>()?;
+    m.add_class::<Renderer>()?;
+    Ok(())
+}
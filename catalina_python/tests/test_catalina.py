@@ -0,0 +1,59 @@
+# Copyright 2022-2025 the Catalina & Vello Authors
+# SPDX-License-Identifier: Apache-2.0 OR MIT
+
+"""Smoke tests of the Python bindings."""
+
+import pytest
+
+import catalina
+
+RED = (1.0, 0.0, 0.0, 1.0)
+
+
+@pytest.fixture(scope="module")
+def renderer():
+    try:
+        return catalina.Renderer()
+    except RuntimeError as error:
+        pytest.skip(f"no GPU device: {error}")
+
+
+def test_scene_round_trips():
+    scene = catalina.Scene()
+    scene.fill_path("M 0 0 L 8 0 L 8 8 Z", RED, even_odd=True)
+    scene.stroke_path("M 0 0 L 8 8", 1.0, RED)
+    data = scene.to_bytes()
+    assert catalina.Scene.from_bytes(data).to_bytes() == data
+
+
+def test_invalid_scene_data_is_rejected():
+    with pytest.raises(ValueError):
+        catalina.Scene.from_bytes(b"not a scene")
+
+
+@pytest.mark.parametrize("width", [-1.0, float("nan"), float("inf")])
+def test_invalid_stroke_width_is_rejected(width):
+    with pytest.raises(ValueError):
+        catalina.Scene().stroke_path("M 0 0 L 8 8", width, RED)
+
+
+def test_render_rgba(renderer):
+    scene = catalina.Scene()
+    scene.fill_rect(4, 4, 8, 8, RED)
+    pixels = renderer.render_rgba(scene, 16, 16)
+    assert len(pixels) == 16 * 16 * 4
+
+    def pixel(x, y):
+        offset = (y * 16 + x) * 4
+        return tuple(pixels[offset : offset + 4])
+
+    assert pixel(8, 8) == (255, 0, 0, 255)
+    assert pixel(1, 1) == (0, 0, 0, 0)
+
+
+def test_render_png(renderer):
+    scene = catalina.Scene()
+    scene.fill_circle(8, 8, 4, RED)
+    assert renderer.render_png(scene, 16, 16).startswith(b"\x89PNG")
+    with pytest.raises(ValueError):
+        renderer.render_png(scene, 0, 16)
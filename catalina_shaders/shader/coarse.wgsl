@@ -85,6 +85,15 @@ fn alloc_cmd(size: u32) {
     }
 }
 
+#ifdef cost
+fn write_draw(drawobj_ix: u32) {
+    alloc_cmd(2u);
+    ptcl[cmd_offset] = CMD_DRAW;
+    ptcl[cmd_offset + 1u] = drawobj_ix;
+    cmd_offset += 2u;
+}
+#endif
+
 fn write_path(tile: Tile, tile_ix: u32, draw_flags: u32) {
     // We overload the "segments" field to store both count (written by
     // path_count stage) and segment allocation (used by path_tiling and
@@ -103,9 +112,19 @@ fn write_path(tile: Tile, tile_ix: u32, draw_flags: u32) {
         ptcl[cmd_offset + 3u] = u32(fill.backdrop);
         cmd_offset += 4u;
     } else {
+#ifdef winding
+        // Fine rasterization needs the backdrop to output the winding number of the tile.
+        alloc_cmd(4u);
+        ptcl[cmd_offset] = CMD_FILL;
+        ptcl[cmd_offset + 1u] = 0u;
+        ptcl[cmd_offset + 2u] = 0u;
+        ptcl[cmd_offset + 3u] = u32(tile.backdrop);
+        cmd_offset += 4u;
+#else
         alloc_cmd(1u);
         ptcl[cmd_offset] = CMD_SOLID;
         cmd_offset += 1u;
+#endif
     }
 }
 
@@ -131,10 +150,11 @@ fn write_image(info_offset: u32) {
     cmd_offset += 2u;
 }
 
-fn write_begin_clip() {
-    alloc_cmd(1u);
+fn write_begin_clip(begin_clip: CmdBeginClip) {
+    alloc_cmd(2u);
     ptcl[cmd_offset] = CMD_BEGIN_CLIP;
-    cmd_offset += 1u;
+    ptcl[cmd_offset + 1u] = begin_clip.blend;
+    cmd_offset += 2u;
 }
 
 fn write_end_clip(end_clip: CmdEndClip) {
@@ -153,6 +173,21 @@ fn write_blurred_rounded_rect(color: CmdColor, info_offset: u32) {
     cmd_offset += 3u;
 }
 
+fn write_hatch(color: CmdColor, info_offset: u32) {
+    alloc_cmd(3u);
+    ptcl[cmd_offset] = CMD_HATCH;
+    ptcl[cmd_offset + 1u] = info_offset;
+    ptcl[cmd_offset + 2u] = color.rgba_color;
+    cmd_offset += 3u;
+}
+
+fn write_custom(info_offset: u32) {
+    alloc_cmd(2u);
+    ptcl[cmd_offset] = CMD_CUSTOM;
+    ptcl[cmd_offset + 1u] = info_offset;
+    cmd_offset += 2u;
+}
+
 @compute @workgroup_size(256)
 fn main(
     @builtin(local_invocation_id) local_id: vec3<u32>,
@@ -375,6 +410,9 @@ fn main(
             if clip_zero_depth == 0u {
                 let tile_ix = sh_tile_base[el_ix] + sh_tile_stride[el_ix] * tile_y + tile_x;
                 let tile = tiles[tile_ix];
+#ifdef cost
+                write_draw(drawobj_ix);
+#endif
                 switch drawtag {
                     case DRAWTAG_FILL_COLOR: {
                         write_path(tile, tile_ix, draw_flags);
@@ -387,6 +425,15 @@ fn main(
                         let info_offset = di + 1u;
                         write_blurred_rounded_rect(CmdColor(rgba_color), info_offset);
                     }
+                    case DRAWTAG_FILL_HATCH: {
+                        write_path(tile, tile_ix, draw_flags);
+                        let rgba_color = scene[dd];
+                        write_hatch(CmdColor(rgba_color), di + 1u);
+                    }
+                    case DRAWTAG_FILL_CUSTOM: {
+                        write_path(tile, tile_ix, draw_flags);
+                        write_custom(di + 1u);
+                    }
                     case DRAWTAG_FILL_LIN_GRADIENT: {
                         write_path(tile, tile_ix, draw_flags);
                         let index = scene[dd];
@@ -413,7 +460,7 @@ fn main(
                         if tile.segment_count_or_ix == 0u && tile.backdrop == 0 {
                             clip_zero_depth = clip_depth + 1u;
                         } else {
-                            write_begin_clip();
+                            write_begin_clip(CmdBeginClip(scene[dd]));
                             render_blend_depth += 1u;
                             max_blend_depth = max(max_blend_depth, render_blend_depth);
                         }
@@ -110,7 +110,8 @@ fn main(
         let di = m.info_offset;
         if tag_word == DRAWTAG_FILL_COLOR || tag_word == DRAWTAG_FILL_LIN_GRADIENT ||
             tag_word == DRAWTAG_FILL_RAD_GRADIENT || tag_word == DRAWTAG_FILL_SWEEP_GRADIENT ||
-            tag_word == DRAWTAG_FILL_IMAGE || tag_word == DRAWTAG_BEGIN_CLIP || tag_word == DRAWTAG_BLURRED_ROUNDED_RECT
+            tag_word == DRAWTAG_FILL_IMAGE || tag_word == DRAWTAG_BEGIN_CLIP || tag_word == DRAWTAG_BLURRED_ROUNDED_RECT ||
+            tag_word == DRAWTAG_FILL_HATCH || tag_word == DRAWTAG_FILL_CUSTOM
         {
             let bbox = path_bbox[m.path_ix];
             // TODO: bbox is mostly yagni here, sort that out. Maybe clips?
@@ -123,7 +124,8 @@ fn main(
             let draw_flags = bbox.draw_flags;
             if tag_word == DRAWTAG_FILL_LIN_GRADIENT || tag_word == DRAWTAG_FILL_RAD_GRADIENT ||
                 tag_word == DRAWTAG_FILL_SWEEP_GRADIENT || tag_word == DRAWTAG_FILL_IMAGE || 
-                tag_word == DRAWTAG_BLURRED_ROUNDED_RECT
+                tag_word == DRAWTAG_BLURRED_ROUNDED_RECT || tag_word == DRAWTAG_FILL_HATCH ||
+                tag_word == DRAWTAG_FILL_CUSTOM
             {
                 transform = read_transform(config.transform_base, bbox.trans_ix);
             }
@@ -269,6 +271,41 @@ fn main(
                     info[di + 9u] = scene[dd + 3u];
                     info[di + 10u] = scene[dd + 4u];
                 }
+                case DRAWTAG_FILL_HATCH: {
+                    info[di] = draw_flags;
+                    let angle = bitcast<f32>(scene[dd + 1u]);
+                    let spacing = bitcast<f32>(scene[dd + 2u]);
+                    let line_width = bitcast<f32>(scene[dd + 3u]);
+                    let cross = scene[dd + 4u];
+                    // Each set of lines is where a linear function of the brush coordinates,
+                    // in units of the spacing, is an integer. Express it in device coordinates.
+                    let inv = transform_inverse(transform);
+                    let normal = vec2(-sin(angle), cos(angle)) / spacing;
+                    var normals = array(normal, normal);
+                    if cross != 0u {
+                        normals[1] = vec2(normal.y, -normal.x);
+                    }
+                    for (var j = 0u; j < 2u; j += 1u) {
+                        let n = normals[j];
+                        info[di + 1u + j * 3u] = bitcast<u32>(dot(inv.matrx.xy, n));
+                        info[di + 2u + j * 3u] = bitcast<u32>(dot(inv.matrx.zw, n));
+                        info[di + 3u + j * 3u] = bitcast<u32>(dot(inv.translate, n));
+                    }
+                    info[di + 7u] = bitcast<u32>(0.5 * line_width / spacing);
+                }
+                case DRAWTAG_FILL_CUSTOM: {
+                    info[di] = draw_flags;
+                    let inv = transform_inverse(transform);
+                    info[di + 1u] = bitcast<u32>(inv.matrx.x);
+                    info[di + 2u] = bitcast<u32>(inv.matrx.y);
+                    info[di + 3u] = bitcast<u32>(inv.matrx.z);
+                    info[di + 4u] = bitcast<u32>(inv.matrx.w);
+                    info[di + 5u] = bitcast<u32>(inv.translate.x);
+                    info[di + 6u] = bitcast<u32>(inv.translate.y);
+                    for (var j = 0u; j < 5u; j += 1u) {
+                        info[di + 7u + j] = scene[dd + j];
+                    }
+                }
                 default: {}
             }
         }
@@ -38,8 +38,13 @@ var<storage> info: array<u32>;
 @group(0) @binding(4)
 var<storage, read_write> blend_spill: array<u32>;
 
+#ifdef winding
+@group(0) @binding(5)
+var output: texture_storage_2d<r32float, write>;
+#else
 @group(0) @binding(5)
 var output: texture_storage_2d<rgba8unorm, write>;
+#endif
 
 @group(0) @binding(6)
 var gradients: texture_2d<f32>;
@@ -47,6 +52,15 @@ var gradients: texture_2d<f32>;
 @group(0) @binding(7)
 var image_atlas: texture_2d<f32>;
 
+#ifdef cost
+// The number of counters for each draw object: the commands, painted pixels and segments
+// belonging to it.
+const COST_STRIDE = 3u;
+
+@group(0) @binding(8)
+var<storage, read_write> costs: array<atomic<u32>>;
+#endif
+
 // MSAA-only bindings and utilities
 #ifdef msaa
 
@@ -81,7 +95,7 @@ var<workgroup> sh_count: array<u32, WG_SIZE>;
 // The values are stored packed, as 4 8-bit subwords in a 32 bit word.
 // The values are biased signed integers, with 0x80 representing a winding
 // number of 0, so that the range of -128 to 127 (inclusive) can be stored
-// without carry.
+// without carry. Larger winding numbers are wrapped modulo 256.
 //
 // For the even-odd case, the same storage is repurposed, so that a single
 // word contains 16 one-bit winding parity values packed to the word.
@@ -107,6 +121,11 @@ var<workgroup> sh_winding: array<atomic<u32>, 64u>;
 // pixel. The encoding and packing is the same as `sh_winding_y`.
 var<workgroup> sh_samples: array<atomic<u32>, SH_SAMPLES_SIZE>;
 
+// Adds the packed 8-bit subwords of `a` and `b` modulo 256, without carrying between them.
+fn add_subwords(a: u32, b: u32) -> u32 {
+    return ((a & 0x7f7f7f7fu) + (b & 0x7f7f7f7fu)) ^ ((a ^ b) & 0x80808080u);
+}
+
 // number of integer cells spanned by interval defined by a, b
 fn span(a: f32, b: f32) -> u32 {
     return u32(max(ceil(max(a, b)) - floor(min(a, b)), 1.0));
@@ -165,6 +184,15 @@ fn fill_path_ms(fill: CmdFill, local_id: vec2<u32>, result: ptr<function, array<
     }
     workgroupBarrier();
     let n_batch = (n_segs + (WG_SIZE - 1u)) / WG_SIZE;
+    // Each segment changes a winding number by at most 1, so within a batch the winding
+    // numbers can't carry out of their subwords. Across batches, such as for many coincident
+    // edges, they can, so the batches are accumulated here modulo 256 instead.
+    var acc_winding = 0x80808080u;
+    var acc_winding_y = 0x80808080u;
+    var acc_samples: array<u32, PIXELS_PER_THREAD * SAMPLE_WORDS_PER_PIXEL>;
+    for (var i = 0u; i < sample_count; i++) {
+        acc_samples[i] = 0x80808080u;
+    }
     for (var batch = 0u; batch < n_batch; batch++) {
         let seg_ix = batch * WG_SIZE + th_ix;
         let seg_off = fill.seg_data + seg_ix;
@@ -379,6 +407,35 @@ fn fill_path_ms(fill: CmdFill, local_id: vec2<u32>, result: ptr<function, array<
 #endif
         }
         workgroupBarrier();
+        if n_batch > 1u {
+            // Move the winding numbers of this batch into the accumulators, and start the
+            // next batch from zero.
+            if th_ix < 64u {
+                if th_ix < 4u {
+                    let batch_y = atomicExchange(&sh_winding_y[th_ix], 0x80808080u);
+                    acc_winding_y = add_subwords(acc_winding_y, batch_y ^ 0x80808080u);
+                }
+                let batch_x = atomicExchange(&sh_winding[th_ix], 0x80808080u);
+                acc_winding = add_subwords(acc_winding, batch_x ^ 0x80808080u);
+            }
+            for (var i = 0u; i < sample_count; i++) {
+                let batch_samples = atomicExchange(&sh_samples[th_ix * sample_count + i], 0x80808080u);
+                acc_samples[i] = add_subwords(acc_samples[i], batch_samples ^ 0x80808080u);
+            }
+            workgroupBarrier();
+        }
+    }
+    if n_batch > 1u {
+        if th_ix < 64u {
+            if th_ix < 4u {
+                atomicStore(&sh_winding_y[th_ix], acc_winding_y);
+            }
+            atomicStore(&sh_winding[th_ix], acc_winding);
+        }
+        for (var i = 0u; i < sample_count; i++) {
+            atomicStore(&sh_samples[th_ix * sample_count + i], acc_samples[i]);
+        }
+        workgroupBarrier();
     }
     var area: array<f32, PIXELS_PER_THREAD>;
     let major = (th_ix * PIXELS_PER_THREAD) >> 2u;
@@ -391,23 +448,24 @@ fn fill_path_ms(fill: CmdFill, local_id: vec2<u32>, result: ptr<function, array<
     // the prefix by adding the preceding reduced values.
     //
     // Addition of 2 biased signed values is accomplished by adding the
-    // values, then subtracting the bias.
-    packed_w += (packed_w - 0x808080u) << 8u;
-    packed_w += (packed_w - 0x8080u) << 16u;
+    // values, then subtracting the bias. The subwords are added modulo 256,
+    // so that high winding numbers don't carry into their neighbors.
+    packed_w = add_subwords(packed_w, (packed_w ^ 0x80808080u) << 8u);
+    packed_w = add_subwords(packed_w, (packed_w ^ 0x80808080u) << 16u);
     var packed_y = atomicLoad(&sh_winding_y[local_id.y >> 2u]);
-    packed_y += (packed_y - 0x808080u) << 8u;
-    packed_y += (packed_y - 0x8080u) << 16u;
+    packed_y = add_subwords(packed_y, (packed_y ^ 0x80808080u) << 8u);
+    packed_y = add_subwords(packed_y, (packed_y ^ 0x80808080u) << 16u);
     var wind_y = (packed_y >> ((local_id.y & 3u) << 3u)) - 0x80u;
     if (local_id.y & 3u) == 3u && local_id.x == 0u {
         let prefix_y = wind_y;
         atomicStore(&sh_winding_y_prefix[local_id.y >> 2u], prefix_y);
     }
-    let prefix_x = ((packed_w >> 24u) - 0x80u) * 0x1010101u;
+    let prefix_x = ((packed_w >> 24u) ^ 0x80u) * 0x1010101u;
     // reuse sh_winding to store prefix as well
     atomicStore(&sh_winding[major], prefix_x);
     workgroupBarrier();
     for (var i = (major & ~3u); i < major; i++) {
-        packed_w += atomicLoad(&sh_winding[i]);
+        packed_w = add_subwords(packed_w, atomicLoad(&sh_winding[i]));
     }
     // packed_w now contains the winding numbers for a slice of 4 pixels,
     // each relative to the top left of the row.
@@ -443,56 +501,51 @@ fn fill_path_ms(fill: CmdFill, local_id: vec2<u32>, result: ptr<function, array<
     for (var i = 0u; i < PIXELS_PER_THREAD; i++) {
         let pix_ix = th_ix * PIXELS_PER_THREAD + i;
         let minor = i; // assumes PIXELS_PER_THREAD == 4
-        let expected_zero = (((packed_w >> (minor * 8u)) + wind_y) & 0xffu) - u32(fill.backdrop);
-        // When the expected_zero value exceeds the range of what can be stored
-        // in a (biased) signed integer, then there is no sample value that can
-        // be equal to the expected value, thus all resulting bits are 1.
-        if expected_zero >= 256u {
-            area[i] = 1.0;
-        } else {
+        // The winding numbers are only known modulo 256, so a sample is
+        // counted as covered unless its winding number is a multiple of 256.
+        let expected_zero = ((packed_w >> (minor * 8u)) + wind_y - u32(fill.backdrop)) & 0xffu;
 #ifdef msaa8
-            let samples0 = atomicLoad(&sh_samples[pix_ix * 2u]);
-            let samples1 = atomicLoad(&sh_samples[pix_ix * 2u + 1u]);
-            let xored0 = (expected_zero * 0x1010101u) ^ samples0;
-            let xored0_2 = xored0 | (xored0 * 2u);
-            let xored1 = (expected_zero * 0x1010101u) ^ samples1;
-            let xored1_2 = xored1 | (xored1 >> 1u);
-            // xored2 contains 2-reductions from each word, interleaved
-            let xored2 = (xored0_2 & 0xAAAAAAAAu) | (xored1_2 & 0x55555555u);
-            // bits 4 * k + 2 and 4 * k + 3 contain 4-reductions
-            let xored4 = xored2 | (xored2 * 4u);
-            // bits 8 * k + 6 and 8 * k + 7 contain 8-reductions
-            let xored8 = xored4 | (xored4 * 16u);
-            area[i] = f32(countOneBits(xored8 & 0xC0C0C0C0u)) * 0.125;
+        let samples0 = atomicLoad(&sh_samples[pix_ix * 2u]);
+        let samples1 = atomicLoad(&sh_samples[pix_ix * 2u + 1u]);
+        let xored0 = (expected_zero * 0x1010101u) ^ samples0;
+        let xored0_2 = xored0 | (xored0 * 2u);
+        let xored1 = (expected_zero * 0x1010101u) ^ samples1;
+        let xored1_2 = xored1 | (xored1 >> 1u);
+        // xored2 contains 2-reductions from each word, interleaved
+        let xored2 = (xored0_2 & 0xAAAAAAAAu) | (xored1_2 & 0x55555555u);
+        // bits 4 * k + 2 and 4 * k + 3 contain 4-reductions
+        let xored4 = xored2 | (xored2 * 4u);
+        // bits 8 * k + 6 and 8 * k + 7 contain 8-reductions
+        let xored8 = xored4 | (xored4 * 16u);
+        area[i] = f32(countOneBits(xored8 & 0xC0C0C0C0u)) * 0.125;
 #endif
 #ifdef msaa16
-            let samples0 = atomicLoad(&sh_samples[pix_ix * 4u]);
-            let samples1 = atomicLoad(&sh_samples[pix_ix * 4u + 1u]);
-            let samples2 = atomicLoad(&sh_samples[pix_ix * 4u + 2u]);
-            let samples3 = atomicLoad(&sh_samples[pix_ix * 4u + 3u]);
-            let xored0 = (expected_zero * 0x1010101u) ^ samples0;
-            let xored0_2 = xored0 | (xored0 * 2u);
-            let xored1 = (expected_zero * 0x1010101u) ^ samples1;
-            let xored1_2 = xored1 | (xored1 >> 1u);
-            // xored01 contains 2-reductions from words 0 and 1, interleaved
-            let xored01 = (xored0_2 & 0xAAAAAAAAu) | (xored1_2 & 0x55555555u);
-            // bits 4 * k + 2 and 4 * k + 3 contain 4-reductions
-            let xored01_4 = xored01 | (xored01 * 4u);
-            let xored2 = (expected_zero * 0x1010101u) ^ samples2;
-            let xored2_2 = xored2 | (xored2 * 2u);
-            let xored3 = (expected_zero * 0x1010101u) ^ samples3;
-            let xored3_2 = xored3 | (xored3 >> 1u);
-            // xored23 contains 2-reductions from words 2 and 3, interleaved
-            let xored23 = (xored2_2 & 0xAAAAAAAAu) | (xored3_2 & 0x55555555u);
-            // bits 4 * k and 4 * k + 1 contain 4-reductions
-            let xored23_4 = xored23 | (xored23 >> 2u);
-            // each bit is a 4-reduction, with values from all 4 words
-            let xored4 = (xored01_4 & 0xCCCCCCCCu) | (xored23_4 & 0x33333333u);
-            // bits 8 * k + {4, 5, 6, 7} contain 8-reductions
-            let xored8 = xored4 | (xored4 * 16u);
-            area[i] = f32(countOneBits(xored8 & 0xF0F0F0F0u)) * 0.0625;
+        let samples0 = atomicLoad(&sh_samples[pix_ix * 4u]);
+        let samples1 = atomicLoad(&sh_samples[pix_ix * 4u + 1u]);
+        let samples2 = atomicLoad(&sh_samples[pix_ix * 4u + 2u]);
+        let samples3 = atomicLoad(&sh_samples[pix_ix * 4u + 3u]);
+        let xored0 = (expected_zero * 0x1010101u) ^ samples0;
+        let xored0_2 = xored0 | (xored0 * 2u);
+        let xored1 = (expected_zero * 0x1010101u) ^ samples1;
+        let xored1_2 = xored1 | (xored1 >> 1u);
+        // xored01 contains 2-reductions from words 0 and 1, interleaved
+        let xored01 = (xored0_2 & 0xAAAAAAAAu) | (xored1_2 & 0x55555555u);
+        // bits 4 * k + 2 and 4 * k + 3 contain 4-reductions
+        let xored01_4 = xored01 | (xored01 * 4u);
+        let xored2 = (expected_zero * 0x1010101u) ^ samples2;
+        let xored2_2 = xored2 | (xored2 * 2u);
+        let xored3 = (expected_zero * 0x1010101u) ^ samples3;
+        let xored3_2 = xored3 | (xored3 >> 1u);
+        // xored23 contains 2-reductions from words 2 and 3, interleaved
+        let xored23 = (xored2_2 & 0xAAAAAAAAu) | (xored3_2 & 0x55555555u);
+        // bits 4 * k and 4 * k + 1 contain 4-reductions
+        let xored23_4 = xored23 | (xored23 >> 2u);
+        // each bit is a 4-reduction, with values from all 4 words
+        let xored4 = (xored01_4 & 0xCCCCCCCCu) | (xored23_4 & 0x33333333u);
+        // bits 8 * k + {4, 5, 6, 7} contain 8-reductions
+        let xored8 = xored4 | (xored4 * 16u);
+        area[i] = f32(countOneBits(xored8 & 0xF0F0F0F0u)) * 0.0625;
 #endif
-        }
     }
     *result = area;
 }
@@ -616,7 +669,7 @@ fn fill_path_ms_evenodd(fill: CmdFill, local_id: vec2<u32>, result: ptr<function
             let zp = floor(a * f32(sub_ix - 1u) + b);
             if sub_ix == 0u {
                 is_delta = y0i == xy0.y;
-                is_bump = xy0.x == 0.0;
+                is_bump = xy0.x == 0.0 && y0i != xy0.y;
             } else {
                 is_delta = z == zp;
                 is_bump = is_positive_slope && !is_delta;
@@ -753,6 +806,56 @@ fn read_blur_rect(cmd_ix: u32) -> CmdBlurRect {
     return CmdBlurRect(rgba_color, matrx, xlat, width, height, radius, std_dev);
 }
 
+fn read_hatch(cmd_ix: u32) -> CmdHatch {
+    let info_offset = ptcl[cmd_ix + 1u];
+    let rgba_color = ptcl[cmd_ix + 2u];
+    var lines: array<vec3<f32>, 2>;
+    for (var j = 0u; j < 2u; j += 1u) {
+        let offset = info_offset + j * 3u;
+        lines[j] = bitcast<vec3<f32>>(vec3(info[offset], info[offset + 1u], info[offset + 2u]));
+    }
+    let half_width = bitcast<f32>(info[info_offset + 6u]);
+    return CmdHatch(rgba_color, lines[0], lines[1], half_width);
+}
+
+// Returns the coverage of a pixel by the nearest of a set of hatch lines, where `d` is the
+// value of their function at the center of the pixel and `grad` its change per pixel.
+fn hatch_coverage(d: f32, grad: f32, half_width: f32) -> f32 {
+    // Distance to the nearest line and its half width, in pixels.
+    let inv_grad = 1.0 / max(grad, 1e-6);
+    let dist = abs(fract(d + 0.5) - 0.5) * inv_grad;
+    let half_width_px = half_width * inv_grad;
+    // Lines narrower than a pixel keep their average coverage.
+    return clamp(min(half_width_px + 0.5 - dist, 2.0 * half_width_px), 0.0, 1.0);
+}
+
+fn read_custom(cmd_ix: u32) -> CmdCustom {
+    let info_offset = ptcl[cmd_ix + 1u];
+    let m0 = bitcast<f32>(info[info_offset]);
+    let m1 = bitcast<f32>(info[info_offset + 1u]);
+    let m2 = bitcast<f32>(info[info_offset + 2u]);
+    let m3 = bitcast<f32>(info[info_offset + 3u]);
+    let matrx = vec4(m0, m1, m2, m3);
+    let xlat = vec2(bitcast<f32>(info[info_offset + 4u]), bitcast<f32>(info[info_offset + 5u]));
+    let id = info[info_offset + 6u];
+    let data = vec4(
+        info[info_offset + 7u],
+        info[info_offset + 8u],
+        info[info_offset + 9u],
+        info[info_offset + 10u]
+    );
+    return CmdCustom(matrx, xlat, id, data);
+}
+
+// Paint functions registered with the renderer are linked in by replacing the lines between
+// these markers with a function of the same signature, which calls the paint function `id`.
+// CUSTOM_PAINTS_BEGIN
+// Returns the premultiplied color of custom paint `id` at the point `xy` in brush coordinates.
+fn custom_paint(id: u32, xy: vec2<f32>, data: vec4<u32>) -> vec4<f32> {
+    return vec4(0.0);
+}
+// CUSTOM_PAINTS_END
+
 fn read_lin_grad(cmd_ix: u32) -> CmdLinGrad {
     let index_mode = ptcl[cmd_ix + 1u];
     let index = index_mode >> 2u;
@@ -811,15 +914,23 @@ fn read_image(cmd_ix: u32) -> CmdImage {
     let width_height = info[info_offset + 7u];
     let sample_alpha = info[info_offset + 8u];
     let alpha = f32(sample_alpha & 0xFFu) / 255.0;
-    let quality = sample_alpha >> 12u;
+    let quality = (sample_alpha >> 12u) & 0x3u;
     let x_extend = (sample_alpha >> 10u) & 0x3u;
     let y_extend = (sample_alpha >> 8u) & 0x3u;
+    // Images which were too large for the atlas are scaled down by a power of two, so the
+    // coordinates in the image are scaled by the same amount.
+    let scale = 1.0 / f32(1u << ((sample_alpha >> 14u) & 0xFu));
     // The following are not intended to be bitcasts
     let x = f32(xy >> 16u);
     let y = f32(xy & 0xffffu);
     let width = f32(width_height >> 16u);
     let height = f32(width_height & 0xffffu);
-    return CmdImage(matrx, xlat, vec2(x, y), vec2(width, height), x_extend, y_extend, quality, alpha);
+    return CmdImage(matrx * scale, xlat * scale, vec2(x, y), vec2(width, height), x_extend, y_extend, quality, alpha);
+}
+
+fn read_begin_clip(cmd_ix: u32) -> CmdBeginClip {
+    let blend = ptcl[cmd_ix + 1u];
+    return CmdBeginClip(blend);
 }
 
 fn read_end_clip(cmd_ix: u32) -> CmdEndClip {
@@ -845,6 +956,24 @@ fn extend_mode(t: f32, mode: u32) -> f32 {
     }
 }
 
+// Returns the index of the texel of an image `size` texels across which is sampled for the
+// texel at `ix`, which may be outside of the image.
+fn extend_texel(ix: i32, size: i32, mode: u32) -> i32 {
+    switch mode {
+        case EXTEND_PAD: {
+            return clamp(ix, 0, size - 1);
+        }
+        case EXTEND_REPEAT: {
+            return ((ix % size) + size) % size;
+        }
+        case EXTEND_REFLECT, default: {
+            let period = 2 * size;
+            let t = ((ix % period) + period) % period;
+            return select(t, period - 1 - t, t >= size);
+        }
+    }
+}
+
 const PIXELS_PER_THREAD = 4u;
 
 #ifndef msaa
@@ -896,6 +1025,7 @@ fn fill_path(fill: CmdFill, xy: vec2<f32>, result: ptr<function, array<f32, PIXE
             area[i] += y_edge;
         }
     }
+#ifndef winding
     if even_odd {
         // even-odd winding rule
         for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
@@ -908,6 +1038,7 @@ fn fill_path(fill: CmdFill, xy: vec2<f32>, result: ptr<function, array<f32, PIXE
             area[i] = min(abs(area[i]), 1.0);
         }
     }
+#endif
     *result = area;
 }
 
@@ -925,26 +1056,96 @@ fn main(
         // We use ptcl[0] for this so we don't use up a binding for bump.
         return;
     }
-    let tile_ix = wg_id.y * config.width_in_tiles + wg_id.x;
-    let xy = vec2(f32(global_id.x * PIXELS_PER_THREAD), f32(global_id.y));
+    // When the fine stage is split into bands, the dispatch only covers a subset of the tile rows.
+    let tile_y = wg_id.y + config.fine_row_offset;
+    let tile_ix = tile_y * config.width_in_tiles + wg_id.x;
+    let xy = vec2(f32(global_id.x * PIXELS_PER_THREAD), f32(global_id.y + config.fine_row_offset * TILE_HEIGHT));
     let local_xy = vec2(f32(local_id.x * PIXELS_PER_THREAD), f32(local_id.y));
     var rgba: array<vec4<f32>, PIXELS_PER_THREAD>;
+#ifdef coverage
+    // Coverage starts out empty, whatever the base color.
+    let base_color = vec4(0.0);
+#else
     let base_color = unpack4x8unorm(config.base_color);
+#endif
     for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
         rgba[i] = base_color;
     }
     var blend_stack: array<array<u32, PIXELS_PER_THREAD>, BLEND_STACK_SPLIT>;
     var clip_depth = 0u;
+    // The layers at each of the first 32 depths which are knockout or non-isolated layers.
+    var knockout_mask = 0u;
+    var non_isolated_mask = 0u;
+    // Whether the innermost layer is a knockout layer, and the pixels it started with.
+    var knockout = false;
+    var knockout_bg: array<vec4<f32>, PIXELS_PER_THREAD>;
     var area: array<f32, PIXELS_PER_THREAD>;
     var cmd_ix = tile_ix * PTCL_INITIAL_ALLOC;
     let blend_offset = ptcl[cmd_ix];
     cmd_ix += 1u;
+#ifdef cost
+    var draw_ix = ~0u;
+#endif
     // main interpretation loop
     while true {
         let tag = ptcl[cmd_ix];
         if tag == CMD_END {
             break;
         }
+#ifdef cost
+        if tag == CMD_DRAW {
+            draw_ix = ptcl[cmd_ix + 1u];
+            cmd_ix += 2u;
+            continue;
+        }
+        if tag != CMD_JUMP && draw_ix < arrayLength(&costs) / COST_STRIDE {
+            let cost_ix = draw_ix * COST_STRIDE;
+            // Commands and segments are counted once for the tile.
+            if local_id.x == 0u && local_id.y == 0u {
+                atomicAdd(&costs[cost_ix], 1u);
+                if tag == CMD_FILL {
+                    atomicAdd(&costs[cost_ix + 2u], ptcl[cmd_ix + 1u] >> 1u);
+                }
+            }
+            // The area was computed by the preceding fill.
+            if paint_size(tag) != 0u || tag == CMD_END_CLIP {
+                var pixels = 0u;
+                for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
+                    let x = u32(xy.x) + i;
+                    if area[i] != 0.0 && x < config.target_width && u32(xy.y) < config.target_height {
+                        pixels += 1u;
+                    }
+                }
+                if pixels != 0u {
+                    atomicAdd(&costs[cost_ix + 1u], pixels);
+                }
+            }
+        }
+#endif
+#ifdef coverage
+        // Only coverage is rendered, so every brush is treated as opaque white, without
+        // evaluating it.
+        let size = paint_size(tag);
+        if size != 0u {
+            for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
+                rgba[i] = rgba[i] * (1.0 - area[i]) + vec4(area[i]);
+            }
+            cmd_ix += size;
+            continue;
+        }
+#endif
+#ifdef winding
+        // The winding numbers of the only path replace the base color, without evaluating
+        // its brush.
+        let size = paint_size(tag);
+        if size != 0u {
+            for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
+                rgba[i] = vec4(area[i]);
+            }
+            cmd_ix += size;
+            continue;
+        }
+#endif
         switch tag {
             case CMD_FILL: {
                 let fill = read_fill(cmd_ix);
@@ -966,15 +1167,20 @@ fn main(
                 let fg = unpack4x8unorm(color.rgba_color);
                 for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
                     let fg_i = fg * area[i];
-                    rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                    rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                 }
                 cmd_ix += 2u;
             }
             case CMD_BEGIN_CLIP: {
+                let begin_clip = read_begin_clip(cmd_ix);
+                // Non-isolated layers start with their backdrop, so that the content is
+                // blended with everything beneath it.
+                let isolated = (begin_clip.blend & BLEND_NON_ISOLATED) == 0u;
                 if clip_depth < BLEND_STACK_SPLIT {
                     for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
                         blend_stack[clip_depth][i] = pack4x8unorm(rgba[i]);
-                        rgba[i] = vec4(0.0);
+                        rgba[i] = select(rgba[i], vec4(0.0), isolated);
+                        knockout_bg[i] = rgba[i];
                     }
                 } else {
                     let blend_in_scratch = clip_depth - BLEND_STACK_SPLIT;
@@ -982,11 +1188,20 @@ fn main(
                     let local_blend_start = blend_offset + blend_in_scratch * TILE_WIDTH * TILE_HEIGHT + local_tile_ix;
                     for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
                         blend_spill[local_blend_start + i] = pack4x8unorm(rgba[i]);
-                        rgba[i] = vec4(0.0);
+                        rgba[i] = select(rgba[i], vec4(0.0), isolated);
+                        knockout_bg[i] = rgba[i];
                     }
                 }
+                // Knockout layers nested more deeply than the masks can track are drawn as
+                // normal layers.
+                knockout = (begin_clip.blend & BLEND_KNOCKOUT) != 0u && clip_depth < 32u;
+                if clip_depth < 32u {
+                    let bit = 1u << clip_depth;
+                    knockout_mask = select(knockout_mask & ~bit, knockout_mask | bit, knockout);
+                    non_isolated_mask = select(non_isolated_mask | bit, non_isolated_mask & ~bit, isolated);
+                }
                 clip_depth += 1u;
-                cmd_ix += 1u;
+                cmd_ix += 2u;
             }
             case CMD_END_CLIP: {
                 let end_clip = read_end_clip(cmd_ix);
@@ -1002,8 +1217,36 @@ fn main(
                         bg_rgba = blend_spill[local_blend_start + i];
                     }
                     let bg = unpack4x8unorm(bg_rgba);
-                    let fg = rgba[i] * area[i] * end_clip.alpha;
-                    rgba[i] = blend_mix_compose(bg, fg, end_clip.blend);
+                    if (end_clip.blend & BLEND_NON_ISOLATED) != 0u {
+                        // The content was drawn onto the backdrop, so only the clip and
+                        // alpha of the layer remain to be applied.
+                        rgba[i] = mix(bg, rgba[i], area[i] * end_clip.alpha);
+                    } else {
+                        let fg = rgba[i] * area[i] * end_clip.alpha;
+                        rgba[i] = blend_mix_compose(bg, fg, end_clip.blend & BLEND_MODE_MASK);
+                    }
+                }
+                // Restore the knockout state of the enclosing layer, which started with its
+                // backdrop if it isn't isolated.
+                knockout = clip_depth > 0u && clip_depth <= 32u && ((knockout_mask >> (clip_depth - 1u)) & 1u) != 0u;
+                if knockout {
+                    let parent_depth = clip_depth - 1u;
+                    let parent_isolated = ((non_isolated_mask >> parent_depth) & 1u) == 0u;
+                    for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
+                        // Isolated layers start out transparent.
+                        var parent_bg_rgba = 0u;
+                        if !parent_isolated {
+                            if parent_depth < BLEND_STACK_SPLIT {
+                                parent_bg_rgba = blend_stack[parent_depth][i];
+                            } else {
+                                let blend_in_scratch = parent_depth - BLEND_STACK_SPLIT;
+                                let local_tile_ix = local_id.x * PIXELS_PER_THREAD + local_id.y * TILE_WIDTH;
+                                let local_blend_start = blend_offset + blend_in_scratch * TILE_WIDTH * TILE_HEIGHT + local_tile_ix;
+                                parent_bg_rgba = blend_spill[local_blend_start + i];
+                            }
+                        }
+                        knockout_bg[i] = unpack4x8unorm(parent_bg_rgba);
+                    }
                 }
                 cmd_ix += 3u;
             }
@@ -1058,10 +1301,37 @@ fn main(
 
                     let fg_rgba = blur_rgba * alpha;
                     let fg_i = fg_rgba * area[i];
-                    rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                    rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                 }
                 cmd_ix += 3u;
             }
+            case CMD_HATCH: {
+                let hatch = read_hatch(cmd_ix);
+                let hatch_rgba = unpack4x8unorm(hatch.rgba_color);
+                let grad_0 = length(hatch.line_0.xy);
+                let grad_1 = length(hatch.line_1.xy);
+                for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
+                    let my_xy = vec3(xy.x + f32(i) + 0.5, xy.y + 0.5, 1.0);
+                    let coverage = max(
+                        hatch_coverage(dot(hatch.line_0, my_xy), grad_0, hatch.half_width),
+                        hatch_coverage(dot(hatch.line_1, my_xy), grad_1, hatch.half_width)
+                    );
+                    let fg_i = hatch_rgba * (coverage * area[i]);
+                    rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
+                }
+                cmd_ix += 3u;
+            }
+            case CMD_CUSTOM: {
+                let custom = read_custom(cmd_ix);
+                for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
+                    let my_xy = vec2(xy.x + f32(i) + 0.5, xy.y + 0.5);
+                    let brush_xy = custom.matrx.xy * my_xy.x + custom.matrx.zw * my_xy.y + custom.xlat;
+                    let fg_rgba = custom_paint(custom.id, brush_xy, custom.data);
+                    let fg_i = fg_rgba * area[i];
+                    rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
+                }
+                cmd_ix += 2u;
+            }
             case CMD_LIN_GRAD: {
                 let lin = read_lin_grad(cmd_ix);
                 let d = lin.line_x * xy.x + lin.line_y * xy.y + lin.line_c;
@@ -1070,7 +1340,7 @@ fn main(
                     let x = i32(round(extend_mode(my_d, lin.extend_mode) * f32(GRADIENT_WIDTH - 1)));
                     let fg_rgba = textureLoad(gradients, vec2(x, i32(lin.index)), 0);
                     let fg_i = fg_rgba * area[i];
-                    rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                    rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                 }
                 cmd_ix += 3u;
             }
@@ -1114,7 +1384,7 @@ fn main(
                         let x = i32(round(t * f32(GRADIENT_WIDTH - 1)));
                         let fg_rgba = textureLoad(gradients, vec2(x, i32(rad.index)), 0);
                         let fg_i = fg_rgba * area[i];
-                        rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                        rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                     }
                 }
                 cmd_ix += 3u;
@@ -1148,57 +1418,63 @@ fn main(
                     let ramp_x = i32(round(t * f32(GRADIENT_WIDTH - 1)));
                     let fg_rgba = textureLoad(gradients, vec2(ramp_x, i32(sweep.index)), 0);
                     let fg_i = fg_rgba * area[i];
-                    rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                    rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                 }
                 cmd_ix += 3u;
             }
             case CMD_IMAGE: {
                 let image = read_image(cmd_ix);
-                let atlas_max = image.atlas_offset + image.extents - vec2(1.0);
-                let extents_inv = vec2(1.0) / image.extents;
+                let atlas_offset = vec2<i32>(image.atlas_offset);
+                let size = vec2<i32>(image.extents);
+                // Images which couldn't be added to the atlas, or which are skipped until they
+                // are uploaded, have no extents and draw nothing.
+                let drawn = all(size > vec2(0));
                 switch image.quality {
                     case IMAGE_QUALITY_LOW: {
                         for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
                             // We only need to load from the textures if the value will be used.
-                            if area[i] != 0.0 {
-                                let my_xy = vec2(xy.x + f32(i), xy.y);
-                                var atlas_uv = image.matrx.xy * my_xy.x + image.matrx.zw * my_xy.y + image.xlat;
-                                atlas_uv.x = extend_mode(atlas_uv.x * extents_inv.x, image.x_extend_mode) * image.extents.x;
-                                atlas_uv.y = extend_mode(atlas_uv.y * extents_inv.y, image.y_extend_mode) * image.extents.y;
-                                atlas_uv = atlas_uv + image.atlas_offset;
-                                // TODO: If the image couldn't be added to the atlas (i.e. was too big), this isn't robust
-                                let atlas_uv_clamped = clamp(atlas_uv, image.atlas_offset, atlas_max);
+                            if area[i] != 0.0 && drawn {
+                                // Sample at the center of the pixel, so that an image drawn at its
+                                // natural size on whole pixels maps each texel to one pixel.
+                                let my_xy = vec2(xy.x + f32(i) + 0.5, xy.y + 0.5);
+                                let image_uv = image.matrx.xy * my_xy.x + image.matrx.zw * my_xy.y + image.xlat;
+                                let texel = vec2(
+                                    extend_texel(i32(floor(image_uv.x)), size.x, image.x_extend_mode),
+                                    extend_texel(i32(floor(image_uv.y)), size.y, image.y_extend_mode),
+                                );
                                 // Nearest neighbor sampling
-                                let fg_rgba = premul_alpha(textureLoad(image_atlas, vec2<i32>(atlas_uv_clamped), 0));
+                                let fg_rgba = premul_alpha(textureLoad(image_atlas, atlas_offset + texel, 0));
                                 let fg_i = fg_rgba * area[i] * image.alpha;
-                                rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                                rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                             }
                         }
                     }
                     case IMAGE_QUALITY_MEDIUM, default: {
-                        // We don't have an implementation for `IMAGE_QUALITY_HIGH` yet, just use the same as medium                        
+                        // We don't have an implementation for `IMAGE_QUALITY_HIGH` yet, just use the same as medium
                         for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
                             // We only need to load from the textures if the value will be used.
-                            if area[i] != 0.0 {
-                                let my_xy = vec2(xy.x + f32(i), xy.y);
-                                var atlas_uv = image.matrx.xy * my_xy.x + image.matrx.zw * my_xy.y + image.xlat;
-                                atlas_uv.x = extend_mode(atlas_uv.x * extents_inv.x, image.x_extend_mode) * image.extents.x;
-                                atlas_uv.y = extend_mode(atlas_uv.y * extents_inv.y, image.y_extend_mode) * image.extents.y;
-                                atlas_uv = atlas_uv + image.atlas_offset - vec2(0.5);
-                                // TODO: If the image couldn't be added to the atlas (i.e. was too big), this isn't robust
-                                let atlas_uv_clamped = clamp(atlas_uv, image.atlas_offset, atlas_max);
-                                // We know that the floor and ceil are within the atlas area because atlas_max and
-                                // atlas_offset are integers
-                                let uv_quad = vec4(floor(atlas_uv_clamped), ceil(atlas_uv_clamped));
-                                let uv_frac = fract(atlas_uv);
-                                let a = premul_alpha(textureLoad(image_atlas, vec2<i32>(uv_quad.xy), 0));
-                                let b = premul_alpha(textureLoad(image_atlas, vec2<i32>(uv_quad.xw), 0));
-                                let c = premul_alpha(textureLoad(image_atlas, vec2<i32>(uv_quad.zy), 0));
-                                let d = premul_alpha(textureLoad(image_atlas, vec2<i32>(uv_quad.zw), 0));
+                            if area[i] != 0.0 && drawn {
+                                let my_xy = vec2(xy.x + f32(i) + 0.5, xy.y + 0.5);
+                                // The texels around the sample, relative to their centers.
+                                let image_uv = image.matrx.xy * my_xy.x + image.matrx.zw * my_xy.y + image.xlat - vec2(0.5);
+                                let uv_floor = floor(image_uv);
+                                let uv_frac = image_uv - uv_floor;
+                                let uv0 = vec2<i32>(uv_floor);
+                                // Each texel is wrapped by the extend mode separately, so that samples
+                                // between the last and first texels of a repeated image blend them
+                                // rather than leaving a seam.
+                                let x0 = extend_texel(uv0.x, size.x, image.x_extend_mode);
+                                let x1 = extend_texel(uv0.x + 1, size.x, image.x_extend_mode);
+                                let y0 = extend_texel(uv0.y, size.y, image.y_extend_mode);
+                                let y1 = extend_texel(uv0.y + 1, size.y, image.y_extend_mode);
+                                let a = premul_alpha(textureLoad(image_atlas, atlas_offset + vec2(x0, y0), 0));
+                                let b = premul_alpha(textureLoad(image_atlas, atlas_offset + vec2(x0, y1), 0));
+                                let c = premul_alpha(textureLoad(image_atlas, atlas_offset + vec2(x1, y0), 0));
+                                let d = premul_alpha(textureLoad(image_atlas, atlas_offset + vec2(x1, y1), 0));
                                 // Bilinear sampling
                                 let fg_rgba = mix(mix(a, b, uv_frac.y), mix(c, d, uv_frac.y), uv_frac.x);
                                 let fg_i = fg_rgba * area[i] * image.alpha;
-                                rgba[i] = rgba[i] * (1.0 - fg_i.a) + fg_i;
+                                rgba[i] = paint_draw(rgba[i], fg_i, area[i], knockout, knockout_bg[i]);
                             }
                         }
                     }
@@ -1212,15 +1488,57 @@ fn main(
     for (var i = 0u; i < PIXELS_PER_THREAD; i += 1u) {
         let coords = xy_uint + vec2(i, 0u);
         if coords.x < config.target_width && coords.y < config.target_height {
+#ifdef winding
+            textureStore(output, vec2<i32>(coords), vec4(rgba[i].r, 0.0, 0.0, 0.0));
+#else
             let fg = rgba[i];
             // Max with a small epsilon to avoid NaNs
             let a_inv = 1.0 / max(fg.a, 1e-6);
             let rgba_sep = vec4(fg.rgb * a_inv, fg.a);
             textureStore(output, vec2<i32>(coords), rgba_sep);
+#endif
         }
     } 
 }
 
+// Composes a premultiplied draw over the pixels below it.
+fn paint_over(bg: vec4<f32>, fg: vec4<f32>) -> vec4<f32> {
+#ifdef overprint
+    // Inks absorb light rather than covering each other, so overlapping draws multiply like
+    // the `Multiply` blend mode. This is symmetric, so the order of the draws doesn't matter.
+    let rgb = bg.rgb * (1.0 - fg.a) + fg.rgb * (1.0 - bg.a) + fg.rgb * bg.rgb;
+    return vec4(rgb, bg.a + fg.a * (1.0 - bg.a));
+#else
+    return bg * (1.0 - fg.a) + fg;
+#endif
+}
+
+// Composes a premultiplied draw with coverage `area` over the pixels below it.
+//
+// In a knockout layer, the draw instead replaces the pixels it covers, being composed over
+// the pixels the layer started with.
+fn paint_draw(bg: vec4<f32>, fg: vec4<f32>, area: f32, knockout: bool, knockout_bg: vec4<f32>) -> vec4<f32> {
+    if knockout {
+        return (bg - knockout_bg) * (1.0 - area) + paint_over(knockout_bg, fg);
+    }
+    return paint_over(bg, fg);
+}
+
+// Returns the size of a command which paints a brush, or 0 for other commands.
+fn paint_size(tag: u32) -> u32 {
+    switch tag {
+        case CMD_COLOR, CMD_IMAGE, CMD_CUSTOM: {
+            return 2u;
+        }
+        case CMD_LIN_GRAD, CMD_RAD_GRAD, CMD_SWEEP_GRAD, CMD_BLUR_RECT, CMD_HATCH: {
+            return 3u;
+        }
+        default: {
+            return 0u;
+        }
+    }
+}
+
 fn premul_alpha(rgba: vec4<f32>) -> vec4<f32> {
     return vec4(rgba.rgb * rgba.a, rgba.a);
 }
@@ -459,6 +459,13 @@ fn flatten_euler(
                 }
                 let l0 = select(lp1, lp0, offset >= 0.);
                 let l1 = select(lp0, lp1, offset >= 0.);
+                if offset == 0. {
+                    line_kind = LINE_KIND_FILL;
+                } else if i + 1u == u32(n) && t1 == 1.0 {
+                    line_kind = LINE_KIND_SEAM;
+                } else {
+                    line_kind = LINE_KIND_OFFSET;
+                }
                 output_line_with_transform(path_ix, l0, l1, transform);
                 lp0 = lp1;
             }
@@ -521,6 +528,7 @@ fn draw_cap(
     cap0: vec2f, cap1: vec2f, offset_tangent: vec2f,
     transform: Transform,
 ) {
+    line_kind = LINE_KIND_CAP;
     if cap_style == STYLE_FLAGS_CAP_ROUND {
         flatten_arc(path_ix, cap0, cap1, point, 3.1415927, transform);
         return;
@@ -558,6 +566,7 @@ fn draw_join(
 
     switch style_flags & STYLE_FLAGS_JOIN_MASK {
         case STYLE_FLAGS_JOIN_BEVEL: {
+            line_kind = LINE_KIND_JOIN_BEVEL;
             output_two_lines_with_transform(path_ix, front0, front1, back0, back1, transform);
         }
         case STYLE_FLAGS_JOIN_MITER: {
@@ -565,7 +574,9 @@ fn draw_join(
             let miter_limit = unpack2x16float(style_flags & STYLE_MITER_LIMIT_MASK)[0];
 
             var line_ix: u32;
+            line_kind = LINE_KIND_JOIN_MITER_CLIPPED;
             if 2. * hypot < (hypot + d) * miter_limit * miter_limit && cr != 0. {
+                line_kind = LINE_KIND_JOIN_MITER;
                 let is_backside = cr > 0.;
                 let fp_last = select(front0, back1, is_backside);
                 let fp_this = select(front1, back0, is_backside);
@@ -591,6 +602,7 @@ fn draw_join(
             write_line_with_transform(line_ix + 1u, path_ix, back0, back1, transform);
         }
         case STYLE_FLAGS_JOIN_ROUND: {
+            line_kind = LINE_KIND_JOIN_ROUND;
             var arc0: vec2f;
             var arc1: vec2f;
             var other0: vec2f;
@@ -752,7 +764,7 @@ fn read_path_segment(tag: PathTagData, is_stroke: bool) -> CubicPoints {
 fn write_line(line_ix: u32, path_ix: u32, p0: vec2f, p1: vec2f) {
     bbox = vec4(min(bbox.xy, min(p0, p1)), max(bbox.zw, max(p0, p1)));
     if line_ix < config.lines_size {
-        lines[line_ix] = LineSoup(path_ix, p0, p1);
+        lines[line_ix] = LineSoup(path_ix, line_kind, p0, p1);
     }
 }
 
@@ -811,6 +823,9 @@ var<private> pathdata_base: u32;
 // during LineSoup generation.
 var<private> bbox: vec4f;
 
+// The kind of the lines being generated, which is written into the LineSoup for the debug layers.
+var<private> line_kind: u32;
+
 @compute @workgroup_size(256)
 fn main(
     @builtin(global_invocation_id) global_id: vec3<u32>,
@@ -21,6 +21,15 @@ const MIX_COLOR = 14u;
 const MIX_LUMINOSITY = 15u;
 const MIX_CLIP = 128u;
 
+// Set in the blend mode of non-isolated layers, whose content is drawn onto their backdrop
+// rather than onto a transparent layer.
+const BLEND_NON_ISOLATED = 0x10000u;
+// Set in the blend mode of knockout layers, in which each draw replaces the earlier draws
+// beneath it rather than being composed over them.
+const BLEND_KNOCKOUT = 0x20000u;
+// The bits of a blend mode which select how it mixes and composes.
+const BLEND_MODE_MASK = 0xffffu;
+
 fn screen(cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
     return cb + cs - (cb * cs);
 }
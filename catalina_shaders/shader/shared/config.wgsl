@@ -39,6 +39,9 @@ struct Config {
     segments_size: u32,
     blend_size: u32,
     ptcl_size: u32,
+
+    // First row of tiles processed by the fine stage, used when it is split into bands.
+    fine_row_offset: u32,
 }
 
 // Geometry of tiles and bins
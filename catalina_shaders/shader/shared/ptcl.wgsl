@@ -23,9 +23,16 @@ const CMD_BEGIN_CLIP = 10u;
 const CMD_END_CLIP = 11u;
 const CMD_JUMP = 12u;
 const CMD_BLUR_RECT = 13u;
+const CMD_HATCH = 15u;
+const CMD_CUSTOM = 16u;
+// Only written when measuring the cost of draw objects, followed by the index of the draw
+// object which the commands up to the next one belong to.
+const CMD_DRAW = 14u;
 
 // The individual PTCL structs are written here, but read/write is by
-// hand in the relevant shaders
+// hand in the relevant shaders. The layout of the commands in the buffer
+// is mirrored in `catalina_encoding/src/ptcl.rs`, and changes must be
+// made in both.
 
 struct CmdFill {
     size_and_rule: u32,
@@ -64,6 +71,29 @@ struct CmdBlurRect {
     std_dev: f32,
 }
 
+struct CmdHatch {
+    // Solid line color.
+    rgba_color: u32,
+
+    // The two sets of lines as functions of the device coordinates which are an integer on
+    // the center of each line. These are the same when not cross hatching.
+    line_0: vec3<f32>,
+    line_1: vec3<f32>,
+
+    // Half of the line width, as a fraction of the spacing.
+    half_width: f32,
+}
+
+struct CmdCustom {
+    // The inverse of the brush transform, from device to brush coordinates.
+    matrx: vec4<f32>,
+    xlat: vec2<f32>,
+
+    // The index of the registered paint function, and the data passed to it.
+    id: u32,
+    data: vec4<u32>,
+}
+
 struct CmdLinGrad {
     index: u32,
     extend_mode: u32,
@@ -103,6 +133,10 @@ struct CmdImage {
     alpha: f32,
 }
 
+struct CmdBeginClip {
+    blend: u32,
+}
+
 struct CmdEndClip {
     blend: u32,
     alpha: f32,
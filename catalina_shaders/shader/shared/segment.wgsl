@@ -18,12 +18,29 @@ struct Segment {
 // slice of segments.
 struct LineSoup {
     path_ix: u32,
-    // Note: this creates an alignment gap. Don't worry about
-    // this now, but maybe move to scalars.
+    // Which part of the path the line was flattened from, one of the
+    // LINE_KIND constants below. This fills the alignment gap before
+    // `p0`, and is only read by the debug layers.
+    kind: u32,
     p0: vec2<f32>,
     p1: vec2<f32>,
 }
 
+// A line of a filled path.
+const LINE_KIND_FILL = 0u;
+// A line of the offset curves of a stroke.
+const LINE_KIND_OFFSET = 1u;
+// The last line of an offset curve, which is snapped to the start of the
+// join or cap after it.
+const LINE_KIND_SEAM = 2u;
+// A line of a stroke cap.
+const LINE_KIND_CAP = 3u;
+const LINE_KIND_JOIN_BEVEL = 4u;
+const LINE_KIND_JOIN_MITER = 5u;
+// A miter join which exceeded the miter limit, so was drawn as a bevel.
+const LINE_KIND_JOIN_MITER_CLIPPED = 6u;
+const LINE_KIND_JOIN_ROUND = 7u;
+
 // An intermediate data structure for sorting tile segments.
 struct SegmentCount {
     // Reference to element of LineSoup array
@@ -48,6 +48,9 @@ enum InnerError {
 
     #[error("missing entry point function")]
     EntryPointNotFound,
+
+    #[error("failed to read shader")]
+    Io(#[from] std::io::Error),
 }
 
 impl fmt::Display for ErrorVec {
@@ -59,7 +62,34 @@ impl fmt::Display for ErrorVec {
     }
 }
 
+impl ErrorVec {
+    /// Returns the errors of each shader which failed to compile.
+    pub fn errors(&self) -> &[Error] {
+        &self.0
+    }
+}
+
 impl Error {
+    /// Returns the name of the shader, or the path of the file or directory which couldn't be
+    /// read.
+    pub fn shader_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the diagnostic for the error, with the span of preprocessed source it occurred
+    /// in, or an empty string if there is no source to point to.
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    fn io(path: &Path, error: std::io::Error) -> Self {
+        Self {
+            name: path.display().to_string(),
+            msg: String::new(),
+            source: error.into(),
+        }
+    }
+
     fn new(wgsl: &str, name: &str, error: impl Into<InnerError>) -> Self {
         let source = error.into();
         Self {
@@ -197,24 +227,30 @@ impl ShaderInfo {
                 HashMap::default()
             };
         //println!("{permutation_map:?}");
-        let imports = preprocess::get_imports(shader_dir);
+        let imports = preprocess::get_imports(shader_dir)
+            .map_err(|e| ErrorVec(vec![Error::io(&shader_dir.join("shared"), e)]))?;
         let mut errors = vec![];
         let mut info = HashMap::default();
         let defines: HashSet<_> = HashSet::default();
-        for entry in shader_dir
+        let entries = shader_dir
             .read_dir()
-            .expect("Can read shader import directory")
-            .filter_map(move |e| {
-                e.ok()
-                    .filter(|e| e.path().extension().map(|e| e == "wgsl").unwrap_or(false))
-            })
-        {
+            .map_err(|e| ErrorVec(vec![Error::io(shader_dir, e)]))?;
+        for entry in entries.filter_map(move |e| {
+            e.ok()
+                .filter(|e| e.path().extension().map(|e| e == "wgsl").unwrap_or(false))
+        }) {
             let file_name = entry.file_name();
             if let Some(name) = file_name.to_str() {
                 let suffix = ".wgsl";
                 if let Some(shader_name) = name.strip_suffix(suffix) {
-                    let contents = fs::read_to_string(shader_dir.join(&file_name))
-                        .unwrap_or_else(|_| panic!("Couldn't read shader {shader_name} contents"));
+                    let path = shader_dir.join(&file_name);
+                    let contents = match fs::read_to_string(&path) {
+                        Ok(contents) => contents,
+                        Err(e) => {
+                            errors.push(Error::io(&path, e));
+                            continue;
+                        }
+                    };
                     if let Some(permutations) = permutation_map.get(shader_name) {
                         for permutation in permutations {
                             let mut defines = defines.clone();
@@ -5,28 +5,23 @@ use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::{fs, vec};
 
-pub fn get_imports(shader_dir: &Path) -> HashMap<String, String> {
+pub fn get_imports(shader_dir: &Path) -> std::io::Result<HashMap<String, String>> {
     let mut imports = HashMap::new();
     let imports_dir = shader_dir.join("shared");
-    for entry in imports_dir
-        .read_dir()
-        .expect("Can read shader import directory")
-        .filter_map(move |e| {
-            e.ok()
-                .filter(|e| e.path().extension().map(|e| e == "wgsl").unwrap_or(false))
-        })
-    {
+    for entry in imports_dir.read_dir()?.filter_map(move |e| {
+        e.ok()
+            .filter(|e| e.path().extension().map(|e| e == "wgsl").unwrap_or(false))
+    }) {
         let file_name = entry.file_name();
         if let Some(name) = file_name.to_str() {
             let suffix = ".wgsl";
             if let Some(import_name) = name.strip_suffix(suffix) {
-                let contents = fs::read_to_string(imports_dir.join(&file_name))
-                    .unwrap_or_else(|_| panic!("Couldn't read shader {import_name} contents"));
+                let contents = fs::read_to_string(imports_dir.join(&file_name))?;
                 imports.insert(import_name.to_owned(), contents);
             }
         }
     }
-    imports
+    Ok(imports)
 }
 
 pub struct StackItem {
@@ -9,8 +9,9 @@ use catalina_encoding::{
 };
 
 use super::{
-    CpuBinding, CMD_BEGIN_CLIP, CMD_BLUR_RECT, CMD_COLOR, CMD_END, CMD_END_CLIP, CMD_FILL,
-    CMD_IMAGE, CMD_JUMP, CMD_LIN_GRAD, CMD_RAD_GRAD, CMD_SOLID, CMD_SWEEP_GRAD, PTCL_INITIAL_ALLOC,
+    CpuBinding, CMD_BEGIN_CLIP, CMD_BLUR_RECT, CMD_COLOR, CMD_CUSTOM, CMD_END, CMD_END_CLIP,
+    CMD_FILL, CMD_HATCH, CMD_IMAGE, CMD_JUMP, CMD_LIN_GRAD, CMD_RAD_GRAD, CMD_SOLID,
+    CMD_SWEEP_GRAD, PTCL_INITIAL_ALLOC,
 };
 
 // Tiles per bin
@@ -153,15 +154,45 @@ impl TileState {
         self.cmd_offset += 3;
     }
 
+    fn write_hatch(
+        &mut self,
+        config: &ConfigUniform,
+        bump: &mut BumpAllocators,
+        ptcl: &mut [u32],
+        rgba_color: u32,
+        info_offset: u32,
+    ) {
+        self.alloc_cmd(3, config, bump, ptcl);
+        self.write(ptcl, 0, CMD_HATCH);
+        self.write(ptcl, 1, info_offset);
+        self.write(ptcl, 2, rgba_color);
+        self.cmd_offset += 3;
+    }
+
+    fn write_custom(
+        &mut self,
+        config: &ConfigUniform,
+        bump: &mut BumpAllocators,
+        ptcl: &mut [u32],
+        info_offset: u32,
+    ) {
+        self.alloc_cmd(2, config, bump, ptcl);
+        self.write(ptcl, 0, CMD_CUSTOM);
+        self.write(ptcl, 1, info_offset);
+        self.cmd_offset += 2;
+    }
+
     fn write_begin_clip(
         &mut self,
         config: &ConfigUniform,
         bump: &mut BumpAllocators,
         ptcl: &mut [u32],
+        blend: u32,
     ) {
-        self.alloc_cmd(1, config, bump, ptcl);
+        self.alloc_cmd(2, config, bump, ptcl);
         self.write(ptcl, 0, CMD_BEGIN_CLIP);
-        self.cmd_offset += 1;
+        self.write(ptcl, 1, blend);
+        self.cmd_offset += 2;
     }
 
     fn write_end_clip(
@@ -333,11 +364,21 @@ fn coarse_main(
                                 let rgba_color = scene[dd as usize];
                                 tile_state.write_blur_rect(config, bump, ptcl, rgba_color, di + 1);
                             }
+                            DrawTag::HATCH => {
+                                tile_state.write_path(config, bump, ptcl, tile, draw_flags);
+                                let rgba_color = scene[dd as usize];
+                                tile_state.write_hatch(config, bump, ptcl, rgba_color, di + 1);
+                            }
+                            DrawTag::CUSTOM => {
+                                tile_state.write_path(config, bump, ptcl, tile, draw_flags);
+                                tile_state.write_custom(config, bump, ptcl, di + 1);
+                            }
                             DrawTag::BEGIN_CLIP => {
                                 if tile.segment_count_or_ix == 0 && tile.backdrop == 0 {
                                     clip_zero_depth = clip_depth + 1;
                                 } else {
-                                    tile_state.write_begin_clip(config, bump, ptcl);
+                                    let blend = scene[dd as usize];
+                                    tile_state.write_begin_clip(config, bump, ptcl, blend);
                                     // TODO: Do we need to track this separately, seems like it
                                     // is always the same as clip_depth in this code path
                                     render_blend_depth += 1;
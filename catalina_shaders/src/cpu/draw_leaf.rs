@@ -47,6 +47,8 @@ fn draw_leaf_main(
                 || tag_word == DrawTag::IMAGE
                 || tag_word == DrawTag::BEGIN_CLIP
                 || tag_word == DrawTag::BLUR_RECT
+                || tag_word == DrawTag::HATCH
+                || tag_word == DrawTag::CUSTOM
             {
                 let bbox = path_bbox[m.path_ix as usize];
                 let transform = Transform::read(config.layout.transform_base, bbox.trans_ix, scene);
@@ -191,6 +193,33 @@ fn draw_leaf_main(
                         info[di + 9] = scene[dd as usize + 3];
                         info[di + 10] = scene[dd as usize + 4];
                     }
+                    DrawTag::HATCH => {
+                        info[di] = draw_flags;
+                        let angle = f32::from_bits(scene[dd as usize + 1]);
+                        let spacing = f32::from_bits(scene[dd as usize + 2]);
+                        let line_width = f32::from_bits(scene[dd as usize + 3]);
+                        let cross = scene[dd as usize + 4];
+                        let z = transform.inverse().0;
+                        let normal = Vec2::new(-angle.sin(), angle.cos()) * spacing.recip();
+                        let mut normals = [normal, normal];
+                        if cross != 0 {
+                            normals[1] = Vec2::new(normal.y, -normal.x);
+                        }
+                        for (j, n) in normals.iter().enumerate() {
+                            info[di + 1 + j * 3] = f32::to_bits(z[0] * n.x + z[1] * n.y);
+                            info[di + 2 + j * 3] = f32::to_bits(z[2] * n.x + z[3] * n.y);
+                            info[di + 3 + j * 3] = f32::to_bits(z[4] * n.x + z[5] * n.y);
+                        }
+                        info[di + 7] = f32::to_bits(0.5 * line_width / spacing);
+                    }
+                    DrawTag::CUSTOM => {
+                        info[di] = draw_flags;
+                        let xform = transform.inverse();
+                        for (j, z) in xform.0.iter().enumerate() {
+                            info[di + 1 + j] = f32::to_bits(*z);
+                        }
+                        info[di + 7..di + 12].copy_from_slice(&scene[dd as usize..dd as usize + 5]);
+                    }
                     DrawTag::BEGIN_CLIP => (),
                     _ => todo!("unhandled draw tag {:x}", tag_word.0),
                 }
@@ -91,7 +91,7 @@ fn write_line(
     bbox.add_pt(p1);
     lines[line_ix] = LineSoup {
         path_ix,
-        _padding: Default::default(),
+        kind: LineSoup::KIND_FILL,
         p0: p0.to_array(),
         p1: p1.to_array(),
     };
@@ -157,6 +157,13 @@ fn output_two_lines_with_transform(
     *line_ix += 2;
 }
 
+/// Sets the kind of the lines from `start` up to `end`, which the debug layers show.
+fn set_line_kind(lines: &mut [LineSoup], start: usize, end: usize, kind: u32) {
+    for line in &mut lines[start..end] {
+        line.kind = kind;
+    }
+}
+
 fn flatten_arc(
     path_ix: u32,
     begin: Vec2,
@@ -435,7 +442,7 @@ fn draw_join(
     line_ix: &mut usize,
     lines: &mut [LineSoup],
     bbox: &mut IntBbox,
-) {
+) -> u32 {
     let mut front0 = p0 + n_prev;
     let front1 = p0 + n_next;
     let mut back0 = p0 - n_next;
@@ -451,11 +458,13 @@ fn draw_join(
                     path_ix, front0, front1, back0, back1, transform, line_ix, lines, bbox,
                 );
             }
+            LineSoup::KIND_JOIN_BEVEL
         }
         Style::FLAGS_JOIN_BITS_MITER => {
             let hypot = cr.hypot(d);
             let miter_limit = f16_to_f32((style_flags & Style::MITER_LIMIT_MASK) as u16);
 
+            let mut kind = LineSoup::KIND_JOIN_MITER_CLIPPED;
             if 2. * hypot < (hypot + d) * miter_limit * miter_limit && cr != 0. {
                 let is_backside = cr > 0.;
                 let fp_last = if is_backside { back1 } else { front0 };
@@ -473,10 +482,12 @@ fn draw_join(
                 } else {
                     front0 = miter_pt;
                 }
+                kind = LineSoup::KIND_JOIN_MITER;
             }
             output_two_lines_with_transform(
                 path_ix, front0, front1, back0, back1, transform, line_ix, lines, bbox,
             );
+            kind
         }
         Style::FLAGS_JOIN_BITS_ROUND => {
             let (arc0, arc1, other0, other1) = if cr > 0. {
@@ -496,6 +507,7 @@ fn draw_join(
                 bbox,
             );
             output_line_with_transform(path_ix, other0, other1, transform, line_ix, lines, bbox);
+            LineSoup::KIND_JOIN_ROUND
         }
         _ => unreachable!(),
     }
@@ -706,6 +718,7 @@ fn flatten_main(
                 if is_stroke_cap_marker {
                     if is_open {
                         // Draw start cap
+                        let start = line_ix;
                         let tangent = pts.p3 - pts.p0;
                         let offset_tangent = offset * tangent.normalize();
                         let n = Vec2::new(-offset_tangent.y, offset_tangent.x);
@@ -721,6 +734,7 @@ fn flatten_main(
                             lines,
                             &mut bbox,
                         );
+                        set_line_kind(lines, start, line_ix, LineSoup::KIND_CAP);
                     } else {
                         // Don't draw anything if the path is closed.
                     }
@@ -762,31 +776,31 @@ fn flatten_main(
                     log!("@ tan_next: {:#?}", tan_next);
 
                     // Render offset curves
-                    flatten_euler(
-                        &pts,
-                        path_ix,
-                        &transform,
-                        offset,
-                        pts.p0 + n_start,
-                        pts.p3 + n_prev,
-                        &mut line_ix,
-                        lines,
-                        &mut bbox,
-                    );
-                    flatten_euler(
-                        &pts,
-                        path_ix,
-                        &transform,
-                        -offset,
-                        pts.p0 - n_start,
-                        pts.p3 - n_prev,
-                        &mut line_ix,
-                        lines,
-                        &mut bbox,
-                    );
+                    for (side_offset, start_p, end_p) in [
+                        (offset, pts.p0 + n_start, pts.p3 + n_prev),
+                        (-offset, pts.p0 - n_start, pts.p3 - n_prev),
+                    ] {
+                        let start = line_ix;
+                        flatten_euler(
+                            &pts,
+                            path_ix,
+                            &transform,
+                            side_offset,
+                            start_p,
+                            end_p,
+                            &mut line_ix,
+                            lines,
+                            &mut bbox,
+                        );
+                        if side_offset != 0. && line_ix > start {
+                            set_line_kind(lines, start, line_ix - 1, LineSoup::KIND_OFFSET);
+                            set_line_kind(lines, line_ix - 1, line_ix, LineSoup::KIND_SEAM);
+                        }
+                    }
 
+                    let start = line_ix;
                     if neighbor.do_join {
-                        draw_join(
+                        let kind = draw_join(
                             path_ix,
                             style_flags,
                             pts.p3,
@@ -799,6 +813,7 @@ fn flatten_main(
                             lines,
                             &mut bbox,
                         );
+                        set_line_kind(lines, start, line_ix, kind);
                     } else {
                         // Draw end cap.
                         draw_cap(
@@ -813,6 +828,7 @@ fn flatten_main(
                             lines,
                             &mut bbox,
                         );
+                        set_line_kind(lines, start, line_ix, LineSoup::KIND_CAP);
                     }
                 }
             } else {
@@ -0,0 +1,51 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Linking of user provided paint functions into the fine rasterization shaders.
+
+use std::fmt::Write;
+
+const BEGIN_MARKER: &str = "// CUSTOM_PAINTS_BEGIN";
+const END_MARKER: &str = "// CUSTOM_PAINTS_END";
+
+/// A paint function written in WGSL, which is evaluated for each pixel of the shapes
+/// filled with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomPaintSource {
+    /// The name of the paint function, which must be declared in `source` with the signature
+    /// `fn(xy: vec2<f32>, data: vec4<u32>) -> vec4<f32>`.
+    ///
+    /// The function is given the center of the pixel in the coordinates of the brush, and the
+    /// data encoded with the fill, and returns a premultiplied color.
+    pub entry_point: String,
+    /// The WGSL declarations of the paint function and anything it uses.
+    pub source: String,
+}
+
+/// Replaces the placeholder paint function in the source of a fine shader with one which
+/// dispatches to `paints`, using the index of each paint as its id.
+///
+/// Returns `None` if the source doesn't contain the placeholder.
+pub fn link_custom_paints(fine_source: &str, paints: &[CustomPaintSource]) -> Option<String> {
+    let begin = fine_source.find(BEGIN_MARKER)?;
+    let end = fine_source[begin..].find(END_MARKER)? + begin + END_MARKER.len();
+    let mut linked = String::with_capacity(fine_source.len());
+    linked.push_str(&fine_source[..begin]);
+    for paint in paints {
+        linked.push_str(&paint.source);
+        linked.push('\n');
+    }
+    linked.push_str("fn custom_paint(id: u32, xy: vec2<f32>, data: vec4<u32>) -> vec4<f32> {\n");
+    linked.push_str("    switch id {\n");
+    for (id, paint) in paints.iter().enumerate() {
+        // Writing to a `String` can't fail.
+        let _ = writeln!(
+            linked,
+            "        case {id}u: {{ return {}(xy, data); }}",
+            paint.entry_point
+        );
+    }
+    linked.push_str("        default: { return vec4(0.0); }\n    }\n}");
+    linked.push_str(&fine_source[end..]);
+    Some(linked)
+}
@@ -44,6 +44,7 @@
     reason = "Deferred, only apply in some feature sets so not expect"
 )]
 
+mod custom_paint;
 mod types;
 
 #[cfg(feature = "compile")]
@@ -51,6 +52,7 @@ pub mod compile;
 #[cfg(feature = "cpu")]
 pub mod cpu;
 
+pub use custom_paint::{link_custom_paints, CustomPaintSource};
 #[cfg(feature = "msl")]
 pub use types::msl;
 pub use types::{BindType, BindingInfo, WorkgroupBufferInfo};
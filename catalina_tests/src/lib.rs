@@ -38,10 +38,13 @@ use anyhow::{anyhow, bail, Result};
 use catalina::kurbo::{Affine, Vec2};
 use catalina::peniko::{color::palette, Blob, Color, Image, ImageFormat};
 use catalina::wgpu::{
-    self, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, TexelCopyBufferInfo,
-    TextureDescriptor, TextureFormat, TextureUsages,
+    self, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device, Extent3d, Queue,
+    TexelCopyBufferInfo, TextureDescriptor, TextureFormat, TextureUsages,
+};
+use catalina::{
+    util::block_on_wgpu, util::RenderContext, AaConfig, PipelineStage, Renderer, RendererOptions,
+    Scene,
 };
-use catalina::{util::block_on_wgpu, util::RenderContext, AaConfig, RendererOptions, Scene};
 use scenes::{ExampleScene, ImageCache, SceneParams, SimpleText};
 
 mod compare;
@@ -57,6 +60,8 @@ pub struct TestParams {
     pub height: u32,
     pub base_color: Option<Color>,
     pub use_cpu: bool,
+    /// The stage from which to run on the GPU when `use_cpu` is set.
+    pub gpu_stages_from: Option<PipelineStage>,
     pub name: String,
     pub anti_aliasing: AaConfig,
 }
@@ -68,6 +73,7 @@ impl TestParams {
             height,
             base_color: None,
             use_cpu: false,
+            gpu_stages_from: None,
             name: name.into(),
             anti_aliasing: AaConfig::Area,
         }
@@ -109,16 +115,37 @@ pub async fn get_scene_image(params: &TestParams, scene: &Scene) -> Result<Image
     let device_handle = &mut context.devices[device_id];
     let device = &device_handle.device;
     let queue = &device_handle.queue;
-    let mut renderer = catalina::Renderer::new(
+    let mut renderer = create_renderer(device, params)?;
+    render_with(device, queue, &mut renderer, scene, params)
+}
+
+/// Creates a renderer configured for `params`.
+pub fn create_renderer(device: &Device, params: &TestParams) -> Result<Renderer> {
+    Renderer::new(
         device,
         RendererOptions {
             surface_format: None,
             use_cpu: params.use_cpu,
             num_init_threads: NonZeroUsize::new(1),
+            gpu_stages_from: params.gpu_stages_from,
+            fine_band_rows: None,
+            frames_in_flight: None,
+            disk_cache: None,
+            small_scenes: None,
             antialiasing_support: std::iter::once(params.anti_aliasing).collect(),
         },
     )
-    .or_else(|_| bail!("Got non-Send/Sync error from creating renderer"))?;
+    .or_else(|_| bail!("Got non-Send/Sync error from creating renderer"))
+}
+
+/// Renders `scene` with an existing renderer, for testing the state it keeps between frames.
+pub fn render_with(
+    device: &Device,
+    queue: &Queue,
+    renderer: &mut Renderer,
+    scene: &Scene,
+    params: &TestParams,
+) -> Result<Image> {
     let width = params.width;
     let height = params.height;
     let render_params = catalina::RenderParams {
@@ -126,6 +153,10 @@ pub async fn get_scene_image(params: &TestParams, scene: &Scene) -> Result<Image
         width,
         height,
         antialiasing_method: params.anti_aliasing,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
     };
     let size = Extent3d {
         width,
@@ -189,6 +220,56 @@ pub async fn get_scene_image(params: &TestParams, scene: &Scene) -> Result<Image
     Ok(image)
 }
 
+/// Reads back the contents of a texture with `bytes_per_pixel` bytes per pixel, without the
+/// padding of each row.
+pub fn read_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &wgpu::Texture,
+    bytes_per_pixel: u32,
+) -> Result<Vec<u8>> {
+    let size = texture.size();
+    let byte_width = size.width * bytes_per_pixel;
+    let padded_byte_width = byte_width.next_multiple_of(256);
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("Texture readback"),
+        size: u64::from(padded_byte_width) * u64::from(size.height),
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Copy out texture"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_byte_width),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+    queue.submit([encoder.finish()]);
+    let buf_slice = buffer.slice(..);
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    buf_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+    if let Some(recv_result) = block_on_wgpu(device, receiver.receive()) {
+        recv_result?;
+    } else {
+        bail!("channel was closed");
+    }
+    let data = buf_slice.get_mapped_range();
+    let mut result = Vec::with_capacity((byte_width * size.height).try_into()?);
+    for row in 0..size.height {
+        let start = (row * padded_byte_width).try_into()?;
+        result.extend(&data[start..start + byte_width as usize]);
+    }
+    Ok(result)
+}
+
 pub fn write_png_to_file(
     params: &TestParams,
     out_path: &Path,
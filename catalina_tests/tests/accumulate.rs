@@ -0,0 +1,94 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of temporal accumulation with [`catalina::Renderer::accumulate`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Circle};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{render_to_pixels, RenderContext};
+use catalina::{AaConfig, Accumulator, Error, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 32;
+
+fn params(size: u32) -> RenderParams {
+    RenderParams {
+        base_color: palette::css::WHITE,
+        width: size,
+        height: size,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn frames_are_jittered_and_averaged() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("accumulate", 1, 1)).unwrap();
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLACK,
+        None,
+        &Circle::new((16.0, 16.0), 10.3),
+    );
+    let full = params(SIZE);
+    let single = render_to_pixels(&mut renderer, device, queue, &scene, &full).unwrap();
+    let mut accumulator = Accumulator::new(device, SIZE, SIZE);
+
+    // The first frame isn't jittered, so matches a normal render.
+    renderer
+        .accumulate(device, queue, &scene, &full, &mut accumulator)
+        .unwrap();
+    assert_eq!(accumulator.frames(), 1);
+    assert_eq!(accumulator.read_pixels(device, queue).unwrap(), single);
+
+    let mut jitters = vec![];
+    for _ in 0..15 {
+        let jitter = accumulator.jitter();
+        assert!(jitter.x.abs() < 0.5 && jitter.y.abs() < 0.5);
+        jitters.push(jitter);
+        renderer
+            .accumulate(device, queue, &scene, &full, &mut accumulator)
+            .unwrap();
+    }
+    assert_eq!(accumulator.frames(), 16);
+    jitters.dedup();
+    assert_eq!(jitters.len(), 15);
+    // The average only differs from a single frame at the edge of the circle.
+    let averaged = accumulator.read_pixels(device, queue).unwrap();
+    let at = |pixels: &[u8], x: u32, y: u32| pixels[((y * SIZE + x) * 4) as usize];
+    assert_eq!(at(&averaged, 16, 16), 0);
+    assert_eq!(at(&averaged, 1, 1), 255);
+    assert_ne!(averaged, single);
+    for (a, b) in averaged.iter().zip(&single) {
+        assert!(a.abs_diff(*b) < 64);
+    }
+
+    // After a reset, the next frame is a normal render again.
+    accumulator.reset(device, queue);
+    assert_eq!(accumulator.frames(), 0);
+    renderer
+        .accumulate(device, queue, &scene, &full, &mut accumulator)
+        .unwrap();
+    assert_eq!(accumulator.read_pixels(device, queue).unwrap(), single);
+
+    let result = renderer.accumulate(device, queue, &scene, &params(16), &mut accumulator);
+    assert!(matches!(result, Err(Error::AccumulatorSizeMismatch)));
+}
@@ -0,0 +1,166 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of rendering images in bands with [`catalina::Renderer::render_to_image_in_bands`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroU32;
+
+use catalina::kurbo::{Affine, Circle, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{render_to_pixels, DeviceHandle, RenderContext};
+use catalina::{AaConfig, Error, RenderParams, Renderer, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+fn params(width: u32, height: u32) -> RenderParams {
+    RenderParams {
+        base_color: palette::css::WHITE,
+        width,
+        height,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    }
+}
+
+fn renderer(context: &mut RenderContext) -> (&DeviceHandle, Renderer) {
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let renderer = create_renderer(&handle.device, &TestParams::new("banded", 1, 1)).unwrap();
+    (handle, renderer)
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn bands_match_a_single_pass() {
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 60;
+    let mut scene = Scene::new();
+    // Shapes which cross the seams between the bands, at 16 and 32 pixels.
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Circle::new((32.0, 30.0), 20.5),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Rect::new(4.0, 12.5, 60.0, 35.5),
+    );
+    let mut context = RenderContext::new();
+    let (handle, mut renderer) = renderer(&mut context);
+    let params = params(WIDTH, HEIGHT);
+    let expected = render_to_pixels(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    )
+    .unwrap();
+    let banded = renderer
+        .render_to_image_in_bands(
+            &handle.device,
+            &handle.queue,
+            &scene,
+            &params,
+            NonZeroU32::new(16).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(banded.len(), expected.len());
+    for (ix, (a, b)) in banded.iter().zip(&expected).enumerate() {
+        let pixel = u32::try_from(ix / 4).unwrap();
+        let (x, y) = (pixel % WIDTH, pixel / WIDTH);
+        assert!(a.abs_diff(*b) <= 1, "pixel ({x}, {y}) differs: {a} != {b}");
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn images_wider_than_the_device_limit() {
+    let mut context = RenderContext::new();
+    let (handle, mut renderer) = renderer(&mut context);
+    let limit = handle.device.limits().max_texture_dimension_2d;
+    let width = limit + 100;
+    let height = 2;
+    // The left of the image is red and the right is blue, with the edge after the seam
+    // between the first and second tile.
+    let edge = limit + 50;
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(edge), f64::from(height)),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Rect::new(f64::from(edge), 0.0, f64::from(width), f64::from(height)),
+    );
+    let pixels = renderer
+        .render_to_image_in_bands(
+            &handle.device,
+            &handle.queue,
+            &scene,
+            &params(width, height),
+            NonZeroU32::new(height).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(pixels.len(), (width * height * 4) as usize);
+    let at = |x: u32, y: u32| {
+        let offset = ((y * width + x) * 4) as usize;
+        &pixels[offset..offset + 4]
+    };
+    for y in 0..height {
+        for x in [0, limit - 1, limit, edge - 1] {
+            assert_eq!(at(x, y), [255, 0, 0, 255], "at ({x}, {y})");
+        }
+        for x in [edge, width - 1] {
+            assert_eq!(at(x, y), [0, 0, 255, 255], "at ({x}, {y})");
+        }
+    }
+    let tiled = renderer
+        .render_to_image_tiled(
+            &handle.device,
+            &handle.queue,
+            &scene,
+            &params(width, height),
+        )
+        .unwrap();
+    assert_eq!(tiled, pixels);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn empty_images_are_errors() {
+    let mut context = RenderContext::new();
+    let (handle, mut renderer) = renderer(&mut context);
+    let scene = Scene::new();
+    for (width, height) in [(0, 10), (10, 0)] {
+        let result = renderer.render_to_image_in_bands(
+            &handle.device,
+            &handle.queue,
+            &scene,
+            &params(width, height),
+            NonZeroU32::new(16).unwrap(),
+        );
+        assert!(matches!(result, Err(Error::EmptyTarget)));
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of logging bump allocation overflows with the `debug-logs` feature.
+
+#![cfg(feature = "debug-logs")]
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Mutex;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, BlendMode, Compose, Fill, Mix};
+use catalina::util::RenderContext;
+use catalina::wgpu::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureViewDescriptor,
+};
+use catalina::{AaConfig, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+/// Collects the warnings logged by Catalina.
+struct WarningLogger(Mutex<Vec<String>>);
+
+impl log::Log for WarningLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: WarningLogger = WarningLogger(Mutex::new(Vec::new()));
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn overflows_are_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    // Layers nested deeper than the blend stack kept in registers spill to the blend spill
+    // buffer for every tile they cover, which overflows it for a large enough target.
+    let (width, height) = (2048, 2048);
+    let rect = Rect::new(0.0, 0.0, f64::from(width), f64::from(height));
+    let mut scene = Scene::new();
+    for _ in 0..8 {
+        let blend = BlendMode::new(Mix::Multiply, Compose::SrcOver);
+        scene.push_layer(blend, 1.0, Affine::IDENTITY, &rect);
+    }
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &rect,
+    );
+    for _ in 0..8 {
+        scene.pop_layer();
+    }
+
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("overflow", width, height)).unwrap();
+    let texture = handle.device.create_texture(&TextureDescriptor {
+        label: Some("Target texture"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    let params = RenderParams {
+        base_color: palette::css::BLACK,
+        width,
+        height,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    #[expect(deprecated)]
+    let bump = pollster::block_on(renderer.render_to_texture_async(
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &view,
+        &params,
+    ))
+    .unwrap();
+
+    // The bump allocators are downloaded with `debug-logs`, even without `debug_layers`.
+    let bump = bump.expect("the bump allocators were downloaded");
+    assert_ne!(bump.failed, 0);
+    let warnings = LOGGER.0.lock().unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.starts_with("Bump allocation overflow")),
+        "{warnings:?}"
+    );
+}
@@ -0,0 +1,97 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of layers which are rendered once and composited with arbitrary transforms.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::f64::consts::PI;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{CachedLayer, Scene};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+const SIZE: u32 = 32;
+
+/// Returns the pixel at `(x, y)` of RGBA8 `data` with rows of `SIZE` pixels.
+fn pixel(data: &[u8], x: u32, y: u32) -> [u8; 4] {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    data[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn cached_layer_reused_under_transforms() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("cached_layer", SIZE, SIZE);
+    let mut renderer = create_renderer(&handle.device, &params).unwrap();
+
+    // Red in the left half of the layer.
+    let bounds = Rect::new(0.0, 0.0, SIZE.into(), SIZE.into());
+    let mut content = Scene::new();
+    content.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(SIZE) / 2.0, SIZE.into()),
+    );
+    let mut layer = CachedLayer::new(bounds);
+    let mut draw = |cached: &mut CachedLayer, transform| {
+        let mut scene = Scene::new();
+        let rendered = cached
+            .draw(
+                &mut renderer,
+                &handle.device,
+                &handle.queue,
+                &content,
+                &mut scene,
+                transform,
+            )
+            .unwrap();
+        let image = render_with(
+            &handle.device,
+            &handle.queue,
+            &mut renderer,
+            &scene,
+            &params,
+        )
+        .unwrap();
+        (rendered, image.data.data().to_vec())
+    };
+
+    let (rendered, identity) = draw(&mut layer, Affine::IDENTITY);
+    assert!(rendered);
+    assert_eq!(pixel(&identity, 8, 16), [0xFF, 0, 0, 0xFF]);
+    assert_eq!(pixel(&identity, 24, 16), [0, 0, 0, 0xFF]);
+
+    // Rotating the layer by half a turn moves the red half to the right, without rendering
+    // the content again.
+    let center = (f64::from(SIZE) / 2.0, f64::from(SIZE) / 2.0);
+    let (rerendered, rotated) = draw(&mut layer, Affine::rotate_about(PI, center.into()));
+    assert!(!rerendered);
+    assert_eq!(pixel(&rotated, 8, 16), [0, 0, 0, 0xFF]);
+    assert_eq!(pixel(&rotated, 24, 16), [0xFF, 0, 0, 0xFF]);
+
+    // Only large changes of scale render the content again.
+    assert!(!layer.needs_raster(Affine::scale(1.5)));
+    assert!(!layer.needs_raster(Affine::scale(0.6)));
+    assert!(layer.needs_raster(Affine::scale(3.0)));
+    assert!(layer.needs_raster(Affine::scale(0.25)));
+    let (rasterized, scaled) = draw(&mut layer, Affine::scale(3.0));
+    assert!(rasterized);
+    assert_eq!(pixel(&scaled, 24, 16), [0xFF, 0, 0, 0xFF]);
+
+    layer.invalidate();
+    assert!(layer.needs_raster(Affine::scale(3.0)));
+}
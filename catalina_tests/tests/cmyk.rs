@@ -0,0 +1,104 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the conversion of CMYK and spot colors by [`catalina::CmykConverter`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::peniko::{color::palette, Color};
+use catalina::{Cmyk, CmykConverter};
+
+fn assert_close(actual: Color, expected: Color) {
+    let close = actual
+        .components
+        .iter()
+        .zip(expected.components)
+        .all(|(a, e)| (a - e).abs() < 1e-3);
+    assert!(close, "{actual:?} is not close to {expected:?}");
+}
+
+#[test]
+fn naive_conversion() {
+    let converter = CmykConverter::naive();
+    assert_close(
+        converter.convert(Cmyk::new(0.0, 0.0, 0.0, 0.0)),
+        palette::css::WHITE,
+    );
+    assert_close(
+        converter.convert(Cmyk::new(1.0, 0.0, 0.0, 0.0)),
+        palette::css::AQUA,
+    );
+    assert_close(
+        converter.convert(Cmyk::new(0.0, 1.0, 1.0, 0.0)),
+        palette::css::RED,
+    );
+    assert_close(
+        converter.convert(Cmyk::new(0.0, 0.0, 0.0, 1.0).with_alpha(0.5)),
+        Color::new([0.0, 0.0, 0.0, 0.5]),
+    );
+}
+
+#[test]
+fn ink_colors_on_paper() {
+    let paper = Color::from_rgb8(0xF0, 0xEE, 0xE8);
+    let inks = [
+        Color::from_rgb8(0x00, 0x9F, 0xE3),
+        Color::from_rgb8(0xE5, 0x00, 0x7E),
+        Color::from_rgb8(0xFF, 0xED, 0x00),
+        Color::from_rgb8(0x2B, 0x2A, 0x29),
+    ];
+    let converter = CmykConverter::from_inks(paper, inks);
+    assert_close(converter.convert(Cmyk::new(0.0, 0.0, 0.0, 0.0)), paper);
+    assert_close(converter.convert(Cmyk::new(1.0, 0.0, 0.0, 0.0)), inks[0]);
+    assert_close(converter.convert(Cmyk::new(0.0, 0.0, 0.0, 1.0)), inks[3]);
+    // Overlapping inks are darker than either.
+    let [r, g, b, _] = converter.convert(Cmyk::new(1.0, 1.0, 0.0, 0.0)).components;
+    assert!(r < 0.1 && g < 0.1 && b > 0.2);
+}
+
+#[test]
+fn lut_interpolates_between_samples() {
+    // The naive conversion is linear along each axis, so is reproduced exactly by a table of
+    // only its corners.
+    let naive = CmykConverter::naive();
+    let samples: Vec<[f32; 3]> = (0..16_u32)
+        .map(|ix| {
+            let ink = |bit: u32| ((ix >> bit) & 1) as f32;
+            let [r, g, b, _] = naive
+                .convert(Cmyk::new(ink(3), ink(2), ink(1), ink(0)))
+                .components;
+            [r, g, b]
+        })
+        .collect();
+    let converter = CmykConverter::from_lut(2, samples).unwrap();
+    for cmyk in [
+        Cmyk::new(0.0, 0.0, 0.0, 0.0),
+        Cmyk::new(0.5, 0.0, 0.0, 0.0),
+        Cmyk::new(0.2, 0.4, 0.6, 0.1),
+        Cmyk::new(1.0, 1.0, 1.0, 1.0),
+    ] {
+        assert_close(converter.convert(cmyk), naive.convert(cmyk));
+    }
+    assert!(CmykConverter::from_lut(3, vec![[0.0; 3]; 16]).is_none());
+    assert!(CmykConverter::from_lut(1, vec![[0.0; 3]; 1]).is_none());
+}
+
+#[test]
+fn spot_tints() {
+    let blue = Color::from_rgb8(0x10, 0x0C, 0x9F);
+    let converter = CmykConverter::naive().with_spot("Reflex Blue", blue);
+    assert_close(converter.spot("Reflex Blue", 1.0).unwrap(), blue);
+    assert_close(
+        converter.spot("Reflex Blue", 0.0).unwrap(),
+        palette::css::WHITE,
+    );
+    let [r, _, b, _] = converter.spot("Reflex Blue", 0.5).unwrap().components;
+    assert!(r > 0.5 && b > 0.5);
+    assert!(converter.spot("Warm Red", 1.0).is_none());
+}
@@ -10,6 +10,7 @@
 //! This type of test is useful, as it avoids committing large snapshots to the repository, which are
 //! not handled very well by git.
 
+use catalina::PipelineStage;
 use catalina_tests::{compare_gpu_cpu_sync, encode_test_scene, TestParams};
 use scenes::{test_scenes, ExampleScene};
 
@@ -98,3 +99,13 @@ fn compare_blurred_rounded_rect() {
     let params = TestParams::new("compare_blurred_rounded_rect", 1200, 1200);
     compare_test_scene(test_scene, params);
 }
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn compare_splash_gpu_from_binning() {
+    let test_scene = test_scenes::splash_with_tiger();
+    let mut params = TestParams::new("compare_splash_gpu_from_binning", 600, 600);
+    params.gpu_stages_from = Some(PipelineStage::Binning);
+
+    compare_test_scene(test_scene, params);
+}
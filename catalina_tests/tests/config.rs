@@ -0,0 +1,60 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of reading [`catalina::CatalinaConfig`] from the environment.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use catalina::low_level::DebugLayers;
+use catalina::{AaSupport, CatalinaConfig, Error, PipelineStage};
+
+// The environment is shared by the whole process, so every case is in one test.
+#[test]
+fn from_env() {
+    let config = CatalinaConfig {
+        use_cpu: true,
+        frames_in_flight: NonZeroUsize::new(4),
+        ..Default::default()
+    };
+    std::env::set_var("CATALINA_ANTIALIASING", "area, MSAA16");
+    std::env::set_var("CATALINA_USE_CPU", "off");
+    std::env::set_var("CATALINA_GPU_STAGES_FROM", "path_tiling");
+    std::env::set_var("CATALINA_CACHE_DIR", "/tmp/catalina");
+    std::env::set_var("CATALINA_DEBUG_LAYERS", "bounding_boxes,validation");
+    let config = config.with_env().unwrap();
+    assert_eq!(
+        config.antialiasing,
+        Some(AaSupport {
+            area: true,
+            msaa8: false,
+            msaa16: true,
+        })
+    );
+    assert!(!config.use_cpu);
+    assert_eq!(config.gpu_stages_from, Some(PipelineStage::PathTiling));
+    assert_eq!(
+        config.cache_dir.as_deref(),
+        Some(Path::new("/tmp/catalina"))
+    );
+    assert_eq!(
+        config.debug_layers.bits(),
+        (DebugLayers::BOUNDING_BOXES | DebugLayers::VALIDATION).bits()
+    );
+    // Values which aren't in the environment are kept.
+    assert_eq!(config.frames_in_flight, NonZeroUsize::new(4));
+
+    std::env::set_var("CATALINA_FRAMES_IN_FLIGHT", "0");
+    assert!(matches!(
+        CatalinaConfig::from_env(),
+        Err(Error::InvalidConfig("CATALINA_FRAMES_IN_FLIGHT", value)) if value == "0"
+    ));
+}
@@ -0,0 +1,127 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of rendering coverage masks with [`Renderer::render_coverage_to_texture`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill, Gradient, Mix};
+use catalina::util::RenderContext;
+use catalina::wgpu::{self, Extent3d, TextureDescriptor, TextureFormat, TextureUsages};
+use catalina::{AaConfig, Error, RenderParams, Renderer, Scene};
+use catalina_tests::{create_renderer, read_texture, TestParams};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 32;
+
+/// The left half is covered by a gradient and a translucent color, neither of which affect
+/// coverage, and the right half by a layer with half opacity.
+fn scene() -> Scene {
+    let mut scene = Scene::new();
+    let gradient = Gradient::new_linear((0.0, 0.0), (32.0, 0.0))
+        .with_stops([palette::css::RED, palette::css::TRANSPARENT]);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &gradient,
+        None,
+        &Rect::new(0.0, 0.0, 32.0, 16.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE.with_alpha(0.25),
+        None,
+        &Rect::new(0.0, 16.0, 32.0, 32.0),
+    );
+    let right = Rect::new(32.0, 0.0, 64.0, 32.0);
+    scene.push_layer(Mix::Normal, 0.5, Affine::IDENTITY, &right);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::GREEN,
+        None,
+        &right,
+    );
+    scene.pop_layer();
+    scene
+}
+
+fn coverage_target(device: &wgpu::Device, format: TextureFormat) -> wgpu::Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some("Coverage target"),
+        size: Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+fn render_coverage(
+    context: &RenderContext,
+    device_id: usize,
+    renderer: &mut Renderer,
+    target: &wgpu::Texture,
+) -> Result<(), Error> {
+    let handle = &context.devices[device_id];
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: WIDTH,
+        height: HEIGHT,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    renderer.render_coverage_to_texture(&handle.device, &handle.queue, &scene(), target, &params)
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn coverage_ignores_brushes() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let device = &context.devices[device_id].device;
+    let mut renderer =
+        create_renderer(device, &TestParams::new("coverage", WIDTH, HEIGHT)).unwrap();
+    let target = coverage_target(device, TextureFormat::R8Unorm);
+    render_coverage(&context, device_id, &mut renderer, &target).unwrap();
+    let handle = &context.devices[device_id];
+    let mask = read_texture(&handle.device, &handle.queue, &target, 1).unwrap();
+    let at = |x: u32, y: u32| mask[(y * WIDTH + x) as usize];
+    // The gradient is transparent at its right edge, and the color is translucent.
+    assert_eq!(at(30, 8), 0xFF);
+    assert_eq!(at(8, 24), 0xFF);
+    assert!(at(48, 16).abs_diff(0x80) <= 1);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn coverage_rejects_color_targets() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let device = &context.devices[device_id].device;
+    let mut renderer =
+        create_renderer(device, &TestParams::new("coverage", WIDTH, HEIGHT)).unwrap();
+    let target = coverage_target(device, TextureFormat::Rgba8Unorm);
+    let result = render_coverage(&context, device_id, &mut renderer, &target);
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedCoverageFormat(TextureFormat::Rgba8Unorm))
+    ));
+}
@@ -0,0 +1,104 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of fills with paints registered by [`Renderer::register_custom_paint`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{render_to_pixels, RenderContext};
+use catalina::{AaConfig, CustomPaint, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 32;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// Fills with the color in the data of the paint.
+const SOLID: &str = "
+fn test_solid(xy: vec2<f32>, data: vec4<u32>) -> vec4<f32> {
+    return bitcast<vec4<f32>>(data);
+}
+";
+
+/// Fills the left half of each unit with the color in the data, in brush coordinates.
+const STRIPES: &str = "
+fn test_stripes(xy: vec2<f32>, data: vec4<u32>) -> vec4<f32> {
+    if fract(xy.x) < 0.5 {
+        return bitcast<vec4<f32>>(data);
+    }
+    return vec4(0.0);
+}
+";
+
+fn pixel(pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    pixels[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn custom_paints() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("custom_paint", SIZE, SIZE)).unwrap();
+    let solid = renderer
+        .register_custom_paint(&handle.device, "test_solid", SOLID)
+        .unwrap();
+    let stripes = renderer
+        .register_custom_paint(&handle.device, "test_stripes", STRIPES)
+        .unwrap();
+
+    let mut scene = Scene::new();
+    scene.fill_custom(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &CustomPaint::new(solid).with_floats([1.0, 0.0, 0.0, 1.0]),
+        None,
+        &Rect::new(0.0, 0.0, 16.0, 16.0),
+    );
+    // Stripes 8 pixels wide and 16 pixels apart, with one starting at x = 16.
+    scene.fill_custom(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &CustomPaint::new(stripes).with_floats([0.0, 0.0, 1.0, 1.0]),
+        Some(Affine::translate((16.0, 0.0)) * Affine::scale(16.0)),
+        &Rect::new(0.0, 16.0, 32.0, 32.0),
+    );
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: SIZE,
+        height: SIZE,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    let pixels = render_to_pixels(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    )
+    .unwrap();
+
+    assert_eq!(pixel(&pixels, 4, 4), RED);
+    assert_eq!(pixel(&pixels, 20, 4), WHITE);
+    assert_eq!(pixel(&pixels, 4, 20), BLUE);
+    assert_eq!(pixel(&pixels, 12, 20), WHITE);
+    assert_eq!(pixel(&pixels, 20, 20), BLUE);
+    assert_eq!(pixel(&pixels, 28, 20), WHITE);
+}
@@ -0,0 +1,214 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the regions passed to [`catalina::RenderParams::dirty_rects`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroUsize;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Color, Fill};
+use catalina::util::{read_texture, DeviceHandle, RenderContext};
+use catalina::wgpu::{self, Extent3d, TextureDescriptor, TextureFormat, TextureUsages};
+use catalina::{
+    AaConfig, AaSupport, DirtyRects, RenderParams, Renderer, RendererOptions, Scene,
+    MAX_DIRTY_RECTS,
+};
+
+const SIZE: u32 = 32;
+
+#[test]
+fn overlapping_rects_are_merged() {
+    let dirty: DirtyRects = [
+        Rect::new(0.0, 0.0, 10.0, 10.0),
+        Rect::new(100.0, 0.0, 110.0, 10.0),
+        Rect::new(5.0, 5.0, 20.0, 20.0),
+        Rect::new(50.0, 50.0, 50.0, 60.0),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        dirty.rects(),
+        [
+            Rect::new(100.0, 0.0, 110.0, 10.0),
+            Rect::new(0.0, 0.0, 20.0, 20.0),
+        ]
+    );
+}
+
+#[test]
+fn extra_rects_merge_with_nearest() {
+    let mut dirty = DirtyRects::new();
+    for i in 0..MAX_DIRTY_RECTS {
+        let x = 100.0 * i as f64;
+        dirty.add(Rect::new(x, 0.0, x + 10.0, 10.0));
+    }
+    dirty.add(Rect::new(12.0, 0.0, 14.0, 10.0));
+    assert_eq!(dirty.rects().len(), MAX_DIRTY_RECTS);
+    assert!(dirty.rects().contains(&Rect::new(0.0, 0.0, 14.0, 10.0)));
+}
+
+/// A scene filled with `color`.
+fn filled(color: Color) -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        color,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(SIZE), f64::from(SIZE)),
+    );
+    scene
+}
+
+fn params(dirty_rects: Option<DirtyRects>) -> RenderParams {
+    RenderParams {
+        base_color: palette::css::BLACK,
+        width: SIZE,
+        height: SIZE,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects,
+    }
+}
+
+/// A renderer for textures standing in for a surface, and such a texture.
+fn presented(handle: &DeviceHandle) -> (Renderer, wgpu::Texture) {
+    let renderer = Renderer::new(
+        &handle.device,
+        RendererOptions {
+            surface_format: Some(TextureFormat::Rgba8Unorm),
+            use_cpu: false,
+            num_init_threads: NonZeroUsize::new(1),
+            gpu_stages_from: None,
+            fine_band_rows: None,
+            frames_in_flight: None,
+            disk_cache: None,
+            small_scenes: None,
+            antialiasing_support: AaSupport::area_only(),
+        },
+    )
+    .unwrap();
+    let texture = handle.device.create_texture(&TextureDescriptor {
+        label: Some("Presented texture"),
+        size: Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    (renderer, texture)
+}
+
+/// Renders a frame which only updates the top left corner to green, and returns the pixels
+/// at the corner and away from it.
+fn render_dirty_corner(
+    handle: &DeviceHandle,
+    renderer: &mut Renderer,
+    texture: &wgpu::Texture,
+) -> ([u8; 4], [u8; 4]) {
+    let dirty = [Rect::new(0.0, 0.0, 8.0, 8.0)].into_iter().collect();
+    renderer
+        .render_to_presented_texture(
+            &handle.device,
+            &handle.queue,
+            &filled(palette::css::LIME),
+            texture,
+            &params(Some(dirty)),
+            true,
+        )
+        .unwrap();
+    let pixels = read_texture(&handle.device, &handle.queue, texture).unwrap();
+    let at = |x: u32, y: u32| {
+        let offset = ((y * SIZE + x) * 4) as usize;
+        pixels[offset..offset + 4].try_into().unwrap()
+    };
+    (at(2, 2), at(20, 20))
+}
+
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const LIME: [u8; 4] = [0, 255, 0, 255];
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn dirty_rects_keep_the_previous_frame() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (mut renderer, texture) = presented(handle);
+    renderer
+        .render_to_presented_texture(
+            &handle.device,
+            &handle.queue,
+            &filled(palette::css::BLUE),
+            &texture,
+            &params(None),
+            true,
+        )
+        .unwrap();
+    // Rendering coverage in between uses its own intermediate texture.
+    let mask = handle.device.create_texture(&TextureDescriptor {
+        label: Some("Coverage"),
+        size: Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TextureFormat::R8Unorm,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    renderer
+        .render_coverage_to_texture(
+            &handle.device,
+            &handle.queue,
+            &filled(palette::css::RED),
+            &mask,
+            &params(None),
+        )
+        .unwrap();
+    assert_eq!(
+        render_dirty_corner(handle, &mut renderer, &texture),
+        (LIME, BLUE)
+    );
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn first_frame_after_warmup_is_rendered_in_full() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (mut renderer, texture) = presented(handle);
+    renderer
+        .warmup(
+            &handle.device,
+            &handle.queue,
+            &filled(palette::css::RED),
+            &params(None),
+        )
+        .unwrap();
+    // The warmup scene isn't a previous frame, so none of it is kept.
+    assert_eq!(
+        render_dirty_corner(handle, &mut renderer, &texture),
+        (LIME, LIME)
+    );
+}
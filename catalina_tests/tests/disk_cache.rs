@@ -0,0 +1,80 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of keeping compiled pipelines between runs with [`catalina::DiskCache`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroUsize;
+
+use catalina::kurbo::{Affine, Circle};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{DiskCache, Renderer, RendererOptions, Scene};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+const SIZE: u32 = 32;
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn renders_with_saved_cache() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("disk_cache", SIZE, SIZE);
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Circle::new((16.0, 16.0), 12.0),
+    );
+    let mut uncached = create_renderer(&handle.device, &params).unwrap();
+    let expected = render_with(
+        &handle.device,
+        &handle.queue,
+        &mut uncached,
+        &scene,
+        &params,
+    )
+    .unwrap();
+
+    let dir = std::env::temp_dir().join(format!("catalina_disk_cache_{}", std::process::id()));
+    let cache = DiskCache::new(&dir, &handle.adapter().get_info());
+    // The first run creates the cache, and the second loads it.
+    for _ in 0..2 {
+        let mut renderer = Renderer::new(
+            &handle.device,
+            RendererOptions {
+                surface_format: None,
+                use_cpu: false,
+                num_init_threads: NonZeroUsize::new(1),
+                gpu_stages_from: None,
+                fine_band_rows: None,
+                frames_in_flight: None,
+                disk_cache: Some(cache.clone()),
+                small_scenes: None,
+                antialiasing_support: std::iter::once(params.anti_aliasing).collect(),
+            },
+        )
+        .unwrap();
+        let image = render_with(
+            &handle.device,
+            &handle.queue,
+            &mut renderer,
+            &scene,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(image.data.data(), expected.data.data());
+        renderer.save_disk_cache().unwrap();
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,143 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of downscaling textures with [`Renderer::downscale_texture`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{create_target_texture, read_texture, DeviceHandle, RenderContext};
+use catalina::wgpu;
+use catalina::{AaConfig, DownscaleFilter, Error, RenderParams, Renderer, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SOURCE_SIZE: u32 = 64;
+const TARGET_SIZE: u32 = 8;
+
+/// Columns of alternating red and green pixels, which alias when sampled bilinearly at a
+/// large reduction.
+fn stripes() -> Scene {
+    let mut scene = Scene::new();
+    for x in 0..SOURCE_SIZE {
+        let color = if x % 2 == 0 {
+            palette::css::RED
+        } else {
+            palette::css::LIME
+        };
+        let x = f64::from(x);
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            color,
+            None,
+            &Rect::new(x, 0.0, x + 1.0, f64::from(SOURCE_SIZE)),
+        );
+    }
+    scene
+}
+
+fn downscale_stripes(renderer: &mut Renderer, handle: &DeviceHandle, filter: DownscaleFilter) {
+    let source = create_target_texture(&handle.device, SOURCE_SIZE, SOURCE_SIZE);
+    let view = source.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer
+        .render_to_texture(
+            &handle.device,
+            &handle.queue,
+            &stripes(),
+            &view,
+            &RenderParams {
+                base_color: palette::css::TRANSPARENT,
+                width: SOURCE_SIZE,
+                height: SOURCE_SIZE,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+        )
+        .unwrap();
+    let target = create_target_texture(&handle.device, TARGET_SIZE, TARGET_SIZE);
+    renderer
+        .downscale_texture(&handle.device, &handle.queue, &source, &target, filter)
+        .unwrap();
+    let pixels = read_texture(&handle.device, &handle.queue, &target).unwrap();
+
+    // Half red and half green in linear light is 0.5 in each channel, which is 188 once sRGB
+    // encoded, rather than the 128 of averaging the encoded values. Lanczos is truncated at
+    // the edges, which leaves a little of the stripes in the outer pixels.
+    let tolerance = match filter {
+        DownscaleFilter::Area => 2,
+        DownscaleFilter::Lanczos3 => 6,
+    };
+    for pixel in pixels.chunks_exact(4) {
+        assert!(pixel[0].abs_diff(188) <= tolerance, "{filter:?}: {pixel:?}");
+        assert!(pixel[1].abs_diff(188) <= tolerance, "{filter:?}: {pixel:?}");
+        assert!(pixel[2] <= 1, "{filter:?}: {pixel:?}");
+        assert_eq!(pixel[3], 255, "{filter:?}: {pixel:?}");
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn fine_detail_averages_in_linear_light() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer = create_renderer(
+        &handle.device,
+        &TestParams::new("downscale", SOURCE_SIZE, SOURCE_SIZE),
+    )
+    .unwrap();
+    for filter in [DownscaleFilter::Area, DownscaleFilter::Lanczos3] {
+        downscale_stripes(&mut renderer, handle, filter);
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn rejects_other_formats() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer = create_renderer(
+        &handle.device,
+        &TestParams::new("downscale", SOURCE_SIZE, SOURCE_SIZE),
+    )
+    .unwrap();
+    let source = create_target_texture(&handle.device, SOURCE_SIZE, SOURCE_SIZE);
+    let target = handle.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: TARGET_SIZE,
+            height: TARGET_SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba16Float,
+        usage: wgpu::TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    });
+    let result = renderer.downscale_texture(
+        &handle.device,
+        &handle.queue,
+        &source,
+        &target,
+        DownscaleFilter::Area,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedDownscaleFormat(
+            wgpu::TextureFormat::Rgba16Float
+        ))
+    ));
+}
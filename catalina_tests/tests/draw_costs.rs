@@ -0,0 +1,100 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of measuring the cost of each draw object with
+//! [`Renderer::render_to_texture_with_costs`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill, Gradient, Mix};
+use catalina::util::RenderContext;
+use catalina::wgpu::{self, Extent3d, TextureDescriptor, TextureFormat, TextureUsages};
+use catalina::{AaConfig, DrawKind, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 64;
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn costs_per_draw() {
+    let mut scene = Scene::new();
+    // A background covering the whole target.
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(SIZE), f64::from(SIZE)),
+    );
+    // A widget made of a layer containing a gradient.
+    let widget_start = scene.encoding().draw_tags.len();
+    let bounds = Rect::new(16.0, 16.0, 32.0, 32.0);
+    scene.push_layer(Mix::Normal, 0.5, Affine::IDENTITY, &bounds);
+    let gradient = Gradient::new_linear((16.0, 0.0), (32.0, 0.0))
+        .with_stops([palette::css::RED, palette::css::BLUE]);
+    scene.fill(Fill::NonZero, Affine::IDENTITY, &gradient, None, &bounds);
+    scene.pop_layer();
+    let widget_end = scene.encoding().draw_tags.len();
+
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("draw_costs", SIZE, SIZE)).unwrap();
+    let texture = handle.device.create_texture(&TextureDescriptor {
+        label: Some("Target texture"),
+        size: Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let params = RenderParams {
+        base_color: palette::css::BLACK,
+        width: SIZE,
+        height: SIZE,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    let costs = renderer
+        .render_to_texture_with_costs(&handle.device, &handle.queue, &scene, &view, &params)
+        .unwrap();
+
+    assert_eq!(costs.len(), 4);
+    let kinds: Vec<_> = costs.iter().map(|(kind, _)| kind.unwrap()).collect();
+    assert_eq!(
+        kinds,
+        [
+            DrawKind::Color,
+            DrawKind::BeginLayer,
+            DrawKind::LinearGradient,
+            DrawKind::EndLayer
+        ]
+    );
+    let pixels: Vec<_> = costs.iter().map(|(_, cost)| cost.pixels).collect();
+    assert_eq!(pixels, [u64::from(SIZE * SIZE), 0, 256, 256]);
+    assert_eq!(costs.most_expensive(1), [0]);
+    let widget = costs.range(widget_start..widget_end);
+    assert_eq!(widget.pixels, 512);
+    assert!(widget.commands > 0);
+    assert_eq!(costs.total().pixels, widget.pixels + u64::from(SIZE * SIZE));
+    let by_kind = costs.by_kind();
+    assert_eq!(by_kind[0], (DrawKind::Color, costs.get(0).unwrap().1));
+}
@@ -11,6 +11,7 @@
     clippy::allow_attributes_without_reason
 )]
 
+use catalina::unstable::encoding::Patch;
 use catalina::{kurbo::Affine, peniko::Fill, Scene};
 use catalina_tests::{snapshot_test_sync, TestParams};
 use scenes::SimpleText;
@@ -140,3 +141,45 @@ fn bitmap_undef() {
         .unwrap()
         .assert_mean_less_than(0.001);
 }
+
+fn bitmap_image_ids(scene: &Scene) -> Vec<u64> {
+    scene
+        .encoding()
+        .resources
+        .patches
+        .iter()
+        .filter_map(|patch| match patch {
+            Patch::Image { image, .. } => Some(image.data.id()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn bitmaps_are_kept_while_drawn_in_each_frame() {
+    let mut scene = Scene::new();
+    let mut simple_text = SimpleText::new();
+    let mut draw = |target: &mut Scene| {
+        simple_text.add_bitmap_emoji_run(
+            target,
+            10.,
+            Affine::IDENTITY,
+            None,
+            Fill::EvenOdd,
+            "✅✅",
+        );
+    };
+    draw(&mut scene);
+    let first = bitmap_image_ids(&scene);
+    assert_eq!(first.len(), 2);
+    // Both occurrences share one image.
+    assert_eq!(first[0], first[1]);
+    scene.reset();
+    draw(&mut scene);
+    assert_eq!(bitmap_image_ids(&scene), first);
+    // A frame without the emoji frees its image, so it's decoded again.
+    scene.reset();
+    scene.reset();
+    draw(&mut scene);
+    assert_ne!(bitmap_image_ids(&scene)[0], first[0]);
+}
@@ -0,0 +1,193 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the C API in `catalina_ffi`.
+
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::ptr;
+
+use catalina_ffi::*;
+
+const RED: CatalinaColor = CatalinaColor {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+const TRANSPARENT: CatalinaColor = CatalinaColor {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.0,
+};
+
+/// A closed square path from `min` to `max`.
+fn square(min: f64, max: f64) -> *mut CatalinaPath {
+    let path = catalina_path_new();
+    // Safety: `path` is a valid handle.
+    unsafe {
+        assert_eq!(catalina_path_move_to(path, min, min), CatalinaStatus::Ok);
+        assert_eq!(catalina_path_line_to(path, max, min), CatalinaStatus::Ok);
+        assert_eq!(catalina_path_line_to(path, max, max), CatalinaStatus::Ok);
+        assert_eq!(catalina_path_line_to(path, min, max), CatalinaStatus::Ok);
+        assert_eq!(catalina_path_close(path), CatalinaStatus::Ok);
+    }
+    path
+}
+
+#[test]
+fn null_handles_are_rejected() {
+    let path = square(0.0, 1.0);
+    // Safety: Every handle is null or valid.
+    unsafe {
+        let rule = CatalinaFillRule::NonZero as u32;
+        assert_eq!(
+            catalina_scene_reset(ptr::null_mut()),
+            CatalinaStatus::NullPointer
+        );
+        assert_eq!(
+            catalina_path_line_to(ptr::null_mut(), 0.0, 0.0),
+            CatalinaStatus::NullPointer
+        );
+        assert_eq!(
+            catalina_scene_fill_path(ptr::null_mut(), path, rule, ptr::null(), RED),
+            CatalinaStatus::NullPointer
+        );
+        assert_eq!(
+            catalina_scene_pop_layer(ptr::null_mut()),
+            CatalinaStatus::NullPointer
+        );
+        assert_eq!(
+            catalina_renderer_new(ptr::null_mut()),
+            CatalinaStatus::NullPointer
+        );
+        assert!(catalina_font_new(ptr::null(), 16, 0).is_null());
+        // Releasing null handles does nothing.
+        catalina_scene_free(ptr::null_mut());
+        catalina_path_free(ptr::null_mut());
+        catalina_font_free(ptr::null_mut());
+        catalina_renderer_free(ptr::null_mut());
+        catalina_path_free(path);
+    }
+}
+
+#[test]
+fn invalid_arguments_are_rejected() {
+    let scene = catalina_scene_new();
+    let path = square(0.0, 1.0);
+    // Safety: `scene` and `path` are valid handles.
+    unsafe {
+        assert_eq!(
+            catalina_scene_fill_path(scene, path, 2, ptr::null(), RED),
+            CatalinaStatus::InvalidArgument
+        );
+        assert_eq!(
+            catalina_scene_fill_path_linear_gradient(
+                scene,
+                path,
+                u32::MAX,
+                ptr::null(),
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                ptr::null(),
+                0,
+            ),
+            CatalinaStatus::InvalidArgument
+        );
+        for width in [-1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                catalina_scene_stroke_path(scene, path, width, ptr::null(), RED),
+                CatalinaStatus::InvalidArgument
+            );
+        }
+        assert_eq!(
+            catalina_scene_push_layer(scene, 200, 1.0, ptr::null(), path),
+            CatalinaStatus::InvalidArgument
+        );
+        assert_eq!(
+            catalina_scene_fill_path(
+                scene,
+                path,
+                CatalinaFillRule::EvenOdd as u32,
+                ptr::null(),
+                RED
+            ),
+            CatalinaStatus::Ok
+        );
+        catalina_path_free(path);
+        catalina_scene_free(scene);
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn render_to_pixels() {
+    let scene = catalina_scene_new();
+    let path = square(4.0, 12.0);
+    let mut renderer = ptr::null_mut();
+    // Safety: `scene`, `path` and `renderer` are valid handles, and the pixel buffers are
+    // valid for writes of the given lengths.
+    unsafe {
+        assert_eq!(
+            catalina_scene_fill_path(
+                scene,
+                path,
+                CatalinaFillRule::NonZero as u32,
+                ptr::null(),
+                RED
+            ),
+            CatalinaStatus::Ok
+        );
+        assert_eq!(catalina_renderer_new(&mut renderer), CatalinaStatus::Ok);
+        let mut pixels = vec![0_u8; 16 * 16 * 4];
+        assert_eq!(
+            catalina_renderer_render_to_pixels(
+                renderer,
+                scene,
+                16,
+                16,
+                TRANSPARENT,
+                pixels.as_mut_ptr(),
+                pixels.len() - 1,
+            ),
+            CatalinaStatus::BufferTooSmall
+        );
+        assert_eq!(
+            catalina_renderer_render_to_pixels(
+                renderer,
+                scene,
+                0,
+                16,
+                TRANSPARENT,
+                pixels.as_mut_ptr(),
+                pixels.len(),
+            ),
+            CatalinaStatus::InvalidArgument
+        );
+        assert_eq!(
+            catalina_renderer_render_to_pixels(
+                renderer,
+                scene,
+                16,
+                16,
+                TRANSPARENT,
+                pixels.as_mut_ptr(),
+                pixels.len(),
+            ),
+            CatalinaStatus::Ok
+        );
+        let pixel = |x: usize, y: usize| &pixels[(y * 16 + x) * 4..][..4];
+        assert_eq!(pixel(8, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(1, 1), [0, 0, 0, 0]);
+        catalina_renderer_free(renderer);
+        catalina_path_free(path);
+        catalina_scene_free(scene);
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of winding number accumulation for self-intersecting and overlapping paths, under
+//! both fill rules and every antialiasing method.
+//!
+//! The shapes are made of pixel aligned edges, so the expected coverage of every pixel is
+//! exactly known.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, BezPath, Rect};
+use catalina::peniko::{color::palette, Fill, ImageFormat};
+use catalina::{AaConfig, Scene};
+use catalina_tests::{compare_gpu_cpu_sync, TestParams};
+
+const SIZE: u32 = 100;
+
+/// Adds a closed rectangle to `path`, clockwise in screen space if `clockwise` is set.
+fn rect(path: &mut BezPath, rect: Rect, clockwise: bool) {
+    path.move_to((rect.x0, rect.y0));
+    if clockwise {
+        path.line_to((rect.x1, rect.y0));
+        path.line_to((rect.x1, rect.y1));
+        path.line_to((rect.x0, rect.y1));
+    } else {
+        path.line_to((rect.x0, rect.y1));
+        path.line_to((rect.x1, rect.y1));
+        path.line_to((rect.x1, rect.y0));
+    }
+    path.close_path();
+}
+
+/// Renders `path` in white on black, and checks that exactly the pixels whose centers satisfy
+/// `inside` are covered.
+fn check_coverage(
+    name: &str,
+    path: &BezPath,
+    fill: Fill,
+    aa: AaConfig,
+    use_cpu: bool,
+    inside: impl Fn(f64, f64) -> bool,
+) {
+    let mut scene = Scene::new();
+    scene.fill(fill, Affine::IDENTITY, palette::css::WHITE, None, path);
+    let params = TestParams {
+        use_cpu,
+        anti_aliasing: aa,
+        base_color: Some(palette::css::BLACK),
+        ..TestParams::new(name, SIZE, SIZE)
+    };
+    let image = catalina_tests::render_then_debug_sync(&scene, &params).unwrap();
+    assert_eq!(image.format, ImageFormat::Rgba8);
+    for (ix, pixel) in image.data.data().chunks_exact(4).enumerate() {
+        let x = (ix % SIZE as usize) as f64 + 0.5;
+        let y = (ix / SIZE as usize) as f64 + 0.5;
+        let expected = if inside(x, y) { 255 } else { 0 };
+        assert_eq!(
+            pixel[0], expected,
+            "{name} ({fill:?}, {aa:?}): wrong coverage at ({x}, {y})"
+        );
+    }
+}
+
+const A: Rect = Rect::new(10.0, 10.0, 60.0, 60.0);
+const B: Rect = Rect::new(40.0, 40.0, 90.0, 90.0);
+
+fn contains(rect: Rect, x: f64, y: f64) -> bool {
+    rect.contains((x, y).into())
+}
+
+/// Two overlapping subpaths with the same orientation, so the overlap has a winding of 2.
+fn overlapping_same_direction(aa: AaConfig, use_cpu: bool) {
+    let mut path = BezPath::new();
+    rect(&mut path, A, true);
+    rect(&mut path, B, true);
+    let name = "fill_rules_overlapping_same_direction";
+    check_coverage(name, &path, Fill::NonZero, aa, use_cpu, |x, y| {
+        contains(A, x, y) || contains(B, x, y)
+    });
+    check_coverage(name, &path, Fill::EvenOdd, aa, use_cpu, |x, y| {
+        contains(A, x, y) != contains(B, x, y)
+    });
+}
+
+/// Two overlapping subpaths with opposite orientations, so the overlap has a winding of 0.
+fn overlapping_opposite_direction(aa: AaConfig, use_cpu: bool) {
+    let mut path = BezPath::new();
+    rect(&mut path, A, true);
+    rect(&mut path, B, false);
+    let name = "fill_rules_overlapping_opposite_direction";
+    for fill in [Fill::NonZero, Fill::EvenOdd] {
+        check_coverage(name, &path, fill, aa, use_cpu, |x, y| {
+            contains(A, x, y) != contains(B, x, y)
+        });
+    }
+}
+
+/// A single contour which loops around twice, crossing itself, like a figure-eight whose
+/// loops overlap.
+fn figure_eight(aa: AaConfig, use_cpu: bool) {
+    let mut path = BezPath::new();
+    path.move_to((10.0, 10.0));
+    path.line_to((60.0, 10.0));
+    path.line_to((60.0, 60.0));
+    path.line_to((40.0, 60.0));
+    path.line_to((40.0, 40.0));
+    path.line_to((90.0, 40.0));
+    path.line_to((90.0, 90.0));
+    path.line_to((40.0, 90.0));
+    path.line_to((40.0, 60.0));
+    path.line_to((10.0, 60.0));
+    path.close_path();
+    let name = "fill_rules_figure_eight";
+    check_coverage(name, &path, Fill::NonZero, aa, use_cpu, |x, y| {
+        contains(A, x, y) || contains(B, x, y)
+    });
+    check_coverage(name, &path, Fill::EvenOdd, aa, use_cpu, |x, y| {
+        contains(A, x, y) != contains(B, x, y)
+    });
+}
+
+/// Nested squares with the same orientation, like a square spiral, giving winding numbers
+/// from 1 up to 4 towards the center.
+fn nested_spiral(aa: AaConfig, use_cpu: bool) {
+    let squares = [10.0, 20.0, 30.0, 40.0].map(|inset| {
+        Rect::new(
+            inset,
+            inset,
+            f64::from(SIZE) - inset,
+            f64::from(SIZE) - inset,
+        )
+    });
+    let mut path = BezPath::new();
+    for square in squares {
+        rect(&mut path, square, true);
+    }
+    let winding = |x, y| squares.iter().filter(|s| contains(**s, x, y)).count();
+    let name = "fill_rules_nested_spiral";
+    check_coverage(name, &path, Fill::NonZero, aa, use_cpu, |x, y| {
+        winding(x, y) != 0
+    });
+    check_coverage(name, &path, Fill::EvenOdd, aa, use_cpu, |x, y| {
+        winding(x, y) % 2 == 1
+    });
+}
+
+/// Many coincident subpaths, giving a winding number beyond what fits in a byte.
+fn high_winding(aa: AaConfig, use_cpu: bool) {
+    let mut path = BezPath::new();
+    for _ in 0..300 {
+        rect(&mut path, A, true);
+    }
+    let name = "fill_rules_high_winding";
+    check_coverage(name, &path, Fill::NonZero, aa, use_cpu, |x, y| {
+        contains(A, x, y)
+    });
+    check_coverage(name, &path, Fill::EvenOdd, aa, use_cpu, |_, _| false);
+}
+
+macro_rules! fill_rule_tests {
+    ($($case:ident: $($test:ident($aa:expr, $use_cpu:expr)),*;)*) => {
+        $($(
+            #[test]
+            #[cfg_attr(skip_gpu_tests, ignore)]
+            fn $test() {
+                $case($aa, $use_cpu);
+            }
+        )*)*
+    };
+}
+
+fill_rule_tests! {
+    overlapping_same_direction:
+        overlapping_same_direction_area_gpu(AaConfig::Area, false),
+        overlapping_same_direction_area_cpu(AaConfig::Area, true),
+        overlapping_same_direction_msaa8_gpu(AaConfig::Msaa8, false),
+        overlapping_same_direction_msaa16_gpu(AaConfig::Msaa16, false);
+    overlapping_opposite_direction:
+        overlapping_opposite_direction_area_gpu(AaConfig::Area, false),
+        overlapping_opposite_direction_area_cpu(AaConfig::Area, true),
+        overlapping_opposite_direction_msaa8_gpu(AaConfig::Msaa8, false),
+        overlapping_opposite_direction_msaa16_gpu(AaConfig::Msaa16, false);
+    figure_eight:
+        figure_eight_area_gpu(AaConfig::Area, false),
+        figure_eight_area_cpu(AaConfig::Area, true),
+        figure_eight_msaa8_gpu(AaConfig::Msaa8, false),
+        figure_eight_msaa16_gpu(AaConfig::Msaa16, false);
+    nested_spiral:
+        nested_spiral_area_gpu(AaConfig::Area, false),
+        nested_spiral_area_cpu(AaConfig::Area, true),
+        nested_spiral_msaa8_gpu(AaConfig::Msaa8, false),
+        nested_spiral_msaa16_gpu(AaConfig::Msaa16, false);
+    high_winding:
+        high_winding_area_gpu(AaConfig::Area, false),
+        high_winding_area_cpu(AaConfig::Area, true),
+        high_winding_msaa8_gpu(AaConfig::Msaa8, false),
+        high_winding_msaa16_gpu(AaConfig::Msaa16, false);
+}
+
+/// A curved spiral crossing itself many times, where the GPU must match the CPU reference.
+fn curved_spiral(fill: Fill, aa: AaConfig, name: &str) {
+    let mut path = BezPath::new();
+    let center = (50.0, 50.0);
+    let turns = 5.0;
+    let steps = 200;
+    for i in 0..=steps {
+        let t = f64::from(i) / f64::from(steps);
+        let angle = t * turns * std::f64::consts::TAU;
+        let radius = 5.0 + 40.0 * t;
+        let point = (
+            center.0 + radius * angle.cos(),
+            center.1 + radius * angle.sin(),
+        );
+        if i == 0 {
+            path.move_to(point);
+        } else {
+            path.line_to(point);
+        }
+    }
+    // Closing the spiral makes the closing segment cross every turn.
+    path.close_path();
+    let mut scene = Scene::new();
+    scene.fill(fill, Affine::IDENTITY, palette::css::WHITE, None, &path);
+    let params = TestParams {
+        anti_aliasing: aa,
+        ..TestParams::new(name, SIZE, SIZE)
+    };
+    compare_gpu_cpu_sync(scene, params)
+        .unwrap()
+        .assert_mean_less_than(0.001);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn curved_spiral_nonzero() {
+    curved_spiral(
+        Fill::NonZero,
+        AaConfig::Area,
+        "fill_rules_curved_spiral_nonzero",
+    );
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn curved_spiral_even_odd() {
+    curved_spiral(
+        Fill::EvenOdd,
+        AaConfig::Area,
+        "fill_rules_curved_spiral_even_odd",
+    );
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn curved_spiral_nonzero_msaa16() {
+    curved_spiral(
+        Fill::NonZero,
+        AaConfig::Msaa16,
+        "fill_rules_curved_spiral_nonzero_msaa16",
+    );
+}
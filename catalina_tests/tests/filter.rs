@@ -0,0 +1,297 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of layers with filters, pushed with [`Scene::push_layer_with_filter`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill, Mix};
+use catalina::util::{render_to_pixels, RenderContext};
+use catalina::{
+    AaConfig, Filter, FilterGraph, FilterInput, LightSource, Lighting, RenderParams, Scene,
+    Turbulence,
+};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 32;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// Fills `rect` with red in a layer with `filter`, over a white background.
+fn render_filtered(transform: Affine, rect: Rect, filter: impl Into<FilterGraph>) -> Vec<u8> {
+    render_filtered_with_glow(transform, rect, filter, 1.0)
+}
+
+/// Like [`render_filtered`], with the given [`RenderParams::glow_amount`].
+fn render_filtered_with_glow(
+    transform: Affine,
+    rect: Rect,
+    filter: impl Into<FilterGraph>,
+    glow_amount: f32,
+) -> Vec<u8> {
+    let mut scene = Scene::new();
+    scene.push_layer_with_filter(Mix::Normal, 1.0, transform, &rect, filter);
+    scene.fill(Fill::NonZero, transform, palette::css::RED, None, &rect);
+    scene.pop_layer();
+    render_scene(&scene, glow_amount)
+}
+
+/// Renders `scene` over a white background, with the given [`RenderParams::glow_amount`].
+fn render_scene(scene: &Scene, glow_amount: f32) -> Vec<u8> {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("filter", SIZE, SIZE)).unwrap();
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: SIZE,
+        height: SIZE,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount,
+        dirty_rects: None,
+    };
+    render_to_pixels(&mut renderer, &handle.device, &handle.queue, scene, &params).unwrap()
+}
+
+fn pixel(pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    pixels[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn blur_spreads_edges() {
+    let pixels = render_filtered(
+        Affine::IDENTITY,
+        Rect::new(8.0, 8.0, 24.0, 24.0),
+        Filter::blur(2.0),
+    );
+    assert_eq!(pixel(&pixels, 16, 16), RED);
+    // The edge is a mix of red and the background.
+    let [r, g, b, _] = pixel(&pixels, 8, 16);
+    assert_eq!(r, 255);
+    assert!((60..200).contains(&g) && g == b, "{g}, {b}");
+    // Just outside the edge is tinted, but beyond the extent of the filter isn't.
+    assert_ne!(pixel(&pixels, 6, 16), WHITE);
+    assert_eq!(pixel(&pixels, 1, 16), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn drop_shadow_in_layer_coordinates() {
+    // The offset is doubled by the transform, so the shadow covers (12, 12) to (20, 20).
+    let pixels = render_filtered(
+        Affine::scale(2.0),
+        Rect::new(2.0, 2.0, 6.0, 6.0),
+        Filter::drop_shadow((4.0, 4.0), 0.1, palette::css::BLACK),
+    );
+    assert_eq!(pixel(&pixels, 8, 8), RED);
+    assert_eq!(pixel(&pixels, 11, 11), RED);
+    assert_eq!(pixel(&pixels, 14, 14), BLACK);
+    assert_eq!(pixel(&pixels, 18, 18), BLACK);
+    assert_eq!(pixel(&pixels, 22, 22), WHITE);
+    assert_eq!(pixel(&pixels, 18, 4), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn directional_blur_spreads_along_offset() {
+    let pixels = render_filtered(
+        Affine::IDENTITY,
+        Rect::new(12.0, 8.0, 20.0, 24.0),
+        Filter::directional_blur((8.0, 0.0)),
+    );
+    assert_ne!(pixel(&pixels, 10, 16), WHITE);
+    assert_ne!(pixel(&pixels, 21, 16), WHITE);
+    assert_eq!(pixel(&pixels, 25, 16), WHITE);
+    // The blur is only horizontal.
+    assert_eq!(pixel(&pixels, 16, 4), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn radial_blur_spreads_away_from_center() {
+    let pixels = render_filtered(
+        Affine::IDENTITY,
+        Rect::new(4.0, 14.0, 8.0, 18.0),
+        Filter::radial_blur((16.0, 16.0), 0.5),
+    );
+    assert_ne!(pixel(&pixels, 2, 16), WHITE);
+    assert_eq!(pixel(&pixels, 11, 16), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn turbulence_fills_clip() {
+    let pixels = render_filtered(
+        Affine::IDENTITY,
+        Rect::new(8.0, 8.0, 24.0, 24.0),
+        Filter::turbulence(Turbulence::new((0.1, 0.1), 2).with_fractal(true)),
+    );
+    // The noise replaces the content, and varies across the clip.
+    let inside: Vec<_> = (8..24).map(|x| pixel(&pixels, x, 16)).collect();
+    assert!(inside.iter().all(|&p| p != RED));
+    assert!(inside.iter().any(|&p| p != inside[0]));
+    assert_eq!(pixel(&pixels, 4, 16), WHITE);
+    assert_eq!(pixel(&pixels, 16, 28), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn displacement_map_moves_content() {
+    let pixels = render_filtered(
+        Affine::IDENTITY,
+        Rect::new(8.0, 8.0, 24.0, 24.0),
+        Filter::displacement_map(8.0, Turbulence::new((0.2, 0.2), 1)),
+    );
+    // Points move by at most 4 pixels along each axis.
+    assert_eq!(pixel(&pixels, 16, 16), RED);
+    assert_eq!(pixel(&pixels, 2, 16), WHITE);
+    assert_eq!(pixel(&pixels, 29, 16), WHITE);
+    assert!((8..24).any(|y| pixel(&pixels, 25, y) != WHITE));
+    assert!((8..24).any(|y| pixel(&pixels, 8, y) != RED));
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn dilate_and_erode() {
+    let rect = Rect::new(8.0, 8.0, 24.0, 24.0);
+    let dilated = render_filtered(Affine::IDENTITY, rect, Filter::dilate((2.0, 0.0)));
+    // Only expanded horizontally.
+    assert_eq!(pixel(&dilated, 6, 16), RED);
+    assert_eq!(pixel(&dilated, 25, 16), RED);
+    assert_eq!(pixel(&dilated, 4, 16), WHITE);
+    assert_eq!(pixel(&dilated, 16, 6), WHITE);
+
+    let eroded = render_filtered(Affine::IDENTITY, rect, Filter::erode((2.0, 2.0)));
+    assert_eq!(pixel(&eroded, 16, 16), RED);
+    assert_eq!(pixel(&eroded, 9, 16), WHITE);
+    assert_eq!(pixel(&eroded, 16, 22), WHITE);
+    assert_eq!(pixel(&eroded, 10, 10), RED);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn filter_graph_shadow() {
+    // An offset shadow of the alpha of the content, under the content.
+    let mut graph = FilterGraph::new();
+    let shadow = graph.offset(FilterInput::SourceAlpha, (4.0, 4.0));
+    graph.merge([shadow, FilterInput::Source]);
+    let pixels = render_filtered(Affine::scale(2.0), Rect::new(2.0, 2.0, 6.0, 6.0), graph);
+    assert_eq!(pixel(&pixels, 8, 8), RED);
+    assert_eq!(pixel(&pixels, 11, 11), RED);
+    assert_eq!(pixel(&pixels, 14, 14), BLACK);
+    assert_eq!(pixel(&pixels, 18, 18), BLACK);
+    assert_eq!(pixel(&pixels, 22, 22), WHITE);
+    assert_eq!(pixel(&pixels, 18, 4), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn filter_graph_reuses_images() {
+    // A long chain only needs a few images at once.
+    let mut graph = FilterGraph::new();
+    let mut input = FilterInput::Source;
+    for _ in 0..10 {
+        input = graph.offset(input, (1.0, 0.0));
+    }
+    let pixels = render_filtered(Affine::IDENTITY, Rect::new(8.0, 8.0, 16.0, 16.0), graph);
+    assert_eq!(pixel(&pixels, 20, 12), RED);
+    assert_eq!(pixel(&pixels, 12, 12), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn glow_spreads_bright_content() {
+    let rect = Rect::new(8.0, 8.0, 24.0, 24.0);
+    let glowing = render_filtered(Affine::IDENTITY, rect, Filter::glow(2.0, 0.5, 1.0));
+    assert_eq!(pixel(&glowing, 16, 16), RED);
+    let [r, g, b, _] = pixel(&glowing, 6, 16);
+    assert!(r == 255 && g < 255 && g == b, "{r}, {g}, {b}");
+    assert_eq!(pixel(&glowing, 1, 16), WHITE);
+    // Content which isn't brighter than the threshold doesn't glow.
+    let dim = render_filtered(Affine::IDENTITY, rect, Filter::glow(2.0, 1.0, 1.0));
+    assert_eq!(pixel(&dim, 6, 16), WHITE);
+    // Glows fade out with the render parameters.
+    let faded = render_filtered_with_glow(Affine::IDENTITY, rect, Filter::glow(2.0, 0.5, 1.0), 0.0);
+    assert_eq!(pixel(&faded, 16, 16), RED);
+    assert_eq!(pixel(&faded, 6, 16), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn inner_shadow_stays_inside_content() {
+    let rect = Rect::new(8.0, 8.0, 24.0, 24.0);
+    let filter = Filter::inner_shadow((4.0, 4.0), 0.1, palette::css::BLACK);
+    let pixels = render_filtered(Affine::IDENTITY, rect, filter);
+    // The shadow is cast from the top and left edges.
+    assert_eq!(pixel(&pixels, 9, 16), BLACK);
+    assert_eq!(pixel(&pixels, 16, 9), BLACK);
+    assert_eq!(pixel(&pixels, 16, 16), RED);
+    assert_eq!(pixel(&pixels, 22, 22), RED);
+    assert_eq!(pixel(&pixels, 4, 16), WHITE);
+    assert_eq!(pixel(&pixels, 16, 26), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn lighting_bevels_edges() {
+    // A light from the left, so the left edge faces it and the right edge faces away.
+    let light = LightSource::Distant {
+        azimuth: std::f64::consts::PI,
+        elevation: std::f64::consts::FRAC_PI_4,
+    };
+    let filter = Filter::lighting(Lighting::new(light).with_surface_scale(2.0));
+    let pixels = render_filtered(Affine::IDENTITY, Rect::new(8.0, 8.0, 24.0, 24.0), filter);
+    let [left, center, right] = [8, 16, 23].map(|x| pixel(&pixels, x, 16));
+    assert!(
+        left[0] > center[0] && center[0] > right[0],
+        "{left:?} {center:?} {right:?}"
+    );
+    assert_eq!(center[1..3], [0, 0]);
+    assert_eq!(pixel(&pixels, 4, 16), WHITE);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn reduced_raster_scale_blurs_alike() {
+    let rect = Rect::new(8.0, 8.0, 24.0, 24.0);
+    let mut scene = Scene::new();
+    scene.push_layer_with_filter_at_scale(
+        Mix::Normal,
+        1.0,
+        Affine::IDENTITY,
+        &rect,
+        Filter::blur(4.0),
+        0.25,
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &rect,
+    );
+    scene.pop_layer();
+    let scaled = render_scene(&scene, 1.0);
+    let full = render_filtered(Affine::IDENTITY, rect, Filter::blur(4.0));
+    // The result is close to blurring at full resolution, across the edge of the content.
+    for x in [2, 6, 8, 10, 16] {
+        let [r, g, _, _] = pixel(&scaled, x, 16);
+        let [full_r, full_g, _, _] = pixel(&full, x, 16);
+        assert!(r.abs_diff(full_r) <= 24, "{x}: {r} vs {full_r}");
+        assert!(g.abs_diff(full_g) <= 24, "{x}: {g} vs {full_g}");
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests that scenes assembled from pre-resolved fragments render the same as scenes assembled
+//! with [`Scene::append`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill, Gradient};
+use catalina::{FragmentResolver, Scene};
+use catalina_tests::TestParams;
+use scenes::SimpleText;
+
+const WIDTH: u32 = 200;
+const HEIGHT: u32 = 120;
+
+/// A tile with text, a glyph transform and gradients, as a streaming renderer might receive.
+fn tile(text: &mut SimpleText, label: &str) -> Scene {
+    let mut scene = Scene::new();
+    let gradient = Gradient::new_linear((0.0, 0.0), (90.0, 0.0))
+        .with_stops([palette::css::NAVY, palette::css::TEAL]);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &gradient,
+        None,
+        &Rect::new(0.0, 0.0, 90.0, 50.0),
+    );
+    text.add_run(
+        &mut scene,
+        None,
+        16.0,
+        palette::css::WHITE,
+        Affine::translate((4.0, 20.0)),
+        None,
+        Fill::NonZero,
+        label,
+    );
+    text.add_run(
+        &mut scene,
+        None,
+        12.0,
+        palette::css::YELLOW,
+        Affine::translate((4.0, 42.0)),
+        Some(Affine::skew(-0.3, 0.0)),
+        Fill::NonZero,
+        label,
+    );
+    scene
+}
+
+/// A scene with content of its own, so that appended resources need relocating.
+fn base() -> Scene {
+    let mut scene = Scene::new();
+    let gradient = Gradient::new_radial((100.0, 60.0), 80.0)
+        .with_stops([palette::css::MAROON, palette::css::BLACK]);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &gradient,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(WIDTH), f64::from(HEIGHT)),
+    );
+    scene
+}
+
+fn render(scene: &Scene, name: &str, use_cpu: bool) -> Vec<u8> {
+    let params = TestParams {
+        use_cpu,
+        ..TestParams::new(name, WIDTH, HEIGHT)
+    };
+    let image = catalina_tests::render_then_debug_sync(scene, &params).unwrap();
+    image.data.data().to_vec()
+}
+
+/// Checks that two renders differ by no more than the rounding of the glyph transforms, which
+/// are combined with the appended transform in a different order.
+fn assert_similar(expected: &[u8], actual: &[u8]) {
+    assert_eq!(expected.len(), actual.len());
+    let diffs: Vec<u8> = expected
+        .iter()
+        .zip(actual)
+        .map(|(a, b)| a.abs_diff(*b))
+        .collect();
+    let mean = diffs.iter().map(|d| f64::from(*d)).sum::<f64>() / diffs.len() as f64 / 255.0;
+    let max = diffs.iter().max().copied().unwrap_or(0);
+    assert!(mean < 0.0005, "mean difference {mean} is too large");
+    assert!(max <= 8, "largest difference {max} is too large");
+}
+
+fn fragments_match_append(use_cpu: bool) {
+    let mut text = SimpleText::new();
+    let tiles = [
+        (tile(&mut text, "Tile 1"), Affine::translate((5.0, 5.0))),
+        (tile(&mut text, "Tile 2"), Affine::translate((105.0, 5.0))),
+        (
+            tile(&mut text, "Tile 3"),
+            Affine::translate((30.0, 65.0)) * Affine::scale(1.5),
+        ),
+    ];
+
+    let mut appended = base();
+    for (tile, transform) in &tiles {
+        appended.append(tile, Some(*transform));
+    }
+
+    let mut resolver = FragmentResolver::new();
+    let mut assembled = base();
+    for (tile, transform) in &tiles {
+        let fragment = resolver.resolve(tile);
+        assembled.append_fragment(&fragment, Some(*transform));
+    }
+    // Fragments can also be appended to an empty scene without a transform.
+    let mut single = Scene::new();
+    single.append_fragment(&resolver.resolve(&tiles[0].0), None);
+    let mut single_expected = Scene::new();
+    single_expected.append(&tiles[0].0, None);
+
+    assert_similar(
+        &render(&appended, "fragments_appended", use_cpu),
+        &render(&assembled, "fragments_assembled", use_cpu),
+    );
+    assert_similar(
+        &render(&single_expected, "fragments_single_appended", use_cpu),
+        &render(&single, "fragments_single_assembled", use_cpu),
+    );
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn fragments_match_append_gpu() {
+    fragments_match_append(false);
+}
+
+#[test]
+// The fine shader still requires a GPU, and so we still get a wgpu device
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn fragments_match_append_cpu() {
+    fragments_match_append(true);
+}
@@ -0,0 +1,102 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of reusing buffers between frames with [`catalina::RendererOptions::frames_in_flight`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroUsize;
+
+use catalina::kurbo::{Affine, Circle, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{Renderer, RendererOptions, Scene};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+const SIZE: u32 = 48;
+
+fn rects() -> Scene {
+    let mut scene = Scene::new();
+    for i in 0..4 {
+        let x = f64::from(i) * 12.0;
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            palette::css::RED,
+            None,
+            &Rect::new(x, x, x + 10.0, x + 10.0),
+        );
+    }
+    scene
+}
+
+fn circle() -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::LIME,
+        None,
+        &Circle::new((24.0, 24.0), 20.0),
+    );
+    scene
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn frames_match_fresh_renders() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("frames_in_flight", SIZE, SIZE);
+    let scenes = [rects(), circle()];
+    let expected: Vec<_> = scenes
+        .iter()
+        .map(|scene| {
+            let mut renderer = create_renderer(&handle.device, &params).unwrap();
+            render_with(&handle.device, &handle.queue, &mut renderer, scene, &params).unwrap()
+        })
+        .collect();
+
+    for frames in [1, 2, 3] {
+        let mut renderer = Renderer::new(
+            &handle.device,
+            RendererOptions {
+                surface_format: None,
+                use_cpu: false,
+                num_init_threads: NonZeroUsize::new(1),
+                gpu_stages_from: None,
+                fine_band_rows: None,
+                frames_in_flight: NonZeroUsize::new(frames),
+                disk_cache: None,
+                small_scenes: None,
+                antialiasing_support: std::iter::once(params.anti_aliasing).collect(),
+            },
+        )
+        .unwrap();
+        // Alternating scenes of different sizes makes each frame need different buffers from
+        // the ones freed by the previous frame.
+        for frame in 0..6 {
+            let ix = frame % scenes.len();
+            let image = render_with(
+                &handle.device,
+                &handle.queue,
+                &mut renderer,
+                &scenes[ix],
+                &params,
+            )
+            .unwrap();
+            assert_eq!(
+                image.data.data(),
+                expected[ix].data.data(),
+                "frame {frame} with {frames} frames in flight"
+            );
+        }
+    }
+}
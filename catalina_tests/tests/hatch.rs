@@ -0,0 +1,90 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of hatch pattern fills with [`Scene::fill_hatch`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{render_to_pixels, RenderContext};
+use catalina::{AaConfig, Hatch, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 32;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// Fills the whole target with `hatch` and returns the pixels.
+fn render_hatch(hatch: &Hatch) -> Vec<u8> {
+    let mut scene = Scene::new();
+    scene.fill_hatch(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        hatch,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(SIZE), f64::from(SIZE)),
+    );
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("hatch", SIZE, SIZE)).unwrap();
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: SIZE,
+        height: SIZE,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    render_to_pixels(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    )
+    .unwrap()
+}
+
+fn pixel(pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    pixels[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn hatch_lines() {
+    // Horizontal lines 4 pixels wide, centered on every eighth row boundary.
+    let pixels = render_hatch(&Hatch::new(palette::css::RED, 8.0).with_line_width(4.0));
+    for x in [0, 13, 31] {
+        assert_eq!(pixel(&pixels, x, 0), RED);
+        assert_eq!(pixel(&pixels, x, 4), WHITE);
+        assert_eq!(pixel(&pixels, x, 7), RED);
+        assert_eq!(pixel(&pixels, x, 9), RED);
+        assert_eq!(pixel(&pixels, x, 12), WHITE);
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn cross_hatch_lines() {
+    let hatch = Hatch::new(palette::css::RED, 8.0)
+        .with_line_width(4.0)
+        .with_cross(true);
+    let pixels = render_hatch(&hatch);
+    assert_eq!(pixel(&pixels, 4, 0), RED);
+    assert_eq!(pixel(&pixels, 0, 4), RED);
+    assert_eq!(pixel(&pixels, 4, 4), WHITE);
+    assert_eq!(pixel(&pixels, 12, 12), WHITE);
+}
@@ -0,0 +1,179 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of rendering without a window using the helpers in [`catalina::util`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{render_to_pixels, render_to_pixels_async, DeviceOptions, RenderContext};
+use catalina::wgpu;
+use catalina::{AaConfig, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+// Not a multiple of the row alignment of texture copies, so the rows are padded.
+const WIDTH: u32 = 37;
+const HEIGHT: u32 = 20;
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn render_to_pixels_removes_padding() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 10.0, f64::from(WIDTH), f64::from(HEIGHT)),
+    );
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("headless", WIDTH, HEIGHT)).unwrap();
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: WIDTH,
+        height: HEIGHT,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    let pixels = render_to_pixels(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    )
+    .unwrap();
+    assert_eq!(pixels.len(), (WIDTH * HEIGHT * 4) as usize);
+    let at = |x: u32, y: u32| {
+        let offset = ((y * WIDTH + x) * 4) as usize;
+        &pixels[offset..offset + 4]
+    };
+    assert_eq!(at(WIDTH - 1, 0), [255, 255, 255, 255]);
+    assert_eq!(at(0, 10), [255, 0, 0, 255]);
+    assert_eq!(at(WIDTH - 1, HEIGHT - 1), [255, 0, 0, 255]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn render_to_pixels_without_blocking() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(WIDTH) / 2.0, f64::from(HEIGHT)),
+    );
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("headless", WIDTH, HEIGHT)).unwrap();
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: WIDTH,
+        height: HEIGHT,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    // Unlike `block_on_wgpu`, the executor knows nothing about the device, so this only
+    // finishes if the future polls the device itself.
+    let pixels = pollster::block_on(render_to_pixels_async(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    ))
+    .unwrap();
+    let blocking = render_to_pixels(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    )
+    .unwrap();
+    assert_eq!(pixels, blocking);
+    assert_eq!(pixels[..4], [0, 0, 255, 255]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn device_from_adapter_index() {
+    let mut context = RenderContext::new().with_device_options(DeviceOptions {
+        adapter_index: Some(0),
+        ..Default::default()
+    });
+    let expected = context.enumerate_adapters()[0].get_info();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let info = context.devices[device_id].adapter().get_info();
+    assert_eq!((info.name, info.backend), (expected.name, expected.backend));
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn render_with_external_device() {
+    let mut context = RenderContext::new();
+    let adapter = pollster::block_on(wgpu::util::initialize_adapter_from_env_or_default(
+        &context.instance,
+        None,
+    ))
+    .unwrap();
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            required_features: adapter.features() & wgpu::Features::CLEAR_TEXTURE,
+            ..Default::default()
+        },
+        None,
+    ))
+    .unwrap();
+    let device_id = context.add_device_handle(adapter, device, queue);
+    assert_eq!(pollster::block_on(context.device(None)), Some(device_id));
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("external_device", 4, 4)).unwrap();
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, 4.0, 4.0),
+    );
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: 4,
+        height: 4,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    let pixels = render_to_pixels(
+        &mut renderer,
+        &handle.device,
+        &handle.queue,
+        &scene,
+        &params,
+    )
+    .unwrap();
+    assert_eq!(&pixels[..4], [255, 0, 0, 255]);
+}
@@ -0,0 +1,67 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of layers masked by the alpha of an image.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Blob, Fill, Image, ImageFormat, Mix};
+use catalina::Scene;
+use catalina_tests::{render_then_debug_sync, TestParams};
+
+const SIZE: u32 = 16;
+
+/// An image which is opaque in its left half and transparent in its right half.
+fn half_mask() -> Image {
+    let data: Vec<u8> = (0..SIZE * SIZE)
+        .flat_map(|ix| {
+            let alpha = if ix % SIZE < SIZE / 2 { 0xFF } else { 0 };
+            [0xFF, 0xFF, 0xFF, alpha]
+        })
+        .collect();
+    Image::new(Blob::new(Arc::new(data)), ImageFormat::Rgba8, SIZE, SIZE)
+}
+
+fn pixel(image: &Image, x: u32, y: u32) -> [u8; 4] {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    image.data.data()[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn image_mask_hides_content() {
+    let bounds = Rect::new(0.0, 0.0, SIZE.into(), SIZE.into());
+    let mut scene = Scene::new();
+    scene.push_layer_with_mask(
+        Mix::Normal,
+        1.0,
+        Affine::IDENTITY,
+        &bounds,
+        &half_mask(),
+        Affine::IDENTITY,
+    );
+    // Layers nested inside the masked layer are masked with it.
+    scene.push_layer(Mix::Clip, 1.0, Affine::IDENTITY, &bounds);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &bounds,
+    );
+    scene.pop_layer();
+    scene.pop_layer();
+    let params = TestParams::new("image_mask", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    assert_eq!(pixel(&image, 4, 8), [0xFF, 0, 0, 0xFF]);
+    assert_eq!(pixel(&image, 12, 8), [0, 0, 0, 0xFF]);
+}
@@ -0,0 +1,80 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of sampling repeated images without seams, and of snapping them to device pixels with
+//! [`catalina::PixelSnap::image`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{Blob, Extend, Fill, Image, ImageFormat};
+use catalina::{PixelSnap, Scene};
+use catalina_tests::{render_then_debug_sync, TestParams};
+
+const SIZE: u32 = 16;
+
+/// A tile of two texels, black then white, repeated in both directions.
+fn tile() -> Image {
+    let data = vec![0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    Image::new(Blob::new(Arc::new(data)), ImageFormat::Rgba8, 2, 1).with_extend(Extend::Repeat)
+}
+
+fn row(image: &Image, y: u32) -> Vec<[u8; 4]> {
+    (0..SIZE)
+        .map(|x| {
+            let offset = ((y * SIZE + x) * 4) as usize;
+            image.data.data()[offset..offset + 4].try_into().unwrap()
+        })
+        .collect()
+}
+
+fn render_tiles(name: &str, brush_transform: Affine) -> Image {
+    let mut scene = Scene::new();
+    let bounds = Rect::new(0.0, 0.0, SIZE.into(), SIZE.into());
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &tile(),
+        Some(brush_transform),
+        &bounds,
+    );
+    let params = TestParams::new(name, SIZE, SIZE);
+    render_then_debug_sync(&scene, &params).unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn snapped_tiles_match_their_texels() {
+    let snap = PixelSnap::new(Affine::IDENTITY);
+    let image = render_tiles(
+        "image_snapping_snapped",
+        snap.image(Affine::translate((0.3, 0.6))),
+    );
+    let (black, white) = ([0, 0, 0, 0xFF], [0xFF; 4]);
+    for y in [0, SIZE / 2, SIZE - 1] {
+        let expected: Vec<_> = (0..SIZE)
+            .map(|x| if x % 2 == 0 { black } else { white })
+            .collect();
+        assert_eq!(row(&image, y), expected);
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn repeated_tiles_have_no_seams() {
+    // Every pixel is sampled halfway between a black and a white texel, including those
+    // between the last texel of one tile and the first of the next.
+    let image = render_tiles("image_snapping_seams", Affine::translate((0.5, 0.0)));
+    let pixels = row(&image, SIZE / 2);
+    let first = pixels[0];
+    assert!((0x70..=0x90).contains(&first[0]));
+    assert!(pixels.iter().all(|pixel| *pixel == first));
+}
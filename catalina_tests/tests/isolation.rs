@@ -0,0 +1,87 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of blending within isolated and non-isolated layers.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{Color, Fill, Image, Mix};
+use catalina::Scene;
+use catalina_tests::{render_then_debug_sync, TestParams};
+
+const SIZE: u32 = 16;
+
+fn gray() -> Color {
+    Color::from_rgb8(0x80, 0x80, 0x80)
+}
+
+fn bounds() -> Rect {
+    Rect::new(0.0, 0.0, SIZE.into(), SIZE.into())
+}
+
+/// Returns a scene of gray, with a layer pushed by `push_group` containing gray multiplied
+/// with its backdrop.
+fn multiply_in_group(push_group: impl FnOnce(&mut Scene)) -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(Fill::NonZero, Affine::IDENTITY, gray(), None, &bounds());
+    push_group(&mut scene);
+    scene.push_layer(Mix::Multiply, 1.0, Affine::IDENTITY, &bounds());
+    scene.fill(Fill::NonZero, Affine::IDENTITY, gray(), None, &bounds());
+    scene.pop_layer();
+    scene.pop_layer();
+    scene
+}
+
+fn assert_gray(image: &Image, expected: u8) {
+    let offset = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    let pixel = &image.data.data()[offset..offset + 4];
+    for channel in &pixel[..3] {
+        assert!(
+            channel.abs_diff(expected) <= 2,
+            "{pixel:?} isn't {expected}"
+        );
+    }
+    assert_eq!(pixel[3], 0xFF);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn isolated_layer_blends_with_transparent() {
+    let scene = multiply_in_group(|scene| {
+        scene.push_layer(Mix::Normal, 1.0, Affine::IDENTITY, &bounds());
+    });
+    let params = TestParams::new("isolated_layer", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    // The gray is multiplied with nothing, so is drawn unchanged.
+    assert_gray(&image, 0x80);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn non_isolated_layer_blends_with_backdrop() {
+    let scene = multiply_in_group(|scene| {
+        scene.push_non_isolated_layer(1.0, Affine::IDENTITY, &bounds());
+    });
+    let params = TestParams::new("non_isolated_layer", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    assert_gray(&image, 0x40);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn non_isolated_layer_alpha() {
+    let scene = multiply_in_group(|scene| {
+        scene.push_non_isolated_layer(0.5, Affine::IDENTITY, &bounds());
+    });
+    let params = TestParams::new("non_isolated_layer_alpha", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    // Halfway between the backdrop and the multiplied gray.
+    assert_gray(&image, 0x60);
+}
@@ -0,0 +1,110 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of knockout layers, in which draws replace the earlier draws beneath them.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill, Image, Mix};
+use catalina::Scene;
+use catalina_tests::{render_then_debug_sync, TestParams};
+
+const SIZE: u32 = 16;
+
+fn bounds() -> Rect {
+    Rect::new(0.0, 0.0, SIZE.into(), SIZE.into())
+}
+
+/// Returns a scene of blue, with a layer pushed by `push_group` containing red covered by
+/// translucent lime.
+fn overlapping_draws(push_group: impl FnOnce(&mut Scene)) -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &bounds(),
+    );
+    push_group(&mut scene);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &bounds(),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::LIME.with_alpha(0.5),
+        None,
+        &bounds(),
+    );
+    scene.pop_layer();
+    scene
+}
+
+fn assert_pixel(image: &Image, expected: [u8; 4]) {
+    let offset = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    let pixel = &image.data.data()[offset..offset + 4];
+    for (channel, wanted) in pixel.iter().zip(expected) {
+        assert!(
+            channel.abs_diff(wanted) <= 2,
+            "{pixel:?} isn't {expected:?}"
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn knockout_layer_replaces_earlier_draws() {
+    let scene = overlapping_draws(|scene| {
+        scene.push_knockout_layer(Mix::Normal, 1.0, Affine::IDENTITY, &bounds());
+    });
+    let params = TestParams::new("knockout_layer", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    // The red is knocked out, so the blue beneath the layer shows through the lime.
+    assert_pixel(&image, [0, 0x80, 0x80, 0xFF]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn non_isolated_knockout_layer_shows_backdrop() {
+    let scene = overlapping_draws(|scene| {
+        scene.push_non_isolated_knockout_layer(1.0, Affine::IDENTITY, &bounds());
+    });
+    let params = TestParams::new("non_isolated_knockout_layer", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    assert_pixel(&image, [0, 0x80, 0x80, 0xFF]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn normal_layer_composes_draws() {
+    let scene = overlapping_draws(|scene| {
+        scene.push_layer(Mix::Normal, 1.0, Affine::IDENTITY, &bounds());
+    });
+    let params = TestParams::new("normal_layer_overlap", SIZE, SIZE);
+    let image = render_then_debug_sync(&scene, &params).unwrap();
+    assert_pixel(&image, [0x80, 0x80, 0, 0xFF]);
+}
+
+#[test]
+fn layers_report_knockout() {
+    let mut scene = Scene::new();
+    scene.push_knockout_layer(Mix::Normal, 1.0, Affine::IDENTITY, &bounds());
+    scene.pop_layer();
+    scene.push_non_isolated_knockout_layer(1.0, Affine::IDENTITY, &bounds());
+    scene.pop_layer();
+    let layers = scene.layers();
+    assert!(layers[0].is_knockout && layers[0].is_isolated);
+    assert!(layers[1].is_knockout && !layers[1].is_isolated);
+}
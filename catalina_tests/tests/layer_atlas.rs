@@ -0,0 +1,123 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of packing renders into a [`LayerAtlas`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Color, Fill};
+use catalina::util::{read_texture, RenderContext};
+use catalina::{AtlasEntry, Error, LayerAtlas, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const PAGE_SIZE: u32 = 64;
+
+/// A scene which fills more than `size` with `color`, so that clipping to the entry is
+/// visible.
+fn filled(color: Color, size: u32) -> Scene {
+    let mut scene = Scene::new();
+    let size = f64::from(size);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        color,
+        None,
+        &Rect::new(-4.0, -4.0, size + 4.0, size + 4.0),
+    );
+    scene
+}
+
+fn pixel(pixels: &[u8], x: u32, y: u32) -> &[u8] {
+    let index = ((y * PAGE_SIZE + x) * 4) as usize;
+    &pixels[index..index + 4]
+}
+
+fn overlap(a: &AtlasEntry, b: &AtlasEntry) -> bool {
+    a.page() == b.page() && a.uv_rect().intersect(b.uv_rect()).area() > 0.0
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn entries_are_packed_and_reused() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let device = &handle.device;
+    let queue = &handle.queue;
+    let mut renderer = create_renderer(
+        device,
+        &TestParams::new("layer_atlas", PAGE_SIZE, PAGE_SIZE),
+    )
+    .unwrap();
+    let mut atlas = LayerAtlas::new(device, PAGE_SIZE, 1);
+
+    let red = atlas
+        .render(
+            &mut renderer,
+            device,
+            queue,
+            &filled(palette::css::RED, 20),
+            20,
+            20,
+        )
+        .unwrap();
+    let blue = atlas
+        .render(
+            &mut renderer,
+            device,
+            queue,
+            &filled(palette::css::BLUE, 20),
+            20,
+            20,
+        )
+        .unwrap();
+    assert!(!overlap(&red, &blue));
+    let pixels = read_texture(device, queue, atlas.texture()).unwrap();
+    for (entry, expected) in [(&red, [255, 0, 0, 255]), (&blue, [0, 0, 255, 255])] {
+        let (x, y) = entry.origin();
+        assert_eq!(pixel(&pixels, x, y), expected);
+        assert_eq!(pixel(&pixels, x + 19, y + 19), expected);
+        // The content is clipped to the entry, leaving a transparent border.
+        assert_eq!(pixel(&pixels, x + 20, y + 20), [0, 0, 0, 0]);
+    }
+
+    // Larger than a page can never fit.
+    let result = atlas.render(
+        &mut renderer,
+        device,
+        queue,
+        &filled(palette::css::RED, PAGE_SIZE),
+        PAGE_SIZE,
+        PAGE_SIZE,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::LayerAtlasFull(PAGE_SIZE, PAGE_SIZE))
+    ));
+
+    // Freed space is reused, and cleared of the previous content.
+    let (x, y) = red.origin();
+    atlas.free(red);
+    let green = atlas
+        .render(
+            &mut renderer,
+            device,
+            queue,
+            &filled(palette::css::LIME, 16),
+            16,
+            16,
+        )
+        .unwrap();
+    assert_eq!(green.origin(), (x, y));
+    assert!(!overlap(&green, &blue));
+    let reused = read_texture(device, queue, atlas.texture()).unwrap();
+    assert_eq!(pixel(&reused, x, y), [0, 255, 0, 255]);
+    assert_eq!(pixel(&reused, x + 16, y + 16), [0, 0, 0, 0]);
+}
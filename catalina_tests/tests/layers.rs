@@ -0,0 +1,95 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of inspecting the layers of a scene, with [`catalina::Scene::layers`] and
+//! [`catalina::Scene::layer_overlay`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Circle, Rect, Shape};
+use catalina::peniko::{color::palette, Fill, Mix};
+use catalina::Scene;
+
+#[test]
+fn nested_layers() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    scene.push_layer(
+        Mix::Clip,
+        1.0,
+        Affine::translate((50.0, 50.0)),
+        &Rect::new(0.0, 0.0, 100.0, 80.0),
+    );
+    scene.push_layer(
+        Mix::Multiply,
+        0.5,
+        Affine::IDENTITY,
+        &Circle::new((100.0, 100.0), 20.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Rect::new(0.0, 0.0, 200.0, 200.0),
+    );
+    scene.pop_layer();
+    scene.pop_layer();
+    scene.push_layer(
+        Mix::Normal,
+        1.0,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    scene.pop_layer();
+
+    let layers = scene.layers();
+    assert_eq!(layers.len(), 3);
+    assert_eq!(
+        layers.iter().map(|layer| layer.depth).collect::<Vec<_>>(),
+        [0, 1, 0]
+    );
+    assert_eq!(
+        layers.iter().map(|layer| layer.draw).collect::<Vec<_>>(),
+        [1, 2, 6]
+    );
+    assert!(layers[0].is_clip);
+    assert!(!layers[1].is_clip);
+    assert_eq!(layers[1].alpha, 0.5);
+    assert_eq!(
+        layers[0].clip.bounding_box(),
+        Rect::new(50.0, 50.0, 150.0, 130.0)
+    );
+    let circle = layers[1].clip.bounding_box();
+    assert!((circle.x0 - 80.0).abs() < 1e-3 && (circle.x1 - 120.0).abs() < 1e-3);
+
+    let overlay = scene.layer_overlay();
+    assert!(!overlay.encoding().is_empty());
+    assert!(overlay.layers().is_empty());
+}
+
+#[test]
+fn no_layers() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    assert!(scene.layers().is_empty());
+    assert!(scene.layer_overlay().encoding().is_empty());
+}
@@ -0,0 +1,130 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of measuring and truncating glyph runs with [`catalina::TextMeasure`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::peniko::{Blob, Font};
+use catalina::{Glyph, TextMeasure};
+
+const ROBOTO: &[u8] = include_bytes!("../../examples/assets/roboto/Roboto-Regular.ttf");
+/// The glyphs of `A` and `i` in Roboto, whose advances are 1336 and 497 of 2048 units.
+const GLYPH_A: u32 = 37;
+const GLYPH_I: u32 = 77;
+/// A font size at which a font unit of Roboto is 0.01 pixels.
+const FONT_SIZE: f32 = 20.48;
+const ADVANCE_A: f32 = 13.36;
+
+fn roboto() -> Font {
+    Font::new(Blob::new(Arc::new(ROBOTO)), 0)
+}
+
+/// Font units are scaled by a 16.16 fixed point factor, so metrics are only accurate to about
+/// 0.05%.
+fn assert_near(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-3 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+/// A line of `count` glyphs of `A`, on a baseline at `y`.
+fn line_of_a(count: u16, y: f32) -> Vec<Glyph> {
+    (0..count)
+        .map(|i| Glyph {
+            id: GLYPH_A,
+            x: f32::from(i) * ADVANCE_A,
+            y,
+        })
+        .collect()
+}
+
+#[test]
+fn line_metrics() {
+    let font = roboto();
+    let measure = TextMeasure::new(&font, FONT_SIZE).unwrap();
+    let metrics = measure.line_metrics();
+    assert!(metrics.ascent > 0.0);
+    assert!(metrics.descent > 0.0);
+    assert_near(
+        metrics.line_height(),
+        metrics.ascent + metrics.descent + metrics.leading,
+    );
+}
+
+#[test]
+fn width_includes_last_advance() {
+    let font = roboto();
+    let measure = TextMeasure::new(&font, FONT_SIZE).unwrap();
+    assert_near(measure.advance(GLYPH_I), 4.97);
+    let run = [
+        Glyph {
+            id: GLYPH_A,
+            x: 10.0,
+            y: 0.0,
+        },
+        Glyph {
+            id: GLYPH_I,
+            x: 10.0 + ADVANCE_A,
+            y: 0.0,
+        },
+    ];
+    assert_near(measure.width(&run), ADVANCE_A + 4.97);
+    assert_near(measure.width(&[]), 0.0);
+}
+
+#[test]
+fn truncate_with_ellipsis() {
+    let font = roboto();
+    let measure = TextMeasure::new(&font, FONT_SIZE).unwrap();
+    let ellipsis_width = measure.ellipsis_width();
+    assert!(ellipsis_width > 0.0);
+    let line = line_of_a(5, 30.0);
+
+    // A run which fits is unchanged.
+    assert_eq!(measure.truncate(&line, 5.0 * ADVANCE_A).len(), 5);
+
+    let max_width = 2.0 * ADVANCE_A + ellipsis_width + 0.01;
+    let truncated = measure.truncate(&line, max_width);
+    assert!(truncated.len() > 2);
+    assert!(truncated[..2].iter().all(|glyph| glyph.id == GLYPH_A));
+    assert!(truncated[2..].iter().all(|glyph| glyph.id != GLYPH_A));
+    assert_near(truncated[2].x, 2.0 * ADVANCE_A);
+    assert_near(truncated[2].y, 30.0);
+    assert!(measure.width(&truncated) <= max_width);
+
+    // Only the ellipsis fits.
+    let ellipsis = measure.truncate(&line, ellipsis_width);
+    assert!(ellipsis.iter().all(|glyph| glyph.id != GLYPH_A));
+    assert!(!ellipsis.is_empty());
+
+    // Not even the ellipsis fits.
+    assert!(measure.truncate(&line, ellipsis_width / 2.0).is_empty());
+}
+
+#[test]
+fn truncate_lines_ends_with_ellipsis() {
+    let font = roboto();
+    let measure = TextMeasure::new(&font, FONT_SIZE).unwrap();
+    let lines = vec![line_of_a(1, 0.0), line_of_a(1, 24.0), line_of_a(1, 48.0)];
+    let truncated = measure.truncate_lines(&lines, 100.0, 2);
+    assert_eq!(truncated.len(), 2);
+    assert_eq!(truncated[0].len(), 1);
+    // The second line fits, but is followed by a removed line.
+    assert!(truncated[1].len() > 1);
+    assert_eq!(truncated[1][0].id, GLYPH_A);
+    assert_near(truncated[1][1].x, ADVANCE_A);
+    assert_near(truncated[1][1].y, 24.0);
+
+    assert_eq!(measure.truncate_lines(&lines, 100.0, 3).len(), 3);
+    assert!(measure.truncate_lines(&lines, 100.0, 0).is_empty());
+}
@@ -0,0 +1,58 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of strokes whose width is in device pixels, with [`Scene::stroke_non_scaling`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect, Stroke};
+use catalina::peniko::color::palette;
+use catalina::util::RenderContext;
+use catalina::Scene;
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn width_ignores_the_transform() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("non_scaling_stroke", 64, 64);
+    let mut renderer = create_renderer(&handle.device, &params).unwrap();
+
+    let stretch = Affine::scale_non_uniform(4.0, 1.0);
+    let rect = Rect::new(2.0, 8.0, 14.0, 56.0);
+    let stroke = Stroke::new(2.0);
+    let mut scene = Scene::new();
+    scene.stroke_non_scaling(&stroke, stretch, palette::css::WHITE, None, &rect);
+    // The same outline, already in device space.
+    let mut expected = Scene::new();
+    expected.stroke(
+        &stroke,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &stretch.transform_rect_bbox(rect),
+    );
+    let mut scaled = Scene::new();
+    scaled.stroke(&stroke, stretch, palette::css::WHITE, None, &rect);
+
+    let [image, expected, scaled] = [scene, expected, scaled].map(|scene| {
+        render_with(
+            &handle.device,
+            &handle.queue,
+            &mut renderer,
+            &scene,
+            &params,
+        )
+        .unwrap()
+    });
+    assert_eq!(image.data.data(), expected.data.data());
+    assert_ne!(image.data.data(), scaled.data.data());
+}
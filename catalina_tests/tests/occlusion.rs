@@ -0,0 +1,96 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of culling hidden windows with [`OcclusionCuller`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::{CompositorWindow, OcclusionCuller, Scene};
+
+const VIEWPORT: Rect = Rect::new(0.0, 0.0, 200.0, 200.0);
+
+fn window_content(size: f64) -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &Rect::new(0.0, 0.0, size, size),
+    );
+    scene
+}
+
+fn area(region: &[Rect]) -> f64 {
+    region.iter().map(Rect::area).sum()
+}
+
+#[test]
+fn covered_windows_are_culled() {
+    let content = window_content(100.0);
+    let bounds = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let opaque = [bounds];
+    let windows = [
+        // Entirely behind the front window.
+        CompositorWindow::new(&content, bounds).with_transform(Affine::translate((10.0, 10.0))),
+        // Partly behind it.
+        CompositorWindow::new(&content, bounds).with_transform(Affine::translate((60.0, 60.0))),
+        CompositorWindow::new(&content, Rect::new(0.0, 0.0, 120.0, 120.0))
+            .with_transform(Affine::translate((10.0, 10.0)))
+            .with_opaque(&opaque),
+    ];
+    let mut culler = OcclusionCuller::new();
+    let mut target = Scene::new();
+    culler.compose(&mut target, VIEWPORT, &windows);
+
+    assert!(!culler.is_visible(0));
+    assert!(culler.is_visible(1));
+    assert!(culler.is_visible(2));
+    // Only the opaque region of the front window hides what's behind it.
+    assert_eq!(area(culler.visible_region(1)), 100.0 * 100.0 - 50.0 * 50.0);
+    assert_eq!(area(culler.visible_region(2)), 120.0 * 120.0);
+    let front_opaque = Rect::new(10.0, 10.0, 110.0, 110.0);
+    for back in culler.visible_region(1) {
+        assert!(back.intersect(front_opaque).is_zero_area());
+    }
+
+    let mut expected = Scene::new();
+    expected.append(&content, Some(Affine::translate((60.0, 60.0))));
+    expected.append(&content, Some(Affine::translate((10.0, 10.0))));
+    assert_eq!(target.encoding().n_paths, expected.encoding().n_paths);
+    assert_eq!(target.encoding().transforms, expected.encoding().transforms);
+}
+
+#[test]
+fn transparent_and_rotated_windows_dont_occlude() {
+    let content = window_content(100.0);
+    let bounds = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let opaque = [bounds];
+    let windows = [
+        CompositorWindow::new(&content, bounds),
+        CompositorWindow::new(&content, bounds),
+        CompositorWindow::new(&content, bounds)
+            .with_transform(Affine::rotate(0.1))
+            .with_opaque(&opaque),
+    ];
+    let mut culler = OcclusionCuller::new();
+    culler.compose(&mut Scene::new(), VIEWPORT, &windows);
+    assert!(culler.is_visible(0));
+    assert_eq!(area(culler.visible_region(0)), 100.0 * 100.0);
+    assert!(culler.is_visible(1));
+
+    // Windows outside of the viewport are culled.
+    let offscreen =
+        [CompositorWindow::new(&content, bounds).with_transform(Affine::translate((300.0, 0.0)))];
+    culler.compose(&mut Scene::new(), VIEWPORT, &offscreen);
+    assert!(!culler.is_visible(0));
+    assert!(!culler.is_visible(1));
+}
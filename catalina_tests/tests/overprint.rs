@@ -0,0 +1,108 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of overprint previews and their separations.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Color, Fill};
+use catalina::util::{create_target_texture, read_texture, RenderContext};
+use catalina::wgpu;
+use catalina::{AaConfig, RenderParams, Scene, Separation};
+use catalina_tests::{create_renderer, TestParams};
+
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 4;
+
+/// Cyan in the left three quarters and yellow in the right three quarters, drawn in the
+/// given order.
+fn inks(cyan_first: bool) -> Scene {
+    let cyan = (
+        palette::css::AQUA,
+        Rect::new(0.0, 0.0, 12.0, f64::from(HEIGHT)),
+    );
+    let yellow = (
+        palette::css::YELLOW,
+        Rect::new(4.0, 0.0, 16.0, f64::from(HEIGHT)),
+    );
+    let draws: [(Color, Rect); 2] = if cyan_first {
+        [cyan, yellow]
+    } else {
+        [yellow, cyan]
+    };
+    let mut scene = Scene::new();
+    for (color, rect) in draws {
+        scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &rect);
+    }
+    scene
+}
+
+/// Renders an overprint preview of `scene`, returning the pixels of its first row.
+fn preview(scene: &Scene, separation: Option<Separation>) -> Vec<[u8; 4]> {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("overprint", WIDTH, HEIGHT)).unwrap();
+    let texture = create_target_texture(&handle.device, WIDTH, HEIGHT);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer
+        .render_overprint_preview_to_texture(
+            &handle.device,
+            &handle.queue,
+            scene,
+            &view,
+            &RenderParams {
+                base_color: palette::css::WHITE,
+                width: WIDTH,
+                height: HEIGHT,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+            separation,
+        )
+        .unwrap();
+    let pixels = read_texture(&handle.device, &handle.queue, &texture).unwrap();
+    pixels[..(WIDTH * 4) as usize]
+        .chunks_exact(4)
+        .map(|pixel| pixel.try_into().unwrap())
+        .collect()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn overprint_is_order_independent() {
+    let row = preview(&inks(true), None);
+    assert_eq!(row, preview(&inks(false), None));
+    assert_eq!(row[2], [0, 0xFF, 0xFF, 0xFF]);
+    // Cyan and yellow inks make green where they overlap.
+    assert_eq!(row[8], [0, 0xFF, 0, 0xFF]);
+    assert_eq!(row[14], [0xFF, 0xFF, 0, 0xFF]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn separations_show_each_ink() {
+    let cyan = preview(&inks(true), Some(Separation::Cyan));
+    assert_eq!(
+        [cyan[2], cyan[8], cyan[14]].map(|pixel| pixel[0]),
+        [0, 0, 0xFF]
+    );
+    let yellow = preview(&inks(true), Some(Separation::Yellow));
+    assert_eq!(
+        [yellow[2], yellow[8], yellow[14]].map(|pixel| pixel[0]),
+        [0xFF, 0, 0]
+    );
+    let black = preview(&inks(true), Some(Separation::Black));
+    assert!(black.iter().all(|pixel| pixel[0] == 0xFF));
+}
@@ -0,0 +1,82 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of painting into a retained [`PaintLayer`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Color, Fill};
+use catalina::util::{read_texture, RenderContext};
+use catalina::{PaintLayer, PaintMode, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 16;
+
+fn rect(color: Color, x0: f64, x1: f64) -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        color,
+        None,
+        &Rect::new(x0, 0.0, x1, f64::from(SIZE)),
+    );
+    scene
+}
+
+fn pixel(pixels: &[u8], x: u32) -> [u8; 4] {
+    let index = (x * 4) as usize;
+    pixels[index..index + 4].try_into().unwrap()
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn strokes_are_retained_between_paints() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer =
+        create_renderer(device, &TestParams::new("paint_layer", SIZE, SIZE)).unwrap();
+    let mut layer = PaintLayer::new(device, SIZE, SIZE);
+    let mut paint = |scene: &Scene, mode| {
+        layer
+            .paint(&mut renderer, device, queue, scene, mode)
+            .unwrap();
+        read_texture(device, queue, layer.texture()).unwrap()
+    };
+
+    // Red on the left half, and then green painted behind it across the whole layer.
+    paint(&rect(palette::css::RED, 0.0, 8.0), PaintMode::Normal);
+    let behind = paint(&rect(palette::css::LIME, 0.0, 16.0), PaintMode::Behind);
+    assert_eq!(pixel(&behind, 2), [255, 0, 0, 255]);
+    assert_eq!(pixel(&behind, 12), [0, 255, 0, 255]);
+
+    // Multiplying by yellow keeps the red and green channels.
+    let multiplied = paint(&rect(palette::css::YELLOW, 0.0, 16.0), PaintMode::Multiply);
+    assert_eq!(pixel(&multiplied, 2), [255, 0, 0, 255]);
+    assert_eq!(pixel(&multiplied, 12), [0, 255, 0, 255]);
+
+    // Erasing the middle leaves the content on either side.
+    let erased = paint(&rect(palette::css::BLACK, 4.0, 12.0), PaintMode::Erase);
+    assert_eq!(pixel(&erased, 2), [255, 0, 0, 255]);
+    assert_eq!(pixel(&erased, 8)[3], 0);
+    assert_eq!(pixel(&erased, 14), [0, 255, 0, 255]);
+
+    // Painting with locked alpha doesn't fill the erased area.
+    let locked = paint(&rect(palette::css::BLUE, 0.0, 16.0), PaintMode::LockAlpha);
+    assert_eq!(pixel(&locked, 2), [0, 0, 255, 255]);
+    assert_eq!(pixel(&locked, 8)[3], 0);
+    assert_eq!(pixel(&locked, 14), [0, 0, 255, 255]);
+
+    layer.clear(&mut renderer, device, queue).unwrap();
+    let cleared = read_texture(device, queue, layer.texture()).unwrap();
+    assert!(cleared.iter().all(|c| *c == 0));
+}
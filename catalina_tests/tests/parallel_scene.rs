@@ -0,0 +1,141 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of building scenes from parts encoded on several threads with
+//! [`catalina::ParallelSceneBuilder`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Blob, Fill, Image, ImageFormat, Mix};
+use catalina::unstable::encoding::Patch;
+use catalina::{Filter, ParallelSceneBuilder, Scene};
+
+/// Encodes part `index`, which draws a rectangle and a checkerboard image.
+fn encode_part(index: u64, scene: &mut Scene) {
+    let x = index as f64 * 10.0;
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(x, 0.0, x + 5.0, 5.0),
+    );
+    // Each part decodes its own copy of the image.
+    let data = vec![0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    let image = Image::new(Blob::new(Arc::new(data)), ImageFormat::Rgba8, 2, 1);
+    scene.draw_image(&image, Affine::translate((x, 5.0)));
+}
+
+fn image_ids(scene: &Scene) -> Vec<u64> {
+    scene
+        .encoding()
+        .resources
+        .patches
+        .iter()
+        .filter_map(|patch| match patch {
+            Patch::Image { image, .. } => Some(image.data.id()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn parts_merge_in_key_order() {
+    let builder = ParallelSceneBuilder::new();
+    std::thread::scope(|scope| {
+        // The parts are finished in reverse order.
+        for index in (0..4).rev() {
+            let mut part = builder.part(index);
+            scope.spawn(move || {
+                encode_part(index, &mut part);
+                part.finish();
+            });
+        }
+    });
+    let merged = builder.finish();
+
+    let mut expected = Scene::new();
+    for index in 0..4 {
+        let mut part = Scene::new();
+        encode_part(index, &mut part);
+        expected.append(&part, None);
+    }
+    let (a, b) = (merged.encoding(), expected.encoding());
+    assert_eq!(a.path_data, b.path_data);
+    assert_eq!(a.transforms, b.transforms);
+    assert_eq!(a.draw_data, b.draw_data);
+}
+
+#[test]
+fn duplicate_images_are_shared() {
+    let builder = ParallelSceneBuilder::new().with_resource_dedup(true);
+    for index in 0..3 {
+        let mut part = builder.part(index);
+        encode_part(index, &mut part);
+        part.finish();
+    }
+    let merged = builder.finish();
+    let ids = image_ids(&merged);
+    assert_eq!(ids.len(), 3);
+    assert!(ids.iter().all(|id| *id == ids[0]));
+}
+
+#[test]
+fn images_are_only_shared_when_enabled() {
+    let builder = ParallelSceneBuilder::new();
+    for index in 0..3 {
+        let mut part = builder.part(index);
+        encode_part(index, &mut part);
+        part.finish();
+    }
+    let ids = image_ids(&builder.finish());
+    assert_eq!(ids.len(), 3);
+    assert!(ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2]);
+}
+
+#[test]
+fn zeroed_images_are_shared() {
+    let builder = ParallelSceneBuilder::new().with_resource_dedup(true);
+    for index in 0..2 {
+        let mut part = builder.part(index);
+        let image = Image::new(Blob::new(Arc::new(vec![0; 8])), ImageFormat::Rgba8, 2, 1);
+        part.draw_image(&image, Affine::IDENTITY);
+        part.finish();
+    }
+    let ids = image_ids(&builder.finish());
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[0], ids[1]);
+}
+
+#[test]
+fn renderer_images_are_not_shared() {
+    // Filtered layers are drawn as images with zeroed data, which the renderer replaces with
+    // their texture by id.
+    let builder = ParallelSceneBuilder::new().with_resource_dedup(true);
+    for index in 0..2 {
+        let mut part = builder.part(index);
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        part.push_layer_with_filter(Mix::Normal, 1.0, Affine::IDENTITY, &rect, Filter::blur(1.0));
+        part.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            palette::css::RED,
+            None,
+            &rect,
+        );
+        part.pop_layer();
+        part.finish();
+    }
+    let ids = image_ids(&builder.finish());
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+}
@@ -0,0 +1,103 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of drawing paths given by their elements, with [`Scene::fill_path_elements`] and the
+//! methods like it.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, BezPath, PathEl, Stroke};
+use catalina::peniko::{color::palette, Fill, Mix};
+use catalina::Scene;
+
+/// Two triangles, as an arena of elements which a frame's paths would be slices of.
+fn arena() -> Vec<PathEl> {
+    let mut path = BezPath::new();
+    path.move_to((0.0, 0.0));
+    path.line_to((10.0, 0.0));
+    path.quad_to((10.0, 10.0), (0.0, 10.0));
+    path.close_path();
+    path.move_to((20.0, 0.0));
+    path.curve_to((30.0, 0.0), (30.0, 10.0), (20.0, 10.0));
+    path.close_path();
+    path.elements().to_vec()
+}
+
+#[test]
+fn elements_encode_like_shapes() {
+    let elements = arena();
+    let path = BezPath::from_vec(elements.clone());
+    let transform = Affine::translate((5.0, 5.0));
+    let stroke = Stroke::new(2.0);
+
+    let mut from_shape = Scene::new();
+    from_shape.fill(Fill::EvenOdd, transform, palette::css::RED, None, &path);
+    from_shape.stroke(&stroke, transform, palette::css::BLUE, None, &path);
+
+    let mut from_elements = Scene::new();
+    from_elements.fill_path_elements(
+        Fill::EvenOdd,
+        transform,
+        palette::css::RED,
+        None,
+        elements.iter().copied(),
+    );
+    from_elements.stroke_path_elements(
+        &stroke,
+        transform,
+        palette::css::BLUE,
+        None,
+        elements[..4].iter().chain(&elements[4..]).copied(),
+    );
+
+    assert_same_encoding(&from_shape, &from_elements);
+}
+
+#[test]
+fn streamed_elements_encode_like_shapes() {
+    let elements = arena();
+    let path = BezPath::from_vec(elements.clone());
+    let transform = Affine::scale(2.0);
+    let stroke = Stroke::new(1.5);
+
+    let mut from_shape = Scene::new();
+    from_shape.push_layer(Mix::Clip, 1.0, transform, &path);
+    from_shape.fill(Fill::NonZero, transform, palette::css::RED, None, &path);
+    from_shape.stroke(&stroke, transform, palette::css::BLUE, None, &path);
+    from_shape.pop_layer();
+
+    // The iterators can only be traversed once.
+    let mut from_elements = Scene::new();
+    from_elements.push_layer_path_elements(Mix::Clip, 1.0, transform, elements.iter().copied());
+    from_elements.fill_path_elements(
+        Fill::NonZero,
+        transform,
+        palette::css::RED,
+        None,
+        elements.iter().copied(),
+    );
+    from_elements.stroke_path_elements(
+        &stroke,
+        transform,
+        palette::css::BLUE,
+        None,
+        elements.iter().copied(),
+    );
+    from_elements.pop_layer();
+    assert_same_encoding(&from_shape, &from_elements);
+}
+
+fn assert_same_encoding(a: &Scene, b: &Scene) {
+    let (a, b) = (a.encoding(), b.encoding());
+    assert!(a.path_tags == b.path_tags);
+    assert_eq!(a.path_data, b.path_data);
+    assert!(a.draw_tags == b.draw_tags);
+    assert_eq!(a.draw_data, b.draw_data);
+    assert_eq!(a.transforms, b.transforms);
+}
@@ -0,0 +1,126 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of looking up and applying adapter workarounds with [`catalina::Quirks`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::{NonZeroU32, NonZeroUsize};
+
+use catalina::wgpu::{AdapterInfo, Backend, DeviceType};
+use catalina::{AaSupport, Quirk, Quirks, RendererOptions};
+
+fn adapter(name: &str, vendor: u32, device_type: DeviceType, backend: Backend) -> AdapterInfo {
+    AdapterInfo {
+        name: name.to_owned(),
+        vendor,
+        device: 0,
+        device_type,
+        driver: "Test Driver".to_owned(),
+        driver_info: "1.0".to_owned(),
+        backend,
+    }
+}
+
+fn options() -> RendererOptions {
+    RendererOptions {
+        surface_format: None,
+        use_cpu: false,
+        antialiasing_support: AaSupport::all(),
+        num_init_threads: NonZeroUsize::new(4),
+        gpu_stages_from: None,
+        fine_band_rows: None,
+        frames_in_flight: None,
+        disk_cache: None,
+        small_scenes: None,
+    }
+}
+
+#[test]
+fn builtin_quirks_match_their_adapters() {
+    let metal = adapter(
+        "Apple M1",
+        0x106B,
+        DeviceType::IntegratedGpu,
+        Backend::Metal,
+    );
+    assert_eq!(
+        Quirks::for_adapter(&metal),
+        Quirks {
+            single_threaded_init: true,
+            ..Quirks::NONE
+        }
+    );
+    let software = adapter("llvmpipe", 0x10005, DeviceType::Cpu, Backend::Vulkan);
+    assert_eq!(
+        Quirks::for_adapter(&software),
+        Quirks {
+            use_cpu: true,
+            ..Quirks::NONE
+        }
+    );
+    let discrete = adapter("Radeon", 0x1002, DeviceType::DiscreteGpu, Backend::Vulkan);
+    assert_eq!(Quirks::for_adapter(&discrete), Quirks::NONE);
+}
+
+#[test]
+fn matching_entries_are_combined() {
+    const TABLE: &[Quirk] = &[
+        Quirk {
+            name_contains: Some("mali"),
+            workarounds: Quirks {
+                disable_msaa: true,
+                fine_band_rows: NonZeroU32::new(64),
+                ..Quirks::NONE
+            },
+            ..Quirk::ANY
+        },
+        Quirk {
+            driver_contains: Some("test driver"),
+            workarounds: Quirks {
+                fine_band_rows: NonZeroU32::new(32),
+                ..Quirks::NONE
+            },
+            ..Quirk::ANY
+        },
+        Quirk {
+            backend: Some(Backend::Dx12),
+            workarounds: Quirks {
+                use_cpu: true,
+                ..Quirks::NONE
+            },
+            ..Quirk::ANY
+        },
+    ];
+    let info = adapter(
+        "Mali-G78",
+        0x13B5,
+        DeviceType::IntegratedGpu,
+        Backend::Vulkan,
+    );
+    let quirks = Quirks::from_table(TABLE, &info);
+    // Names and drivers match regardless of case, and the smaller band wins.
+    assert_eq!(
+        quirks,
+        Quirks {
+            disable_msaa: true,
+            fine_band_rows: NonZeroU32::new(32),
+            ..Quirks::NONE
+        }
+    );
+
+    let mut options = options();
+    quirks.apply(&mut options);
+    assert!(!options.use_cpu);
+    assert_eq!(options.num_init_threads, NonZeroUsize::new(4));
+    assert_eq!(options.fine_band_rows, NonZeroU32::new(32));
+    assert!(options.antialiasing_support.area);
+    assert!(!options.antialiasing_support.msaa8);
+    assert!(!options.antialiasing_support.msaa16);
+}
@@ -0,0 +1,89 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of [`catalina::Renderer::describe_passes`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Blob, Fill, Image, ImageFormat};
+use catalina::util::RenderContext;
+use catalina::{RenderParams, Scene};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+const SIZE: u32 = 32;
+
+fn scene() -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(2.0, 2.0, 20.0, 20.0),
+    );
+    let data = [255_u8, 0, 0, 255].repeat(16);
+    let image = Image::new(Blob::new(Arc::new(data)), ImageFormat::Rgba8, 4, 4);
+    scene.draw_image(&image, Affine::translate((12.0, 12.0)));
+    scene
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn describing_passes_leaves_renderer_unchanged() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("describe_passes", SIZE, SIZE);
+    let scene = scene();
+    let render_params = RenderParams {
+        base_color: palette::css::BLACK,
+        width: SIZE,
+        height: SIZE,
+        antialiasing_method: params.anti_aliasing,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+
+    let mut renderer = create_renderer(&handle.device, &params).unwrap();
+    let expected = render_with(
+        &handle.device,
+        &handle.queue,
+        &mut renderer,
+        &scene,
+        &params,
+    )
+    .unwrap();
+    let labels = |described: &catalina::Renderer| {
+        described
+            .describe_passes(&scene, &render_params)
+            .passes
+            .iter()
+            .map(|pass| pass.label)
+            .collect::<Vec<_>>()
+    };
+    let first = labels(&renderer);
+    assert!(first.contains(&"catalina.coarse"));
+    assert_eq!(labels(&renderer), first);
+
+    // The image is still in the kept atlas, so the next frame matches the first.
+    let after = render_with(
+        &handle.device,
+        &handle.queue,
+        &mut renderer,
+        &scene,
+        &params,
+    )
+    .unwrap();
+    assert_eq!(after.data.data(), expected.data.data());
+}
@@ -0,0 +1,119 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of positioning ruby annotations with [`catalina::RubyLayout`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::Point;
+use catalina::peniko::{Blob, Font};
+use catalina::{RubyAlign, RubyLayout, RubyPosition};
+
+const ROBOTO: &[u8] = include_bytes!("../../examples/assets/roboto/Roboto-Regular.ttf");
+/// The glyphs of `A` and `i` in Roboto, whose advances are 1336 and 497 of 2048 units.
+const GLYPH_A: u32 = 37;
+const GLYPH_I: u32 = 77;
+/// A font size at which a font unit of Roboto is 0.01 pixels, so ruby is at 0.005 pixels.
+const FONT_SIZE: f32 = 20.48;
+
+fn roboto() -> Font {
+    Font::new(Blob::new(Arc::new(ROBOTO)), 0)
+}
+
+/// Font units are scaled by a 16.16 fixed point factor, so metrics are only accurate to about
+/// 0.05%.
+fn assert_near(actual: impl Into<f64>, expected: f64) {
+    let actual = actual.into();
+    assert!(
+        (actual - expected).abs() < 1e-3 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn narrow_ruby_is_spread_across_base() {
+    let font = roboto();
+    let layout = RubyLayout::new(&font, FONT_SIZE, &font).unwrap();
+    assert_near(layout.ruby_font_size(), 10.24);
+    let mut pen = Point::new(10.0, 50.0);
+    let run = layout.layout([GLYPH_A], [GLYPH_I, GLYPH_I], &mut pen);
+    assert_near(pen.x, 10.0 + 13.36);
+    assert_eq!(run.base.len(), 1);
+    assert_near(run.base[0].x, 10.0);
+    assert_near(run.base[0].y, 50.0);
+    // The 8.39 pixels left over are split as 1:2:1 around the two ruby glyphs.
+    assert_eq!(run.ruby.len(), 2);
+    assert_near(run.ruby[0].x, 10.0 + 2.0975);
+    assert_near(run.ruby[1].x, 10.0 + 2.0975 + 2.485 + 4.195);
+}
+
+#[test]
+fn narrow_ruby_alignments() {
+    let font = roboto();
+    let ruby_x = |align| {
+        let layout = RubyLayout::new(&font, FONT_SIZE, &font)
+            .unwrap()
+            .with_align(align);
+        let mut pen = Point::ZERO;
+        let run = layout.layout([GLYPH_A], [GLYPH_I, GLYPH_I], &mut pen);
+        (run.ruby[0].x, run.ruby[1].x)
+    };
+    let (start, _) = ruby_x(RubyAlign::Start);
+    assert_near(start, 0.0);
+    let (center, _) = ruby_x(RubyAlign::Center);
+    assert_near(center, (13.36 - 4.97) / 2.0);
+    let (first, last) = ruby_x(RubyAlign::SpaceBetween);
+    assert_near(first, 0.0);
+    assert_near(last, 13.36 - 2.485);
+}
+
+#[test]
+fn wide_ruby_spreads_base() {
+    let font = roboto();
+    let layout = RubyLayout::new(&font, FONT_SIZE, &font).unwrap();
+    let mut pen = Point::ZERO;
+    let run = layout.layout([GLYPH_I], [GLYPH_A, GLYPH_A], &mut pen);
+    assert_near(pen.x, 13.36);
+    assert_near(run.base[0].x, (13.36 - 4.97) / 2.0);
+    assert_near(run.ruby[0].x, 0.0);
+    assert_near(run.ruby[1].x, 6.68);
+}
+
+#[test]
+fn wide_ruby_overhangs_neighbors() {
+    let font = roboto();
+    let layout = RubyLayout::new(&font, FONT_SIZE, &font)
+        .unwrap()
+        .with_max_overhang(10.24);
+    let mut pen = Point::ZERO;
+    let run = layout.layout([GLYPH_I], [GLYPH_A, GLYPH_A], &mut pen);
+    assert_near(pen.x, 4.97);
+    assert_near(run.base[0].x, 0.0);
+    assert_near(run.ruby[0].x, -(13.36 - 4.97) / 2.0);
+
+    // A smaller overhang leaves the rest of the ruby's width to the base.
+    let layout = layout.with_max_overhang(1.0);
+    let mut short_pen = Point::ZERO;
+    let short = layout.layout([GLYPH_I], [GLYPH_A, GLYPH_A], &mut short_pen);
+    assert_near(short_pen.x, 13.36 - 2.0);
+    assert_near(short.ruby[0].x, -1.0);
+}
+
+#[test]
+fn ruby_position() {
+    let font = roboto();
+    let layout = RubyLayout::new(&font, FONT_SIZE, &font).unwrap();
+    let over = layout.layout([GLYPH_A], [GLYPH_I], &mut Point::new(0.0, 100.0));
+    assert!(over.ruby[0].y < 100.0 - FONT_SIZE / 2.0);
+    let layout = layout.with_position(RubyPosition::Under);
+    let under = layout.layout([GLYPH_A], [GLYPH_I], &mut Point::new(0.0, 100.0));
+    assert!(under.ruby[0].y > 100.0 + FONT_SIZE / 4.0);
+}
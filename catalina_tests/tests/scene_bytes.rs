@@ -0,0 +1,98 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of serializing scenes with [`Scene::to_bytes`].
+
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Blob, Fill, Image, ImageFormat, Mix};
+use catalina::{Error, Filter, Scene};
+use catalina_tests::{encode_test_scene, TestParams};
+use scenes::test_scenes;
+
+const RECT: Rect = Rect::new(0.0, 0.0, 16.0, 16.0);
+
+#[test]
+fn test_scenes_round_trip() {
+    for scene in test_scenes::test_scenes().scenes {
+        let name = scene.config.name.clone();
+        let scene = encode_test_scene(scene, &mut TestParams::new(&name, 256, 256));
+        match scene.to_bytes() {
+            Ok(bytes) => {
+                let decoded = Scene::from_bytes(&bytes)
+                    .unwrap_or_else(|error| panic!("{name} couldn't be read: {error}"));
+                assert_eq!(decoded.to_bytes().unwrap(), bytes, "{name}");
+            }
+            Err(Error::UnserializableScene(_)) => {}
+            Err(error) => panic!("{name} couldn't be serialized: {error}"),
+        }
+    }
+}
+
+#[test]
+fn filtered_layers_are_rejected() {
+    let mut scene = Scene::new();
+    scene.push_layer_with_filter(Mix::Normal, 1.0, Affine::IDENTITY, &RECT, Filter::blur(2.0));
+    assert!(matches!(
+        scene.to_bytes(),
+        Err(Error::UnserializableScene(_))
+    ));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &RECT,
+    );
+    scene.pop_layer();
+    assert!(matches!(
+        scene.to_bytes(),
+        Err(Error::UnserializableScene(_))
+    ));
+}
+
+#[test]
+fn masked_layers_are_serialized_once_popped() {
+    let mask = Image::new(Blob::new(Arc::new(vec![255; 4])), ImageFormat::Rgba8, 1, 1);
+    let mut scene = Scene::new();
+    scene.push_layer_with_mask(
+        Mix::Normal,
+        1.0,
+        Affine::IDENTITY,
+        &RECT,
+        &mask,
+        Affine::scale(16.0),
+    );
+    assert!(scene.to_bytes().is_err());
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &RECT,
+    );
+    scene.pop_layer();
+    let bytes = scene.to_bytes().unwrap();
+    assert_eq!(
+        Scene::from_bytes(&bytes).unwrap().to_bytes().unwrap(),
+        bytes
+    );
+}
+
+#[test]
+fn textures_are_rejected() {
+    // Images of cached layers and external textures have no pixels of their own.
+    let texture = Image::new(Blob::new(Arc::new(vec![])), ImageFormat::Rgba8, 4, 4);
+    let mut scene = Scene::new();
+    scene.draw_image(&texture, Affine::IDENTITY);
+    assert!(matches!(
+        scene.to_bytes(),
+        Err(Error::UnserializableScene(_))
+    ));
+}
@@ -0,0 +1,97 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of shaping text with OpenType features using [`catalina::SimpleShaper`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::Point;
+use catalina::peniko::{Blob, Font};
+use catalina::SimpleShaper;
+
+const ROBOTO: &[u8] = include_bytes!("../../examples/assets/roboto/Roboto-Regular.ttf");
+
+fn roboto() -> Font {
+    Font::new(Blob::new(Arc::new(ROBOTO)), 0)
+}
+
+#[test]
+fn no_features_maps_characters() {
+    let font = roboto();
+    let shaper = SimpleShaper::new(&font, 16.0, &[]).unwrap();
+    let glyphs = shaper.glyphs("fi");
+    assert_eq!(glyphs.len(), 2);
+    assert_ne!(glyphs[0], glyphs[1]);
+}
+
+#[test]
+fn liga_forms_ligatures() {
+    let font = roboto();
+    let plain = SimpleShaper::new(&font, 16.0, &[]).unwrap();
+    let shaper = SimpleShaper::new(&font, 16.0, &[*b"liga"]).unwrap();
+    let glyphs = shaper.glyphs("fi");
+    assert_eq!(glyphs.len(), 1);
+    assert!(!plain.glyphs("fi").contains(&glyphs[0]));
+    // Only sequences with a ligature are replaced.
+    assert_eq!(shaper.glyphs("ab"), plain.glyphs("ab"));
+}
+
+#[test]
+fn single_substitution_features() {
+    let font = roboto();
+    let plain = SimpleShaper::new(&font, 16.0, &[]).unwrap();
+    for feature in [*b"smcp", *b"tnum", *b"ss01"] {
+        let shaper = SimpleShaper::new(&font, 16.0, &[feature]).unwrap();
+        let text = "abcdefghijklmnopqrstuvwxyz0123456789";
+        let glyphs = shaper.glyphs(text);
+        assert_eq!(glyphs.len(), text.chars().count());
+        assert_ne!(
+            glyphs,
+            plain.glyphs(text),
+            "{} had no effect",
+            String::from_utf8_lossy(&feature)
+        );
+    }
+}
+
+#[test]
+fn small_caps_replace_lowercase() {
+    let font = roboto();
+    let plain = SimpleShaper::new(&font, 16.0, &[]).unwrap();
+    let shaper = SimpleShaper::new(&font, 16.0, &[*b"smcp"]).unwrap();
+    let small_caps = shaper.glyphs("a");
+    assert_ne!(small_caps, plain.glyphs("a"));
+    // Capitals are kept.
+    assert_eq!(shaper.glyphs("A"), plain.glyphs("A"));
+}
+
+#[test]
+fn missing_features_are_ignored() {
+    let font = roboto();
+    let plain = SimpleShaper::new(&font, 16.0, &[]).unwrap();
+    let shaper = SimpleShaper::new(&font, 16.0, &[*b"zzzz"]).unwrap();
+    assert_eq!(shaper.glyphs("office"), plain.glyphs("office"));
+}
+
+#[test]
+fn shape_advances_the_pen() {
+    let font = roboto();
+    let shaper = SimpleShaper::new(&font, 16.0, &[*b"liga"]).unwrap();
+    let mut pen = Point::new(10.0, 30.0);
+    let glyphs = shaper.shape("office", &mut pen);
+    // "ffi" becomes one glyph.
+    assert!(glyphs.len() < 6);
+    assert_eq!(glyphs[0].x, 10.0);
+    assert!(glyphs.iter().all(|glyph| glyph.y == 30.0));
+    assert!(glyphs.windows(2).all(|pair| pair[0].x < pair[1].x));
+    assert!(pen.x > f64::from(glyphs[glyphs.len() - 1].x));
+    assert_eq!(pen.y, 30.0);
+}
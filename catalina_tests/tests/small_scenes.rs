@@ -0,0 +1,121 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the fast path for small scenes, [`catalina::RendererOptions::small_scenes`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroUsize;
+
+use catalina::kurbo::{Affine, BezPath, Circle, Rect, Stroke};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{Renderer, RendererOptions, Scene, SmallSceneLimits};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+const SIZE: u32 = 32;
+
+/// An arrow cursor with an outline, within the default limits.
+fn cursor() -> Scene {
+    let mut arrow = BezPath::new();
+    arrow.move_to((4.0, 2.0));
+    arrow.line_to((4.0, 26.0));
+    arrow.line_to((10.0, 20.0));
+    arrow.line_to((20.0, 20.0));
+    arrow.close_path();
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &arrow,
+    );
+    scene.stroke(
+        &Stroke::new(1.5),
+        Affine::IDENTITY,
+        palette::css::BLACK,
+        None,
+        &arrow,
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Circle::new((24.0, 24.0), 6.0),
+    );
+    scene
+}
+
+/// A grid of more paths than the default limits allow.
+fn grid() -> Scene {
+    let mut scene = Scene::new();
+    for y in 0..8 {
+        for x in 0..8 {
+            let (x, y) = (f64::from(x) * 4.0, f64::from(y) * 4.0);
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                palette::css::BLUE,
+                None,
+                &Rect::new(x, y, x + 3.0, y + 3.0),
+            );
+        }
+    }
+    scene
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn small_scenes_match_the_gpu() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("small_scenes", SIZE, SIZE);
+    let mut reference = create_renderer(&handle.device, &params).unwrap();
+    let mut renderer = Renderer::new(
+        &handle.device,
+        RendererOptions {
+            surface_format: None,
+            use_cpu: false,
+            num_init_threads: NonZeroUsize::new(1),
+            gpu_stages_from: None,
+            fine_band_rows: None,
+            frames_in_flight: None,
+            disk_cache: None,
+            small_scenes: Some(SmallSceneLimits::default()),
+            antialiasing_support: std::iter::once(params.anti_aliasing).collect(),
+        },
+    )
+    .unwrap();
+    // The grid takes the usual path, between frames which take the fast one.
+    for scene in [cursor(), grid(), cursor()] {
+        let expected = render_with(
+            &handle.device,
+            &handle.queue,
+            &mut reference,
+            &scene,
+            &params,
+        )
+        .unwrap();
+        let image = render_with(
+            &handle.device,
+            &handle.queue,
+            &mut renderer,
+            &scene,
+            &params,
+        )
+        .unwrap();
+        // The CPU stages round differently from the GPU in places.
+        for (a, b) in image.data.data().iter().zip(expected.data.data()) {
+            assert!(a.abs_diff(*b) <= 2, "{a} != {b}");
+        }
+    }
+}
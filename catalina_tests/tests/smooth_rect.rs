@@ -0,0 +1,51 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the outline of [`catalina::SmoothRect`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::f64::consts::PI;
+
+use catalina::kurbo::{PathEl, Point, Rect, RoundedRect, Shape};
+use catalina::SmoothRect;
+
+const RECT: Rect = Rect::new(0.0, 0.0, 100.0, 60.0);
+
+#[test]
+fn unsmoothed_matches_rounded_rect() {
+    let rounded = RoundedRect::from_rect(RECT, 10.0);
+    let smooth = SmoothRect::from(rounded);
+    assert!((smooth.area() - rounded.area()).abs() < 0.1);
+    assert_eq!(smooth.bounding_box(), RECT);
+}
+
+#[test]
+fn smoothing_extends_corners_along_sides() {
+    let smooth = SmoothRect::ios(RECT, 10.0);
+    let Some(PathEl::MoveTo(start)) = smooth.path_elements(0.1).next() else {
+        panic!("the outline starts with a move");
+    };
+    assert!((start - Point::new(16.0, 0.0)).hypot() < 1e-9);
+    // The smoothed corners cut slightly further into the rectangle.
+    let rounded = RoundedRect::from_rect(RECT, 10.0).area();
+    assert!(smooth.area() < rounded);
+    assert!(smooth.area() > rounded - 10.0);
+    assert_eq!(smooth.winding(Point::new(50.0, 30.0)), 1);
+    assert_eq!(smooth.winding(Point::new(0.5, 0.5)), 0);
+}
+
+#[test]
+fn smoothing_is_limited_by_size() {
+    // Corners which meet in the middle of each side leave no room for smoothing, so this
+    // is a circle.
+    let smooth = SmoothRect::from_rect(Rect::new(0.0, 0.0, 40.0, 40.0), 100.0, 1.0);
+    // The arcs are approximated by cubic Béziers, which are slightly outside of the circle.
+    assert!((smooth.area() / (PI * 400.0) - 1.0).abs() < 1e-3);
+}
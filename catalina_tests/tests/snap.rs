@@ -0,0 +1,94 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of snapping geometry to device pixels with [`catalina::PixelSnap`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Line, Point, Rect};
+use catalina::PixelSnap;
+
+#[test]
+fn fills_snap_to_pixel_boundaries() {
+    let snap = PixelSnap::new(Affine::translate((0.25, 0.0)));
+    assert_eq!(
+        snap.rect(Rect::new(1.0, 1.4, 5.5, 3.6)),
+        Rect::new(0.75, 1.0, 5.75, 4.0)
+    );
+    // At a scale factor of 2, half pixels in user space are whole device pixels.
+    let hidpi = PixelSnap::new(Affine::scale(2.0));
+    assert_eq!(hidpi.point(Point::new(1.2, 0.8)), Point::new(1.0, 1.0));
+}
+
+#[test]
+fn odd_strokes_are_centered_on_pixels() {
+    let snap = PixelSnap::new(Affine::IDENTITY);
+    let (rect, stroke) = snap.stroke_rect(Rect::new(2.0, 2.0, 10.0, 6.0), 1.0);
+    assert_eq!(rect, Rect::new(2.5, 2.5, 10.5, 6.5));
+    assert_eq!(stroke.width, 1.0);
+    // Even widths already have their edges on boundaries when centered on one.
+    let (even_rect, even_stroke) = snap.stroke_rect(Rect::new(2.2, 2.0, 10.0, 6.0), 2.0);
+    assert_eq!(even_rect, Rect::new(2.0, 2.0, 10.0, 6.0));
+    assert_eq!(even_stroke.width, 2.0);
+
+    let (line, _) = snap.line(Line::new((0.2, 4.0), (8.7, 4.0)), 1.0);
+    assert_eq!(line, Line::new((0.0, 4.5), (9.0, 4.5)));
+    let (thick, _) = snap.line(Line::new((3.0, 0.0), (3.0, 8.0)), 3.0);
+    assert_eq!(thick, Line::new((3.5, 0.0), (3.5, 8.0)));
+}
+
+#[test]
+fn hairlines_keep_their_weight() {
+    let snap = PixelSnap::new(Affine::scale(2.0));
+    let stroke = snap.stroke(0.25);
+    assert_eq!(stroke.width, 0.5);
+    assert!((stroke.alpha - 0.5).abs() < 1e-6);
+    let wide = snap.stroke(1.2);
+    assert_eq!(wide.width, 1.0);
+    assert_eq!(wide.alpha, 1.0);
+}
+
+#[test]
+fn rotated_geometry_is_unchanged() {
+    let snap = PixelSnap::new(Affine::rotate(0.5));
+    assert!(!snap.is_axis_aligned());
+    let rect = Rect::new(0.3, 0.3, 4.7, 4.7);
+    assert_eq!(snap.rect(rect), rect);
+    assert_eq!(snap.stroke(0.3).width, 0.3);
+}
+
+#[test]
+fn unscaled_images_snap_to_whole_pixels() {
+    let snap = PixelSnap::new(Affine::translate((0.25, 0.0)));
+    // The image is at (3.65, 2.6) on the device, and moves to (4, 3).
+    let image = snap.image(Affine::translate((3.4, 2.6)));
+    assert_eq!(image, Affine::translate((3.75, 3.0)));
+    // Flipped images are snapped too.
+    let flipped =
+        PixelSnap::new(Affine::IDENTITY).image(Affine::new([-1.0, 0.0, 0.0, 1.0, 7.7, 0.2]));
+    assert_eq!(flipped, Affine::new([-1.0, 0.0, 0.0, 1.0, 8.0, 0.0]));
+    // At a scale factor of 2, images drawn at half size are unscaled on the device.
+    let hidpi = PixelSnap::new(Affine::scale(2.0));
+    let half_size = hidpi.image(Affine::translate((1.2, 0.0)) * Affine::scale(0.5));
+    assert_eq!(
+        half_size,
+        Affine::translate((1.0, 0.0)) * Affine::scale(0.5)
+    );
+}
+
+#[test]
+fn scaled_images_are_unchanged() {
+    let snap = PixelSnap::new(Affine::IDENTITY);
+    for transform in [
+        Affine::scale(1.5).then_translate((0.3, 0.3).into()),
+        Affine::rotate(0.1),
+    ] {
+        assert_eq!(snap.image(transform), transform);
+    }
+}
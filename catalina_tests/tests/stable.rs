@@ -0,0 +1,31 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests that scenes can be built using only the [`catalina::stable`] facade.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::stable::kurbo::{Affine, Rect};
+use catalina::stable::peniko::{color::palette, Fill};
+use catalina::stable::Scene;
+
+#[test]
+fn build_scene() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    let mut outer = Scene::new();
+    outer.append(&scene, Some(Affine::translate((5.0, 5.0))));
+    assert!(!outer.encoding().is_empty());
+}
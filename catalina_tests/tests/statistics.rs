@@ -0,0 +1,80 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of the statistics of rendered frames.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{create_target_texture, RenderContext};
+use catalina::wgpu;
+use catalina::{AaConfig, FrameStatistics, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const SIZE: u32 = 16;
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn half_white_frame() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &Rect::new(0.0, 0.0, f64::from(SIZE / 2), f64::from(SIZE)),
+    );
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("statistics", SIZE, SIZE)).unwrap();
+    let texture = create_target_texture(&handle.device, SIZE, SIZE);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer
+        .render_to_texture(
+            &handle.device,
+            &handle.queue,
+            &scene,
+            &view,
+            &RenderParams {
+                base_color: palette::css::BLACK,
+                width: SIZE,
+                height: SIZE,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+        )
+        .unwrap();
+    let statistics = renderer
+        .frame_statistics(&handle.device, &handle.queue, &texture)
+        .unwrap();
+
+    let half = SIZE * SIZE / 2;
+    assert_eq!(statistics.histogram[0], half);
+    // White has a luminance of 1, three quarters of the way along the histogram, which may
+    // round down into the previous bin.
+    assert_eq!(statistics.histogram[191..=192].iter().sum::<u32>(), half);
+    assert_eq!(statistics.histogram.iter().sum::<u32>(), SIZE * SIZE);
+    assert!((statistics.average_luminance - 0.5).abs() < 1e-3);
+    // Colors are averaged in linear light.
+    let [r, g, b, a] = statistics.average.components;
+    assert!([r, g, b]
+        .iter()
+        .all(|channel| (channel - 0.735).abs() < 1e-2));
+    assert!((a - 1.0).abs() < 1e-3);
+    assert!(statistics.luminance_percentile(0.4) < 1e-3);
+    let bright = statistics.luminance_percentile(0.9);
+    assert!(bright >= FrameStatistics::bin_luminance(191));
+    assert!(bright <= FrameStatistics::bin_luminance(192));
+}
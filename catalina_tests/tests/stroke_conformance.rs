@@ -0,0 +1,265 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Conformance of stroke expansion against the CPU reference in [`kurbo::stroke`].
+//!
+//! Each case strokes a path directly, and separately fills the outline computed by kurbo,
+//! using the same pipeline. Any difference between the two is due to stroke expansion, so
+//! these tests catch regressions in cusps, tight joins and inner loops which are too subtle
+//! to notice in the snapshot tests.
+//!
+//! [`kurbo::stroke`]: catalina::kurbo::stroke
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{stroke, Affine, BezPath, Cap, Circle, Join, Shape, Stroke, StrokeOpts};
+use catalina::peniko::{color::palette, Fill, ImageFormat};
+use catalina::Scene;
+use catalina_tests::TestParams;
+
+const SIZE: u32 = 100;
+
+/// The tolerance used when computing the reference outline.
+const REFERENCE_TOLERANCE: f64 = 0.01;
+
+/// The allowed difference between the pipeline's stroke and the reference.
+struct Tolerance {
+    /// The largest allowed mean difference in coverage, in `0..=1`.
+    mean: f64,
+    /// The largest allowed fraction of pixels whose coverage differs by more than a half.
+    bad_pixels: f64,
+}
+
+const TIGHT: Tolerance = Tolerance {
+    mean: 0.002,
+    bad_pixels: 0.0,
+};
+
+/// For cases where flattening differences near singularities are expected to move a few
+/// edge pixels.
+const LOOSE: Tolerance = Tolerance {
+    mean: 0.005,
+    bad_pixels: 0.002,
+};
+
+fn coverage(scene: &Scene, name: &str, use_cpu: bool) -> Vec<f64> {
+    let params = TestParams {
+        use_cpu,
+        base_color: Some(palette::css::BLACK),
+        ..TestParams::new(name, SIZE, SIZE)
+    };
+    let image = catalina_tests::render_then_debug_sync(scene, &params).unwrap();
+    assert_eq!(image.format, ImageFormat::Rgba8);
+    image
+        .data
+        .data()
+        .chunks_exact(4)
+        .map(|pixel| f64::from(pixel[0]) / 255.0)
+        .collect()
+}
+
+fn stroke_conformance(
+    name: &str,
+    path: &impl Shape,
+    style: &Stroke,
+    tolerance: &Tolerance,
+    use_cpu: bool,
+) {
+    let mut stroked = Scene::new();
+    stroked.stroke(style, Affine::IDENTITY, palette::css::WHITE, None, path);
+    let actual = coverage(&stroked, &format!("{name}_stroke"), use_cpu);
+
+    let outline = stroke(
+        path.path_elements(REFERENCE_TOLERANCE),
+        style,
+        &StrokeOpts::default(),
+        REFERENCE_TOLERANCE,
+    );
+    let mut filled = Scene::new();
+    filled.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &outline,
+    );
+    let expected = coverage(&filled, &format!("{name}_reference"), use_cpu);
+
+    let diffs: Vec<f64> = actual
+        .iter()
+        .zip(&expected)
+        .map(|(a, b)| (a - b).abs())
+        .collect();
+    let mean = diffs.iter().sum::<f64>() / diffs.len() as f64;
+    let bad_pixels = diffs.iter().filter(|d| **d > 0.5).count() as f64 / diffs.len() as f64;
+    assert!(
+        mean <= tolerance.mean,
+        "{name}: mean coverage difference {mean} exceeds {}",
+        tolerance.mean
+    );
+    assert!(
+        bad_pixels <= tolerance.bad_pixels,
+        "{name}: {bad_pixels} of pixels differ by more than half, expected at most {}",
+        tolerance.bad_pixels
+    );
+}
+
+fn path(svg: &str) -> BezPath {
+    BezPath::from_svg(svg).unwrap()
+}
+
+/// A cubic whose control points cross over, giving a cusp in the middle.
+fn cusp(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_cusp",
+        &path("M20 80 C80 20 20 20 80 80"),
+        &Stroke::new(12.0),
+        &LOOSE,
+        use_cpu,
+    );
+}
+
+/// A cubic with a small loop, which is much tighter than the stroke width.
+fn inner_loop(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_inner_loop",
+        &path("M10 70 C90 10 10 10 90 70"),
+        &Stroke::new(20.0).with_join(Join::Round),
+        &LOOSE,
+        use_cpu,
+    );
+}
+
+/// A curve which bends more tightly than half the stroke width.
+fn tight_curve(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_tight_curve",
+        &path("M20 50 C50 10 55 90 80 50"),
+        &Stroke::new(30.0).with_caps(Cap::Round),
+        &LOOSE,
+        use_cpu,
+    );
+}
+
+/// A very acute miter join, just within the miter limit.
+fn acute_miter(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_acute_miter",
+        &path("M10 30 L90 45 L10 60"),
+        &Stroke::new(6.0)
+            .with_join(Join::Miter)
+            .with_miter_limit(12.0),
+        &TIGHT,
+        use_cpu,
+    );
+}
+
+/// A very acute miter join beyond the miter limit, which falls back to a bevel.
+fn acute_miter_clipped(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_acute_miter_clipped",
+        &path("M10 40 L90 50 L10 60"),
+        &Stroke::new(6.0)
+            .with_join(Join::Miter)
+            .with_miter_limit(4.0),
+        &TIGHT,
+        use_cpu,
+    );
+}
+
+/// A line which doubles back on itself, with square caps.
+fn reversal(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_reversal",
+        &path("M10 50 L90 50 L30 50"),
+        &Stroke::new(10.0)
+            .with_join(Join::Round)
+            .with_caps(Cap::Square),
+        &TIGHT,
+        use_cpu,
+    );
+}
+
+/// Zero length segments, which should draw a round cap as a dot.
+fn degenerate_segments(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_degenerate_segments",
+        &path("M30 30 L30 30 M70 70 C70 70 70 70 70 70"),
+        &Stroke::new(16.0).with_caps(Cap::Round),
+        &TIGHT,
+        use_cpu,
+    );
+}
+
+/// A cubic whose control points coincide with its end points.
+fn coincident_controls(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_coincident_controls",
+        &path("M15 15 C15 15 85 85 85 85"),
+        &Stroke::new(8.0).with_caps(Cap::Butt),
+        &TIGHT,
+        use_cpu,
+    );
+}
+
+/// A stroke much thinner than a pixel.
+fn hairline(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_hairline",
+        &path("M10 20 C40 90 60 -10 90 80"),
+        &Stroke::new(0.3),
+        &TIGHT,
+        use_cpu,
+    );
+}
+
+/// A dash pattern on a curve, with dashes ending inside joins.
+fn dashed_circle(use_cpu: bool) {
+    stroke_conformance(
+        "stroke_dashed_circle",
+        &Circle::new((50.0, 50.0), 35.0),
+        &Stroke::new(6.0)
+            .with_caps(Cap::Round)
+            .with_dashes(3.0, [10.0, 7.0]),
+        &LOOSE,
+        use_cpu,
+    );
+}
+
+macro_rules! conformance_tests {
+    ($($case:ident: $gpu:ident, $cpu:ident;)*) => {
+        $(
+            #[test]
+            #[cfg_attr(skip_gpu_tests, ignore)]
+            fn $gpu() {
+                $case(false);
+            }
+
+            #[test]
+            // The fine shader still requires a GPU, and so we still get a wgpu device
+            #[cfg_attr(skip_gpu_tests, ignore)]
+            fn $cpu() {
+                $case(true);
+            }
+        )*
+    };
+}
+
+conformance_tests! {
+    cusp: cusp_gpu, cusp_cpu;
+    inner_loop: inner_loop_gpu, inner_loop_cpu;
+    tight_curve: tight_curve_gpu, tight_curve_cpu;
+    acute_miter: acute_miter_gpu, acute_miter_cpu;
+    acute_miter_clipped: acute_miter_clipped_gpu, acute_miter_clipped_cpu;
+    reversal: reversal_gpu, reversal_cpu;
+    degenerate_segments: degenerate_segments_gpu, degenerate_segments_cpu;
+    coincident_controls: coincident_controls_gpu, coincident_controls_cpu;
+    hairline: hairline_gpu, hairline_cpu;
+    dashed_circle: dashed_circle_gpu, dashed_circle_cpu;
+}
@@ -0,0 +1,72 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of [`catalina::Renderer::render_to_texture_supersampled`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::{create_target_texture, read_texture, RenderContext};
+use catalina::wgpu;
+use catalina::{AaConfig, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+const WIDTH: u32 = 8;
+const HEIGHT: u32 = 4;
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn supersampling_removes_conflation_artifacts() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("supersample", 1, 1)).unwrap();
+    // Two black rectangles which meet in the middle of a column of pixels. With area
+    // antialiasing, each covers half of the column, and the white background shows through
+    // the seam.
+    let mut scene = Scene::new();
+    for (x0, x1) in [(0.0, 4.5), (4.5, 8.0)] {
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            palette::css::BLACK,
+            None,
+            &Rect::new(x0, 0.0, x1, f64::from(HEIGHT)),
+        );
+    }
+    let params = RenderParams {
+        base_color: palette::css::WHITE,
+        width: WIDTH,
+        height: HEIGHT,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    let mut render = |supersample| {
+        let texture = create_target_texture(device, WIDTH, HEIGHT);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        renderer
+            .render_to_texture_supersampled(device, queue, &scene, &view, &params, supersample)
+            .unwrap();
+        read_texture(device, queue, &texture).unwrap()
+    };
+    let seam = |pixels: &[u8]| pixels[4 * 4];
+
+    let single = render(1);
+    assert!(seam(&single) > 16, "{}", seam(&single));
+    assert_eq!(render(0), single);
+    // At twice the resolution, the seam is on a pixel boundary.
+    let supersampled = render(2);
+    assert_eq!(seam(&supersampled), 0);
+    assert!(supersampled.chunks(4).all(|pixel| pixel == [0, 0, 0, 255]));
+}
@@ -0,0 +1,71 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of caching text runs with [`catalina::TextRunCache`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::{Scene, TextRunCache, TextRunKey};
+
+/// Stands in for laying out and drawing a label, counting how often it happens.
+fn layout(text: &str, layouts: &mut u32, scene: &mut Scene) {
+    *layouts += 1;
+    let width = text.len() as f64 * 8.0;
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLACK,
+        None,
+        &Rect::new(0.0, 0.0, width, 12.0),
+    );
+}
+
+#[test]
+fn unchanged_runs_are_laid_out_once() {
+    let mut cache = TextRunCache::new();
+    let mut layouts = 0;
+    let save = TextRunKey::new("Save", 14_u32, None);
+    let wrapped = TextRunKey::new("Save", 14_u32, Some(20.0));
+    for _ in 0..3 {
+        let mut scene = Scene::new();
+        for (ix, key) in [&save, &save, &wrapped].into_iter().enumerate() {
+            let transform = Affine::translate((0.0, ix as f64 * 20.0));
+            cache.append(&mut scene, key, transform, |run| {
+                layout(&key.text, &mut layouts, run);
+            });
+        }
+        assert!(!scene.encoding().is_empty());
+        cache.evict_unused();
+    }
+    // The key includes the width the text is laid out in.
+    assert_eq!(layouts, 2);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn unused_runs_are_evicted() {
+    let mut cache = TextRunCache::new();
+    let mut layouts = 0;
+    let key = TextRunKey::new("Cancel", 14_u32, None);
+    let mut scene = Scene::new();
+    cache.append(&mut scene, &key, Affine::IDENTITY, |run| {
+        layout(&key.text, &mut layouts, run);
+    });
+    cache.evict_unused();
+    assert_eq!(cache.len(), 1);
+    // A frame in which the run isn't drawn.
+    cache.evict_unused();
+    assert!(cache.is_empty());
+    cache.append(&mut scene, &key, Affine::IDENTITY, |run| {
+        layout(&key.text, &mut layouts, run);
+    });
+    assert_eq!(layouts, 2);
+}
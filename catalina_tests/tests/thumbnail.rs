@@ -0,0 +1,76 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of rendering thumbnails incrementally with [`ThumbnailJob`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Rect};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{Scene, ThumbnailJob};
+use catalina_tests::{create_renderer, TestParams};
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn thumbnails_are_rendered_a_tile_at_a_time() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("thumbnail", 1, 1)).unwrap();
+    // Red on the left half of the content and blue on the right.
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(0.0, 0.0, 200.0, 100.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Rect::new(200.0, 0.0, 400.0, 100.0),
+    );
+    let mut job = ThumbnailJob::new(
+        device,
+        &scene,
+        Rect::new(0.0, 0.0, 400.0, 100.0),
+        40,
+        palette::css::WHITE,
+    )
+    .with_tile_size(device, 16);
+    assert_eq!((job.width(), job.height()), (40, 10));
+
+    // The first tile is drawn before the rest of the thumbnail.
+    assert!(!job.step(&mut renderer, device, queue).unwrap());
+    let partial = job.read_pixels(device, queue).unwrap();
+    let at = |pixels: &[u8], x: u32, y: u32| -> [u8; 4] {
+        let index = ((y * 40 + x) * 4) as usize;
+        pixels[index..index + 4].try_into().unwrap()
+    };
+    assert_eq!(at(&partial, 8, 5), [255, 0, 0, 255]);
+    assert_eq!(at(&partial, 30, 5), [0, 0, 0, 0]);
+
+    // The thumbnail is 3 tiles wide and 1 tall.
+    assert!(!job.step(&mut renderer, device, queue).unwrap());
+    assert!(job.step(&mut renderer, device, queue).unwrap());
+    assert!(job.is_finished());
+    assert!(job.step(&mut renderer, device, queue).unwrap());
+    let finished = job.read_pixels(device, queue).unwrap();
+    for y in 0..10 {
+        assert_eq!(at(&finished, 0, y), [255, 0, 0, 255]);
+        assert_eq!(at(&finished, 19, y), [255, 0, 0, 255]);
+        assert_eq!(at(&finished, 20, y), [0, 0, 255, 255]);
+        assert_eq!(at(&finished, 39, y), [0, 0, 255, 255]);
+    }
+}
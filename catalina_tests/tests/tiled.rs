@@ -0,0 +1,208 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of rendering images tile by tile with [`catalina::Renderer::render_tiled`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::num::NonZeroU32;
+
+use catalina::kurbo::{Affine, Circle, Rect};
+use catalina::peniko::{color::palette, Fill, Mix};
+use catalina::util::{render_to_pixels, RenderContext};
+use catalina::{AaConfig, Error, Filter, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+fn params(width: u32, height: u32) -> RenderParams {
+    RenderParams {
+        base_color: palette::css::WHITE,
+        width,
+        height,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    }
+}
+
+fn tile_size(width: u32, height: u32) -> (NonZeroU32, NonZeroU32) {
+    (
+        NonZeroU32::new(width).unwrap(),
+        NonZeroU32::new(height).unwrap(),
+    )
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn tiles_assemble_into_the_image() {
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 48;
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Circle::new((30.0, 25.0), 20.5),
+    );
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("tiled", 1, 1)).unwrap();
+    let params = params(WIDTH, HEIGHT);
+    let expected = render_to_pixels(&mut renderer, device, queue, &scene, &params).unwrap();
+
+    let mut tiles = Vec::new();
+    let mut assembled = vec![0; expected.len()];
+    renderer
+        .render_tiled(device, queue, &scene, &params, tile_size(24, 20), |tile| {
+            let pixels = tile.read_pixels(device, queue)?;
+            // The tile can be read back more than once.
+            assert_eq!(tile.read_pixels(device, queue)?, pixels);
+            for (row, tile_row) in pixels.chunks_exact(tile.width as usize * 4).enumerate() {
+                let start = ((tile.y as usize + row) * WIDTH as usize + tile.x as usize) * 4;
+                assembled[start..start + tile_row.len()].copy_from_slice(tile_row);
+            }
+            tiles.push((tile.x, tile.y, tile.width, tile.height));
+            Ok(())
+        })
+        .unwrap();
+    // The tiles at the right and bottom are cut to the size of the image.
+    assert_eq!(
+        tiles,
+        [
+            (0, 0, 24, 20),
+            (24, 0, 24, 20),
+            (48, 0, 16, 20),
+            (0, 20, 24, 20),
+            (24, 20, 24, 20),
+            (48, 20, 16, 20),
+            (0, 40, 24, 8),
+            (24, 40, 24, 8),
+            (48, 40, 16, 8),
+        ]
+    );
+    for (ix, (a, b)) in assembled.iter().zip(&expected).enumerate() {
+        let pixel = u32::try_from(ix / 4).unwrap();
+        let (x, y) = (pixel % WIDTH, pixel / WIDTH);
+        assert!(a.abs_diff(*b) <= 1, "pixel ({x}, {y}) differs: {a} != {b}");
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn tiles_fit_the_device() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("tiled", 1, 1)).unwrap();
+    let limit = device.limits().max_texture_dimension_2d;
+    let params = params(limit + 1, 1);
+    let mut tiles = Vec::new();
+    renderer
+        .render_tiled(
+            device,
+            queue,
+            &Scene::new(),
+            &params,
+            tile_size(u32::MAX, u32::MAX),
+            |tile| {
+                tiles.push((tile.x, tile.width));
+                Ok(())
+            },
+        )
+        .unwrap();
+    assert_eq!(tiles, [(0, limit), (limit, 1)]);
+}
+
+/// A reproduction of <https://github.com/linebender/vello/issues/680>, where only the first
+/// [`catalina::MAX_TARGET_BINS`] bins of a larger target were drawn.
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn targets_with_too_many_bins() {
+    const SIZE: u32 = 256 * 17;
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Rect::new(-5., -5., 256. * 20., 256. * 20.),
+    );
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("many_bins", 1, 1)).unwrap();
+    let params = RenderParams {
+        base_color: palette::css::BLACK,
+        ..params(SIZE, SIZE)
+    };
+    // The target can't be rendered in one pass, rather than being silently cut off.
+    let result = render_to_pixels(&mut renderer, device, queue, &scene, &params);
+    assert!(matches!(result, Err(Error::TargetTooLarge(SIZE, SIZE))));
+    // All 17x17 bins are drawn when it's rendered in tiles.
+    let pixels = renderer
+        .render_to_image_tiled(device, queue, &scene, &params)
+        .unwrap();
+    assert_eq!(pixels.len(), (SIZE * SIZE * 4) as usize);
+    assert!(pixels
+        .chunks_exact(4)
+        .all(|pixel| pixel == [255, 0, 0, 255]));
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn blurs_are_seamless_across_tiles() {
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 48;
+    let mut scene = Scene::new();
+    // The blur spreads the square across the edges of the tiles, so each tile needs the
+    // content of its neighbours.
+    let clip = Rect::new(0.0, 0.0, f64::from(WIDTH), f64::from(HEIGHT));
+    scene.push_layer_with_filter(Mix::Normal, 1.0, Affine::IDENTITY, &clip, Filter::blur(4.0));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::BLUE,
+        None,
+        &Rect::new(8.0, 6.0, 22.0, 18.0),
+    );
+    scene.pop_layer();
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let (device, queue) = (&handle.device, &handle.queue);
+    let mut renderer = create_renderer(device, &TestParams::new("tiled_blur", 1, 1)).unwrap();
+    let params = params(WIDTH, HEIGHT);
+    let expected = render_to_pixels(&mut renderer, device, queue, &scene, &params).unwrap();
+
+    let mut assembled = vec![0; expected.len()];
+    renderer
+        .render_tiled(device, queue, &scene, &params, tile_size(24, 20), |tile| {
+            let pixels = tile.read_pixels(device, queue)?;
+            for (row, tile_row) in pixels.chunks_exact(tile.width as usize * 4).enumerate() {
+                let start = ((tile.y as usize + row) * WIDTH as usize + tile.x as usize) * 4;
+                assembled[start..start + tile_row.len()].copy_from_slice(tile_row);
+            }
+            Ok(())
+        })
+        .unwrap();
+    // The blurred edge of the square crosses the boundary between the first two tiles.
+    let edge = ((12 * WIDTH + 24) * 4) as usize;
+    assert!(expected[edge] > 0 && expected[edge] < 255);
+    for (ix, (a, b)) in assembled.iter().zip(&expected).enumerate() {
+        let pixel = u32::try_from(ix / 4).unwrap();
+        let (x, y) = (pixel % WIDTH, pixel / WIDTH);
+        assert!(a.abs_diff(*b) <= 1, "pixel ({x}, {y}) differs: {a} != {b}");
+    }
+}
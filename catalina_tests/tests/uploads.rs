@@ -0,0 +1,223 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of image uploads spread over several frames, of the image atlas kept between
+//! frames, and of images drawn from external textures.
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::Affine;
+use catalina::peniko::{color::palette, Blob, Image, ImageFormat};
+use catalina::util::RenderContext;
+use catalina::wgpu;
+use catalina::{Placeholder, Renderer, Scene, UploadPriority};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+const SIZE: u32 = 32;
+const IMAGE_BYTES: u64 = SIZE as u64 * SIZE as u64 * 4;
+const PLACEHOLDER: [u8; 4] = [0xC0, 0xC0, 0xC0, 0xFF];
+
+fn solid_image(rgba: [u8; 4]) -> Image {
+    let data: Vec<u8> = rgba.repeat((SIZE * SIZE) as usize);
+    Image::new(Blob::new(Arc::new(data)), ImageFormat::Rgba8, SIZE, SIZE)
+}
+
+/// Draws each image in its own column.
+fn scene(images: &[&Image]) -> Scene {
+    let mut scene = Scene::new();
+    for (ix, image) in images.iter().enumerate() {
+        let x = f64::from(SIZE) * ix as f64;
+        scene.draw_image(image, Affine::translate((x, 0.0)));
+    }
+    scene
+}
+
+struct Frames {
+    context: RenderContext,
+    device_id: usize,
+    renderer: Renderer,
+    params: TestParams,
+}
+
+impl Frames {
+    fn new(name: &str, columns: u32) -> Self {
+        let mut context = RenderContext::new();
+        let device_id = pollster::block_on(context.device(None)).unwrap();
+        let params = TestParams::new(name, SIZE * columns, SIZE);
+        let renderer = create_renderer(&context.devices[device_id].device, &params).unwrap();
+        Self {
+            context,
+            device_id,
+            renderer,
+            params,
+        }
+    }
+
+    /// Renders a frame, returning the center pixel of each column.
+    fn render(&mut self, scene: &Scene) -> Vec<[u8; 4]> {
+        let handle = &self.context.devices[self.device_id];
+        let image = render_with(
+            &handle.device,
+            &handle.queue,
+            &mut self.renderer,
+            scene,
+            &self.params,
+        )
+        .unwrap();
+        let data = image.data.data();
+        let row = (SIZE / 2) as usize * self.params.width as usize * 4;
+        (0..self.params.width / SIZE)
+            .map(|column| {
+                let offset = row + (column * SIZE + SIZE / 2) as usize * 4;
+                data[offset..offset + 4].try_into().unwrap()
+            })
+            .collect()
+    }
+}
+
+const RED: [u8; 4] = [0xFF, 0, 0, 0xFF];
+const GREEN: [u8; 4] = [0, 0xFF, 0, 0xFF];
+const BLUE: [u8; 4] = [0, 0, 0xFF, 0xFF];
+const BLACK: [u8; 4] = [0, 0, 0, 0xFF];
+const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn images_kept_between_frames() {
+    let (red, green) = (solid_image(RED), solid_image(GREEN));
+    let mut frames = Frames::new("uploads_kept", 2);
+    // The second and third frames write nothing to the atlas, and the fourth adds to it.
+    assert_eq!(frames.render(&scene(&[&red])), [RED, BLACK]);
+    assert_eq!(frames.render(&scene(&[&red])), [RED, BLACK]);
+    assert_eq!(frames.render(&scene(&[&red, &red])), [RED, RED]);
+    assert_eq!(frames.render(&scene(&[&green, &red])), [GREEN, RED]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn uploads_spread_over_frames() {
+    let images = [solid_image(RED), solid_image(GREEN), solid_image(BLUE)];
+    let scene = scene(&[&images[0], &images[1], &images[2]]);
+    let mut frames = Frames::new("uploads_spread", 3);
+    frames
+        .renderer
+        .uploads()
+        .set_bytes_per_frame(Some(IMAGE_BYTES));
+    assert_eq!(frames.render(&scene), [RED, PLACEHOLDER, PLACEHOLDER]);
+    assert_eq!(frames.renderer.uploads().len(), 2);
+    assert_eq!(frames.render(&scene), [RED, GREEN, PLACEHOLDER]);
+    assert_eq!(frames.render(&scene), [RED, GREEN, BLUE]);
+    assert!(frames.renderer.uploads().is_empty());
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn uploads_by_priority() {
+    let images = [solid_image(RED), solid_image(GREEN), solid_image(BLUE)];
+    let scene = scene(&[&images[0], &images[1], &images[2]]);
+    let mut frames = Frames::new("uploads_priority", 3);
+    let uploads = frames.renderer.uploads();
+    uploads.set_bytes_per_frame(Some(IMAGE_BYTES));
+    uploads.queue(&images[2], UploadPriority::High);
+    uploads.queue(&images[0], UploadPriority::Immediate);
+    assert_eq!(frames.render(&scene), [RED, PLACEHOLDER, BLUE]);
+    assert_eq!(frames.render(&scene), [RED, GREEN, BLUE]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn placeholders_per_draw() {
+    let images = [RED, GREEN, BLUE, RED].map(solid_image);
+    let mut placeholder_scene = Scene::new();
+    let placeholders = [
+        None,
+        Some(Placeholder::Skip),
+        Some(Placeholder::Color(palette::css::YELLOW)),
+        Some(Placeholder::Checkerboard),
+    ];
+    for (ix, (image, placeholder)) in images.iter().zip(placeholders).enumerate() {
+        let transform = Affine::translate((f64::from(SIZE) * ix as f64, 0.0));
+        match placeholder {
+            Some(placeholder) => {
+                placeholder_scene.draw_image_with_placeholder(image, transform, placeholder);
+            }
+            None => placeholder_scene.draw_image(image, transform),
+        }
+    }
+    let mut frames = Frames::new("uploads_placeholders", 4);
+    frames
+        .renderer
+        .uploads()
+        .set_bytes_per_frame(Some(IMAGE_BYTES));
+    // The first image is uploaded straight away. The center of the checkerboard is the
+    // corner of a light cell.
+    let yellow = [0xFF, 0xFF, 0, 0xFF];
+    assert_eq!(
+        frames.render(&placeholder_scene),
+        [RED, BLACK, yellow, WHITE]
+    );
+    assert_eq!(
+        frames.render(&placeholder_scene),
+        [RED, GREEN, yellow, WHITE]
+    );
+
+    // The default placeholder is used by draws which don't choose their own.
+    let image = solid_image(BLUE);
+    let mut defaults = Frames::new("uploads_default_placeholder", 1);
+    let uploads = defaults.renderer.uploads();
+    uploads.set_bytes_per_frame(Some(IMAGE_BYTES));
+    uploads.set_placeholder(Placeholder::Skip);
+    // Spend the budget of the first frame on another image.
+    uploads.queue(&solid_image(RED), UploadPriority::High);
+    assert_eq!(defaults.render(&scene(&[&image])), [BLACK]);
+    assert_eq!(defaults.render(&scene(&[&image])), [BLUE]);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn external_texture_updates_each_frame() {
+    let mut frames = Frames::new("uploads_external", 2);
+    let device = &frames.context.devices[frames.device_id].device;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("external"),
+        size: wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let external = frames.renderer.register_external_texture(&texture).unwrap();
+    let red = solid_image(RED);
+    let fill = |target: &Frames, rgba: [u8; 4]| {
+        let queue = &target.context.devices[target.device_id].queue;
+        queue.write_texture(
+            texture.as_image_copy(),
+            &rgba.repeat((SIZE * SIZE) as usize),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(SIZE * 4),
+                rows_per_image: None,
+            },
+            texture.size(),
+        );
+    };
+    fill(&frames, GREEN);
+    assert_eq!(frames.render(&scene(&[&external, &red])), [GREEN, RED]);
+    // The texture is copied again while the rest of the atlas is kept.
+    fill(&frames, BLUE);
+    assert_eq!(frames.render(&scene(&[&external, &red])), [BLUE, RED]);
+}
@@ -0,0 +1,86 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of laying out glyphs in vertical lines with [`catalina::VerticalLayout`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use std::sync::Arc;
+
+use catalina::kurbo::Point;
+use catalina::peniko::{Blob, Font};
+use catalina::{VerticalLayout, VerticalOrientation};
+
+const ROBOTO: &[u8] = include_bytes!("../../examples/assets/roboto/Roboto-Regular.ttf");
+/// The glyphs of `A` and `i` in Roboto, whose advances are 1336 and 497 of 2048 units.
+const GLYPH_A: u32 = 37;
+const GLYPH_I: u32 = 77;
+/// A font size at which a font unit of Roboto is 0.01 pixels.
+const FONT_SIZE: f32 = 20.48;
+
+fn roboto() -> Font {
+    Font::new(Blob::new(Arc::new(ROBOTO)), 0)
+}
+
+/// Font units are scaled by a 16.16 fixed point factor, so metrics are only accurate to about
+/// 0.05%.
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-3 * expected.abs().max(1.0),
+        "{actual} != {expected}"
+    );
+}
+
+#[test]
+fn orientation_of_characters() {
+    assert_eq!(VerticalOrientation::of('漢'), VerticalOrientation::Upright);
+    assert_eq!(VerticalOrientation::of('か'), VerticalOrientation::Upright);
+    assert_eq!(VerticalOrientation::of('A'), VerticalOrientation::Rotated);
+    assert_eq!(
+        VerticalOrientation::of('。'),
+        VerticalOrientation::TransformedOrUpright
+    );
+    assert_eq!(
+        VerticalOrientation::of('（'),
+        VerticalOrientation::TransformedOrRotated
+    );
+    assert_eq!(
+        VerticalOrientation::of('ー'),
+        VerticalOrientation::TransformedOrRotated
+    );
+}
+
+#[test]
+fn upright_glyphs_without_vertical_metrics() {
+    let font = roboto();
+    let layout = VerticalLayout::new(&font, FONT_SIZE).unwrap();
+    let mut pen = Point::new(100.0, 0.0);
+    let glyphs = layout.upright([GLYPH_A, GLYPH_I], &mut pen);
+    // Without vertical metrics, each glyph advances by one em.
+    assert_close(pen.y, 2.0 * f64::from(FONT_SIZE));
+    // Glyphs are centered on the line.
+    assert_close(glyphs[0].x.into(), 100.0 - 13.36 / 2.0);
+    assert_close(glyphs[1].x.into(), 100.0 - 4.97 / 2.0);
+    assert_close(f64::from(glyphs[1].y - glyphs[0].y), f64::from(FONT_SIZE));
+}
+
+#[test]
+fn rotated_glyphs_advance_horizontally() {
+    let font = roboto();
+    let layout = VerticalLayout::new(&font, FONT_SIZE).unwrap();
+    let mut pen = Point::new(100.0, 10.0);
+    let glyphs = layout.rotated([GLYPH_A, GLYPH_I], &mut pen);
+    assert_close(pen.y, 10.0 + 13.36 + 4.97);
+    assert_close(glyphs[1].y.into(), 10.0 + 13.36);
+    assert_eq!(glyphs[0].x, glyphs[1].x);
+    // Brackets are only upright with a vertical alternate, which Roboto doesn't have.
+    assert!(!layout.has_alternate(GLYPH_A));
+    assert!(!layout.is_upright('（', GLYPH_A));
+    assert!(layout.is_upright('。', GLYPH_A));
+}
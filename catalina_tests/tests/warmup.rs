@@ -0,0 +1,47 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of warming up the renderer ahead of the first frame.
+
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, Circle};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{AaConfig, RenderParams, Scene};
+use catalina_tests::{create_renderer, TestParams};
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn warmup_fills_the_buffer_pool() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer = create_renderer(&handle.device, &TestParams::new("warmup", 64, 64)).unwrap();
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::RED,
+        None,
+        &Circle::new((32.0, 32.0), 24.0),
+    );
+    let params = RenderParams {
+        base_color: palette::css::BLACK,
+        width: 64,
+        height: 64,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    };
+    assert_eq!(renderer.engine().pooled_buffers(), 0);
+    renderer
+        .warmup(&handle.device, &handle.queue, &scene, &params)
+        .unwrap();
+    assert_ne!(renderer.engine().pooled_buffers(), 0);
+}
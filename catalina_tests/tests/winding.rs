@@ -0,0 +1,131 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of exporting winding numbers with [`Renderer::render_winding_to_texture`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, BezPath};
+use catalina::peniko::color::palette;
+use catalina::util::RenderContext;
+use catalina::wgpu::{self, Extent3d, TextureDescriptor, TextureFormat, TextureUsages};
+use catalina::{AaConfig, Error, RenderParams};
+use catalina_tests::{create_renderer, read_texture, TestParams};
+
+const WIDTH: u32 = 80;
+const HEIGHT: u32 = 32;
+
+/// Adds a rectangle to `path`, in the opposite direction if `reversed`.
+fn rect(path: &mut BezPath, x0: f64, x1: f64, reversed: bool) {
+    let (y0, y1) = (0.0, f64::from(HEIGHT));
+    let mut corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)];
+    if reversed {
+        corners.reverse();
+    }
+    path.move_to(corners[0]);
+    for corner in &corners[1..] {
+        path.line_to(*corner);
+    }
+    path.close_path();
+}
+
+fn target(device: &wgpu::Device, format: TextureFormat) -> wgpu::Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some("Winding target"),
+        size: Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+fn params() -> RenderParams {
+    RenderParams {
+        base_color: palette::css::WHITE,
+        width: WIDTH,
+        height: HEIGHT,
+        antialiasing_method: AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
+    }
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn winding_numbers_exported() {
+    // Two overlapping rectangles in the same direction, then one in the opposite direction,
+    // spanning several tiles so that some are solid.
+    let mut path = BezPath::new();
+    rect(&mut path, 0.0, 32.0, false);
+    rect(&mut path, 16.0, 48.0, false);
+    rect(&mut path, 48.0, 64.0, true);
+
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("winding", WIDTH, HEIGHT)).unwrap();
+    let texture = target(&handle.device, TextureFormat::R32Float);
+    renderer
+        .render_winding_to_texture(
+            &handle.device,
+            &handle.queue,
+            &path,
+            Affine::IDENTITY,
+            &texture,
+            &params(),
+        )
+        .unwrap();
+    let data = read_texture(&handle.device, &handle.queue, &texture, 4).unwrap();
+    let windings: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes(bytes.try_into().unwrap()))
+        .collect();
+    let at = |x: u32| windings[(16 * WIDTH + x) as usize];
+    let direction = at(8);
+    assert_eq!(direction.abs(), 1.0);
+    assert_eq!(at(24), 2.0 * direction);
+    assert_eq!(at(40), direction);
+    assert_eq!(at(56), -direction);
+    assert_eq!(at(72), 0.0);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn winding_rejects_other_formats() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let mut renderer =
+        create_renderer(&handle.device, &TestParams::new("winding", WIDTH, HEIGHT)).unwrap();
+    let texture = target(&handle.device, TextureFormat::Rgba8Unorm);
+    let mut path = BezPath::new();
+    rect(&mut path, 0.0, 32.0, false);
+    let result = renderer.render_winding_to_texture(
+        &handle.device,
+        &handle.queue,
+        &path,
+        Affine::IDENTITY,
+        &texture,
+        &params(),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedWindingFormat(TextureFormat::Rgba8Unorm))
+    ));
+}
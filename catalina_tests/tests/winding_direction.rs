@@ -0,0 +1,91 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tests of correcting the winding direction of subpaths, with [`normalize_winding`].
+
+// The following lints are part of the Linebender standard set,
+// but resolving them has been deferred for now.
+// Feel free to send a PR that solves one or more of these.
+#![allow(
+    clippy::missing_assert_message,
+    clippy::allow_attributes_without_reason
+)]
+
+use catalina::kurbo::{Affine, BezPath, Rect, Shape};
+use catalina::peniko::{color::palette, Fill};
+use catalina::util::RenderContext;
+use catalina::{
+    misoriented_subpaths, normalize_winding, winding_directions, Scene, WindingDirection,
+};
+use catalina_tests::{create_renderer, render_with, TestParams};
+
+/// A square with a hole, and an island in the hole, all drawn clockwise.
+fn nested_squares() -> BezPath {
+    let mut path = BezPath::new();
+    for inset in [0.0, 8.0, 16.0] {
+        path.extend(Rect::new(inset, inset, 48.0 - inset, 48.0 - inset).path_elements(0.1));
+    }
+    path
+}
+
+#[test]
+fn holes_are_reversed() {
+    use WindingDirection::{Clockwise, CounterClockwise};
+
+    let path = nested_squares();
+    assert_eq!(
+        winding_directions(&path),
+        [Some(Clockwise), Some(Clockwise), Some(Clockwise)]
+    );
+    assert_eq!(misoriented_subpaths(&path), [1, 2]);
+
+    let normalized = normalize_winding(&path);
+    assert_eq!(
+        winding_directions(&normalized),
+        [Some(Clockwise), Some(CounterClockwise), Some(Clockwise)]
+    );
+    assert!(misoriented_subpaths(&normalized).is_empty());
+    assert_eq!(normalized.area(), 48.0 * 48.0 - 32.0 * 32.0 + 16.0 * 16.0);
+    // Paths which are already consistent are unchanged.
+    assert_eq!(normalize_winding(&normalized), normalized);
+}
+
+#[test]
+#[cfg_attr(skip_gpu_tests, ignore)]
+fn normalized_non_zero_fills_match_even_odd() {
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None)).unwrap();
+    let handle = &context.devices[device_id];
+    let params = TestParams::new("normalized_winding", 48, 48);
+    let mut renderer = create_renderer(&handle.device, &params).unwrap();
+    let path = nested_squares();
+
+    let mut expected = Scene::new();
+    expected.fill(
+        Fill::EvenOdd,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &path,
+    );
+    let mut scene = Scene::new();
+    scene.set_normalize_winding(true);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        palette::css::WHITE,
+        None,
+        &path,
+    );
+    let [expected, image] = [expected, scene].map(|scene| {
+        render_with(
+            &handle.device,
+            &handle.queue,
+            &mut renderer,
+            &scene,
+            &params,
+        )
+        .unwrap()
+    });
+    assert_eq!(image.data.data(), expected.data.data());
+}
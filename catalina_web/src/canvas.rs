@@ -0,0 +1,685 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::f64::consts::TAU;
+use std::num::NonZeroUsize;
+
+use catalina::kurbo::{Affine, Arc, BezPath, Cap, Join, Point, Rect, Stroke, Vec2};
+use catalina::peniko::color::{self, palette, DynamicColor};
+use catalina::peniko::{BlendMode, Color, Compose, Fill, Mix};
+use catalina::util::{RenderContext, RenderSurface};
+use catalina::wgpu::{self, SurfaceTarget};
+use catalina::{AaConfig, AaSupport, RenderParams, Renderer, RendererOptions, Scene};
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlCanvasElement, OffscreenCanvas};
+
+/// The tolerance used when converting arcs to Bézier curves, in device pixels.
+const ARC_TOLERANCE: f64 = 0.1;
+
+/// The part of the drawing state which is saved and restored by `save` and `restore`.
+#[derive(Clone, Debug)]
+struct DrawState {
+    transform: Affine,
+    fill: Color,
+    stroke: Color,
+    stroke_style: Stroke,
+    global_alpha: f32,
+    blend: BlendMode,
+    /// The number of clip layers pushed since this state was saved.
+    clip_layers: usize,
+}
+
+impl Default for DrawState {
+    fn default() -> Self {
+        Self {
+            transform: Affine::IDENTITY,
+            fill: palette::css::BLACK,
+            stroke: palette::css::BLACK,
+            stroke_style: Stroke::new(1.0),
+            global_alpha: 1.0,
+            blend: BlendMode::default(),
+            clip_layers: 0,
+        }
+    }
+}
+
+/// A canvas rendered by Catalina, with an API modelled on `CanvasRenderingContext2D`.
+///
+/// Paths are built in device space, as with a 2D context, so changing the transform part way
+/// through building a path only affects the points added afterwards.
+#[wasm_bindgen]
+pub struct Canvas {
+    context: RenderContext,
+    surface: RenderSurface<'static>,
+    renderer: Renderer,
+    scene: Scene,
+    path: BezPath,
+    state: DrawState,
+    saved: Vec<DrawState>,
+}
+
+impl std::fmt::Debug for Canvas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Canvas")
+            .field("width", &self.surface.config.width)
+            .field("height", &self.surface.config.height)
+            .field("state", &self.state)
+            .field("saved", &self.saved.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[wasm_bindgen]
+impl Canvas {
+    /// Creates a canvas which renders into an HTML `<canvas>` element.
+    ///
+    /// The canvas is rendered at the element's current `width` and `height`.
+    #[allow(
+        clippy::use_self,
+        reason = "wasm-bindgen doesn't support `Self` in the return type of async functions"
+    )]
+    pub async fn create(canvas: HtmlCanvasElement) -> Result<Canvas, JsError> {
+        let (width, height) = (canvas.width(), canvas.height());
+        Self::new(SurfaceTarget::Canvas(canvas), width, height).await
+    }
+
+    /// Creates a canvas which renders into an `OffscreenCanvas`, such as one transferred to a
+    /// worker.
+    #[allow(
+        clippy::use_self,
+        reason = "wasm-bindgen doesn't support `Self` in the return type of async functions"
+    )]
+    #[wasm_bindgen(js_name = createOffscreen)]
+    pub async fn create_offscreen(canvas: OffscreenCanvas) -> Result<Canvas, JsError> {
+        let (width, height) = (canvas.width(), canvas.height());
+        Self::new(SurfaceTarget::OffscreenCanvas(canvas), width, height).await
+    }
+
+    /// The width of the canvas, in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.surface.config.width
+    }
+
+    /// The height of the canvas, in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.surface.config.height
+    }
+
+    /// Resizes the canvas, which also clears it and resets the drawing state.
+    ///
+    /// This should be called whenever the size of the underlying canvas element changes.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.context
+            .resize_surface(&mut self.surface, width.max(1), height.max(1));
+        self.reset();
+    }
+
+    /// Clears the canvas and resets the drawing state to its defaults.
+    pub fn reset(&mut self) {
+        self.scene.reset();
+        self.path = BezPath::new();
+        self.state = DrawState::default();
+        self.saved.clear();
+    }
+
+    /// Renders everything drawn so far to the canvas.
+    pub fn present(&mut self) -> Result<(), JsError> {
+        let handle = &self.context.devices[self.surface.dev_id];
+        let texture = self.surface.surface.get_current_texture()?;
+        self.renderer.render_to_surface(
+            &handle.device,
+            &handle.queue,
+            &self.scene,
+            &texture,
+            &RenderParams {
+                base_color: palette::css::TRANSPARENT,
+                width: self.surface.config.width,
+                height: self.surface.config.height,
+                antialiasing_method: AaConfig::Area,
+                global_alpha: 1.0,
+                dash_phase: 0.0,
+                glow_amount: 1.0,
+                dirty_rects: None,
+            },
+            true,
+        )?;
+        texture.present();
+        Ok(())
+    }
+
+    // Styles
+
+    /// Sets the color used by `fill` and `fillRect`, as a CSS color string.
+    ///
+    /// Invalid colors are ignored. Gradients and patterns are not supported.
+    #[wasm_bindgen(setter = fillStyle)]
+    pub fn set_fill_style(&mut self, style: &str) {
+        if let Some(color) = parse_color(style) {
+            self.state.fill = color;
+        }
+    }
+
+    /// Sets the color used by `stroke` and `strokeRect`, as a CSS color string.
+    ///
+    /// Invalid colors are ignored. Gradients and patterns are not supported.
+    #[wasm_bindgen(setter = strokeStyle)]
+    pub fn set_stroke_style(&mut self, style: &str) {
+        if let Some(color) = parse_color(style) {
+            self.state.stroke = color;
+        }
+    }
+
+    /// The width of stroked lines.
+    #[wasm_bindgen(getter = lineWidth)]
+    pub fn line_width(&self) -> f64 {
+        self.state.stroke_style.width
+    }
+
+    /// Sets the width of stroked lines. Non-positive and non-finite values are ignored.
+    #[wasm_bindgen(setter = lineWidth)]
+    pub fn set_line_width(&mut self, width: f64) {
+        if width.is_finite() && width > 0.0 {
+            self.state.stroke_style.width = width;
+        }
+    }
+
+    /// Sets the shape of the ends of stroked lines: `"butt"`, `"round"` or `"square"`.
+    #[wasm_bindgen(setter = lineCap)]
+    pub fn set_line_cap(&mut self, cap: &str) {
+        let cap = match cap {
+            "butt" => Cap::Butt,
+            "round" => Cap::Round,
+            "square" => Cap::Square,
+            _ => return,
+        };
+        self.state.stroke_style.start_cap = cap;
+        self.state.stroke_style.end_cap = cap;
+    }
+
+    /// Sets the shape of the corners of stroked lines: `"miter"`, `"round"` or `"bevel"`.
+    #[wasm_bindgen(setter = lineJoin)]
+    pub fn set_line_join(&mut self, join: &str) {
+        self.state.stroke_style.join = match join {
+            "miter" => Join::Miter,
+            "round" => Join::Round,
+            "bevel" => Join::Bevel,
+            _ => return,
+        };
+    }
+
+    /// The miter limit of stroked lines.
+    #[wasm_bindgen(getter = miterLimit)]
+    pub fn miter_limit(&self) -> f64 {
+        self.state.stroke_style.miter_limit
+    }
+
+    /// Sets the miter limit of stroked lines. Non-positive and non-finite values are ignored.
+    #[wasm_bindgen(setter = miterLimit)]
+    pub fn set_miter_limit(&mut self, limit: f64) {
+        if limit.is_finite() && limit > 0.0 {
+            self.state.stroke_style.miter_limit = limit;
+        }
+    }
+
+    /// Sets the dash pattern of stroked lines, or a solid line if `segments` is empty.
+    ///
+    /// As with a 2D context, a list with an odd number of entries is repeated to make it even,
+    /// and a list containing negative or non-finite values is ignored.
+    #[wasm_bindgen(js_name = setLineDash)]
+    pub fn set_line_dash(&mut self, segments: Vec<f64>) {
+        if segments.iter().any(|s| !s.is_finite() || *s < 0.0) {
+            return;
+        }
+        let repeats = if segments.len() % 2 == 1 { 2 } else { 1 };
+        let pattern = segments
+            .iter()
+            .copied()
+            .cycle()
+            .take(segments.len() * repeats);
+        let style = &mut self.state.stroke_style;
+        *style = style.clone().with_dashes(style.dash_offset, pattern);
+    }
+
+    /// The offset into the dash pattern at which stroked lines start.
+    #[wasm_bindgen(getter = lineDashOffset)]
+    pub fn line_dash_offset(&self) -> f64 {
+        self.state.stroke_style.dash_offset
+    }
+
+    /// Sets the offset into the dash pattern at which stroked lines start.
+    #[wasm_bindgen(setter = lineDashOffset)]
+    pub fn set_line_dash_offset(&mut self, offset: f64) {
+        if offset.is_finite() {
+            self.state.stroke_style.dash_offset = offset;
+        }
+    }
+
+    /// The opacity applied to everything drawn.
+    #[wasm_bindgen(getter = globalAlpha)]
+    pub fn global_alpha(&self) -> f32 {
+        self.state.global_alpha
+    }
+
+    /// Sets the opacity applied to everything drawn. Values outside of `0..=1` are ignored.
+    #[wasm_bindgen(setter = globalAlpha)]
+    pub fn set_global_alpha(&mut self, alpha: f32) {
+        if (0.0..=1.0).contains(&alpha) {
+            self.state.global_alpha = alpha;
+        }
+    }
+
+    /// Sets how drawn content is combined with the existing content, using the names from the
+    /// Canvas 2D API such as `"source-over"`, `"multiply"` or `"destination-out"`.
+    ///
+    /// Unknown operations are ignored.
+    #[wasm_bindgen(setter = globalCompositeOperation)]
+    pub fn set_global_composite_operation(&mut self, operation: &str) {
+        if let Some(blend) = parse_composite_operation(operation) {
+            self.state.blend = blend;
+        }
+    }
+
+    // State
+
+    /// Pushes the current drawing state, including the transform, styles and clip, onto a
+    /// stack.
+    pub fn save(&mut self) {
+        self.saved.push(self.state.clone());
+        self.state.clip_layers = 0;
+    }
+
+    /// Pops the drawing state most recently pushed by `save`, removing any clips added since.
+    ///
+    /// Does nothing if there is no saved state.
+    pub fn restore(&mut self) {
+        let Some(state) = self.saved.pop() else {
+            return;
+        };
+        for _ in 0..self.state.clip_layers {
+            self.scene.pop_layer();
+        }
+        self.state = state;
+    }
+
+    // Transforms
+
+    /// Multiplies the current transform by a translation.
+    pub fn translate(&mut self, x: f64, y: f64) {
+        self.state.transform *= Affine::translate((x, y));
+    }
+
+    /// Multiplies the current transform by a clockwise rotation, in radians.
+    pub fn rotate(&mut self, angle: f64) {
+        self.state.transform *= Affine::rotate(angle);
+    }
+
+    /// Multiplies the current transform by a scale.
+    pub fn scale(&mut self, x: f64, y: f64) {
+        self.state.transform *= Affine::scale_non_uniform(x, y);
+    }
+
+    /// Multiplies the current transform by the given matrix.
+    pub fn transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.state.transform *= Affine::new([a, b, c, d, e, f]);
+    }
+
+    /// Replaces the current transform with the given matrix.
+    #[wasm_bindgen(js_name = setTransform)]
+    pub fn set_transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.state.transform = Affine::new([a, b, c, d, e, f]);
+    }
+
+    /// Replaces the current transform with the identity.
+    #[wasm_bindgen(js_name = resetTransform)]
+    pub fn reset_transform(&mut self) {
+        self.state.transform = Affine::IDENTITY;
+    }
+
+    // Paths
+
+    /// Starts a new, empty path.
+    #[wasm_bindgen(js_name = beginPath)]
+    pub fn begin_path(&mut self) {
+        self.path.truncate(0);
+    }
+
+    /// Closes the current subpath.
+    #[wasm_bindgen(js_name = closePath)]
+    pub fn close_path(&mut self) {
+        if !self.path.elements().is_empty() {
+            self.path.close_path();
+        }
+    }
+
+    /// Starts a new subpath at the given point.
+    #[wasm_bindgen(js_name = moveTo)]
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.path.move_to(self.point(x, y));
+    }
+
+    /// Adds a straight line to the given point.
+    #[wasm_bindgen(js_name = lineTo)]
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        self.ensure_subpath(x, y);
+        self.path.line_to(self.point(x, y));
+    }
+
+    /// Adds a quadratic Bézier curve to the given point.
+    #[wasm_bindgen(js_name = quadraticCurveTo)]
+    pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.ensure_subpath(cpx, cpy);
+        self.path.quad_to(self.point(cpx, cpy), self.point(x, y));
+    }
+
+    /// Adds a cubic Bézier curve to the given point.
+    #[wasm_bindgen(js_name = bezierCurveTo)]
+    pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.ensure_subpath(cp1x, cp1y);
+        self.path.curve_to(
+            self.point(cp1x, cp1y),
+            self.point(cp2x, cp2y),
+            self.point(x, y),
+        );
+    }
+
+    /// Adds a closed rectangular subpath.
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.move_to(x, y);
+        self.line_to(x + width, y);
+        self.line_to(x + width, y + height);
+        self.line_to(x, y + height);
+        self.path.close_path();
+        self.move_to(x, y);
+    }
+
+    /// Adds a circular arc, in radians, connected to the current subpath by a straight line.
+    pub fn arc(
+        &mut self,
+        x: f64,
+        y: f64,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        counterclockwise: Option<bool>,
+    ) {
+        self.ellipse(
+            x,
+            y,
+            radius,
+            radius,
+            0.0,
+            start_angle,
+            end_angle,
+            counterclockwise,
+        );
+    }
+
+    /// Adds an elliptical arc, in radians, connected to the current subpath by a straight line.
+    pub fn ellipse(
+        &mut self,
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start_angle: f64,
+        end_angle: f64,
+        counterclockwise: Option<bool>,
+    ) {
+        let sweep_angle = arc_sweep(start_angle, end_angle, counterclockwise.unwrap_or(false));
+        let arc = Arc {
+            center: Point::new(x, y),
+            radii: Vec2::new(radius_x, radius_y),
+            start_angle,
+            sweep_angle,
+            x_rotation: rotation,
+        };
+        let (sin, cos) = start_angle.sin_cos();
+        let start = Affine::translate((x, y))
+            * Affine::rotate(rotation)
+            * Point::new(radius_x * cos, radius_y * sin);
+        if self.path.elements().is_empty() {
+            self.path.move_to(self.point(start.x, start.y));
+        } else {
+            self.path.line_to(self.point(start.x, start.y));
+        }
+        let tolerance = ARC_TOLERANCE
+            / self.state.transform.as_coeffs()[0..4]
+                .iter()
+                .fold(0.0_f64, |max, c| max.max(c.abs()))
+                .max(f64::EPSILON);
+        for el in arc.append_iter(tolerance) {
+            self.path.push(self.state.transform * el);
+        }
+    }
+
+    // Drawing
+
+    /// Fills the current path with the fill style, using the `"nonzero"` (default) or
+    /// `"evenodd"` fill rule.
+    pub fn fill(&mut self, fill_rule: Option<String>) {
+        let fill = match fill_rule.as_deref() {
+            Some("evenodd") => Fill::EvenOdd,
+            _ => Fill::NonZero,
+        };
+        let color = self.state.fill.multiply_alpha(self.state.global_alpha);
+        let path = std::mem::take(&mut self.path);
+        self.draw(|scene| scene.fill(fill, Affine::IDENTITY, color, None, &path));
+        self.path = path;
+    }
+
+    /// Strokes the current path with the stroke style.
+    ///
+    /// The stroke is transformed by the current transform, as with a 2D context.
+    pub fn stroke(&mut self) {
+        let color = self.state.stroke.multiply_alpha(self.state.global_alpha);
+        let transform = self.state.transform;
+        let path = transform.inverse() * self.path.clone();
+        let style = self.state.stroke_style.clone();
+        self.draw(|scene| scene.stroke(&style, transform, color, None, &path));
+    }
+
+    /// Intersects the current clip with the current path, until the state is next restored.
+    ///
+    /// Only the `"nonzero"` fill rule is supported for clips.
+    pub fn clip(&mut self) {
+        self.scene
+            .push_layer(Mix::Clip, 1.0, Affine::IDENTITY, &self.path);
+        self.state.clip_layers += 1;
+    }
+
+    /// Fills a rectangle with the fill style, without affecting the current path.
+    #[wasm_bindgen(js_name = fillRect)]
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let color = self.state.fill.multiply_alpha(self.state.global_alpha);
+        let transform = self.state.transform;
+        let rect = Rect::new(x, y, x + width, y + height);
+        self.draw(|scene| scene.fill(Fill::NonZero, transform, color, None, &rect));
+    }
+
+    /// Strokes a rectangle with the stroke style, without affecting the current path.
+    #[wasm_bindgen(js_name = strokeRect)]
+    pub fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let color = self.state.stroke.multiply_alpha(self.state.global_alpha);
+        let transform = self.state.transform;
+        let rect = Rect::new(x, y, x + width, y + height);
+        let style = self.state.stroke_style.clone();
+        self.draw(|scene| scene.stroke(&style, transform, color, None, &rect));
+    }
+
+    /// Clears a rectangle to transparent black.
+    ///
+    /// Clearing the whole canvas with no transform or clip discards everything drawn so far,
+    /// so that per-frame drawing doesn't accumulate.
+    #[wasm_bindgen(js_name = clearRect)]
+    pub fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let rect = Rect::new(x, y, x + width, y + height);
+        let bounds = Rect::new(0.0, 0.0, self.width().into(), self.height().into());
+        let unclipped =
+            self.state.clip_layers == 0 && self.saved.iter().all(|state| state.clip_layers == 0);
+        if unclipped && self.state.transform == Affine::IDENTITY && rect.abs().contains_rect(bounds)
+        {
+            self.scene.reset();
+            return;
+        }
+        let clear = BlendMode::new(Mix::Normal, Compose::Clear);
+        self.scene
+            .push_layer(clear, 1.0, self.state.transform, &rect);
+        self.scene.pop_layer();
+    }
+}
+
+impl Canvas {
+    async fn new(target: SurfaceTarget<'static>, width: u32, height: u32) -> Result<Self, JsError> {
+        let mut context = RenderContext::new();
+        let surface = context
+            .create_surface(
+                target,
+                width.max(1),
+                height.max(1),
+                wgpu::PresentMode::AutoVsync,
+            )
+            .await?;
+        let renderer = context.devices[surface.dev_id].create_renderer(RendererOptions {
+            surface_format: Some(surface.format),
+            use_cpu: false,
+            antialiasing_support: AaSupport::area_only(),
+            num_init_threads: NonZeroUsize::new(1),
+            gpu_stages_from: None,
+            fine_band_rows: None,
+            frames_in_flight: None,
+            disk_cache: None,
+            small_scenes: None,
+        })?;
+        Ok(Self {
+            context,
+            surface,
+            renderer,
+            scene: Scene::new(),
+            path: BezPath::new(),
+            state: DrawState::default(),
+            saved: Vec::new(),
+        })
+    }
+
+    /// Maps a point from user space to device space.
+    fn point(&self, x: f64, y: f64) -> Point {
+        self.state.transform * Point::new(x, y)
+    }
+
+    /// Starts a subpath at the given point if the path is empty, as a 2D context does.
+    fn ensure_subpath(&mut self, x: f64, y: f64) {
+        if self.path.elements().is_empty() {
+            self.move_to(x, y);
+        }
+    }
+
+    /// Draws into the scene, isolating the content in a layer if the composite operation
+    /// isn't plain source-over.
+    fn draw(&mut self, draw: impl FnOnce(&mut Scene)) {
+        let blend = self.state.blend;
+        let layered = blend != BlendMode::default();
+        if layered {
+            let bounds = Rect::new(0.0, 0.0, self.width().into(), self.height().into());
+            self.scene.push_layer(blend, 1.0, Affine::IDENTITY, &bounds);
+        }
+        draw(&mut self.scene);
+        if layered {
+            self.scene.pop_layer();
+        }
+    }
+}
+
+fn parse_color(style: &str) -> Option<Color> {
+    color::parse_color(style.trim())
+        .ok()
+        .map(DynamicColor::to_alpha_color)
+}
+
+fn parse_composite_operation(operation: &str) -> Option<BlendMode> {
+    let mix = |mix| Some(BlendMode::new(mix, Compose::SrcOver));
+    let compose = |compose| Some(BlendMode::new(Mix::Normal, compose));
+    match operation {
+        "source-over" => compose(Compose::SrcOver),
+        "source-in" => compose(Compose::SrcIn),
+        "source-out" => compose(Compose::SrcOut),
+        "source-atop" => compose(Compose::SrcAtop),
+        "destination-over" => compose(Compose::DestOver),
+        "destination-in" => compose(Compose::DestIn),
+        "destination-out" => compose(Compose::DestOut),
+        "destination-atop" => compose(Compose::DestAtop),
+        "lighter" => compose(Compose::Plus),
+        "copy" => compose(Compose::Copy),
+        "xor" => compose(Compose::Xor),
+        "multiply" => mix(Mix::Multiply),
+        "screen" => mix(Mix::Screen),
+        "overlay" => mix(Mix::Overlay),
+        "darken" => mix(Mix::Darken),
+        "lighten" => mix(Mix::Lighten),
+        "color-dodge" => mix(Mix::ColorDodge),
+        "color-burn" => mix(Mix::ColorBurn),
+        "hard-light" => mix(Mix::HardLight),
+        "soft-light" => mix(Mix::SoftLight),
+        "difference" => mix(Mix::Difference),
+        "exclusion" => mix(Mix::Exclusion),
+        "hue" => mix(Mix::Hue),
+        "saturation" => mix(Mix::Saturation),
+        "color" => mix(Mix::Color),
+        "luminosity" => mix(Mix::Luminosity),
+        _ => None,
+    }
+}
+
+/// Computes the signed sweep of an arc, following the rules of the Canvas 2D `arc` method.
+fn arc_sweep(start_angle: f64, end_angle: f64, counterclockwise: bool) -> f64 {
+    if counterclockwise {
+        if start_angle - end_angle >= TAU {
+            -TAU
+        } else {
+            -(start_angle - end_angle).rem_euclid(TAU)
+        }
+    } else if end_angle - start_angle >= TAU {
+        TAU
+    } else {
+        (end_angle - start_angle).rem_euclid(TAU)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{PI, TAU};
+
+    use catalina::peniko::{color::palette, BlendMode, Compose, Mix};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{arc_sweep, parse_color, parse_composite_operation};
+
+    #[wasm_bindgen_test]
+    fn parses_css_colors() {
+        assert_eq!(parse_color(" red "), Some(palette::css::RED));
+        assert_eq!(parse_color("not a color"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn parses_composite_operations() {
+        assert_eq!(
+            parse_composite_operation("multiply"),
+            Some(BlendMode::new(Mix::Multiply, Compose::SrcOver))
+        );
+        assert_eq!(
+            parse_composite_operation("destination-out"),
+            Some(BlendMode::new(Mix::Normal, Compose::DestOut))
+        );
+        assert_eq!(parse_composite_operation("plus-lighter"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn arcs_sweep_like_a_2d_context() {
+        assert_eq!(arc_sweep(0.0, PI, false), PI);
+        assert_eq!(arc_sweep(0.0, PI, true), -PI);
+        assert_eq!(arc_sweep(0.0, 3.0 * TAU, false), TAU);
+        assert_eq!(arc_sweep(PI, 0.0, false), PI);
+    }
+}
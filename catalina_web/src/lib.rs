@@ -0,0 +1,44 @@
+// Copyright 2022-2025 the Catalina & Vello Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! WebAssembly bindings for Catalina, with an API modelled on `CanvasRenderingContext2D`.
+//!
+//! The [`Canvas`] type renders into an `HTMLCanvasElement` or `OffscreenCanvas` through
+//! WebGPU, and exposes the familiar path, style, transform and state methods of the Canvas 2D
+//! API under their JavaScript names:
+//!
+//! ```js
+//! import init, { Canvas } from "./catalina_web.js";
+//!
+//! await init();
+//! const ctx = await Canvas.create(document.querySelector("canvas"));
+//! ctx.fillStyle = "rebeccapurple";
+//! ctx.beginPath();
+//! ctx.arc(100, 100, 50, 0, 2 * Math.PI);
+//! ctx.fill();
+//! ctx.present();
+//! ```
+//!
+//! Unlike a 2D context, drawing commands are recorded into a scene which is only rendered
+//! when `present` is called.
+//! The recorded content is kept until it is cleared, either by `reset`, by resizing, or by a
+//! `clearRect` covering the whole canvas.
+//!
+//! This crate only has content when compiled for `wasm32`.
+
+// LINEBENDER LINT SET - lib.rs - v2
+// See https://linebender.org/wiki/canonical-lints/
+// These lints aren't included in Cargo.toml because they
+// shouldn't apply to examples and tests
+#![warn(unused_crate_dependencies)]
+#![warn(clippy::print_stdout, clippy::print_stderr)]
+// Targeting e.g. 32-bit means structs containing usize can give false positives for 64-bit.
+#![cfg_attr(target_pointer_width = "64", warn(clippy::trivially_copy_pass_by_ref))]
+// END LINEBENDER LINT SET
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+#[cfg(target_arch = "wasm32")]
+mod canvas;
+
+#[cfg(target_arch = "wasm32")]
+pub use canvas::Canvas;
@@ -94,16 +94,19 @@ async fn render(mut scenes: SceneSet, index: usize, args: &Args) -> Result<()> {
     let device_handle = &mut context.devices[device_id];
     let device = &device_handle.device;
     let queue = &device_handle.queue;
-    let mut renderer = catalina::Renderer::new(
-        device,
-        RendererOptions {
+    let mut renderer = device_handle
+        .create_renderer(RendererOptions {
             surface_format: None,
             use_cpu: args.use_cpu,
             num_init_threads: NonZeroUsize::new(1),
+            gpu_stages_from: None,
+            fine_band_rows: None,
+            frames_in_flight: None,
+            disk_cache: None,
+            small_scenes: None,
             antialiasing_support: catalina::AaSupport::area_only(),
-        },
-    )
-    .or_else(|_| bail!("Got non-Send/Sync error from creating renderer"))?;
+        })
+        .or_else(|_| bail!("Got non-Send/Sync error from creating renderer"))?;
     let mut fragment = Scene::new();
     let example_scene = &mut scenes.scenes[index];
     let mut text = SimpleText::new();
@@ -150,6 +153,10 @@ async fn render(mut scenes: SceneSet, index: usize, args: &Args) -> Result<()> {
         width,
         height,
         antialiasing_method: catalina::AaConfig::Area,
+        global_alpha: 1.0,
+        dash_phase: 0.0,
+        glow_amount: 1.0,
+        dirty_rects: None,
     };
     let mut scene = Scene::new();
     scene.append(&fragment, Some(transform));
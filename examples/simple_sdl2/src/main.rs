@@ -85,6 +85,10 @@ fn main() {
                     width,
                     height,
                     antialiasing_method: AaConfig::Msaa16,
+                    global_alpha: 1.0,
+                    dash_phase: 0.0,
+                    glow_amount: 1.0,
+                    dirty_rects: None,
                 },
                 true,
             )
@@ -106,16 +110,19 @@ fn main() {
 }
 
 fn create_vello_renderer(render_cx: &RenderContext, surface: &RenderSurface<'_>) -> Renderer {
-    Renderer::new(
-        &render_cx.devices[surface.dev_id].device,
-        RendererOptions {
+    render_cx.devices[surface.dev_id]
+        .create_renderer(RendererOptions {
             surface_format: Some(surface.format),
             use_cpu: false,
             antialiasing_support: catalina::AaSupport::all(),
             num_init_threads: NonZeroUsize::new(1),
-        },
-    )
-    .expect("Couldn't create renderer")
+            gpu_stages_from: None,
+            fine_band_rows: None,
+            frames_in_flight: None,
+            disk_cache: None,
+            small_scenes: None,
+        })
+        .expect("Couldn't create renderer")
 }
 
 fn add_shapes_to_scene(scene: &mut Scene) {
@@ -204,20 +204,23 @@ impl ApplicationHandler<UserEvent> for VelloApp<'_> {
             let id = render_state.surface.dev_id;
             self.renderers[id].get_or_insert_with(|| {
                 let start = Instant::now();
-                let renderer = Renderer::new(
-                    &self.context.devices[id].device,
-                    RendererOptions {
+                let renderer = self.context.devices[id]
+                    .create_renderer(RendererOptions {
                         surface_format: Some(render_state.surface.format),
                         use_cpu: self.use_cpu,
                         antialiasing_support: AA_CONFIGS.iter().copied().collect(),
                         num_init_threads: NonZeroUsize::new(self.num_init_threads),
-                    },
-                )
-                .map_err(|e| {
-                    // Pretty-print any renderer creation error using Display formatting before unwrapping.
-                    anyhow::format_err!("{e}")
-                })
-                .expect("Failed to create renderer");
+                        gpu_stages_from: None,
+                        fine_band_rows: None,
+                        frames_in_flight: None,
+                        disk_cache: None,
+                        small_scenes: None,
+                    })
+                    .map_err(|e| {
+                        // Pretty-print any renderer creation error using Display formatting before unwrapping.
+                        anyhow::format_err!("{e}")
+                    })
+                    .expect("Failed to create renderer");
                 log::info!("Creating renderer {id} took {:?}", start.elapsed());
                 #[cfg(feature = "wgpu-profiler")]
                 let mut renderer = renderer;
@@ -338,7 +341,7 @@ impl ApplicationHandler<UserEvent> for VelloApp<'_> {
                                         },
                                     );
                                 }
-                                debug_layer @ ("1" | "2" | "3" | "4") => {
+                                debug_layer @ ("1" | "2" | "3" | "4" | "5") => {
                                     match debug_layer {
                                         "1" => {
                                             self.debug.toggle(DebugLayers::BOUNDING_BOXES);
@@ -352,6 +355,9 @@ impl ApplicationHandler<UserEvent> for VelloApp<'_> {
                                         "4" => {
                                             self.debug.toggle(DebugLayers::VALIDATION);
                                         }
+                                        "5" => {
+                                            self.debug.toggle(DebugLayers::STROKE_EXPANSION);
+                                        }
                                         _ => unreachable!(),
                                     }
                                     if !self.debug.is_empty() && !self.async_pipeline {
@@ -495,6 +501,10 @@ impl ApplicationHandler<UserEvent> for VelloApp<'_> {
                     width,
                     height,
                     antialiasing_method,
+                    global_alpha: 1.0,
+                    dash_phase: 0.0,
+                    glow_amount: 1.0,
+                    dirty_rects: None,
                 };
                 self.scene.reset();
                 let mut transform = self.transform;
@@ -637,9 +647,8 @@ impl ApplicationHandler<UserEvent> for VelloApp<'_> {
                 let result = self.renderers[render_state.surface.dev_id]
                     .as_mut()
                     .unwrap()
-                    .reload_shaders(&device_handle.device);
-                // We know that the only async here (`pop_error_scope`) is actually sync, so blocking is fine
-                match pollster::block_on(result) {
+                    .reload_shaders(device_handle);
+                match result {
                     Ok(_) => log::info!("Reloading took {:?}", start.elapsed()),
                     Err(e) => log::error!("Failed to reload shaders: {e}"),
                 }
@@ -678,23 +687,26 @@ fn run(
         let mut renderers = vec![];
         renderers.resize_with(render_cx.devices.len(), || None);
         let id = render_state.surface.dev_id;
-        let renderer = Renderer::new(
-            &render_cx.devices[id].device,
-            RendererOptions {
+        let renderer = render_cx.devices[id]
+            .create_renderer(RendererOptions {
                 surface_format: Some(render_state.surface.format),
                 use_cpu: args.use_cpu,
                 antialiasing_support: AA_CONFIGS.iter().copied().collect(),
                 // We currently initialise on one thread on WASM, but mark this here
                 // anyway
                 num_init_threads: NonZeroUsize::new(1),
-            },
-        )
-        .map_err(|e| {
-            // Pretty-print any renderer creation error using Display formatting before unwrapping.
-            eprintln!("{e}");
-            e
-        })
-        .expect("Failed to create renderer");
+                gpu_stages_from: None,
+                fine_band_rows: None,
+                frames_in_flight: None,
+                disk_cache: None,
+                small_scenes: None,
+            })
+            .map_err(|e| {
+                // Pretty-print any renderer creation error using Display formatting before unwrapping.
+                eprintln!("{e}");
+                e
+            })
+            .expect("Failed to create renderer");
         #[cfg(feature = "wgpu-profiler")]
         let mut renderer = renderer;
         #[cfg(feature = "wgpu-profiler")]